@@ -5,6 +5,8 @@
 //   Mode 0: Clear spatial grid
 //   Mode 1: Build spatial grid (insert all entities into cells)
 //   Mode 2: Movement (NPCs) + combat targeting (NPCs + towers) via grid
+// Scripted (cutscene) frames dispatch a single pass instead:
+//   Mode 3: Integrate positions from scripted velocities (no grid/separation/combat)
 
 // PowerShell-style mental model:
 // - This shader is a parallel loop over entity indices (`i`).
@@ -34,6 +36,14 @@ struct Params {
     tile_grid_height: u32,
     tile_cell_size: f32,
     entity_count: u32,
+    // World bounds (max <= min = unbounded)
+    bounds_min_x: f32,
+    bounds_min_y: f32,
+    bounds_max_x: f32,
+    bounds_max_y: f32,
+    scripted_clamp: u32,  // mode 3: 1 = clamp to world bounds
+    separation_mode: u32, // 0 = 3x3 grid neighbors, 1 = brute-force scan (SeparationMode)
+    separation_scale: f32, // 0 = separation push off, ramps to 1 (SeparationState)
 }
 
 // Storage buffers matching Rust bind group layout
@@ -64,6 +74,9 @@ struct Params {
 
 // Tile flags: 1 u32 per world grid cell, bitfield for tile modifiers
 @group(0) @binding(18) var<storage, read> tile_flags: array<u32>;
+
+// Scripted velocities (px/s) — read only by mode 3, ignored by the normal passes
+@group(0) @binding(19) var<storage, read> scripted_velocities: array<vec2<f32>>;
 const TILE_ROAD: u32 = 32u;  // bit 5
 const TILE_WALL: u32 = 64u;  // bit 6 — blocks enemy faction NPCs
 const WALL_FACTION_SHIFT: u32 = 8u;  // bits 8-11 encode wall owner faction
@@ -72,6 +85,149 @@ const WALL_FACTION_MASK: u32 = 0xFu;
 // Entity type flags
 const ENTITY_BUILDING: u32 = 2u;      // bit 1: skip separation/NPC targeting
 const ENTITY_UNTARGETABLE: u32 = 4u;  // bit 2: cannot be selected as combat target
+const ENTITY_INACTIVE: u32 = 32u;     // bit 5: freed/hidden slot, exempt from bounds clamp
+const ENTITY_PASSIVE: u32 = 64u;      // bit 6: never acquires targets (stance), still targetable
+const ENTITY_ANCHORED: u32 = 128u;    // bit 7: resting in place — no movement, not pushed; movers steer around it
+const ENTITY_COMMITTED: u32 = 256u;   // bit 8: keep last frame's combat target while still valid
+// Target priority profile (bits 3-4): 0 = nearest, 1 = lowest HP, 2 = highest threat
+const PRIORITY_SHIFT: u32 = 3u;
+const PRIORITY_MASK: u32 = 3u;
+const PRIORITY_LOWEST_HP: u32 = 1u;
+const PRIORITY_HIGHEST_THREAT: u32 = 2u;
+const THREAT_SHIFT: u32 = 16u;        // bits 16-23: threat value this entity presents as a target
+const THREAT_MASK: u32 = 0xFFu;
+
+// Anchored-neighbor avoidance (one-way: movers steer around anchored units, never the reverse)
+const ANCHOR_AVOID_RADIUS_MULT: f32 = 1.5;  // reach, in separation radii
+const ANCHOR_AVOID_WEIGHT: f32 = 3.0;       // vs. 1.0 for ordinary separation
+const ANCHOR_AVOID_CAP: f32 = 0.75;         // max push, in fractions of speed (< 1 so a mover always squeezes through)
+
+// One separation/dodge neighbor `j` of mover `i`, shared by the grid and brute-force scans.
+// Accumulates into the caller's avoidance, dodge and anchor push; may settle `i` transitively.
+fn separation_neighbor(
+    i: u32,
+    j: i32,
+    pos: vec2<f32>,
+    goal: vec2<f32>,
+    my_faction: i32,
+    my_on_road: bool,
+    is_moving: bool,
+    my_dir: vec2<f32>,
+    settled: ptr<function, i32>,
+    avoidance: ptr<function, vec2<f32>>,
+    dodge: ptr<function, vec2<f32>>,
+    anchor_push: ptr<function, vec2<f32>>,
+) {
+    let sep_radius_sq = params.separation_radius * params.separation_radius;
+    let approach_radius = params.separation_radius * 2.0;
+    let approach_radius_sq = approach_radius * approach_radius;
+    let anchor_radius = params.separation_radius * ANCHOR_AVOID_RADIUS_MULT;
+    let anchor_radius_sq = anchor_radius * anchor_radius;
+
+    let other_pos = positions[j];
+    let other_flags = entity_flags[j];
+    if ((other_flags & ENTITY_BUILDING) != 0u) { return; }  // skip buildings in separation
+    if ((other_flags & ENTITY_ANCHORED) != 0u) {
+        // Anchored neighbor: wider, heavier push on movers only, accumulated apart
+        // from ordinary separation so it can be capped on its own below
+        if (is_moving) {
+            let adiff = pos - other_pos;
+            let ad_sq = dot(adiff, adiff);
+            if (ad_sq < anchor_radius_sq && ad_sq > 0.0001) {
+                let ad = sqrt(ad_sq);
+                *anchor_push += adiff * ((anchor_radius - ad) / ad);
+            }
+        }
+        return;
+    }
+    var diff = pos - other_pos;
+    let dist_sq = dot(diff, diff);
+    let neighbor_settled = arrivals[j];
+
+    // --- Separation force ---
+    if (dist_sq < sep_radius_sq) {
+        // Both on road → skip separation (smooth traffic flow)
+        if (my_on_road) {
+            let j_tcol = u32(other_pos.x / params.tile_cell_size);
+            let j_trow = u32(other_pos.y / params.tile_cell_size);
+            if (j_tcol < params.tile_grid_width && j_trow < params.tile_grid_height) {
+                let j_tidx = j_trow * params.tile_grid_width + j_tcol;
+                if ((tile_flags[j_tidx] & TILE_ROAD) != 0u) { return; }
+            }
+        }
+
+        // Both settled at same goal → skip separation (prevents patrol oscillation)
+        if (*settled == 1 && neighbor_settled == 1) {
+            let goal_j = goals[j];
+            if (goal.x == goal_j.x && goal.y == goal_j.y) { return; }
+        }
+
+        // Transitive arrival: bump a settled neighbor at same goal → settle too
+        if (*settled == 0 && neighbor_settled == 1) {
+            let goal_j = goals[j];
+            if (goal.x == goal_j.x && goal.y == goal_j.y) {
+                *settled = 1;
+            }
+        }
+
+        // Priority rule: settled and moving actors push differently.
+        var push_strength = 1.0;
+        if (*settled == 1 && neighbor_settled == 1) {
+            push_strength = 0.15;  // Both settled, different goals: minimal push
+        } else if (*settled == 0 && neighbor_settled == 1) {
+            push_strength = 0.2;  // I'm moving, they're settled: barely block me
+        } else if (*settled == 1 && neighbor_settled == 0) {
+            push_strength = 2.0;  // I'm settled, they're moving: shove me aside
+        }
+
+        // Same-faction boost: spread out when heading to same area
+        if (factions[j] == my_faction) {
+            push_strength *= 1.5;
+        }
+
+        if (dist_sq < 0.0001) {
+            let angle = f32(i) * 2.399 + f32(j) * 0.7;
+            diff = vec2<f32>(cos(angle), sin(angle));
+            *avoidance += diff * params.separation_radius * push_strength;
+        } else {
+            let dist = sqrt(dist_sq);
+            let overlap = params.separation_radius - dist;
+            *avoidance += diff * (overlap / dist) * push_strength;
+        }
+    }
+
+    // --- Dodge: steer sideways around approaching movers ---
+    if (is_moving && neighbor_settled == 0 && dist_sq < approach_radius_sq && dist_sq > 0.0001) {
+        let dist2 = sqrt(dist_sq);
+        let to_other = -diff / dist2;
+        let i_approach = dot(my_dir, to_other);
+
+        if (i_approach > 0.3) {
+            let other_goal = goals[j];
+            let ot = other_goal - other_pos;
+            let ot_len = length(ot);
+
+            if (ot_len > 0.001) {
+                let other_dir = ot / ot_len;
+                let they_approach = -dot(other_dir, to_other);
+
+                let perp = vec2<f32>(-my_dir.y, my_dir.x);
+                var dodge_strength = 0.4;
+                if (they_approach > 0.3) {
+                    dodge_strength = 0.5;
+                } else if (they_approach < -0.3) {
+                    dodge_strength = 0.3;
+                }
+
+                if (i < u32(j)) {
+                    *dodge += perp * dodge_strength;
+                } else {
+                    *dodge -= perp * dodge_strength;
+                }
+            }
+        }
+    }
+}
 
 @compute @workgroup_size(64, 1, 1)
 fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
@@ -117,6 +273,30 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         return;
     }
 
+    // =========================================================================
+    // MODE 3: Scripted motion (cutscenes)
+    // =========================================================================
+    // Mode 3: pos += scripted velocity * delta. Goals, speed, separation and the grid are
+    // ignored; combat targets are cleared so CPU combat doesn't act on stale results.
+    if (params.mode == 3u) {
+        if (i >= params.entity_count) { return; }
+        combat_targets[i] = -1;
+        threat_counts[i] = 0u;
+        let flags = entity_flags[i];
+        var pos = positions[i];
+        if (pos.x < -9000.0 || (flags & (ENTITY_INACTIVE | ENTITY_BUILDING)) != 0u) { return; }
+        pos += scripted_velocities[i] * params.delta;
+        if (params.scripted_clamp != 0u && params.bounds_max_x > params.bounds_min_x) {
+            pos = clamp(
+                pos,
+                vec2<f32>(params.bounds_min_x, params.bounds_min_y),
+                vec2<f32>(params.bounds_max_x, params.bounds_max_y),
+            );
+        }
+        positions[i] = pos;
+        return;
+    }
+
     // =========================================================================
     // MODE 2: Movement (NPCs) + Combat Targeting (NPCs + towers)
     // =========================================================================
@@ -150,8 +330,8 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let gh = i32(params.grid_height);
     let mpc = i32(params.max_per_cell);
 
-    // --- Movement, separation, dodge (only for moving NPCs, speed > 0) ---
-    if (speed > 0.0) {
+    // --- Movement, separation, dodge (only for moving, non-anchored NPCs) ---
+    if (speed > 0.0 && (my_flags & ENTITY_ANCHORED) == 0u) {
     // Movable entity branch only (buildings usually have speed = 0).
     let goal = goals[i];
     var settled = arrivals[i];
@@ -183,9 +363,7 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     // Same-faction NPCs repel more strongly to prevent convoy clumping.
     var avoidance = vec2<f32>(0.0, 0.0);
     var dodge = vec2<f32>(0.0, 0.0);
-    let sep_radius_sq = params.separation_radius * params.separation_radius;
-    let approach_radius = params.separation_radius * 2.0;
-    let approach_radius_sq = approach_radius * approach_radius;
+    var anchor_push = vec2<f32>(0.0, 0.0);
 
     // Pre-compute goal direction for dodge (only if moving toward goal)
     let is_moving = wants_goal && dist_to_goal > params.arrival_threshold;
@@ -198,119 +376,43 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let cx = clamp(i32(pos.x / params.cell_size), 0, gw - 1);
     let cy = clamp(i32(pos.y / params.cell_size), 0, gh - 1);
 
-    // Broad phase: inspect 3x3 neighboring cells.
-    for (var dy: i32 = -1; dy <= 1; dy++) {
-        let ny = cy + dy;
-        if (ny < 0 || ny >= gh) { continue; }
-
-        for (var dx: i32 = -1; dx <= 1; dx++) {
-            let nx = cx + dx;
-            if (nx < 0 || nx >= gw) { continue; }
-
-            let cell_idx = ny * gw + nx;
-            let cell_count = min(atomicLoad(&grid_counts[cell_idx]), mpc);
-            let cell_base = cell_idx * mpc;
-
-            for (var n: i32 = 0; n < cell_count; n++) {
-                let j = grid_data[cell_base + n];
-                if (j == i32(i)) { continue; }
-                if (j < 0 || u32(j) >= params.entity_count) { continue; }
-
-                let other_pos = positions[j];
-                if ((entity_flags[j] & ENTITY_BUILDING) != 0u) { continue; }  // skip buildings in separation
-                var diff = pos - other_pos;
-                let dist_sq = dot(diff, diff);
-                let neighbor_settled = arrivals[j];
-
-                // --- Separation force ---
-                if (dist_sq < sep_radius_sq) {
-                    // Both on road → skip separation (smooth traffic flow)
-                    if (my_on_road) {
-                        let j_tcol = u32(other_pos.x / params.tile_cell_size);
-                        let j_trow = u32(other_pos.y / params.tile_cell_size);
-                        if (j_tcol < params.tile_grid_width && j_trow < params.tile_grid_height) {
-                            let j_tidx = j_trow * params.tile_grid_width + j_tcol;
-                            if ((tile_flags[j_tidx] & TILE_ROAD) != 0u) { continue; }
-                        }
-                    }
-
-                    // Both settled at same goal → skip separation (prevents patrol oscillation)
-                    if (settled == 1 && neighbor_settled == 1) {
-                        let goal_j = goals[j];
-                        if (goal.x == goal_j.x && goal.y == goal_j.y) { continue; }
-                    }
-
-                    // Transitive arrival: bump a settled neighbor at same goal → settle too
-                    if (settled == 0 && neighbor_settled == 1) {
-                        let goal_j = goals[j];
-                        if (goal.x == goal_j.x && goal.y == goal_j.y) {
-                            settled = 1;
-                        }
-                    }
-
-                    // Priority rule: settled and moving actors push differently.
-                    var push_strength = 1.0;
-                    if (settled == 1 && neighbor_settled == 1) {
-                        push_strength = 0.15;  // Both settled, different goals: minimal push
-                    } else if (settled == 0 && neighbor_settled == 1) {
-                        push_strength = 0.2;  // I'm moving, they're settled: barely block me
-                    } else if (settled == 1 && neighbor_settled == 0) {
-                        push_strength = 2.0;  // I'm settled, they're moving: shove me aside
-                    }
-
-                    // Same-faction boost: spread out when heading to same area
-                    if (factions[j] == my_faction) {
-                        push_strength *= 1.5;
-                    }
-
-                    if (dist_sq < 0.0001) {
-                        let angle = f32(i) * 2.399 + f32(j) * 0.7;
-                        diff = vec2<f32>(cos(angle), sin(angle));
-                        avoidance += diff * params.separation_radius * push_strength;
-                    } else {
-                        let dist = sqrt(dist_sq);
-                        let overlap = params.separation_radius - dist;
-                        avoidance += diff * (overlap / dist) * push_strength;
-                    }
-                }
-
-                // --- Dodge: steer sideways around approaching movers ---
-                if (is_moving && neighbor_settled == 0 && dist_sq < approach_radius_sq && dist_sq > 0.0001) {
-                    let dist2 = sqrt(dist_sq);
-                    let to_other = -diff / dist2;
-                    let i_approach = dot(my_dir, to_other);
-
-                    if (i_approach > 0.3) {
-                        let other_goal = goals[j];
-                        let ot = other_goal - other_pos;
-                        let ot_len = length(ot);
-
-                        if (ot_len > 0.001) {
-                            let other_dir = ot / ot_len;
-                            let they_approach = -dot(other_dir, to_other);
-
-                            let perp = vec2<f32>(-my_dir.y, my_dir.x);
-                            var dodge_strength = 0.4;
-                            if (they_approach > 0.3) {
-                                dodge_strength = 0.5;
-                            } else if (they_approach < -0.3) {
-                                dodge_strength = 0.3;
-                            }
-
-                            if (i < u32(j)) {
-                                dodge += perp * dodge_strength;
-                            } else {
-                                dodge -= perp * dodge_strength;
-                            }
-                        }
-                    }
+    // The scan always runs: dodge, anchored push and transitive arrival need it even with
+    // separation off (separation_scale 0, see SeparationState), which only zeroes the push.
+    if (params.separation_mode == 1u) {
+        // Brute-force reference (SeparationMode::BruteForce): every live entity, no grid, so
+        // max_per_cell overflow can't hide neighbors. Only selected at capped counts.
+        for (var j: i32 = 0; j < i32(params.entity_count); j++) {
+            if (j == i32(i) || positions[j].x < -9000.0) { continue; }
+            separation_neighbor(i, j, pos, goal, my_faction, my_on_road, is_moving, my_dir,
+                &settled, &avoidance, &dodge, &anchor_push);
+        }
+    } else {
+        // Broad phase: inspect 3x3 neighboring cells.
+        for (var dy: i32 = -1; dy <= 1; dy++) {
+            let ny = cy + dy;
+            if (ny < 0 || ny >= gh) { continue; }
+
+            for (var dx: i32 = -1; dx <= 1; dx++) {
+                let nx = cx + dx;
+                if (nx < 0 || nx >= gw) { continue; }
+
+                let cell_idx = ny * gw + nx;
+                let cell_count = min(atomicLoad(&grid_counts[cell_idx]), mpc);
+                let cell_base = cell_idx * mpc;
+
+                for (var n: i32 = 0; n < cell_count; n++) {
+                    let j = grid_data[cell_base + n];
+                    if (j == i32(i)) { continue; }
+                    if (j < 0 || u32(j) >= params.entity_count) { continue; }
+                    separation_neighbor(i, j, pos, goal, my_faction, my_on_road, is_moving, my_dir,
+                        &settled, &avoidance, &dodge, &anchor_push);
                 }
             }
         }
     }
 
     // Convert raw separation vectors into configured world-space strength.
-    avoidance *= params.separation_strength;
+    avoidance *= params.separation_strength * params.separation_scale;
 
     // Normalize dodge direction, scale to fraction of separation strength
     let dodge_len = length(dodge);
@@ -320,11 +422,21 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     avoidance += dodge;
 
     // Clamp total avoidance so it can't wildly overpower movement
-    let avoidance_mag = length(avoidance);
     let max_avoidance = speed * 1.5;
-    if (avoidance_mag > max_avoidance) {
-        avoidance = (avoidance / avoidance_mag) * max_avoidance;
+    if (length(avoidance) > max_avoidance) {
+        avoidance = (avoidance / length(avoidance)) * max_avoidance;
+    }
+
+    // Anchored clusters: steer around them, but cap the push below forward speed so a dense
+    // static group or a narrow gap between two can slow a mover, never pin it in place
+    anchor_push *= params.separation_strength * ANCHOR_AVOID_WEIGHT;
+    let anchor_mag = length(anchor_push);
+    let max_anchor = speed * ANCHOR_AVOID_CAP;
+    if (anchor_mag > max_anchor) {
+        anchor_push = (anchor_push / anchor_mag) * max_anchor;
     }
+    avoidance += anchor_push;
+    let avoidance_mag = length(avoidance);
 
     // --- Projectile dodge: strafe away from incoming arrows (spatial grid) ---
     // Projectile dodge is optional and only active when unlocked.
@@ -485,6 +597,15 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         }
     }
 
+    // World border: clamp active entities inside the map
+    if (params.bounds_max_x > params.bounds_min_x && (my_flags & ENTITY_INACTIVE) == 0u) {
+        pos = clamp(
+            pos,
+            vec2<f32>(params.bounds_min_x, params.bounds_min_y),
+            vec2<f32>(params.bounds_max_x, params.bounds_max_y),
+        );
+    }
+
     positions[i] = pos;
     arrivals[i] = settled;
     backoff[i] = my_backoff;
@@ -512,6 +633,9 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let my_cy = i32(pos.y / params.cell_size);
 
     // Running best target and local crowd metrics.
+    // Candidates compare by (profile key, distance): Nearest keeps key at 0 so distance decides.
+    let my_priority = (my_flags >> PRIORITY_SHIFT) & PRIORITY_MASK;
+    var best_key = 3.4e38;
     var best_dist_sq = range_sq;
     var best_target: i32 = -1;
     var threat_enemies = 0u;
@@ -553,16 +677,41 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
                     }
                 }
 
-                // Combat targeting: nearest enemy within combat_range
-                if (!same_faction && dist_sq3 < best_dist_sq) {
-                    best_dist_sq = dist_sq3;
-                    best_target = other3;
+                // Combat targeting: best enemy within combat_range by priority profile
+                if (!same_faction && dist_sq3 < range_sq) {
+                    var key = 0.0;
+                    if (my_priority == PRIORITY_LOWEST_HP) {
+                        key = other_hp;
+                    } else if (my_priority == PRIORITY_HIGHEST_THREAT) {
+                        key = -f32((entity_flags[other3] >> THREAT_SHIFT) & THREAT_MASK);
+                    }
+                    if (key < best_key || (key == best_key && dist_sq3 < best_dist_sq)) {
+                        best_key = key;
+                        best_dist_sq = dist_sq3;
+                        best_target = other3;
+                    }
                 }
             }
         }
     }
 
+    // Target commitment: a committed unit keeps last frame's target over the scan's pick
+    // while that target is alive, hostile, targetable and in range (stops melee flicker).
+    let prev_target = combat_targets[i];
+    if ((my_flags & ENTITY_COMMITTED) != 0u && prev_target >= 0 && u32(prev_target) < params.entity_count) {
+        let pf = factions[prev_target];
+        let prev_diff = pos - positions[prev_target];
+        if (healths[prev_target] > 0.0
+            && (entity_flags[prev_target] & ENTITY_UNTARGETABLE) == 0u
+            && pf != my_faction && pf != -1 && pf != 0
+            && dot(prev_diff, prev_diff) < range_sq) {
+            best_target = prev_target;
+        }
+    }
+
     // Final writes consumed by CPU and later render/AI stages.
-    combat_targets[i] = select(-1, best_target, needs_combat);
+    // Passive (HoldFire / unprovoked ReturnFire) still scans for threat counts but never targets.
+    let passive = (my_flags & ENTITY_PASSIVE) != 0u;
+    combat_targets[i] = select(-1, best_target, needs_combat && !passive);
     threat_counts[i] = (threat_enemies << 16u) | (threat_allies & 0xFFFFu);
 }
@@ -20,6 +20,7 @@ struct VertexInput {
     @location(7) scale: f32,             // world-space quad size (32=NPC, 64=building)
     @location(8) atlas_id: f32,          // 0=character, 1=world, 2=heal halo, 3=sleep icon, 4=arrow
     @location(9) rotation: f32,          // radians, 0=no rotation (used for projectile orientation)
+    @location(10) stretch: vec2<f32>,    // per-axis multiplier on scale (projectile width/length)
 };
 
 struct NpcVertexInput {
@@ -71,19 +72,32 @@ struct Camera {
     zoom: f32,
     entity_count: u32,
     viewport: vec2<f32>,
-    bldg_layers: f32,
-    extras_cols: f32,
     lod_zoom: f32,
-    _pad: u32,
+    // Physical pixels per viewport unit to snap sprite centers to; 0 = off (see snap_to_pixel)
+    pixel_snap: f32,
+    // Atlas grids in texels: (cell_w, cell_h, sprite_w, sprite_h)
+    char_atlas: vec4<f32>,
+    world_atlas: vec4<f32>,
+    extras_atlas: vec4<f32>,
+    building_atlas: vec4<f32>,
+    // Sheet sizes in texels: (char_w, char_h, world_w, world_h)
+    atlas_sizes: vec4<f32>,
+    // Sheet sizes in texels: (extras_w, extras_h, building_w, building_h)
+    extra_atlas_sizes: vec4<f32>,
 };
 @group(1) @binding(0) var<uniform> camera: Camera;
 
 // NPC storage buffers (bind group 2, used by vertex_npc only)
 struct NpcVisual {
     sprite_col: f32, sprite_row: f32, atlas_id: f32, flash: f32,
-    r: f32, g: f32, b: f32, a: f32,
+    r: f32, g: f32, b: f32, highlight: f32,
 };
 
+// Body highlight (selection/hover/scripted): tint toward the selection-bracket cyan.
+// Layered over the faction tint; the fragment's damage flash still wins over it.
+const HIGHLIGHT_COLOR: vec3<f32> = vec3<f32>(0.39, 0.78, 1.0);
+const HIGHLIGHT_MIX: f32 = 0.6;
+
 struct EquipSlot {
     col: f32, row: f32, atlas: f32, _pad: f32,
 };
@@ -93,23 +107,15 @@ struct EquipSlot {
 @group(2) @binding(2) var<storage, read> npc_visual_buf: array<NpcVisual>;
 @group(2) @binding(3) var<storage, read> npc_equip: array<EquipSlot>;
 
-// Character atlas layout (roguelikeChar_transparent.png: 918x203)
-const CHAR_CELL: f32 = 17.0;
-const CHAR_SPRITE: f32 = 16.0;
-const CHAR_TEX_W: f32 = 918.0;
-const CHAR_TEX_H: f32 = 203.0;
-
-// World atlas layout (roguelikeSheet_transparent.png: 968x526)
-const WORLD_CELL: f32 = 17.0;
-const WORLD_SPRITE: f32 = 16.0;
-const WORLD_TEX_W: f32 = 968.0;
-const WORLD_TEX_H: f32 = 526.0;
+// Atlas layouts come from camera.*_atlas / atlas_sizes / extra_atlas_sizes (AtlasLayout in
+// gpu.rs; defaults: 16px sprites, 1px margin, 918x203 and 968x526 character/world sheets;
+// 64px cells, no margin for the extras strip and the building strip)
 
 // Degenerate triangle — moves vertex off-screen to discard
 const HIDDEN: vec4<f32> = vec4<f32>(0.0, 0.0, -2.0, 1.0);
 
 
-// Building atlas layer count comes from camera.bldg_layers (= BUILDING_REGISTRY.len())
+// Building atlas: one layer per row of camera.building_atlas (BUILDING_REGISTRY + autotile layers)
 
 // Atlas ID 7 = building (source of truth: constants.rs)
 fn is_building_atlas(id: f32) -> bool {
@@ -125,9 +131,11 @@ fn calc_uv(sprite_col: f32, sprite_row: f32, atlas_id: f32, quad_uv: vec2<f32>)
     if is_building_atlas(atlas_id) {
         // Building atlas: vertical strip, sprite_col selects tile layer
         // Inset by half a pixel to avoid sampling at layer boundaries
-        let inset = 0.5 / (camera.bldg_layers * 64.0);
-        let v = (sprite_col + clamp(quad_uv.y, inset, 1.0 - inset)) / camera.bldg_layers;
-        return vec2<f32>(quad_uv.x, v);
+        let g = camera.building_atlas;
+        let size = camera.extra_atlas_sizes.zw;
+        let inset = 0.5 / size.y;
+        let v = sprite_col * g.y + clamp(quad_uv.y, inset, 1.0 - inset) * g.w;
+        return vec2<f32>(quad_uv.x * g.z / size.x, v / size.y);
     } else if atlas_id >= 1.5 {
         // Extras atlas: horizontal grid of 64x64 cells, atlas_id maps to column
         var col: f32 = 0.0;
@@ -135,19 +143,22 @@ fn calc_uv(sprite_col: f32, sprite_row: f32, atlas_id: f32, quad_uv: vec2<f32>)
         else if atlas_id >= 3.5 { col = 2.0; }  // arrow (atlas 4)
         else if atlas_id >= 2.5 { col = 1.0; }  // sleep (atlas 3)
         // else col = 0.0 → heal (atlas 2)
-        let px = (col + quad_uv.x) / camera.extras_cols;
-        let py = quad_uv.y;
-        return vec2<f32>(px, py);
+        let g = camera.extras_atlas;
+        let px = col * g.x + quad_uv.x * g.z;
+        let py = quad_uv.y * g.w;
+        return vec2<f32>(px / camera.extra_atlas_sizes.x, py / camera.extra_atlas_sizes.y);
     } else if atlas_id < 0.5 {
         // Character atlas
-        let px = sprite_col * CHAR_CELL + quad_uv.x * CHAR_SPRITE;
-        let py = sprite_row * CHAR_CELL + quad_uv.y * CHAR_SPRITE;
-        return vec2<f32>(px / CHAR_TEX_W, py / CHAR_TEX_H);
+        let g = camera.char_atlas;
+        let px = sprite_col * g.x + quad_uv.x * g.z;
+        let py = sprite_row * g.y + quad_uv.y * g.w;
+        return vec2<f32>(px / camera.atlas_sizes.x, py / camera.atlas_sizes.y);
     } else {
         // World atlas
-        let px = sprite_col * WORLD_CELL + quad_uv.x * WORLD_SPRITE;
-        let py = sprite_row * WORLD_CELL + quad_uv.y * WORLD_SPRITE;
-        return vec2<f32>(px / WORLD_TEX_W, py / WORLD_TEX_H);
+        let g = camera.world_atlas;
+        let px = sprite_col * g.x + quad_uv.x * g.z;
+        let py = sprite_row * g.y + quad_uv.y * g.w;
+        return vec2<f32>(px / camera.atlas_sizes.z, py / camera.atlas_sizes.w);
     }
 }
 
@@ -158,6 +169,16 @@ fn world_to_clip(world_pos: vec2<f32>) -> vec4<f32> {
     return vec4<f32>(ndc.x, ndc.y, 0.0, 1.0);
 }
 
+// Round a sprite's center to the physical pixel grid so it doesn't shimmer at fractional zooms.
+// Only the center moves: the quad keeps its shape, and the CPU zeroes pixel_snap at high zoom
+// where sub-pixel motion should stay smooth.
+fn snap_to_pixel(world_pos: vec2<f32>) -> vec2<f32> {
+    if camera.pixel_snap <= 0.0 { return world_pos; }
+    let half = camera.viewport * 0.5;
+    let px = ((world_pos - camera.pos) * camera.zoom + half) * camera.pixel_snap;
+    return (round(px) / camera.pixel_snap - half) / camera.zoom + camera.pos;
+}
+
 // =============================================================================
 // VERTEX: Instance buffer path (farms, building HP bars, projectiles)
 // =============================================================================
@@ -166,14 +187,15 @@ fn world_to_clip(world_pos: vec2<f32>) -> vec4<f32> {
 fn vertex(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
 
-    // Rotate local quad point then offset by instance world position.
+    // Stretch and rotate local quad point then offset by instance world position.
     let c = cos(in.rotation);
     let s = sin(in.rotation);
+    let local = in.quad_pos * in.stretch;
     let rotated = vec2<f32>(
-        in.quad_pos.x * c - in.quad_pos.y * s,
-        in.quad_pos.x * s + in.quad_pos.y * c,
+        local.x * c - local.y * s,
+        local.x * s + local.y * c,
     );
-    let world_pos = in.instance_pos + rotated * in.scale;
+    let world_pos = snap_to_pixel(in.instance_pos) + rotated * in.scale;
 
     out.clip_position = world_to_clip(world_pos);
     out.uv = calc_uv(in.sprite_cell.x, in.sprite_cell.y, in.atlas_id, in.quad_uv);
@@ -208,6 +230,8 @@ fn vertex_npc(in: NpcVertexInput) -> VertexOutput {
     if camera.zoom < camera.lod_zoom && layer > 0u { out.clip_position = HIDDEN; return out; }
 
     let vis = npc_visual_buf[slot];
+    // Negative flash = spawn fade-in in progress (alpha = 1 + flash); see EntityGpuState::packed_flash
+    let fade = select(1.0, clamp(1.0 + vis.flash, 0.0, 1.0), vis.flash < 0.0);
     var sprite_col: f32; var sprite_row: f32;
     var atlas_id: f32; var flash: f32;
     var color: vec4<f32>; var scale: f32 = 32.0; var health: f32;
@@ -218,8 +242,12 @@ fn vertex_npc(in: NpcVertexInput) -> VertexOutput {
         sprite_col = vis.sprite_col;
         sprite_row = vis.sprite_row;
         atlas_id = vis.atlas_id;
-        flash = vis.flash;
-        color = vec4<f32>(vis.r, vis.g, vis.b, vis.a);
+        flash = max(vis.flash, 0.0);
+        color = vec4<f32>(vis.r, vis.g, vis.b, 1.0);
+        if vis.highlight > 0.0 {
+            let h = HIGHLIGHT_MIX * min(vis.highlight, 1.0);
+            color = vec4<f32>(mix(color.rgb, HIGHLIGHT_COLOR, h), 1.0);
+        }
         health = clamp(npc_healths[slot], 0.0, 1.0);
     } else {
         // Layers 1..7 are equipment/overlay sprites.
@@ -228,7 +256,7 @@ fn vertex_npc(in: NpcVertexInput) -> VertexOutput {
         sprite_col = eq.col;
         sprite_row = eq.row;
         atlas_id = eq.atlas;
-        flash = vis.flash;
+        flash = max(vis.flash, 0.0);
         health = 1.0; // equipment layers don't show HP bars
 
         // Color/scale by atlas type (matches CPU-side prepare_npc_buffers logic)
@@ -243,6 +271,7 @@ fn vertex_npc(in: NpcVertexInput) -> VertexOutput {
             color = vec4<f32>(vis.r, vis.g, vis.b, 1.0);      // equipment: job color
         }
     }
+    color.a = color.a * fade;
 
     // Compile-time pass gates allow one shader source for multiple draw passes.
 #ifdef MODE_BUILDING_BODY
@@ -255,16 +284,19 @@ fn vertex_npc(in: NpcVertexInput) -> VertexOutput {
     if layer == 0u || is_building_atlas(atlas_id) { out.clip_position = HIDDEN; return out; }
 #endif
 
-    // Building sprites use larger scale and bypass NPC HP bar logic.
+    // Building sprites cover their footprint (64px per cell) and bypass NPC HP bar logic.
+    // write_building_visual stores the footprint (w, h) in equip layer 0 of building slots.
+    var quad_size = vec2<f32>(scale, scale);
     if is_building_atlas(atlas_id) {
-        scale = 64.0;
+        let footprint = npc_equip[slot * 7u];
+        quad_size = 64.0 * vec2<f32>(max(footprint.col, 1.0), max(footprint.row, 1.0));
         health = 1.0; // suppress NPC HP bar; BuildingHpRender handles via atlas_id=5
     }
 
     // Float carried-item icon above NPC head (layer 4 = carried loot)
     var y_offset: f32 = 0.0;
     if layer == 4u || layer == 5u { y_offset = 30.0; }
-    out.clip_position = world_to_clip(pos + vec2<f32>(0.0, y_offset) + in.quad_pos * scale);
+    out.clip_position = world_to_clip(snap_to_pixel(pos) + vec2<f32>(0.0, y_offset) + in.quad_pos * quad_size);
     out.uv = calc_uv(sprite_col, sprite_row, atlas_id, in.quad_uv);
     out.color = color;
     out.health = health;
@@ -285,7 +317,7 @@ fn vertex_selection(in: SelectionInput) -> VertexOutput {
     var out: VertexOutput;
     let pos = npc_positions[in.slot];
     if pos.x < -9000.0 { out.clip_position = HIDDEN; return out; }
-    out.clip_position = world_to_clip(pos + vec2<f32>(0.0, in.y_offset) + in.quad_pos * in.scale);
+    out.clip_position = world_to_clip(snap_to_pixel(pos) + vec2<f32>(0.0, in.y_offset) + in.quad_pos * in.scale);
     out.uv = vec2<f32>(0.0, 0.0);
     out.color = in.color;
     out.health = 1.0;
@@ -316,7 +348,7 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
         let in_bl = (u < bracket_len && v > 1.0 - bracket_w) || (u < bracket_w && v > 1.0 - bracket_len);
         let in_br = (u > 1.0 - bracket_len && v > 1.0 - bracket_w) || (u > 1.0 - bracket_w && v > 1.0 - bracket_len);
         if !(in_tl || in_tr || in_bl || in_br) { discard; }
-        return vec4<f32>(in.color.rgb, in.color.a);
+        return vec4<f32>(in.color.rgb, 1.0);
     }
 
     // Far zoom LOD path: replace textured shading with flat-color silhouettes.
@@ -326,7 +358,7 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
         // HP bars, overlays, extras: invisible at this zoom
         if in.atlas_id >= 1.5 { discard; }
         // NPCs: solid faction-colored rectangle (no texture sampling)
-        return vec4<f32>(in.color.rgb, 1.0);
+        return vec4<f32>(in.color.rgb, in.color.a);
     }
 
     // Building sprite (atlas_id 7) — must come before bar branches to avoid discard
@@ -339,7 +371,7 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
         }
         // Building textured path.
         // Snap V to texel center to prevent cross-layer bleeding after interpolation
-        let tex_h = camera.bldg_layers * 64.0;
+        let tex_h = camera.extra_atlas_sizes.w;
         let snapped_v = (floor(in.uv.y * tex_h) + 0.5) / tex_h;
         let tex_color = textureSample(building_texture, building_sampler, vec2<f32>(in.uv.x, snapped_v));
         if tex_color.a < 0.1 { discard; }
@@ -388,10 +420,10 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
 
     // Extras atlas sprites: heal (2), sleep (3), arrow (4), boat (8)
     if in.atlas_id >= 1.5 {
-        // Generic extras-atlas sprite path.
+        // Generic extras-atlas sprite path (color alpha fades projectile trails).
         let tex_color = textureSample(extras_texture, extras_sampler, in.uv);
         if tex_color.a < 0.1 { discard; }
-        return vec4<f32>(tex_color.rgb * in.color.rgb, tex_color.a);
+        return vec4<f32>(tex_color.rgb * in.color.rgb, tex_color.a * in.color.a);
     }
 
     // Carried item sprite (atlas_id 1): original colors, no grayscale tint
@@ -399,7 +431,7 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
         // Carried world item path with original texture colors.
         let tex_color = textureSample(world_texture, world_sampler, in.uv);
         if tex_color.a < 0.1 { discard; }
-        return vec4<f32>(tex_color.rgb, tex_color.a);
+        return vec4<f32>(tex_color.rgb, tex_color.a * in.color.a);
     }
 
     // Health bar in bottom 15% of sprite (quad_uv.y > 0.85 = bottom rows)
@@ -438,7 +470,7 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     }
 
     let brightness = dot(tex_color.rgb, vec3<f32>(0.299, 0.587, 0.114));
-    var final_color = vec4<f32>(brightness * in.color.rgb, tex_color.a);
+    var final_color = vec4<f32>(brightness * in.color.rgb, tex_color.a * in.color.a);
 
     // Hit flash overlay for character sprites.
     if in.flash > 0.0 && in.atlas_id < 0.5 {
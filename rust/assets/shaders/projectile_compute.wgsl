@@ -64,6 +64,9 @@ struct ProjParams {
 // Per-projectile homing targets. -1 = no homing.
 @group(0) @binding(18) var<storage, read_write> proj_homing_targets: array<i32>;
 
+// Per-projectile ballistic arc: (height, vertical speed, gravity, unused). Gravity 0 = flat.
+@group(0) @binding(19) var<storage, read_write> proj_arcs: array<vec4<f32>>;
+
 @compute @workgroup_size(64, 1, 1)
 fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let i = global_id.x;
@@ -138,6 +141,17 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     // Hit already recorded in a previous frame; do not re-hit.
     if (proj_hits[i].x >= 0) { return; }
 
+    // Arced shots fly over everything and only test for a hit where they come down.
+    // Height steps exactly under constant gravity, so the landing frame matches the CPU solve.
+    var landed = false;
+    let arc = proj_arcs[i];
+    if (arc.z > 0.0) {
+        let height = arc.x + arc.y * params.delta - 0.5 * arc.z * params.delta * params.delta;
+        proj_arcs[i] = vec4<f32>(height, arc.y - arc.z * params.delta, arc.z, arc.w);
+        if (height > 0.0) { return; }
+        landed = true;
+    }
+
     // Oriented rectangle collision: arrow is long along velocity, thin perpendicular
     let my_faction = proj_factions[i];
     let speed_sq = dot(vel, vel);
@@ -147,7 +161,8 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     // - perpendicular axis for width checks
     var fwd: vec2<f32>;
     var perp: vec2<f32>;
-    var use_oriented: bool = speed_sq > 0.001;
+    // A landing shell strikes straight down: round hit test, not the arrow's long box.
+    var use_oriented: bool = speed_sq > 0.001 && !landed;
     if (use_oriented) {
         let inv_speed = 1.0 / sqrt(speed_sq);
         fwd = vel * inv_speed;
@@ -158,9 +173,9 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let cx = i32(pos.x / params.cell_size);
     let cy = i32(pos.y / params.cell_size);
 
-    // Bounds check
-    if (cx < 0 || cx >= i32(params.grid_width)) { return; }
-    if (cy < 0 || cy >= i32(params.grid_height)) { return; }
+    // Bounds check. A shell landing off the grid still lands (CPU frees the slot).
+    let off_grid = cx < 0 || cx >= i32(params.grid_width) || cy < 0 || cy >= i32(params.grid_height);
+    if (off_grid && !landed) { return; }
 
     // Broad phase: check current cell plus 8 neighboring cells.
     let gw = i32(params.grid_width);
@@ -227,4 +242,11 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
             }
         }
     }
+
+    if (landed) {
+        // Came down on empty ground — the CPU still applies any splash at the landing point
+        proj_hits[i] = vec2<i32>(-3, 0);
+        proj_active[i] = 0;
+        proj_positions[i] = vec2<f32>(-9999.0, -9999.0);
+    }
 }
@@ -18,11 +18,11 @@ use endless::messages::*;
 use endless::resources::*;
 use endless::systems::stats;
 use endless::systems::{
-    AiPlayerConfig, AiPlayerState, advance_waypoints_system, arrival_system, attack_system,
-    building_tower_system, construction_tick_system, cooldown_system, damage_system, death_system,
-    decision_system, energy_system, gpu_position_readback, growth_system, healing_system,
-    npc_regen_system, on_duty_tick_system, process_proj_hits, resolve_movement_system,
-    spawn_npc_system, spawner_respawn_system,
+    AiPlayerConfig, AiPlayerState, SpawnOverrideQueue, advance_waypoints_system, arrival_system,
+    attack_system, building_tower_system, construction_tick_system, cooldown_system, damage_system,
+    death_system, decision_system, energy_system, gpu_position_readback, growth_system,
+    healing_system, npc_regen_system, on_duty_tick_system, process_proj_hits,
+    resolve_movement_system, spawn_npc_system, spawner_respawn_system,
 };
 use endless::world;
 
@@ -43,6 +43,8 @@ fn build_bench_app() -> App {
         .add_message::<GpuUpdateMsg>()
         .add_message::<ProjGpuUpdateMsg>()
         .add_message::<CombatLogMsg>()
+        .add_message::<FarmEventMsg>()
+        .add_message::<endless::messages::AttackAnimMsg>()
         .add_message::<WorkIntentMsg>()
         .add_message::<BuildingGridDirtyMsg>()
         .add_message::<TerrainDirtyMsg>()
@@ -74,6 +76,8 @@ fn build_bench_app() -> App {
         .init_resource::<PathRequestQueue>()
         .init_resource::<PathfindConfig>()
         .init_resource::<PathfindStats>()
+        .init_resource::<PositionSync>()
+        .init_resource::<WorldBounds>()
         .init_resource::<KillStats>()
         .init_resource::<SelectedNpc>()
         .init_resource::<SelectedBuilding>()
@@ -90,6 +94,7 @@ fn build_bench_app() -> App {
         .init_resource::<FactionStats>()
         .init_resource::<FactionList>()
         .init_resource::<Reputation>()
+        .init_resource::<FactionMvp>()
         .init_resource::<RaiderState>()
         .init_resource::<BuildingHealState>()
         .init_resource::<ActiveHealingSlots>()
@@ -112,10 +117,33 @@ fn build_bench_app() -> App {
         .init_resource::<AiPlayerConfig>()
         .init_resource::<NpcDecisionConfig>()
         .init_resource::<stats::CombatConfig>()
+        .init_resource::<endless::systems::balance::BalanceConfig>()
+        .init_resource::<CombatRng>()
+        .init_resource::<endless::resources::ComputeBackend>()
+        .init_resource::<endless::resources::AttackAnimOutbox>()
+        .init_resource::<endless::systems::BodyguardTargets>()
+        .init_resource::<endless::resources::ProjectileArcConfig>()
+        .init_resource::<endless::systems::TownHappiness>()
+        .init_resource::<endless::systems::DeathKnockback>()
+        .init_resource::<AggroMemoryConfig>()
+        .init_resource::<TargetStickiness>()
+        .init_resource::<RespawnPolicy>()
+        .init_resource::<CombatZones>()
+        .init_resource::<LeadTargeting>()
+        .init_resource::<NpcVelocities>()
+        .init_resource::<NpcFacing>()
+        .init_resource::<SpawnOverrideQueue>()
+        .init_resource::<endless::world::StartingLoadouts>()
+        .init_resource::<TributeState>()
+        .init_resource::<BehaviorLod>()
+        .init_resource::<EnergyThresholds>()
+        .init_resource::<PanicState>()
+        .init_resource::<NoBuildZones>()
         .init_resource::<AutoUpgrade>()
         .init_resource::<MiningPolicy>()
         .init_resource::<GameAudio>()
         .init_resource::<NextLootItemId>()
+        .init_resource::<LootConfig>()
         .init_resource::<MerchantInventory>()
         .init_resource::<EntityGpuState>()
         .insert_resource(endless::settings::UserSettings::default());
@@ -172,11 +200,13 @@ fn populate_npcs(app: &mut App, count: usize) {
             kind: FactionKind::Neutral,
             name: "Neutral".into(),
             towns: vec![],
+            stance: None,
         });
         fl.factions.push(FactionData {
             kind: FactionKind::Player,
             name: "Player".into(),
             towns: vec![0],
+            stance: None,
         });
     }
 
@@ -315,6 +345,53 @@ fn bench_decision_system(c: &mut Criterion) {
     group.finish();
 }
 
+/// Same as decision/energy benches but with every NPC off-screen, so behavior LOD staggers
+/// them all (compare against the plain groups to see the LOD saving).
+fn far_camera_lod(app: &mut App) {
+    let mut lod = app.world_mut().resource_mut::<BehaviorLod>();
+    lod.enabled = true;
+    lod.camera_known = true;
+    lod.camera_center = Vec2::splat(-1.0e6);
+    lod.view_half = Vec2::splat(100.0);
+}
+
+fn bench_behavior_lod(c: &mut Criterion) {
+    let mut group = c.benchmark_group("behavior_lod");
+    group.sample_size(20);
+    for &count in COUNTS {
+        group.bench_with_input(
+            BenchmarkId::new("decision_system", count),
+            &count,
+            |b, &count| {
+                let mut app = build_bench_app();
+                spawn_bench_town(&mut app);
+                populate_npcs(&mut app, count);
+                far_camera_lod(&mut app);
+                let _ = app.world_mut().run_system_once(decision_system);
+                b.iter(|| {
+                    let _ = app.world_mut().run_system_once(decision_system);
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("energy_system", count),
+            &count,
+            |b, &count| {
+                let mut app = build_bench_app();
+                spawn_bench_town(&mut app);
+                populate_npcs(&mut app, count);
+                far_camera_lod(&mut app);
+                app.world_mut().resource_mut::<GameTime>().time_scale = 1.0;
+                let _ = app.world_mut().run_system_once(energy_system);
+                b.iter(|| {
+                    let _ = app.world_mut().run_system_once(energy_system);
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
 fn bench_damage_system(c: &mut Criterion) {
     let mut group = c.benchmark_group("damage_system");
     for &count in COUNTS {
@@ -433,6 +510,7 @@ fn bench_resolve_movement_system(c: &mut Criterion) {
                             current: 0,
                             goal_world: Vec2::ZERO,
                             path_cooldown: 0.0,
+                            retarget_cooldown: 0.0,
                             path_chunks: vec![],
                         });
                     }
@@ -496,6 +574,7 @@ fn bench_resolve_movement_unbounded(c: &mut Criterion) {
                             current: 0,
                             goal_world: Vec2::ZERO,
                             path_cooldown: 0.0,
+                            retarget_cooldown: 0.0,
                             path_chunks: vec![],
                         });
                     }
@@ -553,6 +632,7 @@ fn bench_building_tower_system(c: &mut Criterion) {
                         kind: FactionKind::AiRaider,
                         name: "Enemy".into(),
                         towns: vec![],
+                        stance: None,
                     });
                 }
                 // Spawn tower buildings with Building + Health components
@@ -1304,9 +1384,49 @@ fn bench_process_proj_hits(c: &mut Criterion) {
     group.finish();
 }
 
+/// Sync-poll encoding cost: per-NPC JSON objects vs the packed binary blob, 500 transforms.
+fn bench_transform_sync(c: &mut Criterion) {
+    use endless::systems::remote::{NpcTransform, base64_encode, encode_transforms_packed};
+    let mut group = c.benchmark_group("transform_sync");
+    let records: Vec<NpcTransform> = (0..500u32)
+        .map(|i| NpcTransform {
+            slot: i,
+            pos: Vec2::new((i % 50) as f32 * 32.0, (i / 50) as f32 * 32.0),
+            job: (i % 5) as u8,
+            faction: (i % 3) as i16,
+        })
+        .collect();
+    group.bench_function("json/500", |b| {
+        b.iter(|| {
+            let npcs: Vec<serde_json::Value> = records
+                .iter()
+                .map(|t| {
+                    serde_json::json!({
+                        "slot": t.slot,
+                        "x": t.pos.x,
+                        "y": t.pos.y,
+                        "job": t.job,
+                        "faction": t.faction,
+                    })
+                })
+                .collect();
+            serde_json::to_string(&npcs).unwrap()
+        });
+    });
+    group.bench_function("packed/500", |b| {
+        let mut bytes = Vec::new();
+        b.iter(|| {
+            encode_transforms_packed(&records, &mut bytes);
+            base64_encode(&bytes)
+        });
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_decision_system,
+    bench_behavior_lod,
     bench_damage_system,
     bench_healing_system,
     bench_attack_system,
@@ -1327,5 +1447,6 @@ criterion_group!(
     bench_on_duty_tick_system,
     bench_spawn_npc_system,
     bench_process_proj_hits,
+    bench_transform_sync,
 );
 criterion_main!(benches);
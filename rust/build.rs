@@ -34,9 +34,25 @@ fn main() {
         .unwrap_or_else(|| "unknown".to_string());
     let commit = commit.trim();
 
+    // Resolved Bevy version from Cargo.lock (engine version for bug reports)
+    let bevy_version = std::fs::read_to_string("Cargo.lock")
+        .ok()
+        .and_then(|lock| {
+            let mut lines = lock.lines();
+            lines.find(|l| l.trim() == "name = \"bevy\"")?;
+            let line = lines.next()?.trim();
+            Some(
+                line.trim_start_matches("version = ")
+                    .trim_matches('"')
+                    .to_string(),
+            )
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
     // Pass to compiler as environment variables
     println!("cargo:rustc-env=BUILD_TIMESTAMP={}", timestamp);
     println!("cargo:rustc-env=BUILD_COMMIT={}", commit);
+    println!("cargo:rustc-env=BEVY_VERSION={}", bevy_version);
 
     // No rerun-if-changed = rerun on any package file change (always fresh timestamp)
 }
@@ -32,7 +32,20 @@ impl Position {
 /// - Farmer (green): works at farms, avoids combat
 /// - Archer (blue): patrols and fights raiders
 /// - Raider (red): attacks guards, steals from farms
-#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Reflect)]
+#[derive(
+    Component,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Reflect,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 #[reflect(Component)]
 pub enum Job {
     Farmer,
@@ -151,6 +164,46 @@ pub struct NpcWorkState {
     pub worksite: Option<Entity>,
 }
 
+/// Farm pinned by a manual assignment (`assign_npc_to_farm`). Work claims go to this farm
+/// instead of the nearest free one until the assignment is cleared or the farm is gone.
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct AssignedFarm(pub Entity);
+
+/// Bed reserved by a manual assignment (`assign_npc_to_bed`). `Home` points at the bed;
+/// `prev_home` is restored when the assignment is cleared.
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct AssignedBed {
+    pub bed: Entity,
+    pub prev_home: Vec2,
+}
+
+/// Waypoint reserved by a manual assignment (`assign_npc_to_post`). The unit's `PatrolRoute`
+/// is just this post, and town-wide patrol rebuilds leave it alone.
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct AssignedPost(pub Entity);
+
+/// Protects another NPC of the same faction (`assign_bodyguard`): keeps close to it and fights
+/// whoever targets it, never past `BODYGUARD_LEASH`. Dropped when the charge dies.
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Bodyguard(pub Entity);
+
+/// Farmer conscripted into the town militia (`conscript`): fights as a Fighter at
+/// `MILITIA_STAT_MULT` damage/HP until `demobilize` restores `prev_job`. Population stats keep
+/// counting the unit under `prev_job`, so a militia death is a farmer death.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Militia {
+    pub prev_job: Job,
+    /// Farm pin (`AssignedFarm`) to restore on demobilize.
+    pub farm: Option<Entity>,
+    /// Damage / max HP last written by the militia scaling; a re-resolve (upgrade, equip)
+    /// that overwrites them gets scaled again.
+    pub scaled: [f32; 2],
+}
+
 /// Unified carry component for ALL NPCs. Always present — replaces the old fragmented
 /// Activity::Returning{loot} payload + CarriedGold component.
 /// Loot lives here; Activity::Returning just means "going home."
@@ -325,6 +378,11 @@ impl Activity {
             ..Default::default()
         }
     }
+
+    /// No assigned work, rest, or route — what the "next idle unit" hotkey cycles through.
+    pub fn is_idle(&self) -> bool {
+        matches!(self.kind, ActivityKind::Idle | ActivityKind::Wander)
+    }
 }
 
 /// Whether the NPC is in combat. Orthogonal to Activity — a Raiding NPC can be Fighting.
@@ -366,6 +424,96 @@ pub enum ManualTarget {
     Position(Vec2),
 }
 
+/// One stack of a carried item. Ids are free-form (`"healing_potion"`, quest items).
+#[derive(Clone, Debug, PartialEq, Eq, Reflect, serde::Serialize, serde::Deserialize)]
+pub struct ItemStack {
+    pub id: String,
+    pub count: u32,
+}
+
+/// Per-NPC carried items (potions, quest items), distinct from equipped gear in
+/// `NpcEquipment`. Always present. Holds at most `INVENTORY_CAPACITY` items across all stacks.
+#[derive(Component, Default, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Inventory(pub Vec<ItemStack>);
+
+impl Inventory {
+    pub fn count(&self, id: &str) -> u32 {
+        self.0.iter().filter(|s| s.id == id).map(|s| s.count).sum()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.0.iter().map(|s| s.count).sum()
+    }
+
+    /// Add up to `count` of `id` without exceeding `capacity` total items.
+    /// Returns how many were accepted; the rest is overflow.
+    pub fn give(&mut self, id: &str, count: u32, capacity: u32) -> u32 {
+        let accepted = count.min(capacity.saturating_sub(self.total()));
+        if accepted > 0 {
+            match self.0.iter_mut().find(|s| s.id == id) {
+                Some(stack) => stack.count += accepted,
+                None => self.0.push(ItemStack {
+                    id: id.to_string(),
+                    count: accepted,
+                }),
+            }
+        }
+        accepted
+    }
+
+    /// Remove up to `count` of `id`. Returns how many were taken; the rest is shortfall.
+    pub fn take(&mut self, id: &str, count: u32) -> u32 {
+        let Some(i) = self.0.iter().position(|s| s.id == id) else {
+            return 0;
+        };
+        let taken = count.min(self.0[i].count);
+        self.0[i].count -= taken;
+        if self.0[i].count == 0 {
+            self.0.remove(i);
+        }
+        taken
+    }
+}
+
+/// Corpse drop lying on the ground (not a GPU slot; drawn as an overlay icon).
+/// Picked up by the nearest living NPC within `LootConfig::pickup_radius`, whatever its
+/// faction; food/gold go to the picker's town storage, the item to its town equipment.
+#[derive(Component, Clone, Debug)]
+pub struct GroundLoot {
+    pub pos: Vec2,
+    pub food: i32,
+    pub gold: i32,
+    pub item: Option<crate::constants::LootItem>,
+    /// The dead NPC's `Inventory` (`LootConfig::drop_inventory`); goes to the picker's own.
+    pub items: Vec<ItemStack>,
+    /// Game seconds (`GameTime::total_seconds`) at drop.
+    pub dropped_at: f32,
+}
+
+/// Guard sent toward a town alert by `reinforce_system`. Removed (and the guard sent back to
+/// its patrol post) when the alert clears; dropped silently if decision_system takes over.
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Reinforcing {
+    pub town: usize,
+}
+
+/// Defender falling back to its town center for a last stand (`last_stand_system`).
+/// Removed, and the defender released to re-engage, once the force ratio recovers.
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct LastStand {
+    pub town: usize,
+}
+
+/// NPC anchored in place while resting (`anchor_system`). Sets `ENTITY_FLAG_ANCHORED` so the
+/// compute shader skips its movement and leaves it out of separation. Removed on wake, on
+/// taking damage, or when the building it rests at is destroyed.
+#[derive(Component, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct Anchored;
+
 /// High-churn NPC boolean flags bundled into one component to avoid archetype moves.
 /// Toggled at runtime by various systems. Query-friendly: `Query<&mut NpcFlags>`.
 #[derive(Component, Default, Clone, Reflect)]
@@ -392,6 +540,8 @@ pub struct NpcPath {
     pub goal_world: Vec2,
     /// Cooldown (seconds) after A* failure — prevents retry thrash.
     pub path_cooldown: f32,
+    /// Seconds left before an automatic retarget is accepted after arrival.
+    pub retarget_cooldown: f32,
     /// Precomputed set of HPA chunk coords this path passes through.
     pub path_chunks: Vec<(usize, usize)>,
 }
@@ -473,6 +623,203 @@ impl Faction {
 #[reflect(Component)]
 pub struct AttackTimer(pub f32);
 
+/// Windup seconds (at base attack speed) between starting an attack and the hit/shot.
+/// Absent = attacks fire instantly when the cooldown is ready.
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct AttackWindup(pub f32);
+
+/// Transient: attack in progress, fires when `elapsed` reaches the scaled windup.
+/// Removed on fire, whiff, or interruption (attack_system sweeps stale ones).
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Attacking {
+    pub elapsed: f32,
+    pub target: usize,
+}
+
+/// Promoted veteran. Buffs same-town NPCs within `aura_radius` by `buff` (fractional damage bonus).
+/// Inserted on reaching OFFICER_PROMOTION_LEVEL or via `endless/promote_npc`.
+#[derive(Component, Clone, Copy, Debug, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+pub struct Officer {
+    pub aura_radius: f32,
+    pub buff: f32,
+}
+
+/// Officer aura currently applied to this NPC (max of overlapping auras, never summed).
+/// Recomputed every tick by officer_aura_system; read by attack_system, never baked into CachedStats.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct AuraBuff(pub f32);
+
+/// Military unit beyond its town's supply radius: slower attacks and faster energy drain.
+/// Inserted and removed by supply_system; the penalties apply at use, never baked into CachedStats.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct OutOfSupply;
+
+/// Combat target selection profile, scored by the GPU targeting pass (entity_flags bits 3-4).
+/// Per-unit override; NPCs without it use their squad's `target_priority`.
+#[derive(
+    Component,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Reflect,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[reflect(Component)]
+pub enum TargetPriority {
+    /// Closest enemy in range.
+    #[default]
+    Nearest,
+    /// Lowest current HP in range (finish off the wounded); ties go to the closest.
+    LowestHp,
+    /// Highest threat in range (damage output, officers first); ties go to the closest.
+    HighestThreat,
+}
+
+impl TargetPriority {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Nearest => "Nearest",
+            Self::LowestHp => "LowestHp",
+            Self::HighestThreat => "HighestThreat",
+        }
+    }
+}
+
+/// Per-unit engagement rule. Gates both auto-targeting (GPU passive flag, entity_flags bit 6)
+/// and attack_system's chase/attack. A `ManualTarget` is an explicit order and ignores stance.
+#[derive(
+    Component,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Reflect,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[reflect(Component)]
+pub enum CombatStance {
+    /// Engage any enemy in range (default behavior).
+    #[default]
+    FireAtWill,
+    /// Stay passive until damaged, then engage for `RETURN_FIRE_WINDOW` seconds.
+    ReturnFire,
+    /// Never acquire targets, even when attacked. Still a valid target for enemies.
+    HoldFire,
+}
+
+impl CombatStance {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::FireAtWill => "FireAtWill",
+            Self::ReturnFire => "ReturnFire",
+            Self::HoldFire => "HoldFire",
+        }
+    }
+
+    /// True if the unit must not pick its own targets. `provoked` = took damage recently.
+    pub fn is_passive(self, provoked: bool) -> bool {
+        match self {
+            Self::FireAtWill => false,
+            Self::ReturnFire => !provoked,
+            Self::HoldFire => true,
+        }
+    }
+}
+
+/// Squad-wide engagement posture. `squad_stance_system` hands members the matching
+/// `CombatStance`, plus an `EngageZone` when Defensive.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, serde::Serialize, serde::Deserialize,
+)]
+pub enum SquadStance {
+    /// Engage whatever comes in range and pursue it (default).
+    #[default]
+    Aggressive,
+    /// Hold the squad target: only engage enemies within the squad's engage radius of it.
+    Defensive,
+    /// Don't pick fights; return fire when attacked.
+    Passive,
+}
+
+impl SquadStance {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Aggressive => "Aggressive",
+            Self::Defensive => "Defensive",
+            Self::Passive => "Passive",
+        }
+    }
+
+    /// The per-unit stance squad members get.
+    pub fn combat_stance(self) -> CombatStance {
+        match self {
+            Self::Aggressive | Self::Defensive => CombatStance::FireAtWill,
+            Self::Passive => CombatStance::ReturnFire,
+        }
+    }
+}
+
+/// The unit's `CombatStance` was set on it directly; its squad's stance leaves it alone.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct StanceOverride;
+
+/// The unit's `CombatStance` (and `EngageZone`) came from its squad. Both are removed when
+/// it leaves the squad, so it reverts to the default stance.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SquadStanceApplied;
+
+/// Defensive squad member: attack_system only engages targets within `radius` of `anchor`
+/// (the squad target; where the fight began when the squad has none).
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct EngageZone {
+    pub anchor: Option<Vec2>,
+    pub radius: f32,
+}
+
+impl EngageZone {
+    /// True if `target` may be engaged by a unit whose fight began at `origin`.
+    pub fn allows(&self, origin: Vec2, target: Vec2) -> bool {
+        self.anchor.unwrap_or(origin).distance(target) <= self.radius
+    }
+}
+
+/// Transient: seconds left in which a ReturnFire unit counts as provoked.
+/// Inserted/refreshed by damage_system, ticked down and removed by return_fire_system.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Provoked(pub f32);
+
+/// Transient: the unit recently acquired `target` (slot) and is committed to it for `remaining`
+/// game seconds. Inserted by attack_system on a target change, ticked down and removed by
+/// target_commit_system; mirrored into `ENTITY_FLAG_COMMITTED`.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct TargetCommit {
+    pub target: usize,
+    pub remaining: f32,
+}
+
+/// Last-known position of the NPC this unit was fighting, refreshed every tick it holds a
+/// target. Once the target leaves acquisition range, attack_system keeps the unit in combat
+/// and walks it here until `remaining` runs out (see `AggroMemoryConfig`).
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct AggroMemory {
+    pub last_pos: Vec2,
+    /// Game seconds of chase left.
+    pub remaining: f32,
+}
+
 // ============================================================================
 // NPC PROGRESSION
 // ============================================================================
@@ -486,6 +833,8 @@ pub struct AttackTimer(pub f32);
 pub struct NpcStats {
     pub name: String,
     pub xp: i32,
+    /// NPC kills credited to this unit (feeds `FactionMvp`).
+    pub kills: i32,
 }
 
 // ============================================================================
@@ -845,6 +1194,40 @@ pub struct FleeThreshold {
 #[reflect(Component)]
 pub struct LeashRange(pub f32);
 
+/// Lobbed shots: a shooter carrying this (ranged NPC, tower or fountain) fires ballistic arcs
+/// instead of flat shots. Each shot takes its shooter's arc when fired
+/// (`ProjGpuUpdate::Spawn`). Set over BRP (`endless/projectile_arc`); `gravity` 0 = flat.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct ProjArc {
+    /// Downward pull on the shot's height (px/s²). Lower gravity = longer reach at one speed.
+    pub gravity: f32,
+    /// On landing, every enemy NPC within this radius of the landing point takes the hit.
+    /// 0 = only whoever is struck where it comes down.
+    pub splash_radius: f32,
+}
+
+impl ProjArc {
+    /// Non-finite values become 0; splash is capped at `PROJ_ARC_SPLASH_MAX`.
+    pub fn new(gravity: f32, splash_radius: f32) -> Self {
+        let clean = |v: f32, max: f32| {
+            if v.is_finite() {
+                v.clamp(0.0, max)
+            } else {
+                0.0
+            }
+        };
+        Self {
+            gravity: clean(gravity, f32::MAX),
+            splash_radius: clean(splash_radius, crate::constants::PROJ_ARC_SPLASH_MAX),
+        }
+    }
+
+    pub fn is_arced(&self) -> bool {
+        self.gravity > 0.0
+    }
+}
+
 /// Drop everything and return home when HP drops below this percentage.
 /// Distinct from FleeThreshold: wounded NPCs enter recovery mode.
 #[derive(Component, Clone, Copy, Reflect)]
@@ -858,7 +1241,7 @@ pub struct WoundedThreshold {
 // ============================================================================
 
 /// 7 spectrum axes. Magnitude sign determines pole (+Brave/-Coward, etc).
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Reflect)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Reflect, serde::Serialize, serde::Deserialize)]
 pub enum TraitKind {
     Courage,   // +Brave / -Coward
     Diligence, // +Efficient / -Lazy
@@ -1024,6 +1407,16 @@ impl Personality {
         names.join(" + ")
     }
 
+    /// Signed magnitude of a trait axis (0 when the NPC lacks it).
+    pub fn magnitude(&self, kind: TraitKind) -> f32 {
+        [self.trait1, self.trait2]
+            .iter()
+            .flatten()
+            .filter(|t| t.kind == kind)
+            .map(|t| t.magnitude)
+            .sum()
+    }
+
     /// Compute behavior modifiers from traits.
     pub fn get_behavior_mods(&self) -> TraitBehaviorMods {
         let mut mods = TraitBehaviorMods::default();
@@ -99,12 +99,14 @@ pub struct BuildingDef {
     pub worksite: Option<WorksiteDef>,
     /// True = uses 4-neighbor auto-tiling (requires TileSpec::External sprite strip).
     pub autotile: bool,
+    /// Cells covered (w, h). Position is the footprint center; every covered cell is occupied.
+    pub footprint: (u8, u8),
 }
 
 impl BuildingDef {
     /// Loot dropped when this building is destroyed: half the build cost as food.
-    pub fn loot_drop(&self) -> Option<LootDrop> {
-        let amount = self.cost / 2;
+    pub fn loot_drop(&self, costs: &crate::resources::BuildingCosts) -> Option<LootDrop> {
+        let amount = costs.cost(self.kind) / 2;
         if amount > 0 {
             Some(LootDrop {
                 item: ItemKind::Food,
@@ -141,6 +143,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 1: Bed
     BuildingDef {
@@ -163,6 +166,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 2: Waypoint
     BuildingDef {
@@ -185,6 +189,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 3: Farm
     BuildingDef {
@@ -213,6 +218,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
             town_scoped: true,
         }),
         autotile: false,
+        footprint: (1, 1),
     },
     // 5: Farmer Home
     BuildingDef {
@@ -238,6 +244,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: true,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 6: Archer Home
     BuildingDef {
@@ -263,6 +270,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: true,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 7: Tent (raider spawner)
     BuildingDef {
@@ -288,6 +296,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: true,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 8: Gold Mine
     BuildingDef {
@@ -316,6 +325,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
             town_scoped: false,
         }),
         autotile: false,
+        footprint: (1, 1),
     },
     // 9: Miner Home
     BuildingDef {
@@ -341,6 +351,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 10: Crossbow Home
     BuildingDef {
@@ -366,6 +377,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: true,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 11: Fighter Home
     BuildingDef {
@@ -391,6 +403,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: true,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 12: Road (dirt) — expands buildable area by 3 tiles
     BuildingDef {
@@ -413,6 +426,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: true,
+        footprint: (1, 1),
     },
     // 13: StoneRoad — expands buildable area by 5 tiles
     BuildingDef {
@@ -435,6 +449,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: true,
+        footprint: (1, 1),
     },
     // 14: MetalRoad — expands buildable area by 7 tiles
     BuildingDef {
@@ -457,6 +472,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: true,
+        footprint: (1, 1),
     },
     // 15: Wall
     BuildingDef {
@@ -479,6 +495,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: true,
+        footprint: (1, 1),
     },
     // 14: Tower (auto-shoots enemies)
     BuildingDef {
@@ -501,6 +518,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 15: Merchant (buy/sell equipment)
     BuildingDef {
@@ -523,6 +541,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 16: Casino (blackjack minigame, 1 per town)
     BuildingDef {
@@ -533,7 +552,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         cost: 80,
         label: "Casino",
         help: "Play blackjack",
-        tooltip: "Casino — play blackjack against AI factions for gold.\n1 per town. HP: 200",
+        tooltip: "Casino — play blackjack against AI factions for gold.\n1 per town, covers 2x2 cells. HP: 200",
         player_buildable: true,
         raider_buildable: false,
         placement: PlacementMode::TownGrid,
@@ -545,6 +564,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: false,
+        footprint: (2, 2),
     },
     // 17: LumberMill
     BuildingDef {
@@ -570,6 +590,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 18: Quarry
     BuildingDef {
@@ -595,6 +616,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
         is_unit_home: false,
         worksite: None,
         autotile: false,
+        footprint: (1, 1),
     },
     // 19: TreeNode
     BuildingDef {
@@ -623,6 +645,7 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
             town_scoped: false,
         }),
         autotile: false,
+        footprint: (1, 1),
     },
     // 20: RockNode
     BuildingDef {
@@ -651,6 +674,30 @@ pub const BUILDING_REGISTRY: &[BuildingDef] = &[
             town_scoped: false,
         }),
         autotile: false,
+        footprint: (1, 1),
+    },
+    // 21: Granary (raises town food storage cap)
+    BuildingDef {
+        kind: BuildingKind::Granary,
+        display: DisplayCategory::Economy,
+        tile: TileSpec::External("sprites/house.png"),
+        hp: 150.0,
+        cost: 20,
+        label: "Granary",
+        help: "Raises food storage cap",
+        tooltip: "Granary — raises the town's food storage cap\nwhen storage limits are on. Losing one lowers\nthe cap and spoils the overflow. HP: 150",
+        player_buildable: true,
+        raider_buildable: false,
+        placement: PlacementMode::TownGrid,
+        is_tower: false,
+        tower_stats: None,
+        on_place: OnPlace::None,
+        spawner: None,
+        save_key: Some("granaries"),
+        is_unit_home: false,
+        worksite: None,
+        autotile: false,
+        footprint: (1, 1),
     },
 ];
 
@@ -670,11 +717,6 @@ pub fn tileset_index(kind: BuildingKind) -> u16 {
         .unwrap_or_else(|| panic!("no tileset index for {:?}", kind)) as u16
 }
 
-/// Food cost to build a building. Returns 0 for non-buildable types.
-pub fn building_cost(kind: BuildingKind) -> i32 {
-    building_def(kind).cost
-}
-
 // ── Autotile helpers ──────────────────────────────────────────────
 
 /// Extra atlas layers per auto-tile kind (NS, 4 corners, cross, 4 T-junctions = 10).
@@ -18,6 +18,8 @@ pub const MAX_BUILDINGS: usize = MAX_NPC_COUNT;
 
 /// Total entity capacity: NPCs + buildings share unified GPU collision buffers.
 pub const MAX_ENTITIES: usize = MAX_NPC_COUNT + MAX_BUILDINGS;
+/// Entity slots stepped by the CPU compute fallback (`ComputeBackend::Cpu`); targeting is O(n²).
+pub const CPU_FALLBACK_MAX_ENTITIES: usize = 512;
 
 /// Entity flag bits for unified entity_flags GPU buffer.
 /// Bit 0: combat targeting enabled (archers, raiders, towers).
@@ -26,6 +28,21 @@ pub const ENTITY_FLAG_COMBAT: u32 = 1;
 pub const ENTITY_FLAG_BUILDING: u32 = 2;
 /// Bit 2: entity cannot be selected as a combat target (roads).
 pub const ENTITY_FLAG_UNTARGETABLE: u32 = 4;
+/// Bits 3-4: combat target priority profile (`TargetPriority` discriminant).
+pub const ENTITY_FLAG_PRIORITY_SHIFT: u32 = 3;
+/// Bit 5: slot is hidden/free. Exempt from world-bounds clamping (hidden sentinel position).
+pub const ENTITY_FLAG_INACTIVE: u32 = 32;
+/// Bit 6: NPC never acquires combat targets (HoldFire, or ReturnFire not yet provoked).
+/// Still targetable by enemies.
+pub const ENTITY_FLAG_PASSIVE: u32 = 64;
+/// Bit 7: NPC is anchored (resting in place). Skips movement and goal-seeking, and is neither
+/// pushed by nor pushes neighbours in separation.
+pub const ENTITY_FLAG_ANCHORED: u32 = 128;
+/// Bit 8: NPC is committed to its current combat target (`TargetCommit`). The shader keeps the
+/// previous target while it stays alive, hostile and in range.
+pub const ENTITY_FLAG_COMMITTED: u32 = 256;
+/// Bits 16-23: threat value (0-255) this entity presents to HighestThreat targeting.
+pub const ENTITY_FLAG_THREAT_SHIFT: u32 = 16;
 
 /// Neutral faction — friendly to everyone. Used for world-owned buildings (gold mines).
 pub const FACTION_NEUTRAL: i32 = 0;
@@ -42,6 +59,18 @@ pub const ARRIVAL_THRESHOLD: f32 = 20.0;
 /// Prevents pile-up when boid separation pushes NPCs away from shared waypoints.
 pub const INTERMEDIATE_ARRIVAL_THRESHOLD: f32 = 96.0;
 
+/// Default GPU separation radius (px) and push strength (`BalanceConfig` overrides).
+pub const SEPARATION_RADIUS: f32 = 40.0;
+pub const SEPARATION_STRENGTH: f32 = 200.0;
+/// Frames over which separation strength ramps back to full after being re-enabled.
+pub const SEPARATION_RAMP_FRAMES: u32 = 30;
+/// Entity count above which `SeparationMode::BruteForce` falls back to the grid (the O(n²)
+/// scan costs ~16M pair checks per frame here).
+pub const SEPARATION_BRUTE_FORCE_MAX: usize = 4096;
+
+/// Default movement (px) before GPU readback rewrites an NPC's ECS Position (0 = every frame).
+pub const POSITION_SYNC_THRESHOLD: f32 = 1.0;
+
 /// Cells around each A* path cell that receive extra cost during batch accumulation (1 = 3×3 area).
 pub const PATH_SPREAD_RADIUS: i32 = 1;
 /// Cost added per affected cell during path accumulation. Grass=100, so +100 doubles traversal cost.
@@ -63,6 +92,7 @@ pub const GOLD_SPRITE: (f32, f32) = (41.0, 11.0);
 // Visual indicator sprites (column, row) — placeholder coordinates, verify against atlas
 pub const SLEEP_SPRITE: (f32, f32) = (24.0, 7.0);
 pub const HEAL_SPRITE: (f32, f32) = (23.0, 0.0);
+pub const OFFICER_INSIGNIA_SPRITE: (f32, f32) = (32.0, 10.0);
 
 // Distinct colors for raider factions (warm/aggressive palette)
 pub const RAIDER_COLORS: [(f32, f32, f32); 10] = [
@@ -92,6 +122,14 @@ pub fn raider_faction_color(faction: i32) -> (f32, f32, f32, f32) {
 /// Energy threshold below which NPCs go rest.
 pub const ENERGY_HUNGRY: f32 = 50.0;
 
+/// Default daytime window (game hours): dawn inclusive, dusk exclusive.
+pub const DEFAULT_DAWN_HOUR: i32 = 6;
+pub const DEFAULT_DUSK_HOUR: i32 = 20;
+
+/// Allowed real-seconds-per-game-day range for `GameTime::set_day_length`.
+pub const DAY_LENGTH_MIN_SECS: f32 = 24.0;
+pub const DAY_LENGTH_MAX_SECS: f32 = 86_400.0;
+
 /// Ticks an archer waits at a post before moving to next.
 pub const ARCHER_PATROL_WAIT: u32 = 60;
 
@@ -104,6 +142,13 @@ pub const ENERGY_TIRED_THRESHOLD: f32 = 30.0;
 /// Energy threshold below which NPCs consider eating (emergency only).
 pub const ENERGY_EAT_THRESHOLD: f32 = 10.0;
 
+/// Minimum gap kept between a job's rest and resume thresholds (see `EnergyThresholds`).
+pub const ENERGY_THRESHOLD_MIN_GAP: f32 = 10.0;
+
+/// Default energy recovery/drain per game hour (`BalanceConfig` overrides).
+pub const ENERGY_RECOVER_PER_HOUR: f32 = 100.0 / 6.0; // 6 hours to full (resting)
+pub const ENERGY_DRAIN_PER_HOUR: f32 = 100.0 / 24.0; // 24 hours to empty (active)
+
 // ============================================================================
 // UTILITY AI ACTION SCORES
 // ============================================================================
@@ -143,6 +188,8 @@ pub const MAX_FARMS: usize = 500;
 
 /// Maximum projectiles the system can handle.
 pub const MAX_PROJECTILES: usize = 50000;
+/// Most projectiles `ProjBufferWrites::active_projectiles` returns in one call.
+pub const ACTIVE_PROJECTILES_QUERY_MAX: usize = 4096;
 
 /// Oriented rectangle hitbox for arrow projectiles.
 pub const PROJECTILE_HIT_HALF_LENGTH: f32 = 24.0; // along travel direction
@@ -153,9 +200,30 @@ pub const PROJECTILE_HIT_HALF_WIDTH: f32 = 8.0; // perpendicular to travel
 pub const NPC_HITBOX_HALF: [f32; 2] = [16.0, 16.0];
 pub const BUILDING_HITBOX_HALF: [f32; 2] = [32.0, 32.0];
 
+/// Default seconds a freshly spawned NPC takes to fade from transparent to opaque.
+/// 0 = pop in instantly (off by default). Tunable at runtime via
+/// `EntityGpuState::set_spawn_fade_duration`.
+pub const SPAWN_FADE_SECS: f32 = 0.0;
+
+/// Seconds a death-knockback corpse slides before its slot is hidden and freed.
+pub const DEATH_KNOCKBACK_SECS: f32 = 0.35;
+/// Longest corpse slide in px (`DeathKnockback::set_death_knockback` clamps to this).
+pub const DEATH_KNOCKBACK_MAX: f32 = 200.0;
+
 /// Floats per projectile instance in MultiMesh buffer.
 pub const PROJ_FLOATS_PER_INSTANCE: usize = 12;
 
+/// Most trail quads a projectile can draw (length of its prior-position ring).
+pub const PROJ_TRAIL_MAX: usize = 8;
+
+/// World distance a projectile travels between recorded trail points.
+pub const PROJ_TRAIL_SPACING: f32 = 10.0;
+
+/// Largest splash radius an arced projectile kind can have (px).
+pub const PROJ_ARC_SPLASH_MAX: f32 = 256.0;
+/// Seconds an arced shot's lifetime runs past its flight time, so it lands before expiring.
+pub const PROJ_ARC_LAND_MARGIN: f32 = 0.25;
+
 /// Size of push constants for projectile compute shader.
 pub const PROJ_PUSH_CONSTANTS_SIZE: usize = 32;
 
@@ -178,6 +246,12 @@ pub const RAIDER_MAX_POP: i32 = 500;
 /// Minimum raiders needed to form a raid group.
 pub const RAID_GROUP_SIZE: i32 = 3;
 
+/// Default cap on raiders sent in one raid party.
+pub const RAID_PARTY_MAX: usize = 8;
+
+/// Default game hours between raid parties from one camp.
+pub const RAID_PARTY_COOLDOWN_HOURS: f32 = 2.0;
+
 /// Villager population per raider town (1 raider town per 20 villagers).
 pub const VILLAGERS_PER_RAIDER: i32 = 20;
 
@@ -213,6 +287,73 @@ pub const STARVING_HP_CAP: f32 = 0.5;
 /// Speed multiplier when starving (50% of normal).
 pub const STARVING_SPEED_MULT: f32 = 0.5;
 
+// ============================================================================
+// CROWD PRESS CONSTANTS
+// ============================================================================
+
+/// Game-seconds between crowd press (trample) damage applications.
+pub const TRAMPLE_INTERVAL_SECS: f32 = 1.0;
+
+/// Crowd press never pushes HP below this fraction of max — it weakens, never kills.
+pub const TRAMPLE_HP_FLOOR: f32 = 0.25;
+
+// ============================================================================
+// OFFICER CONSTANTS
+// ============================================================================
+
+/// Military NPCs reaching this level are promoted to officer.
+pub const OFFICER_PROMOTION_LEVEL: i32 = 5;
+
+/// Radius (px) of an officer's aura over same-town NPCs.
+pub const OFFICER_AURA_RADIUS: f32 = 200.0;
+
+/// Fractional damage bonus granted by an officer aura (0.15 = +15%).
+pub const OFFICER_AURA_BUFF: f32 = 0.15;
+
+/// Threat multiplier for officers, so HighestThreat targeting picks them over peers.
+pub const OFFICER_THREAT_MULT: f32 = 2.0;
+
+/// Seconds a ReturnFire unit stays provoked after taking damage.
+pub const RETURN_FIRE_WINDOW: f32 = 5.0;
+
+/// Default town food storage cap with no granaries (when storage limits are on).
+pub const FOOD_CAP_BASE: i32 = 500;
+/// Food storage cap added per granary.
+pub const FOOD_CAP_PER_GRANARY: i32 = 500;
+
+/// Default incoming-damage multipliers for `CombatZones`: per wall cell in or around the
+/// defender's cell, standing on a road, and standing in open water.
+pub const COMBAT_ZONE_WALL: f32 = 0.85;
+pub const COMBAT_ZONE_ROAD: f32 = 0.9;
+pub const COMBAT_ZONE_WATER: f32 = 1.25;
+/// Lowest combined cover multiplier, so a unit boxed in by walls still takes damage.
+pub const COMBAT_COVER_FLOOR: f32 = 0.6;
+
+/// Damage and max HP of conscripted militia relative to a Fighter.
+pub const MILITIA_STAT_MULT: f32 = 0.6;
+
+/// A followed unit that moves farther than this (px) in one frame teleported (respawn,
+/// migration); the camera snaps to it instead of panning across the map.
+pub const CAMERA_FOLLOW_SNAP_DIST: f32 = 512.0;
+
+/// Targets tracked slower than this (px/s) count as stationary: shots aim straight at them
+/// instead of leading by position-readback jitter.
+pub const LEAD_MIN_SPEED: f32 = 8.0;
+/// Position jumps faster than this (px/s) are teleports or slot reuse, not movement.
+pub const LEAD_MAX_SPEED: f32 = 1000.0;
+/// Seconds without a position change before a tracked NPC is treated as stopped.
+pub const NPC_VELOCITY_STALE_SECS: f32 = 0.5;
+/// Default damage bonus for a hit straight from behind (`CombatConfig.flank_bonus`).
+/// Side hits get a third of it, frontal hits none.
+pub const FLANK_BONUS: f32 = 0.25;
+/// Upper bound on `flank_bonus`, however it is configured.
+pub const FLANK_BONUS_CAP: f32 = 1.0;
+/// Hits within this cosine of the defender's facing (60 degrees) count as frontal.
+pub const FLANK_FRONT_DOT: f32 = 0.5;
+/// Fraction of the way a unit's facing turns toward its new heading each tick. Smooths
+/// melee swirls so flank damage doesn't flip between front and rear frame to frame.
+pub const FACING_TURN_RATE: f32 = 0.25;
+
 // ============================================================================
 // BUILDING SYSTEM CONSTANTS
 // ============================================================================
@@ -230,6 +371,122 @@ pub const BASE_GRID_MAX: i32 = 3;
 /// Maximum grid extent (rows/cols -49 to +50 = 100x100).
 pub const MAX_GRID_EXTENT: i32 = 49;
 
+/// Build-area utilization (occupied / buildable cells) at which towns with the
+/// `auto_expand` policy buy the next Expansion ring.
+pub const AUTO_EXPAND_UTILIZATION: f32 = 0.8;
+
+/// Default corpse-loot pickup radius (px) for `LootConfig`.
+pub const LOOT_PICKUP_RADIUS: f32 = 48.0;
+/// Default game seconds a corpse drop lies on the ground before despawning.
+pub const LOOT_LIFETIME_SECS: f32 = 60.0;
+/// Default cap on ground loot; the oldest drops despawn first past it.
+pub const LOOT_MAX_ACTIVE: usize = 512;
+
+/// Total items (across all stacks) an NPC `Inventory` holds.
+pub const INVENTORY_CAPACITY: u32 = 20;
+/// Item id consumed by `potion_use_system`.
+pub const HEALING_POTION_ID: &str = "healing_potion";
+/// HP restored per healing potion (capped at max HP).
+pub const HEALING_POTION_HEAL: f32 = 40.0;
+/// NPCs drink a potion below this fraction of max HP.
+pub const HEALING_POTION_HP_FRAC: f32 = 0.35;
+
+/// Game seconds without enemy damage before a town alert clears.
+pub const TOWN_ALERT_CLEAR_SECS: f32 = 10.0;
+/// Default policy radius (px) around a town alert that idle/patrolling guards answer.
+pub const REINFORCE_RADIUS: f32 = 800.0;
+/// Default fraction of a town's guards held back at their posts during an alert.
+pub const REINFORCE_RESERVE: f32 = 0.34;
+/// Default policy enemies-per-defender ratio that triggers a last stand.
+pub const LAST_STAND_RATIO: f32 = 2.0;
+/// Radius (px) around a town alert in which enemies and defenders are counted.
+pub const LAST_STAND_RADIUS: f32 = 600.0;
+/// Game seconds between last-stand force-ratio checks.
+pub const LAST_STAND_CHECK_SECS: f32 = 3.0;
+/// A running last stand only lifts once the ratio drops below this fraction of the trigger.
+pub const LAST_STAND_RECOVER: f32 = 0.75;
+/// Spacing (px) between defenders in the rally formation.
+pub const LAST_STAND_SPACING: f32 = 20.0;
+/// Distance (px) a bodyguard keeps from its charge; each guard takes its own bearing.
+pub const BODYGUARD_OFFSET: f32 = 40.0;
+/// A bodyguard within this distance (px) of its spot next to the charge stays put.
+pub const BODYGUARD_FOLLOW_SLACK: f32 = 24.0;
+/// Bodyguards never fight or chase past this distance (px) from their charge.
+pub const BODYGUARD_LEASH: f32 = 300.0;
+/// Default policy panic gained per second for each fleeing same-town neighbour (0 = off).
+pub const PANIC_SPREAD: f32 = 0.2;
+/// Default policy panic at which a unit routs.
+pub const PANIC_THRESHOLD: f32 = 1.0;
+/// Radius (px) within which a fleeing unit spreads panic.
+pub const PANIC_RADIUS: f32 = 150.0;
+/// Panic lost per second. Outpaces the spread from fewer than 3 fleeing neighbours at the
+/// default rate, so one casualty never starts a rout.
+pub const PANIC_DECAY_PER_SEC: f32 = 0.5;
+/// Fleeing neighbours counted per unit; caps how fast panic can build.
+pub const PANIC_MAX_SOURCES: usize = 5;
+/// Game seconds a flee keeps counting as a panic source.
+pub const PANIC_FLEE_SECS: f32 = 6.0;
+/// Game seconds a panicked unit stays routed.
+pub const PANIC_ROUT_SECS: f32 = 8.0;
+/// Game seconds between panic updates.
+pub const PANIC_CHECK_SECS: f32 = 0.5;
+/// Default policy radius (px) around a friendly building inside which field units stay
+/// supplied (0 = supply lines off).
+pub const SUPPLY_RADIUS: f32 = 1200.0;
+/// Attack cooldown multiplier for out-of-supply units.
+pub const SUPPLY_COOLDOWN_MULT: f32 = 1.5;
+/// Energy drain multiplier for out-of-supply units.
+pub const SUPPLY_ENERGY_DRAIN_MULT: f32 = 3.0;
+/// Game seconds between supply checks.
+pub const SUPPLY_CHECK_SECS: f32 = 1.0;
+
+// ============================================================================
+// FATIGUE
+// ============================================================================
+
+/// Energy below which units start fighting worse. Tunable in the balance file.
+pub const FATIGUE_ENERGY_THRESHOLD: f32 = 30.0;
+/// Fraction of damage lost (and attack speed lost) at 0 energy; scales linearly from the
+/// threshold down. Tunable in the balance file.
+pub const FATIGUE_MAX_PENALTY: f32 = 0.4;
+/// Fatigue penalty reduction per point of Vitality magnitude (Hardy +1 halves it, Frail
+/// -1 makes it 1.5x).
+pub const HARDY_FATIGUE_RESIST: f32 = 0.5;
+/// Lowest combined DPS fraction fatigue and supply penalties can push a unit to.
+/// Tunable in the balance file.
+pub const COMBAT_MODIFIER_FLOOR: f32 = 0.35;
+
+// ============================================================================
+// THREAT MAP
+// ============================================================================
+
+/// Threat map cell size (px). Coarse on purpose: 16x16 cells on an 8000px world.
+pub const THREAT_MAP_CELL_SIZE: f32 = 512.0;
+/// Game seconds between threat map rebuilds (each rebuild scans every military unit).
+pub const THREAT_MAP_UPDATE_SECS: f32 = 3.0;
+/// Enemy strength near a target that doubles its effective distance when AI squads pick targets.
+pub const AI_THREAT_AVOID_STRENGTH: f32 = 50.0;
+
+// ============================================================================
+// WIN CONDITIONS
+// ============================================================================
+
+/// Game seconds between win/loss condition checks.
+pub const VICTORY_CHECK_SECS: f32 = 2.0;
+
+/// Neutral town happiness (0-100): where towns start and what no pressure settles at.
+pub const HAPPINESS_BASE: f32 = 50.0;
+/// Food stock per resident that reads as neither surplus nor shortage.
+pub const HAPPINESS_FOOD_COMFORT: f32 = 5.0;
+/// Most points food can add (at twice the comfort stock) or take (at none).
+pub const HAPPINESS_FOOD_MAX: f32 = 25.0;
+/// Points lost per fraction of residents killed in a day (10% dead = -15).
+pub const HAPPINESS_DEATH_WEIGHT: f32 = 150.0;
+pub const HAPPINESS_DEATH_MAX: f32 = 30.0;
+/// Points lost per resident beyond one per home, as a fraction of homes (50% over = -25).
+pub const HAPPINESS_CROWDING_WEIGHT: f32 = 50.0;
+pub const HAPPINESS_CROWDING_MAX: f32 = 25.0;
+
 // ============================================================================
 // BUILDING TOWER STATS
 // ============================================================================
@@ -273,9 +530,28 @@ pub const TOWER_STATS: TowerStats = TowerStats {
 /// Maximum number of player-controlled squads.
 pub const MAX_SQUADS: usize = 10;
 
+/// Default squad engage radius (px): how far from its hold point a Defensive squad engages.
+pub const SQUAD_ENGAGE_RADIUS: f32 = 400.0;
+
+/// Result cap for rectangle selection queries (box select, `endless/select_in_rect`).
+pub const SELECT_RECT_MAX: usize = 1000;
+
 /// Default real-time seconds between AI decisions.
 pub const DEFAULT_AI_INTERVAL: f32 = 5.0;
 
+/// Defensive faction stance: AI waves only target buildings within this distance of home.
+pub const STANCE_DEFENSIVE_RADIUS: f32 = 2500.0;
+
+/// Tribute: default share of a subject town's income paid to its overlord.
+pub const DEFAULT_TRIBUTE_FRACTION: f32 = 0.25;
+/// Tribute: default game days between payments.
+pub const DEFAULT_TRIBUTE_INTERVAL_DAYS: u32 = 1;
+
+/// Behavior LOD: off-screen, non-fighting NPCs run decision/energy this many times less often.
+pub const BEHAVIOR_LOD_STRIDE: usize = 4;
+/// Behavior LOD: world-space margin around the camera view still updated at full rate.
+pub const BEHAVIOR_LOD_MARGIN: f32 = 256.0;
+
 // ============================================================================
 // GOLD MINE CONSTANTS
 // ============================================================================
@@ -360,7 +636,17 @@ pub enum FactionKind {
 
 /// Town type identity. Replaces implicit `is_raider: bool` branching.
 #[derive(
-    Clone, Copy, PartialEq, Eq, Hash, Debug, Reflect, serde::Serialize, serde::Deserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Reflect,
+    serde::Serialize,
+    serde::Deserialize,
 )]
 pub enum TownKind {
     Player,
@@ -412,6 +698,23 @@ pub fn town_def(kind: TownKind) -> &'static TownDef {
         .unwrap_or_else(|| panic!("no TownDef for {:?}", kind))
 }
 
+// ============================================================================
+// ADAPTIVE QUALITY
+// ============================================================================
+
+/// Highest (cheapest) adaptive quality level. 0 = full quality.
+pub const QUALITY_MAX_LEVEL: u8 = 4;
+/// Frames the smoothed frame time must stay over budget before dropping a level.
+pub const QUALITY_DOWNGRADE_FRAMES: u32 = 90;
+/// Frames the smoothed frame time must stay under `budget * QUALITY_HEADROOM` before raising a level.
+pub const QUALITY_UPGRADE_FRAMES: u32 = 300;
+/// Fraction of the budget counted as headroom (dead band between this and 1.0 holds the level).
+pub const QUALITY_HEADROOM: f32 = 0.75;
+/// EMA weight for the frame time sample (smooths single-frame spikes).
+pub const QUALITY_SMOOTHING: f32 = 0.1;
+/// Live combat projectile cap at the cheapest level (over cap, ranged hits resolve instantly).
+pub const QUALITY_PROJECTILE_CAP: usize = 4000;
+
 // ============================================================================
 // ATLAS IDS (shared between gpu.rs, render.rs, and npc_render.wgsl)
 // ============================================================================
@@ -581,6 +884,7 @@ mod tests {
             BuildingKind::Casino,
             BuildingKind::TreeNode,
             BuildingKind::RockNode,
+            BuildingKind::Granary,
         ];
         for kind in kinds {
             let def = building_def(kind);
@@ -0,0 +1,270 @@
+//! CPU compute fallback — runs the NPC movement and combat-targeting passes on the CPU when
+//! GPU compute is unavailable (`ComputeBackend::Cpu`).
+//!
+//! The render world flags `GPU_COMPUTE_FAILED` when the NPC compute pipeline can't run (no
+//! render app, shader/pipeline error); `select_compute_backend` then switches the backend,
+//! once. `--cpu-compute` forces it from startup. `cpu_compute_system` reuses the same CPU
+//! buffers as the GPU path: it reads goals/speeds/factions/health/flags from
+//! `EntityGpuState`, steps positions straight toward their goals (no separation, dodge or
+//! road bonus), picks combat targets with the shader's rules, and fills `GpuReadState` the
+//! way the readbacks would. Stepped positions are written back as dirty so rendering follows.
+//! Only the first `CPU_FALLBACK_MAX_ENTITIES` slots are stepped — O(n²) targeting is fine for
+//! a small sim, not a full map. Projectiles stay GPU-only; NPC attacks deal direct damage.
+
+use std::sync::atomic::Ordering;
+
+use bevy::prelude::*;
+
+use crate::constants::{
+    CPU_FALLBACK_MAX_ENTITIES, ENTITY_FLAG_ANCHORED, ENTITY_FLAG_BUILDING, ENTITY_FLAG_COMBAT,
+    ENTITY_FLAG_COMMITTED, ENTITY_FLAG_INACTIVE, ENTITY_FLAG_PASSIVE, ENTITY_FLAG_PRIORITY_SHIFT,
+    ENTITY_FLAG_THREAT_SHIFT, ENTITY_FLAG_UNTARGETABLE,
+};
+use crate::gpu::{EntityGpuData, EntityGpuState, RenderFrameConfig};
+use crate::messages::GPU_COMPUTE_FAILED;
+use crate::resources::{ComputeBackend, ComputeMode, GpuReadState, GpuSlotPool};
+
+// Target priority profiles (entity flag bits 3-4), as in npc_compute.wgsl
+const PRIORITY_MASK: u32 = 3;
+const PRIORITY_LOWEST_HP: u32 = 1;
+const PRIORITY_HIGHEST_THREAT: u32 = 2;
+const THREAT_MASK: u32 = 0xFF;
+
+/// Switch to the CPU backend once the render world reports GPU compute failure.
+pub fn select_compute_backend(mut backend: ResMut<ComputeBackend>) {
+    if *backend == ComputeBackend::Gpu && GPU_COMPUTE_FAILED.load(Ordering::Relaxed) {
+        *backend = ComputeBackend::Cpu;
+        warn!(
+            "GPU compute unavailable — falling back to CPU movement/combat (first {} entity slots)",
+            CPU_FALLBACK_MAX_ENTITIES
+        );
+    }
+}
+
+/// Step movement + combat targeting on the CPU. Runs right after `populate_gpu_state` so
+/// this frame's goals, spawns and teleports are already applied.
+pub fn cpu_compute_system(
+    config: Res<RenderFrameConfig>,
+    slots: Res<GpuSlotPool>,
+    mut npc_state: ResMut<EntityGpuState>,
+    mut read: ResMut<GpuReadState>,
+) {
+    let count = slots.count().min(CPU_FALLBACK_MAX_ENTITIES);
+    cpu_step(
+        &mut npc_state,
+        &mut read,
+        &config.npc,
+        config.compute_mode,
+        count,
+    );
+}
+
+/// One CPU compute frame over slots `0..count`.
+pub fn cpu_step(
+    state: &mut EntityGpuState,
+    read: &mut GpuReadState,
+    params: &EntityGpuData,
+    mode: ComputeMode,
+    count: usize,
+) {
+    let count = count.min(state.speeds.len());
+    // New slots (first frame or growth) start from the CPU-side positions; spawns and
+    // teleports this frame overwrite whatever was stepped before
+    let known = read.positions.len() / 2;
+    read.positions.resize(count * 2, -9999.0);
+    for i in known..count {
+        read.positions[i * 2] = state.positions[i * 2];
+        read.positions[i * 2 + 1] = state.positions[i * 2 + 1];
+    }
+    for &i in &state.position_dirty_indices {
+        if i < count {
+            read.positions[i * 2] = state.positions[i * 2];
+            read.positions[i * 2 + 1] = state.positions[i * 2 + 1];
+        }
+    }
+
+    let dt = params.delta;
+    let bounded = params.bounds_max_x > params.bounds_min_x;
+    for i in 0..count {
+        let flags = state.entity_flags[i];
+        let mut pos = Vec2::new(read.positions[i * 2], read.positions[i * 2 + 1]);
+        if pos.x < -9000.0
+            || flags & (ENTITY_FLAG_INACTIVE | ENTITY_FLAG_BUILDING | ENTITY_FLAG_ANCHORED) != 0
+        {
+            continue;
+        }
+        let before = pos;
+        if mode == ComputeMode::Scripted {
+            pos += Vec2::new(state.velocities[i * 2], state.velocities[i * 2 + 1]) * dt;
+        } else {
+            let speed = state.speeds[i];
+            if speed <= 0.0 {
+                continue;
+            }
+            let goal = Vec2::new(state.targets[i * 2], state.targets[i * 2 + 1]);
+            let to_goal = goal - pos;
+            let dist = to_goal.length();
+            if dist > params.arrival_threshold {
+                pos += to_goal / dist * (speed * dt).min(dist);
+                state.arrivals[i] = 0;
+            } else {
+                state.arrivals[i] = 1;
+            }
+        }
+        if bounded {
+            pos = pos.clamp(
+                Vec2::new(params.bounds_min_x, params.bounds_min_y),
+                Vec2::new(params.bounds_max_x, params.bounds_max_y),
+            );
+        }
+        if pos != before {
+            read.positions[i * 2] = pos.x;
+            read.positions[i * 2 + 1] = pos.y;
+            state.positions[i * 2] = pos.x;
+            state.positions[i * 2 + 1] = pos.y;
+            state.position_dirty_indices.push(i);
+        }
+    }
+    state.position_dirty_indices.sort_unstable();
+    state.position_dirty_indices.dedup();
+
+    // Combat targeting + threat counts (brute force over the stepped slots)
+    read.combat_targets.resize(count, -1);
+    read.threat_counts.resize(count, 0);
+    let range_sq = params.combat_range * params.combat_range;
+    let threat_sq = params.threat_radius * params.threat_radius;
+    for i in 0..count {
+        let flags = state.entity_flags[i];
+        let pos = Vec2::new(read.positions[i * 2], read.positions[i * 2 + 1]);
+        let needs_combat = flags & ENTITY_FLAG_COMBAT != 0;
+        if pos.x < -9000.0
+            || state.healths[i] <= 0.0
+            || (flags & ENTITY_FLAG_BUILDING != 0 && !needs_combat)
+        {
+            read.combat_targets[i] = -1;
+            read.threat_counts[i] = 0;
+            continue;
+        }
+        let my_faction = state.factions[i];
+        let priority = (flags >> ENTITY_FLAG_PRIORITY_SHIFT) & PRIORITY_MASK;
+        let hostile = |j: usize| {
+            let f = state.factions[j];
+            f != my_faction && f != -1 && f != 0
+        };
+        let targetable = |j: usize| {
+            state.healths[j] > 0.0 && state.entity_flags[j] & ENTITY_FLAG_UNTARGETABLE == 0
+        };
+        let dist_sq = |j: usize| {
+            Vec2::new(read.positions[j * 2], read.positions[j * 2 + 1]).distance_squared(pos)
+        };
+        let mut best: Option<(f32, f32, usize)> = None;
+        let (mut enemies, mut allies) = (0u32, 0u32);
+        for j in 0..count {
+            if j == i || !targetable(j) {
+                continue;
+            }
+            let d = dist_sq(j);
+            if d <= threat_sq {
+                if hostile(j) {
+                    enemies += 1;
+                } else {
+                    allies += 1;
+                }
+            }
+            if hostile(j) && d < range_sq {
+                let key = match priority {
+                    PRIORITY_LOWEST_HP => state.healths[j],
+                    PRIORITY_HIGHEST_THREAT => {
+                        -(((state.entity_flags[j] >> ENTITY_FLAG_THREAT_SHIFT) & THREAT_MASK)
+                            as f32)
+                    }
+                    _ => 0.0,
+                };
+                if best.is_none_or(|(bk, bd, _)| key < bk || (key == bk && d < bd)) {
+                    best = Some((key, d, j));
+                }
+            }
+        }
+        let mut target = best.map_or(-1, |(_, _, j)| j as i32);
+        // Committed units keep last frame's target while it stays valid
+        let prev = read.combat_targets[i];
+        if flags & ENTITY_FLAG_COMMITTED != 0 && prev >= 0 && (prev as usize) < count {
+            let p = prev as usize;
+            if targetable(p) && hostile(p) && dist_sq(p) < range_sq {
+                target = prev;
+            }
+        }
+        let passive = flags & ENTITY_FLAG_PASSIVE != 0;
+        read.combat_targets[i] = if needs_combat && !passive { target } else { -1 };
+        read.threat_counts[i] = (enemies << 16) | (allies & 0xFFFF);
+    }
+
+    read.health.clear();
+    read.health.extend_from_slice(&state.healths[..count]);
+    read.factions.clear();
+    read.factions.extend_from_slice(&state.factions[..count]);
+    read.npc_count = count;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn(state: &mut EntityGpuState, i: usize, pos: Vec2, faction: i32) {
+        state.positions[i * 2] = pos.x;
+        state.positions[i * 2 + 1] = pos.y;
+        state.targets[i * 2] = pos.x;
+        state.targets[i * 2 + 1] = pos.y;
+        state.speeds[i] = 100.0;
+        state.factions[i] = faction;
+        state.healths[i] = 1.0;
+        state.entity_flags[i] = ENTITY_FLAG_COMBAT;
+        state.position_dirty_indices.push(i);
+    }
+
+    #[test]
+    fn cpu_backend_moves_to_targets_and_acquires_enemies() {
+        let mut state = EntityGpuState::default();
+        let mut read = GpuReadState::default();
+        let params = EntityGpuData {
+            delta: 0.1,
+            combat_range: 150.0,
+            ..Default::default()
+        };
+        spawn(&mut state, 0, Vec2::new(0.0, 0.0), 1);
+        spawn(&mut state, 1, Vec2::new(1000.0, 0.0), 2);
+        spawn(&mut state, 2, Vec2::new(0.0, 50.0), 1);
+        state.targets[0] = 500.0;
+
+        cpu_step(&mut state, &mut read, &params, ComputeMode::Normal, 3);
+        assert!(
+            (read.positions[0] - 10.0).abs() < 1e-3,
+            "speed * delta toward the goal"
+        );
+        assert!(
+            state.position_dirty_indices.contains(&0),
+            "stepped position is uploaded"
+        );
+        assert_eq!(read.combat_targets, vec![-1, -1, -1], "nobody in range yet");
+
+        for _ in 0..60 {
+            cpu_step(&mut state, &mut read, &params, ComputeMode::Normal, 3);
+        }
+        assert!((read.positions[0] - 500.0).abs() <= params.arrival_threshold);
+        assert_eq!(state.arrivals[0], 1, "settles at the goal");
+
+        // The enemy walks up until the two are ~100px apart: both sides acquire each other
+        state.targets[2] = 560.0;
+        for _ in 0..60 {
+            cpu_step(&mut state, &mut read, &params, ComputeMode::Normal, 3);
+        }
+        assert_eq!(read.combat_targets[0], 1);
+        assert_eq!(
+            read.combat_targets[1], 0,
+            "nearest hostile, not the ally further back"
+        );
+        assert_eq!(read.combat_targets[2], -1, "out of range");
+        assert_eq!(read.threat_counts[0] >> 16, 1);
+        assert_eq!(read.npc_count, 3);
+    }
+}
@@ -119,6 +119,15 @@ pub struct BuildingInstance {
     pub faction: i32,
 }
 
+/// Grid cells occupied by a building instance (its full footprint).
+fn building_cells(inst: &BuildingInstance) -> impl Iterator<Item = (i32, i32)> {
+    crate::world::footprint_cells(
+        inst.position,
+        crate::constants::building_def(inst.kind).footprint,
+        TOWN_GRID_SPACING,
+    )
+}
+
 /// Per-NPC runtime state. All NPC data lives here — no ECS components except GpuSlot.
 /// Parallel to BuildingInstance: both live in EntityMap, shared slot namespace.
 #[derive(Clone)]
@@ -134,6 +143,23 @@ pub struct NpcEntry {
     pub dead: bool,
 }
 
+/// Slot usage split by state (`EntityMap::get_npc_counts`). Every slot below the high-water
+/// mark is exactly one of alive, dead, hidden, building or free.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NpcCounts {
+    /// High-water mark of the slot allocator (NPCs and buildings share it).
+    pub allocated: usize,
+    pub alive: usize,
+    /// Registered NPCs already marked dead, waiting on death_system to despawn them.
+    pub dead: usize,
+    /// Slots still held with nothing registered on them, e.g. corpses sliding from a death
+    /// knockback.
+    pub hidden: usize,
+    pub buildings: usize,
+    /// Freed slots waiting to be reused.
+    pub free_slots: usize,
+}
+
 /// Unified entity registry — ALL entities (NPCs + buildings) slot→entity mapping,
 /// plus building-specific instance data, spatial grid, and indexes.
 /// Populated on NPC spawn and building placement, used by damage/combat/rendering for entity lookup.
@@ -159,11 +185,15 @@ pub struct EntityMap {
     npc_by_town: HashMap<i32, DenseSlotSet>,
     // Per-worksite FIFO claim order: slot -> claimer entity queue.
     worksite_claim_queue: HashMap<usize, Vec<Entity>>,
+    // Why the last failed claim failed (cleared on success); see `last_error`.
+    last_error: Option<&'static str>,
 
     // Spatial grid
     spatial_cell_size: f32,
     spatial_width: usize,
     spatial_cells: Vec<Vec<usize>>,
+    // NPC slots per cell of the same grid, rebuilt on demand from GPU positions
+    npc_spatial_cells: Vec<Vec<usize>>,
 
     // Kind-filtered spatial indexes (for worksite queries)
     // Key: (kind, town_idx, cell_index) → slots matching kind+town in that cell
@@ -260,9 +290,7 @@ impl EntityMap {
             if let Some(slots) = self.by_kind_town.get_mut(&(old.kind, old.town_idx)) {
                 slots.remove(slot);
             }
-            let old_gc = (old.position.x / TOWN_GRID_SPACING).floor() as i32;
-            let old_gr = (old.position.y / TOWN_GRID_SPACING).floor() as i32;
-            self.by_grid_cell.remove(&(old_gc, old_gr));
+            self.clear_grid_cells(&old);
             self.spatial_remove(slot, old.position);
             self.spawner_slots.remove(slot);
         }
@@ -274,9 +302,9 @@ impl EntityMap {
             .entry((kind, inst.town_idx))
             .or_default()
             .insert(slot, inst.clone());
-        let gc = (inst.position.x / TOWN_GRID_SPACING).floor() as i32;
-        let gr = (inst.position.y / TOWN_GRID_SPACING).floor() as i32;
-        self.by_grid_cell.insert((gc, gr), slot);
+        for cell in building_cells(&inst) {
+            self.by_grid_cell.insert(cell, slot);
+        }
         let is_spawner = crate::constants::building_def(inst.kind).spawner.is_some();
         let pos = inst.position;
         self.instances.insert(slot, inst);
@@ -287,6 +315,15 @@ impl EntityMap {
         }
     }
 
+    /// Clear every grid cell a building's footprint occupies (only cells still mapped to it).
+    fn clear_grid_cells(&mut self, inst: &BuildingInstance) {
+        for cell in building_cells(inst) {
+            if self.by_grid_cell.get(&cell) == Some(&inst.slot) {
+                self.by_grid_cell.remove(&cell);
+            }
+        }
+    }
+
     /// Remove an instance by slot. Returns removed instance if any.
     fn remove_instance(&mut self, slot: usize) -> Option<BuildingInstance> {
         if let Some(inst) = self.instances.remove(slot) {
@@ -296,9 +333,7 @@ impl EntityMap {
             if let Some(slots) = self.by_kind_town.get_mut(&(inst.kind, inst.town_idx)) {
                 slots.remove(slot);
             }
-            let gc = (inst.position.x / TOWN_GRID_SPACING).floor() as i32;
-            let gr = (inst.position.y / TOWN_GRID_SPACING).floor() as i32;
-            self.by_grid_cell.remove(&(gc, gr));
+            self.clear_grid_cells(&inst);
             self.spatial_remove(slot, inst.position);
             self.spawner_slots.remove(slot);
             self.occupancy.remove(slot);
@@ -518,6 +553,22 @@ impl EntityMap {
         }
     }
 
+    /// Move an NPC to another town (and faction), keeping the per-town index in sync.
+    pub fn set_npc_town(&mut self, slot: usize, town_idx: i32, faction: i32) {
+        let Some(entry) = self.npcs.get_mut(&slot) else {
+            return;
+        };
+        let old_town = entry.town_idx;
+        entry.town_idx = town_idx;
+        entry.faction = faction;
+        if old_town != town_idx {
+            if let Some(slots) = self.npc_by_town.get_mut(&old_town) {
+                slots.remove(slot);
+            }
+            self.npc_by_town.entry(town_idx).or_default().insert(slot);
+        }
+    }
+
     pub fn get_npc(&self, slot: usize) -> Option<&NpcEntry> {
         self.npcs.get(&slot)
     }
@@ -550,6 +601,73 @@ impl EntityMap {
         self.npcs.len()
     }
 
+    /// Population split for display. `npc_count` and the allocator's high-water mark both
+    /// include the dead and recycled. The split follows the dead flag death_system sets when
+    /// it picks up a kill (deaths past its per-frame cap stay dead until drained), not the GPU
+    /// health readback, which lags a frame and still holds the old occupant's health on a
+    /// recycled slot.
+    pub fn get_npc_counts(&self, slots: &crate::resources::GpuSlotPool) -> NpcCounts {
+        let dead = self.npcs.values().filter(|n| n.dead).count();
+        let alive = self.npcs.len() - dead;
+        let allocated = slots.count();
+        let free_slots = slots.free_list().len();
+        let buildings = self.building_count();
+        NpcCounts {
+            allocated,
+            alive,
+            dead,
+            hidden: allocated.saturating_sub(alive + dead + buildings + free_slots),
+            buildings,
+            free_slots,
+        }
+    }
+
+    /// Living NPC slots whose GPU position lies inside the world-space rect spanned by
+    /// corners `a` and `b` (either order), filtered by `keep`. Sorted by slot and capped
+    /// at `limit`. Hidden slots (position < -9000) never match.
+    pub fn npcs_in_rect(
+        &self,
+        positions: &[f32],
+        a: Vec2,
+        b: Vec2,
+        limit: usize,
+        keep: impl Fn(&NpcEntry) -> bool,
+    ) -> Vec<usize> {
+        let (min, max) = (a.min(b), a.max(b));
+        let mut slots: Vec<usize> = self
+            .npcs
+            .values()
+            .filter(|n| !n.dead && keep(n))
+            .filter(|n| {
+                let i = n.slot;
+                if i * 2 + 1 >= positions.len() {
+                    return false;
+                }
+                let (px, py) = (positions[i * 2], positions[i * 2 + 1]);
+                px > -9000.0 && px >= min.x && px <= max.x && py >= min.y && py <= max.y
+            })
+            .map(|n| n.slot)
+            .collect();
+        slots.sort_unstable();
+        slots.truncate(limit);
+        slots
+    }
+
+    /// Living NPC slots of one town passing `keep`, ascending.
+    pub fn town_npc_slots_where(
+        &self,
+        town_idx: i32,
+        keep: impl Fn(&NpcEntry) -> bool,
+    ) -> Vec<usize> {
+        let mut slots: Vec<usize> = self
+            .npcs_for_town(town_idx)
+            .filter(|n| !n.dead && keep(n))
+            .map(|n| n.slot)
+            .collect();
+        slots.sort_unstable();
+        slots
+    }
+
     pub fn clear_npcs(&mut self) {
         let npc_slots: Vec<usize> = self.npcs.keys().copied().collect();
         for slot in npc_slots {
@@ -559,6 +677,7 @@ impl EntityMap {
         }
         self.npcs.clear();
         self.npc_by_town.clear();
+        self.npc_spatial_cells.iter_mut().for_each(|c| c.clear());
     }
 
     /// Check if a slot is an NPC (vs building).
@@ -573,6 +692,7 @@ impl EntityMap {
         self.spatial_width = (world_size_px / self.spatial_cell_size).ceil() as usize + 1;
         let total = self.spatial_width * self.spatial_width;
         self.spatial_cells.resize_with(total, Vec::new);
+        self.npc_spatial_cells.resize_with(total, Vec::new);
     }
 
     pub fn rebuild_spatial(&mut self) {
@@ -690,20 +810,29 @@ impl EntityMap {
         self.spatial_cell_size
     }
 
-    pub fn for_each_nearby(
-        &self,
-        pos: Vec2,
-        radius: f32,
-        mut f: impl FnMut(&BuildingInstance, i16),
-    ) {
+    /// Cell index bounds `(min_cx, max_cx, min_cy, max_cy)` covering `radius` around `pos`,
+    /// clamped to the grid. None before `init_spatial`.
+    fn cell_span(&self, pos: Vec2, radius: f32) -> Option<(usize, usize, usize, usize)> {
         if self.spatial_width == 0 {
-            return;
+            return None;
         }
         let cs = self.spatial_cell_size;
         let min_cx = ((pos.x - radius).max(0.0) / cs) as usize;
         let max_cx = (((pos.x + radius) / cs) as usize).min(self.spatial_width - 1);
         let min_cy = ((pos.y - radius).max(0.0) / cs) as usize;
         let max_cy = (((pos.y + radius) / cs) as usize).min(self.spatial_width - 1);
+        Some((min_cx, max_cx, min_cy, max_cy))
+    }
+
+    pub fn for_each_nearby(
+        &self,
+        pos: Vec2,
+        radius: f32,
+        mut f: impl FnMut(&BuildingInstance, i16),
+    ) {
+        let Some((min_cx, max_cx, min_cy, max_cy)) = self.cell_span(pos, radius) else {
+            return;
+        };
         for cy in min_cy..=max_cy {
             let row = cy * self.spatial_width;
             for cx in min_cx..=max_cx {
@@ -717,6 +846,52 @@ impl EntityMap {
         }
     }
 
+    // ── NPC spatial queries ───────────────────────────────────────────
+
+    /// Bucket living NPCs into the grid's cells by their GPU `positions` (`[x, y]` per slot).
+    /// NPCs move every frame, so callers rebuild right before a burst of
+    /// `for_each_npc_nearby` queries. Hidden slots (x < -9000) are left out.
+    pub fn rebuild_npc_spatial(&mut self, positions: &[f32]) {
+        if self.spatial_width == 0 {
+            return;
+        }
+        for cell in &mut self.npc_spatial_cells {
+            cell.clear();
+        }
+        let cs = self.spatial_cell_size;
+        for n in self.npcs.values().filter(|n| !n.dead) {
+            let Some(p) = positions.get(n.slot * 2..n.slot * 2 + 2) else {
+                continue;
+            };
+            if p[0] < -9000.0 {
+                continue;
+            }
+            let cx = (p[0] / cs) as usize;
+            let cy = (p[1] / cs) as usize;
+            if cx < self.spatial_width && cy < self.spatial_width {
+                self.npc_spatial_cells[cy * self.spatial_width + cx].push(n.slot);
+            }
+        }
+    }
+
+    /// NPCs bucketed by the last `rebuild_npc_spatial` in cells within `radius` of `pos`.
+    /// Cell-granular: callers check the exact distance against current positions.
+    pub fn for_each_npc_nearby(&self, pos: Vec2, radius: f32, mut f: impl FnMut(&NpcEntry)) {
+        let Some((min_cx, max_cx, min_cy, max_cy)) = self.cell_span(pos, radius) else {
+            return;
+        };
+        for cy in min_cy..=max_cy {
+            let row = cy * self.spatial_width;
+            for cx in min_cx..=max_cx {
+                for &slot in &self.npc_spatial_cells[row + cx] {
+                    if let Some(n) = self.npcs.get(&slot) {
+                        f(n);
+                    }
+                }
+            }
+        }
+    }
+
     // ── Kind-filtered spatial queries ─────────────────────────────────
 
     /// Convert pixel radius to cell radius from a center cell.
@@ -790,6 +965,34 @@ impl EntityMap {
         }
     }
 
+    /// True if any building of `faction` sits within `radius` of pos. Stops at the first hit,
+    /// so dense friendly territory answers after a cell or two.
+    pub fn any_faction_building_within(&self, pos: Vec2, radius: f32, faction: i32) -> bool {
+        if self.spatial_width == 0 {
+            return false;
+        }
+        let r2 = radius * radius;
+        let cs = self.spatial_cell_size;
+        let min_cx = ((pos.x - radius).max(0.0) / cs) as usize;
+        let max_cx = (((pos.x + radius) / cs) as usize).min(self.spatial_width - 1);
+        let min_cy = ((pos.y - radius).max(0.0) / cs) as usize;
+        let max_cy = (((pos.y + radius) / cs) as usize).min(self.spatial_width - 1);
+        for cy in min_cy..=max_cy {
+            let row = cy * self.spatial_width;
+            for cx in min_cx..=max_cx {
+                let hit = self.spatial_cells[row + cx].iter().any(|&slot| {
+                    self.instances.get(slot).is_some_and(|i| {
+                        i.faction == faction && i.position.distance_squared(pos) <= r2
+                    })
+                });
+                if hit {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
     /// Cell-ring query: iterate kind+town buildings only in cells between inner and outer radii.
     /// inner_cell_r=0, outer_cell_r=0 visits only the center cell.
     /// Each cell is visited exactly once across successive ring expansions.
@@ -956,8 +1159,9 @@ impl EntityMap {
         })
     }
 
-    /// Validate and claim a worksite slot. Returns None if stale/invalid.
-    /// Single authority point for all worksite claims.
+    /// Validate and claim a worksite slot (beds and posts included). Returns None if
+    /// stale/invalid, with the reason in `last_error`. Single authority point for all
+    /// worksite claims.
     pub fn try_claim_worksite(
         &mut self,
         slot: usize,
@@ -967,14 +1171,36 @@ impl EntityMap {
         claimer: Option<Entity>,
     ) -> Option<ClaimedWorksite> {
         let occ = self.occupancy.get(slot).copied().unwrap_or(0) as i32;
-        let valid = self.instances.get(slot).is_some_and(|inst| {
-            inst.kind == expected_kind
-                && expected_town.is_none_or(|t| {
+        let [missing, wrong_kind, foreign, full] =
+            if expected_kind == crate::world::BuildingKind::Bed {
+                [
+                    "bed slot has no building",
+                    "building is not a bed",
+                    "bed belongs to another town",
+                    "bed already occupied",
+                ]
+            } else {
+                [
+                    "worksite slot has no building",
+                    "worksite is a different building kind",
+                    "worksite belongs to another town",
+                    "worksite already fully occupied",
+                ]
+            };
+        self.last_error = match self.instances.get(slot) {
+            None => Some(missing),
+            Some(inst) if inst.kind != expected_kind => Some(wrong_kind),
+            Some(inst)
+                if !expected_town.is_none_or(|t| {
                     inst.town_idx == t || inst.town_idx == crate::constants::TOWN_NONE
-                })
-                && occ < max_occupants
-        });
-        if valid {
+                }) =>
+            {
+                Some(foreign)
+            }
+            Some(_) if occ >= max_occupants => Some(full),
+            Some(_) => None,
+        };
+        if self.last_error.is_none() {
             if let Some(o) = self.occupancy.get_mut(slot) {
                 *o += 1;
             }
@@ -991,6 +1217,11 @@ impl EntityMap {
         }
     }
 
+    /// Why the most recent failed `try_claim_worksite` failed. None after a success.
+    pub fn last_error(&self) -> Option<&'static str> {
+        self.last_error
+    }
+
     // ── Debug validation ──────────────────────────────────────────────
 
     /// Verify all kind-filtered spatial indexes are consistent with back-index.
@@ -1092,3 +1323,230 @@ pub struct ClaimedWorksite {
     pub slot: usize,
     pub position: Vec2,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Dead, GpuSlot, Health, Job, LastHitBy};
+    use crate::messages::{CombatLogMsg, DespawnNpcMsg, GpuUpdateMsg, ProjGpuUpdateMsg};
+    use crate::resources::{GpuSlotPool, PopulationStats};
+    use crate::systems::{
+        DeathKnockback, corpse_knockback_system, death_system, despawn_npc_system,
+    };
+    use bevy::time::TimeUpdateStrategy;
+
+    fn setup_death_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_secs_f32(0.1),
+        ));
+        app.init_resource::<crate::resources::GameTime>()
+            .init_resource::<crate::systems::DeathQueue>()
+            .init_resource::<EntityMap>()
+            .init_resource::<PopulationStats>()
+            .init_resource::<crate::resources::FactionStats>()
+            .init_resource::<crate::resources::HealthDebug>()
+            .init_resource::<crate::resources::KillStats>()
+            .init_resource::<GpuSlotPool>()
+            .init_resource::<crate::world::WorldGrid>()
+            .init_resource::<crate::world::WorldData>()
+            .init_resource::<crate::resources::SelectedBuilding>()
+            .init_resource::<crate::resources::SelectedNpc>()
+            .init_resource::<crate::systems::AiPlayerState>()
+            .init_resource::<crate::resources::EndlessMode>()
+            .init_resource::<crate::gpu::EntityGpuState>()
+            .init_resource::<crate::resources::ProjSlotAllocator>()
+            .init_resource::<crate::resources::NextLootItemId>()
+            .init_resource::<crate::resources::LootConfig>()
+            .init_resource::<DeathKnockback>()
+            .init_resource::<crate::resources::WorldBounds>()
+            .init_resource::<crate::resources::Reputation>()
+            .init_resource::<crate::resources::FactionMvp>()
+            .init_resource::<crate::resources::NpcLogCache>()
+            .init_resource::<crate::resources::PanicState>()
+            .init_resource::<crate::resources::ActiveHealingSlots>()
+            .init_resource::<crate::resources::BuildingCosts>()
+            .init_resource::<crate::resources::SquadState>()
+            .init_resource::<crate::resources::TownIndex>()
+            .init_resource::<crate::systems::stats::CombatConfig>()
+            .init_resource::<crate::resources::PathRequestQueue>()
+            .init_resource::<crate::resources::UiState>();
+        app.add_message::<DespawnNpcMsg>()
+            .add_message::<GpuUpdateMsg>()
+            .add_message::<ProjGpuUpdateMsg>()
+            .add_message::<CombatLogMsg>()
+            .add_message::<crate::messages::WorkIntentMsg>()
+            .add_message::<crate::resources::PlaySfxMsg>()
+            .add_message::<crate::messages::BuildingGridDirtyMsg>()
+            .add_message::<crate::messages::TerrainDirtyMsg>()
+            .add_message::<crate::messages::PatrolsDirtyMsg>()
+            .add_message::<crate::messages::PatrolPerimeterDirtyMsg>()
+            .add_message::<crate::messages::HealingZonesDirtyMsg>()
+            .add_message::<crate::messages::SquadsDirtyMsg>()
+            .add_message::<crate::messages::MiningDirtyMsg>()
+            .add_message::<crate::messages::PatrolSwapMsg>();
+        app.add_systems(
+            Update,
+            (death_system, corpse_knockback_system, despawn_npc_system).chain(),
+        );
+        app
+    }
+
+    #[test]
+    fn npc_counts_agree_with_population_stats() {
+        let mut app = setup_death_app();
+        let npcs = [
+            (Job::Farmer, 0),
+            (Job::Farmer, 0),
+            (Job::Archer, 0),
+            (Job::Archer, 1),
+            (Job::Miner, 1),
+        ];
+        for (job, town) in npcs {
+            let world = app.world_mut();
+            let slot = world.resource_mut::<GpuSlotPool>().alloc_reset().unwrap();
+            let entity = world.spawn((GpuSlot(slot), Health(100.0))).id();
+            world
+                .resource_mut::<EntityMap>()
+                .register_npc(slot, entity, job, 1, town);
+            crate::systems::pop_inc_alive(&mut world.resource_mut::<PopulationStats>(), job, town);
+        }
+        // Slot 3's killer (slot 0) stands left of it, so its body slides before the slot frees
+        app.world_mut()
+            .resource_mut::<crate::gpu::EntityGpuState>()
+            .positions = (0..5).flat_map(|i| [100.0 * i as f32, 500.0]).collect();
+        app.world_mut()
+            .resource_mut::<DeathKnockback>()
+            .set_death_knockback(80.0);
+
+        let counts = |app: &App| {
+            let world = app.world();
+            world
+                .resource::<EntityMap>()
+                .get_npc_counts(world.resource::<GpuSlotPool>())
+        };
+        let pop = |app: &App| {
+            let stats = app.world().resource::<PopulationStats>();
+            let alive: i32 = stats.0.values().map(|s| s.alive).sum();
+            let dead: i32 = stats.0.values().map(|s| s.dead).sum();
+            (alive as usize, dead)
+        };
+
+        // Damage tags slots 2 and 3 Dead; neither count moves until death_system runs
+        for slot in [2, 3] {
+            let world = app.world_mut();
+            let entity = world.resource::<EntityMap>().get_npc(slot).unwrap().entity;
+            world.entity_mut(entity).insert(Dead);
+            if slot == 3 {
+                world.entity_mut(entity).insert(LastHitBy(0));
+            }
+        }
+        let c = counts(&app);
+        assert_eq!((c.alive, c.dead), (5, 0));
+        assert_eq!(c.alive, pop(&app).0);
+
+        // death_system: slot 2 is freed, slot 3 keeps its slot while the corpse slides
+        app.update();
+        assert_eq!(
+            counts(&app),
+            NpcCounts {
+                allocated: 5,
+                alive: 3,
+                dead: 0,
+                hidden: 1,
+                buildings: 0,
+                free_slots: 1,
+            }
+        );
+        assert_eq!(pop(&app), (3, 2));
+
+        // The corpse lands and its slot is freed
+        for _ in 0..10 {
+            app.update();
+        }
+        let c = counts(&app);
+        assert_eq!((c.alive, c.hidden, c.free_slots), (3, 0, 2));
+        assert_eq!(c.alive, pop(&app).0);
+
+        // Scripted despawn is not a death: alive drops, dead stays put
+        app.world_mut().write_message(DespawnNpcMsg { slot: 4 });
+        app.update();
+        let c = counts(&app);
+        assert_eq!((c.alive, c.dead, c.free_slots), (2, 0, 3));
+        assert_eq!(pop(&app), (2, 2));
+    }
+
+    #[test]
+    fn npcs_in_rect_normalizes_filters_and_caps() {
+        use crate::constants::FACTION_PLAYER;
+        let mut world = World::new();
+        let mut map = EntityMap::default();
+        let npcs = [
+            (Job::Archer, FACTION_PLAYER),
+            (Job::Farmer, FACTION_PLAYER),
+            (Job::Archer, 2),
+            (Job::Archer, FACTION_PLAYER),
+            (Job::Archer, FACTION_PLAYER),
+        ];
+        for (slot, (job, faction)) in npcs.into_iter().enumerate() {
+            let entity = world.spawn(GpuSlot(slot)).id();
+            map.register_npc(slot, entity, job, faction, 0);
+        }
+        map.get_npc_mut(3).unwrap().dead = true;
+        let positions = [
+            10.0, 10.0, // player archer
+            20.0, 20.0, // player farmer
+            30.0, 30.0, // enemy archer
+            15.0, 15.0, // dead player archer
+            -10000.0, -10000.0, // hidden
+        ];
+        let player = |n: &NpcEntry| n.faction == FACTION_PLAYER;
+
+        // Reversed corners select the same rect
+        let a = map.npcs_in_rect(&positions, Vec2::splat(40.0), Vec2::ZERO, 10, player);
+        let b = map.npcs_in_rect(&positions, Vec2::ZERO, Vec2::splat(40.0), 10, player);
+        assert_eq!(a, vec![0, 1]);
+        assert_eq!(a, b);
+        assert_eq!(
+            map.npcs_in_rect(&positions, Vec2::ZERO, Vec2::splat(40.0), 10, |_| true),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            map.npcs_in_rect(&positions, Vec2::ZERO, Vec2::splat(40.0), 1, |_| true),
+            vec![0]
+        );
+        assert!(
+            map.npcs_in_rect(&positions, Vec2::splat(-1e5), Vec2::ZERO, 10, |_| true)
+                .is_empty(),
+            "hidden slots never match"
+        );
+    }
+
+    #[test]
+    fn npc_spatial_grid_finds_neighbours_across_cells() {
+        let mut world = World::new();
+        let mut map = EntityMap::default();
+        map.init_spatial(1024.0);
+        for slot in 0..4 {
+            let entity = world.spawn(GpuSlot(slot)).id();
+            map.register_npc(slot, entity, Job::Archer, 1, 0);
+        }
+        map.get_npc_mut(2).unwrap().dead = true;
+        let positions = [
+            250.0, 10.0, // cell (0, 0)
+            262.0, 10.0, // cell (1, 0), across the border
+            255.0, 10.0, // dead
+            900.0, 900.0, // far cell
+        ];
+        let nearby = |map: &EntityMap| {
+            let mut slots = Vec::new();
+            map.for_each_npc_nearby(Vec2::new(256.0, 10.0), 20.0, |n| slots.push(n.slot));
+            slots.sort_unstable();
+            slots
+        };
+        assert!(nearby(&map).is_empty(), "empty until rebuilt");
+        map.rebuild_npc_spatial(&positions);
+        assert_eq!(nearby(&map), vec![0, 1]);
+    }
+}
@@ -34,9 +34,10 @@ use std::borrow::Cow;
 use crate::components::{Building, Dead, Faction, GpuSlot, Job};
 use crate::constants::{
     FOOD_SPRITE, GOLD_SPRITE, MAX_ENTITIES, MAX_NPC_COUNT, MAX_PROJECTILES as MAX_PROJECTILE_COUNT,
-    PROJECTILE_HIT_HALF_LENGTH, PROJECTILE_HIT_HALF_WIDTH,
+    OFFICER_INSIGNIA_SPRITE, PROJECTILE_HIT_HALF_LENGTH, PROJECTILE_HIT_HALF_WIDTH,
+    SPAWN_FADE_SECS,
 };
-use crate::messages::{GpuUpdate, GpuUpdateMsg, ProjGpuUpdate, ProjGpuUpdateMsg};
+use crate::messages::{GpuUpdate, GpuUpdateMsg, ProjGpuUpdate, ProjGpuUpdateMsg, ProjKind};
 use crate::resources::{
     GameTime, GpuReadState, GpuSlotPool, NpcTargetThrashDebug, ProjHitState, ProjPositionState,
 };
@@ -79,9 +80,13 @@ const SHADER_ASSET_PATH: &str = "shaders/npc_compute.wgsl";
 const PROJ_SHADER_ASSET_PATH: &str = "shaders/projectile_compute.wgsl";
 const WORKGROUP_SIZE: u32 = 64;
 /// 256×256 cells × 128px = 32,768px — covers max 1000×1000 world (32,000px).
-const GRID_WIDTH: u32 = 256;
-const GRID_HEIGHT: u32 = 256;
-const MAX_PER_CELL: u32 = 48;
+pub(crate) const GRID_WIDTH: u32 = 256;
+pub(crate) const GRID_HEIGHT: u32 = 256;
+pub(crate) const GRID_CELL_SIZE: f32 = 128.0;
+pub(crate) const MAX_PER_CELL: u32 = 48;
+/// Cap on cells × max_per_cell for a configured grid (128 MiB of i32 — the default storage
+/// binding limit).
+pub(crate) const MAX_GRID_ENTRIES: u64 = 32 * 1024 * 1024;
 
 // =============================================================================
 // RESOURCES (Main World)
@@ -108,18 +113,122 @@ pub struct EntityGpuData {
     pub tile_grid_height: u32,
     pub tile_cell_size: f32,
     pub entity_count: u32,
+    /// World bounds for position clamping (max <= min = unbounded). See `WorldBounds`.
+    pub bounds_min_x: f32,
+    pub bounds_min_y: f32,
+    pub bounds_max_x: f32,
+    pub bounds_max_y: f32,
+    /// Scripted mode only: 1 = clamp integrated positions to the world bounds.
+    pub scripted_clamp: u32,
+    /// 0 = grid neighbor scan, 1 = brute-force scan. See `SeparationMode`.
+    pub separation_mode: u32,
+    /// Multiplier on the separation push only (0 = off); dodge and anchored push keep
+    /// `separation_strength`. See `SeparationState::step`.
+    pub separation_scale: f32,
+}
+
+/// Compute pass selector value for the scripted-motion pass (modes 0-2 are the normal passes).
+pub const MODE_SCRIPTED: u32 = 3;
+
+/// Spatial grid shape shared by the NPC and projectile compute passes (the projectile pass
+/// reads the NPC grid and vice versa, so one shape serves both). Copied into the compute
+/// uniforms every frame; a change makes the render world reallocate both grids' buffers.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct GridConfig {
+    pub width: u32,
+    pub height: u32,
+    pub cell_size: f32,
+    /// Entities indexed per cell; extras are invisible to neighbour queries.
+    pub max_per_cell: u32,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            width: GRID_WIDTH,
+            height: GRID_HEIGHT,
+            cell_size: GRID_CELL_SIZE,
+            max_per_cell: MAX_PER_CELL,
+        }
+    }
+}
+
+impl GridConfig {
+    pub fn cells(&self) -> u32 {
+        self.width * self.height
+    }
+
+    /// Covered area in px from the origin.
+    pub fn extent(&self) -> Vec2 {
+        Vec2::new(
+            self.width as f32 * self.cell_size,
+            self.height as f32 * self.cell_size,
+        )
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 || self.max_per_cell == 0 {
+            return Err("width, height and max_per_cell must be positive".into());
+        }
+        if !self.cell_size.is_finite() || self.cell_size < 1.0 {
+            return Err("cell_size must be at least 1".into());
+        }
+        let entries = self.width as u64 * self.height as u64 * self.max_per_cell as u64;
+        if entries > MAX_GRID_ENTRIES {
+            return Err(format!(
+                "grid too large: {entries} entries (max {MAX_GRID_ENTRIES})"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Warning when part of the world lies outside the grid (those NPCs get clamped into the
+    /// edge cells and crowd them, so neighbour queries miss entities).
+    pub fn coverage_warning(&self, bounds: &crate::resources::WorldBounds) -> Option<String> {
+        let extent = self.extent();
+        (bounds.is_set() && (extent.x < bounds.max.x || extent.y < bounds.max.y)).then(|| {
+            format!(
+                "grid covers {:.0}x{:.0}px but the world is {:.0}x{:.0}px",
+                extent.x, extent.y, bounds.max.x, bounds.max.y
+            )
+        })
+    }
+}
+
+/// Validate and apply a new spatial grid shape. Returns a coverage warning when the grid no
+/// longer spans the world; the config is applied either way.
+pub fn set_grid_config(
+    world: &mut World,
+    width: u32,
+    height: u32,
+    cell_size: f32,
+    max_per_cell: u32,
+) -> Result<Option<String>, String> {
+    let grid = GridConfig {
+        width,
+        height,
+        cell_size,
+        max_per_cell,
+    };
+    grid.validate()?;
+    let warning = grid.coverage_warning(world.resource::<crate::resources::WorldBounds>());
+    if let Some(w) = &warning {
+        warn!("Spatial grid: {w}");
+    }
+    world.resource_mut::<GridConfig>().set_if_neq(grid);
+    Ok(warning)
 }
 
 impl Default for EntityGpuData {
     fn default() -> Self {
         Self {
             count: 0,
-            separation_radius: 40.0,
-            separation_strength: 200.0,
+            separation_radius: crate::constants::SEPARATION_RADIUS,
+            separation_strength: crate::constants::SEPARATION_STRENGTH,
             delta: 0.016,
             grid_width: GRID_WIDTH,
             grid_height: GRID_HEIGHT,
-            cell_size: 128.0,
+            cell_size: GRID_CELL_SIZE,
             max_per_cell: MAX_PER_CELL,
             arrival_threshold: crate::constants::ARRIVAL_THRESHOLD,
             mode: 0,
@@ -131,6 +240,13 @@ impl Default for EntityGpuData {
             tile_grid_height: 0,
             tile_cell_size: 64.0,
             entity_count: 0,
+            bounds_min_x: 0.0,
+            bounds_min_y: 0.0,
+            bounds_max_x: 0.0,
+            bounds_max_y: 0.0,
+            scripted_clamp: 1,
+            separation_mode: 0,
+            separation_scale: 1.0,
         }
     }
 }
@@ -144,6 +260,10 @@ pub struct RenderFrameConfig {
     pub textures: NpcSpriteTexture,
     pub readback: ReadbackHandles,
     pub tile_flags: Vec<u32>,
+    /// Which compute passes run this frame. See `ScriptedMotion`.
+    pub compute_mode: crate::resources::ComputeMode,
+    /// `Cpu` skips the NPC compute node (`cpu_compute_system` steps the sim instead).
+    pub compute_backend: crate::resources::ComputeBackend,
 }
 
 /// All persistent per-entity GPU data: compute fields + visual state + dirty tracking.
@@ -167,11 +287,19 @@ pub struct EntityGpuState {
     pub max_healths: Vec<f32>,
     /// Arrival flags: one i32 per NPC (0 = moving, 1 = settled)
     pub arrivals: Vec<i32>,
+    /// Scripted velocity buffer: [vx0, vy0, vx1, vy1, ...] px/s. Read only by the scripted pass.
+    pub velocities: Vec<f32>,
     // --- Visual state (sprite frames + flash, updated by messages) ---
     /// Sprite indices: [col, row, atlas, 0] per NPC, stride 4
     pub sprite_indices: Vec<f32>,
     /// Damage flash intensity: 0.0-1.0 per NPC (decays at 5.0/s)
     pub flash_values: Vec<f32>,
+    /// Selection/hover highlight strength: 0.0-1.0 per NPC (set by `npc_highlight_system`)
+    pub highlights: Vec<f32>,
+    /// Spawn fade-in alpha: 0.0 (just spawned) ramps to 1.0 (opaque) over `spawn_fade_secs`
+    pub fade_values: Vec<f32>,
+    /// Spawn fade-in duration in seconds (0 = instant). See `set_spawn_fade_duration`.
+    pub spawn_fade_secs: f32,
     // --- Flags (bit 0: combat scan enabled) ---
     pub entity_flags: Vec<u32>,
     /// Hitbox half-sizes: [half_w, half_h] per entity (interleaved, stride 2)
@@ -195,10 +323,14 @@ pub struct EntityGpuState {
     pub health_dirty_indices: Vec<usize>,
     pub flags_dirty_indices: Vec<usize>,
     pub half_size_dirty_indices: Vec<usize>,
+    pub velocity_dirty_indices: Vec<usize>,
     /// Slots hidden this frame — used by build_visual_upload to clear stale visual/equip data.
     pub hidden_indices: Vec<usize>,
     /// Last-known target buffer size for full-upload fallback detection.
     pub target_buffer_size: usize,
+    /// Upload every compute buffer in bulk at the next extract, ignoring the dirty lists.
+    /// Set after a headless fast-forward stepped this copy without any extracts.
+    pub full_upload: bool,
     // --- Visual dirty tracking (event-driven visual upload) ---
     /// Slots whose visual/equip data changed this frame (sprite, activity, equipment changes)
     pub visual_dirty_indices: Vec<usize>,
@@ -212,7 +344,7 @@ pub struct EntityGpuState {
 /// Read via `Extract<Res<NpcVisualUpload>>` in Extract phase (zero clone).
 #[derive(Resource, Default)]
 pub struct NpcVisualUpload {
-    /// [sprite_col, sprite_row, atlas, flash, r, g, b, a] per NPC — matches NpcVisual in npc_render.wgsl
+    /// [sprite_col, sprite_row, atlas, flash, r, g, b, highlight] per NPC — matches NpcVisual in npc_render.wgsl
     pub visual_data: Vec<f32>,
     /// [col, row, atlas, pad] × 6 layers per NPC — matches EquipSlot in npc_render.wgsl
     pub equip_data: Vec<f32>,
@@ -237,8 +369,12 @@ impl Default for EntityGpuState {
             healths: vec![0.0; max],
             max_healths: vec![100.0; max],
             arrivals: vec![0; max],
+            velocities: vec![0.0; max * 2],
             sprite_indices: vec![0.0; max * 4],
             flash_values: vec![0.0; max],
+            highlights: vec![0.0; max],
+            fade_values: vec![1.0; max],
+            spawn_fade_secs: SPAWN_FADE_SECS,
             entity_flags: vec![0; max],
             half_sizes: vec![0.0; max * 2],
             dirty_targets: false,
@@ -250,8 +386,10 @@ impl Default for EntityGpuState {
             health_dirty_indices: Vec::new(),
             flags_dirty_indices: Vec::new(),
             half_size_dirty_indices: Vec::new(),
+            velocity_dirty_indices: Vec::new(),
             hidden_indices: Vec::new(),
             target_buffer_size: 0,
+            full_upload: false,
             visual_dirty_indices: Vec::new(),
             flash_only_indices: Vec::new(),
             visual_full_rebuild: true,
@@ -260,6 +398,62 @@ impl Default for EntityGpuState {
 }
 
 impl EntityGpuState {
+    /// Set how long newly spawned NPCs take to fade in. 0 = appear instantly.
+    /// Slots already mid-fade keep ramping at the new rate.
+    pub fn set_spawn_fade_duration(&mut self, secs: f32) {
+        self.spawn_fade_secs = if secs.is_finite() { secs.max(0.0) } else { 0.0 };
+    }
+
+    /// Alpha to render a slot with — 1.0 unless it is still fading in.
+    pub fn fade_alpha(&self, idx: usize) -> f32 {
+        self.fade_values.get(idx).copied().unwrap_or(1.0)
+    }
+
+    /// Packed flash float for the visual buffer. A positive value is the damage flash;
+    /// a negative value means "fading in" with alpha = 1 + value. Damage flash wins while
+    /// both are active — it only lasts ~0.2s and a hit NPC should read as hit.
+    pub fn packed_flash(&self, idx: usize) -> f32 {
+        let flash = self.flash_values.get(idx).copied().unwrap_or(0.0);
+        let fade = self.fade_alpha(idx);
+        if flash <= 0.0 && fade < 1.0 {
+            fade - 1.0
+        } else {
+            flash
+        }
+    }
+
+    /// Advance damage flash decay and spawn fade-in for the first `active` slots, collecting
+    /// every slot that changed into `flash_only_indices`.
+    pub fn tick_flash_and_fade(&mut self, dt: f32, active: usize) {
+        const FLASH_DECAY_RATE: f32 = 5.0;
+        let active = active
+            .min(self.flash_values.len())
+            .min(self.fade_values.len());
+        let fade_step = if self.spawn_fade_secs > 0.0 {
+            dt / self.spawn_fade_secs
+        } else {
+            1.0
+        };
+        self.flash_only_indices.clear();
+        let slots = self.flash_values[..active]
+            .iter_mut()
+            .zip(self.fade_values[..active].iter_mut());
+        for (slot_idx, (flash, fade)) in slots.enumerate() {
+            let mut changed = false;
+            if *flash > 0.0 {
+                *flash = (*flash - dt * FLASH_DECAY_RATE).max(0.0);
+                changed = true;
+            }
+            if *fade < 1.0 {
+                *fade = (*fade + fade_step).min(1.0);
+                changed = true;
+            }
+            if changed {
+                self.flash_only_indices.push(slot_idx);
+            }
+        }
+    }
+
     /// Apply a GPU update to the state.
     pub fn apply(&mut self, update: &GpuUpdate) {
         match update {
@@ -285,6 +479,14 @@ impl EntityGpuState {
                     self.arrival_dirty_indices.push(*idx);
                 }
             }
+            GpuUpdate::SetVelocity { idx, vx, vy } => {
+                let i = *idx * 2;
+                if i + 1 < self.velocities.len() {
+                    self.velocities[i] = *vx;
+                    self.velocities[i + 1] = *vy;
+                    self.velocity_dirty_indices.push(*idx);
+                }
+            }
             GpuUpdate::SetSpeed { idx, speed } => {
                 if *idx < self.speeds.len() {
                     self.speeds[*idx] = *speed;
@@ -344,6 +546,22 @@ impl EntityGpuState {
                 if *idx < self.flash_values.len() {
                     self.flash_values[*idx] = 0.0;
                 }
+                if *idx < self.highlights.len() {
+                    self.highlights[*idx] = 0.0;
+                }
+                // Dying mid-fade skips the rest of the ramp
+                if *idx < self.fade_values.len() {
+                    self.fade_values[*idx] = 1.0;
+                }
+                if i + 1 < self.velocities.len() {
+                    self.velocities[i] = 0.0;
+                    self.velocities[i + 1] = 0.0;
+                    self.velocity_dirty_indices.push(*idx);
+                }
+                if *idx < self.entity_flags.len() {
+                    self.entity_flags[*idx] |= crate::constants::ENTITY_FLAG_INACTIVE;
+                    self.flags_dirty_indices.push(*idx);
+                }
                 self.hidden_indices.push(*idx);
                 self.visual_dirty_indices.push(*idx);
             }
@@ -368,6 +586,18 @@ impl EntityGpuState {
                     self.visual_dirty_indices.push(*idx);
                 }
             }
+            GpuUpdate::StartSpawnFade { idx } => {
+                if *idx < self.fade_values.len() {
+                    self.fade_values[*idx] = if self.spawn_fade_secs > 0.0 { 0.0 } else { 1.0 };
+                    self.visual_dirty_indices.push(*idx);
+                }
+            }
+            GpuUpdate::SetHighlight { idx, strength } => {
+                if *idx < self.highlights.len() {
+                    self.highlights[*idx] = *strength;
+                    self.visual_dirty_indices.push(*idx);
+                }
+            }
             GpuUpdate::MarkVisualDirty { idx } => {
                 self.visual_dirty_indices.push(*idx);
             }
@@ -408,13 +638,14 @@ fn write_npc_visual(
     npc_flags_q: &Query<&crate::components::NpcFlags>,
     equipment_q: &Query<(&crate::components::NpcEquipment, &crate::components::Job)>,
     carried_loot_q: &Query<&crate::components::CarriedLoot>,
+    officer_q: &Query<(), With<crate::components::Officer>>,
 ) {
     let base = idx * 8;
     if base + 7 >= upload.visual_data.len() {
         return;
     }
 
-    // Visual data: [sprite_col, sprite_row, atlas, flash, r, g, b, a]
+    // Visual data: [sprite_col, sprite_row, atlas, flash, r, g, b, highlight]
     upload.visual_data[base] = gpu_state
         .sprite_indices
         .get(idx * 4)
@@ -430,8 +661,8 @@ fn write_npc_visual(
         .get(idx * 4 + 2)
         .copied()
         .unwrap_or(0.0);
-    upload.visual_data[base + 3] = gpu_state.flash_values.get(idx).copied().unwrap_or(0.0);
-    let (r, g, b, a) = if faction == crate::constants::FACTION_PLAYER {
+    upload.visual_data[base + 3] = gpu_state.packed_flash(idx);
+    let (r, g, b, _) = if faction == crate::constants::FACTION_PLAYER {
         job.color()
     } else {
         crate::constants::raider_faction_color(faction)
@@ -439,7 +670,7 @@ fn write_npc_visual(
     upload.visual_data[base + 4] = r;
     upload.visual_data[base + 5] = g;
     upload.visual_data[base + 6] = b;
-    upload.visual_data[base + 7] = a;
+    upload.visual_data[base + 7] = gpu_state.highlights.get(idx).copied().unwrap_or(0.0);
 
     // Equip data: 7 layers × [col, row, atlas, pad]
     let eq = idx * 28;
@@ -493,9 +724,11 @@ fn write_npc_visual(
     upload.equip_data[eq + 18] = ia;
     upload.equip_data[eq + 19] = 0.0;
 
-    // Layer 5: Status (sleep icon)
+    // Layer 5: Status (sleep icon, else officer insignia)
     let (sc, sr, sa) = if npc_activity.is_some_and(|a| a.visual_key() == 1) {
         (0.0, 0.0, 3.0)
+    } else if officer_q.contains(entity) {
+        (OFFICER_INSIGNIA_SPRITE.0, OFFICER_INSIGNIA_SPRITE.1, 1.0)
     } else {
         (-1.0, 0.0, 0.0)
     };
@@ -514,8 +747,14 @@ fn write_npc_visual(
 }
 
 /// Write building visual data for a single slot into upload buffers.
+/// Equip layer 0 carries the footprint `(w, h)` so npc_render.wgsl sizes the quad to it.
 #[inline]
-fn write_building_visual(idx: usize, gpu_state: &EntityGpuState, upload: &mut NpcVisualUpload) {
+fn write_building_visual(
+    idx: usize,
+    kind: crate::world::BuildingKind,
+    gpu_state: &EntityGpuState,
+    upload: &mut NpcVisualUpload,
+) {
     let base = idx * 8;
     if base + 7 >= upload.visual_data.len() {
         return;
@@ -532,11 +771,16 @@ fn write_building_visual(idx: usize, gpu_state: &EntityGpuState, upload: &mut Np
     upload.visual_data[base + 4] = 1.0; // r (white tint)
     upload.visual_data[base + 5] = 1.0; // g
     upload.visual_data[base + 6] = 1.0; // b
-    upload.visual_data[base + 7] = 1.0; // a
+    upload.visual_data[base + 7] = 0.0; // no highlight
     // Wipe stale NPC equip overlays on building slots
     let eq = idx * 28;
     if eq + 27 < upload.equip_data.len() {
         upload.equip_data[eq..eq + 28].fill(-1.0);
+        // Layer 0: footprint in cells, tagged with the building atlas so no overlay pass draws it
+        let (fw, fh) = crate::constants::building_def(kind).footprint;
+        upload.equip_data[eq] = fw.max(1) as f32;
+        upload.equip_data[eq + 1] = fh.max(1) as f32;
+        upload.equip_data[eq + 2] = crate::constants::ATLAS_BUILDING;
     }
 }
 
@@ -565,8 +809,9 @@ pub fn build_visual_upload(
     npc_flags_q: Query<&crate::components::NpcFlags>,
     equipment_q: Query<(&crate::components::NpcEquipment, &crate::components::Job)>,
     carried_loot_q: Query<&crate::components::CarriedLoot>,
+    officer_q: Query<(), With<crate::components::Officer>>,
     npc_q: Query<(Entity, &GpuSlot, &Job, &Faction), (Without<Building>, Without<Dead>)>,
-    building_q: Query<&GpuSlot, (With<Building>, Without<Dead>)>,
+    building_q: Query<(&GpuSlot, &Building), Without<Dead>>,
 ) {
     // Read live count from authoritative source — not the stale RenderFrameConfig copy
     let entity_count = slots.count();
@@ -595,10 +840,11 @@ pub fn build_visual_upload(
                 &npc_flags_q,
                 &equipment_q,
                 &carried_loot_q,
+                &officer_q,
             );
         }
-        for es in building_q.iter() {
-            write_building_visual(es.0, &gpu_state, &mut upload);
+        for (es, building) in building_q.iter() {
+            write_building_visual(es.0, building.kind, &gpu_state, &mut upload);
         }
         gpu_state.visual_full_rebuild = false;
         upload.visual_full_upload = true;
@@ -626,9 +872,10 @@ pub fn build_visual_upload(
                     &npc_flags_q,
                     &equipment_q,
                     &carried_loot_q,
+                    &officer_q,
                 );
-            } else if entity_map.get_instance(idx).is_some() {
-                write_building_visual(idx, &gpu_state, &mut upload);
+            } else if let Some(inst) = entity_map.get_instance(idx) {
+                write_building_visual(idx, inst.kind, &gpu_state, &mut upload);
             } else {
                 clear_visual_slot(idx, &mut upload);
             }
@@ -636,7 +883,7 @@ pub fn build_visual_upload(
         upload.visual_full_upload = false;
 
         // Flash-only slots: update just the flash float in visual_data, skip equip entirely.
-        // These are slots whose flash is decaying but nothing else changed (no sprite/activity/equipment change).
+        // These are slots whose flash is decaying (or spawn fade ramping) but nothing else changed (no sprite/activity/equipment change).
         // Use binary_search on the sorted visual_dirty_indices to skip slots already handled above.
         for &idx in &gpu_state.flash_only_indices {
             if gpu_state.visual_dirty_indices.binary_search(&idx).is_ok() {
@@ -644,8 +891,7 @@ pub fn build_visual_upload(
             }
             let base = idx * 8;
             if base + 3 < upload.visual_data.len() {
-                upload.visual_data[base + 3] =
-                    gpu_state.flash_values.get(idx).copied().unwrap_or(0.0);
+                upload.visual_data[base + 3] = gpu_state.packed_flash(idx);
             }
         }
 
@@ -690,9 +936,34 @@ pub fn populate_gpu_state(
     real_time: Res<Time<Real>>,
     time: Res<Time>,
     mut slots: ResMut<GpuSlotPool>,
+    bounds: Res<crate::resources::WorldBounds>,
 ) {
     let sink_window_key = real_time.elapsed_secs_f64().floor() as i64;
+    apply_gpu_frame(
+        &mut npc_state,
+        &mut target_thrash,
+        &mut slots,
+        &bounds,
+        events.read(),
+        sink_window_key,
+        time.delta_secs(),
+    );
+}
+
+/// One frame of `populate_gpu_state`: reset dirty tracking, apply slot frees/resets and
+/// `updates`, then advance flash/fade by `dt`. Fast-forward calls it per tick with its own
+/// message cursor.
+pub(crate) fn apply_gpu_frame<'a>(
+    npc_state: &mut EntityGpuState,
+    target_thrash: &mut NpcTargetThrashDebug,
+    slots: &mut GpuSlotPool,
+    bounds: &crate::resources::WorldBounds,
+    updates: impl Iterator<Item = &'a GpuUpdateMsg>,
+    sink_window_key: i64,
+    dt: f32,
+) {
     // Reset dirty flags and per-index dirty tracking
+    npc_state.full_upload = false;
     npc_state.dirty_targets = false;
     npc_state.position_dirty_indices.clear();
     npc_state.arrival_dirty_indices.clear();
@@ -702,6 +973,7 @@ pub fn populate_gpu_state(
     npc_state.health_dirty_indices.clear();
     npc_state.flags_dirty_indices.clear();
     npc_state.half_size_dirty_indices.clear();
+    npc_state.velocity_dirty_indices.clear();
     npc_state.hidden_indices.clear();
 
     // Hide freed slots (deallocation cleanup — position=-9999, health=0, speed=0, flags=0)
@@ -722,7 +994,7 @@ pub fn populate_gpu_state(
             npc_state.speed_dirty_indices.push(slot);
         }
         if slot < npc_state.entity_flags.len() {
-            npc_state.entity_flags[slot] = 0;
+            npc_state.entity_flags[slot] = crate::constants::ENTITY_FLAG_INACTIVE;
             npc_state.flags_dirty_indices.push(slot);
         }
     }
@@ -769,31 +1041,45 @@ pub fn populate_gpu_state(
         if slot < npc_state.flash_values.len() {
             npc_state.flash_values[slot] = 0.0;
         }
+        if slot < npc_state.highlights.len() {
+            npc_state.highlights[slot] = 0.0;
+        }
+        if slot < npc_state.fade_values.len() {
+            npc_state.fade_values[slot] = 1.0;
+        }
+        if pi + 1 < npc_state.velocities.len() {
+            npc_state.velocities[pi] = 0.0;
+            npc_state.velocities[pi + 1] = 0.0;
+            npc_state.velocity_dirty_indices.push(slot);
+        }
     }
 
-    for msg in events.read() {
+    for msg in updates {
         let update = &msg.0;
         if let GpuUpdate::SetTarget { idx, x, y } = update {
             target_thrash.record_sink(*idx, sink_window_key, *x, *y);
+            // Keep movement goals inside the world (inactive slots keep their sentinel)
+            let inactive = npc_state
+                .entity_flags
+                .get(*idx)
+                .is_some_and(|f| f & crate::constants::ENTITY_FLAG_INACTIVE != 0);
+            let goal = Vec2::new(*x, *y);
+            let clamped = bounds.clamp(goal);
+            if !inactive && clamped != goal {
+                npc_state.apply(&GpuUpdate::SetTarget {
+                    idx: *idx,
+                    x: clamped.x,
+                    y: clamped.y,
+                });
+                continue;
+            }
         }
         npc_state.apply(update);
     }
 
-    // Decay damage flash values (1.0 → 0.0 in ~0.2s)
+    // Decay damage flash values (1.0 → 0.0 in ~0.2s) and ramp spawn fade-in (0.0 → 1.0)
     // Flash-only slots go to flash_only_indices (visual update but no equip upload needed).
-    let dt = time.delta_secs();
-    const FLASH_DECAY_RATE: f32 = 5.0;
-    let active = slots.count().min(npc_state.flash_values.len());
-    {
-        let state = &mut *npc_state;
-        state.flash_only_indices.clear();
-        for (slot_idx, flash) in state.flash_values[..active].iter_mut().enumerate() {
-            if *flash > 0.0 {
-                *flash = (*flash - dt * FLASH_DECAY_RATE).max(0.0);
-                state.flash_only_indices.push(slot_idx);
-            }
-        }
-    }
+    npc_state.tick_flash_and_fade(dt, slots.count());
 
     // Pre-sort+dedup dirty index Vecs so extract phase receives coalesce-ready data
     macro_rules! sort_dedup {
@@ -812,6 +1098,7 @@ pub fn populate_gpu_state(
     sort_dedup!(npc_state.health_dirty_indices);
     sort_dedup!(npc_state.flags_dirty_indices);
     sort_dedup!(npc_state.half_size_dirty_indices);
+    sort_dedup!(npc_state.velocity_dirty_indices);
 }
 
 // =============================================================================
@@ -844,7 +1131,7 @@ impl Default for ProjGpuData {
             hit_half_width: PROJECTILE_HIT_HALF_WIDTH,
             grid_width: GRID_WIDTH,
             grid_height: GRID_HEIGHT,
-            cell_size: 128.0,
+            cell_size: GRID_CELL_SIZE,
             max_per_cell: MAX_PER_CELL,
             mode: 0,
             entity_count: 0,
@@ -852,6 +1139,23 @@ impl Default for ProjGpuData {
     }
 }
 
+/// One live projectile as reported by `ProjBufferWrites::active_projectiles`.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct ActiveProjectile {
+    /// Pool slot index — the same `proj_idx` hit events carry.
+    pub idx: usize,
+    pub x: f32,
+    pub y: f32,
+    /// Launch velocity (px/s). Homing shots curve on the GPU without a velocity readback.
+    pub vx: f32,
+    pub vy: f32,
+    pub faction: i32,
+    pub damage: f32,
+    /// Lifetime given at spawn (seconds).
+    pub lifetime: f32,
+    pub kind: ProjKind,
+}
+
 /// Projectile buffer data to upload to GPU each frame.
 /// Read during Extract via Extract<Res<T>> (zero-clone).
 #[derive(Resource)]
@@ -863,8 +1167,13 @@ pub struct ProjBufferWrites {
     pub shooters: Vec<i32>,
     pub lifetimes: Vec<f32>,
     pub homing_targets: Vec<i32>,
+    pub arcs: Vec<f32>, // [height, vz, gravity, 0] per proj; gravity 0 = flat
     pub active: Vec<i32>,
     pub hits: Vec<i32>, // [npc_idx, processed] per proj
+    /// Render-only: visual kind per proj (never uploaded to the compute shader).
+    pub kinds: Vec<ProjKind>,
+    /// CPU-only: splash radius applied where an arced shot lands (0 = direct hit only).
+    pub splash_radii: Vec<f32>,
     pub dirty: bool,
     /// Per-slot dirty tracking: Spawn writes all fields, Deactivate writes active+hits
     pub spawn_dirty_indices: Vec<usize>,
@@ -886,8 +1195,11 @@ impl Default for ProjBufferWrites {
             shooters: vec![-1; max],
             lifetimes: vec![0.0; max],
             homing_targets: vec![-1; max],
+            arcs: vec![0.0; max * 4],
             active: vec![0; max],
             hits: vec![-1; max * 2], // -1 = no hit
+            kinds: vec![ProjKind::Arrow; max],
+            splash_radii: vec![0.0; max],
             dirty: false,
             spawn_dirty_indices: Vec::new(),
             deactivate_dirty_indices: Vec::new(),
@@ -911,6 +1223,9 @@ impl ProjBufferWrites {
                 shooter,
                 lifetime,
                 homing_target,
+                kind,
+                arc,
+                vz,
             } => {
                 let i2 = *idx * 2;
                 if i2 + 1 < self.positions.len() {
@@ -923,6 +1238,18 @@ impl ProjBufferWrites {
                     self.shooters[*idx] = *shooter;
                     self.lifetimes[*idx] = *lifetime;
                     self.homing_targets[*idx] = *homing_target;
+                    let (vz, gravity) = if arc.is_arced() {
+                        (*vz, arc.gravity)
+                    } else {
+                        (0.0, 0.0)
+                    };
+                    self.arcs[*idx * 4..*idx * 4 + 4].copy_from_slice(&[0.0, vz, gravity, 0.0]);
+                    self.kinds[*idx] = *kind;
+                    self.splash_radii[*idx] = if arc.is_arced() {
+                        arc.splash_radius
+                    } else {
+                        0.0
+                    };
                     self.active[*idx] = 1;
                     self.hits[i2] = -1;
                     self.hits[i2 + 1] = 0;
@@ -959,6 +1286,63 @@ impl ProjBufferWrites {
     }
 }
 
+impl ProjBufferWrites {
+    /// Where the arced shot in `slot` comes down: its spawn point plus ground velocity over
+    /// the flight time. None for flat shots.
+    pub fn landing(&self, slot: usize) -> Option<Vec2> {
+        let arc = self.arcs.get(slot * 4..slot * 4 + 4)?;
+        if arc[2] <= 0.0 {
+            return None;
+        }
+        let flight_time = 2.0 * arc[1] / arc[2];
+        let i2 = slot * 2;
+        Some(
+            Vec2::new(self.positions[i2], self.positions[i2 + 1])
+                + Vec2::new(self.velocities[i2], self.velocities[i2 + 1]) * flight_time,
+        )
+    }
+
+    /// Active projectiles in slot order, at most `cap`. Positions come from the GPU readback
+    /// (`live_positions`, `[x, y]` per slot) when it covers the slot, else the spawn position.
+    /// Freed slots are never returned. The second value is the total active count before the cap.
+    pub fn active_projectiles(
+        &self,
+        live_positions: &[f32],
+        cap: usize,
+    ) -> (Vec<ActiveProjectile>, usize) {
+        let mut slots: Vec<usize> = self
+            .active_set
+            .iter()
+            .copied()
+            .filter(|&i| self.active.get(i).is_some_and(|&a| a != 0))
+            .collect();
+        slots.sort_unstable();
+        let total = slots.len();
+        let list = slots
+            .into_iter()
+            .take(cap)
+            .map(|i| {
+                let i2 = i * 2;
+                let pos = live_positions
+                    .get(i2..i2 + 2)
+                    .unwrap_or(&self.positions[i2..i2 + 2]);
+                ActiveProjectile {
+                    idx: i,
+                    x: pos[0],
+                    y: pos[1],
+                    vx: self.velocities[i2],
+                    vy: self.velocities[i2 + 1],
+                    faction: self.factions[i],
+                    damage: self.damages[i],
+                    lifetime: self.lifetimes[i],
+                    kind: self.kinds[i],
+                }
+            })
+            .collect();
+        (list, total)
+    }
+}
+
 /// Apply projectile GPU updates from Bevy messages to ProjBufferWrites.
 pub fn populate_proj_buffer_writes(
     mut events: MessageReader<ProjGpuUpdateMsg>,
@@ -1094,13 +1478,30 @@ fn setup_readback_buffers(
 /// Dynamically spawn/despawn Readback entities with buffer_range sized to current counts.
 /// Quantized to power-of-2 buckets to avoid per-frame respawn churn.
 /// Factions read every 60 frames, threat_counts every 30 frames (stale-tolerant).
+/// Both intervals stretch by `QualityState::readback_mult()` under adaptive quality.
 fn sync_readback_ranges(
     mut commands: Commands,
     config: Res<RenderFrameConfig>,
     mut rb_state: ResMut<ReadbackState>,
     slots: Res<GpuSlotPool>,
     proj_alloc: Res<crate::resources::ProjSlotAllocator>,
+    quality: Res<crate::resources::QualityState>,
+    backend: Res<crate::resources::ComputeBackend>,
 ) {
+    // CPU fallback owns GpuReadState; stale GPU buffers must not overwrite it
+    if *backend == crate::resources::ComputeBackend::Cpu {
+        for entity in rb_state.always_entities.drain(..) {
+            if let Ok(mut cmds) = commands.get_entity(entity) {
+                cmds.despawn();
+            }
+        }
+        for (entity, _) in rb_state.throttled_entities.drain(..) {
+            if let Ok(mut cmds) = commands.get_entity(entity) {
+                cmds.despawn();
+            }
+        }
+        return;
+    }
     let entity_count = slots.count();
     let proj_count = proj_alloc.next;
 
@@ -1114,8 +1515,9 @@ fn sync_readback_ranges(
 
     rb_state.faction_frame_counter += 1;
     rb_state.threat_frame_counter += 1;
-    let faction_due = rb_state.faction_frame_counter >= 60;
-    let threat_due = rb_state.threat_frame_counter >= 30;
+    let rb_mult = quality.readback_mult();
+    let faction_due = rb_state.faction_frame_counter >= 60 * rb_mult;
+    let threat_due = rb_state.threat_frame_counter >= 30 * rb_mult;
 
     let sz = |count: usize, elem: usize| -> u64 { (count * elem) as u64 };
     /// Copy GPU readback bytes into an existing Vec, reusing its allocation.
@@ -1278,11 +1680,32 @@ impl Plugin for GpuComputePlugin {
             .init_resource::<NpcVisualUpload>()
             .init_resource::<ProjBufferWrites>()
             .init_resource::<ReadbackState>()
-            .add_systems(Update, (update_gpu_data, update_proj_gpu_data))
-            .add_systems(FixedUpdate, (populate_tile_flags, sync_readback_ranges))
+            .init_resource::<crate::resources::WorldBounds>()
+            .init_resource::<crate::resources::ScriptedMotion>()
+            .init_resource::<GridConfig>()
+            .init_resource::<crate::resources::ComputeBackend>()
+            .add_systems(
+                Update,
+                (
+                    crate::cpu_compute::select_compute_backend,
+                    update_gpu_data,
+                    update_proj_gpu_data,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (populate_tile_flags, sync_world_bounds, sync_readback_ranges),
+            )
             .add_systems(
                 PostUpdate,
-                (populate_gpu_state, build_visual_upload).chain(),
+                (
+                    populate_gpu_state,
+                    crate::cpu_compute::cpu_compute_system
+                        .run_if(resource_equals(crate::resources::ComputeBackend::Cpu)),
+                    build_visual_upload,
+                )
+                    .chain(),
             )
             .add_systems(PostUpdate, populate_proj_buffer_writes);
 
@@ -1298,6 +1721,8 @@ impl Plugin for GpuComputePlugin {
             Some(ra) => ra,
             None => {
                 warn!("RenderApp not available - GPU compute disabled");
+                crate::messages::GPU_COMPUTE_FAILED
+                    .store(true, std::sync::atomic::Ordering::Relaxed);
                 return;
             }
         };
@@ -1311,6 +1736,10 @@ impl Plugin for GpuComputePlugin {
                     init_proj_compute_pipeline,
                 ),
             )
+            .add_systems(
+                Render,
+                resize_grid_buffers.in_set(RenderSystems::PrepareResources),
+            )
             .add_systems(
                 Render,
                 (prepare_npc_bind_groups, prepare_proj_bind_groups)
@@ -1344,9 +1773,35 @@ fn update_gpu_data(
     dt: Res<crate::resources::DeltaTime>,
     town_access: crate::systemparams::TownAccess,
     world_data: Res<WorldData>,
+    bounds: Res<crate::resources::WorldBounds>,
+    balance: Res<crate::systems::balance::BalanceConfig>,
+    scripted: Res<crate::resources::ScriptedMotion>,
+    grid: Res<GridConfig>,
+    quality: Res<crate::resources::QualityState>,
+    mut separation: ResMut<crate::resources::SeparationState>,
+    backend: Res<crate::resources::ComputeBackend>,
 ) {
     config.npc.count = slots.count() as u32;
+    config.compute_backend = *backend;
+    config.npc.grid_width = grid.width;
+    config.npc.grid_height = grid.height;
+    config.npc.cell_size = grid.cell_size;
+    config.npc.max_per_cell = grid.max_per_cell;
+    config.npc.proj_max_per_cell = grid.max_per_cell;
+    config.compute_mode = scripted.mode;
+    config.npc.scripted_clamp = scripted.clamp_bounds as u32;
+    config.npc.separation_radius = balance.separation_radius;
+    config.npc.separation_strength = balance.separation_strength;
+    config.npc.separation_scale = separation.step(quality.separation_allowed());
+    config.npc.bounds_min_x = bounds.min.x;
+    config.npc.bounds_min_y = bounds.min.y;
+    config.npc.bounds_max_x = bounds.max.x;
+    config.npc.bounds_max_y = bounds.max.y;
     config.npc.entity_count = slots.count() as u32;
+    config.npc.separation_mode = match separation.effective_mode(slots.count()) {
+        crate::resources::SeparationMode::Grid => 0,
+        crate::resources::SeparationMode::BruteForce => 1,
+    };
     config.npc.delta = dt.0;
 
     let player_town_idx = world_data
@@ -1358,6 +1813,21 @@ fn update_gpu_data(
     config.npc.dodge_unlocked = if stats::dodge_unlocked(&levels) { 1 } else { 0 };
 }
 
+/// Derive WorldBounds from the world grid (worldgen and load resize it). Cheap compare per tick.
+pub fn sync_world_bounds(
+    grid: Res<crate::world::WorldGrid>,
+    spatial: Res<GridConfig>,
+    mut bounds: ResMut<crate::resources::WorldBounds>,
+) {
+    let derived = crate::resources::WorldBounds::from_grid(grid.width, grid.height, grid.cell_size);
+    if *bounds != derived {
+        *bounds = derived;
+        if let Some(w) = spatial.coverage_warning(&bounds) {
+            warn!("Spatial grid: {w}");
+        }
+    }
+}
+
 /// Populate tile_flags vec from WorldGrid for GPU upload.
 /// Only rebuilds when buildings have changed.
 fn populate_tile_flags(
@@ -1433,6 +1903,8 @@ pub struct EntityGpuBuffers {
     pub speeds: Buffer,
     pub grid_counts: Buffer,
     pub grid_data: Buffer,
+    /// (cells, max_per_cell) the grid buffers were sized for.
+    pub grid_shape: (u32, u32),
     pub arrivals: Buffer,
     pub backoff: Buffer,
     pub factions: Buffer,
@@ -1443,6 +1915,8 @@ pub struct EntityGpuBuffers {
     pub tile_flags: Buffer,
     /// Per-entity hitbox half-sizes [half_w, half_h] for projectile collision.
     pub half_sizes: Buffer,
+    /// Scripted velocities [vx, vy] per entity (scripted pass only).
+    pub velocities: Buffer,
 }
 
 /// Bind groups for compute passes (one per mode, different uniform buffer).
@@ -1451,6 +1925,7 @@ struct NpcBindGroups {
     mode0: BindGroup, // Clear grid
     mode1: BindGroup, // Build grid
     mode2: BindGroup, // Movement + targeting
+    mode3: BindGroup, // Scripted motion
 }
 
 /// Pipeline resources for compute.
@@ -1460,14 +1935,120 @@ struct NpcComputePipeline {
     pipeline_id: CachedComputePipelineId,
 }
 
+/// Uniform-grid sprite sheet layout in texels (cell = sprite + margin). Uploaded to the render
+/// shader through the camera uniform so atlases can be swapped at runtime (`endless/sprite_atlas`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasLayout {
+    pub sheet_w: f32,
+    pub sheet_h: f32,
+    pub sprite_w: f32,
+    pub sprite_h: f32,
+    pub margin: f32,
+}
+
+impl AtlasLayout {
+    /// roguelikeChar_transparent.png (NPC bodies + equipment).
+    pub const CHARACTER: Self = Self {
+        sheet_w: 918.0,
+        sheet_h: 203.0,
+        sprite_w: 16.0,
+        sprite_h: 16.0,
+        margin: 1.0,
+    };
+    /// roguelikeSheet_transparent.png (farms, carried items).
+    pub const WORLD: Self = Self {
+        sheet_w: 968.0,
+        sheet_h: 526.0,
+        sprite_w: 16.0,
+        sprite_h: 16.0,
+        margin: 1.0,
+    };
+    /// Extras item strip composited at world spawn (heal, sleep, arrow, boat), one 64px
+    /// cell per column.
+    pub const EXTRAS: Self = Self::strip(4, false);
+
+    /// Composited building strip (location sprites), one 64px layer per row.
+    pub const fn building(layers: usize) -> Self {
+        Self::strip(layers, true)
+    }
+
+    /// `cells` 64px cells in a row (or a column when `vertical`), no margin.
+    const fn strip(cells: usize, vertical: bool) -> Self {
+        let cell = crate::world::ATLAS_CELL as f32;
+        let len = cell * cells as f32;
+        Self {
+            sheet_w: if vertical { cell } else { len },
+            sheet_h: if vertical { len } else { cell },
+            sprite_w: cell,
+            sprite_h: cell,
+            margin: 0.0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let all = [
+            self.sheet_w,
+            self.sheet_h,
+            self.sprite_w,
+            self.sprite_h,
+            self.margin,
+        ];
+        if all.iter().any(|v| !v.is_finite()) {
+            return Err("atlas values must be finite".into());
+        }
+        if self.sprite_w < 1.0 || self.sprite_h < 1.0 || self.margin < 0.0 {
+            return Err("sprite size must be >= 1 and margin >= 0".into());
+        }
+        if self.sprite_w > self.sheet_w || self.sprite_h > self.sheet_h {
+            return Err(format!(
+                "sprite {}x{} larger than sheet {}x{}",
+                self.sprite_w, self.sprite_h, self.sheet_w, self.sheet_h
+            ));
+        }
+        Ok(())
+    }
+
+    /// Shader grid params: (cell_w, cell_h, sprite_w, sprite_h).
+    pub fn grid(&self) -> Vec4 {
+        Vec4::new(
+            self.sprite_w + self.margin,
+            self.sprite_h + self.margin,
+            self.sprite_w,
+            self.sprite_h,
+        )
+    }
+}
+
 /// NPC sprite texture handles. Owned by RenderFrameConfig.
 /// Set by the render module after loading sprite sheets.
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct NpcSpriteTexture {
     pub handle: Option<Handle<Image>>,
     pub world_handle: Option<Handle<Image>>,
     pub building_handle: Option<Handle<Image>>,
     pub extras_handle: Option<Handle<Image>>,
+    pub char_layout: AtlasLayout,
+    pub world_layout: AtlasLayout,
+    pub extras_layout: AtlasLayout,
+    pub building_layout: AtlasLayout,
+}
+
+impl Default for NpcSpriteTexture {
+    fn default() -> Self {
+        Self {
+            handle: None,
+            world_handle: None,
+            building_handle: None,
+            extras_handle: None,
+            char_layout: AtlasLayout::CHARACTER,
+            world_layout: AtlasLayout::WORLD,
+            extras_layout: AtlasLayout::EXTRAS,
+            building_layout: AtlasLayout::building(
+                crate::constants::BUILDING_REGISTRY.len()
+                    + crate::constants::autotile_total_extra_layers(),
+            ),
+        }
+    }
 }
 
 /// GPU buffers for projectile compute.
@@ -1480,10 +2061,13 @@ pub struct ProjGpuBuffers {
     pub shooters: Buffer,
     pub lifetimes: Buffer,
     pub homing_targets: Buffer,
+    pub arcs: Buffer,
     pub active: Buffer,
     pub hits: Buffer,
     pub grid_counts: Buffer,
     pub grid_data: Buffer,
+    /// (cells, max_per_cell) the grid buffers were sized for.
+    pub grid_shape: (u32, u32),
 }
 
 /// Bind groups for projectile compute pass (3 modes: clear grid, build grid, movement+collision).
@@ -1505,14 +2089,78 @@ struct ProjComputePipeline {
 // PIPELINE INITIALIZATION
 // =============================================================================
 
+/// Spatial grid buffers for `(cells, max_per_cell)`: per-cell counts and the cell index.
+fn create_grid_buffers(
+    render_device: &RenderDevice,
+    prefix: &str,
+    (cells, max_per_cell): (u32, u32),
+) -> (Buffer, Buffer) {
+    let cells = cells as u64;
+    let i32_size = std::mem::size_of::<i32>() as u64;
+    let counts = render_device.create_buffer(&BufferDescriptor {
+        label: Some(&format!("{prefix}grid_counts")),
+        size: cells * i32_size,
+        usage: BufferUsages::STORAGE,
+        mapped_at_creation: false,
+    });
+    let data = render_device.create_buffer(&BufferDescriptor {
+        label: Some(&format!("{prefix}grid_data")),
+        size: cells * max_per_cell as u64 * i32_size,
+        usage: BufferUsages::STORAGE,
+        mapped_at_creation: false,
+    });
+    (counts, data)
+}
+
+/// Reallocate the NPC and projectile grid buffers when the extracted grid shape changes.
+/// Runs before bind groups are prepared, so this frame's bind groups use the new buffers.
+fn resize_grid_buffers(
+    config: Option<Res<RenderFrameConfig>>,
+    render_device: Res<RenderDevice>,
+    buffers: Option<ResMut<EntityGpuBuffers>>,
+    proj: Option<ResMut<ProjGpuBuffers>>,
+) {
+    let (Some(config), Some(mut buffers), Some(mut proj)) = (config, buffers, proj) else {
+        return;
+    };
+    let npc_shape = (
+        config.npc.grid_width * config.npc.grid_height,
+        config.npc.max_per_cell,
+    );
+    if buffers.grid_shape != npc_shape {
+        let (counts, data) = create_grid_buffers(&render_device, "", npc_shape);
+        buffers.grid_counts = counts;
+        buffers.grid_data = data;
+        buffers.grid_shape = npc_shape;
+        info!(
+            "Spatial grid resized: {}x{} cells of {}px, {} per cell",
+            config.npc.grid_width,
+            config.npc.grid_height,
+            config.npc.cell_size,
+            config.npc.max_per_cell
+        );
+    }
+    let proj_shape = (
+        config.proj.grid_width * config.proj.grid_height,
+        config.proj.max_per_cell,
+    );
+    if proj.grid_shape != proj_shape {
+        let (counts, data) = create_grid_buffers(&render_device, "proj_", proj_shape);
+        proj.grid_counts = counts;
+        proj.grid_data = data;
+        proj.grid_shape = proj_shape;
+    }
+}
+
 fn init_npc_compute_pipeline(
     mut commands: Commands,
     render_device: Res<RenderDevice>,
     asset_server: Res<AssetServer>,
     pipeline_cache: Res<PipelineCache>,
 ) {
-    let grid_cells = (GRID_WIDTH * GRID_HEIGHT) as usize;
-    let grid_data_size = grid_cells * MAX_PER_CELL as usize;
+    // Sized for the default grid; resize_grid_buffers follows GridConfig from the first frame
+    let grid_shape = (GRID_WIDTH * GRID_HEIGHT, MAX_PER_CELL);
+    let (grid_counts, grid_data) = create_grid_buffers(&render_device, "", grid_shape);
 
     // Create GPU buffers — entity-sized for unified NPC + building collision
     let max_ents = MAX_ENTITIES;
@@ -1534,18 +2182,9 @@ fn init_npc_compute_pipeline(
             usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
             mapped_at_creation: false,
         }),
-        grid_counts: render_device.create_buffer(&BufferDescriptor {
-            label: Some("grid_counts"),
-            size: (grid_cells * std::mem::size_of::<i32>()) as u64,
-            usage: BufferUsages::STORAGE,
-            mapped_at_creation: false,
-        }),
-        grid_data: render_device.create_buffer(&BufferDescriptor {
-            label: Some("grid_data"),
-            size: (grid_data_size * std::mem::size_of::<i32>()) as u64,
-            usage: BufferUsages::STORAGE,
-            mapped_at_creation: false,
-        }),
+        grid_counts,
+        grid_data,
+        grid_shape,
         arrivals: render_device.create_buffer(&BufferDescriptor {
             label: Some("entity_arrivals"),
             size: (max_ents * std::mem::size_of::<i32>()) as u64,
@@ -1600,6 +2239,12 @@ fn init_npc_compute_pipeline(
             usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
             mapped_at_creation: false,
         }),
+        velocities: render_device.create_buffer(&BufferDescriptor {
+            label: Some("entity_velocities"),
+            size: (max_ents * std::mem::size_of::<[f32; 2]>()) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }),
     };
 
     commands.insert_resource(buffers);
@@ -1645,6 +2290,8 @@ fn init_npc_compute_pipeline(
                 storage_buffer_read_only::<Vec<u32>>(false),
                 // 18: tile_flags (bitfield per world grid cell: bit 0=road)
                 storage_buffer_read_only::<Vec<u32>>(false),
+                // 19: scripted velocities (read only by the scripted pass)
+                storage_buffer_read_only::<Vec<[f32; 2]>>(false),
             ),
         ),
     );
@@ -1696,7 +2343,7 @@ fn prepare_npc_bind_groups(
     let Some(config) = config else { return };
     let params = &config.npc;
 
-    // Create 3 uniform buffers (one per mode) for multi-dispatch
+    // One uniform buffer per mode for multi-dispatch
     let layout = &pipeline_cache.get_bind_group_layout(&pipeline.bind_group_layout);
     let storage_bindings = (
         buffers.positions.as_entire_buffer_binding(),
@@ -1711,19 +2358,10 @@ fn prepare_npc_bind_groups(
         buffers.combat_targets.as_entire_buffer_binding(),
     );
 
-    let mut p0 = params.clone();
-    p0.mode = 0;
-    let mut p1 = params.clone();
-    p1.mode = 1;
-    let mut p2 = params.clone();
-    p2.mode = 2;
-
-    let mut ub0 = UniformBuffer::from(p0);
-    let mut ub1 = UniformBuffer::from(p1);
-    let mut ub2 = UniformBuffer::from(p2);
-    ub0.write_buffer(&render_device, &render_queue);
-    ub1.write_buffer(&render_device, &render_queue);
-    ub2.write_buffer(&render_device, &render_queue);
+    let threat_bind = buffers.threat_counts.as_entire_buffer_binding();
+    let flags_bind = buffers.entity_flags.as_entire_buffer_binding();
+    let tile_bind = buffers.tile_flags.as_entire_buffer_binding();
+    let velocity_bind = buffers.velocities.as_entire_buffer_binding();
 
     // Projectile grid + data bindings (read-only from NPC compute)
     let proj_bind = (
@@ -1734,90 +2372,49 @@ fn prepare_npc_bind_groups(
         proj.factions.as_entire_buffer_binding(),
     );
 
-    let threat_bind = buffers.threat_counts.as_entire_buffer_binding();
-    let flags_bind = buffers.entity_flags.as_entire_buffer_binding();
-    let tile_bind = buffers.tile_flags.as_entire_buffer_binding();
-
-    let mode0 = render_device.create_bind_group(
-        Some("npc_compute_bg_mode0"),
-        layout,
-        &BindGroupEntries::sequential((
-            storage_bindings.0.clone(),
-            storage_bindings.1.clone(),
-            storage_bindings.2.clone(),
-            storage_bindings.3.clone(),
-            storage_bindings.4.clone(),
-            storage_bindings.5.clone(),
-            storage_bindings.6.clone(),
-            storage_bindings.7.clone(),
-            storage_bindings.8.clone(),
-            storage_bindings.9.clone(),
-            &ub0,
-            proj_bind.0.clone(),
-            proj_bind.1.clone(),
-            proj_bind.2.clone(),
-            proj_bind.3.clone(),
-            proj_bind.4.clone(),
-            threat_bind.clone(),
-            flags_bind.clone(),
-            tile_bind.clone(),
-        )),
-    );
-    let mode1 = render_device.create_bind_group(
-        Some("npc_compute_bg_mode1"),
-        layout,
-        &BindGroupEntries::sequential((
-            storage_bindings.0.clone(),
-            storage_bindings.1.clone(),
-            storage_bindings.2.clone(),
-            storage_bindings.3.clone(),
-            storage_bindings.4.clone(),
-            storage_bindings.5.clone(),
-            storage_bindings.6.clone(),
-            storage_bindings.7.clone(),
-            storage_bindings.8.clone(),
-            storage_bindings.9.clone(),
-            &ub1,
-            proj_bind.0.clone(),
-            proj_bind.1.clone(),
-            proj_bind.2.clone(),
-            proj_bind.3.clone(),
-            proj_bind.4.clone(),
-            threat_bind.clone(),
-            flags_bind.clone(),
-            tile_bind.clone(),
-        )),
-    );
-    let mode2 = render_device.create_bind_group(
-        Some("npc_compute_bg_mode2"),
-        layout,
-        &BindGroupEntries::sequential((
-            storage_bindings.0.clone(),
-            storage_bindings.1.clone(),
-            storage_bindings.2.clone(),
-            storage_bindings.3.clone(),
-            storage_bindings.4.clone(),
-            storage_bindings.5.clone(),
-            storage_bindings.6.clone(),
-            storage_bindings.7.clone(),
-            storage_bindings.8.clone(),
-            storage_bindings.9.clone(),
-            &ub2,
-            proj_bind.0.clone(),
-            proj_bind.1.clone(),
-            proj_bind.2.clone(),
-            proj_bind.3.clone(),
-            proj_bind.4.clone(),
-            threat_bind.clone(),
-            flags_bind.clone(),
-            tile_bind.clone(),
-        )),
-    );
+    // Same buffers for every mode; only the uniform's `mode` differs
+    let bind_group_for_mode = |mode: u32, label: &'static str| {
+        let mut p = params.clone();
+        p.mode = mode;
+        let mut ub = UniformBuffer::from(p);
+        ub.write_buffer(&render_device, &render_queue);
+        render_device.create_bind_group(
+            Some(label),
+            layout,
+            &BindGroupEntries::sequential((
+                storage_bindings.0.clone(),
+                storage_bindings.1.clone(),
+                storage_bindings.2.clone(),
+                storage_bindings.3.clone(),
+                storage_bindings.4.clone(),
+                storage_bindings.5.clone(),
+                storage_bindings.6.clone(),
+                storage_bindings.7.clone(),
+                storage_bindings.8.clone(),
+                storage_bindings.9.clone(),
+                &ub,
+                proj_bind.0.clone(),
+                proj_bind.1.clone(),
+                proj_bind.2.clone(),
+                proj_bind.3.clone(),
+                proj_bind.4.clone(),
+                threat_bind.clone(),
+                flags_bind.clone(),
+                tile_bind.clone(),
+                velocity_bind.clone(),
+            )),
+        )
+    };
+    let mode0 = bind_group_for_mode(0, "npc_compute_bg_mode0");
+    let mode1 = bind_group_for_mode(1, "npc_compute_bg_mode1");
+    let mode2 = bind_group_for_mode(2, "npc_compute_bg_mode2");
+    let mode3 = bind_group_for_mode(MODE_SCRIPTED, "npc_compute_bg_mode3");
 
     commands.insert_resource(NpcBindGroups {
         mode0,
         mode1,
         mode2,
+        mode3,
     });
 
     if let Some(s) = start {
@@ -1837,6 +2434,8 @@ fn prepare_npc_bind_groups(
 enum NpcComputeState {
     Loading,
     Ready,
+    /// Pipeline failed to build; the main world runs the CPU fallback.
+    Failed,
 }
 
 struct NpcComputeNode {
@@ -1867,12 +2466,15 @@ impl render_graph::Node for NpcComputeNode {
                     }
                     CachedPipelineState::Err(PipelineCacheError::ShaderNotLoaded(_)) => {}
                     CachedPipelineState::Err(err) => {
-                        panic!("NPC compute shader error: {err}")
+                        error!("NPC compute shader error: {err} — switching to CPU fallback");
+                        crate::messages::GPU_COMPUTE_FAILED
+                            .store(true, std::sync::atomic::Ordering::Relaxed);
+                        self.state = NpcComputeState::Failed;
                     }
                     _ => {}
                 }
             }
-            NpcComputeState::Ready => {}
+            NpcComputeState::Ready | NpcComputeState::Failed => {}
         }
     }
 
@@ -1902,6 +2504,9 @@ impl render_graph::Node for NpcComputeNode {
         let Some(config) = world.get_resource::<RenderFrameConfig>() else {
             return Ok(());
         };
+        if config.compute_backend == crate::resources::ComputeBackend::Cpu {
+            return Ok(());
+        }
         let pipeline_cache = world.resource::<PipelineCache>();
         let pipeline = world.resource::<NpcComputePipeline>();
 
@@ -1916,38 +2521,48 @@ impl render_graph::Node for NpcComputeNode {
             return Ok(());
         };
 
-        let grid_cells = GRID_WIDTH * GRID_HEIGHT;
+        let grid_cells = config.npc.grid_width * config.npc.grid_height;
         let grid_wg = grid_cells.div_ceil(WORKGROUP_SIZE);
         let entity_wg = entity_count.div_ceil(WORKGROUP_SIZE);
 
-        // Pass 0: Clear spatial grid
-        {
+        if config.compute_mode == crate::resources::ComputeMode::Scripted {
+            // Scripted pass: integrate scripted velocities only (no grid, separation or combat)
             let mut pass = render_context
                 .command_encoder()
                 .begin_compute_pass(&ComputePassDescriptor::default());
-            pass.set_bind_group(0, &bind_groups.mode0, &[]);
-            pass.set_pipeline(compute_pipeline);
-            pass.dispatch_workgroups(grid_wg, 1, 1);
-        }
-
-        // Pass 1: Build spatial grid (insert all entities into cells)
-        {
-            let mut pass = render_context
-                .command_encoder()
-                .begin_compute_pass(&ComputePassDescriptor::default());
-            pass.set_bind_group(0, &bind_groups.mode1, &[]);
+            pass.set_bind_group(0, &bind_groups.mode3, &[]);
             pass.set_pipeline(compute_pipeline);
             pass.dispatch_workgroups(entity_wg, 1, 1);
-        }
+        } else {
+            // Pass 0: Clear spatial grid
+            {
+                let mut pass = render_context
+                    .command_encoder()
+                    .begin_compute_pass(&ComputePassDescriptor::default());
+                pass.set_bind_group(0, &bind_groups.mode0, &[]);
+                pass.set_pipeline(compute_pipeline);
+                pass.dispatch_workgroups(grid_wg, 1, 1);
+            }
 
-        // Pass 2: Movement (NPCs) + combat targeting (NPCs + towers)
-        {
-            let mut pass = render_context
-                .command_encoder()
-                .begin_compute_pass(&ComputePassDescriptor::default());
-            pass.set_bind_group(0, &bind_groups.mode2, &[]);
-            pass.set_pipeline(compute_pipeline);
-            pass.dispatch_workgroups(entity_wg, 1, 1);
+            // Pass 1: Build spatial grid (insert all entities into cells)
+            {
+                let mut pass = render_context
+                    .command_encoder()
+                    .begin_compute_pass(&ComputePassDescriptor::default());
+                pass.set_bind_group(0, &bind_groups.mode1, &[]);
+                pass.set_pipeline(compute_pipeline);
+                pass.dispatch_workgroups(entity_wg, 1, 1);
+            }
+
+            // Pass 2: Movement (NPCs) + combat targeting (NPCs + towers)
+            {
+                let mut pass = render_context
+                    .command_encoder()
+                    .begin_compute_pass(&ComputePassDescriptor::default());
+                pass.set_bind_group(0, &bind_groups.mode2, &[]);
+                pass.set_pipeline(compute_pipeline);
+                pass.dispatch_workgroups(entity_wg, 1, 1);
+            }
         }
 
         // Copy positions + combat_targets → readback ShaderStorageBuffer assets
@@ -2030,8 +2645,13 @@ fn update_proj_gpu_data(
     slots: Res<GpuSlotPool>,
     proj_alloc: Res<crate::resources::ProjSlotAllocator>,
     dt: Res<crate::resources::DeltaTime>,
+    grid: Res<GridConfig>,
 ) {
     config.proj.proj_count = proj_alloc.next as u32;
+    config.proj.grid_width = grid.width;
+    config.proj.grid_height = grid.height;
+    config.proj.cell_size = grid.cell_size;
+    config.proj.max_per_cell = grid.max_per_cell;
     config.proj._npc_count = slots.count() as u32;
     config.proj.entity_count = slots.count() as u32;
     config.proj.delta = dt.0;
@@ -2044,8 +2664,8 @@ fn init_proj_compute_pipeline(
     pipeline_cache: Res<PipelineCache>,
 ) {
     let max = MAX_PROJECTILE_COUNT;
-    let grid_cells = (GRID_WIDTH * GRID_HEIGHT) as usize;
-    let grid_data_size = grid_cells * MAX_PER_CELL as usize;
+    let grid_shape = (GRID_WIDTH * GRID_HEIGHT, MAX_PER_CELL);
+    let (grid_counts, grid_data) = create_grid_buffers(&render_device, "proj_", grid_shape);
 
     let buffers = ProjGpuBuffers {
         positions: render_device.create_buffer(&BufferDescriptor {
@@ -2089,6 +2709,12 @@ fn init_proj_compute_pipeline(
             contents: bytemuck::cast_slice(&vec![-1i32; max]),
             usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
         }),
+        arcs: render_device.create_buffer(&BufferDescriptor {
+            label: Some("proj_arcs"),
+            size: (max * std::mem::size_of::<[f32; 4]>()) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }),
         active: render_device.create_buffer(&BufferDescriptor {
             label: Some("proj_active"),
             size: (max * std::mem::size_of::<i32>()) as u64,
@@ -2101,25 +2727,17 @@ fn init_proj_compute_pipeline(
             usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
             mapped_at_creation: false,
         }),
-        grid_counts: render_device.create_buffer(&BufferDescriptor {
-            label: Some("proj_grid_counts"),
-            size: (grid_cells * std::mem::size_of::<i32>()) as u64,
-            usage: BufferUsages::STORAGE,
-            mapped_at_creation: false,
-        }),
-        grid_data: render_device.create_buffer(&BufferDescriptor {
-            label: Some("proj_grid_data"),
-            size: (grid_data_size * std::mem::size_of::<i32>()) as u64,
-            usage: BufferUsages::STORAGE,
-            mapped_at_creation: false,
-        }),
+        grid_counts,
+        grid_data,
+        grid_shape,
     };
 
     commands.insert_resource(buffers);
 
-    // 19 bindings — must match projectile_compute.wgsl binding order exactly:
+    // 20 bindings — must match projectile_compute.wgsl binding order exactly:
     // 0-7: proj rw, 8-10: NPC ro, 11-12: NPC grid ro, 13: uniform,
-    // 14-15: proj grid rw, 16: half_sizes ro, 17: entity_flags ro, 18: homing_targets rw
+    // 14-15: proj grid rw, 16: half_sizes ro, 17: entity_flags ro, 18: homing_targets rw,
+    // 19: arcs rw
     let bind_group_layout = BindGroupLayoutDescriptor::new(
         "ProjComputeLayout",
         &BindGroupLayoutEntries::sequential(
@@ -2152,6 +2770,8 @@ fn init_proj_compute_pipeline(
                 storage_buffer_read_only::<Vec<u32>>(false), // 17: entity_flags
                 // 18: homing targets (read_write)
                 storage_buffer::<Vec<i32>>(false), // 18: homing_targets
+                // 19: ballistic arcs (read_write)
+                storage_buffer::<Vec<[f32; 4]>>(false), // 19: arcs
             ),
         ),
     );
@@ -2232,6 +2852,7 @@ fn prepare_proj_bind_groups(
     let half_sizes_bind = ent.half_sizes.as_entire_buffer_binding();
     let entity_flags_bind = ent.entity_flags.as_entire_buffer_binding();
     let homing_bind = proj.homing_targets.as_entire_buffer_binding();
+    let arcs_bind = proj.arcs.as_entire_buffer_binding();
 
     let mode0 = render_device.create_bind_group(
         Some("proj_compute_bg_mode0"),
@@ -2256,6 +2877,7 @@ fn prepare_proj_bind_groups(
             half_sizes_bind.clone(),                     // 16
             entity_flags_bind.clone(),                   // 17
             homing_bind.clone(),                         // 18
+            arcs_bind.clone(),                           // 19
         )),
     );
     let mode1 = render_device.create_bind_group(
@@ -2281,6 +2903,7 @@ fn prepare_proj_bind_groups(
             half_sizes_bind.clone(),
             entity_flags_bind.clone(),
             homing_bind.clone(),
+            arcs_bind.clone(),
         )),
     );
     let mode2 = render_device.create_bind_group(
@@ -2306,6 +2929,7 @@ fn prepare_proj_bind_groups(
             half_sizes_bind.clone(),
             entity_flags_bind.clone(),
             homing_bind.clone(),
+            arcs_bind.clone(),
         )),
     );
 
@@ -2403,7 +3027,7 @@ impl render_graph::Node for ProjectileComputeNode {
             return Ok(());
         };
 
-        let grid_cells = GRID_WIDTH * GRID_HEIGHT;
+        let grid_cells = config.proj.grid_width * config.proj.grid_height;
         let grid_wg = grid_cells.div_ceil(WORKGROUP_SIZE);
         let proj_wg = proj_count.div_ceil(WORKGROUP_SIZE);
 
@@ -2489,9 +3113,34 @@ mod tests {
             shooter: 42,
             lifetime: 3.0,
             homing_target: -1,
+            kind: ProjKind::Arrow,
+            arc: Default::default(),
+            vz: 0.0,
         }
     }
 
+    #[test]
+    fn grid_config_validates_and_warns_on_coverage() {
+        let mut world = World::new();
+        world.init_resource::<GridConfig>();
+        world.insert_resource(crate::resources::WorldBounds::from_grid(250, 250, 32.0));
+
+        // Smaller cells, deeper buckets, still spanning the 8000px world
+        assert_eq!(set_grid_config(&mut world, 256, 256, 32.0, 96), Ok(None));
+        assert_eq!(world.resource::<GridConfig>().max_per_cell, 96);
+
+        // Too small for the world: applied, but with a warning
+        let warning = set_grid_config(&mut world, 64, 64, 32.0, 96).unwrap();
+        assert!(warning.is_some_and(|w| w.contains("2048x2048")));
+        assert_eq!(world.resource::<GridConfig>().width, 64);
+
+        // Rejected outright: config unchanged
+        assert!(set_grid_config(&mut world, 0, 64, 32.0, 96).is_err());
+        assert!(set_grid_config(&mut world, 64, 64, 0.5, 96).is_err());
+        assert!(set_grid_config(&mut world, 4096, 4096, 32.0, 64).is_err());
+        assert_eq!(world.resource::<GridConfig>().width, 64);
+    }
+
     #[test]
     fn readback_bucket_caps_to_buffer_capacity() {
         assert_eq!(readback_bucket(MAX_NPC_COUNT, MAX_NPC_COUNT), MAX_NPC_COUNT);
@@ -2535,4 +3184,123 @@ mod tests {
         assert_eq!(writes.active_set_index[7], 1);
         assert_eq!(writes.active_set_index[9], usize::MAX);
     }
+
+    #[test]
+    fn atlas_layout_defaults_match_shipped_sheets_and_validate() {
+        // Matches the former hardcoded shader constants: 17px cells, 16px sprites.
+        assert_eq!(
+            AtlasLayout::CHARACTER.grid(),
+            Vec4::new(17.0, 17.0, 16.0, 16.0)
+        );
+        assert!(AtlasLayout::CHARACTER.validate().is_ok());
+        assert!(AtlasLayout::WORLD.validate().is_ok());
+        // Extras: 4 columns of 64px (former `extras_cols`); buildings: one 64px row per layer
+        assert_eq!(
+            (AtlasLayout::EXTRAS.sheet_w, AtlasLayout::EXTRAS.sheet_h),
+            (256.0, 64.0)
+        );
+        let building = AtlasLayout::building(10);
+        assert_eq!((building.sheet_w, building.sheet_h), (64.0, 640.0));
+        assert_eq!(building.grid(), Vec4::new(64.0, 64.0, 64.0, 64.0));
+        assert!(building.validate().is_ok());
+
+        let too_big = AtlasLayout {
+            sprite_w: 1000.0,
+            ..AtlasLayout::CHARACTER
+        };
+        assert!(too_big.validate().is_err());
+        let negative_margin = AtlasLayout {
+            margin: -1.0,
+            ..AtlasLayout::WORLD
+        };
+        assert!(negative_margin.validate().is_err());
+    }
+
+    #[test]
+    fn active_projectiles_lists_live_slots_in_order() {
+        let mut writes = ProjBufferWrites::default();
+        for idx in [9, 2, 5] {
+            writes.apply(&spawn_update(idx));
+        }
+        writes.apply(&ProjGpuUpdate::Deactivate { idx: 5 });
+
+        // No readback yet: spawn positions, slot order, freed slot excluded
+        let (list, total) = writes.active_projectiles(&[], 10);
+        assert_eq!(total, 2);
+        assert_eq!(list.iter().map(|p| p.idx).collect::<Vec<_>>(), vec![2, 9]);
+        assert_eq!((list[0].x, list[0].y), (2.0, 2.5));
+        assert_eq!(list[1].kind, ProjKind::Arrow);
+
+        // Readback positions win; the cap truncates but reports the full count
+        let mut live = vec![0.0; 20];
+        live[4] = 40.0;
+        live[5] = 41.0;
+        let (list, total) = writes.active_projectiles(&live, 1);
+        assert_eq!(total, 2);
+        assert_eq!(list.len(), 1);
+        assert_eq!((list[0].idx, list[0].x, list[0].y), (2, 40.0, 41.0));
+    }
+
+    #[test]
+    fn spawn_fade_ramps_in_and_resets_on_hide() {
+        let mut state = EntityGpuState::default();
+        state.set_spawn_fade_duration(0.5);
+        state.apply(&GpuUpdate::StartSpawnFade { idx: 3 });
+        assert_eq!(state.fade_alpha(3), 0.0);
+        assert_eq!(state.packed_flash(3), -1.0);
+
+        state.tick_flash_and_fade(0.25, 8);
+        assert!((state.fade_alpha(3) - 0.5).abs() < 1e-5);
+        assert_eq!(state.flash_only_indices, vec![3]);
+
+        // Damage flash wins over the fade while it lasts
+        state.apply(&GpuUpdate::SetDamageFlash {
+            idx: 3,
+            intensity: 1.0,
+        });
+        assert_eq!(state.packed_flash(3), 1.0);
+
+        // Dying mid-fade drops straight to opaque so death handling sees a clean slot
+        state.apply(&GpuUpdate::Hide { idx: 3 });
+        assert_eq!(state.fade_alpha(3), 1.0);
+        assert_eq!(state.packed_flash(3), 0.0);
+
+        // Duration 0 = instant, including for a respawn into the same slot
+        state.set_spawn_fade_duration(0.0);
+        state.apply(&GpuUpdate::StartSpawnFade { idx: 3 });
+        assert_eq!(state.fade_alpha(3), 1.0);
+    }
+
+    #[test]
+    fn building_visual_carries_footprint_for_quad_size() {
+        use crate::world::BuildingKind;
+        let mut state = EntityGpuState::default();
+        let mut upload = NpcVisualUpload::default();
+        upload.visual_data.resize(3 * 8, -1.0);
+        upload.equip_data.resize(3 * 28, -1.0);
+        for (idx, kind) in [(1, BuildingKind::Farm), (2, BuildingKind::Casino)] {
+            state.apply(&GpuUpdate::SetSpriteFrame {
+                idx,
+                col: crate::constants::tileset_index(kind) as f32,
+                row: 0.0,
+                atlas: crate::constants::ATLAS_BUILDING,
+            });
+            write_building_visual(idx, kind, &state, &mut upload);
+        }
+        // npc_render.wgsl sizes the quad to 64px per footprint cell from equip layer 0
+        assert_eq!(
+            upload.equip_data[28..31],
+            [1.0, 1.0, crate::constants::ATLAS_BUILDING]
+        );
+        assert_eq!(
+            upload.equip_data[56..59],
+            [2.0, 2.0, crate::constants::ATLAS_BUILDING]
+        );
+        assert_eq!(
+            crate::constants::building_def(BuildingKind::Casino).footprint,
+            (2, 2)
+        );
+        // Remaining layers stay empty so no overlay draws on a building
+        assert!(upload.equip_data[60..84].chunks(4).all(|l| l[0] < 0.0));
+    }
 }
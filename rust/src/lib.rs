@@ -12,6 +12,7 @@
 
 pub mod components;
 pub mod constants;
+pub mod cpu_compute;
 pub mod entity_map;
 pub mod gpu;
 pub mod messages;
@@ -42,11 +43,11 @@ use messages::{
 use resources::{
     ActiveHealingSlots, AutoUpgrade, BuildMenuContext, BuildingHealState, CombatDebug, CombatLog,
     DebugFlags, DeltaTime, Difficulty, EndlessMode, EntityMap, FactionList, FactionStats,
-    FollowSelected, GameAudio, GameConfig, GameTime, GpuReadState, GpuSlotPool, HealingZoneCache,
-    HealthDebug, HelpCatalog, KillStats, MerchantInventory, MigrationState, MiningPolicy,
-    NextLootItemId, NpcLogCache, NpcTargetThrashDebug, PlaySfxMsg, PopulationStats, ProjHitState,
-    ProjPositionState, ProjSlotAllocator, RaiderState, Reputation, SelectedBuilding, SelectedNpc,
-    SquadState, SystemTimings, TowerState, TutorialState, UiState, UpsCounter,
+    FarmEventOutbox, FollowSelected, GameAudio, GameConfig, GameTime, GpuReadState, GpuSlotPool,
+    HealingZoneCache, HealthDebug, HelpCatalog, KillStats, MerchantInventory, MigrationState,
+    MiningPolicy, NextLootItemId, NpcLogCache, NpcTargetThrashDebug, PlaySfxMsg, PopulationStats,
+    ProjHitState, ProjPositionState, ProjSlotAllocator, RaiderState, Reputation, SelectedBuilding,
+    SelectedNpc, SquadState, SystemTimings, TowerState, TutorialState, UiState, UpsCounter,
 };
 use systems::*;
 use systems::{AiPlayerConfig, AiPlayerState};
@@ -106,6 +107,29 @@ fn smooth_delta(time: Res<Time>, game_time: Res<GameTime>, mut dt: ResMut<DeltaT
     };
 }
 
+/// Feed real frame time into the adaptive quality controller and apply the projectile cap.
+/// Readback and LOD knobs read `QualityState` directly (sync_readback_ranges, extract_camera_state).
+fn adaptive_quality_system(
+    time: Res<Time<bevy::time::Real>>,
+    mut quality: ResMut<resources::QualityState>,
+    mut proj_alloc: ResMut<resources::ProjSlotAllocator>,
+) {
+    let frame_ms = time.delta_secs() * 1000.0;
+    if frame_ms <= 0.0 {
+        return;
+    }
+    if let Some(level) = quality.record(frame_ms) {
+        info!(
+            "Adaptive quality: level {} (smoothed {:.1}ms, budget {:.1}ms)",
+            level, quality.smoothed_ms, quality.budget_ms
+        );
+    }
+    let cap = quality.projectile_cap();
+    if proj_alloc.combat_cap != cap {
+        proj_alloc.combat_cap = cap;
+    }
+}
+
 fn frame_timer_start(timings: Res<SystemTimings>, time: Res<Time>) {
     timings.record_frame_delta(time.delta_secs());
     // Drain render-world atomic timings into SystemTimings
@@ -118,14 +142,30 @@ fn frame_timer_start(timings: Res<SystemTimings>, time: Res<Time>) {
                 timings.record(RT_NAMES[i], f32::from_bits(bits));
             }
         }
-        // Drain tracing-captured system timings (Bevy auto-spans)
-        if let Ok(mut map) = crate::tracing_layer::TRACING_TIMINGS.lock() {
+        // One graph sample per frame, phases grouped like the profiler tab
+        let mut sample = resources::PerfSample {
+            frame_ms: time.delta_secs() * 1000.0,
+            ..Default::default()
+        };
+        // Drain tracing-captured system timings (Bevy auto-spans), summing the sample as we go
+        {
+            let mut map = resources::recover_or_log(
+                &crate::tracing_layer::TRACING_TIMINGS,
+                "tracing_timings",
+            );
             for (name, ms) in map.iter_mut() {
                 timings.record_traced(name, *ms);
+                if name.starts_with("endless::") {
+                    sample.game_ms += *ms;
+                } else {
+                    sample.engine_ms += *ms;
+                }
                 // Decay stale entries — active systems overwrite via on_exit each frame
                 *ms *= 0.9;
             }
         }
+        sample.render_ms = timings.timings_total();
+        timings.record_history(sample);
     }
 }
 
@@ -133,6 +173,41 @@ fn startup_system() {
     info!("Endless ECS initialized - systems registered");
 }
 
+/// Fill VersionInfo GPU fields from the render adapter and publish it for crash reports.
+fn version_info_system(
+    adapter: Option<Res<bevy::render::renderer::RenderAdapterInfo>>,
+    mut version: ResMut<resources::VersionInfo>,
+) {
+    if let Some(adapter) = adapter {
+        version.gpu_device_name = adapter.name.clone();
+        version.gpu_backend = format!("{:?}", adapter.backend);
+        version.gpu_driver = format!("{} {}", adapter.driver, adapter.driver_info)
+            .trim()
+            .to_string();
+    }
+    version.publish();
+    info!("{}", version.summary());
+}
+
+/// Refresh the crash-report game-state snapshot. Cheap: a handful of scalar copies per
+/// frame, with the combat-log tail only rebuilt when the log changed.
+fn crash_context_system(
+    game_time: Res<GameTime>,
+    entity_map: Res<EntityMap>,
+    world_data: Res<world::WorldData>,
+    combat_log: Res<resources::CombatLog>,
+    combat_rng: Res<resources::CombatRng>,
+) {
+    resources::CrashContext::update(
+        game_time.day(),
+        game_time.hour(),
+        entity_map.npc_count(),
+        world_data.towns.len(),
+        combat_rng.seed,
+        combat_log.is_changed().then_some(&*combat_log),
+    );
+}
+
 /// Skip main menu when --autostart is passed. Loads saved settings and starts a new game.
 fn autostart_system(
     auto: Res<resources::AutoStart>,
@@ -153,7 +228,8 @@ fn autostart_system(
     let saved = settings::load_settings();
 
     // World gen config
-    wg_config.gen_style = world::WorldGenStyle::Continents;
+    wg_config.generator = world::WorldGenStyle::Continents;
+    wg_config.seed = None;
     wg_config.world_width = saved.world_size;
     wg_config.world_height = saved.world_size;
     wg_config.num_towns = 1;
@@ -180,11 +256,13 @@ fn autostart_system(
     wg_config.ai_towns = ai_builder_count;
     wg_config.raider_towns = ai_raider_count;
     wg_config.gold_mines_per_town = saved.gold_mines_per_town;
+    wg_config.faction_loadouts = saved.faction_loadouts.clone();
 
     // AI/NPC config
     ai_config.decision_interval = saved.ai_interval;
     npc_config.interval = saved.npc_interval;
     pathfind_config.max_per_frame = user_settings.pathfind_max_per_frame.max(1);
+    pathfind_config.retarget_cooldown = user_settings.retarget_cooldown.max(0.0);
 
     // Runtime resources
     commands.insert_resource(saved.difficulty);
@@ -226,8 +304,12 @@ fn sync_debug_settings(
     settings: Res<crate::settings::UserSettings>,
     mut flags: ResMut<DebugFlags>,
     mut timings: ResMut<SystemTimings>,
+    mut snapshots: ResMut<save::SnapshotHistory>,
+    mut save_config: ResMut<save::SaveConfig>,
 ) {
     flags.readback = settings.debug_readback;
+    snapshots.enabled = settings.debug_rewind;
+    save_config.compress = settings.compress_saves;
     flags.combat = settings.debug_combat;
     flags.spawns = settings.debug_spawns;
     flags.behavior = settings.debug_behavior;
@@ -294,6 +376,9 @@ pub fn build_app(app: &mut App) {
         .add_message::<GpuUpdateMsg>()
         .add_message::<ProjGpuUpdateMsg>()
         .add_message::<CombatLogMsg>()
+        .add_message::<messages::FarmEventMsg>()
+        .add_message::<messages::GameOverMsg>()
+        .add_message::<messages::AttackAnimMsg>()
         .add_message::<messages::WorkIntentMsg>()
         .add_message::<BuildingGridDirtyMsg>()
         .add_message::<TerrainDirtyMsg>()
@@ -308,6 +393,15 @@ pub fn build_app(app: &mut App) {
         // Resources
         .init_resource::<Difficulty>()
         .init_resource::<EntityMap>()
+        .init_resource::<resources::SpriteTable>()
+        .init_resource::<resources::BehaviorLod>()
+        .init_resource::<resources::EnergyThresholds>()
+        .init_resource::<resources::TownAlerts>()
+        .init_resource::<resources::LastStandState>()
+        .init_resource::<resources::PanicState>()
+        .init_resource::<resources::NoBuildZones>()
+        .init_resource::<resources::BuildingCosts>()
+        .init_resource::<resources::LootConfig>()
         .init_resource::<PopulationStats>()
         .init_resource::<GameConfig>()
         .init_resource::<GameTime>()
@@ -315,10 +409,15 @@ pub fn build_app(app: &mut App) {
         .init_resource::<world::WorldData>()
         .init_resource::<HealthDebug>()
         .init_resource::<systems::DeathQueue>()
+        .init_resource::<systems::DeathKnockback>()
         .init_resource::<CombatDebug>()
         .init_resource::<NpcTargetThrashDebug>()
         .init_resource::<resources::PathRequestQueue>()
         .init_resource::<resources::PathfindConfig>()
+        .init_resource::<resources::PositionSync>()
+        .init_resource::<resources::VersionInfo>()
+        .init_resource::<resources::QualityState>()
+        .init_resource::<resources::SeparationState>()
         .init_resource::<resources::PathfindStats>()
         .init_resource::<KillStats>()
         .init_resource::<SelectedNpc>()
@@ -336,6 +435,7 @@ pub fn build_app(app: &mut App) {
         .init_resource::<FactionStats>()
         .init_resource::<FactionList>()
         .init_resource::<Reputation>()
+        .init_resource::<resources::FactionMvp>()
         .init_resource::<RaiderState>()
         .init_resource::<BuildingHealState>()
         .init_resource::<ActiveHealingSlots>()
@@ -346,10 +446,17 @@ pub fn build_app(app: &mut App) {
         .init_resource::<UpsCounter>()
         .init_resource::<world::WorldGrid>()
         .init_resource::<world::WorldGenConfig>()
+        .init_resource::<world::StartingLoadouts>()
         .init_resource::<UiState>()
         .init_resource::<CombatLog>()
+        .init_resource::<FarmEventOutbox>()
+        .init_resource::<resources::AttackAnimOutbox>()
+        .init_resource::<resources::GameOverOutbox>()
+        .init_resource::<WinCondition>()
+        .init_resource::<BodyguardTargets>()
         .init_resource::<BuildMenuContext>()
         .add_message::<DestroyBuildingMsg>()
+        .add_message::<messages::DespawnNpcMsg>()
         .add_message::<SelectFactionMsg>()
         .init_resource::<TowerState>()
         .init_resource::<resources::BuildingHpRender>()
@@ -364,6 +471,35 @@ pub fn build_app(app: &mut App) {
         .init_resource::<systems::ai_player::PerimeterSyncDirty>()
         .init_resource::<resources::NpcDecisionConfig>()
         .init_resource::<systems::stats::CombatConfig>()
+        .init_resource::<systems::balance::BalanceConfig>()
+        .init_resource::<systems::balance::BalanceSource>()
+        .init_resource::<systems::npc_log_file::NpcLogFile>()
+        .init_resource::<resources::CombatRng>()
+        .init_resource::<resources::AggroMemoryConfig>()
+        .init_resource::<resources::IdleCycle>()
+        .init_resource::<resources::NpcHighlight>()
+        .init_resource::<resources::RallyConfig>()
+        .init_resource::<resources::TargetStickiness>()
+        .init_resource::<resources::PlayerFocus>()
+        .init_resource::<resources::CombatZones>()
+        .init_resource::<resources::ProjectileVisualConfig>()
+        .init_resource::<resources::RespawnPolicy>()
+        .init_resource::<resources::LeadTargeting>()
+        .init_resource::<resources::RaidPartyConfig>()
+        .init_resource::<resources::RaidParties>()
+        .init_resource::<resources::NpcVelocities>()
+        .init_resource::<resources::NpcFacing>()
+        .init_resource::<systems::ThreatMap>()
+        .init_resource::<resources::FoodStorageConfig>()
+        .init_resource::<resources::FoodStorageState>()
+        .init_resource::<systems::HappinessConfig>()
+        .init_resource::<systems::TownHappiness>()
+        .init_resource::<systems::TechTree>()
+        .init_resource::<resources::AnchorConfig>()
+        .init_resource::<systems::SpawnOverrideQueue>()
+        .init_resource::<systems::quick_battle::QuickBattle>()
+        .init_resource::<systems::fast_forward::FastForward>()
+        .init_resource::<resources::TributeState>()
         .add_message::<systems::stats::UpgradeMsg>()
         .add_message::<systems::stats::EquipItemMsg>()
         .add_message::<systems::stats::UnequipItemMsg>()
@@ -372,6 +508,8 @@ pub fn build_app(app: &mut App) {
         .init_resource::<MiningPolicy>()
         .init_resource::<save::SaveLoadRequest>()
         .init_resource::<save::SaveToast>()
+        .init_resource::<save::SnapshotHistory>()
+        .init_resource::<save::SaveConfig>()
         .init_resource::<GameAudio>()
         .init_resource::<NextLootItemId>()
         .init_resource::<MerchantInventory>()
@@ -390,6 +528,10 @@ pub fn build_app(app: &mut App) {
             RemotePlugin::default()
                 .with_method("endless/summary", systems::remote::summary_handler)
                 .with_method("endless/build", systems::remote::build_handler)
+                .with_method(
+                    "endless/buildable_status",
+                    systems::remote::buildable_status_handler,
+                )
                 .with_method("endless/destroy", systems::remote::destroy_handler)
                 .with_method("endless/upgrade", systems::remote::upgrade_handler)
                 .with_method("endless/policy", systems::remote::policy_handler)
@@ -399,14 +541,290 @@ pub fn build_app(app: &mut App) {
                     systems::remote::squad_target_handler,
                 )
                 .with_method("endless/ai_manager", systems::remote::ai_manager_handler)
+                .with_method(
+                    "endless/faction_stance",
+                    systems::remote::faction_stance_handler,
+                )
                 .with_method("endless/chat", systems::remote::chat_handler)
                 .with_method("endless/debug", systems::remote::debug_handler)
-                .with_method("endless/perf", systems::remote::perf_handler),
+                .with_method("endless/perf", systems::remote::perf_handler)
+                .with_method("endless/trample", systems::remote::trample_handler)
+                .with_method(
+                    "endless/attack_windup",
+                    systems::remote::attack_windup_handler,
+                )
+                .with_method("endless/combat_rng", systems::remote::combat_rng_handler)
+                .with_method("endless/despawn_npc", systems::remote::despawn_npc_handler)
+                .with_method("endless/promote_npc", systems::remote::promote_npc_handler)
+                .with_method("endless/officers", systems::remote::officers_handler)
+                .with_method(
+                    "endless/position_sync",
+                    systems::remote::position_sync_handler,
+                )
+                .with_method("endless/version", systems::remote::version_handler)
+                .with_method(
+                    "endless/target_priority",
+                    systems::remote::target_priority_handler,
+                )
+                .with_method(
+                    "endless/combat_stance",
+                    systems::remote::combat_stance_handler,
+                )
+                .with_method(
+                    "endless/world_bounds",
+                    systems::remote::world_bounds_handler,
+                )
+                .with_method(
+                    "endless/performance_budget",
+                    systems::remote::performance_budget_handler,
+                )
+                .with_method("endless/quality", systems::remote::quality_handler)
+                .with_method(
+                    "endless/behavior_lod",
+                    systems::remote::behavior_lod_handler,
+                )
+                .with_method(
+                    "endless/projectile_limits",
+                    systems::remote::projectile_limits_handler,
+                )
+                .with_method(
+                    "endless/projectile_debug",
+                    systems::remote::projectile_debug_handler,
+                )
+                .with_method("endless/migrate", systems::remote::migrate_handler)
+                .with_method("endless/migration", systems::remote::migration_handler)
+                .with_method(
+                    "endless/cancel_migration",
+                    systems::remote::cancel_migration_handler,
+                )
+                .with_method(
+                    "endless/select_in_rect",
+                    systems::remote::select_in_rect_handler,
+                )
+                .with_method(
+                    "endless/selection_bounds",
+                    systems::remote::selection_bounds_handler,
+                )
+                .with_method(
+                    "endless/sprite_mapping",
+                    systems::remote::sprite_mapping_handler,
+                )
+                .with_method(
+                    "endless/sprite_atlas",
+                    systems::remote::sprite_atlas_handler,
+                )
+                .with_method(
+                    "endless/quick_battle",
+                    systems::remote::quick_battle_handler,
+                )
+                .with_method(
+                    "endless/quick_battle_status",
+                    systems::remote::quick_battle_status_handler,
+                )
+                .with_method(
+                    "endless/quick_battle_reset",
+                    systems::remote::quick_battle_reset_handler,
+                )
+                .with_method("endless/npc_path", systems::remote::npc_path_handler)
+                .with_method("endless/tribute", systems::remote::tribute_handler)
+                .with_method("endless/set_tribute", systems::remote::set_tribute_handler)
+                .with_method(
+                    "endless/perf_history",
+                    systems::remote::perf_history_handler,
+                )
+                .with_method(
+                    "endless/energy_thresholds",
+                    systems::remote::energy_thresholds_handler,
+                )
+                .with_method("endless/damage_area", systems::remote::damage_area_handler)
+                .with_method("endless/time_config", systems::remote::time_config_handler)
+                .with_method(
+                    "endless/attack_target",
+                    systems::remote::attack_target_handler,
+                )
+                .with_method(
+                    "endless/clear_attack_target",
+                    systems::remote::clear_attack_target_handler,
+                )
+                .with_method("endless/loot_config", systems::remote::loot_config_handler)
+                .with_method(
+                    "endless/aggro_memory",
+                    systems::remote::aggro_memory_handler,
+                )
+                .with_method("endless/transforms", systems::remote::transforms_handler)
+                .with_method(
+                    "endless/transforms_packed",
+                    systems::remote::transforms_packed_handler,
+                )
+                .with_method("endless/locations", systems::remote::locations_handler)
+                .with_method("endless/anchor", systems::remote::anchor_handler)
+                .with_method(
+                    "endless/fast_forward",
+                    systems::remote::fast_forward_handler,
+                )
+                .with_method(
+                    "endless/target_stickiness",
+                    systems::remote::target_stickiness_handler,
+                )
+                .with_method(
+                    "endless/food_storage",
+                    systems::remote::food_storage_handler,
+                )
+                .with_method(
+                    "endless/town_storage",
+                    systems::remote::town_storage_handler,
+                )
+                .with_method(
+                    "endless/select_next_idle",
+                    systems::remote::select_next_idle_handler,
+                )
+                .with_method(
+                    "endless/reload_balance",
+                    systems::remote::reload_balance_handler,
+                )
+                .with_method(
+                    "endless/npc_highlight",
+                    systems::remote::npc_highlight_handler,
+                )
+                .with_method("endless/squad_rally", systems::remote::squad_rally_handler)
+                .with_method(
+                    "endless/compute_mode",
+                    systems::remote::compute_mode_handler,
+                )
+                .with_method(
+                    "endless/npc_velocity",
+                    systems::remote::npc_velocity_handler,
+                )
+                .with_method("endless/faction_mvp", systems::remote::faction_mvp_handler)
+                .with_method("endless/world_hash", systems::remote::world_hash_handler)
+                .with_method(
+                    "endless/no_build_zone",
+                    systems::remote::no_build_zone_handler,
+                )
+                .with_method(
+                    "endless/clear_no_build_zones",
+                    systems::remote::clear_no_build_zones_handler,
+                )
+                .with_method("endless/npc_logging", systems::remote::npc_logging_handler)
+                .with_method("endless/grid_config", systems::remote::grid_config_handler)
+                .with_method(
+                    "endless/lead_targeting",
+                    systems::remote::lead_targeting_handler,
+                )
+                .with_method(
+                    "endless/raid_parties",
+                    systems::remote::raid_parties_handler,
+                )
+                .with_method("endless/camp_status", systems::remote::camp_status_handler)
+                .with_method("endless/give_item", systems::remote::give_item_handler)
+                .with_method("endless/take_item", systems::remote::take_item_handler)
+                .with_method("endless/inventory", systems::remote::inventory_handler)
+                .with_method(
+                    "endless/respawn_location",
+                    systems::remote::respawn_location_handler,
+                )
+                .with_method("endless/spawn_grid", systems::remote::spawn_grid_handler)
+                .with_method(
+                    "endless/player_focus",
+                    systems::remote::player_focus_handler,
+                )
+                .with_method("endless/combat_zone", systems::remote::combat_zone_handler)
+                .with_method("endless/assign_npc", systems::remote::assign_npc_handler)
+                .with_method(
+                    "endless/clear_assignment",
+                    systems::remote::clear_assignment_handler,
+                )
+                .with_method(
+                    "endless/projectile_visual",
+                    systems::remote::projectile_visual_handler,
+                )
+                .with_method("endless/conscript", systems::remote::conscript_handler)
+                .with_method("endless/demobilize", systems::remote::demobilize_handler)
+                .with_method("endless/militia", systems::remote::militia_handler)
+                .with_method(
+                    "endless/npc_snapshot",
+                    systems::remote::npc_snapshot_handler,
+                )
+                .with_method("endless/npc_restore", systems::remote::npc_restore_handler)
+                .with_method(
+                    "endless/npc_engagement",
+                    systems::remote::npc_engagement_handler,
+                )
+                .with_method("endless/farm_events", systems::remote::farm_events_handler)
+                .with_method("endless/separation", systems::remote::separation_handler)
+                .with_method(
+                    "endless/save_blueprint",
+                    systems::remote::save_blueprint_handler,
+                )
+                .with_method(
+                    "endless/stamp_blueprint",
+                    systems::remote::stamp_blueprint_handler,
+                )
+                .with_method(
+                    "endless/predict_combat",
+                    systems::remote::predict_combat_handler,
+                )
+                .with_method(
+                    "endless/compute_backend",
+                    systems::remote::compute_backend_handler,
+                )
+                .with_method("endless/snapshot", systems::remote::snapshot_handler)
+                .with_method("endless/rewind", systems::remote::rewind_handler)
+                .with_method(
+                    "endless/attack_events",
+                    systems::remote::attack_events_handler,
+                )
+                .with_method("endless/spawn_fade", systems::remote::spawn_fade_handler)
+                .with_method("endless/threat_map", systems::remote::threat_map_handler)
+                .with_method(
+                    "endless/active_projectiles",
+                    systems::remote::active_projectiles_handler,
+                )
+                .with_method(
+                    "endless/auto_assign_guards",
+                    systems::remote::auto_assign_guards_handler,
+                )
+                .with_method(
+                    "endless/win_condition",
+                    systems::remote::win_condition_handler,
+                )
+                .with_method("endless/game_state", systems::remote::game_state_handler)
+                .with_method(
+                    "endless/assign_bodyguard",
+                    systems::remote::assign_bodyguard_handler,
+                )
+                .with_method(
+                    "endless/clear_bodyguard",
+                    systems::remote::clear_bodyguard_handler,
+                )
+                .with_method("endless/pixel_snap", systems::remote::pixel_snap_handler)
+                .with_method(
+                    "endless/death_knockback",
+                    systems::remote::death_knockback_handler,
+                )
+                .with_method(
+                    "endless/build_validity",
+                    systems::remote::build_validity_handler,
+                )
+                .with_method("endless/npc_counts", systems::remote::npc_counts_handler)
+                .with_method(
+                    "endless/projectile_arc",
+                    systems::remote::projectile_arc_handler,
+                )
+                .with_method("endless/happiness", systems::remote::happiness_handler)
+                .with_method("endless/save_info", systems::remote::save_info_handler)
+                .with_method(
+                    "endless/squad_stance",
+                    systems::remote::squad_stance_handler,
+                )
+                .with_method("endless/tech", systems::remote::tech_handler),
         )
         .add_plugins(RemoteHttpPlugin::default())
         .init_resource::<systems::remote::RemoteBuildQueue>()
         .init_resource::<systems::remote::RemoteDestroyQueue>()
         .init_resource::<systems::remote::RemoteUpgradeQueue>()
+        .init_resource::<systems::remote::RemoteDespawnQueue>()
+        .init_resource::<systems::remote::RemoteDamageQueue>()
         .init_resource::<systems::remote::RemoteLlmLogQueue>()
         .init_resource::<systems::remote::RemoteCombatLogRing>()
         .init_resource::<resources::RemoteAllowedTowns>()
@@ -421,10 +839,17 @@ pub fn build_app(app: &mut App) {
         .register_type::<components::Home>()
         .register_type::<components::PatrolRoute>()
         .register_type::<components::NpcWorkState>()
+        .register_type::<components::AssignedFarm>()
+        .register_type::<components::AssignedBed>()
+        .register_type::<components::AssignedPost>()
+        .register_type::<components::Bodyguard>()
         .register_type::<components::CarriedLoot>()
         .register_type::<components::Activity>()
         .register_type::<components::CombatState>()
         .register_type::<components::ManualTarget>()
+        .register_type::<components::Reinforcing>()
+        .register_type::<components::LastStand>()
+        .register_type::<components::Anchored>()
         .register_type::<components::NpcFlags>()
         .register_type::<components::NpcPath>()
         .register_type::<components::SquadId>()
@@ -434,6 +859,14 @@ pub fn build_app(app: &mut App) {
         .register_type::<components::CachedStats>()
         .register_type::<components::Faction>()
         .register_type::<components::AttackTimer>()
+        .register_type::<components::AttackWindup>()
+        .register_type::<components::Attacking>()
+        .register_type::<components::Officer>()
+        .register_type::<components::AuraBuff>()
+        .register_type::<components::OutOfSupply>()
+        .register_type::<components::TargetPriority>()
+        .register_type::<components::CombatStance>()
+        .register_type::<components::Provoked>()
         .register_type::<components::Stealer>()
         .register_type::<components::HasEnergy>()
         .register_type::<components::NpcEquipment>()
@@ -466,6 +899,8 @@ pub fn build_app(app: &mut App) {
         .register_type::<Difficulty>()
         // Startup
         .add_systems(Startup, startup_system)
+        .add_systems(Startup, version_info_system)
+        .add_systems(Startup, systems::balance::load_balance_system)
         .add_systems(Startup, systems::audio::load_music)
         .add_systems(Startup, systems::audio::load_sfx)
         // Autostart: skip main menu if --autostart was passed
@@ -474,6 +909,9 @@ pub fn build_app(app: &mut App) {
         .add_systems(OnEnter(AppState::Playing), systems::audio::start_music)
         .add_systems(OnExit(AppState::Playing), systems::audio::stop_music)
         .add_systems(Update, smooth_delta)
+        .add_systems(Update, systems::npc_log_file::npc_log_flush_system)
+        .add_systems(Update, adaptive_quality_system.run_if(game_active.clone()))
+        .add_systems(Update, crash_context_system.run_if(game_active.clone()))
         .add_systems(
             Update,
             systems::audio::jukebox_system.run_if(in_state(AppState::Playing)),
@@ -506,7 +944,20 @@ pub fn build_app(app: &mut App) {
         // Drain
         .add_systems(
             FixedUpdate,
-            (drain_game_config, drain_combat_log).in_set(Step::Drain),
+            (
+                drain_game_config,
+                drain_combat_log,
+                drain_farm_events,
+                drain_attack_anim_events,
+                drain_game_over_events,
+            )
+                .in_set(Step::Drain),
+        )
+        .add_systems(
+            FixedUpdate,
+            systems::balance::apply_balance_system
+                .in_set(Step::Drain)
+                .run_if(resource_changed::<systems::balance::BalanceConfig>),
         )
         // GPU→ECS position readback
         .add_systems(
@@ -523,7 +974,22 @@ pub fn build_app(app: &mut App) {
                 .in_set(Step::Spawn)
                 .before(spawn_npc_system),
         )
-        // Spawn
+        // Quick battle deploy/teardown (BRP or test harness → spawn/despawn messages)
+        .add_systems(
+            FixedUpdate,
+            systems::quick_battle::quick_battle_system
+                .in_set(Step::Spawn)
+                .after(systems::remote::drain_remote_queues)
+                .before(despawn_npc_system),
+        )
+        // Spawn — scripted despawns free slots before this tick's spawns reuse them
+        .add_systems(
+            FixedUpdate,
+            despawn_npc_system
+                .after(systems::remote::drain_remote_queues)
+                .before(spawn_npc_system)
+                .in_set(Step::Spawn),
+        )
         .add_systems(FixedUpdate, spawn_npc_system.in_set(Step::Spawn))
         // Combat
         .add_systems(
@@ -531,9 +997,18 @@ pub fn build_app(app: &mut App) {
             (
                 process_proj_hits,
                 cooldown_system,
+                return_fire_system,
+                squad_stance_system,
+                target_commit_system,
+                officer_aura_system,
+                target_priority_system,
+                npc_velocity_system,
+                bodyguard_system,
                 attack_system,
+                trample_system,
                 damage_system,
                 death_system,
+                corpse_knockback_system,
                 building_tower_system,
             )
                 .chain()
@@ -552,14 +1027,21 @@ pub fn build_app(app: &mut App) {
                     update_healing_zone_cache.before(healing_system),
                     healing_system,
                     npc_regen_system,
+                    potion_use_system.after(npc_regen_system),
                 ),
                 on_duty_tick_system,
                 game_time_system,
                 (
                     construction_tick_system.before(growth_system),
-                    growth_system,
+                    // Before decision_system so a farm's Ready event precedes its Harvested
+                    growth_system.before(decision_system),
+                ),
+                (
+                    raider_forage_system,
+                    tribute_system
+                        .after(raider_forage_system)
+                        .after(arrival_system),
                 ),
-                raider_forage_system,
                 spawner_respawn_system,
                 mining_policy_system
                     .after(spawner_respawn_system)
@@ -569,18 +1051,31 @@ pub fn build_app(app: &mut App) {
                 farm_visual_system,
                 (
                     auto_upgrade_system,
+                    systems::stats::auto_expand_system,
                     systems::stats::auto_tower_upgrade_system,
                     systems::stats::auto_equip_system,
                     systems::stats::prune_town_equipment_system,
                 ),
-                process_upgrades_system.after(auto_upgrade_system),
-                systems::stats::process_equip_system
-                    .after(process_upgrades_system)
-                    .after(systems::stats::auto_equip_system),
+                process_upgrades_system
+                    .after(auto_upgrade_system)
+                    .after(systems::stats::auto_expand_system),
+                (
+                    systems::stats::process_equip_system
+                        .after(process_upgrades_system)
+                        .after(systems::stats::auto_equip_system),
+                    systems::militia_stats_system.after(systems::stats::process_equip_system),
+                ),
                 systems::ai_player::ai_dirty_drain_system.before(ai_decision_system),
                 ai_decision_system,
-                endless_system,
-                (rebuild_patrol_routes_system, squad_cleanup_system),
+                (
+                    scripted_migration_system.before(endless_system),
+                    endless_system,
+                ),
+                (
+                    rebuild_patrol_routes_system,
+                    guard_post_system,
+                    squad_cleanup_system,
+                ),
             )
                 .in_set(Step::Behavior),
         )
@@ -596,6 +1091,65 @@ pub fn build_app(app: &mut App) {
                 .before(rebuild_patrol_routes_system)
                 .in_set(Step::Behavior),
         )
+        .add_systems(
+            FixedUpdate,
+            (town_alert_system, last_stand_system, reinforce_system)
+                .chain()
+                .before(decision_system)
+                .in_set(Step::Behavior),
+        )
+        .add_systems(
+            FixedUpdate,
+            raid_party_system
+                .after(ai_squad_commander_system)
+                .before(decision_system)
+                .in_set(Step::Behavior),
+        )
+        .add_systems(
+            FixedUpdate,
+            panic_system
+                .after(last_stand_system)
+                .before(decision_system)
+                .in_set(Step::Behavior),
+        )
+        .add_systems(FixedUpdate, supply_system.in_set(Step::Behavior))
+        .add_systems(
+            FixedUpdate,
+            threat_map_system
+                .before(ai_squad_commander_system)
+                .in_set(Step::Behavior),
+        )
+        .add_systems(FixedUpdate, loot_system.in_set(Step::Behavior))
+        .add_systems(FixedUpdate, victory_system.in_set(Step::Behavior))
+        .add_systems(
+            FixedUpdate,
+            squad_order_system
+                .after(squad_cleanup_system)
+                .before(decision_system)
+                .in_set(Step::Behavior),
+        )
+        .add_systems(
+            FixedUpdate,
+            food_storage_system
+                .after(tribute_system)
+                .in_set(Step::Behavior),
+        )
+        .add_systems(
+            FixedUpdate,
+            systems::happiness_system
+                .after(food_storage_system)
+                .in_set(Step::Behavior),
+        )
+        .add_systems(
+            FixedUpdate,
+            systems::research_system
+                .after(systems::happiness_system)
+                .in_set(Step::Behavior),
+        )
+        .add_systems(
+            FixedUpdate,
+            anchor_system.after(decision_system).in_set(Step::Behavior),
+        )
         .add_systems(
             FixedUpdate,
             ai_squad_commander_system
@@ -610,6 +1164,12 @@ pub fn build_app(app: &mut App) {
                 .in_set(Step::Behavior),
         )
         .add_systems(FixedUpdate, sync_building_hp_render.in_set(Step::Behavior))
+        .add_systems(
+            FixedUpdate,
+            sprite_state_system
+                .after(decision_system)
+                .in_set(Step::Behavior),
+        )
         .add_systems(FixedUpdate, merchant_tick_system.in_set(Step::Behavior))
         .add_systems(
             FixedUpdate,
@@ -633,6 +1193,7 @@ pub fn build_app(app: &mut App) {
                     .after(world::rebuild_building_grid_system),
                 systems::pathfinding::invalidate_paths_on_building_change
                     .after(world::rebuild_building_grid_system),
+                sync_combat_zones_system.after(world::rebuild_building_grid_system),
             )
                 .in_set(Step::Behavior),
         )
@@ -671,6 +1232,13 @@ pub fn build_app(app: &mut App) {
                 .after(save::save_game_system)
                 .run_if(in_state(AppState::Playing)),
         )
+        .add_systems(
+            Update,
+            save::rewind_snapshot_system
+                .after(save::autosave_system)
+                .before(save::load_game_system)
+                .run_if(in_state(AppState::Playing)),
+        )
         .add_systems(
             Update,
             save::save_toast_tick_system.run_if(in_state(AppState::Playing)),
@@ -83,11 +83,15 @@ fn install_crash_handler() {
              Time: {:?}\n\n\
              Panic: {}\n\
              Location: {}\n\n\
+             Game State:\n{}\n\n\
              Backtrace:\n{}",
-            env!("CARGO_PKG_VERSION"),
+            endless::resources::VersionInfo::reported().summary(),
             std::time::SystemTime::now(),
             message,
             location,
+            endless::resources::CrashContext::reported()
+                .map(|c| c.summary())
+                .unwrap_or_else(|| "(no game in progress)".to_string()),
             backtrace,
         );
 
@@ -224,6 +228,9 @@ fn main() {
     if std::env::args().any(|a| a == "--autostart") {
         app.insert_resource(endless::resources::AutoStart(true));
     }
+    if std::env::args().any(|a| a == "--cpu-compute") {
+        app.insert_resource(endless::resources::ComputeBackend::Cpu);
+    }
     if let Some(pos) = std::env::args().position(|a| a == "--test") {
         let filter = std::env::args().nth(pos + 1);
         app.insert_resource(endless::resources::CliTestMode {
@@ -46,6 +46,12 @@ pub struct ReassignMsg {
 #[derive(Message, Clone)]
 pub struct DestroyBuildingMsg(pub usize, pub usize); // (grid_col, grid_row)
 
+/// Scripted NPC removal (not a death) by GPU slot. Writer: BRP/scripted events. Reader: despawn_npc_system.
+#[derive(Message, Clone)]
+pub struct DespawnNpcMsg {
+    pub slot: usize,
+}
+
 /// Request to focus the Factions panel on a faction id.
 #[derive(Message, Clone)]
 pub struct SelectFactionMsg(pub i32);
@@ -62,6 +68,66 @@ pub struct CombatLogMsg {
     pub location: Option<bevy::math::Vec2>,
 }
 
+/// A scenario ended: `WinCondition` victory (`won`) or defeat conditions were met.
+/// Writer: victory_system. Drained into `GameOverOutbox` for `endless/game_state`.
+#[derive(Message, Clone, Debug, serde::Serialize)]
+pub struct GameOverMsg {
+    pub won: bool,
+    pub reason: String,
+    pub day: i32,
+}
+
+/// Farm crop transition. Writers: growth_system (Ready), decision_system (Harvested).
+/// A farm that ripens and is harvested within one tick sends both, in that order.
+#[derive(Message, Clone, Debug, PartialEq)]
+pub struct FarmEventMsg {
+    pub kind: FarmEventKind,
+    pub farm_slot: usize,
+    pub position: bevy::math::Vec2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FarmEventKind {
+    Ready,
+    Harvested,
+}
+
+impl FarmEventKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ready => "farm_ready",
+            Self::Harvested => "farm_harvested",
+        }
+    }
+}
+
+/// Attack animation cue for an external renderer. Writer: attack_system, only while
+/// `AttackAnimOutbox.enabled` and only for on-screen attackers. `Swing` goes out when an
+/// attack starts (its impact lands `windup` game-seconds later, with the damage); a swing
+/// that never connects is followed by `Whiff` for the same attacker.
+#[derive(Message, Clone, Debug, PartialEq)]
+pub struct AttackAnimMsg {
+    pub kind: AttackAnimKind,
+    pub slot: usize,
+    pub target_slot: usize,
+    pub windup: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttackAnimKind {
+    Swing,
+    Whiff,
+}
+
+impl AttackAnimKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Swing => "attack_swing",
+            Self::Whiff => "attack_whiff",
+        }
+    }
+}
+
 // ============================================================================
 // DIRTY-FLAG MESSAGES (replace DirtyFlags resource)
 // ============================================================================
@@ -213,6 +279,8 @@ pub enum GpuUpdate {
     SetPosition { idx: usize, x: f32, y: f32 },
     /// Set speed
     SetSpeed { idx: usize, speed: f32 },
+    /// Set scripted velocity (px/s), integrated only while `ComputeMode::Scripted` is active
+    SetVelocity { idx: usize, vx: f32, vy: f32 },
     /// Set sprite frame (column, row in sprite sheet, atlas: 0.0=character, 1.0=world)
     SetSpriteFrame {
         idx: usize,
@@ -222,6 +290,10 @@ pub enum GpuUpdate {
     },
     /// Set damage flash intensity (1.0 = full white, decays to 0.0)
     SetDamageFlash { idx: usize, intensity: f32 },
+    /// Start the spawn fade-in (alpha 0 → 1 over `EntityGpuState::spawn_fade_secs`)
+    StartSpawnFade { idx: usize },
+    /// Set body highlight tint (0.0 = none, 1.0 = selected; see `NpcHighlight`)
+    SetHighlight { idx: usize, strength: f32 },
     /// Set entity flags (bit 0: combat scan enabled, bit 1: building)
     SetFlags { idx: usize, flags: u32 },
     /// Set entity hitbox half-size for projectile collision (Minkowski sum with arrow hitbox)
@@ -238,6 +310,23 @@ pub enum GpuUpdate {
 // PROJECTILE GPU UPDATES (Bevy -> GPU)
 // ============================================================================
 
+/// What fired a projectile — selects its entry in `ProjectileVisualConfig`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjKind {
+    /// NPC ranged attack.
+    #[default]
+    Arrow,
+    /// Tower / fortress shot.
+    Tower,
+    /// Loot flying to the killer (no damage).
+    Loot,
+}
+
+impl ProjKind {
+    pub const ALL: [ProjKind; 3] = [ProjKind::Arrow, ProjKind::Tower, ProjKind::Loot];
+}
+
 /// GPU update for projectile buffers.
 #[derive(Clone, Debug)]
 pub enum ProjGpuUpdate {
@@ -253,6 +342,11 @@ pub enum ProjGpuUpdate {
         shooter: i32,
         lifetime: f32,
         homing_target: i32,
+        kind: ProjKind,
+        /// The shooter's gravity and splash; a default (flat) arc flies level and hits on contact.
+        arc: crate::components::ProjArc,
+        /// Upward launch speed of an arced shot (see `ballistic_launch`), 0 when flat.
+        vz: f32,
     },
     /// Deactivate a projectile (hit processed by CPU).
     Deactivate { idx: usize },
@@ -272,6 +366,10 @@ pub struct ProjGpuUpdateMsg(pub ProjGpuUpdate);
 
 pub static RENDER_PROFILING: AtomicBool = AtomicBool::new(false);
 
+/// Set by the render world when the NPC compute pipeline can't run (no render app, shader or
+/// pipeline error). `select_compute_backend` switches the sim to the CPU fallback.
+pub static GPU_COMPUTE_FAILED: AtomicBool = AtomicBool::new(false);
+
 pub const RT_EXTRACT_NPC: usize = 0;
 pub const RT_EXTRACT_PROJ: usize = 1;
 pub const RT_PREPARE_NPC: usize = 2;
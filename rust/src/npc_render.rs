@@ -109,6 +109,8 @@ pub struct InstanceData {
     pub scale: f32,
     pub atlas_id: f32,
     pub rotation: f32,
+    /// Per-axis multiplier on `scale` before rotation ([1, 1] = square quad).
+    pub stretch: [f32; 2],
 }
 
 /// Per-bracket instance data for GPU selection overlay.
@@ -168,7 +170,7 @@ pub struct NpcRenderBuffers {
 /// GPU storage buffers for NPC visual data (CPU-uploaded, read by vertex_npc shader).
 #[derive(Resource)]
 pub struct NpcVisualBuffers {
-    /// [f32; 8] per slot: [sprite_col, sprite_row, body_atlas, flash, r, g, b, a]
+    /// [f32; 8] per slot: [sprite_col, sprite_row, body_atlas, flash, r, g, b, highlight]
     pub visual: Buffer,
     /// [f32; 24] per slot: 6 equipment layers × [col, row, atlas, _pad]
     pub equip: Buffer,
@@ -216,6 +218,66 @@ pub struct SelectionRenderBuffers {
 pub struct ProjRenderBuffers {
     pub instance_buffer: RawBufferVec<InstanceData>,
     pub instance_count: u32,
+    pub trails: ProjTrailRing,
+}
+
+/// Recent positions per projectile slot, newest last, for trail quads. Render world only;
+/// allocated on first use so games without trails pay nothing.
+#[derive(Default)]
+pub struct ProjTrailRing {
+    /// `PROJ_TRAIL_MAX` points per slot.
+    points: Vec<[f32; 2]>,
+    heads: Vec<u8>,
+    lens: Vec<u8>,
+}
+
+impl ProjTrailRing {
+    fn ensure(&mut self, slots: usize) {
+        if self.heads.len() < slots {
+            self.points
+                .resize(slots * crate::constants::PROJ_TRAIL_MAX, [0.0; 2]);
+            self.heads.resize(slots, 0);
+            self.lens.resize(slots, 0);
+        }
+    }
+
+    /// Forget a slot's history (projectile respawned in it).
+    fn reset(&mut self, idx: usize) {
+        if let Some(len) = self.lens.get_mut(idx) {
+            *len = 0;
+        }
+    }
+
+    /// Record `pos` once it is `PROJ_TRAIL_SPACING` past the newest point, so trail spacing
+    /// doesn't depend on frame rate.
+    fn record(&mut self, idx: usize, pos: [f32; 2]) {
+        use crate::constants::{PROJ_TRAIL_MAX, PROJ_TRAIL_SPACING};
+        let Some(&len) = self.lens.get(idx) else {
+            return;
+        };
+        let base = idx * PROJ_TRAIL_MAX;
+        let head = self.heads[idx] as usize;
+        if len > 0 {
+            let last = self.points[base + (head + PROJ_TRAIL_MAX - 1) % PROJ_TRAIL_MAX];
+            let (dx, dy) = (pos[0] - last[0], pos[1] - last[1]);
+            if dx * dx + dy * dy < PROJ_TRAIL_SPACING * PROJ_TRAIL_SPACING {
+                return;
+            }
+        }
+        self.points[base + head] = pos;
+        self.heads[idx] = ((head + 1) % PROJ_TRAIL_MAX) as u8;
+        self.lens[idx] = (len as usize + 1).min(PROJ_TRAIL_MAX) as u8;
+    }
+
+    /// Up to `n` prior points, newest first.
+    fn recent(&self, idx: usize, n: usize) -> impl Iterator<Item = [f32; 2]> + '_ {
+        use crate::constants::PROJ_TRAIL_MAX;
+        let len = self.lens.get(idx).map_or(0, |&l| l as usize);
+        let head = self.heads.get(idx).map_or(0, |&h| h as usize);
+        (1..=len.min(n)).map(move |k| {
+            self.points[idx * PROJ_TRAIL_MAX + (head + PROJ_TRAIL_MAX - k) % PROJ_TRAIL_MAX]
+        })
+    }
 }
 
 /// The specialized render pipeline — supports both instance and storage buffer modes.
@@ -241,10 +303,18 @@ pub struct CameraUniform {
     pub zoom: f32,
     pub entity_count: u32,
     pub viewport: Vec2,
-    pub bldg_layers: f32,
-    pub extras_cols: f32,
     pub lod_zoom: f32,
-    pub _pad: u32,
+    /// Physical pixels per viewport unit to snap sprite centers to (0 = off).
+    pub pixel_snap: f32,
+    /// Character atlas grid (cell_w, cell_h, sprite_w, sprite_h) — see `AtlasLayout::grid`.
+    pub char_atlas: Vec4,
+    pub world_atlas: Vec4,
+    pub extras_atlas: Vec4,
+    pub building_atlas: Vec4,
+    /// Sheet sizes (char_w, char_h, world_w, world_h).
+    pub atlas_sizes: Vec4,
+    /// Sheet sizes (extras_w, extras_h, building_w, building_h).
+    pub extra_atlas_sizes: Vec4,
 }
 
 /// Bind group for camera uniform.
@@ -646,6 +716,7 @@ fn extract_camera_state(
     query: Extract<Query<(&Transform, &Projection), With<MainCamera>>>,
     windows: Extract<Query<&Window>>,
     user_settings: Extract<Res<crate::settings::UserSettings>>,
+    quality: Extract<Option<Res<crate::resources::QualityState>>>,
 ) {
     let Ok((transform, projection)) = query.single() else {
         return;
@@ -661,7 +732,12 @@ fn extract_camera_state(
         position: transform.translation.truncate(),
         zoom,
         viewport: Vec2::new(window.width(), window.height()),
-        lod_zoom: user_settings.lod_transition,
+        lod_zoom: user_settings.lod_transition * quality.as_ref().map_or(1.0, |q| q.lod_mult()),
+        pixel_snap: crate::render::pixel_snap_scale(
+            user_settings.pixel_snap,
+            zoom,
+            window.scale_factor(),
+        ),
     });
 }
 
@@ -749,6 +825,7 @@ fn build_building_body_instances(
             scale: 64.0,
             atlas_id: atlas,
             rotation: 0.0,
+            stretch: [1.0, 1.0],
         });
     }
 }
@@ -764,6 +841,7 @@ fn build_overlay_instances(
         &crate::components::ProductionState,
         &crate::components::ConstructionProgress,
     )>,
+    ground_loot_q: Query<&crate::components::GroundLoot>,
 ) {
     overlay.0.clear();
 
@@ -788,6 +866,7 @@ fn build_overlay_instances(
                     scale: 32.0,
                     atlas_id: 1.0,
                     rotation: 0.0,
+                    stretch: [1.0, 1.0],
                 });
             }
             crate::world::BuildingKind::GoldMine => {
@@ -800,6 +879,7 @@ fn build_overlay_instances(
                     scale: 24.0,
                     atlas_id: 6.0,
                     rotation: 0.0,
+                    stretch: [1.0, 1.0],
                 });
             }
             _ => {}
@@ -811,6 +891,8 @@ fn build_overlay_instances(
         .len()
         .min(building_hp.health_pcts.len());
     for i in 0..bhp_count {
+        // Bar sits in the bottom of a quad the size of the building footprint
+        let (fw, fh) = building_hp.footprints.get(i).copied().unwrap_or((1, 1));
         overlay.0.push(InstanceData {
             position: [building_hp.positions[i].x, building_hp.positions[i].y],
             sprite: [0.0, 0.0],
@@ -820,6 +902,27 @@ fn build_overlay_instances(
             scale: 64.0,
             atlas_id: 5.0,
             rotation: 0.0,
+            stretch: [fw.max(1) as f32, fh.max(1) as f32],
+        });
+    }
+
+    // Corpse loot: item sprite (character atlas), else gold, else food icon
+    for loot in &ground_loot_q {
+        let (sprite, atlas_id) = match &loot.item {
+            Some(item) => (item.sprite, 0.0),
+            None if loot.gold > 0 => (crate::constants::GOLD_SPRITE, 1.0),
+            None => (crate::constants::FOOD_SPRITE, 1.0),
+        };
+        overlay.0.push(InstanceData {
+            position: [loot.pos.x, loot.pos.y],
+            sprite: [sprite.0, sprite.1],
+            color: [1.0, 1.0, 1.0, 1.0],
+            health: 1.0,
+            flash: 0.0,
+            scale: 12.0,
+            atlas_id,
+            rotation: 0.0,
+            stretch: [1.0, 1.0],
         });
     }
 }
@@ -873,13 +976,16 @@ fn build_selection_overlay(
         });
     }
 
-    // Single building selection (gold, slightly offset Y)
+    // Single building selection (gold, slightly offset Y), framing the whole footprint
     if selected_building.active {
         if let Some(slot) = selected_building.slot {
+            let (fw, fh) = selected_building.kind.map_or((1, 1), |kind| {
+                crate::constants::building_def(kind).footprint
+            });
             instances.0.push(SelectionInstance {
                 slot: slot as u32,
                 color: [1.0, 0.86, 0.35, 0.90],
-                scale: 72.0,
+                scale: 64.0 * fw.max(fh).max(1) as f32 + 8.0,
                 y_offset: 4.0,
                 _pad: 0.0,
             });
@@ -920,6 +1026,29 @@ mod tests {
     use super::*;
     use crate::components::{Faction, GpuSlot, Job, NpcFlags};
 
+    #[test]
+    fn trail_ring_spaces_points_and_resets_on_respawn() {
+        use crate::constants::{PROJ_TRAIL_MAX, PROJ_TRAIL_SPACING};
+        let mut ring = ProjTrailRing::default();
+        ring.ensure(4);
+        for step in 0..(PROJ_TRAIL_MAX * 2) {
+            let x = step as f32 * PROJ_TRAIL_SPACING;
+            ring.record(2, [x, 0.0]);
+            // Sub-spacing moves are ignored
+            ring.record(2, [x + 1.0, 0.0]);
+        }
+        let pts: Vec<_> = ring.recent(2, 3).collect();
+        let newest = (PROJ_TRAIL_MAX * 2 - 1) as f32 * PROJ_TRAIL_SPACING;
+        assert_eq!(pts.len(), 3);
+        assert_eq!(pts[0], [newest, 0.0], "newest first");
+        assert_eq!(pts[2], [newest - 2.0 * PROJ_TRAIL_SPACING, 0.0]);
+        assert_eq!(ring.recent(2, 99).count(), PROJ_TRAIL_MAX, "ring is capped");
+        assert_eq!(ring.recent(1, 3).count(), 0, "other slots untouched");
+
+        ring.reset(2);
+        assert_eq!(ring.recent(2, 3).count(), 0);
+    }
+
     fn setup_selection_overlay_app() -> App {
         let mut app = App::new();
         app.init_resource::<SelectionOverlayInstances>()
@@ -1392,79 +1521,121 @@ fn extract_npc_data(
     if let Some(gpu_bufs) = gpu_buffers {
         // Read live count from authoritative GpuSlotPool — not stale RenderFrameConfig copy
         let n = slots.count();
-        // Positions: strict coalescing — GPU writes positions[i] every frame, stale CPU values teleport NPCs
-        write_coalesced_exact_f32(
-            &render_queue,
-            &gpu_bufs.positions,
-            &gpu_state.positions,
-            &gpu_state.position_dirty_indices,
-            2,
-        );
-        // Arrivals: gap-safe — CPU array is always 0, GPU re-computes settled=1 same frame
-        write_coalesced_i32(
-            &render_queue,
-            &gpu_bufs.arrivals,
-            &gpu_state.arrivals,
-            &gpu_state.arrival_dirty_indices,
-            1,
-            GAP_STRIDE_1,
-        );
-        // CPU-authoritative: gap-based coalescing safe (EntityGpuState is ground truth)
-        if gpu_state.dirty_targets {
-            if *prev_target_size != n {
-                write_bulk(&render_queue, &gpu_bufs.targets, &gpu_state.targets, n * 2);
-                *prev_target_size = n;
-            } else {
-                write_coalesced_f32(
-                    &render_queue,
-                    &gpu_bufs.targets,
-                    &gpu_state.targets,
-                    &gpu_state.target_dirty_indices,
-                    2,
-                    GAP_STRIDE_2,
-                );
+        if gpu_state.full_upload {
+            // After a fast-forward: the CPU copy moved on without any extracts
+            write_bulk(
+                &render_queue,
+                &gpu_bufs.positions,
+                &gpu_state.positions,
+                n * 2,
+            );
+            write_bulk(&render_queue, &gpu_bufs.arrivals, &gpu_state.arrivals, n);
+            write_bulk(&render_queue, &gpu_bufs.targets, &gpu_state.targets, n * 2);
+            write_bulk(&render_queue, &gpu_bufs.speeds, &gpu_state.speeds, n);
+            write_bulk(&render_queue, &gpu_bufs.factions, &gpu_state.factions, n);
+            write_bulk(&render_queue, &gpu_bufs.healths, &gpu_state.healths, n);
+            write_bulk(
+                &render_queue,
+                &gpu_bufs.entity_flags,
+                &gpu_state.entity_flags,
+                n,
+            );
+            write_bulk(
+                &render_queue,
+                &gpu_bufs.half_sizes,
+                &gpu_state.half_sizes,
+                n * 2,
+            );
+            write_bulk(
+                &render_queue,
+                &gpu_bufs.velocities,
+                &gpu_state.velocities,
+                n * 2,
+            );
+            *prev_target_size = n;
+        } else {
+            // Positions: strict coalescing — GPU writes positions[i] every frame, stale CPU values teleport NPCs
+            write_coalesced_exact_f32(
+                &render_queue,
+                &gpu_bufs.positions,
+                &gpu_state.positions,
+                &gpu_state.position_dirty_indices,
+                2,
+            );
+            // Arrivals: gap-safe — CPU array is always 0, GPU re-computes settled=1 same frame
+            write_coalesced_i32(
+                &render_queue,
+                &gpu_bufs.arrivals,
+                &gpu_state.arrivals,
+                &gpu_state.arrival_dirty_indices,
+                1,
+                GAP_STRIDE_1,
+            );
+            // CPU-authoritative: gap-based coalescing safe (EntityGpuState is ground truth)
+            if gpu_state.dirty_targets {
+                if *prev_target_size != n {
+                    write_bulk(&render_queue, &gpu_bufs.targets, &gpu_state.targets, n * 2);
+                    *prev_target_size = n;
+                } else {
+                    write_coalesced_f32(
+                        &render_queue,
+                        &gpu_bufs.targets,
+                        &gpu_state.targets,
+                        &gpu_state.target_dirty_indices,
+                        2,
+                        GAP_STRIDE_2,
+                    );
+                }
             }
+            write_coalesced_f32(
+                &render_queue,
+                &gpu_bufs.speeds,
+                &gpu_state.speeds,
+                &gpu_state.speed_dirty_indices,
+                1,
+                GAP_STRIDE_1,
+            );
+            write_coalesced_i32(
+                &render_queue,
+                &gpu_bufs.factions,
+                &gpu_state.factions,
+                &gpu_state.faction_dirty_indices,
+                1,
+                GAP_STRIDE_1,
+            );
+            write_coalesced_f32(
+                &render_queue,
+                &gpu_bufs.healths,
+                &gpu_state.healths,
+                &gpu_state.health_dirty_indices,
+                1,
+                GAP_STRIDE_1,
+            );
+            write_coalesced_u32(
+                &render_queue,
+                &gpu_bufs.entity_flags,
+                &gpu_state.entity_flags,
+                &gpu_state.flags_dirty_indices,
+                1,
+                GAP_STRIDE_1,
+            );
+            write_coalesced_f32(
+                &render_queue,
+                &gpu_bufs.half_sizes,
+                &gpu_state.half_sizes,
+                &gpu_state.half_size_dirty_indices,
+                2,
+                GAP_STRIDE_2,
+            );
+            write_coalesced_f32(
+                &render_queue,
+                &gpu_bufs.velocities,
+                &gpu_state.velocities,
+                &gpu_state.velocity_dirty_indices,
+                2,
+                GAP_STRIDE_2,
+            );
         }
-        write_coalesced_f32(
-            &render_queue,
-            &gpu_bufs.speeds,
-            &gpu_state.speeds,
-            &gpu_state.speed_dirty_indices,
-            1,
-            GAP_STRIDE_1,
-        );
-        write_coalesced_i32(
-            &render_queue,
-            &gpu_bufs.factions,
-            &gpu_state.factions,
-            &gpu_state.faction_dirty_indices,
-            1,
-            GAP_STRIDE_1,
-        );
-        write_coalesced_f32(
-            &render_queue,
-            &gpu_bufs.healths,
-            &gpu_state.healths,
-            &gpu_state.health_dirty_indices,
-            1,
-            GAP_STRIDE_1,
-        );
-        write_coalesced_u32(
-            &render_queue,
-            &gpu_bufs.entity_flags,
-            &gpu_state.entity_flags,
-            &gpu_state.flags_dirty_indices,
-            1,
-            GAP_STRIDE_1,
-        );
-        write_coalesced_f32(
-            &render_queue,
-            &gpu_bufs.half_sizes,
-            &gpu_state.half_sizes,
-            &gpu_state.half_size_dirty_indices,
-            2,
-            GAP_STRIDE_2,
-        );
         // Road flags: upload when present (rebuilt when roads change)
         if !config.tile_flags.is_empty() {
             render_queue.write_buffer(
@@ -1578,8 +1749,9 @@ fn extract_proj_data(
     mut commands: Commands,
     writes: Extract<Res<ProjBufferWrites>>,
     proj_pos_state: Extract<Res<crate::resources::ProjPositionState>>,
+    visuals: Extract<Res<crate::resources::ProjectileVisualConfig>>,
     gpu_buffers: Option<Res<ProjGpuBuffers>>,
-    existing_buffers: Option<ResMut<ProjRenderBuffers>>,
+    mut existing_buffers: Option<ResMut<ProjRenderBuffers>>,
     render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
 ) {
@@ -1639,6 +1811,7 @@ fn extract_proj_data(
                     &[idx],
                     1,
                 );
+                write_dirty_f32(&render_queue, &gpu_bufs.arcs, &writes.arcs, &[idx], 4);
                 write_dirty_i32(&render_queue, &gpu_bufs.active, &writes.active, &[idx], 1);
                 write_dirty_i32(&render_queue, &gpu_bufs.hits, &writes.hits, &[idx], 2);
             }
@@ -1659,8 +1832,22 @@ fn extract_proj_data(
 
     // --- Build projectile instance buffer for rendering ---
     let readback_positions = &proj_pos_state.0;
+    let mut trails = existing_buffers
+        .as_mut()
+        .map(|b| std::mem::take(&mut b.trails))
+        .unwrap_or_default();
+    let max_trail = visuals.max_trail();
+    if max_trail > 0 {
+        trails.ensure(writes.active.len());
+        for &idx in &writes.spawn_dirty_indices {
+            trails.reset(idx);
+        }
+    }
 
+    // Trail quads ride in the same buffer: reserve the worst case up front so the
+    // allocation covers every head plus its trail.
     let mut instances = RawBufferVec::new(BufferUsages::VERTEX);
+    instances.reserve(writes.active_set.len() * (1 + max_trail), &render_device);
     for &i in &writes.active_set {
         let i2 = i * 2;
 
@@ -1676,28 +1863,58 @@ fn extract_proj_data(
             continue;
         }
 
-        let faction = writes.factions[i];
-        let (cr, cg, cb) = if faction == crate::constants::FACTION_PLAYER {
-            (0.0, 0.0, 1.0)
-        } else {
-            let (r, g, b, _) = crate::constants::raider_faction_color(faction);
-            (r, g, b)
+        let visual = visuals.get(writes.kinds[i]);
+        let (cr, cg, cb) = match visual.color {
+            Some([r, g, b]) => (r, g, b),
+            None => {
+                let faction = writes.factions[i];
+                if faction == crate::constants::FACTION_PLAYER {
+                    (0.0, 0.0, 1.0)
+                } else {
+                    let (r, g, b, _) = crate::constants::raider_faction_color(faction);
+                    (r, g, b)
+                }
+            }
         };
 
         let vx = writes.velocities[i2];
         let vy = writes.velocities[i2 + 1];
         let angle = vy.atan2(vx) - std::f32::consts::FRAC_PI_2;
-
-        instances.push(InstanceData {
+        let head = InstanceData {
             position: [px, py],
             sprite: [0.0, 0.0],
             color: [cr, cg, cb, 1.0],
             health: 1.0,
             flash: 0.0,
-            scale: 32.0,
+            scale: 1.0,
             atlas_id: 4.0,
             rotation: angle,
-        });
+            stretch: [visual.size.x, visual.size.y],
+        };
+
+        // Oldest first so the head draws on top of its trail
+        if visual.trail_len > 0 {
+            let n = visual.trail_len;
+            let mut trail: [[f32; 2]; crate::constants::PROJ_TRAIL_MAX] = Default::default();
+            let mut count = 0;
+            for p in trails.recent(i, n) {
+                trail[count] = p;
+                count += 1;
+            }
+            for k in (0..count).rev() {
+                let fade = 1.0 - (k + 1) as f32 / (n + 1) as f32;
+                instances.push(InstanceData {
+                    position: trail[k],
+                    color: [cr, cg, cb, fade],
+                    scale: 0.6 + 0.4 * fade,
+                    ..head
+                });
+            }
+        }
+        if max_trail > 0 {
+            trails.record(i, [px, py]);
+        }
+        instances.push(head);
     }
 
     let actual_count = instances.len() as u32;
@@ -1706,10 +1923,12 @@ fn extract_proj_data(
     if let Some(mut buffers) = existing_buffers {
         buffers.instance_buffer = instances;
         buffers.instance_count = actual_count;
+        buffers.trails = trails;
     } else {
         commands.insert_resource(ProjRenderBuffers {
             instance_buffer: instances,
             instance_count: actual_count,
+            trails,
         });
     }
 
@@ -1917,16 +2136,40 @@ fn prepare_npc_camera_bind_group(
         return;
     };
 
+    let layouts = |t: &crate::gpu::NpcSpriteTexture| {
+        (
+            t.char_layout,
+            t.world_layout,
+            t.extras_layout,
+            t.building_layout,
+        )
+    };
+    let (char_layout, world_layout, extras_layout, building_layout) = config
+        .as_ref()
+        .map_or_else(|| layouts(&Default::default()), |c| layouts(&c.textures));
     let uniform = CameraUniform {
         camera_pos: camera_state.position,
         zoom: camera_state.zoom,
         entity_count: config.map(|c| c.npc.count).unwrap_or(0),
         viewport: camera_state.viewport,
-        bldg_layers: (crate::constants::BUILDING_REGISTRY.len()
-            + crate::constants::autotile_total_extra_layers()) as f32,
-        extras_cols: 4.0,
         lod_zoom: camera_state.lod_zoom,
-        _pad: 0,
+        pixel_snap: camera_state.pixel_snap,
+        char_atlas: char_layout.grid(),
+        world_atlas: world_layout.grid(),
+        extras_atlas: extras_layout.grid(),
+        building_atlas: building_layout.grid(),
+        atlas_sizes: Vec4::new(
+            char_layout.sheet_w,
+            char_layout.sheet_h,
+            world_layout.sheet_w,
+            world_layout.sheet_h,
+        ),
+        extra_atlas_sizes: Vec4::new(
+            extras_layout.sheet_w,
+            extras_layout.sheet_h,
+            building_layout.sheet_w,
+            building_layout.sheet_h,
+        ),
     };
 
     let mut buffer = UniformBuffer::from(uniform);
@@ -2190,6 +2433,11 @@ fn instance_vertex_layout() -> VertexBufferLayout {
                 offset: 48,
                 shader_location: 9,
             },
+            VertexAttribute {
+                format: bevy::render::render_resource::VertexFormat::Float32x2,
+                offset: 52,
+                shader_location: 10,
+            },
         ],
     }
 }
@@ -8,13 +8,12 @@ use bevy::prelude::*;
 
 use bevy::sprite_render::{AlphaMode2d, TileData, TilemapChunk, TilemapChunkTileData};
 
-use crate::components::{
-    Activity, ActivityKind, Building, Dead, Faction, GpuSlot, Job, ManualTarget, MinerHomeConfig,
-    NpcFlags, SquadId,
-};
+use crate::components::{Activity, ActivityKind, ManualTarget, MinerHomeConfig, NpcFlags, SquadId};
 use crate::gpu::RenderFrameConfig;
 use crate::messages::{SelectFactionMsg, TerrainDirtyMsg};
-use crate::resources::{EntityMap, LeftPanelTab, SelectedBuilding, SelectedNpc, UiState};
+use crate::resources::{
+    BehaviorLod, EntityMap, LeftPanelTab, SelectedBuilding, SelectedNpc, UiState,
+};
 use crate::settings::{ControlAction, UserSettings};
 use crate::world::{
     BuildingKind, TERRAIN_TILES, WorldData, WorldGrid, build_building_atlas, build_extras_atlas,
@@ -72,6 +71,55 @@ pub struct NpcSprite {
 #[derive(Component)]
 pub struct MainCamera;
 
+/// Which runtime-swappable sprite sheet an `AtlasSwap` targets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AtlasTarget {
+    /// NPC bodies + equipment (atlas_id 0).
+    Character,
+    /// Farms and carried-item icons (atlas_id 1).
+    World,
+    /// Item icon strip: heal, sleep, arrow, boat (atlas_ids 2-4, 8).
+    Item,
+    /// Building (location) strip, one layer per row (atlas_id 7).
+    Location,
+}
+
+/// A requested sprite sheet swap. `path` None keeps the current texture and only changes the
+/// layout; otherwise the new texture is loaded and swapped in once the asset server reports it
+/// loaded (a failed load logs an error and keeps the current atlas).
+#[derive(Clone, Debug)]
+pub struct AtlasSwap {
+    pub target: AtlasTarget,
+    pub path: Option<String>,
+    pub layout: crate::gpu::AtlasLayout,
+}
+
+/// Atlas swaps queued by `endless/sprite_atlas`, applied by `atlas_swap_system`.
+#[derive(Resource, Default)]
+pub struct AtlasSwapQueue {
+    pub requests: Vec<AtlasSwap>,
+    /// Swaps waiting on their texture to finish loading.
+    pub pending: Vec<(AtlasSwap, Handle<Image>)>,
+    /// Last applied item/location swap per target. Those atlases are composited at world
+    /// spawn, so `spawn_world_tilemap` re-queues these over the fresh composite.
+    pub composited: Vec<AtlasSwap>,
+}
+
+impl AtlasSwapQueue {
+    fn remember_composited(&mut self, swap: &AtlasSwap) {
+        if !matches!(swap.target, AtlasTarget::Item | AtlasTarget::Location) {
+            return;
+        }
+        let mut swap = swap.clone();
+        if let Some(i) = self.composited.iter().position(|s| s.target == swap.target) {
+            // A layout-only change keeps the texture an earlier swap loaded
+            let prev = self.composited.swap_remove(i);
+            swap.path = swap.path.or(prev.path);
+        }
+        self.composited.push(swap);
+    }
+}
+
 /// Camera state for the render world — extracted from Bevy camera each frame.
 /// Not used in the main world; input systems write to Transform + Projection directly.
 #[derive(Resource, Clone)]
@@ -80,6 +128,22 @@ pub struct CameraState {
     pub zoom: f32,
     pub viewport: Vec2,
     pub lod_zoom: f32,
+    /// Physical pixels per viewport unit to snap sprite centers to, 0 = off. See `pixel_snap_scale`.
+    pub pixel_snap: f32,
+}
+
+/// Pixel snapping switches itself off from this zoom up: a texel spans several screen pixels,
+/// so sub-pixel motion is visible and snapping would make slow walkers step.
+pub const PIXEL_SNAP_MAX_ZOOM: f32 = 2.0;
+
+/// Snap grid handed to the sprite shader: the window scale factor while snapping is on and the
+/// camera is below `PIXEL_SNAP_MAX_ZOOM`, otherwise 0 (smooth sub-pixel placement).
+pub fn pixel_snap_scale(enabled: bool, zoom: f32, scale_factor: f32) -> f32 {
+    if enabled && zoom < PIXEL_SNAP_MAX_ZOOM {
+        scale_factor
+    } else {
+        0.0
+    }
 }
 
 const EDGE_PAN_MARGIN: f32 = 8.0;
@@ -94,6 +158,7 @@ impl Plugin for RenderPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SpriteAssets>()
             .init_resource::<TilemapSpawned>()
+            .init_resource::<AtlasSwapQueue>()
             .add_systems(Startup, (setup_camera, load_sprites))
             .add_systems(
                 Update,
@@ -103,11 +168,14 @@ impl Plugin for RenderPlugin {
                     camera_edge_pan_system,
                     camera_zoom_system,
                     camera_follow_system,
+                    behavior_lod_camera_system.after(camera_follow_system),
                     click_to_select_system,
+                    npc_highlight_system.after(click_to_select_system),
                     box_select_system,
                     spawn_world_tilemap,
                     sync_terrain_tilemap,
                     sync_terrain_visibility,
+                    atlas_swap_system,
                 ),
             );
     }
@@ -180,6 +248,114 @@ fn load_sprites(
     );
 }
 
+/// Apply queued sprite sheet swaps. Layout-only swaps apply immediately; texture swaps wait for
+/// the new image to load, keep the current atlas on failure, and take the sheet size from the
+/// loaded image when it differs from the request. NPC quad size is a per-instance world size,
+/// independent of sprite texels, so world scale is unchanged by a new sprite size.
+fn atlas_swap_system(
+    mut queue: ResMut<AtlasSwapQueue>,
+    mut config: ResMut<RenderFrameConfig>,
+    mut assets: ResMut<SpriteAssets>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+) {
+    if queue.requests.is_empty() && queue.pending.is_empty() {
+        return;
+    }
+    for swap in std::mem::take(&mut queue.requests) {
+        match &swap.path {
+            Some(path) => {
+                let handle = asset_server.load(path.clone());
+                queue.pending.push((swap, handle));
+            }
+            None => {
+                apply_atlas_swap(&mut config, &mut assets, &swap, None);
+                queue.remember_composited(&swap);
+            }
+        }
+    }
+
+    let mut still_pending = Vec::new();
+    for (mut swap, handle) in std::mem::take(&mut queue.pending) {
+        match asset_server.load_state(handle.id()) {
+            bevy::asset::LoadState::Loaded => {
+                if let Some(image) = images.get(&handle) {
+                    let size = image.size_f32();
+                    if size != Vec2::new(swap.layout.sheet_w, swap.layout.sheet_h) {
+                        warn!(
+                            "sprite atlas {:?}: requested sheet {}x{}, image is {}x{} — using image size",
+                            swap.target, swap.layout.sheet_w, swap.layout.sheet_h, size.x, size.y
+                        );
+                        swap.layout.sheet_w = size.x;
+                        swap.layout.sheet_h = size.y;
+                    }
+                }
+                if let Err(e) = swap.layout.validate() {
+                    error!(
+                        "sprite atlas {:?}: {e} — keeping current atlas",
+                        swap.target
+                    );
+                    continue;
+                }
+                apply_atlas_swap(&mut config, &mut assets, &swap, Some(handle));
+                queue.remember_composited(&swap);
+            }
+            bevy::asset::LoadState::Failed(err) => {
+                error!(
+                    "sprite atlas {:?}: failed to load '{}': {err} — keeping current atlas",
+                    swap.target,
+                    swap.path.as_deref().unwrap_or("?")
+                );
+            }
+            _ => still_pending.push((swap, handle)),
+        }
+    }
+    queue.pending = still_pending;
+}
+
+fn apply_atlas_swap(
+    config: &mut RenderFrameConfig,
+    assets: &mut SpriteAssets,
+    swap: &AtlasSwap,
+    handle: Option<Handle<Image>>,
+) {
+    let textures = &mut config.textures;
+    match swap.target {
+        AtlasTarget::Character => {
+            textures.char_layout = swap.layout;
+            if let Some(h) = handle {
+                assets.char_texture = h.clone();
+                textures.handle = Some(h);
+            }
+        }
+        AtlasTarget::World => {
+            textures.world_layout = swap.layout;
+            if let Some(h) = handle {
+                assets.world_texture = h.clone();
+                textures.world_handle = Some(h);
+            }
+        }
+        AtlasTarget::Item => {
+            textures.extras_layout = swap.layout;
+            if let Some(h) = handle {
+                textures.extras_handle = Some(h);
+            }
+        }
+        AtlasTarget::Location => {
+            textures.building_layout = swap.layout;
+            if let Some(h) = handle {
+                textures.building_handle = Some(h);
+            }
+        }
+    }
+    info!(
+        "sprite atlas {:?} -> {} ({:?})",
+        swap.target,
+        swap.path.as_deref().unwrap_or("current texture"),
+        swap.layout
+    );
+}
+
 // =============================================================================
 // CAMERA SYSTEMS
 // =============================================================================
@@ -192,6 +368,22 @@ fn ortho_zoom(projection: &Projection) -> f32 {
     }
 }
 
+/// Publish the camera's visible world rect for behavior LOD (`BehaviorLod`).
+fn behavior_lod_camera_system(
+    windows: Query<&Window>,
+    query: Query<(&Transform, &Projection), With<MainCamera>>,
+    mut lod: ResMut<BehaviorLod>,
+) {
+    let Ok(window) = windows.single() else { return };
+    let Ok((transform, projection)) = query.single() else {
+        return;
+    };
+    let zoom = ortho_zoom(projection).max(0.001);
+    lod.camera_center = transform.translation.truncate();
+    lod.view_half = Vec2::new(window.width(), window.height()) * 0.5 / zoom;
+    lod.camera_known = true;
+}
+
 /// Keyboard camera pan. Speed scales inversely with zoom for consistent screen-space feel.
 /// Uses wall-clock delta (not game-scaled DeltaTime) so camera speed is independent of game speed.
 fn camera_pan_system(
@@ -389,30 +581,68 @@ fn camera_zoom_system(
     transform.translation.y = new_position.y;
 }
 
-/// Track the camera to the selected NPC when follow mode is active.
+/// Where the camera should head to keep `target` inside the dead zone (half-size
+/// `dead_zone`, world px) around `camera`: unchanged while inside, else just far enough.
+fn follow_goal(camera: Vec2, target: Vec2, dead_zone: Vec2) -> Vec2 {
+    let offset = target - camera;
+    let excess = (offset.abs() - dead_zone.max(Vec2::ZERO)).max(Vec2::ZERO);
+    camera + excess * offset.signum()
+}
+
+/// One frame of smoothed follow. `smoothing` is the gap fraction closed per 1/60 s, so the
+/// feel doesn't depend on frame rate; >= 1 snaps.
+fn follow_step(camera: Vec2, goal: Vec2, smoothing: f32, dt: f32) -> Vec2 {
+    if smoothing >= 1.0 {
+        return goal;
+    }
+    let t = 1.0 - (1.0 - smoothing.max(0.0)).powf(dt * 60.0);
+    camera.lerp(goal, t)
+}
+
+/// Track the camera to the selected NPC when follow mode is active. Smoothed with a dead
+/// zone per `CameraFollowConfig`; snaps when the followed unit changes or teleports.
 fn camera_follow_system(
     selected: Res<SelectedNpc>,
     follow: Res<crate::resources::FollowSelected>,
     gpu_state: Res<crate::resources::GpuReadState>,
-    mut query: Query<&mut Transform, With<MainCamera>>,
+    user_settings: Res<UserSettings>,
+    time: Res<Time>,
+    mut last: Local<Option<(usize, Vec2)>>,
+    mut query: Query<(&mut Transform, &Projection), With<MainCamera>>,
 ) {
     if !follow.0 || selected.0 < 0 {
+        *last = None;
         return;
     }
     let idx = selected.0 as usize;
-    let positions = &gpu_state.positions;
-    if idx * 2 + 1 >= positions.len() {
-        return;
-    }
-    let x = positions[idx * 2];
-    let y = positions[idx * 2 + 1];
-    if x < -9000.0 {
+    let Some(target) = gpu_slot_position(&gpu_state.positions, idx) else {
+        return; // dead/hidden
+    };
+    let Ok((mut transform, projection)) = query.single_mut() else {
         return;
-    } // dead/hidden
-    if let Ok(mut transform) = query.single_mut() {
-        transform.translation.x = x;
-        transform.translation.y = y;
-    }
+    };
+    let snap = match *last {
+        Some((slot, prev)) => {
+            slot != idx || prev.distance(target) > crate::constants::CAMERA_FOLLOW_SNAP_DIST
+        }
+        None => true,
+    };
+    *last = Some((idx, target));
+
+    let camera = transform.translation.truncate();
+    let pos = if snap {
+        target
+    } else {
+        let cfg = user_settings.camera_follow;
+        let scale = match projection {
+            Projection::Orthographic(ortho) => ortho.scale,
+            _ => 1.0,
+        };
+        let goal = follow_goal(camera, target, Vec2::from(cfg.dead_zone) * scale);
+        follow_step(camera, goal, cfg.smoothing, time.delta_secs())
+    };
+    transform.translation.x = pos.x;
+    transform.translation.y = pos.y;
 }
 
 /// Tracks last click for double-click detection.
@@ -795,6 +1025,54 @@ fn click_to_select_system(
 // BOX SELECT
 // =============================================================================
 
+/// Sync the shader body highlight with the selected NPC, the hovered NPC (when
+/// `NpcHighlight::hover` is on), and scripted highlights. Only changed slots are written.
+fn npc_highlight_system(
+    windows: Query<&Window>,
+    camera_query: Query<(&Transform, &Projection), With<MainCamera>>,
+    mut egui_contexts: bevy_egui::EguiContexts,
+    entity_map: Res<EntityMap>,
+    gpu_state: Res<crate::resources::GpuReadState>,
+    selected: Res<SelectedNpc>,
+    mut highlight: ResMut<crate::resources::NpcHighlight>,
+    mut gpu_updates: MessageWriter<crate::messages::GpuUpdateMsg>,
+) {
+    let live = |slot: usize| entity_map.get_npc(slot).is_some_and(|n| !n.dead);
+    // Scripted highlights end with the unit
+    highlight.scripted.retain(|&slot| live(slot));
+
+    let mut hovered = None;
+    let over_ui = egui_contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.is_pointer_over_area());
+    if highlight.hover && !over_ui {
+        if let (Ok(window), Ok((transform, projection))) = (windows.single(), camera_query.single())
+        {
+            if let Some(cursor_pos) = window.cursor_position() {
+                let zoom = ortho_zoom(projection);
+                let screen_center = Vec2::new(window.width(), window.height()) / 2.0;
+                let mouse_offset = Vec2::new(
+                    cursor_pos.x - screen_center.x,
+                    screen_center.y - cursor_pos.y,
+                );
+                let world_pos = transform.translation.truncate() + mouse_offset / zoom;
+                hovered =
+                    nearest_npc_hit(&entity_map, &gpu_state.positions, world_pos, 40.0, |_| true)
+                        .map(|hit| hit.slot);
+            }
+        }
+    }
+    highlight.hovered = hovered;
+
+    let selected = usize::try_from(selected.0).ok().filter(|&slot| live(slot));
+    let want = highlight.desired(selected);
+    for (idx, strength) in highlight.sync(want) {
+        gpu_updates.write(crate::messages::GpuUpdateMsg(
+            crate::messages::GpuUpdate::SetHighlight { idx, strength },
+        ));
+    }
+}
+
 /// Runs every frame to track box-select drag state.
 /// Left-press starts drag, movement > 5px activates box mode,
 /// release selects all player NPCs in the AABB and populates the active squad.
@@ -810,7 +1088,6 @@ fn box_select_system(
     mut selected_building: ResMut<crate::resources::SelectedBuilding>,
     mut commands: Commands,
     mut npc_flags_q: Query<&mut NpcFlags>,
-    box_npc_q: Query<(&GpuSlot, &Job, &Faction), (Without<Building>, Without<Dead>)>,
     gpu_state: Res<crate::resources::GpuReadState>,
 ) {
     // Don't box-select while building or placing squad targets
@@ -853,40 +1130,21 @@ fn box_select_system(
             if dist > 5.0 {
                 squad_state.box_selecting = true;
             }
+            squad_state.drag_end = Some(world_pos);
         }
     }
 
     if mouse.just_released(MouseButton::Left) {
         if squad_state.box_selecting {
             if let Some(start) = squad_state.drag_start {
-                let min_x = start.x.min(world_pos.x);
-                let max_x = start.x.max(world_pos.x);
-                let min_y = start.y.min(world_pos.y);
-                let max_y = start.y.max(world_pos.y);
-
-                let positions = &gpu_state.positions;
-                let mut selected_slots: Vec<usize> = Vec::new();
-                for (slot, job, faction) in box_npc_q.iter() {
-                    // Player faction is 1; 0 is neutral and should never box-select.
-                    if faction.0 != crate::constants::FACTION_PLAYER {
-                        continue;
-                    }
-                    if !job.is_military() {
-                        continue;
-                    }
-                    let i = slot.0;
-                    if i * 2 + 1 >= positions.len() {
-                        continue;
-                    }
-                    let px = positions[i * 2];
-                    let py = positions[i * 2 + 1];
-                    if px < -9000.0 {
-                        continue;
-                    }
-                    if px >= min_x && px <= max_x && py >= min_y && py <= max_y {
-                        selected_slots.push(i);
-                    }
-                }
+                // Player faction is 1; 0 is neutral and should never box-select.
+                let selected_slots = entity_map.npcs_in_rect(
+                    &gpu_state.positions,
+                    start,
+                    world_pos,
+                    crate::constants::SELECT_RECT_MAX,
+                    |n| n.faction == crate::constants::FACTION_PLAYER && n.job.is_military(),
+                );
 
                 if !selected_slots.is_empty() {
                     let selected_set: std::collections::HashSet<usize> =
@@ -948,6 +1206,7 @@ fn box_select_system(
             }
         }
         squad_state.drag_start = None;
+        squad_state.drag_end = None;
         squad_state.box_selecting = false;
     }
 }
@@ -985,6 +1244,7 @@ fn spawn_world_tilemap(
     mut images: ResMut<Assets<Image>>,
     mut spawned: ResMut<TilemapSpawned>,
     mut config: ResMut<RenderFrameConfig>,
+    mut swaps: ResMut<AtlasSwapQueue>,
 ) {
     if spawned.0 || grid.width == 0 {
         return;
@@ -1055,6 +1315,9 @@ fn spawn_world_tilemap(
         );
     }
     config.textures.building_handle = Some(building_atlas);
+    config.textures.building_layout = crate::gpu::AtlasLayout::building(
+        btiles.len() + crate::constants::autotile_total_extra_layers(),
+    );
 
     // Extras atlas: composites heal, sleep, arrow, boat into a single grid texture
     let extras_imgs: Option<Vec<Image>> = assets
@@ -1064,7 +1327,11 @@ fn spawn_world_tilemap(
         .collect();
     if let Some(extras_imgs) = extras_imgs {
         config.textures.extras_handle = Some(build_extras_atlas(&extras_imgs, &mut images));
+        config.textures.extras_layout = crate::gpu::AtlasLayout::EXTRAS;
     }
+    // Item/location swaps outlive the composite they replaced
+    let reapply = swaps.composited.clone();
+    swaps.requests.extend(reapply);
 
     info!(
         "World tilemap spawned: {}x{} grid ({} terrain chunks)",
@@ -1118,6 +1385,40 @@ fn sync_terrain_visibility(
 mod tests {
     use super::*;
 
+    #[test]
+    fn pixel_snap_only_below_the_zoom_cutoff() {
+        assert_eq!(pixel_snap_scale(true, 0.75, 1.5), 1.5);
+        assert_eq!(pixel_snap_scale(false, 0.75, 1.5), 0.0);
+        assert_eq!(pixel_snap_scale(true, PIXEL_SNAP_MAX_ZOOM, 1.0), 0.0);
+        assert_eq!(pixel_snap_scale(true, 4.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn follow_holds_inside_dead_zone_and_eases_outside() {
+        let cam = Vec2::new(100.0, 100.0);
+        let zone = Vec2::new(50.0, 30.0);
+        assert_eq!(follow_goal(cam, Vec2::new(140.0, 80.0), zone), cam);
+        // Only far enough to bring the unit back to the zone edge
+        assert_eq!(
+            follow_goal(cam, Vec2::new(200.0, 40.0), zone),
+            Vec2::new(150.0, 70.0)
+        );
+
+        let goal = Vec2::new(200.0, 100.0);
+        let eased = follow_step(cam, goal, 0.2, 1.0 / 60.0);
+        assert!((eased.x - 120.0).abs() < 1e-3);
+        // Two half-length frames land where one full frame does
+        let half = follow_step(
+            follow_step(cam, goal, 0.2, 1.0 / 120.0),
+            goal,
+            0.2,
+            1.0 / 120.0,
+        );
+        assert!(half.distance(eased) < 1e-3);
+        assert_eq!(follow_step(cam, goal, 1.0, 1.0 / 60.0), goal);
+    }
+
+    use crate::components::{Faction, GpuSlot, Job};
     use bevy::time::TimeUpdateStrategy;
     use bevy_egui::EguiUserTextures;
 
@@ -8,7 +8,31 @@ use bevy::reflect::Reflect;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Poisoned-lock recoveries since startup (see `recover_or_log`).
+static LOCK_POISON_RECOVERIES: AtomicUsize = AtomicUsize::new(0);
+
+/// Lock `mutex`, recovering the guard if a panic on another thread poisoned it. The data is
+/// still valid for our plain-value locks, and skipping it would leave whatever the lock feeds
+/// frozen with no error. Clears the poison so only the first lock after each panic pays for
+/// it, and logs the 1st, 2nd, 4th, 8th... recovery so a panicking thread can't flood the log.
+pub fn recover_or_log<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        mutex.clear_poison();
+        let n = LOCK_POISON_RECOVERIES.fetch_add(1, Ordering::Relaxed) + 1;
+        if n.is_power_of_two() {
+            error!("lock '{name}' was poisoned by a panic; recovered ({n} recoveries so far)");
+        }
+        poisoned.into_inner()
+    })
+}
+
+/// Total poisoned-lock recoveries since startup.
+pub fn lock_poison_recoveries() -> usize {
+    LOCK_POISON_RECOVERIES.load(Ordering::Relaxed)
+}
 
 /// CLI flag: skip main menu and start a new game with saved settings.
 #[derive(Resource, Default)]
@@ -34,6 +58,8 @@ pub struct SystemTimings {
     pub frame_ms: Mutex<f32>,
     /// Rolling peak frame time (resets every PEAK_WINDOW frames).
     frame_peak: Mutex<(f32, u32)>,
+    /// Per-frame history for the frame-time graph (only written while `enabled`).
+    history: Mutex<PerfHistory>,
     pub enabled: bool,
 }
 
@@ -44,6 +70,7 @@ impl Default for SystemTimings {
             traced: Mutex::new(HashMap::new()),
             frame_ms: Mutex::new(0.0),
             frame_peak: Mutex::new((0.0, 0)),
+            history: Mutex::new(PerfHistory::default()),
             enabled: false,
         }
     }
@@ -54,16 +81,14 @@ impl SystemTimings {
     pub fn record_frame_delta(&self, dt_secs: f32) {
         if self.enabled {
             let ms = dt_secs * 1000.0;
-            if let Ok(mut fm) = self.frame_ms.lock() {
-                *fm = *fm * (1.0 - EMA_ALPHA) + ms * EMA_ALPHA;
-            }
-            if let Ok(mut fp) = self.frame_peak.lock() {
-                fp.0 = fp.0.max(ms);
-                fp.1 += 1;
-                if fp.1 >= 120 {
-                    fp.0 = ms;
-                    fp.1 = 0;
-                }
+            let mut fm = recover_or_log(&self.frame_ms, "frame_ms");
+            *fm = *fm * (1.0 - EMA_ALPHA) + ms * EMA_ALPHA;
+            let mut fp = recover_or_log(&self.frame_peak, "frame_peak");
+            fp.0 = fp.0.max(ms);
+            fp.1 += 1;
+            if fp.1 >= 120 {
+                fp.0 = ms;
+                fp.1 = 0;
             }
         }
     }
@@ -71,46 +96,164 @@ impl SystemTimings {
     /// Record a timing value directly (same EMA as scope guard).
     /// Use for accumulated sub-section timings recorded after a loop.
     pub fn record(&self, name: &'static str, ms: f32) {
-        if let Ok(mut data) = self.data.lock() {
-            let entry = data.entry(name).or_insert(0.0);
-            *entry = *entry * (1.0 - EMA_ALPHA) + ms * EMA_ALPHA;
-        }
+        let mut data = recover_or_log(&self.data, "timings");
+        let entry = data.entry(name).or_insert(0.0);
+        *entry = *entry * (1.0 - EMA_ALPHA) + ms * EMA_ALPHA;
     }
 
     /// Record a tracing-captured timing (from Bevy auto-spans).
     pub fn record_traced(&self, name: &str, ms: f32) {
-        if let Ok(mut traced) = self.traced.lock() {
-            let entry = traced.entry(name.to_string()).or_insert(0.0);
-            // Already EMA-smoothed by the tracing layer; just copy the latest value.
-            *entry = ms;
+        let mut traced = recover_or_log(&self.traced, "traced_timings");
+        // Already EMA-smoothed by the tracing layer; just copy the latest value.
+        // Only allocate the key the first time a system shows up.
+        match traced.get_mut(name) {
+            Some(entry) => *entry = ms,
+            None => {
+                traced.insert(name.to_string(), ms);
+            }
         }
     }
 
     pub fn get_timings(&self) -> HashMap<&'static str, f32> {
-        self.data.lock().map(|d| d.clone()).unwrap_or_default()
+        recover_or_log(&self.data, "timings").clone()
+    }
+
+    /// Sum of all non-traced timings, without cloning the map.
+    pub fn timings_total(&self) -> f32 {
+        recover_or_log(&self.data, "timings").values().sum()
     }
 
     pub fn get_traced_timings(&self) -> HashMap<String, f32> {
-        self.traced.lock().map(|d| d.clone()).unwrap_or_default()
+        recover_or_log(&self.traced, "traced_timings").clone()
     }
 
     /// Get per-system peak ms from the tracing layer's rolling window.
     pub fn get_traced_peaks(&self) -> HashMap<String, f32> {
-        crate::tracing_layer::TRACING_PEAKS
-            .lock()
-            .map(|p| p.iter().map(|(k, (peak, _))| (k.clone(), *peak)).collect())
-            .unwrap_or_default()
+        recover_or_log(&crate::tracing_layer::TRACING_PEAKS, "tracing_peaks")
+            .iter()
+            .map(|(k, (peak, _))| (k.clone(), *peak))
+            .collect()
     }
 
     pub fn get_frame_ms(&self) -> f32 {
-        self.frame_ms.lock().map(|f| *f).unwrap_or(0.0)
+        *recover_or_log(&self.frame_ms, "frame_ms")
     }
 
     pub fn get_frame_peak_ms(&self) -> f32 {
-        self.frame_peak.lock().map(|f| f.0).unwrap_or(0.0)
+        recover_or_log(&self.frame_peak, "frame_peak").0
+    }
+
+    /// Append one frame to the rolling history. No-op while the profiler is off.
+    pub fn record_history(&self, sample: PerfSample) {
+        if self.enabled {
+            recover_or_log(&self.history, "perf_history").push(sample);
+        }
+    }
+
+    /// Oldest-first copy of the rolling history as parallel arrays, with stats.
+    pub fn get_perf_history(&self) -> PerfHistorySnapshot {
+        recover_or_log(&self.history, "perf_history").snapshot()
+    }
+}
+
+/// Frames kept by `PerfHistory` (~4s at 60fps).
+pub const PERF_HISTORY_LEN: usize = 240;
+
+/// One frame of the profiler graph. Phases match the profiler tab groups:
+/// game = `endless::` systems, engine = other traced systems, render = render-world timings.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct PerfSample {
+    pub frame_ms: f32,
+    pub game_ms: f32,
+    pub engine_ms: f32,
+    pub render_ms: f32,
+}
+
+/// Fixed-size ring buffer of per-frame samples. Written once per frame, read on demand.
+pub struct PerfHistory {
+    samples: [PerfSample; PERF_HISTORY_LEN],
+    head: usize,
+    len: usize,
+}
+
+impl Default for PerfHistory {
+    fn default() -> Self {
+        Self {
+            samples: [PerfSample::default(); PERF_HISTORY_LEN],
+            head: 0,
+            len: 0,
+        }
+    }
+}
+
+impl PerfHistory {
+    pub fn push(&mut self, sample: PerfSample) {
+        self.samples[self.head] = sample;
+        self.head = (self.head + 1) % PERF_HISTORY_LEN;
+        self.len = (self.len + 1).min(PERF_HISTORY_LEN);
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Samples oldest → newest.
+    pub fn iter(&self) -> impl Iterator<Item = &PerfSample> {
+        let start = (self.head + PERF_HISTORY_LEN - self.len) % PERF_HISTORY_LEN;
+        (0..self.len).map(move |i| &self.samples[(start + i) % PERF_HISTORY_LEN])
+    }
+
+    pub fn snapshot(&self) -> PerfHistorySnapshot {
+        let mut snap = PerfHistorySnapshot::default();
+        for s in self.iter() {
+            snap.frame_ms.push(s.frame_ms);
+            snap.game_ms.push(s.game_ms);
+            snap.engine_ms.push(s.engine_ms);
+            snap.render_ms.push(s.render_ms);
+        }
+        snap
+    }
+}
+
+/// min/max/mean over a series, computed on read (all zero when empty).
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct PerfStats {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+impl PerfStats {
+    pub fn of(values: &[f32]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        let (min, max, sum) = values
+            .iter()
+            .fold((f32::MAX, f32::MIN, 0.0), |(lo, hi, sum), &v| {
+                (lo.min(v), hi.max(v), sum + v)
+            });
+        Self {
+            min,
+            max,
+            mean: sum / values.len() as f32,
+        }
     }
 }
 
+/// Parallel per-frame arrays (index i is the same frame in every series), oldest first.
+#[derive(Clone, Default, Debug)]
+pub struct PerfHistorySnapshot {
+    pub frame_ms: Vec<f32>,
+    pub game_ms: Vec<f32>,
+    pub engine_ms: Vec<f32>,
+    pub render_ms: Vec<f32>,
+}
+
 /// Delta time for the current frame (seconds).
 #[derive(Resource, Default)]
 pub struct DeltaTime(pub f32);
@@ -131,6 +274,132 @@ impl Default for NpcDecisionConfig {
     }
 }
 
+/// Work→rest→work hysteresis for one job: stop working below `rest_below`,
+/// stay resting until `resume_above`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnergyThreshold {
+    pub rest_below: f32,
+    pub resume_above: f32,
+}
+
+impl Default for EnergyThreshold {
+    fn default() -> Self {
+        Self {
+            rest_below: crate::constants::ENERGY_TIRED_THRESHOLD,
+            resume_above: crate::constants::ENERGY_WAKE_THRESHOLD,
+        }
+    }
+}
+
+impl EnergyThreshold {
+    /// Clamp into 0..=100 with `rest_below` at least `ENERGY_THRESHOLD_MIN_GAP` under
+    /// `resume_above` (otherwise units flip between rest and work every decision).
+    /// Returns the valid pair and whether anything had to change.
+    pub fn clamped(rest_below: f32, resume_above: f32) -> (Self, bool) {
+        use crate::constants::ENERGY_THRESHOLD_MIN_GAP as GAP;
+        let resume = resume_above.clamp(GAP, 100.0);
+        let t = Self {
+            rest_below: rest_below.clamp(0.0, resume - GAP),
+            resume_above: resume,
+        };
+        let changed = t.rest_below != rest_below || t.resume_above != resume_above;
+        (t, changed)
+    }
+
+    /// Idle units start scoring rest this far above `rest_below` (50 with the defaults),
+    /// so they top up before they'd be forced off the job.
+    pub fn rest_score_below(&self) -> f32 {
+        use crate::constants::{ENERGY_HUNGRY, ENERGY_TIRED_THRESHOLD};
+        (self.rest_below + ENERGY_HUNGRY - ENERGY_TIRED_THRESHOLD).min(self.resume_above)
+    }
+}
+
+/// Per-job energy thresholds read by `decision_system`. Jobs without an override use
+/// `ENERGY_TIRED_THRESHOLD` / `ENERGY_WAKE_THRESHOLD`.
+#[derive(Resource, Default)]
+pub struct EnergyThresholds {
+    pub per_job: HashMap<crate::components::Job, EnergyThreshold>,
+}
+
+impl EnergyThresholds {
+    pub fn get(&self, job: crate::components::Job) -> EnergyThreshold {
+        self.per_job.get(&job).copied().unwrap_or_default()
+    }
+
+    /// Set a job's thresholds, clamped to a valid hysteresis band. Returns the stored
+    /// pair and whether the input was clamped.
+    pub fn set(
+        &mut self,
+        job: crate::components::Job,
+        rest_below: f32,
+        resume_above: f32,
+    ) -> (EnergyThreshold, bool) {
+        let (t, clamped) = EnergyThreshold::clamped(rest_below, resume_above);
+        self.per_job.insert(job, t);
+        (t, clamped)
+    }
+}
+
+/// Behavior level-of-detail. NPCs outside the camera view (plus `margin`) that are not
+/// fighting run decision/energy `stride`× less often, bucketed by slot; energy integrates the
+/// skipped time so it still converges. Combat, starvation and everything on screen run at
+/// full rate. The camera rect is written by `behavior_lod_camera_system`; without a camera
+/// (headless tests, benches) nothing is considered far. Off by default until an in-game
+/// measurement shows it pays for the gate (see docs/performance.md).
+#[derive(Resource)]
+pub struct BehaviorLod {
+    pub enabled: bool,
+    /// Far NPCs update once per `stride` of their normal cadence (1 = no LOD).
+    pub stride: usize,
+    /// World-space margin beyond the view edge still treated as near.
+    pub margin: f32,
+    pub camera_center: Vec2,
+    pub view_half: Vec2,
+    pub camera_known: bool,
+}
+
+impl Default for BehaviorLod {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stride: crate::constants::BEHAVIOR_LOD_STRIDE,
+            margin: crate::constants::BEHAVIOR_LOD_MARGIN,
+            camera_center: Vec2::ZERO,
+            view_half: Vec2::ZERO,
+            camera_known: false,
+        }
+    }
+}
+
+impl BehaviorLod {
+    /// Effective stride (1 when LOD is off or no camera has been seen).
+    pub fn active_stride(&self) -> usize {
+        if self.enabled && self.camera_known {
+            self.stride.max(1)
+        } else {
+            1
+        }
+    }
+
+    /// True when `pos` is outside the view rect expanded by `margin`.
+    pub fn is_far(&self, pos: Vec2) -> bool {
+        if self.active_stride() <= 1 {
+            return false;
+        }
+        let d = (pos - self.camera_center).abs();
+        d.x > self.view_half.x + self.margin || d.y > self.view_half.y + self.margin
+    }
+
+    /// Update stride for an NPC at `pos` (1 = every tick of its normal cadence).
+    pub fn stride_at(&self, pos: Vec2, fighting: bool) -> usize {
+        if fighting || !self.is_far(pos) {
+            1
+        } else {
+            self.active_stride()
+        }
+    }
+}
+
 /// Population counts per (job_id, clan_id).
 #[derive(Default, Clone)]
 pub struct PopStats {
@@ -177,6 +446,12 @@ pub struct GameTime {
     pub paused: bool,
     pub last_hour: i32,    // Previous hour (for detecting hour ticks)
     pub hour_ticked: bool, // True if hour just changed this frame
+    /// Clock anchor: game hours elapsed at `origin_seconds`. Moved by `set_day_length` so a
+    /// rate change never jumps the clock.
+    pub origin_hours: f32,
+    pub origin_seconds: f32,
+    pub dawn_hour: i32, // Daytime is dawn_hour..dusk_hour
+    pub dusk_hour: i32,
 }
 
 impl GameTime {
@@ -195,8 +470,13 @@ impl GameTime {
         }
     }
 
+    /// Fractional game hours since start.
+    pub fn elapsed_hours(&self) -> f32 {
+        self.origin_hours + (self.total_seconds - self.origin_seconds) / self.seconds_per_hour
+    }
+
     pub fn total_hours(&self) -> i32 {
-        (self.total_seconds / self.seconds_per_hour) as i32
+        self.elapsed_hours() as i32
     }
 
     pub fn day(&self) -> i32 {
@@ -208,13 +488,32 @@ impl GameTime {
     }
 
     pub fn minute(&self) -> i32 {
-        let seconds_into_hour = self.total_seconds % self.seconds_per_hour;
-        ((seconds_into_hour / self.seconds_per_hour) * 60.0) as i32
+        (self.elapsed_hours().fract() * 60.0) as i32
     }
 
     pub fn is_daytime(&self) -> bool {
         let h = self.hour();
-        (6..20).contains(&h)
+        (self.dawn_hour..self.dusk_hour).contains(&h)
+    }
+
+    /// Real seconds per game day at `time_scale` 1.
+    pub fn day_length(&self) -> f32 {
+        self.seconds_per_hour * 24.0
+    }
+
+    /// Change how fast the clock runs. Re-anchors first so day/hour/minute stay continuous;
+    /// `total_seconds` (used for timers) is untouched.
+    pub fn set_day_length(&mut self, real_seconds: f32) {
+        use crate::constants::{DAY_LENGTH_MAX_SECS, DAY_LENGTH_MIN_SECS};
+        self.origin_hours = self.elapsed_hours();
+        self.origin_seconds = self.total_seconds;
+        self.seconds_per_hour = real_seconds.clamp(DAY_LENGTH_MIN_SECS, DAY_LENGTH_MAX_SECS) / 24.0;
+    }
+
+    /// Set the daytime window. Hours are clamped to 0..=24 and dusk kept after dawn.
+    pub fn set_daylight(&mut self, dawn_hour: i32, dusk_hour: i32) {
+        self.dawn_hour = dawn_hour.clamp(0, 23);
+        self.dusk_hour = dusk_hour.clamp(self.dawn_hour + 1, 24);
     }
 }
 
@@ -228,6 +527,10 @@ impl Default for GameTime {
             last_hour: 0,
             hour_ticked: false,
             paused: false,
+            origin_hours: 0.0,
+            origin_seconds: 0.0,
+            dawn_hour: crate::constants::DEFAULT_DAWN_HOUR,
+            dusk_hour: crate::constants::DEFAULT_DUSK_HOUR,
         }
     }
 }
@@ -253,6 +556,31 @@ pub struct KillStats {
     pub villager_kills: i32, // Villagers (farmers/archers) killed by raiders
 }
 
+/// Round-robin cursor for "select next idle unit" (hotkey and `endless/select_next_idle`).
+/// Remembers the last slot picked rather than a list position, so units joining or leaving
+/// the idle set between calls never make the cycle skip anyone.
+#[derive(Resource, Default)]
+pub struct IdleCycle {
+    pub town: Option<usize>,
+    pub last_slot: Option<usize>,
+}
+
+impl IdleCycle {
+    /// Next slot after the last pick in `idle` (ascending), wrapping. Switching towns restarts.
+    pub fn next(&mut self, town: usize, idle: &[usize]) -> Option<usize> {
+        if self.town != Some(town) {
+            self.town = Some(town);
+            self.last_slot = None;
+        }
+        let pick = self
+            .last_slot
+            .and_then(|last| idle.iter().copied().find(|&s| s > last))
+            .or_else(|| idle.first().copied());
+        self.last_slot = pick;
+        pick
+    }
+}
+
 /// Currently selected NPC index (-1 = none).
 #[derive(Resource)]
 pub struct SelectedNpc(pub i32);
@@ -288,6 +616,140 @@ pub struct ReturningSet(pub HashSet<Entity>);
 #[derive(Resource, Default)]
 pub struct FollowSelected(pub bool);
 
+/// Shader body highlight for NPCs: the selected unit, optionally the hovered one, and any
+/// scripted slots (`endless/npc_highlight`, e.g. a tutorial pointing at a unit).
+/// `applied` mirrors what was last sent to the GPU, so a slot that stops being highlighted is
+/// cleared explicitly — even when that NPC is off-screen or dead.
+#[derive(Resource)]
+pub struct NpcHighlight {
+    pub selection: bool,
+    pub hover: bool,
+    pub hovered: Option<usize>,
+    pub scripted: HashSet<usize>,
+    pub applied: HashMap<usize, f32>,
+}
+
+impl Default for NpcHighlight {
+    fn default() -> Self {
+        Self {
+            selection: true,
+            hover: false,
+            hovered: None,
+            scripted: HashSet::new(),
+            applied: HashMap::new(),
+        }
+    }
+}
+
+impl NpcHighlight {
+    /// Full strength for scripted/selected slots, half for hover.
+    pub const SELECTED: f32 = 1.0;
+    pub const HOVERED: f32 = 0.5;
+
+    /// Strength each slot should have now. `selected` is the live selected slot, if any.
+    pub fn desired(&self, selected: Option<usize>) -> HashMap<usize, f32> {
+        let mut want: HashMap<usize, f32> = HashMap::new();
+        if self.hover {
+            if let Some(slot) = self.hovered {
+                want.insert(slot, Self::HOVERED);
+            }
+        }
+        if self.selection {
+            if let Some(slot) = selected {
+                want.insert(slot, Self::SELECTED);
+            }
+        }
+        for &slot in &self.scripted {
+            want.insert(slot, Self::SELECTED);
+        }
+        want
+    }
+
+    /// GPU writes `(slot, strength)` that move `applied` to `want`: clears for slots that
+    /// dropped out, sets for new or changed ones. Updates `applied`.
+    pub fn sync(&mut self, want: HashMap<usize, f32>) -> Vec<(usize, f32)> {
+        let mut writes: Vec<(usize, f32)> = self
+            .applied
+            .keys()
+            .filter(|slot| !want.contains_key(slot))
+            .map(|&slot| (slot, 0.0))
+            .collect();
+        writes.extend(
+            want.iter()
+                .filter(|&(slot, s)| self.applied.get(slot) != Some(s))
+                .map(|(&slot, &s)| (slot, s)),
+        );
+        writes.sort_by_key(|&(slot, _)| slot);
+        self.applied = want;
+        writes
+    }
+}
+
+/// Which NPC compute passes run. `Scripted` (cutscenes) skips the grid, separation,
+/// goal-seeking and combat passes and integrates positions straight from per-NPC scripted
+/// velocities (`GpuUpdate::SetVelocity`). Normal mode never reads those velocities.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ComputeMode {
+    #[default]
+    Normal,
+    Scripted,
+}
+
+/// Which backend steps NPC movement and combat targeting. `Cpu` is the slow fallback when GPU
+/// compute is unavailable (see `cpu_compute`); the UI warns while it is active.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComputeBackend {
+    #[default]
+    Gpu,
+    Cpu,
+}
+
+/// Scripted motion for cutscenes (`endless/compute_mode`, `endless/npc_velocity`).
+/// `moving` tracks slots with a non-zero scripted velocity so leaving scripted mode can zero
+/// them; otherwise the next cutscene would start with the previous one's velocities.
+#[derive(Resource)]
+pub struct ScriptedMotion {
+    pub mode: ComputeMode,
+    /// Clamp scripted positions to the world bounds.
+    pub clamp_bounds: bool,
+    pub moving: HashSet<usize>,
+}
+
+impl Default for ScriptedMotion {
+    fn default() -> Self {
+        Self {
+            mode: ComputeMode::Normal,
+            clamp_bounds: true,
+            moving: HashSet::new(),
+        }
+    }
+}
+
+impl ScriptedMotion {
+    /// Record a scripted velocity for `slot` (caller sends the `SetVelocity`).
+    pub fn set_velocity(&mut self, slot: usize, velocity: Vec2) {
+        if velocity == Vec2::ZERO {
+            self.moving.remove(&slot);
+        } else {
+            self.moving.insert(slot);
+        }
+    }
+
+    /// Switch compute mode. Returns the slots whose velocity must be zeroed (all of them
+    /// when leaving scripted mode), sorted.
+    pub fn set_mode(&mut self, mode: ComputeMode) -> Vec<usize> {
+        let leaving = self.mode == ComputeMode::Scripted && mode != ComputeMode::Scripted;
+        self.mode = mode;
+        if !leaving {
+            return Vec::new();
+        }
+        let mut slots: Vec<usize> = self.moving.drain().collect();
+        slots.sort_unstable();
+        slots
+    }
+}
+
 // ============================================================================
 // DEBUG RESOURCES
 // ============================================================================
@@ -374,6 +836,268 @@ impl Default for CombatDebug {
     }
 }
 
+/// How long units remember an enemy that slipped out of acquisition range.
+/// 0 (default) = drop combat the moment the target is lost. Set via `endless/aggro_memory`.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct AggroMemoryConfig {
+    pub secs: f32,
+}
+
+/// How long units stick with a newly acquired combat target before nearest/priority selection
+/// may switch them. 0 (default) = re-pick every frame. Set via `endless/target_stickiness`.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct TargetStickiness {
+    pub secs: f32,
+}
+
+/// Which shooters aim at where a moving target will be rather than where it is.
+/// Set via `endless/lead_targeting`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeadTargetMode {
+    #[default]
+    Off,
+    /// Only NPCs with the Sharpshot trait.
+    Sharpshot,
+    All,
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LeadTargeting {
+    pub mode: LeadTargetMode,
+}
+
+impl LeadTargeting {
+    pub fn leads(&self, precision: f32) -> bool {
+        match self.mode {
+            LeadTargetMode::Off => false,
+            LeadTargetMode::Sharpshot => precision > 0.0,
+            LeadTargetMode::All => true,
+        }
+    }
+}
+
+/// Per-slot NPC velocity estimated from position readback deltas, for lead targeting.
+/// Readback lands less often than FixedUpdate ticks, so a velocity is only re-measured when
+/// the position actually changes and decays to zero after `NPC_VELOCITY_STALE_SECS` still.
+#[derive(Resource, Default)]
+pub struct NpcVelocities {
+    last_pos: Vec<Vec2>,
+    /// Game time `last_pos` was read.
+    last_seen: Vec<f32>,
+    vel: Vec<Vec2>,
+}
+
+impl NpcVelocities {
+    pub fn velocity(&self, slot: usize) -> Vec2 {
+        self.vel.get(slot).copied().unwrap_or(Vec2::ZERO)
+    }
+
+    /// Fold in one readback (`[x0, y0, x1, y1, ...]`) taken at game time `now`.
+    pub fn observe(&mut self, positions: &[f32], now: f32) {
+        use crate::constants::{LEAD_MAX_SPEED, NPC_VELOCITY_STALE_SECS};
+        let n = positions.len() / 2;
+        if self.last_pos.len() != n {
+            self.last_pos.resize(n, Vec2::new(-9999.0, -9999.0));
+            self.last_seen.resize(n, now);
+            self.vel.resize(n, Vec2::ZERO);
+        }
+        for slot in 0..n {
+            let pos = Vec2::new(positions[slot * 2], positions[slot * 2 + 1]);
+            let prev = self.last_pos[slot];
+            let dt = now - self.last_seen[slot];
+            if pos == prev {
+                if !(0.0..=NPC_VELOCITY_STALE_SECS).contains(&dt) {
+                    self.vel[slot] = Vec2::ZERO;
+                }
+                continue;
+            }
+            self.last_pos[slot] = pos;
+            self.last_seen[slot] = now;
+            if pos.x < -9000.0 || prev.x < -9000.0 || dt <= 0.0 {
+                self.vel[slot] = Vec2::ZERO;
+                continue;
+            }
+            let v = (pos - prev) / dt;
+            // Half-weight smoothing irons out uneven readback spacing
+            self.vel[slot] = if v.length() > LEAD_MAX_SPEED {
+                Vec2::ZERO
+            } else {
+                (self.vel[slot] + v) * 0.5
+            };
+        }
+    }
+}
+
+/// Per-slot unit facing (unit vector, zero = unknown) for flanking. Moving units face their
+/// tracked velocity; stationary ones turn toward their combat target, otherwise keep their
+/// last heading. Turns `FACING_TURN_RATE` of the way per tick instead of snapping.
+#[derive(Resource, Default)]
+pub struct NpcFacing {
+    dirs: Vec<Vec2>,
+}
+
+impl NpcFacing {
+    pub fn facing(&self, slot: usize) -> Vec2 {
+        self.dirs.get(slot).copied().unwrap_or(Vec2::ZERO)
+    }
+
+    /// Advance the first `npc_count` slots one tick from the current readback
+    /// (`[x0, y0, x1, y1, ...]`, which may extend past them to building targets).
+    pub fn update(
+        &mut self,
+        velocities: &NpcVelocities,
+        positions: &[f32],
+        combat_targets: &[i32],
+        npc_count: usize,
+    ) {
+        use crate::constants::{FACING_TURN_RATE, LEAD_MIN_SPEED};
+        let pos_at = |slot: usize| {
+            positions
+                .get(slot * 2..slot * 2 + 2)
+                .map(|p| Vec2::new(p[0], p[1]))
+                .filter(|p| p.x > -9000.0)
+        };
+        let n = npc_count.min(positions.len() / 2);
+        self.dirs.resize(n, Vec2::ZERO);
+        for slot in 0..n {
+            let Some(pos) = pos_at(slot) else {
+                // Dead or free slot: the next occupant starts without a heading
+                self.dirs[slot] = Vec2::ZERO;
+                continue;
+            };
+            let vel = velocities.velocity(slot);
+            let desired = if vel.length() >= LEAD_MIN_SPEED {
+                vel.normalize()
+            } else {
+                combat_targets
+                    .get(slot)
+                    .and_then(|&t| usize::try_from(t).ok())
+                    .and_then(pos_at)
+                    .map_or(Vec2::ZERO, |t| (t - pos).normalize_or_zero())
+            };
+            if desired == Vec2::ZERO {
+                continue;
+            }
+            let current = self.dirs[slot];
+            self.dirs[slot] = if current == Vec2::ZERO {
+                desired
+            } else {
+                // Rotate rather than lerp so a full about-face still turns
+                Vec2::from_angle(current.angle_to(desired) * FACING_TURN_RATE).rotate(current)
+            };
+        }
+    }
+}
+
+/// Which settled activities anchor an NPC in place (see `Anchored`). Set via `endless/anchor`.
+#[derive(Resource, Clone, Debug)]
+pub struct AnchorConfig {
+    /// Sleeping (Rest+Active).
+    pub rest: bool,
+    /// Recovering at a fountain (Heal+Active).
+    pub heal: bool,
+    /// Guards standing at a patrol post (Patrol+Holding).
+    pub on_duty: bool,
+}
+
+impl Default for AnchorConfig {
+    fn default() -> Self {
+        Self {
+            rest: true,
+            heal: false,
+            on_duty: false,
+        }
+    }
+}
+
+/// Outcome of one `CombatRng` roll.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttackRoll {
+    Hit,
+    Crit,
+    Miss,
+}
+
+/// Optional combat variance: miss chance, crits, and damage spread. Everything defaults
+/// to zero, so attacks stay flat and deterministic until a script enables variance.
+/// Rolls hash `seed` with a per-attack counter (no thread RNG) so the same seed and
+/// attack order replay identically.
+#[derive(Resource, Clone, Debug)]
+pub struct CombatRng {
+    pub seed: u64,
+    /// Base chance an attack misses (0..1).
+    pub miss_chance: f32,
+    /// Base chance an attack crits (0..1).
+    pub crit_chance: f32,
+    /// Damage multiplier on crit.
+    pub crit_mult: f32,
+    /// Damage varies uniformly in +/- spread (0.1 = +/-10%).
+    pub damage_spread: f32,
+    /// Extra crit chance per point of Sharpshot (Precision) magnitude.
+    pub sharpshot_crit: f32,
+    /// Extra dodge chance per point of Swift (Agility) magnitude on the target.
+    pub swift_dodge: f32,
+    /// Attacks rolled so far (mixed into the hash).
+    pub counter: u64,
+}
+
+impl Default for CombatRng {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            miss_chance: 0.0,
+            crit_chance: 0.0,
+            crit_mult: 2.0,
+            damage_spread: 0.0,
+            sharpshot_crit: 0.0,
+            swift_dodge: 0.0,
+            counter: 0,
+        }
+    }
+}
+
+impl CombatRng {
+    pub fn enabled(&self) -> bool {
+        self.miss_chance > 0.0
+            || self.crit_chance > 0.0
+            || self.damage_spread > 0.0
+            || self.sharpshot_crit > 0.0
+            || self.swift_dodge > 0.0
+    }
+
+    /// splitmix64 of (seed, counter, lane) mapped to [0, 1).
+    fn unit(&self, lane: u64) -> f32 {
+        crate::world::splitmix_unit(
+            self.seed
+                .wrapping_add(self.counter.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+                .wrapping_add(lane.wrapping_mul(0xD1B5_4A32_D192_ED03)),
+        )
+    }
+
+    /// Roll one attack. `precision` = attacker Sharpshot magnitude, `agility` = target
+    /// Swift magnitude (negative poles lower the chance). Returns (damage, outcome);
+    /// a miss deals 0. Disabled = (damage, Hit) without advancing the counter.
+    pub fn roll(&mut self, damage: f32, precision: f32, agility: f32) -> (f32, AttackRoll) {
+        if !self.enabled() {
+            return (damage, AttackRoll::Hit);
+        }
+        self.counter = self.counter.wrapping_add(1);
+        let miss = (self.miss_chance + self.swift_dodge * agility).clamp(0.0, 1.0);
+        if self.unit(0) < miss {
+            return (0.0, AttackRoll::Miss);
+        }
+        let spread = self.damage_spread.clamp(0.0, 1.0);
+        let damage = damage * (1.0 + spread * (self.unit(1) * 2.0 - 1.0));
+        let crit = (self.crit_chance + self.sharpshot_crit * precision).clamp(0.0, 1.0);
+        if self.unit(2) < crit {
+            (damage * self.crit_mult.max(1.0), AttackRoll::Crit)
+        } else {
+            (damage, AttackRoll::Hit)
+        }
+    }
+}
+
 /// Runtime metric for target intent thrashing.
 /// Tracks per-NPC SetTarget reason flips within the current game minute.
 #[derive(Resource, Default)]
@@ -558,6 +1282,14 @@ pub enum MovementPriority {
     DirectControl = 6,
 }
 
+impl MovementPriority {
+    /// Only routine moves (wander, job routes) wait out `PathfindConfig::retarget_cooldown`
+    /// after arrival; squad orders, combat, fleeing and player moves apply immediately.
+    pub fn bypasses_retarget_cooldown(self) -> bool {
+        self >= Self::Squad
+    }
+}
+
 /// A single movement intent submitted by a gameplay system.
 #[derive(Clone, Debug)]
 pub struct MovementIntent {
@@ -595,6 +1327,9 @@ pub struct PathRequest {
 pub struct PathRequestQueue {
     /// World-space intents awaiting grid conversion. Priority-wins-per-entity dedup.
     pending_intents: HashMap<Entity, MovementIntent>,
+    /// Intents held back by the post-arrival retarget cooldown. Re-offered on the next
+    /// drain unless a fresh intent for the same entity arrived meanwhile.
+    deferred_intents: HashMap<Entity, MovementIntent>,
     /// Grid-space path requests in 3 priority buckets.
     buckets: [HashMap<Entity, PathRequest>; 3],
 }
@@ -634,9 +1369,17 @@ impl PathRequestQueue {
     pub fn drain_intents(
         &mut self,
     ) -> std::collections::hash_map::Drain<'_, Entity, MovementIntent> {
+        for (entity, intent) in self.deferred_intents.drain() {
+            self.pending_intents.entry(entity).or_insert(intent);
+        }
         self.pending_intents.drain()
     }
 
+    /// Hold an intent until the next drain (retarget cooldown still running).
+    pub fn defer(&mut self, entity: Entity, intent: MovementIntent) {
+        self.deferred_intents.insert(entity, intent);
+    }
+
     /// Insert or merge a grid-space path request. Per-entity dedupe within priority bucket.
     /// Movement source has fresher goal — prefer it over Invalidation.
     pub fn enqueue(&mut self, req: PathRequest) {
@@ -706,6 +1449,11 @@ pub struct PathfindConfig {
     pub stuck_repath_frames: u32,
     /// Max milliseconds per tick for A* processing (early break guard).
     pub max_time_budget_ms: f32,
+    /// Seconds after arriving at a final destination before an automatic retarget is
+    /// accepted. Smooths arrival stampedes into fewer target uploads; 0 disables.
+    /// Squad, combat, survival and player targets bypass it
+    /// (`MovementPriority::bypasses_retarget_cooldown`).
+    pub retarget_cooldown: f32,
 }
 
 impl Default for PathfindConfig {
@@ -716,30 +1464,131 @@ impl Default for PathfindConfig {
             max_nodes: 5000,
             stuck_repath_frames: 30,
             max_time_budget_ms: 2.0,
+            retarget_cooldown: 0.0,
         }
     }
 }
 
-/// Live A* pathfinding metrics for profiler display. Updated every frame by resolve_movement_system.
-#[derive(Resource)]
-pub struct PathfindStats {
-    /// EMA-smoothed: paths processed per frame
-    pub processed: f32,
-    /// EMA-smoothed: LOS bypasses per frame
-    pub los_bypass: f32,
-    /// EMA-smoothed: full A* calls per frame
-    pub astar_calls: f32,
-    /// EMA-smoothed: A* failures (no path / node limit) per frame
-    pub astar_fails: f32,
-    /// EMA-smoothed: elapsed ms per frame
-    pub elapsed_ms: f32,
-    /// Snapshot: queue depth after processing
-    pub queue_remaining: usize,
-    /// Last limit reason ("count" or "time")
-    pub limit_reason: &'static str,
+/// Playable world rectangle in world px. Derived from the world grid after worldgen/load
+/// (`sync_world_bounds`). The compute shader clamps active NPC positions into it, and
+/// `GpuUpdate::SetTarget` goals are clamped on apply. `max <= min` means unbounded
+/// (no world yet). Inactive entities (`ENTITY_FLAG_INACTIVE`) are exempt.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct WorldBounds {
+    pub min: Vec2,
+    pub max: Vec2,
 }
 
-impl Default for PathfindStats {
+impl WorldBounds {
+    /// Bounds covering a `width` x `height` grid of `cell_size` px cells.
+    pub fn from_grid(width: usize, height: usize, cell_size: f32) -> Self {
+        Self {
+            min: Vec2::ZERO,
+            max: Vec2::new(width as f32 * cell_size, height as f32 * cell_size),
+        }
+    }
+
+    /// True once a world exists (non-empty rectangle).
+    pub fn is_set(&self) -> bool {
+        self.max.x > self.min.x && self.max.y > self.min.y
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        !self.is_set()
+            || (pos.x >= self.min.x
+                && pos.x <= self.max.x
+                && pos.y >= self.min.y
+                && pos.y <= self.max.y)
+    }
+
+    /// Clamp `pos` into bounds (identity when unbounded).
+    pub fn clamp(&self, pos: Vec2) -> Vec2 {
+        if self.is_set() {
+            pos.clamp(self.min, self.max)
+        } else {
+            pos
+        }
+    }
+}
+
+/// Dirty filter for GPU readback → ECS `Position`. gpu_position_readback only rewrites an
+/// NPC's Position when it moved more than `threshold` px since its last sync, so idle
+/// populations (resting, garrisoned) stop tripping change detection every frame.
+#[derive(Resource)]
+pub struct PositionSync {
+    /// Minimum movement (px) before Position is rewritten. 0 = sync every frame.
+    pub threshold: f32,
+    /// Last synced position per slot. None = force sync on next readback (spawn/teleport/reset).
+    pub last_synced: Vec<Option<Vec2>>,
+    /// Positions written last readback.
+    pub synced: usize,
+    /// Positions skipped (under threshold) last readback.
+    pub skipped: usize,
+}
+
+impl Default for PositionSync {
+    fn default() -> Self {
+        Self {
+            threshold: crate::constants::POSITION_SYNC_THRESHOLD,
+            last_synced: Vec::new(),
+            synced: 0,
+            skipped: 0,
+        }
+    }
+}
+
+impl PositionSync {
+    /// Force the next readback to sync this slot regardless of threshold.
+    pub fn invalidate(&mut self, slot: usize) {
+        if let Some(entry) = self.last_synced.get_mut(slot) {
+            *entry = None;
+        }
+    }
+
+    /// Forget all synced positions (keeps threshold) — first readback after fully re-syncs.
+    pub fn reset(&mut self) {
+        self.last_synced.clear();
+        self.synced = 0;
+        self.skipped = 0;
+    }
+
+    /// True if `pos` should be written to the slot's Position; records it as synced.
+    pub fn should_sync(&mut self, slot: usize, pos: Vec2) -> bool {
+        if slot >= self.last_synced.len() {
+            self.last_synced.resize(slot + 1, None);
+        }
+        let moved = match self.last_synced[slot] {
+            None => true,
+            Some(_) if self.threshold <= 0.0 => true,
+            Some(last) => last.distance_squared(pos) > self.threshold * self.threshold,
+        };
+        if moved {
+            self.last_synced[slot] = Some(pos);
+        }
+        moved
+    }
+}
+
+/// Live A* pathfinding metrics for profiler display. Updated every frame by resolve_movement_system.
+#[derive(Resource)]
+pub struct PathfindStats {
+    /// EMA-smoothed: paths processed per frame
+    pub processed: f32,
+    /// EMA-smoothed: LOS bypasses per frame
+    pub los_bypass: f32,
+    /// EMA-smoothed: full A* calls per frame
+    pub astar_calls: f32,
+    /// EMA-smoothed: A* failures (no path / node limit) per frame
+    pub astar_fails: f32,
+    /// EMA-smoothed: elapsed ms per frame
+    pub elapsed_ms: f32,
+    /// Snapshot: queue depth after processing
+    pub queue_remaining: usize,
+    /// Last limit reason ("count" or "time")
+    pub limit_reason: &'static str,
+}
+
+impl Default for PathfindStats {
     fn default() -> Self {
         Self {
             processed: 0.0,
@@ -792,6 +1641,25 @@ pub struct NpcLogEntry {
     pub message: Cow<'static, str>,
 }
 
+/// Which log entries are kept for the on-disk NPC log (`NpcLogFile`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NpcLogCapture {
+    #[default]
+    Off,
+    /// Entries pushed out of the in-memory ring (overflow, slot reuse, despawn, deselect).
+    Evicted,
+    /// Every entry for every NPC as it is pushed, regardless of the UI log mode.
+    All,
+}
+
+/// A log entry waiting to be written to disk. `generation` tells apart NPCs that shared a slot.
+#[derive(Clone)]
+pub struct NpcLogRecord {
+    pub slot: usize,
+    pub generation: u32,
+    pub entry: NpcLogEntry,
+}
+
 /// Per-NPC activity logs. Indexed by slot. 500 entries max per NPC.
 #[derive(Resource)]
 pub struct NpcLogCache {
@@ -804,6 +1672,12 @@ pub struct NpcLogCache {
     pub player_faction: i32,
     /// Per-slot faction cache (set from decision_system iteration).
     slot_factions: Vec<i32>,
+    /// Disk capture mode (synced from `NpcLogFile` by npc_log_flush_system).
+    pub capture: NpcLogCapture,
+    /// Per-slot spawn generation, bumped each time a new NPC takes the slot.
+    generations: Vec<u32>,
+    /// Captured entries not yet handed to the log file writer.
+    pending: Vec<NpcLogRecord>,
 }
 
 impl Default for NpcLogCache {
@@ -814,6 +1688,9 @@ impl Default for NpcLogCache {
             selected: -1,
             player_faction: 0,
             slot_factions: vec![-1; MAX_NPC_COUNT],
+            capture: NpcLogCapture::Off,
+            generations: vec![0; MAX_NPC_COUNT],
+            pending: Vec::new(),
         }
     }
 }
@@ -832,10 +1709,7 @@ impl NpcLogCache {
         if new_selected != self.selected {
             // Clear previous selection's log when in SelectedOnly mode
             if self.mode == crate::settings::NpcLogMode::SelectedOnly {
-                let old = self.selected as usize;
-                if old < self.logs.len() {
-                    self.logs[old].clear();
-                }
+                self.clear_slot(self.selected as usize);
             }
             self.selected = new_selected;
         }
@@ -854,6 +1728,19 @@ impl NpcLogCache {
         if idx >= MAX_NPC_COUNT {
             return;
         }
+        let message = message.into();
+        if self.capture == NpcLogCapture::All {
+            self.pending.push(NpcLogRecord {
+                slot: idx,
+                generation: self.generations[idx],
+                entry: NpcLogEntry {
+                    day,
+                    hour,
+                    minute,
+                    message: message.clone(),
+                },
+            });
+        }
 
         // Gate by mode
         match self.mode {
@@ -875,15 +1762,62 @@ impl NpcLogCache {
             day,
             hour,
             minute,
-            message: message.into(),
+            message,
         };
         if let Some(log) = self.logs.get_mut(idx) {
             if log.len() >= NPC_LOG_CAPACITY {
-                log.pop_front();
+                if let Some(old) = log.pop_front() {
+                    if self.capture == NpcLogCapture::Evicted {
+                        self.pending.push(NpcLogRecord {
+                            slot: idx,
+                            generation: self.generations[idx],
+                            entry: old,
+                        });
+                    }
+                }
             }
             log.push_back(entry);
         }
     }
+
+    /// Empty a slot's ring (captured as evicted when disk logging is on).
+    pub fn clear_slot(&mut self, idx: usize) {
+        let Some(log) = self.logs.get_mut(idx) else {
+            return;
+        };
+        if self.capture == NpcLogCapture::Evicted {
+            let generation = self.generations[idx];
+            self.pending.extend(log.drain(..).map(|entry| NpcLogRecord {
+                slot: idx,
+                generation,
+                entry,
+            }));
+        } else {
+            log.clear();
+        }
+    }
+
+    /// A new NPC took `idx`: drop the previous occupant's ring and start a new generation.
+    pub fn begin_life(&mut self, idx: usize) {
+        self.clear_slot(idx);
+        if let Some(generation) = self.generations.get_mut(idx) {
+            *generation = generation.wrapping_add(1);
+        }
+    }
+
+    /// Spawn generation of the NPC currently in `idx`.
+    pub fn generation(&self, idx: usize) -> u32 {
+        self.generations.get(idx).copied().unwrap_or(0)
+    }
+
+    /// Hand over captured entries for writing.
+    pub fn take_pending(&mut self) -> Vec<NpcLogRecord> {
+        std::mem::take(&mut self.pending)
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
 }
 
 // ============================================================================
@@ -972,6 +1906,10 @@ impl GpuSlotPool {
     pub fn alive(&self) -> usize {
         self.pool.alive()
     }
+    /// Slots `alloc_reset` can still hand out before the pool runs out.
+    pub fn available(&self) -> usize {
+        self.pool.max - self.pool.alive()
+    }
     pub fn reset(&mut self) {
         self.pool.reset();
         self.pending_resets.clear();
@@ -1003,26 +1941,363 @@ impl GpuSlotPool {
     }
 }
 
+/// Combat projectile spawn limits (`endless/projectile_limits`). Loot fly projectiles are exempt.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProjectileLimits {
+    /// Max combat projectiles one shooter may spawn per game-second. 0 = unlimited.
+    pub per_shooter_per_sec: u32,
+    /// Cap on live combat projectiles, on top of `combat_cap`. 0 = no extra cap.
+    pub global_cap: usize,
+    /// At capacity, recycle the oldest live combat projectile instead of dropping the new shot.
+    pub evict_oldest: bool,
+}
+
+/// Lifetime counters for combat shots that didn't get a fresh projectile slot.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProjDropStats {
+    pub rate_limited: u64,
+    pub at_capacity: u64,
+    pub evicted: u64,
+}
+
+/// Slot handed out by `ProjSlotAllocator::alloc_combat`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CombatSlot {
+    Fresh(usize),
+    /// Reused from the oldest live combat projectile; caller must deactivate it before spawning.
+    Evicted(usize),
+}
+
+impl CombatSlot {
+    pub fn idx(self) -> usize {
+        match self {
+            Self::Fresh(i) | Self::Evicted(i) => i,
+        }
+    }
+}
+
 /// Projectile slot allocator. Wraps SlotPool like GpuSlotPool.
 #[derive(Resource)]
-pub struct ProjSlotAllocator(pub SlotPool);
+pub struct ProjSlotAllocator {
+    pub pool: SlotPool,
+    /// Soft cap on live projectiles for combat shots (set by `QualityState`). Loot fly
+    /// projectiles ignore it; only the hard pool max applies to them.
+    pub combat_cap: usize,
+    pub limits: ProjectileLimits,
+    pub drops: ProjDropStats,
+    /// Per-shooter spawn times (game seconds) within the last second.
+    shooter_shots: HashMap<i32, VecDeque<f32>>,
+    last_prune: f32,
+    /// Combat slots in spawn order (eviction only). Entries whose seq no longer matches
+    /// `slot_seq` were freed or reused and are skipped.
+    spawn_order: VecDeque<(usize, u64)>,
+    slot_seq: Vec<u64>,
+    next_seq: u64,
+}
 
 impl Default for ProjSlotAllocator {
     fn default() -> Self {
-        Self(SlotPool::new(MAX_PROJECTILES))
+        Self {
+            pool: SlotPool::new(MAX_PROJECTILES),
+            combat_cap: MAX_PROJECTILES,
+            limits: ProjectileLimits::default(),
+            drops: ProjDropStats::default(),
+            shooter_shots: HashMap::new(),
+            last_prune: 0.0,
+            spawn_order: VecDeque::new(),
+            slot_seq: Vec::new(),
+            next_seq: 0,
+        }
+    }
+}
+
+impl ProjSlotAllocator {
+    /// Live combat projectile cap: quality cap, tightened by `limits.global_cap`.
+    pub fn effective_cap(&self) -> usize {
+        match self.limits.global_cap {
+            0 => self.combat_cap,
+            cap => cap.min(self.combat_cap),
+        }
+    }
+
+    /// Allocate a combat projectile slot for `shooter` at game time `now`. None when the
+    /// shooter is over its rate limit, or at capacity with eviction off (counted in `drops`).
+    pub fn alloc_combat(&mut self, shooter: i32, now: f32) -> Option<CombatSlot> {
+        let per_sec = self.limits.per_shooter_per_sec as usize;
+        if per_sec > 0 {
+            self.prune_shooters(now);
+            let shots = self.shooter_shots.entry(shooter).or_default();
+            // `t > now` covers the clock jumping back (load/new game)
+            while shots.front().is_some_and(|&t| now - t >= 1.0 || t > now) {
+                shots.pop_front();
+            }
+            if shots.len() >= per_sec {
+                self.drops.rate_limited += 1;
+                return None;
+            }
+        }
+
+        let fresh = if self.pool.alive() < self.effective_cap() {
+            self.pool.alloc()
+        } else {
+            None
+        };
+        let slot = match fresh {
+            Some(idx) => CombatSlot::Fresh(idx),
+            None => match self
+                .limits
+                .evict_oldest
+                .then(|| self.pop_oldest())
+                .flatten()
+            {
+                Some(idx) => {
+                    self.drops.evicted += 1;
+                    CombatSlot::Evicted(idx)
+                }
+                None => {
+                    self.drops.at_capacity += 1;
+                    return None;
+                }
+            },
+        };
+
+        if per_sec > 0 {
+            self.shooter_shots
+                .entry(shooter)
+                .or_default()
+                .push_back(now);
+        }
+        if self.limits.evict_oldest {
+            let idx = slot.idx();
+            if self.slot_seq.len() <= idx {
+                self.slot_seq.resize(idx + 1, 0);
+            }
+            self.next_seq += 1;
+            self.slot_seq[idx] = self.next_seq;
+            self.spawn_order.push_back((idx, self.next_seq));
+            // Drop freed entries from the front so the queue tracks roughly the live set
+            while self
+                .spawn_order
+                .front()
+                .is_some_and(|&(i, seq)| self.slot_seq[i] != seq)
+            {
+                self.spawn_order.pop_front();
+            }
+        }
+        Some(slot)
+    }
+
+    /// Return a projectile slot to the pool (also retires it from the eviction queue).
+    pub fn free(&mut self, slot: usize) {
+        if let Some(seq) = self.slot_seq.get_mut(slot) {
+            *seq = 0;
+        }
+        self.pool.free(slot);
+    }
+
+    /// Replace the limits. Turning eviction off drops the spawn-order queue.
+    pub fn set_limits(&mut self, limits: ProjectileLimits) {
+        if !limits.evict_oldest {
+            self.spawn_order.clear();
+            self.slot_seq.clear();
+        }
+        if limits.per_shooter_per_sec == 0 {
+            self.shooter_shots.clear();
+        }
+        self.limits = limits;
+    }
+
+    fn pop_oldest(&mut self) -> Option<usize> {
+        while let Some((idx, seq)) = self.spawn_order.pop_front() {
+            if self.slot_seq.get(idx) == Some(&seq) {
+                return Some(idx);
+            }
+        }
+        None
+    }
+
+    /// Forget shooters with no shots in the last second (once per game-second).
+    fn prune_shooters(&mut self, now: f32) {
+        if (now - self.last_prune).abs() < 1.0 {
+            return;
+        }
+        self.last_prune = now;
+        self.shooter_shots
+            .retain(|_, shots| shots.back().is_some_and(|&t| now - t < 1.0 && t <= now));
     }
 }
 
 impl std::ops::Deref for ProjSlotAllocator {
     type Target = SlotPool;
     fn deref(&self) -> &SlotPool {
-        &self.0
+        &self.pool
     }
 }
 
 impl std::ops::DerefMut for ProjSlotAllocator {
     fn deref_mut(&mut self) -> &mut SlotPool {
-        &mut self.0
+        &mut self.pool
+    }
+}
+
+/// Adaptive quality controller. Steps existing cost knobs down when the smoothed frame
+/// time stays over `budget_ms`, and back up after sustained headroom (hysteresis):
+/// - level 1: throttled readbacks (factions/threat) run at half rate
+/// - level 2: + NPC render LOD kicks in at twice the zoom
+/// - level 3: + live combat projectiles capped at `QUALITY_PROJECTILE_CAP`
+/// - level 4: + NPC separation off (see `SeparationState`)
+#[derive(Resource, Clone, Debug, Default)]
+pub struct QualityState {
+    /// Target frame time in ms. 0 = adaptive quality off (pinned at level 0).
+    pub budget_ms: f32,
+    /// 0 = full quality .. `QUALITY_MAX_LEVEL` = cheapest.
+    pub level: u8,
+    /// EMA of real frame time (ms).
+    pub smoothed_ms: f32,
+    over_frames: u32,
+    under_frames: u32,
+}
+
+impl QualityState {
+    /// Set the frame budget (0 disables). Resets to full quality so the controller re-converges.
+    pub fn set_budget(&mut self, budget_ms: f32) {
+        self.budget_ms = budget_ms.max(0.0);
+        self.level = 0;
+        self.over_frames = 0;
+        self.under_frames = 0;
+    }
+
+    /// Feed one frame time sample. Returns the new level when it changed.
+    pub fn record(&mut self, frame_ms: f32) -> Option<u8> {
+        use crate::constants::*;
+        self.smoothed_ms = if self.smoothed_ms <= 0.0 {
+            frame_ms
+        } else {
+            self.smoothed_ms + (frame_ms - self.smoothed_ms) * QUALITY_SMOOTHING
+        };
+        if self.budget_ms <= 0.0 {
+            let changed = self.level != 0;
+            self.level = 0;
+            return changed.then_some(0);
+        }
+        if self.smoothed_ms > self.budget_ms {
+            self.over_frames += 1;
+            self.under_frames = 0;
+        } else if self.smoothed_ms < self.budget_ms * QUALITY_HEADROOM {
+            self.under_frames += 1;
+            self.over_frames = 0;
+        } else {
+            // Dead band: hold the level
+            self.over_frames = 0;
+            self.under_frames = 0;
+        }
+        if self.over_frames >= QUALITY_DOWNGRADE_FRAMES && self.level < QUALITY_MAX_LEVEL {
+            self.level += 1;
+            self.over_frames = 0;
+            return Some(self.level);
+        }
+        if self.under_frames >= QUALITY_UPGRADE_FRAMES && self.level > 0 {
+            self.level -= 1;
+            self.under_frames = 0;
+            return Some(self.level);
+        }
+        None
+    }
+
+    /// Multiplier on throttled readback intervals (factions, threat counts).
+    pub fn readback_mult(&self) -> u32 {
+        if self.level >= 1 { 2 } else { 1 }
+    }
+
+    /// Multiplier on the render LOD transition zoom (higher = LOD sooner).
+    pub fn lod_mult(&self) -> f32 {
+        if self.level >= 2 { 2.0 } else { 1.0 }
+    }
+
+    /// Soft cap on live combat projectiles.
+    pub fn projectile_cap(&self) -> usize {
+        if self.level >= 3 {
+            crate::constants::QUALITY_PROJECTILE_CAP
+        } else {
+            MAX_PROJECTILES
+        }
+    }
+
+    /// False at the cheapest level: the compute pass skips NPC separation.
+    pub fn separation_allowed(&self) -> bool {
+        self.level < 4
+    }
+}
+
+/// Global NPC separation switch for very high counts. Off zeroes the compute shader's
+/// separation push: units overlap, while approach dodge, anchored-cluster push and transitive
+/// arrival (which share the neighbor scan) keep working.
+/// Adaptive quality also turns it off at its cheapest level. When it comes back on, strength
+/// ramps up over `SEPARATION_RAMP_FRAMES` so a packed crowd doesn't burst apart in one frame.
+#[derive(Resource, Clone, Debug)]
+pub struct SeparationState {
+    pub enabled: bool,
+    /// Multiplier on separation strength (0 = off, 1 = full), stepped once per frame.
+    pub ramp: f32,
+    /// Neighbor search the compute shader uses. Set via `set_mode` or `endless/separation`.
+    pub mode: SeparationMode,
+}
+
+impl Default for SeparationState {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ramp: 1.0,
+            mode: SeparationMode::Grid,
+        }
+    }
+}
+
+/// How the compute shader finds separation neighbors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeparationMode {
+    /// 3x3 spatial-grid cells; neighbors past `max_per_cell` in a cell are invisible.
+    #[default]
+    Grid,
+    /// Every live entity, O(n²). A correctness reference for the grid path, only honored up
+    /// to `SEPARATION_BRUTE_FORCE_MAX` entities.
+    BruteForce,
+}
+
+impl SeparationMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Grid => "grid",
+            Self::BruteForce => "brute_force",
+        }
+    }
+}
+
+impl SeparationState {
+    pub fn set_mode(&mut self, mode: SeparationMode) {
+        self.mode = mode;
+    }
+
+    /// Mode actually dispatched for `entity_count` entities: brute force falls back to the
+    /// grid above `SEPARATION_BRUTE_FORCE_MAX`.
+    pub fn effective_mode(&self, entity_count: usize) -> SeparationMode {
+        if entity_count > crate::constants::SEPARATION_BRUTE_FORCE_MAX {
+            SeparationMode::Grid
+        } else {
+            self.mode
+        }
+    }
+
+    /// Advance one frame. `allowed` = adaptive quality permits separation. Returns the
+    /// strength multiplier for this frame.
+    pub fn step(&mut self, allowed: bool) -> f32 {
+        self.ramp = if self.enabled && allowed {
+            (self.ramp + 1.0 / crate::constants::SEPARATION_RAMP_FRAMES as f32).min(1.0)
+        } else {
+            0.0
+        };
+        self.ramp
     }
 }
 
@@ -1047,10 +2322,210 @@ pub struct ProjHitState(pub Vec<[i32; 2]>);
 #[derive(Resource, Default)]
 pub struct ProjPositionState(pub Vec<f32>);
 
+/// How one kind of projectile is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProjectileVisual {
+    /// World-space quad size: x across the flight direction, y along it.
+    pub size: Vec2,
+    /// Fixed tint; None keeps the shooter's faction color.
+    pub color: Option<[f32; 3]>,
+    /// Faded copies drawn at prior positions (0 = no trail, capped at `PROJ_TRAIL_MAX`).
+    pub trail_len: usize,
+}
+
+impl Default for ProjectileVisual {
+    fn default() -> Self {
+        Self {
+            size: Vec2::splat(32.0),
+            color: None,
+            trail_len: 0,
+        }
+    }
+}
+
+/// Per-`ProjKind` projectile size, tint and trail. Read by extract_proj_data, set over BRP
+/// (`endless/projectile_visual`). Defaults match the plain faction-tinted arrow.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ProjectileVisualConfig {
+    visuals: [ProjectileVisual; 3],
+}
+
+impl ProjectileVisualConfig {
+    pub fn get(&self, kind: crate::messages::ProjKind) -> &ProjectileVisual {
+        &self.visuals[kind as usize]
+    }
+
+    pub fn set_projectile_visual(
+        &mut self,
+        kind: crate::messages::ProjKind,
+        size_x: f32,
+        size_y: f32,
+        color: Option<[f32; 3]>,
+        trail_len: usize,
+    ) {
+        self.visuals[kind as usize] = ProjectileVisual {
+            size: Vec2::new(size_x.max(1.0), size_y.max(1.0)),
+            color: color.map(|c| c.map(|v| v.clamp(0.0, 1.0))),
+            trail_len: trail_len.min(crate::constants::PROJ_TRAIL_MAX),
+        };
+    }
+
+    /// Longest trail of any kind — how many positions the render ring keeps per projectile.
+    pub fn max_trail(&self) -> usize {
+        self.visuals.iter().map(|v| v.trail_len).max().unwrap_or(0)
+    }
+}
+
 /// O(1) lookup from town_idx → Bevy Entity for town ECS entities.
 #[derive(Resource, Default)]
 pub struct TownIndex(pub HashMap<i32, Entity>);
 
+/// One job's corpse drop: `chance` to drop anything, then inclusive food/gold ranges and
+/// the chance of an equipment item.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LootDropRule {
+    pub chance: f32,
+    pub food: (i32, i32),
+    pub gold: (i32, i32),
+    pub item_chance: f32,
+}
+
+impl Default for LootDropRule {
+    fn default() -> Self {
+        Self {
+            chance: 0.5,
+            food: (1, 3),
+            gold: (0, 2),
+            item_chance: 0.05,
+        }
+    }
+}
+
+/// What one corpse leaves behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LootRoll {
+    pub food: i32,
+    pub gold: i32,
+    pub item: bool,
+}
+
+/// Optional per-town food storage limits. When enabled, `food_storage_system` clamps each
+/// town's food to `cap_base + cap_per_granary * granaries` (overflow is wasted and logged) and
+/// once per game day spoils `spoilage_fraction` of the stock above `spoil_above * cap`.
+/// Set via `endless/food_storage`.
+#[derive(Resource, Clone, Debug)]
+pub struct FoodStorageConfig {
+    pub enabled: bool,
+    pub cap_base: i32,
+    pub cap_per_granary: i32,
+    /// Fraction (0-1) of the food above the spoilage threshold lost each day. 0 = no spoilage.
+    pub spoilage_fraction: f32,
+    /// Spoilage threshold as a fraction (0-1) of the cap.
+    pub spoil_above: f32,
+}
+
+impl Default for FoodStorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cap_base: crate::constants::FOOD_CAP_BASE,
+            cap_per_granary: crate::constants::FOOD_CAP_PER_GRANARY,
+            spoilage_fraction: 0.0,
+            spoil_above: 0.5,
+        }
+    }
+}
+
+impl FoodStorageConfig {
+    pub fn cap(&self, granaries: usize) -> i32 {
+        self.cap_base
+            .saturating_add(self.cap_per_granary.saturating_mul(granaries as i32))
+            .max(0)
+    }
+
+    /// Food lost to one day of spoilage. Only stock above the threshold spoils, so the result
+    /// never exceeds `food - threshold` and never drives food negative.
+    pub fn spoilage(&self, food: i32, cap: i32) -> i32 {
+        let threshold = (cap as f32 * self.spoil_above.clamp(0.0, 1.0)) as i32;
+        let above = food - threshold.max(0);
+        if above <= 0 {
+            return 0;
+        }
+        ((above as f32 * self.spoilage_fraction.clamp(0.0, 1.0)) as i32).min(above)
+    }
+}
+
+/// Per-town storage bookkeeping for `food_storage_system`, indexed by town.
+#[derive(Resource, Default)]
+pub struct FoodStorageState {
+    /// Cap applied last tick (detects granary loss); 0 before a town's first tick.
+    pub caps: Vec<i32>,
+    /// Food spoiled at the most recent day rollover.
+    pub spoiled_last_day: Vec<i32>,
+    /// Overflow wasted since the last hourly log line.
+    pub wasted: Vec<i32>,
+    pub last_day: i32,
+}
+
+/// Optional corpse loot. When enabled, `death_system` rolls each dying NPC's job rule and
+/// spawns a `GroundLoot` at the body; `loot_system` handles pickup and timeout.
+/// Set via `endless/loot_config`.
+#[derive(Resource, Clone, Debug)]
+pub struct LootConfig {
+    pub enabled: bool,
+    pub pickup_radius: f32,
+    /// Game seconds before an unclaimed drop despawns.
+    pub lifetime: f32,
+    pub max_active: usize,
+    pub default_rule: LootDropRule,
+    pub per_job: HashMap<crate::components::Job, LootDropRule>,
+    /// Dying NPCs drop their `Inventory` as ground loot (independent of `enabled`).
+    pub drop_inventory: bool,
+}
+
+impl Default for LootConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pickup_radius: crate::constants::LOOT_PICKUP_RADIUS,
+            lifetime: crate::constants::LOOT_LIFETIME_SECS,
+            max_active: crate::constants::LOOT_MAX_ACTIVE,
+            default_rule: LootDropRule::default(),
+            per_job: HashMap::new(),
+            drop_inventory: true,
+        }
+    }
+}
+
+impl LootConfig {
+    pub fn rule(&self, job: crate::components::Job) -> LootDropRule {
+        self.per_job.get(&job).copied().unwrap_or(self.default_rule)
+    }
+
+    /// Deterministic drop roll for `job` from `seed`. None = nothing dropped.
+    pub fn roll(&self, job: crate::components::Job, seed: u64) -> Option<LootRoll> {
+        let rule = self.rule(job);
+        if loot_unit(seed, 0) >= rule.chance {
+            return None;
+        }
+        let range = |(lo, hi): (i32, i32), lane| {
+            let (lo, hi) = (lo.max(0), hi.max(lo.max(0)));
+            lo + (loot_unit(seed, lane) * (hi - lo + 1) as f32) as i32
+        };
+        let roll = LootRoll {
+            food: range(rule.food, 1),
+            gold: range(rule.gold, 2),
+            item: loot_unit(seed, 3) < rule.item_chance,
+        };
+        (roll != LootRoll::default()).then_some(roll)
+    }
+}
+
+/// splitmix64 of (seed, lane) mapped to [0, 1).
+fn loot_unit(seed: u64, lane: u64) -> f32 {
+    crate::world::splitmix_unit(seed.wrapping_add(lane.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+}
+
 /// Monotonic counter for unique loot item IDs.
 #[derive(Resource, Default)]
 pub struct NextLootItemId {
@@ -1110,6 +2585,63 @@ impl MerchantInventory {
 
 pub use crate::constants::FactionKind;
 
+/// Coarse aggression override for a faction's AI and units. Unset = personality-driven.
+/// Stance only gates *initiating* fights; units always defend themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum FactionStance {
+    /// Never start waves; units leash tightly and stay home.
+    Passive,
+    /// Waves only against targets near home; units leash normally.
+    Defensive,
+    /// Seek out enemies: more military, faster wave retargeting, long leash.
+    Aggressive,
+}
+
+impl FactionStance {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Passive => "passive",
+            Self::Defensive => "defensive",
+            Self::Aggressive => "aggressive",
+        }
+    }
+
+    /// Scales AI military desire (building military homes, waypoints, military upgrades).
+    pub fn military_desire_mult(self) -> f32 {
+        match self {
+            Self::Passive => 0.6,
+            Self::Defensive => 1.0,
+            Self::Aggressive => 1.4,
+        }
+    }
+
+    /// Whether an AI squad may start a new wave against a target `dist` from home.
+    pub fn may_initiate(self, dist: f32) -> bool {
+        match self {
+            Self::Passive => false,
+            Self::Defensive => dist <= crate::constants::STANCE_DEFENSIVE_RADIUS,
+            Self::Aggressive => true,
+        }
+    }
+
+    /// Scales the AI squad retarget cooldown between waves.
+    pub fn retarget_cooldown_mult(self) -> f32 {
+        match self {
+            Self::Aggressive => 0.5,
+            Self::Passive | Self::Defensive => 1.0,
+        }
+    }
+
+    /// Leash override for fighting units: (always leash, distance multiplier).
+    pub fn leash(self) -> (bool, f32) {
+        match self {
+            Self::Passive => (true, 0.5),
+            Self::Defensive => (true, 1.0),
+            Self::Aggressive => (false, 1.5),
+        }
+    }
+}
+
 /// A faction in the game. Owns towns, buildings, and NPCs.
 #[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
 pub struct FactionData {
@@ -1117,6 +2649,9 @@ pub struct FactionData {
     pub name: String,
     /// Town indices owned by this faction (most factions own exactly 1 town).
     pub towns: Vec<usize>,
+    /// Aggression override (`endless/faction_stance`). None = personality default.
+    #[serde(default)]
+    pub stance: Option<FactionStance>,
 }
 
 /// All factions. Index 0 = Neutral, 1 = Player, 2+ = AI.
@@ -1147,6 +2682,12 @@ impl FactionList {
             .position(|f| f.kind == FactionKind::Player)
     }
 
+    pub fn stance(&self, faction_idx: i32) -> Option<FactionStance> {
+        self.factions
+            .get(usize::try_from(faction_idx).ok()?)
+            .and_then(|f| f.stance)
+    }
+
     pub fn player_town(&self) -> Option<usize> {
         self.factions
             .iter()
@@ -1214,6 +2755,188 @@ impl Reputation {
     }
 }
 
+/// One kill in a faction's kill feed. `killer_slot` is an NPC or tower/fountain slot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KillFeedEntry {
+    pub killer_slot: usize,
+    pub killer_name: String,
+    pub victim_name: String,
+    pub victim_faction: i32,
+    pub day: i32,
+    pub hour: i32,
+    pub minute: i32,
+}
+
+/// A faction's top-killing living NPC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MvpEntry {
+    pub slot: usize,
+    pub kills: i32,
+}
+
+impl MvpEntry {
+    /// More kills wins; equal kills go to the lower slot so the pick never flickers.
+    fn beats(self, other: MvpEntry) -> bool {
+        self.kills > other.kills || (self.kills == other.kills && self.slot < other.slot)
+    }
+}
+
+/// Per-faction kill feed (newest last, capped at `FEED_LEN`) and MVP tracker, fed by
+/// `death_system`. The MVP updates in O(1) per kill; when the MVP dies or is despawned its
+/// faction goes into `stale` and `death_system` rescans that faction once.
+#[derive(Resource, Default)]
+pub struct FactionMvp {
+    pub feeds: HashMap<i32, VecDeque<KillFeedEntry>>,
+    pub mvp: HashMap<i32, MvpEntry>,
+    pub stale: HashSet<i32>,
+}
+
+impl FactionMvp {
+    pub const FEED_LEN: usize = 20;
+
+    /// NPC `slot` of `faction` now has `kills` kills.
+    pub fn on_kill(&mut self, faction: i32, slot: usize, kills: i32) {
+        let entry = MvpEntry { slot, kills };
+        match self.mvp.get_mut(&faction) {
+            Some(best) if best.slot == slot => best.kills = kills,
+            Some(best) if !entry.beats(*best) => {}
+            _ => {
+                self.mvp.insert(faction, entry);
+            }
+        }
+    }
+
+    /// NPC `slot` of `faction` died or was removed: if it was the MVP, queue a rescan.
+    pub fn on_death(&mut self, faction: i32, slot: usize) {
+        if self.mvp.get(&faction).is_some_and(|m| m.slot == slot) {
+            self.mvp.remove(&faction);
+            self.stale.insert(faction);
+        }
+    }
+
+    /// Recompute a faction's MVP from its living NPCs' `(slot, kills)`.
+    pub fn rescan(&mut self, faction: i32, living: impl IntoIterator<Item = (usize, i32)>) {
+        self.stale.remove(&faction);
+        let best = living
+            .into_iter()
+            .filter(|&(_, kills)| kills > 0)
+            .map(|(slot, kills)| MvpEntry { slot, kills })
+            .reduce(|a, b| if b.beats(a) { b } else { a });
+        match best {
+            Some(best) => self.mvp.insert(faction, best),
+            None => self.mvp.remove(&faction),
+        };
+    }
+
+    pub fn push_feed(&mut self, faction: i32, entry: KillFeedEntry) {
+        let feed = self.feeds.entry(faction).or_default();
+        if feed.len() >= Self::FEED_LEN {
+            feed.pop_front();
+        }
+        feed.push_back(entry);
+    }
+}
+
+/// A subject town paying a share of its income to an overlord town.
+/// Tribute comes out of income delivered since the last payment, never the stockpile
+/// that existed before, so a subject that earns nothing pays nothing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Tribute {
+    pub from_town: usize,
+    pub to_town: usize,
+    /// Share of income paid (0..1).
+    pub fraction: f32,
+    /// Game days between payments.
+    pub interval_days: u32,
+    /// Owning factions when the tribute was set. A change on either side (rebellion,
+    /// recapture) ends the relationship.
+    pub from_faction: i32,
+    pub to_faction: i32,
+    /// Food/gold delivered to the subject since the last payment.
+    #[serde(default)]
+    pub accrued_food: i32,
+    #[serde(default)]
+    pub accrued_gold: i32,
+    /// Game hours since the last payment.
+    #[serde(default)]
+    pub hours: u32,
+    /// Lifetime totals paid.
+    #[serde(default)]
+    pub paid_food: i32,
+    #[serde(default)]
+    pub paid_gold: i32,
+}
+
+impl Tribute {
+    pub fn new(from_town: usize, to_town: usize, from_faction: i32, to_faction: i32) -> Self {
+        Self {
+            from_town,
+            to_town,
+            fraction: crate::constants::DEFAULT_TRIBUTE_FRACTION,
+            interval_days: crate::constants::DEFAULT_TRIBUTE_INTERVAL_DAYS,
+            from_faction,
+            to_faction,
+            accrued_food: 0,
+            accrued_gold: 0,
+            hours: 0,
+            paid_food: 0,
+            paid_gold: 0,
+        }
+    }
+
+    /// Take `fraction` of accrued income, capped by what the subject currently holds
+    /// (it may have spent the income already). Resets the accrual window.
+    pub fn settle(&mut self, food_stock: i32, gold_stock: i32) -> (i32, i32) {
+        let food = ((self.accrued_food as f32 * self.fraction) as i32).clamp(0, food_stock.max(0));
+        let gold = ((self.accrued_gold as f32 * self.fraction) as i32).clamp(0, gold_stock.max(0));
+        self.accrued_food = 0;
+        self.accrued_gold = 0;
+        self.hours = 0;
+        self.paid_food += food;
+        self.paid_gold += gold;
+        (food, gold)
+    }
+}
+
+/// Active tribute relationships. A town pays at most one overlord.
+#[derive(Resource, Default)]
+pub struct TributeState {
+    pub tributes: Vec<Tribute>,
+}
+
+impl TributeState {
+    /// Tribute `town` pays, if it is a subject.
+    pub fn paid_by(&self, town: usize) -> Option<&Tribute> {
+        self.tributes.iter().find(|t| t.from_town == town)
+    }
+
+    /// Tributes paid to `town`.
+    pub fn received_by(&self, town: usize) -> impl Iterator<Item = &Tribute> {
+        self.tributes.iter().filter(move |t| t.to_town == town)
+    }
+
+    /// Count delivered income toward the next payment (no-op for free towns).
+    pub fn record_income(&mut self, town: usize, food: i32, gold: i32) {
+        if let Some(t) = self.tributes.iter_mut().find(|t| t.from_town == town) {
+            t.accrued_food += food.max(0);
+            t.accrued_gold += gold.max(0);
+        }
+    }
+
+    /// Start or replace the tribute `tribute.from_town` pays.
+    pub fn set(&mut self, tribute: Tribute) {
+        self.tributes.retain(|t| t.from_town != tribute.from_town);
+        self.tributes.push(tribute);
+    }
+
+    /// Free `town` from tribute. Returns false if it wasn't paying any.
+    pub fn clear(&mut self, town: usize) -> bool {
+        let before = self.tributes.len();
+        self.tributes.retain(|t| t.from_town != town);
+        self.tributes.len() != before
+    }
+}
+
 /// Towns that the LLM player is allowed to control via BRP write endpoints.
 /// Populated from main menu AI slot config. Empty = no restrictions (legacy/debug).
 #[derive(Resource, Default, Reflect)]
@@ -1253,11 +2976,94 @@ impl RaiderState {
     }
 }
 
+/// Raid-party mode for raider camps. When enabled, camps stop feeding every raider into one
+/// attack squad and instead send parties of idle raiders at enemy farms, so a depleted camp
+/// raids weaker. Set via `endless/raid_parties`.
+#[derive(Resource, Clone, Debug)]
+pub struct RaidPartyConfig {
+    pub enabled: bool,
+    /// A camp with fewer idle raiders waits instead of sending a token force.
+    pub min_party: usize,
+    pub max_party: usize,
+    /// Game hours between parties from the same camp.
+    pub cooldown_hours: f32,
+}
+
+impl Default for RaidPartyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_party: crate::constants::RAID_GROUP_SIZE as usize,
+            max_party: crate::constants::RAID_PARTY_MAX,
+            cooldown_hours: crate::constants::RAID_PARTY_COOLDOWN_HOURS,
+        }
+    }
+}
+
+/// One raider camp's population as of the last `raid_party_system` heartbeat.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct CampStatus {
+    /// Living raiders homed at the camp.
+    pub total: usize,
+    /// Idle or wandering near the camp, not fighting: eligible for the next party.
+    pub available: usize,
+    /// Out on a raid.
+    pub raiding: usize,
+    /// Carrying loot (or retreating) home.
+    pub returning: usize,
+    /// Game hour the camp may send its next party.
+    pub next_party_hour: f32,
+}
+
+/// Raider camp tracking for raid-party mode, keyed by camp town index.
+#[derive(Resource, Default)]
+pub struct RaidParties {
+    pub camps: HashMap<usize, CampStatus>,
+}
+
+impl RaidParties {
+    pub fn get_camp_status(&self, camp_idx: usize) -> Option<CampStatus> {
+        self.camps.get(&camp_idx).copied()
+    }
+}
+
+/// Where a spawner building's replacement NPC appears.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RespawnLocation {
+    /// At the spawner building (its home).
+    #[default]
+    OriginalSpawn,
+    TownCenter,
+    /// The town's nearest unoccupied bed to the spawner.
+    NearestBed,
+}
+
+/// Per-faction respawn placement for `spawner_respawn_system`. Factions without an entry use
+/// `OriginalSpawn`. Set via `endless/respawn_location`.
+#[derive(Resource, Default)]
+pub struct RespawnPolicy {
+    pub by_faction: HashMap<i32, RespawnLocation>,
+}
+
+impl RespawnPolicy {
+    pub fn mode(&self, faction: i32) -> RespawnLocation {
+        self.by_faction.get(&faction).copied().unwrap_or_default()
+    }
+}
+
 impl FactionStats {
     pub fn init(&mut self, count: usize) {
         self.stats = vec![FactionStat::default(); count];
     }
 
+    /// Grow to at least `count` factions (scripted factions past the world's list).
+    pub fn ensure_size(&mut self, count: usize) {
+        if self.stats.len() < count {
+            self.stats.resize(count, FactionStat::default());
+        }
+    }
+
     pub fn inc_alive(&mut self, faction: i32) {
         if let Some(s) = self.stats.get_mut(faction as usize) {
             s.alive += 1;
@@ -1288,7 +3094,7 @@ impl FactionStats {
 // ============================================================================
 
 /// Active tab in the left panel.
-#[derive(Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum LeftPanelTab {
     #[default]
     Roster,
@@ -1461,6 +3267,223 @@ impl UiState {
 // BUILD MENU STATE
 // ============================================================================
 
+/// World-space rectangles where nothing new may be built. Checked by `place_building` (every
+/// player, AI and BRP placement path) and by the build ghost. Buildings already standing inside
+/// a zone are left alone; zones only block new placement.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct NoBuildZones(pub Vec<Rect>);
+
+impl NoBuildZones {
+    /// Placement error (and ghost hint) for a cell inside a zone.
+    pub const REASON: &'static str = "inside a no-build zone";
+
+    pub fn add_no_build_zone(&mut self, rect: Rect) {
+        self.0.push(rect);
+    }
+
+    pub fn clear_no_build_zones(&mut self) {
+        self.0.clear();
+    }
+
+    /// True if `pos` lies inside any zone (edges inclusive).
+    pub fn contains(&self, pos: Vec2) -> bool {
+        self.0.iter().any(|r| r.contains(pos))
+    }
+}
+
+/// Food cost of each building kind: the registry cost unless the balance file overrides it
+/// (`apply_balance_system`). Every cost check and charge reads it from here.
+#[derive(Resource, Default, Clone, Debug, PartialEq)]
+pub struct BuildingCosts {
+    overrides: Vec<(crate::world::BuildingKind, i32)>,
+}
+
+impl BuildingCosts {
+    /// Replace the overrides. Kinds not listed fall back to the registry.
+    pub fn set_overrides(&mut self, overrides: Vec<(crate::world::BuildingKind, i32)>) {
+        self.overrides = overrides;
+    }
+
+    /// Food cost to build `kind`. 0 for non-buildable types.
+    pub fn cost(&self, kind: crate::world::BuildingKind) -> i32 {
+        self.overrides
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|&(_, c)| c)
+            .unwrap_or_else(|| crate::constants::building_def(kind).cost)
+    }
+}
+
+/// Which player-owned town the build menu, economy panel and policies act on.
+/// `None` = the first player town. Set via the top-bar switcher or `endless/player_focus`;
+/// `player_focus_system` moves off towns the player no longer owns.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlayerFocus(pub Option<usize>);
+
+impl PlayerFocus {
+    /// Focused town if the player still owns it, else the first player town.
+    pub fn town(&self, towns: &[crate::world::Town]) -> Option<usize> {
+        let owned = |i: usize| {
+            towns
+                .get(i)
+                .is_some_and(|t| t.faction == crate::constants::FACTION_PLAYER)
+        };
+        self.0
+            .filter(|&i| owned(i))
+            .or_else(|| (0..towns.len()).find(|&i| owned(i)))
+    }
+}
+
+/// Kinds of cell that scale the damage an NPC takes while standing in them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneKind {
+    /// Per wall cell in or next to the defender's cell. Stacks, down to `COMBAT_COVER_FLOOR`.
+    Wall,
+    /// Standing on any road tier.
+    Road,
+    /// Standing in open water.
+    Water,
+}
+
+/// Per-cell incoming-damage multiplier read by `damage_system` at the defender's GPU position.
+/// Terrain is stamped on a full rebuild (world init/load or a modifier change); walls and roads
+/// are an overlay restamped by `sync_combat_zones_system` on `BuildingGridDirtyMsg`.
+/// Set multipliers via `set_zone_modifier` or `endless/combat_zone`.
+#[derive(Resource)]
+pub struct CombatZones {
+    pub wall: f32,
+    pub road: f32,
+    pub water: f32,
+    cells: Vec<f32>,
+    /// Flat indices stamped by the wall/road overlay (reverted on the next sync).
+    overlay: Vec<usize>,
+    width: usize,
+    cell_size: f32,
+    stale: bool,
+}
+
+impl Default for CombatZones {
+    fn default() -> Self {
+        Self {
+            wall: crate::constants::COMBAT_ZONE_WALL,
+            road: crate::constants::COMBAT_ZONE_ROAD,
+            water: crate::constants::COMBAT_ZONE_WATER,
+            cells: Vec::new(),
+            overlay: Vec::new(),
+            width: 0,
+            cell_size: crate::constants::TOWN_GRID_SPACING,
+            stale: true,
+        }
+    }
+}
+
+impl CombatZones {
+    pub fn zone_modifier(&self, kind: ZoneKind) -> f32 {
+        match kind {
+            ZoneKind::Wall => self.wall,
+            ZoneKind::Road => self.road,
+            ZoneKind::Water => self.water,
+        }
+    }
+
+    /// Change a zone's multiplier. The table is rebuilt on the next sync.
+    pub fn set_zone_modifier(&mut self, kind: ZoneKind, mult: f32) {
+        let mult = mult.max(0.0);
+        match kind {
+            ZoneKind::Wall => self.wall = mult,
+            ZoneKind::Road => self.road = mult,
+            ZoneKind::Water => self.water = mult,
+        }
+        self.stale = true;
+    }
+
+    pub fn needs_rebuild(&self, grid: &crate::world::WorldGrid) -> bool {
+        self.stale || self.cells.len() != grid.cells.len()
+    }
+
+    /// Multiplier for a cell with `walls` wall cells around it, a road underfoot, and/or water.
+    /// Cover (walls, road) is capped at `COMBAT_COVER_FLOOR`; water exposure applies on top.
+    pub fn cell_multiplier(&self, walls: u32, road: bool, water: bool) -> f32 {
+        let mut mult = self.wall.powi(walls as i32);
+        if road {
+            mult *= self.road;
+        }
+        mult = mult.max(crate::constants::COMBAT_COVER_FLOOR);
+        if water { mult * self.water } else { mult }
+    }
+
+    /// Refresh the table: full terrain pass when stale, then restamp cells near walls and roads.
+    pub fn sync(&mut self, grid: &crate::world::WorldGrid, entity_map: &EntityMap) {
+        use crate::world::{Biome, BuildingKind};
+        let water = |idx: usize| grid.cells[idx].terrain == Biome::Water;
+        if self.needs_rebuild(grid) {
+            let cells: Vec<f32> = (0..grid.cells.len())
+                .map(|i| self.cell_multiplier(0, false, water(i)))
+                .collect();
+            self.cells = cells;
+            self.width = grid.width;
+            self.cell_size = grid.cell_size;
+            self.stale = false;
+        } else {
+            for &idx in &self.overlay {
+                self.cells[idx] = self.cell_multiplier(0, false, water(idx));
+            }
+        }
+        self.overlay.clear();
+
+        let (w, h) = (grid.width as i32, grid.height as i32);
+        let mut stamped: HashMap<usize, (u32, bool)> = HashMap::new();
+        for kind in [
+            BuildingKind::Wall,
+            BuildingKind::Road,
+            BuildingKind::StoneRoad,
+            BuildingKind::MetalRoad,
+        ] {
+            let footprint = crate::constants::building_def(kind).footprint;
+            for inst in entity_map.iter_kind(kind) {
+                for (gc, gr) in
+                    crate::world::footprint_cells(inst.position, footprint, grid.cell_size)
+                {
+                    let reach = if kind == BuildingKind::Wall { 1 } else { 0 };
+                    for r in gr - reach..=gr + reach {
+                        for c in gc - reach..=gc + reach {
+                            if c < 0 || r < 0 || c >= w || r >= h {
+                                continue;
+                            }
+                            let e = stamped.entry((r * w + c) as usize).or_default();
+                            if kind == BuildingKind::Wall {
+                                e.0 += 1;
+                            } else {
+                                e.1 = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for (idx, (walls, road)) in stamped {
+            self.cells[idx] = self.cell_multiplier(walls, road, water(idx));
+            self.overlay.push(idx);
+        }
+    }
+
+    /// Multiplier at world position `pos` (1.0 off the grid or before the first sync).
+    pub fn at(&self, pos: Vec2) -> f32 {
+        let (col, row) = (
+            (pos.x / self.cell_size).floor(),
+            (pos.y / self.cell_size).floor(),
+        );
+        if self.width == 0 || col < 0.0 || row < 0.0 || col as usize >= self.width {
+            return 1.0;
+        }
+        self.cells
+            .get(row as usize * self.width + col as usize)
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
+
 /// Context for build palette + placement mode.
 #[derive(Resource)]
 pub struct BuildMenuContext {
@@ -1478,6 +3501,8 @@ pub struct BuildMenuContext {
     pub drag_current_slot: Option<(usize, usize)>,
     /// Show the mouse-follow build hint sprite (hidden when snapped over a valid build slot).
     pub show_cursor_hint: bool,
+    /// Why the hovered cell is blocked, when the ghost knows a reason worth naming.
+    pub ghost_block_reason: Option<&'static str>,
     /// Bevy image handles for ghost preview sprites (populated by build_menu init).
     pub ghost_sprites: std::collections::HashMap<crate::world::BuildingKind, Handle<Image>>,
     /// Active build menu category tab.
@@ -1494,6 +3519,7 @@ impl Default for BuildMenuContext {
             drag_start_slot: None,
             drag_current_slot: None,
             show_cursor_hint: true,
+            ghost_block_reason: None,
             ghost_sprites: std::collections::HashMap::new(),
             build_tab: crate::constants::DisplayCategory::Economy,
         }
@@ -1525,10 +3551,14 @@ pub enum CombatEventKind {
     Loot,
     Llm,
     Chat,
+    /// Scenario victory or defeat (see `WinCondition`).
+    GameOver,
+    /// Food wasted over a storage cap or spoiled overnight (see `FoodStorageConfig`).
+    Spoilage,
 }
 
 impl CombatEventKind {
-    const COUNT: usize = 10;
+    const COUNT: usize = 12;
 
     fn index(self) -> usize {
         match self {
@@ -1542,6 +3572,8 @@ impl CombatEventKind {
             Self::Loot => 7,
             Self::Llm => 8,
             Self::Chat => 9,
+            Self::GameOver => 10,
+            Self::Spoilage => 11,
         }
     }
 }
@@ -1559,6 +3591,39 @@ pub struct CombatLogEntry {
     pub location: Option<bevy::math::Vec2>,
 }
 
+/// Farm events waiting for an external consumer (`endless/farm_events` drains it). Oldest are
+/// dropped past `FARM_EVENT_OUTBOX_CAP` so an unpolled outbox can't grow forever.
+#[derive(Resource, Default)]
+pub struct FarmEventOutbox(pub VecDeque<crate::messages::FarmEventMsg>);
+
+pub const FARM_EVENT_OUTBOX_CAP: usize = 1024;
+
+/// Game over events waiting for `endless/game_state` to drain them. Oldest are dropped past
+/// `GAME_OVER_OUTBOX_CAP`.
+#[derive(Resource, Default)]
+pub struct GameOverOutbox(pub VecDeque<crate::messages::GameOverMsg>);
+
+/// A scenario ends once, so only a reload or a new goal can add more.
+pub const GAME_OVER_OUTBOX_CAP: usize = 16;
+
+/// Attack swing/whiff cues waiting for an external animation consumer
+/// (`endless/attack_events`). Off until a consumer enables it, so nothing is recorded in
+/// normal play. `swinging` holds announced swings still winding up, so each gets exactly one
+/// outcome: the hit (silent) or a `Whiff`.
+#[derive(Resource, Default)]
+pub struct AttackAnimOutbox {
+    pub enabled: bool,
+    pub events: VecDeque<crate::messages::AttackAnimMsg>,
+    /// Attacker entity -> (slot, target slot) of an announced, unresolved swing.
+    pub swinging: HashMap<Entity, (usize, usize)>,
+}
+
+/// World-space margin around the camera view within which attackers still get swing cues.
+pub const ATTACK_ANIM_VIEW_MARGIN: f32 = 64.0;
+
+/// Undrained swing cues kept in `AttackAnimOutbox`; a few big fights fill this within a poll.
+pub const ATTACK_ANIM_OUTBOX_CAP: usize = 4096;
+
 const COMBAT_LOG_PER_KIND: usize = 200;
 
 /// Global combat event log. Per-kind ring buffers (200 each), newest at back.
@@ -1644,6 +3709,8 @@ pub struct TowerState {
 pub struct BuildingHpRender {
     pub positions: Vec<Vec2>,
     pub health_pcts: Vec<f32>,
+    /// Footprint (w, h) in cells, so the bar spans the whole building.
+    pub footprints: Vec<(u8, u8)>,
 }
 
 /// Per-town auto-upgrade flags. When enabled, upgrades are purchased automatically
@@ -1734,6 +3801,54 @@ pub struct PolicySet {
     /// Equipment count that triggers NPC return-home to deposit loot.
     #[serde(default = "default_loot_threshold")]
     pub loot_threshold: usize,
+    /// Buy the next Expansion ring when the build area is `AUTO_EXPAND_UTILIZATION` full.
+    #[serde(default)]
+    pub auto_expand: bool,
+    /// Send idle/patrolling guards within `reinforce_radius` toward a town alert.
+    #[serde(default = "crate::save::default_true")]
+    pub reinforce_enabled: bool,
+    #[serde(default = "default_reinforce_radius")]
+    pub reinforce_radius: f32,
+    /// Fraction (0-1) of the town's guards that stay on their posts during an alert.
+    #[serde(default = "default_reinforce_reserve")]
+    pub reinforce_reserve: f32,
+    /// Enemies-per-defender ratio near a town alert at which defenders fall back to the
+    /// town center for a last stand. 0 = never.
+    #[serde(default = "default_last_stand_ratio")]
+    pub last_stand_ratio: f32,
+    /// Panic gained per second from each fleeing neighbour. 0 = panic never spreads.
+    #[serde(default = "default_panic_spread")]
+    pub panic_spread: f32,
+    /// Panic at which a unit routs.
+    #[serde(default = "default_panic_threshold")]
+    pub panic_threshold: f32,
+    /// Military units farther than this from every friendly building are out of supply
+    /// (slower attacks, faster energy drain). 0 = supply lines off.
+    #[serde(default = "default_supply_radius")]
+    pub supply_radius: f32,
+    /// Pin free guards to uncovered posts (and rehome guards from removed posts) whenever
+    /// the town's waypoints change.
+    #[serde(default)]
+    pub auto_assign_guards: bool,
+}
+
+fn default_reinforce_radius() -> f32 {
+    crate::constants::REINFORCE_RADIUS
+}
+fn default_reinforce_reserve() -> f32 {
+    crate::constants::REINFORCE_RESERVE
+}
+fn default_last_stand_ratio() -> f32 {
+    crate::constants::LAST_STAND_RATIO
+}
+fn default_panic_spread() -> f32 {
+    crate::constants::PANIC_SPREAD
+}
+fn default_panic_threshold() -> f32 {
+    crate::constants::PANIC_THRESHOLD
+}
+fn default_supply_radius() -> f32 {
+    crate::constants::SUPPLY_RADIUS
 }
 
 pub const DEFAULT_LOOT_THRESHOLD: usize = 3;
@@ -1762,6 +3877,15 @@ impl Default for PolicySet {
             reserve_food: 0,
             reserve_gold: 0,
             loot_threshold: 3,
+            auto_expand: false,
+            reinforce_enabled: true,
+            reinforce_radius: crate::constants::REINFORCE_RADIUS,
+            reinforce_reserve: crate::constants::REINFORCE_RESERVE,
+            last_stand_ratio: crate::constants::LAST_STAND_RATIO,
+            panic_spread: crate::constants::PANIC_SPREAD,
+            panic_threshold: crate::constants::PANIC_THRESHOLD,
+            supply_radius: crate::constants::SUPPLY_RADIUS,
+            auto_assign_guards: false,
         }
     }
 }
@@ -1898,6 +4022,103 @@ impl Difficulty {
     }
 }
 
+// ============================================================================
+// TOWN ALERTS
+// ============================================================================
+
+/// A town taking enemy damage. `pos` tracks the latest hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TownAlert {
+    pub pos: Vec2,
+    /// Game seconds (`GameTime::total_seconds`) of the latest enemy hit.
+    pub last_hit: f32,
+}
+
+/// Per-town alert state, raised by `town_alert_system` from enemy `DamageMsg`s on a town's
+/// NPCs or buildings. Indexed by town index.
+#[derive(Resource, Default)]
+pub struct TownAlerts {
+    pub alerts: Vec<Option<TownAlert>>,
+}
+
+impl TownAlerts {
+    pub fn get(&self, town: usize) -> Option<&TownAlert> {
+        self.alerts.get(town).and_then(|a| a.as_ref())
+    }
+
+    pub fn raise(&mut self, town: usize, pos: Vec2, now: f32) {
+        if town >= self.alerts.len() {
+            self.alerts.resize(town + 1, None);
+        }
+        self.alerts[town] = Some(TownAlert { pos, last_hit: now });
+    }
+
+    /// Clear alerts with no enemy hit in `TOWN_ALERT_CLEAR_SECS`. Returns the cleared towns.
+    pub fn expire(&mut self, now: f32) -> Vec<usize> {
+        let mut cleared = Vec::new();
+        for (town, alert) in self.alerts.iter_mut().enumerate() {
+            if alert.is_some_and(|a| now - a.last_hit > crate::constants::TOWN_ALERT_CLEAR_SECS) {
+                *alert = None;
+                cleared.push(town);
+            }
+        }
+        cleared
+    }
+}
+
+/// Towns whose defenders are falling back for a last stand. Re-evaluated every
+/// `LAST_STAND_CHECK_SECS` by `last_stand_system`, not per frame, so the order doesn't flip-flop.
+#[derive(Resource, Default)]
+pub struct LastStandState {
+    pub retreating: Vec<bool>,
+    /// Game seconds of the next force-ratio check.
+    pub next_check: f32,
+}
+
+impl LastStandState {
+    pub fn is_retreating(&self, town: usize) -> bool {
+        self.retreating.get(town).copied().unwrap_or(false)
+    }
+}
+
+/// Per-NPC panic, spread by `panic_system` from fleeing units to same-town neighbours.
+/// Panic decays every update, so only several neighbours fleeing at once build it up; past
+/// the policy `panic_threshold` a unit routs and becomes a panic source itself.
+#[derive(Resource, Default)]
+pub struct PanicState {
+    /// Panic by NPC slot. Calm units are absent.
+    pub levels: HashMap<usize, f32>,
+    /// Slots that fled combat -> game seconds until the flee stops counting as a source.
+    pub fleeing: HashMap<usize, f32>,
+    /// Slots routed by panic -> game seconds the rout ends.
+    pub routed: HashMap<usize, f32>,
+    /// Game seconds of the last update.
+    pub last_update: f32,
+}
+
+impl PanicState {
+    /// Slot just fled combat (HP flee or rout): a panic source for `PANIC_FLEE_SECS`.
+    pub fn mark_fleeing(&mut self, slot: usize, now: f32) {
+        self.fleeing
+            .insert(slot, now + crate::constants::PANIC_FLEE_SECS);
+    }
+
+    pub fn is_routed(&self, slot: usize) -> bool {
+        self.routed.contains_key(&slot)
+    }
+
+    pub fn level(&self, slot: usize) -> f32 {
+        self.levels.get(&slot).copied().unwrap_or(0.0)
+    }
+
+    /// Drop everything tracked for a slot (death, despawn).
+    pub fn forget(&mut self, slot: usize) {
+        self.levels.remove(&slot);
+        self.fleeing.remove(&slot);
+        self.routed.remove(&slot);
+    }
+}
+
 // ============================================================================
 // SQUADS
 // ============================================================================
@@ -1943,8 +4164,58 @@ pub struct Squad {
     pub owner: SquadOwner,
     /// Hold fire: when true, members only attack their ManualTarget (no auto-engage).
     pub hold_fire: bool,
+    /// Target priority for members without a per-unit `TargetPriority` override.
+    pub target_priority: crate::components::TargetPriority,
+    /// Engagement posture for members without a per-unit `StanceOverride`.
+    pub stance: crate::components::SquadStance,
+    /// How far (px) from its hold point a Defensive squad engages.
+    pub engage_radius: f32,
     /// Equipment count that triggers this squad to return home and deposit loot.
     pub loot_threshold: usize,
+    /// Pending rally-then-attack order (phase 1). Cleared once the attack is issued or the
+    /// target is changed by anything else.
+    pub rally: Option<SquadRally>,
+}
+
+/// Two-phase order: `target` holds `rally` until the squad gathers there, then switches to
+/// `attack`. Driven by `squad_order_system`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SquadRally {
+    pub rally: Vec2,
+    pub attack: Vec2,
+    /// Game seconds (`GameTime::total_seconds`) the order was issued.
+    pub issued_at: f32,
+}
+
+/// When a rallying squad counts as gathered.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct RallyConfig {
+    /// Members within this distance (px) of the rally point count as gathered.
+    pub radius: f32,
+    /// Fraction of living members that must be gathered before the attack goes out.
+    pub fraction: f32,
+    /// Game seconds after which the attack goes out regardless (stuck members).
+    pub timeout_secs: f32,
+}
+
+impl Default for RallyConfig {
+    fn default() -> Self {
+        Self {
+            radius: 120.0,
+            fraction: 0.75,
+            timeout_secs: 60.0,
+        }
+    }
+}
+
+impl RallyConfig {
+    /// True when phase 1 is over: enough of the living members gathered, the timeout
+    /// passed, or nobody is left alive to wait for.
+    pub fn ready(&self, gathered: usize, alive: usize, elapsed: f32) -> bool {
+        alive == 0
+            || gathered as f32 >= self.fraction * alive as f32
+            || elapsed >= self.timeout_secs
+    }
 }
 
 impl Squad {
@@ -1967,7 +4238,11 @@ impl Default for Squad {
             wave_retreat_below_pct: 50,
             owner: SquadOwner::Player,
             hold_fire: false,
+            target_priority: Default::default(),
+            stance: Default::default(),
+            engage_radius: crate::constants::SQUAD_ENGAGE_RADIUS,
             loot_threshold: default_loot_threshold(),
+            rally: None,
         }
     }
 }
@@ -1982,6 +4257,8 @@ pub struct SquadState {
     pub placing_target: bool,
     /// Box-select drag: world-space start position (None = not dragging).
     pub drag_start: Option<Vec2>,
+    /// Box-select drag: latest world-space cursor position while dragging.
+    pub drag_end: Option<Vec2>,
     /// True while mouse is held and drag exceeds threshold (5px).
     pub box_selecting: bool,
     /// DC NPCs keep fighting after looting instead of returning home.
@@ -1997,6 +4274,7 @@ impl Default for SquadState {
             selected: 0,
             placing_target: false,
             drag_start: None,
+            drag_end: None,
             box_selecting: false,
             dc_no_return: false,
         }
@@ -2004,6 +4282,15 @@ impl Default for SquadState {
 }
 
 impl SquadState {
+    /// Normalized (min, max) world rect of the active box-select drag.
+    pub fn selection_bounds(&self) -> Option<(Vec2, Vec2)> {
+        if !self.box_selecting {
+            return None;
+        }
+        let (a, b) = (self.drag_start?, self.drag_end?);
+        Some((a.min(b), a.max(b)))
+    }
+
     /// Allocate a new squad with the given owner. Returns the squad index.
     pub fn alloc_squad(&mut self, owner: SquadOwner) -> usize {
         let idx = self.squads.len();
@@ -2014,6 +4301,37 @@ impl SquadState {
         idx
     }
 
+    /// Rally-then-attack: send the squad to `rally` now; `squad_order_system` switches the
+    /// target to `attack` once it has gathered. Returns false for an unknown squad.
+    pub fn rally_then_attack(&mut self, squad: usize, rally: Vec2, attack: Vec2, now: f32) -> bool {
+        let Some(squad) = self.squads.get_mut(squad) else {
+            return false;
+        };
+        squad.target = Some(rally);
+        squad.rally = Some(SquadRally {
+            rally,
+            attack,
+            issued_at: now,
+        });
+        true
+    }
+
+    /// Set a squad's stance and engage radius; `squad_stance_system` applies them to members
+    /// next tick. Returns false for an unknown squad.
+    pub fn set_squad_stance(
+        &mut self,
+        squad: usize,
+        stance: crate::components::SquadStance,
+        engage_radius: f32,
+    ) -> bool {
+        let Some(squad) = self.squads.get_mut(squad) else {
+            return false;
+        };
+        squad.stance = stance;
+        squad.engage_radius = engage_radius.max(0.0);
+        true
+    }
+
     /// Iterate squads owned by a specific AI town.
     pub fn squads_for_town(&self, tdi: usize) -> impl Iterator<Item = (usize, &Squad)> {
         self.squads
@@ -2126,32 +4444,323 @@ impl HelpCatalog {
 // TUTORIAL STATE
 // ============================================================================
 
-/// Guided tutorial state machine. Step 0 = not started, 1-10 = active, 255 = done.
+/// What a tutorial step waits for before auto-advancing. Evaluated generically by `tutorial_ui_system`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TutorialCondition {
+    /// Info-only step — advanced by the Next button.
+    Manual,
+    /// Camera moved more than this many world units from where the tutorial started.
+    CameraMoved(f32),
+    /// Build menu opened.
+    BuildMenuOpen,
+    /// Player town has built `n` more of this kind than it had when the tutorial started.
+    BuildCount(crate::world::BuildingKind, usize),
+    /// Any NPC selected.
+    UnitSelected,
+    /// Camera following the selected NPC.
+    FollowingUnit,
+    /// Player town food stockpile above this amount.
+    FoodAbove(i32),
+    /// Upgrade tree opened.
+    TechTreeOpen,
+    /// Left panel open on this tab.
+    PanelOpen(LeftPanelTab),
+    /// Wall-clock seconds since this step started.
+    TimeElapsed(f64),
+}
+
+/// UI element a tutorial step points at (for highlight rendering).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TutorialTarget {
+    Camera,
+    BuildMenu,
+    Building(crate::world::BuildingKind),
+    SelectedNpc,
+    TopBar,
+    TechTree,
+    Panel(LeftPanelTab),
+}
+
+/// One tutorial step. `instruction` uses `{}` placeholders filled in order with the
+/// player's key labels for `keys`.
+#[derive(Clone, Debug)]
+pub struct TutorialStep {
+    pub instruction: &'static str,
+    pub keys: &'static [crate::settings::ControlAction],
+    pub completion_condition: TutorialCondition,
+    pub highlight_target: Option<TutorialTarget>,
+}
+
+const fn tutorial_step(
+    instruction: &'static str,
+    keys: &'static [crate::settings::ControlAction],
+    completion_condition: TutorialCondition,
+    highlight_target: Option<TutorialTarget>,
+) -> TutorialStep {
+    TutorialStep {
+        instruction,
+        keys,
+        completion_condition,
+        highlight_target,
+    }
+}
+
+/// Default guided tutorial. Reorder/add entries here — nothing else keys off step numbers.
+pub fn default_tutorial_steps() -> Vec<TutorialStep> {
+    use crate::settings::ControlAction as K;
+    use crate::world::BuildingKind as B;
+    use TutorialCondition as C;
+    use TutorialTarget as T;
+    vec![
+        tutorial_step(
+            "Camera: right-drag, WASD, or screen edge. Scroll = zoom",
+            &[],
+            C::CameraMoved(50.0),
+            Some(T::Camera),
+        ),
+        tutorial_step(
+            "{} = build menu. Use Economy/Military tabs to find buildings",
+            &[K::ToggleBuildMenu],
+            C::BuildMenuOpen,
+            Some(T::BuildMenu),
+        ),
+        tutorial_step(
+            "Build a Farm",
+            &[],
+            C::BuildCount(B::Farm, 1),
+            Some(T::Building(B::Farm)),
+        ),
+        tutorial_step(
+            "Build a Farmer Home - spawns 1 farmer who works the nearest farm",
+            &[],
+            C::BuildCount(B::FarmerHome, 1),
+            Some(T::Building(B::FarmerHome)),
+        ),
+        tutorial_step(
+            "Each building = 1 NPC. NPC dies -> respawns after 12 hours\nBuilding destroyed -> NPC lives on but won't respawn",
+            &[],
+            C::Manual,
+            None,
+        ),
+        tutorial_step(
+            "Click an NPC to select them - see their stats in the bottom panel",
+            &[],
+            C::UnitSelected,
+            Some(T::SelectedNpc),
+        ),
+        tutorial_step(
+            "{} = follow selected NPC. Press {} again or WASD to stop",
+            &[K::ToggleFollow, K::ToggleFollow],
+            C::FollowingUnit,
+            Some(T::SelectedNpc),
+        ),
+        tutorial_step(
+            "Food incoming - top bar shows your stockpile",
+            &[],
+            C::FoodAbove(0),
+            Some(T::TopBar),
+        ),
+        tutorial_step(
+            "NPCs eat 1 food when low on energy\nNo food -> starvation (half HP, half speed)",
+            &[],
+            C::Manual,
+            None,
+        ),
+        tutorial_step(
+            "Build a Waypoint - archers patrol between them in order",
+            &[],
+            C::BuildCount(B::Waypoint, 1),
+            Some(T::Building(B::Waypoint)),
+        ),
+        tutorial_step(
+            "Build an Archer Home - spawns 1 archer",
+            &[],
+            C::BuildCount(B::ArcherHome, 1),
+            Some(T::Building(B::ArcherHome)),
+        ),
+        tutorial_step(
+            "Build Walls around your town to block enemies\nClick a wall to upgrade its tier",
+            &[],
+            C::Manual,
+            Some(T::Building(B::Wall)),
+        ),
+        tutorial_step(
+            "{} = upgrades - spend food and gold to buff your NPCs",
+            &[K::ToggleUpgrades],
+            C::TechTreeOpen,
+            Some(T::TechTree),
+        ),
+        tutorial_step(
+            "Upgrades cost food and gold. Miners extract gold from mines",
+            &[],
+            C::Manual,
+            None,
+        ),
+        tutorial_step(
+            "Build Roads between buildings for a 1.5x NPC speed boost",
+            &[],
+            C::Manual,
+            Some(T::Building(B::Road)),
+        ),
+        tutorial_step(
+            "Expansion upgrade = +1 build range per level (starts 8x8)",
+            &[],
+            C::Manual,
+            Some(T::TechTree),
+        ),
+        tutorial_step(
+            "Build a Miner Home - spawns 1 miner who works the nearest gold mine",
+            &[],
+            C::BuildCount(B::MinerHome, 1),
+            Some(T::Building(B::MinerHome)),
+        ),
+        tutorial_step(
+            "{} = policies - control NPC behavior (schedules, flee HP, aggression)",
+            &[K::TogglePolicies],
+            C::PanelOpen(LeftPanelTab::Policies),
+            Some(T::Panel(LeftPanelTab::Policies)),
+        ),
+        tutorial_step(
+            "{} = patrol order - reorder waypoints to set the archer patrol route",
+            &[K::TogglePatrols],
+            C::PanelOpen(LeftPanelTab::Patrols),
+            Some(T::Panel(LeftPanelTab::Patrols)),
+        ),
+        tutorial_step(
+            "{} = squads - group archers into squads",
+            &[K::ToggleSquads],
+            C::PanelOpen(LeftPanelTab::Squads),
+            Some(T::Panel(LeftPanelTab::Squads)),
+        ),
+        tutorial_step(
+            "1-9 = select squad + click map to send them. 0 = squad 10",
+            &[],
+            C::Manual,
+            None,
+        ),
+        tutorial_step(
+            "{} = quicksave, {} = quickload\nESC > Save/Load for named saves",
+            &[K::QuickSave, K::QuickLoad],
+            C::Manual,
+            None,
+        ),
+        tutorial_step(
+            "All keys are rebindable in ESC > Settings > Controls",
+            &[],
+            C::Manual,
+            None,
+        ),
+        tutorial_step(
+            "{} = roster, {} = factions, {} = combat log, {} = help",
+            &[
+                K::ToggleRoster,
+                K::ToggleFactions,
+                K::ToggleCombatLog,
+                K::ToggleHelp,
+            ],
+            C::Manual,
+            None,
+        ),
+    ]
+}
+
+/// Where the player is in the tutorial.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TutorialProgress {
+    #[default]
+    NotStarted,
+    /// Index into `TutorialState.steps`.
+    Active(usize),
+    Done,
+}
+
+/// Guided tutorial state machine, driven by the `steps` table.
 #[derive(Resource)]
 pub struct TutorialState {
-    pub step: u8,
-    pub initial_farms: usize,
-    pub initial_farmer_homes: usize,
-    pub initial_waypoints: usize,
-    pub initial_archer_homes: usize,
-    pub initial_miner_homes: usize,
+    pub progress: TutorialProgress,
+    pub steps: Vec<TutorialStep>,
+    /// Player-town building counts at tutorial start, for `BuildCount` conditions.
+    pub initial_buildings: HashMap<crate::world::BuildingKind, usize>,
     pub camera_start: Vec2,
     /// Wall-clock seconds when tutorial started (for 10-minute auto-end).
     pub start_time: f64,
+    /// Wall-clock seconds when the current step started (for `TimeElapsed`).
+    pub step_start_time: f64,
 }
 
 impl Default for TutorialState {
     fn default() -> Self {
         Self {
-            step: 0,
-            initial_farms: 0,
-            initial_farmer_homes: 0,
-            initial_waypoints: 0,
-            initial_archer_homes: 0,
-            initial_miner_homes: 0,
+            progress: TutorialProgress::NotStarted,
+            steps: default_tutorial_steps(),
+            initial_buildings: HashMap::new(),
             camera_start: Vec2::ZERO,
             start_time: 0.0,
+            step_start_time: 0.0,
+        }
+    }
+}
+
+impl TutorialState {
+    pub fn is_active(&self) -> bool {
+        matches!(self.progress, TutorialProgress::Active(_))
+    }
+
+    /// Current step for rendering (instruction + highlight target). None when inactive.
+    pub fn current_step(&self) -> Option<&TutorialStep> {
+        match self.progress {
+            TutorialProgress::Active(i) => self.steps.get(i),
+            _ => None,
+        }
+    }
+
+    /// 1-based position of the current step, for "n/total" display.
+    pub fn step_number(&self) -> Option<usize> {
+        match self.progress {
+            TutorialProgress::Active(i) => Some(i + 1),
+            _ => None,
+        }
+    }
+
+    /// Begin at the first step (or finish immediately if the table is empty).
+    pub fn start(&mut self, now: f64) {
+        self.start_time = now;
+        self.step_start_time = now;
+        self.progress = if self.steps.is_empty() {
+            TutorialProgress::Done
+        } else {
+            TutorialProgress::Active(0)
+        };
+    }
+
+    /// Move to the next step. Returns true if that finished the tutorial.
+    pub fn advance(&mut self, now: f64) -> bool {
+        if let TutorialProgress::Active(i) = self.progress {
+            self.step_start_time = now;
+            self.progress = if i + 1 < self.steps.len() {
+                TutorialProgress::Active(i + 1)
+            } else {
+                TutorialProgress::Done
+            };
+        }
+        self.progress == TutorialProgress::Done
+    }
+
+    pub fn finish(&mut self) {
+        self.progress = TutorialProgress::Done;
+    }
+
+    /// Building kinds referenced by `BuildCount` steps (snapshotted at tutorial start).
+    pub fn tracked_building_kinds(&self) -> Vec<crate::world::BuildingKind> {
+        let mut kinds = Vec::new();
+        for s in &self.steps {
+            if let TutorialCondition::BuildCount(kind, _) = s.completion_condition {
+                if !kinds.contains(&kind) {
+                    kinds.push(kind);
+                }
+            }
         }
+        kinds
     }
 }
 
@@ -2163,6 +4772,8 @@ impl Default for TutorialState {
 /// Phase 1 (boat): boat_slot is Some, member_slots empty, town_data_idx None
 /// Phase 2 (walk): boat_slot None, member_slots filled, town_data_idx None
 /// Phase 3 (settle): town created, NPCs get Home, migration cleared
+/// Scripted migrations (`endless/migrate`) skip the boat: existing members of
+/// `source_town` detach and walk straight to `settle_target`.
 pub struct MigrationGroup {
     // Boat phase
     pub boat_slot: Option<usize>,
@@ -2179,6 +4790,21 @@ pub struct MigrationGroup {
     pub faction: i32,
     // Set at settle
     pub town_data_idx: Option<usize>,
+    /// Scripted migration: town the members left (None = endless-mode boat group).
+    pub source_town: Option<usize>,
+    /// Existing town to join on arrival instead of founding a new one.
+    pub join_town: Option<usize>,
+}
+
+/// Scripted migration command, drained by `scripted_migration_system`.
+#[derive(Clone, Debug)]
+pub enum MigrationRequest {
+    Start {
+        from_town: usize,
+        to_pos: Vec2,
+        member_slots: Vec<usize>,
+    },
+    Cancel,
 }
 
 /// Tracks dynamic raider town migrations.
@@ -2188,6 +4814,8 @@ pub struct MigrationState {
     pub check_timer: f32,
     /// Debug: force-spawn a migration group next frame (ignores cooldown/population checks).
     pub debug_spawn: bool,
+    /// Queued scripted start/cancel commands (BRP).
+    pub requests: Vec<MigrationRequest>,
 }
 
 /// Pending AI respawn queued by endless mode after a town is defeated.
@@ -2279,6 +4907,10 @@ pub struct GameAudio {
     pub sfx_handles: std::collections::HashMap<SfxKind, Vec<Handle<AudioSource>>>,
     /// Whether arrow shoot SFX plays (disabled by default — the sound is rough).
     pub sfx_shoot_enabled: bool,
+    /// Most SFX playing at once; new requests fill the free voices by priority, then distance.
+    pub sfx_max_voices: usize,
+    /// On-screen SFX requests dropped last frame (coalesced or over the voice budget).
+    pub sfx_suppressed: usize,
 }
 
 impl Default for GameAudio {
@@ -2293,6 +4925,8 @@ impl Default for GameAudio {
             music_speed: 1.0,
             sfx_handles: std::collections::HashMap::new(),
             sfx_shoot_enabled: false,
+            sfx_max_voices: 8,
+            sfx_suppressed: 0,
         }
     }
 }
@@ -2301,14 +4935,34 @@ impl Default for GameAudio {
 #[derive(Component)]
 pub struct MusicTrack;
 
+/// Marker component for a playing SFX entity (counted against `GameAudio.sfx_max_voices`).
+#[derive(Component)]
+pub struct SfxVoice;
+
 /// Sound effect categories.
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum SfxKind {
     ArrowShoot,
     Death,
     Build,
     Click,
     Upgrade,
+    /// Combat variance markers (CombatRng) — no sound bound; floating-text trigger.
+    Crit,
+    Miss,
+}
+
+impl SfxKind {
+    /// Playback priority when voices are scarce: UI feedback, then deaths, then hits and
+    /// build/upgrade cues, then shots.
+    pub fn priority(self) -> u8 {
+        match self {
+            SfxKind::Click => 4,
+            SfxKind::Death => 3,
+            SfxKind::Crit | SfxKind::Miss | SfxKind::Build | SfxKind::Upgrade => 2,
+            SfxKind::ArrowShoot => 1,
+        }
+    }
 }
 
 /// Fire-and-forget SFX trigger message. Position enables spatial culling (None = always play).
@@ -2318,6 +4972,87 @@ pub struct PlaySfxMsg {
     pub position: Option<Vec2>,
 }
 
+// ============================================================================
+// SPRITE TABLE (job + state → body sprite)
+// ============================================================================
+
+/// Coarse NPC visual state used to pick a body sprite from `SpriteTable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SpriteState {
+    Idle,
+    Working,
+    Resting,
+    Fighting,
+}
+
+impl SpriteState {
+    pub const ALL: [Self; 4] = [Self::Idle, Self::Working, Self::Resting, Self::Fighting];
+
+    /// Fighting wins; otherwise an Active sleep-visual activity is Resting and an Active
+    /// working activity is Working. Everything else (transit, wander, patrol) is Idle.
+    pub fn derive(
+        activity: &crate::components::Activity,
+        combat: &crate::components::CombatState,
+    ) -> Self {
+        use crate::components::ActivityPhase;
+        if combat.is_fighting() {
+            Self::Fighting
+        } else if activity.phase != ActivityPhase::Active {
+            Self::Idle
+        } else if activity.kind.def().sleep_visual {
+            Self::Resting
+        } else if activity.kind.def().is_working {
+            Self::Working
+        } else {
+            Self::Idle
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Idle => "Idle",
+            Self::Working => "Working",
+            Self::Resting => "Resting",
+            Self::Fighting => "Fighting",
+        }
+    }
+}
+
+/// Body sprite overrides keyed by (job, state), for modding. Unmapped pairs fall back to the
+/// job's `NpcDef.sprite`, so an empty table renders exactly as before.
+/// `sprite_state_system` applies it and only emits `SetSpriteFrame` when a slot's frame changes.
+#[derive(Resource, Default)]
+pub struct SpriteTable {
+    pub overrides: HashMap<(crate::components::Job, SpriteState), (f32, f32)>,
+}
+
+impl SpriteTable {
+    /// Set (Some) or clear (None) the frame for a (job, state) pair.
+    pub fn set(
+        &mut self,
+        job: crate::components::Job,
+        state: SpriteState,
+        frame: Option<(f32, f32)>,
+    ) {
+        match frame {
+            Some(f) => {
+                self.overrides.insert((job, state), f);
+            }
+            None => {
+                self.overrides.remove(&(job, state));
+            }
+        }
+    }
+
+    /// Frame (col, row) for this job in this state.
+    pub fn resolve(&self, job: crate::components::Job, state: SpriteState) -> (f32, f32) {
+        self.overrides
+            .get(&(job, state))
+            .copied()
+            .unwrap_or_else(|| crate::constants::npc_def(job).sprite)
+    }
+}
+
 // ============================================================================
 // CHAT INBOX (LLM ↔ Player messaging)
 // ============================================================================
@@ -2351,12 +5086,436 @@ impl ChatInbox {
     }
 }
 
+/// Build + engine + GPU identity for bug reports. GPU fields are filled at startup from the
+/// render adapter (`version_info_system`); until then they read "unknown".
+#[derive(Resource, Clone, Debug, Serialize)]
+pub struct VersionInfo {
+    pub crate_version: &'static str,
+    pub build_commit: &'static str,
+    pub build_timestamp: &'static str,
+    pub bevy_version: &'static str,
+    pub gpu_device_name: String,
+    pub gpu_backend: String,
+    pub gpu_driver: String,
+}
+
+/// Latest VersionInfo, readable from the panic hook (which has no World access).
+static REPORTED_VERSION: Mutex<Option<VersionInfo>> = Mutex::new(None);
+
+impl Default for VersionInfo {
+    fn default() -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION"),
+            build_commit: env!("BUILD_COMMIT"),
+            build_timestamp: env!("BUILD_TIMESTAMP"),
+            bevy_version: env!("BEVY_VERSION"),
+            gpu_device_name: "unknown".into(),
+            gpu_backend: "unknown".into(),
+            gpu_driver: "unknown".into(),
+        }
+    }
+}
+
+impl VersionInfo {
+    /// One-line summary users can paste into a bug report.
+    pub fn summary(&self) -> String {
+        format!(
+            "Endless {} ({} built {}) | Bevy {} | GPU: {} [{}] driver {}",
+            self.crate_version,
+            self.build_commit,
+            self.build_timestamp,
+            self.bevy_version,
+            self.gpu_device_name,
+            self.gpu_backend,
+            self.gpu_driver,
+        )
+    }
+
+    /// Publish this info for the crash handler.
+    pub fn publish(&self) {
+        *recover_or_log(&REPORTED_VERSION, "reported_version") = Some(self.clone());
+    }
+
+    /// Most recently published info, or build-only info if the GPU was never queried.
+    /// Uses `try_lock` so a panic hook can never deadlock on it.
+    pub fn reported() -> Self {
+        REPORTED_VERSION
+            .try_lock()
+            .ok()
+            .and_then(|v| v.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Combat-log lines carried in a crash report.
+pub const CRASH_CONTEXT_LOG_LINES: usize = 5;
+
+/// Game-state snapshot attached to crash reports. Refreshed every frame by
+/// `crash_context_system`; the panic hook reads it via `CrashContext::reported()`.
+#[derive(Clone, Debug, Default)]
+pub struct CrashContext {
+    pub day: i32,
+    pub hour: i32,
+    pub npc_count: usize,
+    pub town_count: usize,
+    /// Most recent combat-log lines, oldest first.
+    pub recent_log: Vec<String>,
+    /// CombatRng seed. A new game starts it at the world seed, so it also reproduces the map
+    /// unless something reseeded combat mid-run (e.g. a quick battle).
+    pub combat_seed: u64,
+}
+
+/// Latest CrashContext, readable from the panic hook (which has no World access).
+static CRASH_CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+
+impl CrashContext {
+    /// Update the published snapshot in place. `recent_log` is only rebuilt when `log` is
+    /// Some, so callers can skip the sort on frames where the combat log did not change.
+    /// Skips the frame if the lock is contended or poisoned.
+    pub fn update(
+        day: i32,
+        hour: i32,
+        npc_count: usize,
+        town_count: usize,
+        combat_seed: u64,
+        log: Option<&CombatLog>,
+    ) {
+        let Ok(mut slot) = CRASH_CONTEXT.try_lock() else {
+            return;
+        };
+        let ctx = slot.get_or_insert_with(Self::default);
+        ctx.day = day;
+        ctx.hour = hour;
+        ctx.npc_count = npc_count;
+        ctx.town_count = town_count;
+        ctx.combat_seed = combat_seed;
+        if let Some(log) = log {
+            ctx.recent_log = Self::log_tail(log, CRASH_CONTEXT_LOG_LINES);
+        }
+    }
+
+    /// Last `n` combat-log lines across all kinds, ordered by game time.
+    pub fn log_tail(log: &CombatLog, n: usize) -> Vec<String> {
+        let mut entries: Vec<&CombatLogEntry> = log.iter_all().collect();
+        entries.sort_by_key(|e| (e.day, e.hour, e.minute));
+        let skip = entries.len().saturating_sub(n);
+        entries[skip..]
+            .iter()
+            .map(|e| format!("[D{} {:02}:{:02}] {}", e.day, e.hour, e.minute, e.message))
+            .collect()
+    }
+
+    /// Most recently published snapshot, or None if no game has started. A poisoned lock
+    /// (panic while updating) still yields the last snapshot; a held lock yields None
+    /// so the panic hook can never deadlock on it.
+    pub fn reported() -> Option<Self> {
+        match CRASH_CONTEXT.try_lock() {
+            Ok(v) => v.clone(),
+            Err(std::sync::TryLockError::Poisoned(e)) => e.into_inner().clone(),
+            Err(std::sync::TryLockError::WouldBlock) => None,
+        }
+    }
+
+    /// Multi-line block for the crash report.
+    pub fn summary(&self) -> String {
+        let mut s = format!(
+            "Day {} {:02}:00 | NPCs: {} | Towns: {} | Combat seed: {}",
+            self.day, self.hour, self.npc_count, self.town_count, self.combat_seed,
+        );
+        if self.recent_log.is_empty() {
+            s.push_str("\nRecent log: (empty)");
+        } else {
+            s.push_str("\nRecent log:");
+            for line in &self.recent_log {
+                s.push_str("\n  ");
+                s.push_str(line);
+            }
+        }
+        s
+    }
+}
+
 // Test12 relocated to src/tests/vertical_slice.rs — uses shared TestState resource.
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn poisoned_timings_lock_recovers() {
+        let timings = SystemTimings::default();
+        std::thread::scope(|s| {
+            let poisoner = s.spawn(|| {
+                let _guard = timings.data.lock();
+                panic!("poison the timings lock");
+            });
+            assert!(poisoner.join().is_err());
+        });
+        assert!(timings.data.is_poisoned());
+
+        let before = lock_poison_recoveries();
+        timings.record("after_panic", 10.0);
+        timings.record("after_panic", 10.0);
+        assert!(
+            timings.get_timings().contains_key("after_panic"),
+            "frames after the panic still record"
+        );
+        assert!(!timings.data.is_poisoned(), "poison cleared on recovery");
+        assert!(lock_poison_recoveries() > before, "recovery counted");
+    }
+
+    #[test]
+    fn separation_ramps_back_in() {
+        let mut s = SeparationState::default();
+        assert_eq!(s.step(true), 1.0);
+        s.enabled = false;
+        assert_eq!(s.step(true), 0.0);
+        s.enabled = true;
+        assert_eq!(s.step(false), 0.0, "adaptive quality holds it off");
+        let first = s.step(true);
+        assert!(first > 0.0 && first < 0.1, "no full-strength pop: {first}");
+        for _ in 0..crate::constants::SEPARATION_RAMP_FRAMES {
+            s.step(true);
+        }
+        assert_eq!(s.ramp, 1.0);
+    }
+
+    #[test]
+    fn brute_force_separation_capped_to_grid() {
+        use crate::constants::SEPARATION_BRUTE_FORCE_MAX;
+        let mut s = SeparationState::default();
+        assert_eq!(s.effective_mode(100), SeparationMode::Grid);
+        s.set_mode(SeparationMode::BruteForce);
+        assert_eq!(s.effective_mode(100), SeparationMode::BruteForce);
+        assert_eq!(
+            s.effective_mode(SEPARATION_BRUTE_FORCE_MAX),
+            SeparationMode::BruteForce
+        );
+        assert_eq!(
+            s.effective_mode(SEPARATION_BRUTE_FORCE_MAX + 1),
+            SeparationMode::Grid,
+            "too many entities for the O(n^2) scan"
+        );
+        let mode: SeparationMode = serde_json::from_str("\"brute_force\"").unwrap();
+        assert_eq!(mode, SeparationMode::BruteForce);
+    }
+
+    #[test]
+    fn quality_steps_with_hysteresis() {
+        use crate::constants::*;
+        let mut q = QualityState::default();
+        q.set_budget(16.0);
+        // Brief spikes don't change the level
+        for _ in 0..QUALITY_DOWNGRADE_FRAMES / 2 {
+            q.record(40.0);
+        }
+        for _ in 0..50 {
+            q.record(14.0); // dead band: smoothed settles between 12 and 16
+        }
+        assert_eq!(q.level, 0);
+        // Sustained overload steps down one level per window
+        let mut changes = Vec::new();
+        for _ in 0..QUALITY_DOWNGRADE_FRAMES * 10 {
+            if let Some(l) = q.record(40.0) {
+                changes.push(l);
+            }
+        }
+        assert_eq!(changes, vec![1, 2, 3, 4]);
+        assert_eq!(q.projectile_cap(), QUALITY_PROJECTILE_CAP);
+        assert!(!q.separation_allowed());
+        // Headroom steps back up, slower than it stepped down
+        let mut frames = 0;
+        while q.level == QUALITY_MAX_LEVEL {
+            q.record(5.0);
+            frames += 1;
+        }
+        assert!(frames >= QUALITY_UPGRADE_FRAMES);
+        // Budget 0 disables and resets
+        q.set_budget(0.0);
+        q.record(100.0);
+        assert_eq!(q.level, 0);
+        assert_eq!(q.readback_mult(), 1);
+    }
+
+    #[test]
+    fn faction_stance_gates_initiation_and_defaults_to_none() {
+        assert!(!FactionStance::Passive.may_initiate(0.0));
+        assert!(FactionStance::Defensive.may_initiate(100.0));
+        assert!(
+            !FactionStance::Defensive.may_initiate(crate::constants::STANCE_DEFENSIVE_RADIUS + 1.0)
+        );
+        assert!(FactionStance::Aggressive.may_initiate(1e6));
+
+        // Saves without the field load as "no override"
+        let old: FactionData =
+            serde_json::from_str(r#"{"kind":"AiBuilder","name":"Old","towns":[2]}"#).unwrap();
+        assert_eq!(old.stance, None);
+
+        let mut list = FactionList::default();
+        list.factions.push(old.clone());
+        list.factions.push(FactionData {
+            stance: Some(FactionStance::Passive),
+            ..old
+        });
+        assert_eq!(list.stance(0), None);
+        assert_eq!(list.stance(1), Some(FactionStance::Passive));
+        assert_eq!(list.stance(-1), None);
+        assert_eq!(list.stance(9), None);
+    }
+
+    #[test]
+    fn combat_rng_default_is_flat_and_seed_is_reproducible() {
+        let mut flat = CombatRng::default();
+        assert_eq!(flat.roll(10.0, 1.5, 1.5), (10.0, AttackRoll::Hit));
+        assert_eq!(flat.counter, 0);
+
+        let cfg = CombatRng {
+            seed: 42,
+            miss_chance: 0.2,
+            crit_chance: 0.2,
+            damage_spread: 0.1,
+            ..Default::default()
+        };
+        let (mut a, mut b) = (cfg.clone(), cfg);
+        let rolls_a: Vec<_> = (0..200).map(|_| a.roll(10.0, 0.0, 0.0)).collect();
+        let rolls_b: Vec<_> = (0..200).map(|_| b.roll(10.0, 0.0, 0.0)).collect();
+        assert_eq!(rolls_a, rolls_b);
+        assert!(rolls_a.iter().any(|r| r.1 == AttackRoll::Miss));
+        assert!(rolls_a.iter().any(|r| r.1 == AttackRoll::Crit));
+        for (dmg, roll) in rolls_a {
+            match roll {
+                AttackRoll::Miss => assert_eq!(dmg, 0.0),
+                AttackRoll::Hit => assert!((9.0..=11.0).contains(&dmg)),
+                AttackRoll::Crit => assert!((18.0..=22.0).contains(&dmg)),
+            }
+        }
+    }
+
+    #[test]
+    fn combat_rng_traits_shift_chances() {
+        let mut rng = CombatRng {
+            sharpshot_crit: 1.0,
+            swift_dodge: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(rng.roll(10.0, 1.0, 0.0).1, AttackRoll::Crit);
+        assert_eq!(rng.roll(10.0, 0.0, 1.0).1, AttackRoll::Miss);
+        assert_eq!(rng.roll(10.0, -1.0, -1.0), (10.0, AttackRoll::Hit));
+    }
+
+    #[test]
+    fn world_bounds_clamp_and_unset() {
+        let unset = WorldBounds::default();
+        assert!(!unset.is_set());
+        assert_eq!(unset.clamp(Vec2::new(-5.0, 1e6)), Vec2::new(-5.0, 1e6));
+        assert!(unset.contains(Vec2::splat(-1e6)));
+
+        let b = WorldBounds::from_grid(10, 5, 64.0);
+        assert_eq!(b.max, Vec2::new(640.0, 320.0));
+        assert_eq!(b.clamp(Vec2::new(-10.0, 500.0)), Vec2::new(0.0, 320.0));
+        assert_eq!(b.clamp(Vec2::new(100.0, 100.0)), Vec2::new(100.0, 100.0));
+        assert!(b.contains(Vec2::new(640.0, 0.0)));
+        assert!(!b.contains(Vec2::new(641.0, 0.0)));
+    }
+
+    #[test]
+    fn version_summary_includes_build_and_gpu() {
+        let info = VersionInfo {
+            gpu_device_name: "Test Adapter".into(),
+            gpu_backend: "Vulkan".into(),
+            ..Default::default()
+        };
+        let summary = info.summary();
+        assert!(summary.contains(env!("CARGO_PKG_VERSION")));
+        assert!(summary.contains(env!("BUILD_COMMIT")));
+        assert!(summary.contains("Test Adapter [Vulkan]"));
+    }
+
+    #[test]
+    fn projectile_rate_limit_is_per_shooter_per_second() {
+        let mut alloc = ProjSlotAllocator::default();
+        alloc.set_limits(ProjectileLimits {
+            per_shooter_per_sec: 2,
+            ..Default::default()
+        });
+        assert!(alloc.alloc_combat(7, 10.0).is_some());
+        assert!(alloc.alloc_combat(7, 10.2).is_some());
+        assert!(alloc.alloc_combat(7, 10.4).is_none());
+        // Another shooter has its own budget
+        assert!(alloc.alloc_combat(8, 10.4).is_some());
+        // Oldest shot ages out after a second
+        assert!(alloc.alloc_combat(7, 11.0).is_some());
+        assert_eq!(alloc.drops.rate_limited, 1);
+    }
+
+    #[test]
+    fn projectile_eviction_recycles_oldest_live_slot() {
+        let mut alloc = ProjSlotAllocator::default();
+        alloc.set_limits(ProjectileLimits {
+            global_cap: 3,
+            ..Default::default()
+        });
+        let slots: Vec<usize> = (0..3)
+            .map(|i| alloc.alloc_combat(i, 0.0).unwrap().idx())
+            .collect();
+        assert_eq!(alloc.alloc_combat(9, 0.0), None);
+        assert_eq!(alloc.drops.at_capacity, 1);
+
+        alloc.set_limits(ProjectileLimits {
+            global_cap: 3,
+            evict_oldest: true,
+            ..Default::default()
+        });
+        // Re-register spawn order under eviction: free and refill
+        for &slot in &slots {
+            alloc.free(slot);
+        }
+        let a = alloc.alloc_combat(0, 1.0).unwrap().idx();
+        let b = alloc.alloc_combat(1, 1.0).unwrap().idx();
+        let c = alloc.alloc_combat(2, 1.0).unwrap().idx();
+        // `a` already hit something: the oldest live one is `b`
+        alloc.free(a);
+        let a2 = alloc.alloc_combat(3, 1.0).unwrap();
+        assert_eq!(a2, CombatSlot::Fresh(a));
+        assert_eq!(alloc.alloc_combat(4, 1.0), Some(CombatSlot::Evicted(b)));
+        assert_eq!(alloc.alloc_combat(5, 1.0), Some(CombatSlot::Evicted(c)));
+        assert_eq!(alloc.drops.evicted, 2);
+        assert_eq!(alloc.alive(), 3);
+    }
+
+    #[test]
+    fn crash_context_keeps_latest_log_lines_in_time_order() {
+        let mut log = CombatLog::default();
+        log.push(CombatEventKind::Kill, 0, 2, 5, 0, "late kill".into());
+        log.push(CombatEventKind::Raid, 0, 1, 9, 30, "early raid".into());
+        for i in 0..CRASH_CONTEXT_LOG_LINES {
+            log.push(
+                CombatEventKind::Kill,
+                0,
+                1,
+                10,
+                i as i32,
+                format!("kill {i}"),
+            );
+        }
+        let tail = CrashContext::log_tail(&log, CRASH_CONTEXT_LOG_LINES);
+        assert_eq!(tail.len(), CRASH_CONTEXT_LOG_LINES);
+        assert!(!tail.iter().any(|l| l.contains("early raid")));
+        assert_eq!(tail.last().unwrap(), "[D2 05:00] late kill");
+
+        let ctx = CrashContext {
+            day: 2,
+            hour: 5,
+            npc_count: 42,
+            town_count: 3,
+            recent_log: tail,
+            combat_seed: 7,
+        };
+        let summary = ctx.summary();
+        assert!(summary.starts_with("Day 2 05:00 | NPCs: 42 | Towns: 3 | Combat seed: 7"));
+        assert!(summary.contains("\n  [D2 05:00] late kill"));
+    }
+
     #[test]
     fn open_armory_closes_legacy_inventory_tab() {
         let mut ui = UiState {
@@ -2396,4 +5555,279 @@ mod tests {
         assert!(ui.armory_open);
         assert!(!ui.left_panel_open);
     }
+
+    #[test]
+    fn tutorial_advances_through_table_then_done() {
+        let mut t = TutorialState::default();
+        let n = t.steps.len();
+        assert!(t.current_step().is_none());
+
+        t.start(1.0);
+        assert_eq!(t.step_number(), Some(1));
+        for i in 1..n {
+            assert!(!t.advance(1.0 + i as f64));
+            assert_eq!(t.step_number(), Some(i + 1));
+        }
+        assert!(t.advance(100.0), "advancing past the last step finishes");
+        assert_eq!(t.progress, TutorialProgress::Done);
+        assert!(t.current_step().is_none());
+    }
+
+    #[test]
+    fn tutorial_empty_table_finishes_on_start() {
+        let mut t = TutorialState {
+            steps: Vec::new(),
+            ..Default::default()
+        };
+        t.start(0.0);
+        assert_eq!(t.progress, TutorialProgress::Done);
+    }
+
+    #[test]
+    fn tutorial_default_table_preserves_steps() {
+        let t = TutorialState::default();
+        assert_eq!(t.steps.len(), 24);
+        assert_eq!(
+            t.steps[2].completion_condition,
+            TutorialCondition::BuildCount(crate::world::BuildingKind::Farm, 1)
+        );
+        let kinds = t.tracked_building_kinds();
+        assert_eq!(
+            kinds.len(),
+            5,
+            "farm, farmer home, waypoint, archer home, miner home"
+        );
+    }
+
+    #[test]
+    fn tribute_pays_share_of_income_not_stockpile() {
+        let mut state = TributeState::default();
+        state.set(Tribute::new(1, 0, 2, 1));
+        state.tributes[0].fraction = 0.5;
+
+        // Income for other towns is ignored
+        state.record_income(3, 100, 100);
+        state.record_income(1, 40, 10);
+        state.record_income(1, 20, 0);
+        let t = &mut state.tributes[0];
+        assert_eq!((t.accrued_food, t.accrued_gold), (60, 10));
+
+        // Big stockpile, small income → pays half the income only
+        assert_eq!(t.settle(10_000, 10_000), (30, 5));
+        assert_eq!((t.accrued_food, t.accrued_gold, t.hours), (0, 0, 0));
+
+        // No income since → nothing owed, even with a stockpile
+        assert_eq!(t.settle(10_000, 10_000), (0, 0));
+
+        // Income already spent → capped by what's left
+        t.accrued_food = 100;
+        assert_eq!(t.settle(7, 0), (7, 0));
+        assert_eq!((t.paid_food, t.paid_gold), (37, 5));
+
+        // One overlord per town; replacing keeps a single entry
+        state.set(Tribute::new(1, 2, 2, 3));
+        assert_eq!(state.tributes.len(), 1);
+        assert_eq!(state.paid_by(1).map(|t| t.to_town), Some(2));
+        assert!(state.clear(1));
+        assert!(!state.clear(1));
+    }
+
+    #[test]
+    fn perf_history_wraps_and_reports_stats() {
+        let timings = SystemTimings::default();
+        let sample = |ms: f32| PerfSample {
+            frame_ms: ms,
+            game_ms: ms * 0.5,
+            ..Default::default()
+        };
+        // Disabled profiler records nothing
+        timings.record_history(sample(1.0));
+        assert!(timings.get_perf_history().frame_ms.is_empty());
+
+        let mut timings = timings;
+        timings.enabled = true;
+        for i in 0..PERF_HISTORY_LEN + 10 {
+            timings.record_history(sample(i as f32));
+        }
+        let snap = timings.get_perf_history();
+        assert_eq!(snap.frame_ms.len(), PERF_HISTORY_LEN);
+        assert_eq!(snap.game_ms.len(), PERF_HISTORY_LEN);
+        // Oldest 10 evicted, order preserved
+        assert_eq!(snap.frame_ms[0], 10.0);
+        assert_eq!(
+            snap.frame_ms[PERF_HISTORY_LEN - 1],
+            (PERF_HISTORY_LEN + 9) as f32
+        );
+        assert_eq!(snap.game_ms[0], 5.0);
+
+        let stats = PerfStats::of(&snap.frame_ms);
+        assert_eq!(stats.min, 10.0);
+        assert_eq!(stats.max, (PERF_HISTORY_LEN + 9) as f32);
+        assert_eq!(stats.mean, (10.0 + (PERF_HISTORY_LEN + 9) as f32) / 2.0);
+        assert_eq!(PerfStats::of(&[]), PerfStats::default());
+    }
+
+    #[test]
+    fn energy_thresholds_keep_hysteresis_band() {
+        use crate::components::Job;
+        use crate::constants::ENERGY_THRESHOLD_MIN_GAP;
+        let mut t = EnergyThresholds::default();
+        assert_eq!(t.get(Job::Archer), EnergyThreshold::default());
+
+        // Valid band stored as-is, other jobs untouched
+        let (set, clamped) = t.set(Job::Archer, 15.0, 80.0);
+        assert!(!clamped);
+        assert_eq!((set.rest_below, set.resume_above), (15.0, 80.0));
+        assert_eq!(t.get(Job::Farmer), EnergyThreshold::default());
+
+        // Inverted / too-narrow band: rest_below pulled under resume_above
+        let (set, clamped) = t.set(Job::Farmer, 70.0, 60.0);
+        assert!(clamped);
+        assert_eq!(set.rest_below, 60.0 - ENERGY_THRESHOLD_MIN_GAP);
+        let (set, clamped) = t.set(Job::Farmer, -5.0, 150.0);
+        assert!(clamped);
+        assert_eq!((set.rest_below, set.resume_above), (0.0, 100.0));
+
+        // Rest scoring starts above rest_below but never past resume_above
+        assert_eq!(EnergyThreshold::default().rest_score_below(), 50.0);
+        let (set, _) = EnergyThreshold::clamped(40.0, 55.0);
+        assert_eq!(set.rest_score_below(), 55.0);
+    }
+
+    #[test]
+    fn day_length_change_keeps_clock_continuous() {
+        let mut gt = GameTime::default();
+        gt.total_seconds = 10.5 * gt.seconds_per_hour;
+        let (day, hour, minute) = (gt.day(), gt.hour(), gt.minute());
+        gt.set_day_length(480.0); // 20s per hour
+        assert_eq!((gt.day(), gt.hour(), gt.minute()), (day, hour, minute));
+        assert_eq!(gt.day_length(), 480.0);
+        // Next hour now takes half an hour's worth of the new rate
+        gt.total_seconds += 10.0;
+        assert_eq!(gt.total_hours(), 11);
+
+        gt.set_daylight(8, 18);
+        gt.total_seconds += 2.0 * 20.0; // 19:00
+        assert!(!gt.is_daytime());
+        gt.set_daylight(30, 2);
+        assert_eq!((gt.dawn_hour, gt.dusk_hour), (23, 24));
+    }
+
+    #[test]
+    fn npc_highlight_clears_previous_selection() {
+        let mut hl = NpcHighlight::default();
+        let want = hl.desired(Some(4));
+        assert_eq!(hl.sync(want), vec![(4, NpcHighlight::SELECTED)]);
+        // Unchanged: nothing to write
+        let want = hl.desired(Some(4));
+        assert!(hl.sync(want).is_empty());
+        // Selection moves: old slot cleared explicitly, new one set
+        let want = hl.desired(Some(9));
+        assert_eq!(hl.sync(want), vec![(4, 0.0), (9, NpcHighlight::SELECTED)]);
+        // Hover off by default; on, it sits under selection and scripted
+        hl.hover = true;
+        hl.hovered = Some(9);
+        hl.scripted.insert(2);
+        let want = hl.desired(None);
+        assert_eq!(
+            hl.sync(want),
+            vec![(2, NpcHighlight::SELECTED), (9, NpcHighlight::HOVERED)]
+        );
+        hl.scripted.clear();
+        hl.hovered = None;
+        let want = hl.desired(None);
+        assert_eq!(hl.sync(want), vec![(2, 0.0), (9, 0.0)]);
+    }
+
+    #[test]
+    fn idle_cycle_round_robins_without_skipping() {
+        let mut cycle = IdleCycle::default();
+        assert_eq!(cycle.next(0, &[]), None);
+        assert_eq!(cycle.next(0, &[3, 7, 9]), Some(3));
+        assert_eq!(cycle.next(0, &[3, 7, 9]), Some(7));
+        // 7 got assigned work: the cycle still lands on 9, not past it
+        assert_eq!(cycle.next(0, &[3, 9]), Some(9));
+        assert_eq!(cycle.next(0, &[3, 9]), Some(3));
+        // A new idle unit slots into order
+        assert_eq!(cycle.next(0, &[3, 5, 9]), Some(5));
+        // Other town restarts from the front
+        assert_eq!(cycle.next(1, &[4, 8]), Some(4));
+    }
+
+    #[test]
+    fn scripted_motion_zeroes_velocities_on_exit() {
+        let mut motion = ScriptedMotion::default();
+        assert!(motion.set_mode(ComputeMode::Scripted).is_empty());
+        motion.set_velocity(8, Vec2::new(40.0, 0.0));
+        motion.set_velocity(3, Vec2::new(0.0, -20.0));
+        motion.set_velocity(5, Vec2::new(10.0, 10.0));
+        // Stopped explicitly: nothing left to zero
+        motion.set_velocity(5, Vec2::ZERO);
+        // Re-selecting scripted keeps them
+        assert!(motion.set_mode(ComputeMode::Scripted).is_empty());
+        assert_eq!(motion.set_mode(ComputeMode::Normal), vec![3, 8]);
+        assert!(motion.moving.is_empty());
+        assert!(motion.set_mode(ComputeMode::Normal).is_empty());
+    }
+
+    #[test]
+    fn faction_mvp_tracks_kills_and_rescans_on_death() {
+        let mut mvp = FactionMvp::default();
+        mvp.on_kill(1, 7, 1);
+        mvp.on_kill(1, 3, 1);
+        // Tie: lowest slot wins
+        assert_eq!(mvp.mvp[&1], MvpEntry { slot: 3, kills: 1 });
+        mvp.on_kill(1, 7, 2);
+        assert_eq!(mvp.mvp[&1].slot, 7);
+        // Equal kills from a higher slot never steals it
+        mvp.on_kill(1, 9, 2);
+        assert_eq!(mvp.mvp[&1].slot, 7);
+        // Non-MVP death is a no-op; MVP death queues a rescan
+        mvp.on_death(1, 3);
+        assert!(mvp.stale.is_empty());
+        mvp.on_death(1, 7);
+        assert!(mvp.stale.contains(&1) && !mvp.mvp.contains_key(&1));
+        mvp.rescan(1, [(12, 2), (9, 2), (3, 1)]);
+        assert_eq!(mvp.mvp[&1], MvpEntry { slot: 9, kills: 2 });
+        assert!(mvp.stale.is_empty());
+        mvp.rescan(1, [(4, 0)]);
+        assert!(!mvp.mvp.contains_key(&1));
+
+        for i in 0..FactionMvp::FEED_LEN + 3 {
+            mvp.push_feed(
+                2,
+                KillFeedEntry {
+                    killer_slot: i,
+                    killer_name: String::new(),
+                    victim_name: String::new(),
+                    victim_faction: 1,
+                    day: 1,
+                    hour: 0,
+                    minute: 0,
+                },
+            );
+        }
+        let feed = &mvp.feeds[&2];
+        assert_eq!(feed.len(), FactionMvp::FEED_LEN);
+        assert_eq!(feed.front().unwrap().killer_slot, 3);
+    }
+
+    #[test]
+    fn player_focus_falls_back_to_owned_town() {
+        use crate::constants::{FACTION_PLAYER, TownKind};
+        let town = |faction| crate::world::Town {
+            name: String::new(),
+            center: Vec2::ZERO,
+            faction,
+            kind: TownKind::Player,
+        };
+        let mut towns = vec![town(2), town(FACTION_PLAYER), town(FACTION_PLAYER)];
+        assert_eq!(PlayerFocus(None).town(&towns), Some(1));
+        assert_eq!(PlayerFocus(Some(2)).town(&towns), Some(2));
+        assert_eq!(PlayerFocus(Some(0)).town(&towns), Some(1), "AI town");
+        towns[2].faction = 3; // captured
+        assert_eq!(PlayerFocus(Some(2)).town(&towns), Some(1));
+        towns[1].faction = 3;
+        assert_eq!(PlayerFocus(Some(1)).town(&towns), None);
+    }
 }
@@ -1,12 +1,14 @@
 //! Save/Load system with quicksave/quickload shortcuts and JSON serialization.
 //! Save format is self-contained: dedicated serde structs decouple from ECS types.
 
+use bevy::ecs::message::Messages;
+use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::components::*;
-use crate::constants::{ItemKind, MAX_SQUADS};
-use crate::messages::GpuUpdateMsg;
+use crate::constants::{DEFAULT_DAWN_HOUR, DEFAULT_DUSK_HOUR, ItemKind, MAX_SQUADS};
+use crate::messages::{GpuUpdate, GpuUpdateMsg};
 use crate::resources::*;
 use crate::settings::{ControlAction, UserSettings};
 use crate::systems::AiPlayerState;
@@ -199,6 +201,15 @@ pub struct SaveData {
     pub total_seconds: f32,
     pub seconds_per_hour: f32,
     pub time_scale: f32,
+    // Clock anchor from the last day-length change (0/0 = never changed)
+    #[serde(default)]
+    pub clock_origin_hours: f32,
+    #[serde(default)]
+    pub clock_origin_seconds: f32,
+    #[serde(default = "default_dawn_hour")]
+    pub dawn_hour: i32,
+    #[serde(default = "default_dusk_hour")]
+    pub dusk_hour: i32,
     #[serde(default)]
     pub food: Vec<i32>,
     #[serde(default)]
@@ -282,10 +293,32 @@ pub struct SaveData {
     #[serde(default)]
     pub faction_list: Vec<crate::resources::FactionData>,
 
+    // Tribute relationships (subject town → overlord)
+    #[serde(default)]
+    pub tributes: Vec<crate::resources::Tribute>,
+
     // Faction-vs-faction reputation matrix (2D).
     #[serde(default, deserialize_with = "deserialize_reputation")]
     pub reputation: Vec<Vec<f32>>,
 
+    // Scenario win/loss conditions (and the outcome, once decided)
+    #[serde(default)]
+    pub win_condition: Option<crate::systems::WinCondition>,
+
+    // Tech definitions, research settings and each town's unlocks
+    #[serde(default)]
+    pub tech: Option<crate::systems::TechTree>,
+
+    // CombatRng seed and roll counter, so variance rolls resume where they left off
+    #[serde(default)]
+    pub combat_rng: Option<CombatRngSave>,
+
+    // Happiness settings and each town's meter (old saves start with the feature off)
+    #[serde(default)]
+    pub happiness_config: Option<crate::systems::HappinessConfig>,
+    #[serde(default)]
+    pub town_happiness: Option<crate::systems::TownHappiness>,
+
     // Building vecs + towns — registry-driven via #[serde(flatten)]
     // Captures: towns, farms, beds, waypoints, farmer_homes, archer_homes,
     // crossbow_homes, fighter_homes, tents, miner_homes, gold_mines
@@ -344,6 +377,23 @@ pub struct SquadSave {
     pub member_uids: Option<Vec<u64>>,
     #[serde(default)]
     pub loot_threshold: Option<usize>,
+    #[serde(default)]
+    pub target_priority: TargetPriority,
+    #[serde(default)]
+    pub stance: SquadStance,
+    #[serde(default = "default_squad_engage_radius")]
+    pub engage_radius: f32,
+    /// Rally-then-attack order still gathering (`SquadRally`); `None` for plain targets.
+    #[serde(default)]
+    pub rally: Option<SquadRallySave>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SquadRallySave {
+    pub rally: [f32; 2],
+    pub attack: [f32; 2],
+    /// Game seconds the order was issued; game time is restored with the save.
+    pub issued_at: f32,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -353,6 +403,12 @@ pub struct FactionStatSave {
     pub kills: i32,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct CombatRngSave {
+    pub seed: u64,
+    pub counter: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct AiPlayerSave {
     pub town_data_idx: usize,
@@ -392,14 +448,29 @@ pub struct MigrationSave {
     pub starting_food: i32,
     #[serde(default)]
     pub starting_gold: i32,
+    #[serde(default)]
+    pub settle_target: Option<[f32; 2]>,
+    #[serde(default)]
+    pub source_town: Option<usize>,
+    #[serde(default)]
+    pub join_town: Option<usize>,
 }
 
-fn default_true() -> bool {
+pub(crate) fn default_true() -> bool {
     true
 }
 fn default_wave_retreat_below_pct() -> usize {
     50
 }
+fn default_squad_engage_radius() -> f32 {
+    crate::constants::SQUAD_ENGAGE_RADIUS
+}
+fn default_dawn_hour() -> i32 {
+    DEFAULT_DAWN_HOUR
+}
+fn default_dusk_hour() -> i32 {
+    DEFAULT_DUSK_HOUR
+}
 
 fn load_squad_loot_threshold(
     saved_loot_threshold: Option<usize>,
@@ -453,12 +524,31 @@ pub struct NpcSaveData {
     pub work_position: Option<[f32; 2]>,
     pub squad_id: Option<i32>,
     #[serde(default)]
+    pub kills: i32,
+    #[serde(default)]
     pub carried_food: Option<i32>,
     pub carried_gold: Option<i32>,
     #[serde(default)]
     pub carried_equipment: Vec<crate::constants::LootItem>,
     #[serde(default)]
+    pub inventory: Vec<ItemStack>,
+    #[serde(default)]
     pub equipment: NpcEquipment,
+    /// Per-unit override only; `None` follows the squad's priority.
+    #[serde(default)]
+    pub target_priority: Option<TargetPriority>,
+    /// Promoted veterans keep their aura; `None` for everyone else.
+    #[serde(default)]
+    pub officer: Option<Officer>,
+    /// The unit's own stance (`StanceOverride`). Squad-applied stances come back from the squad.
+    #[serde(default)]
+    pub stance: Option<CombatStance>,
+    /// Manual farm/bed/post pins (`assign_npc`), re-reserved on load.
+    #[serde(default)]
+    pub assignments: Vec<AssignmentSave>,
+    /// Slot of the NPC this unit guards (`Bodyguard`). NPC slots survive a load unchanged.
+    #[serde(default)]
+    pub bodyguard_of: Option<usize>,
     // Legacy fields for backward compat (old saves)
     #[serde(default)]
     pub weapon: Option<[f32; 2]>,
@@ -468,6 +558,17 @@ pub struct NpcSaveData {
     pub armor: Option<[f32; 2]>,
 }
 
+/// One manual assignment. Building slots are reallocated on load, so the building is keyed by
+/// its position (like spawners) and resolved again once it has been placed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AssignmentSave {
+    pub kind: crate::systems::AssignmentKind,
+    pub building: [f32; 2],
+    /// Home to go back to when a bed assignment is cleared.
+    #[serde(default)]
+    pub prev_home: Option<[f32; 2]>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub enum ActivitySave {
     Idle,
@@ -818,6 +919,12 @@ pub fn collect_save_data(
     merchant_inv: &crate::resources::MerchantInventory,
     faction_list: &crate::resources::FactionList,
     bld_state: &std::collections::HashMap<usize, BuildingStateSnapshot>,
+    tribute: &crate::resources::TributeState,
+    win_condition: &crate::systems::WinCondition,
+    tech: Option<&crate::systems::TechTree>,
+    combat_rng: &crate::resources::CombatRng,
+    happiness_config: &crate::systems::HappinessConfig,
+    town_happiness: &crate::systems::TownHappiness,
 ) -> SaveData {
     // Terrain + buildings
     let terrain: Vec<u8> = grid.cells.iter().map(|c| biome_to_u8(c.terrain)).collect();
@@ -948,6 +1055,14 @@ pub fn collect_save_data(
             owner: s.owner,
             member_uids: Some(s.members.iter().map(|e| e.to_bits()).collect()),
             loot_threshold: Some(s.loot_threshold),
+            target_priority: s.target_priority,
+            stance: s.stance,
+            engage_radius: s.engage_radius,
+            rally: s.rally.map(|r| SquadRallySave {
+                rally: v2(r.rally),
+                attack: v2(r.attack),
+                issued_at: r.issued_at,
+            }),
         })
         .collect();
 
@@ -1006,6 +1121,10 @@ pub fn collect_save_data(
         total_seconds: game_time.total_seconds,
         seconds_per_hour: game_time.seconds_per_hour,
         time_scale: game_time.time_scale,
+        clock_origin_hours: game_time.origin_hours,
+        clock_origin_seconds: game_time.origin_seconds,
+        dawn_hour: game_time.dawn_hour,
+        dusk_hour: game_time.dusk_hour,
         food: town_food.to_vec(),
         gold: town_gold.to_vec(),
         wood: town_wood.to_vec(),
@@ -1038,6 +1157,15 @@ pub fn collect_save_data(
         raider_max_pop: raider_state.max_pop.clone(),
         faction_stats: faction_stats_save,
         faction_list: faction_list.factions.clone(),
+        tributes: tribute.tributes.clone(),
+        win_condition: Some(win_condition.clone()),
+        tech: tech.cloned(),
+        combat_rng: Some(CombatRngSave {
+            seed: combat_rng.seed,
+            counter: combat_rng.counter,
+        }),
+        happiness_config: Some(happiness_config.clone()),
+        town_happiness: Some(town_happiness.clone()),
         reputation: reputation.values.clone(),
         kill_stats: [kill_stats.archer_kills, kill_stats.villager_kills],
         npcs,
@@ -1056,6 +1184,9 @@ pub fn collect_save_data(
                 upgrade_levels: g.upgrade_levels.clone(),
                 starting_food: g.starting_food,
                 starting_gold: g.starting_gold,
+                settle_target: Some(v2(g.settle_target)),
+                source_town: g.source_town,
+                join_town: g.join_town,
             }),
         next_entity_uid: None, // legacy field, no longer used
         next_loot_item_id: next_loot_id.next,
@@ -1067,18 +1198,102 @@ pub fn collect_save_data(
     }
 }
 
+/// Leading bytes of a compressed save; a deflate stream of the JSON follows. Plain saves
+/// start with `{`, so the two formats never collide.
+const COMPRESSED_SAVE_MAGIC: &[u8] = b"ENDLSZ01";
+
+/// How save files are written (`compress_saves` setting). Reading detects the format on its
+/// own, so a compressed save loads whatever `compress` is now.
+#[derive(Resource, Default)]
+pub struct SaveConfig {
+    /// Deflate save files behind `COMPRESSED_SAVE_MAGIC`. Off = plain JSON.
+    pub compress: bool,
+    last_save: Option<SaveWriteInfo>,
+}
+
+impl SaveConfig {
+    /// Size and cost of the most recent save written this session.
+    pub fn get_last_save_info(&self) -> Option<&SaveWriteInfo> {
+        self.last_save.as_ref()
+    }
+}
+
+/// What writing one save cost.
+#[derive(Clone, Debug)]
+pub struct SaveWriteInfo {
+    pub path: std::path::PathBuf,
+    pub compressed: bool,
+    /// Size of the save as plain JSON.
+    pub json_bytes: usize,
+    /// Size of the file on disk (equal to `json_bytes` when uncompressed).
+    pub file_bytes: usize,
+    /// Serialize + compress + write, in milliseconds.
+    pub write_ms: f32,
+}
+
+impl SaveWriteInfo {
+    /// Short size/time summary for toasts and logs.
+    pub fn summary(&self) -> String {
+        let mb = |b: usize| b as f32 / (1024.0 * 1024.0);
+        if self.compressed {
+            format!(
+                "{:.1} MB -> {:.1} MB in {:.0} ms",
+                mb(self.json_bytes),
+                mb(self.file_bytes),
+                self.write_ms
+            )
+        } else {
+            format!("{:.1} MB in {:.0} ms", mb(self.file_bytes), self.write_ms)
+        }
+    }
+}
+
 /// Write SaveData to the quicksave file.
-pub fn write_save(data: &SaveData) -> Result<(), String> {
+pub fn write_save(data: &SaveData, compress: bool) -> Result<SaveWriteInfo, String> {
     let path = quicksave_path().ok_or("cannot determine save directory")?;
-    write_save_to(data, &path)
+    write_save_to(data, &path, compress)
 }
 
-/// Write save data to a specific path.
-pub fn write_save_to(data: &SaveData, path: &std::path::Path) -> Result<(), String> {
-    let json = serde_json::to_string(data).map_err(|e| format!("serialize: {e}"))?;
-    std::fs::write(path, json).map_err(|e| format!("write {}: {e}", path.display()))?;
-    info!("Game saved to {}", path.display());
-    Ok(())
+/// Write save data to a specific path, deflated when `compress` is set.
+pub fn write_save_to(
+    data: &SaveData,
+    path: &std::path::Path,
+    compress: bool,
+) -> Result<SaveWriteInfo, String> {
+    let start = std::time::Instant::now();
+    let (json_bytes, bytes) = encode_save(data, compress)?;
+    std::fs::write(path, &bytes).map_err(|e| format!("write {}: {e}", path.display()))?;
+    let info = SaveWriteInfo {
+        path: path.to_path_buf(),
+        compressed: compress,
+        json_bytes,
+        file_bytes: bytes.len(),
+        write_ms: start.elapsed().as_secs_f32() * 1000.0,
+    };
+    info!("Game saved to {} ({})", path.display(), info.summary());
+    Ok(info)
+}
+
+/// Save file bytes: plain JSON, or the magic header plus deflated JSON. Also returns the
+/// plain JSON size.
+fn encode_save<T: Serialize>(data: &T, compress: bool) -> Result<(usize, Vec<u8>), String> {
+    if !compress {
+        let json = serde_json::to_vec(data).map_err(|e| format!("serialize: {e}"))?;
+        return Ok((json.len(), json));
+    }
+    let (json_bytes, packed) = compress_json(data)?;
+    let mut bytes = Vec::with_capacity(COMPRESSED_SAVE_MAGIC.len() + packed.len());
+    bytes.extend_from_slice(COMPRESSED_SAVE_MAGIC);
+    bytes.extend_from_slice(&packed);
+    Ok((json_bytes, bytes))
+}
+
+/// Parse save file bytes in either format.
+fn decode_save<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    match bytes.strip_prefix(COMPRESSED_SAVE_MAGIC) {
+        Some(packed) => decompress_json(packed),
+        None => serde_json::from_slice(bytes).map_err(|e| format!("deserialize: {e}")),
+    }
 }
 
 /// Return the path for a rotating autosave slot (0, 1, 2).
@@ -1119,9 +1334,8 @@ pub fn list_saves() -> Vec<SaveFileInfo> {
 
 /// Read SaveData from an arbitrary path.
 pub fn read_save_from(path: &std::path::Path) -> Result<SaveData, String> {
-    let json =
-        std::fs::read_to_string(path).map_err(|e| format!("read {}: {e}", path.display()))?;
-    let data: SaveData = serde_json::from_str(&json).map_err(|e| format!("deserialize: {e}"))?;
+    let bytes = std::fs::read(path).map_err(|e| format!("read {}: {e}", path.display()))?;
+    let data: SaveData = decode_save(&bytes)?;
     if data.version > SAVE_VERSION {
         return Err(format!(
             "save version {} > supported {}",
@@ -1131,6 +1345,10 @@ pub fn read_save_from(path: &std::path::Path) -> Result<SaveData, String> {
     if data.version < SAVE_VERSION {
         info!("Migrating save from v{} to v{}", data.version, SAVE_VERSION);
     }
+    if let Some(tech) = &data.tech {
+        tech.validate()
+            .map_err(|e| format!("invalid tech tree in {}: {e}", path.display()))?;
+    }
     Ok(data)
 }
 
@@ -1218,6 +1436,9 @@ pub fn apply_save(
     game_time.total_seconds = save.total_seconds;
     game_time.seconds_per_hour = save.seconds_per_hour;
     game_time.time_scale = save.time_scale.max(0.0);
+    game_time.origin_hours = save.clock_origin_hours;
+    game_time.origin_seconds = save.clock_origin_seconds;
+    game_time.set_daylight(save.dawn_hour, save.dusk_hour);
     game_time.start_hour = 6;
     game_time.last_hour = game_time.total_hours();
     game_time.hour_ticked = false;
@@ -1272,12 +1493,20 @@ pub fn apply_save(
             wave_retreat_below_pct: ss.wave_retreat_below_pct.clamp(1, 100),
             owner: ss.owner,
             hold_fire: false,
+            target_priority: ss.target_priority,
+            stance: ss.stance,
+            engage_radius: ss.engage_radius.max(0.0),
             loot_threshold: load_squad_loot_threshold(
                 ss.loot_threshold,
                 ss.owner,
                 &saved_policies,
                 player_town_idx,
             ),
+            rally: ss.rally.map(|r| SquadRally {
+                rally: to_vec2(r.rally),
+                attack: to_vec2(r.attack),
+                issued_at: r.issued_at,
+            }),
         });
     }
     // Ensure at least MAX_SQUADS player squads exist.
@@ -1356,7 +1585,7 @@ pub fn apply_save(
         migration_state.active = Some(MigrationGroup {
             boat_slot: None,
             boat_pos: Vec2::ZERO,
-            settle_target: Vec2::ZERO,
+            settle_target: ms.settle_target.map(to_vec2).unwrap_or(Vec2::ZERO),
             is_raider: ms.is_raider,
             upgrade_levels: ms.upgrade_levels.clone(),
             starting_food: ms.starting_food,
@@ -1364,6 +1593,8 @@ pub fn apply_save(
             member_slots: ms.member_slots.clone(),
             faction: ms.faction,
             town_data_idx: ms.town_data_idx,
+            source_town: ms.source_town,
+            join_town: ms.join_town,
         });
         migration_state.check_timer = ms.check_timer;
     } else {
@@ -1475,109 +1706,324 @@ pub struct SaveToast {
 // ============================================================================
 
 /// Collect NPC save data from EntityMap NpcInstances + ECS queries.
-pub fn collect_npc_data(
-    entity_map: &EntityMap,
-    npc_stats_q: &Query<&NpcStats>,
-    squad_id_q: &Query<&SquadId>,
-    activity_q: &Query<&Activity>,
-    position_q: &Query<&Position>,
-    health_q: &Query<&Health, Without<Building>>,
-    energy_q: &Query<&Energy>,
-    combat_state_q: &Query<&CombatState>,
-    attack_type_q: &Query<&BaseAttackType>,
-    personality_q: &Query<&Personality>,
-    home_q: &Query<&Home>,
-    work_state_q: &Query<&NpcWorkState>,
-    carried_loot_q: &Query<&CarriedLoot>,
-    equipment_q: &Query<&NpcEquipment>,
-    has_energy_q: &Query<&HasEnergy>,
-) -> Vec<NpcSaveData> {
-    let mut npcs = Vec::new();
-    for npc in entity_map.iter_npcs() {
-        if npc.dead {
-            continue;
-        }
-        let idx = npc.slot;
-        let stats = npc_stats_q.get(npc.entity).cloned().unwrap_or_default();
-
-        npcs.push(NpcSaveData {
-            slot: idx,
-            position: position_q
-                .get(npc.entity)
-                .map(|p| [p.x, p.y])
-                .unwrap_or([0.0, 0.0]),
-            job: match npc.job {
-                Job::Farmer => 0,
-                Job::Archer => 1,
-                Job::Raider => 2,
-                Job::Fighter => 3,
-                Job::Miner => 4,
-                Job::Crossbow => 5,
-                Job::Boat => 6,
-                Job::Woodcutter => 7,
-                Job::Quarrier => 8,
-            },
-            faction: npc.faction,
-            town_id: npc.town_idx,
-            health: health_q.get(npc.entity).map(|h| h.0).unwrap_or(100.0),
-            uid: entity_map.entities.get(&idx).map(|e| e.to_bits()),
-            energy: if has_energy_q.get(npc.entity).is_ok() {
-                energy_q.get(npc.entity).map(|e| e.0).unwrap_or(100.0)
-            } else {
-                100.0
-            },
-            activity: activity_q
-                .get(npc.entity)
-                .map(ActivitySave::from_activity)
-                .unwrap_or(ActivitySave::Idle),
-            combat_state: combat_state_q
-                .get(npc.entity)
-                .map(CombatStateSave::from_combat_state)
-                .unwrap_or(CombatStateSave::None),
-            personality: personality_q
-                .get(npc.entity)
-                .map(PersonalitySave::from_personality)
-                .unwrap_or_default(),
-            name: stats.name.clone(),
-            level: crate::systems::stats::level_from_xp(stats.xp),
-            xp: stats.xp,
-            attack_type: match attack_type_q
-                .get(npc.entity)
-                .copied()
-                .unwrap_or(BaseAttackType::Melee)
-            {
-                BaseAttackType::Melee => 0,
-                BaseAttackType::Ranged => 1,
-            },
-            home: home_q
-                .get(npc.entity)
-                .map(|h| [h.0.x, h.0.y])
-                .unwrap_or([0.0, 0.0]),
-            work_position: work_state_q
-                .get(npc.entity)
-                .ok()
-                .and_then(|ws| ws.worksite)
-                .and_then(|e| entity_map.instance_by_entity(e).map(|i| v2(i.position))),
-            squad_id: squad_id_q.get(npc.entity).ok().map(|s| s.0),
-            carried_food: carried_loot_q
-                .get(npc.entity)
-                .ok()
-                .and_then(|cl| if cl.food > 0 { Some(cl.food) } else { None }),
-            carried_gold: carried_loot_q
-                .get(npc.entity)
-                .ok()
-                .and_then(|cl| if cl.gold > 0 { Some(cl.gold) } else { None }),
-            carried_equipment: carried_loot_q
-                .get(npc.entity)
-                .map(|cl| cl.equipment.clone())
-                .unwrap_or_default(),
-            equipment: equipment_q.get(npc.entity).cloned().unwrap_or_default(),
-            weapon: None,
-            helmet: None,
-            armor: None,
-        });
+pub fn collect_npc_data(entity_map: &EntityMap, nq: &SaveNpcQueries) -> Vec<NpcSaveData> {
+    entity_map
+        .iter_npcs()
+        .filter(|npc| !npc.dead)
+        .map(|npc| npc_save_data(entity_map, npc, nq))
+        .collect()
+}
+
+/// Save record for one NPC (shared by full saves and `snapshot_npc`).
+fn npc_save_data(entity_map: &EntityMap, npc: &NpcEntry, nq: &SaveNpcQueries) -> NpcSaveData {
+    let SaveNpcQueries {
+        squad_id_q,
+        activity_q,
+        position_q,
+        health_q,
+        energy_q,
+        combat_state_q,
+        attack_type_q,
+        personality_q,
+        home_q,
+        work_state_q,
+        carried_loot_q,
+        inventory_q,
+        equipment_q,
+        has_energy_q,
+        npc_stats_q,
+        militia_q,
+        target_priority_q,
+        officer_q,
+        stance_q,
+        assignment_q,
+        bodyguard_q,
+    } = nq;
+    let idx = npc.slot;
+    let stats = npc_stats_q.get(npc.entity).cloned().unwrap_or_default();
+
+    NpcSaveData {
+        slot: idx,
+        position: position_q
+            .get(npc.entity)
+            .map(|p| [p.x, p.y])
+            .unwrap_or([0.0, 0.0]),
+        // Militia is temporary: save them as the job they were conscripted from
+        job: match militia_q.get(npc.entity).map_or(npc.job, |m| m.prev_job) {
+            Job::Farmer => 0,
+            Job::Archer => 1,
+            Job::Raider => 2,
+            Job::Fighter => 3,
+            Job::Miner => 4,
+            Job::Crossbow => 5,
+            Job::Boat => 6,
+            Job::Woodcutter => 7,
+            Job::Quarrier => 8,
+        },
+        faction: npc.faction,
+        town_id: npc.town_idx,
+        health: health_q.get(npc.entity).map(|h| h.0).unwrap_or(100.0),
+        uid: entity_map.entities.get(&idx).map(|e| e.to_bits()),
+        energy: if has_energy_q.get(npc.entity).is_ok() {
+            energy_q.get(npc.entity).map(|e| e.0).unwrap_or(100.0)
+        } else {
+            100.0
+        },
+        activity: activity_q
+            .get(npc.entity)
+            .map(ActivitySave::from_activity)
+            .unwrap_or(ActivitySave::Idle),
+        combat_state: combat_state_q
+            .get(npc.entity)
+            .map(CombatStateSave::from_combat_state)
+            .unwrap_or(CombatStateSave::None),
+        personality: personality_q
+            .get(npc.entity)
+            .map(PersonalitySave::from_personality)
+            .unwrap_or_default(),
+        name: stats.name.clone(),
+        level: crate::systems::stats::level_from_xp(stats.xp),
+        xp: stats.xp,
+        kills: stats.kills,
+        attack_type: match attack_type_q
+            .get(npc.entity)
+            .copied()
+            .unwrap_or(BaseAttackType::Melee)
+        {
+            BaseAttackType::Melee => 0,
+            BaseAttackType::Ranged => 1,
+        },
+        home: home_q
+            .get(npc.entity)
+            .map(|h| [h.0.x, h.0.y])
+            .unwrap_or([0.0, 0.0]),
+        work_position: work_state_q
+            .get(npc.entity)
+            .ok()
+            .and_then(|ws| ws.worksite)
+            .and_then(|e| entity_map.instance_by_entity(e).map(|i| v2(i.position))),
+        squad_id: squad_id_q.get(npc.entity).ok().map(|s| s.0),
+        carried_food: carried_loot_q
+            .get(npc.entity)
+            .ok()
+            .and_then(|cl| if cl.food > 0 { Some(cl.food) } else { None }),
+        carried_gold: carried_loot_q
+            .get(npc.entity)
+            .ok()
+            .and_then(|cl| if cl.gold > 0 { Some(cl.gold) } else { None }),
+        carried_equipment: carried_loot_q
+            .get(npc.entity)
+            .map(|cl| cl.equipment.clone())
+            .unwrap_or_default(),
+        inventory: inventory_q
+            .get(npc.entity)
+            .map(|inv| inv.0.clone())
+            .unwrap_or_default(),
+        equipment: equipment_q.get(npc.entity).cloned().unwrap_or_default(),
+        target_priority: target_priority_q.get(npc.entity).ok().copied(),
+        officer: officer_q.get(npc.entity).ok().copied(),
+        stance: stance_q.get(npc.entity).ok().copied(),
+        assignments: assignment_q
+            .get(npc.entity)
+            .map(|(farm, bed, post)| {
+                use crate::systems::AssignmentKind;
+                let pin = |kind, building: Entity, prev_home: Option<Vec2>| {
+                    entity_map
+                        .instance_by_entity(building)
+                        .map(|inst| AssignmentSave {
+                            kind,
+                            building: v2(inst.position),
+                            prev_home: prev_home.map(v2),
+                        })
+                };
+                [
+                    farm.and_then(|f| pin(AssignmentKind::Farm, f.0, None)),
+                    bed.and_then(|b| pin(AssignmentKind::Bed, b.bed, Some(b.prev_home))),
+                    post.and_then(|p| pin(AssignmentKind::Post, p.0, None)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect()
+            })
+            .unwrap_or_default(),
+        bodyguard_of: bodyguard_q
+            .get(npc.entity)
+            .ok()
+            .and_then(|b| entity_map.slot_for_entity(b.0)),
+        weapon: None,
+        helmet: None,
+        armor: None,
     }
-    npcs
+}
+
+// ============================================================================
+// SINGLE-NPC SNAPSHOT
+// ============================================================================
+
+/// Current `NpcSnapshot` format. Added fields only need `#[serde(default)]`; bump this when
+/// an existing field changes meaning.
+pub const NPC_SNAPSHOT_VERSION: u32 = 1;
+
+/// One NPC's state for undo and test fixtures: its save record plus the slot's spawn
+/// generation and GPU movement target.
+#[derive(Serialize, Deserialize)]
+pub struct NpcSnapshot {
+    pub version: u32,
+    /// `NpcLogCache::generation` of the slot when captured — tells a reallocated slot apart.
+    pub generation: u32,
+    pub npc: NpcSaveData,
+    #[serde(default)]
+    pub target: Option<[f32; 2]>,
+}
+
+/// Capture the live NPC at `slot` as a versioned JSON snapshot.
+pub fn snapshot_npc(world: &mut World, slot: usize) -> Result<Vec<u8>, String> {
+    let mut state: SystemState<(Res<EntityMap>, SaveNpcQueries)> = SystemState::new(world);
+    let (entity_map, nq) = state.get(world);
+    let npc = entity_map
+        .get_npc(slot)
+        .filter(|n| !n.dead)
+        .ok_or_else(|| format!("no live NPC at slot {slot}"))?;
+    let npc = npc_save_data(&entity_map, npc, &nq);
+    let target = world
+        .get_resource::<crate::gpu::EntityGpuState>()
+        .and_then(|gpu| gpu.targets.get(slot * 2..slot * 2 + 2))
+        .map(|t| [t[0], t[1]]);
+    let snapshot = NpcSnapshot {
+        version: NPC_SNAPSHOT_VERSION,
+        generation: world.resource::<NpcLogCache>().generation(slot),
+        npc,
+        target,
+    };
+    serde_json::to_vec(&snapshot).map_err(|e| e.to_string())
+}
+
+/// Put a `snapshot_npc` capture back onto the NPC at `slot`: components in place, stats
+/// re-resolved, GPU position/target/health re-sent. Refuses if the slot has been reallocated
+/// since (or the snapshot came from another slot) unless `force`; job, faction and town must
+/// always match. Worksite, bed and post reservations are left as they are.
+pub fn restore_npc(
+    world: &mut World,
+    slot: usize,
+    snapshot: &[u8],
+    force: bool,
+) -> Result<(), String> {
+    let snap: NpcSnapshot =
+        serde_json::from_slice(snapshot).map_err(|e| format!("bad snapshot: {e}"))?;
+    if snap.version > NPC_SNAPSHOT_VERSION {
+        return Err(format!(
+            "snapshot format v{} is newer than v{NPC_SNAPSHOT_VERSION}",
+            snap.version
+        ));
+    }
+    let (entity, job, faction, town_idx) = match world.resource::<EntityMap>().get_npc(slot) {
+        Some(n) if !n.dead => (n.entity, n.job, n.faction, n.town_idx),
+        _ => return Err(format!("no live NPC at slot {slot}")),
+    };
+    let data = snap.npc;
+    // Saves record militia under their original job
+    let saved_job = world.get::<Militia>(entity).map_or(job, |m| m.prev_job);
+    if Job::from_i32(data.job as i32) != saved_job
+        || data.faction != faction
+        || data.town_id != town_idx
+    {
+        return Err(format!(
+            "snapshot job, faction or town differs from the NPC at slot {slot}"
+        ));
+    }
+    let same_npc = data.slot == slot
+        && snap.generation == world.resource::<NpcLogCache>().generation(slot)
+        && data.uid.is_none_or(|uid| uid == entity.to_bits());
+    if !same_npc && !force {
+        return Err(format!(
+            "slot {slot} holds a different NPC than the snapshot (use force to overwrite)"
+        ));
+    }
+
+    let position = to_vec2(data.position);
+    let squad_changed = world.get::<SquadId>(entity).map(|s| s.0) != data.squad_id;
+    let mut e = world.entity_mut(entity);
+    e.insert((
+        Position {
+            x: position.x,
+            y: position.y,
+        },
+        Health(data.health),
+        data.activity.to_activity(),
+        data.combat_state.to_combat_state(),
+        data.personality.to_personality(),
+        Home(to_vec2(data.home)),
+        Inventory(data.inventory),
+        data.equipment,
+    ));
+    if e.contains::<HasEnergy>() {
+        e.insert(Energy(data.energy));
+    }
+    if let Some(mut stats) = e.get_mut::<NpcStats>() {
+        stats.name = data.name;
+        stats.xp = data.xp;
+        stats.kills = data.kills;
+    }
+    if let Some(mut loot) = e.get_mut::<CarriedLoot>() {
+        loot.food = data.carried_food.unwrap_or(0);
+        loot.gold = data.carried_gold.unwrap_or(0);
+        loot.equipment = data.carried_equipment;
+    }
+    match data.squad_id {
+        Some(id) => e.insert(SquadId(id)),
+        None => e.remove::<SquadId>(),
+    };
+    match data.target_priority {
+        Some(priority) => e.insert(priority),
+        None => e.remove::<TargetPriority>(),
+    };
+    match data.officer {
+        Some(officer) => e.insert(officer),
+        None => e.remove::<Officer>(),
+    };
+    if let Some(stance) = data.stance {
+        e.insert((stance, StanceOverride));
+    } else if e.contains::<StanceOverride>() {
+        e.remove::<(CombatStance, StanceOverride)>();
+    }
+
+    // Level, personality and equipment may have changed; militia_stats_system rescales militia
+    let stats = crate::systems::stats::resolve_npc_stats(world, entity, job);
+    let (speed, max_health) = (stats.speed, stats.max_health);
+    world.entity_mut(entity).insert((stats, Speed(speed)));
+
+    let target = snap.target.map_or(position, to_vec2);
+    let mut gpu = world.resource_mut::<Messages<GpuUpdateMsg>>();
+    for update in [
+        GpuUpdate::SetPosition {
+            idx: slot,
+            x: position.x,
+            y: position.y,
+        },
+        GpuUpdate::SetTarget {
+            idx: slot,
+            x: target.x,
+            y: target.y,
+        },
+        GpuUpdate::SetSpeed { idx: slot, speed },
+        GpuUpdate::SetMaxHealth {
+            idx: slot,
+            max_health,
+        },
+        GpuUpdate::SetHealth {
+            idx: slot,
+            health: data.health,
+        },
+        GpuUpdate::MarkVisualDirty { idx: slot },
+    ] {
+        gpu.write(GpuUpdateMsg(update));
+    }
+    if squad_changed {
+        world
+            .resource_mut::<Messages<crate::messages::SquadsDirtyMsg>>()
+            .write(crate::messages::SquadsDirtyMsg);
+    }
+    Ok(())
 }
 
 // ============================================================================
@@ -1605,12 +2051,18 @@ pub struct SaveFactionState<'w> {
     pub faction_stats: ResMut<'w, FactionStats>,
     pub faction_list: ResMut<'w, crate::resources::FactionList>,
     pub reputation: ResMut<'w, crate::resources::Reputation>,
+    pub faction_mvp: ResMut<'w, crate::resources::FactionMvp>,
     pub kill_stats: ResMut<'w, KillStats>,
     pub ai_state: ResMut<'w, AiPlayerState>,
     pub migration_state: ResMut<'w, MigrationState>,
     pub endless: ResMut<'w, EndlessMode>,
     pub next_loot_id: ResMut<'w, crate::resources::NextLootItemId>,
     pub merchant_inv: ResMut<'w, crate::resources::MerchantInventory>,
+    pub tribute: ResMut<'w, crate::resources::TributeState>,
+    pub win_condition: ResMut<'w, crate::systems::WinCondition>,
+    pub combat_rng: ResMut<'w, crate::resources::CombatRng>,
+    pub happiness_config: ResMut<'w, crate::systems::HappinessConfig>,
+    pub town_happiness: ResMut<'w, crate::systems::TownHappiness>,
 }
 
 /// NPC queries for save (collect_npc_data).
@@ -1627,9 +2079,24 @@ pub struct SaveNpcQueries<'w, 's> {
     pub home_q: Query<'w, 's, &'static Home>,
     pub work_state_q: Query<'w, 's, &'static NpcWorkState>,
     pub carried_loot_q: Query<'w, 's, &'static CarriedLoot>,
+    pub inventory_q: Query<'w, 's, &'static Inventory>,
     pub equipment_q: Query<'w, 's, &'static NpcEquipment>,
     pub has_energy_q: Query<'w, 's, &'static HasEnergy>,
     pub npc_stats_q: Query<'w, 's, &'static NpcStats>,
+    pub militia_q: Query<'w, 's, &'static Militia>,
+    pub target_priority_q: Query<'w, 's, &'static TargetPriority>,
+    pub officer_q: Query<'w, 's, &'static Officer>,
+    pub stance_q: Query<'w, 's, &'static CombatStance, With<StanceOverride>>,
+    pub assignment_q: Query<
+        'w,
+        's,
+        (
+            Option<&'static AssignedFarm>,
+            Option<&'static AssignedBed>,
+            Option<&'static AssignedPost>,
+        ),
+    >,
+    pub bodyguard_q: Query<'w, 's, &'static Bodyguard>,
 }
 
 /// NPC tracking resources for load.
@@ -1645,6 +2112,7 @@ pub struct LoadNpcTracking<'w> {
     pub healing_cache: ResMut<'w, HealingZoneCache>,
     pub active_healing: ResMut<'w, ActiveHealingSlots>,
     pub npc_gpu_state: ResMut<'w, crate::gpu::EntityGpuState>,
+    pub death_knockback: ResMut<'w, crate::systems::DeathKnockback>,
 }
 
 // ============================================================================
@@ -1953,6 +2421,112 @@ pub fn restore_growth_from_save(save: &SaveData, entity_map: &EntityMap, command
     }
 }
 
+// ============================================================================
+// STATE HASH
+// ============================================================================
+
+/// Position quantum (px) for `world_state_hash`.
+pub const HASH_POSITION_STEP: f32 = 0.1;
+/// Health / game-time quantum for `world_state_hash`.
+pub const HASH_VALUE_STEP: f32 = 0.01;
+
+/// Round to a multiple of `step` so float noise below it doesn't change the hash.
+fn quantize(v: f32, step: f32) -> i64 {
+    (v / step).round() as i64
+}
+
+/// FNV-1a, fed explicit little-endian bytes so the hash is the same on every platform and
+/// toolchain (std's `DefaultHasher` promises neither).
+struct StateHasher(u64);
+
+impl StateHasher {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn int(&mut self, v: i64) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        self.int(s.len() as i64);
+        self.bytes(s.as_bytes());
+    }
+}
+
+/// Stable checksum of the salient simulation state: game time, every living NPC (slot, job,
+/// faction, town, position, health), every building (kind, town, faction, position, health)
+/// and each town's food/gold. Floats are quantized (`HASH_POSITION_STEP`, `HASH_VALUE_STEP`)
+/// so a save/load round trip or a re-run of the same fixed-timestep sim hashes equal, while
+/// any real divergence changes it. Buildings are keyed by content, not slot, since a load
+/// may hand out different building slots.
+pub fn world_state_hash(world: &World) -> u64 {
+    let mut h = StateHasher(0xcbf2_9ce4_8422_2325);
+    let entity_map = world.resource::<EntityMap>();
+
+    h.int(quantize(
+        world.resource::<GameTime>().total_seconds,
+        HASH_VALUE_STEP,
+    ));
+
+    let mut npcs: Vec<_> = entity_map.iter_npcs().filter(|n| !n.dead).collect();
+    npcs.sort_by_key(|n| n.slot);
+    h.int(npcs.len() as i64);
+    for npc in npcs {
+        let pos = world
+            .get::<Position>(npc.entity)
+            .map_or(Vec2::ZERO, |p| Vec2::new(p.x, p.y));
+        let health = world.get::<Health>(npc.entity).map_or(0.0, |hp| hp.0);
+        h.int(npc.slot as i64);
+        h.str(&format!("{:?}", npc.job));
+        h.int(npc.faction as i64);
+        h.int(npc.town_idx as i64);
+        h.int(quantize(pos.x, HASH_POSITION_STEP));
+        h.int(quantize(pos.y, HASH_POSITION_STEP));
+        h.int(quantize(health, HASH_VALUE_STEP));
+    }
+
+    let mut buildings: Vec<_> = entity_map
+        .iter_instances()
+        .map(|inst| {
+            let health = entity_map
+                .entities
+                .get(&inst.slot)
+                .and_then(|&e| world.get::<Health>(e))
+                .map_or(0.0, |hp| hp.0);
+            (
+                format!("{:?}", inst.kind),
+                inst.town_idx as i64,
+                inst.faction as i64,
+                quantize(inst.position.x, HASH_POSITION_STEP),
+                quantize(inst.position.y, HASH_POSITION_STEP),
+                quantize(health, HASH_VALUE_STEP),
+            )
+        })
+        .collect();
+    buildings.sort();
+    h.int(buildings.len() as i64);
+    for (kind, town, faction, x, y, health) in &buildings {
+        h.str(kind);
+        for v in [*town, *faction, *x, *y, *health] {
+            h.int(v);
+        }
+    }
+
+    let mut towns: Vec<_> = world.resource::<TownIndex>().0.iter().collect();
+    towns.sort_by_key(|(t, _)| **t);
+    h.int(towns.len() as i64);
+    for (&town, &entity) in towns {
+        h.int(town as i64);
+        h.int(world.get::<FoodStore>(entity).map_or(0, |f| f.0) as i64);
+        h.int(world.get::<GoldStore>(entity).map_or(0, |g| g.0) as i64);
+    }
+    h.0
+}
+
 // ============================================================================
 // BEVY SYSTEMS
 // ============================================================================
@@ -1976,53 +2550,35 @@ pub fn save_load_input_system(
     }
 }
 
-/// Execute save when requested.
-pub fn save_game_system(
-    mut save_msgs: MessageReader<SaveGameMsg>,
-    mut request: ResMut<SaveLoadRequest>,
-    mut toast: ResMut<SaveToast>,
-    ws: SaveWorldState,
-    fs: SaveFactionState,
-    entity_map: Res<EntityMap>,
-    building_query: Query<(&Building, &GpuSlot, &Health), Without<Dead>>,
-    nq: SaveNpcQueries,
-    bld_component_q: Query<
-        (
-            &GpuSlot,
-            Option<&WaypointOrder>,
-            Option<&MinerHomeConfig>,
-            Option<&WallLevel>,
-            Option<&TowerBuildingState>,
-            Option<&ProductionState>,
-            Option<&ConstructionProgress>,
-            Option<&SpawnerState>,
-        ),
-        With<Building>,
-    >,
-) {
-    if save_msgs.read().next().is_none() {
-        return;
-    }
-
-    let npcs = collect_npc_data(
-        &entity_map,
-        &nq.npc_stats_q,
-        &nq.squad_id_q,
-        &nq.activity_q,
-        &nq.position_q,
-        &nq.health_q,
-        &nq.energy_q,
-        &nq.combat_state_q,
-        &nq.attack_type_q,
-        &nq.personality_q,
-        &nq.home_q,
-        &nq.work_state_q,
-        &nq.carried_loot_q,
-        &nq.equipment_q,
-        &nq.has_energy_q,
-    );
-    let building_hp = collect_building_hp(&building_query, &entity_map);
-    let bld_state = collect_building_state_snapshot(&bld_component_q);
+/// Building state components captured alongside HP (shared by every save path).
+pub type SaveBuildingStateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GpuSlot,
+        Option<&'static WaypointOrder>,
+        Option<&'static MinerHomeConfig>,
+        Option<&'static WallLevel>,
+        Option<&'static TowerBuildingState>,
+        Option<&'static ProductionState>,
+        Option<&'static ConstructionProgress>,
+        Option<&'static SpawnerState>,
+    ),
+    With<Building>,
+>;
+
+/// Snapshot the live world into a `SaveData` (quicksave, autosave and rewind snapshots).
+fn gather_save_data(
+    ws: &SaveWorldState,
+    fs: &SaveFactionState,
+    entity_map: &EntityMap,
+    building_query: &Query<(&Building, &GpuSlot, &Health), Without<Dead>>,
+    nq: &SaveNpcQueries,
+    bld_component_q: &SaveBuildingStateQuery,
+) -> SaveData {
+    let npcs = collect_npc_data(entity_map, nq);
+    let building_hp = collect_building_hp(building_query, entity_map);
+    let bld_state = collect_building_state_snapshot(bld_component_q);
     // Collect town data from ECS entities
     let n_towns = ws.world_data.towns.len();
     let town_area_levels: Vec<i32> = (0..n_towns)
@@ -2049,10 +2605,10 @@ pub fn save_game_system(
     let town_equipment: Vec<Vec<crate::constants::LootItem>> = (0..n_towns)
         .map(|i| ws.town_access.equipment(i as i32).unwrap_or_default())
         .collect();
-    let data = collect_save_data(
+    collect_save_data(
         &ws.grid,
         &ws.world_data,
-        &entity_map,
+        entity_map,
         &ws.game_time,
         &town_area_levels,
         &town_food,
@@ -2077,18 +2633,53 @@ pub fn save_game_system(
         &fs.merchant_inv,
         &fs.faction_list,
         &bld_state,
+        &fs.tribute,
+        &fs.win_condition,
+        ws.town_access.tech(),
+        &fs.combat_rng,
+        &fs.happiness_config,
+        &fs.town_happiness,
+    )
+}
+
+/// Execute save when requested.
+pub fn save_game_system(
+    mut save_msgs: MessageReader<SaveGameMsg>,
+    mut request: ResMut<SaveLoadRequest>,
+    mut toast: ResMut<SaveToast>,
+    mut save_config: ResMut<SaveConfig>,
+    ws: SaveWorldState,
+    fs: SaveFactionState,
+    entity_map: Res<EntityMap>,
+    building_query: Query<(&Building, &GpuSlot, &Health), Without<Dead>>,
+    nq: SaveNpcQueries,
+    bld_component_q: SaveBuildingStateQuery,
+) {
+    if save_msgs.read().next().is_none() {
+        return;
+    }
+
+    let data = gather_save_data(
+        &ws,
+        &fs,
+        &entity_map,
+        &building_query,
+        &nq,
+        &bld_component_q,
     );
 
+    let compress = save_config.compress;
     let result = if let Some(path) = request.save_path.take() {
-        write_save_to(&data, &path)
+        write_save_to(&data, &path, compress)
     } else {
-        write_save(&data)
+        write_save(&data, compress)
     };
 
     match result {
-        Ok(()) => {
-            toast.message = format!("Game Saved ({} NPCs)", data.npcs.len());
+        Ok(info) => {
+            toast.message = format!("Game Saved ({} NPCs, {})", data.npcs.len(), info.summary());
             toast.timer = 2.0;
+            save_config.last_save = Some(info);
         }
         Err(e) => {
             error!("Save failed: {e}");
@@ -2102,24 +2693,13 @@ pub fn save_game_system(
 pub fn autosave_system(
     mut request: ResMut<SaveLoadRequest>,
     mut toast: ResMut<SaveToast>,
+    mut save_config: ResMut<SaveConfig>,
     ws: SaveWorldState,
     fs: SaveFactionState,
     entity_map: Res<EntityMap>,
     building_query: Query<(&Building, &GpuSlot, &Health), Without<Dead>>,
     nq: SaveNpcQueries,
-    bld_component_q: Query<
-        (
-            &GpuSlot,
-            Option<&WaypointOrder>,
-            Option<&MinerHomeConfig>,
-            Option<&WallLevel>,
-            Option<&TowerBuildingState>,
-            Option<&ProductionState>,
-            Option<&ConstructionProgress>,
-            Option<&SpawnerState>,
-        ),
-        With<Building>,
-    >,
+    bld_component_q: SaveBuildingStateQuery,
 ) {
     if request.autosave_hours <= 0 || !ws.game_time.hour_ticked {
         return;
@@ -2138,84 +2718,20 @@ pub fn autosave_system(
         return;
     };
 
-    let npcs = collect_npc_data(
+    let data = gather_save_data(
+        &ws,
+        &fs,
         &entity_map,
-        &nq.npc_stats_q,
-        &nq.squad_id_q,
-        &nq.activity_q,
-        &nq.position_q,
-        &nq.health_q,
-        &nq.energy_q,
-        &nq.combat_state_q,
-        &nq.attack_type_q,
-        &nq.personality_q,
-        &nq.home_q,
-        &nq.work_state_q,
-        &nq.carried_loot_q,
-        &nq.equipment_q,
-        &nq.has_energy_q,
-    );
-    let building_hp = collect_building_hp(&building_query, &entity_map);
-    let bld_state = collect_building_state_snapshot(&bld_component_q);
-    let n_towns = ws.world_data.towns.len();
-    let town_area_levels: Vec<i32> = (0..n_towns)
-        .map(|i| ws.town_access.area_level(i as i32))
-        .collect();
-    let town_food: Vec<i32> = (0..n_towns)
-        .map(|i| ws.town_access.food(i as i32))
-        .collect();
-    let town_gold: Vec<i32> = (0..n_towns)
-        .map(|i| ws.town_access.gold(i as i32))
-        .collect();
-    let town_wood: Vec<i32> = (0..n_towns)
-        .map(|i| ws.town_access.wood(i as i32))
-        .collect();
-    let town_stone: Vec<i32> = (0..n_towns)
-        .map(|i| ws.town_access.stone(i as i32))
-        .collect();
-    let town_upgrades: Vec<Vec<u8>> = (0..n_towns)
-        .map(|i| ws.town_access.upgrade_levels(i as i32))
-        .collect();
-    let town_policies: Vec<crate::resources::PolicySet> = (0..n_towns)
-        .map(|i| ws.town_access.policy(i as i32).unwrap_or_default())
-        .collect();
-    let town_equipment: Vec<Vec<crate::constants::LootItem>> = (0..n_towns)
-        .map(|i| ws.town_access.equipment(i as i32).unwrap_or_default())
-        .collect();
-    let data = collect_save_data(
-        &ws.grid,
-        &ws.world_data,
-        &entity_map,
-        &ws.game_time,
-        &town_area_levels,
-        &town_food,
-        &town_gold,
-        &town_wood,
-        &town_stone,
-        building_hp,
-        &town_upgrades,
-        &town_policies,
-        &ws.auto_upgrade,
-        &ws.squad_state,
-        &fs.raider_state,
-        &fs.faction_stats,
-        &fs.reputation,
-        &fs.kill_stats,
-        &fs.ai_state,
-        &fs.migration_state,
-        &fs.endless,
-        npcs,
-        &fs.next_loot_id,
-        &town_equipment,
-        &fs.merchant_inv,
-        &fs.faction_list,
-        &bld_state,
+        &building_query,
+        &nq,
+        &bld_component_q,
     );
 
-    match write_save_to(&data, &path) {
-        Ok(()) => {
+    match write_save_to(&data, &path, save_config.compress) {
+        Ok(info) => {
             toast.message = format!("Autosaved slot {} ({} NPCs)", slot + 1, data.npcs.len());
             toast.timer = 2.0;
+            save_config.last_save = Some(info);
         }
         Err(e) => {
             error!("Autosave failed: {e}");
@@ -2225,9 +2741,161 @@ pub fn autosave_system(
     }
 }
 
+// ============================================================================
+// REWIND SNAPSHOTS (debug)
+// ============================================================================
+
+/// Rewind snapshots kept in memory; the oldest is dropped first.
+pub const MAX_REWIND_SNAPSHOTS: usize = 8;
+
+/// One compressed in-memory save, taken at the start of a game day or on demand.
+pub struct RewindSnapshot {
+    pub day: i32,
+    pub hour: i32,
+    /// Uncompressed JSON size, for reporting.
+    pub raw_bytes: usize,
+    /// Deflate-compressed `SaveData` JSON.
+    pub data: Vec<u8>,
+}
+
+/// Debug-only rolling history of full-state snapshots (`debug_rewind` setting). Captured by
+/// `rewind_snapshot_system` each new game day; `rewind_to` queues a restore that
+/// `load_game_system` applies through the same path as a file load. Off in normal play.
+#[derive(Resource, Default)]
+pub struct SnapshotHistory {
+    pub enabled: bool,
+    pub snapshots: std::collections::VecDeque<RewindSnapshot>,
+    /// Capture on the next frame regardless of the day boundary (`endless/snapshot`).
+    pub capture_requested: bool,
+    /// Snapshot day to restore on the next frame.
+    pub pending_rewind: Option<i32>,
+    last_day: i32,
+}
+
+impl SnapshotHistory {
+    /// Day of the retained snapshot closest to `day` (earlier wins a tie).
+    pub fn nearest(&self, day: i32) -> Option<i32> {
+        self.snapshots
+            .iter()
+            .min_by_key(|s| ((s.day - day).abs(), s.day))
+            .map(|s| s.day)
+    }
+
+    fn push(&mut self, snapshot: RewindSnapshot) {
+        // A re-capture of the same day replaces the older one
+        self.snapshots.retain(|s| s.day != snapshot.day);
+        self.snapshots.push_back(snapshot);
+        while self.snapshots.len() > MAX_REWIND_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Decompress the snapshot for `day` and drop every later one (the timeline forks).
+    fn take_for_restore(&mut self, day: i32) -> Result<SaveData, String> {
+        let snapshot = self
+            .snapshots
+            .iter()
+            .find(|s| s.day == day)
+            .ok_or_else(|| format!("no snapshot for day {day}"))?;
+        let save = decompress_json(&snapshot.data)?;
+        self.snapshots.retain(|s| s.day <= day);
+        self.last_day = day;
+        Ok(save)
+    }
+}
+
+fn compress_json<T: Serialize>(data: &T) -> Result<(usize, Vec<u8>), String> {
+    use std::io::Write;
+    let json = serde_json::to_vec(data).map_err(|e| format!("serialize: {e}"))?;
+    let mut enc = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::fast());
+    enc.write_all(&json).map_err(|e| format!("compress: {e}"))?;
+    let packed = enc.finish().map_err(|e| format!("compress: {e}"))?;
+    Ok((json.len(), packed))
+}
+
+/// Inflate and parse deflated JSON. A stream that stops before its end is reported as
+/// truncated rather than as a parse error.
+fn decompress_json<T: serde::de::DeserializeOwned>(packed: &[u8]) -> Result<T, String> {
+    let dec = flate2::read::DeflateDecoder::new(packed);
+    serde_json::from_reader(dec).map_err(|e| {
+        if e.is_eof() || e.io_error_kind() == Some(std::io::ErrorKind::UnexpectedEof) {
+            "compressed save is truncated".into()
+        } else {
+            format!("corrupt compressed save: {e}")
+        }
+    })
+}
+
+/// Queue a rewind to the retained snapshot nearest `day`. Returns the snapshot's day.
+pub fn rewind_to(history: &mut SnapshotHistory, day: i32) -> Result<i32, String> {
+    if !history.enabled {
+        return Err("rewind is disabled (enable debug_rewind in settings)".into());
+    }
+    let target = history
+        .nearest(day)
+        .ok_or("no rewind snapshots captured yet")?;
+    history.pending_rewind = Some(target);
+    Ok(target)
+}
+
+/// Capture a rewind snapshot at each new game day (or when requested). Debug-only: does
+/// nothing unless `SnapshotHistory.enabled`.
+pub fn rewind_snapshot_system(
+    mut history: ResMut<SnapshotHistory>,
+    ws: SaveWorldState,
+    fs: SaveFactionState,
+    entity_map: Res<EntityMap>,
+    building_query: Query<(&Building, &GpuSlot, &Health), Without<Dead>>,
+    nq: SaveNpcQueries,
+    bld_component_q: SaveBuildingStateQuery,
+) {
+    if !history.enabled {
+        if !history.snapshots.is_empty() {
+            history.snapshots.clear();
+        }
+        return;
+    }
+    let day = ws.game_time.day();
+    if day < history.last_day {
+        // Clock went backwards without a rewind: new game or file load, a different timeline
+        history.snapshots.clear();
+    }
+    let new_day = ws.game_time.hour_ticked && day != history.last_day;
+    if !new_day && !history.capture_requested {
+        return;
+    }
+    history.capture_requested = false;
+    history.last_day = day;
+
+    let data = gather_save_data(
+        &ws,
+        &fs,
+        &entity_map,
+        &building_query,
+        &nq,
+        &bld_component_q,
+    );
+    match compress_json(&data) {
+        Ok((raw_bytes, packed)) => {
+            info!(
+                "Rewind snapshot day {day}: {} KB ({} KB raw)",
+                packed.len() / 1024,
+                raw_bytes / 1024
+            );
+            history.push(RewindSnapshot {
+                day,
+                hour: ws.game_time.hour(),
+                raw_bytes,
+                data: packed,
+            });
+        }
+        Err(e) => error!("Rewind snapshot failed: {e}"),
+    }
+}
+
 /// Spawn NPC entities from save data. Shared between in-game load (F9) and menu load.
 pub fn spawn_npcs_from_save(
-    save: &SaveData,
+    npcs: &[NpcSaveData],
     commands: &mut Commands,
     entity_map: &mut EntityMap,
     pop_stats: &mut PopulationStats,
@@ -2235,7 +2903,7 @@ pub fn spawn_npcs_from_save(
     combat_config: &CombatConfig,
     town_upgrade_levels: &[Vec<u8>],
 ) {
-    for npc in &save.npcs {
+    for npc in npcs {
         let overrides = NpcSpawnOverrides {
             health: Some(npc.health),
             energy: Some(npc.energy),
@@ -2245,11 +2913,16 @@ pub fn spawn_npcs_from_save(
             name: Some(npc.name.clone()),
             level: Some(npc.level),
             xp: Some(npc.xp),
+            kills: Some(npc.kills),
             equipment: npc.equipment.clone(),
             carried_food: npc.carried_food,
             carried_gold: npc.carried_gold,
             carried_equipment: npc.carried_equipment.clone(),
+            inventory: npc.inventory.clone(),
             squad_id: npc.squad_id,
+            target_priority: npc.target_priority,
+            officer: npc.officer,
+            stance: npc.stance,
         };
 
         // Patrol units always get starting_post=0 on load (patrol route rebuilt from world)
@@ -2282,6 +2955,72 @@ pub fn spawn_npcs_from_save(
                 .unwrap_or(&[]),
         );
     }
+    restore_assignments(npcs, commands);
+    restore_bodyguards(npcs, commands);
+}
+
+/// Re-attach `Bodyguard` links by slot once every NPC entity exists; a missing or dead charge
+/// drops the link, as its death would have.
+fn restore_bodyguards(npcs: &[NpcSaveData], commands: &mut Commands) {
+    let links: Vec<(usize, usize)> = npcs
+        .iter()
+        .filter_map(|npc| npc.bodyguard_of.map(|charge| (npc.slot, charge)))
+        .collect();
+    if links.is_empty() {
+        return;
+    }
+    commands.queue(move |world: &mut World| {
+        for (guard, charge) in links {
+            let em = world.resource::<EntityMap>();
+            let live = |slot| em.get_npc(slot).filter(|n| !n.dead).map(|n| n.entity);
+            let (Some(guard_entity), Some(charge_entity)) = (live(guard), live(charge)) else {
+                warn!("load: dropped bodyguard link {guard} -> {charge}");
+                continue;
+            };
+            world
+                .entity_mut(guard_entity)
+                .insert(Bodyguard(charge_entity));
+        }
+    });
+}
+
+/// Re-pin saved manual assignments once the NPC and building entities exist. Goes through
+/// `assign_npc`, so the building reservations come back along with the components.
+fn restore_assignments(npcs: &[NpcSaveData], commands: &mut Commands) {
+    let pins: Vec<(usize, AssignmentSave)> = npcs
+        .iter()
+        .flat_map(|npc| npc.assignments.iter().map(|a| (npc.slot, a.clone())))
+        .collect();
+    if pins.is_empty() {
+        return;
+    }
+    commands.queue(move |world: &mut World| {
+        for (slot, pin) in pins {
+            let pos = Vec2::from(pin.building);
+            let em = world.resource::<EntityMap>();
+            let building = em.find_by_position(pos).map(|inst| inst.slot);
+            let entity = em.get_npc(slot).map(|npc| npc.entity);
+            let (Some(building), Some(entity)) = (building, entity) else {
+                warn!("load: dropped {:?} assignment of NPC {slot}", pin.kind);
+                continue;
+            };
+            // Loaded farmers point at their farm without holding a claim yet
+            if pin.kind == crate::systems::AssignmentKind::Farm {
+                if let Some(mut ws) = world.get_mut::<NpcWorkState>(entity) {
+                    ws.worksite = None;
+                }
+            }
+            if let Err(e) = crate::systems::assign_npc(world, slot, building, pin.kind) {
+                warn!("load: {:?} assignment of NPC {slot} failed: {e}", pin.kind);
+                continue;
+            }
+            if let (Some(prev), Some(mut bed)) =
+                (pin.prev_home, world.get_mut::<AssignedBed>(entity))
+            {
+                bed.prev_home = Vec2::from(prev);
+            }
+        }
+    });
 }
 
 /// Shared save-restore pipeline used by both menu load and in-game F9 load.
@@ -2304,6 +3043,7 @@ pub fn restore_world_from_save(
     *tracking.building_hp_render = Default::default();
     *tracking.npc_gpu_state = Default::default();
     *tracking.active_healing = Default::default();
+    tracking.death_knockback.corpses.clear();
     tracking.dirty_writers.emit_all();
     tracking.tilemap_spawned.0 = false;
 
@@ -2336,6 +3076,7 @@ pub fn restore_world_from_save(
             kind: FactionKind::Neutral,
             name: "Neutral".to_string(),
             towns: vec![],
+            stance: None,
         });
         for (ti, town) in ws.world_data.towns.iter().enumerate() {
             let kind = match town.kind {
@@ -2348,11 +3089,24 @@ pub fn restore_world_from_save(
                 kind,
                 name,
                 towns: vec![ti],
+                stance: None,
             });
         }
     } else {
         fs.faction_list.factions = save.faction_list.clone();
     }
+    fs.tribute.tributes = save.tributes.clone();
+    *fs.win_condition = save.win_condition.clone().unwrap_or_default();
+    if let Some(tech) = ws.town_access.tech_mut() {
+        *tech = save.tech.clone().unwrap_or_default();
+    }
+    // Old saves keep the current seed; variance settings are config, not save state
+    if let Some(rng) = save.combat_rng {
+        fs.combat_rng.seed = rng.seed;
+        fs.combat_rng.counter = rng.counter;
+    }
+    *fs.happiness_config = save.happiness_config.clone().unwrap_or_default();
+    *fs.town_happiness = save.town_happiness.clone().unwrap_or_default();
 
     // Spawn ECS town entities from loaded save data
     world::spawn_town_entities(
@@ -2393,7 +3147,7 @@ pub fn restore_world_from_save(
 
     // Rebuild NPCs from save payload.
     spawn_npcs_from_save(
-        save,
+        &save.npcs,
         commands,
         entity_map,
         &mut tracking.pop_stats,
@@ -2401,6 +3155,11 @@ pub fn restore_world_from_save(
         combat_config,
         &town_data.upgrades,
     );
+    // MVPs from saved per-NPC kills (kill feeds are not saved)
+    *fs.faction_mvp = Default::default();
+    for npc in save.npcs.iter().filter(|n| n.kills > 0) {
+        fs.faction_mvp.on_kill(npc.faction, npc.slot, npc.kills);
+    }
 
     // Old-save fixup: convert legacy squad member slots to Entities (NPCs are now spawned)
     for (si, ss) in save.squads.iter().enumerate() {
@@ -2430,13 +3189,18 @@ pub fn load_game_system(
     combat_config: Res<CombatConfig>,
     npc_query: Query<Entity, With<GpuSlot>>,
     marker_query: Query<Entity, With<FarmReadyMarker>>,
+    mut history: ResMut<SnapshotHistory>,
 ) {
-    if load_msgs.read().next().is_none() {
+    let load_requested = load_msgs.read().next().is_some();
+    let rewind = history.pending_rewind.take();
+    if !load_requested && rewind.is_none() {
         return;
     }
 
-    // Read save file (from explicit path or quicksave)
-    let save = match if let Some(path) = request.load_path.take() {
+    // Read save: rewind snapshot, explicit path or quicksave
+    let save = match if let Some(day) = rewind {
+        history.take_for_restore(day)
+    } else if let Some(path) = request.load_path.take() {
         read_save_from(&path)
     } else {
         read_save()
@@ -2481,7 +3245,10 @@ pub fn load_game_system(
         &combat_config,
     );
 
-    toast.message = format!("Game Loaded ({} NPCs)", save.npcs.len());
+    toast.message = match rewind {
+        Some(day) => format!("Rewound to day {day} ({} NPCs)", save.npcs.len()),
+        None => format!("Game Loaded ({} NPCs)", save.npcs.len()),
+    };
     toast.timer = 2.0;
     info!("Load complete: {} NPCs restored", save.npcs.len());
 }
@@ -2717,4 +3484,562 @@ mod tests {
             "missing stone should default to empty"
         );
     }
+
+    #[test]
+    fn squad_target_priority_saves_and_defaults() {
+        let old = r#"{"members":[],"target":null,"target_size":0,"patrol_enabled":true,"rest_when_tired":true}"#;
+        let squad: SquadSave = serde_json::from_str(old).unwrap();
+        assert_eq!(squad.target_priority, TargetPriority::Nearest);
+        assert_eq!(squad.stance, SquadStance::Aggressive);
+        assert_eq!(squad.engage_radius, crate::constants::SQUAD_ENGAGE_RADIUS);
+        assert_eq!(squad.rally, None);
+        let rally = SquadRallySave {
+            rally: [100.0, 200.0],
+            attack: [900.0, 250.0],
+            issued_at: 42.5,
+        };
+        let json = serde_json::to_string(&SquadSave {
+            target_priority: TargetPriority::HighestThreat,
+            stance: SquadStance::Defensive,
+            engage_radius: 250.0,
+            rally: Some(rally),
+            ..squad
+        })
+        .unwrap();
+        let loaded: SquadSave = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.target_priority, TargetPriority::HighestThreat);
+        assert_eq!(loaded.stance, SquadStance::Defensive);
+        assert_eq!(loaded.engage_radius, 250.0);
+        assert_eq!(loaded.rally, Some(rally));
+    }
+
+    fn npc_world() -> App {
+        let mut app = App::new();
+        app.add_message::<GpuUpdateMsg>();
+        app.init_resource::<EntityMap>();
+        app.init_resource::<PopulationStats>();
+        app.init_resource::<CombatConfig>();
+        app.init_resource::<TownIndex>();
+        app.insert_resource(GameTime {
+            total_seconds: 1234.5,
+            ..Default::default()
+        });
+        app
+    }
+
+    /// `npc_world` plus a 25x25 grid and everything `gather_save_data` and
+    /// `restore_world_from_save` touch.
+    fn save_world() -> App {
+        use crate::messages::{
+            BuildingGridDirtyMsg, HealingZonesDirtyMsg, MiningDirtyMsg, PatrolPerimeterDirtyMsg,
+            PatrolSwapMsg, PatrolsDirtyMsg, SquadsDirtyMsg, TerrainDirtyMsg,
+        };
+
+        let mut app = npc_world();
+        app.add_message::<BuildingGridDirtyMsg>()
+            .add_message::<TerrainDirtyMsg>()
+            .add_message::<PatrolsDirtyMsg>()
+            .add_message::<PatrolPerimeterDirtyMsg>()
+            .add_message::<HealingZonesDirtyMsg>()
+            .add_message::<SquadsDirtyMsg>()
+            .add_message::<MiningDirtyMsg>()
+            .add_message::<PatrolSwapMsg>();
+        app.init_resource::<WorldData>()
+            .init_resource::<AutoUpgrade>()
+            .init_resource::<SquadState>()
+            .init_resource::<TowerState>()
+            .init_resource::<crate::systems::TechTree>()
+            .init_resource::<RaiderState>()
+            .init_resource::<FactionStats>()
+            .init_resource::<crate::resources::FactionList>()
+            .init_resource::<crate::resources::Reputation>()
+            .init_resource::<crate::resources::FactionMvp>()
+            .init_resource::<KillStats>()
+            .init_resource::<AiPlayerState>()
+            .init_resource::<MigrationState>()
+            .init_resource::<EndlessMode>()
+            .init_resource::<crate::resources::NextLootItemId>()
+            .init_resource::<crate::resources::MerchantInventory>()
+            .init_resource::<crate::resources::TributeState>()
+            .init_resource::<crate::systems::WinCondition>()
+            .init_resource::<GpuSlotPool>()
+            .init_resource::<CombatLog>()
+            .init_resource::<GpuReadState>()
+            .init_resource::<crate::render::TilemapSpawned>()
+            .init_resource::<BuildingHpRender>()
+            .init_resource::<HealingZoneCache>()
+            .init_resource::<ActiveHealingSlots>()
+            .init_resource::<crate::gpu::EntityGpuState>()
+            .init_resource::<crate::systems::DeathKnockback>()
+            .init_resource::<crate::resources::CombatRng>()
+            .init_resource::<crate::systems::HappinessConfig>()
+            .init_resource::<crate::systems::TownHappiness>();
+        let mut grid = WorldGrid::default();
+        grid.width = 25;
+        grid.height = 25;
+        grid.cells = vec![WorldCell::default(); 25 * 25];
+        app.world_mut()
+            .resource_mut::<EntityMap>()
+            .init_spatial(grid.width as f32 * grid.cell_size);
+        app.insert_resource(grid);
+        app
+    }
+
+    #[test]
+    fn save_round_trip_reproduces_world_hash() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut original = save_world();
+        original.insert_resource(crate::resources::CombatRng {
+            seed: 42,
+            counter: 17,
+            ..Default::default()
+        });
+        let happiness_config = crate::systems::HappinessConfig {
+            enabled: true,
+            daily_rate: 0.5,
+            effect: 0.1,
+        };
+        let mut town_happiness = crate::systems::TownHappiness::default();
+        town_happiness
+            .towns
+            .push(crate::systems::happiness::Happiness {
+                value: 31.5,
+                factors: crate::systems::happiness::HappinessFactors {
+                    food: -12.0,
+                    deaths: -6.5,
+                    crowding: 0.0,
+                },
+            });
+        original.insert_resource(happiness_config.clone());
+        original.insert_resource(town_happiness.clone());
+        let center = Vec2::new(384.0, 384.0);
+        original
+            .world_mut()
+            .resource_mut::<WorldData>()
+            .towns
+            .push(world::Town {
+                name: "Testville".into(),
+                center,
+                faction: crate::constants::FACTION_PLAYER,
+                kind: crate::constants::TownKind::Player,
+            });
+        original
+            .world_mut()
+            .run_system_once(
+                move |mut commands: Commands,
+                      mut entity_map: ResMut<EntityMap>,
+                      mut pop_stats: ResMut<PopulationStats>,
+                      mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+                      combat_config: Res<CombatConfig>,
+                      mut slots: ResMut<GpuSlotPool>,
+                      mut town_index: ResMut<TownIndex>,
+                      world_data: Res<WorldData>| {
+                    world::spawn_town_entities(
+                        &mut commands,
+                        &mut town_index,
+                        &world_data.towns,
+                        &[0],
+                        &[75],
+                        &[12],
+                        &[],
+                        &[],
+                        &[],
+                        &[],
+                        &[],
+                    );
+                    // NPCs below take slots 0-3
+                    slots.set_next(4);
+                    world::place_building(
+                        &mut slots,
+                        &mut entity_map,
+                        &mut commands,
+                        &mut gpu_updates,
+                        world::BuildingKind::Fountain,
+                        center,
+                        0,
+                        crate::constants::FACTION_PLAYER,
+                        &Default::default(),
+                        None,
+                        None,
+                    )
+                    .unwrap();
+                    world::place_building(
+                        &mut slots,
+                        &mut entity_map,
+                        &mut commands,
+                        &mut gpu_updates,
+                        world::BuildingKind::Bed,
+                        Vec2::new(160.0, 160.0),
+                        0,
+                        crate::constants::FACTION_PLAYER,
+                        &Default::default(),
+                        None,
+                        None,
+                    )
+                    .unwrap();
+                    for (slot, job, x) in [(0, 1, 100.25), (1, 0, 340.7), (3, 2, 512.05)] {
+                        let overrides = NpcSpawnOverrides {
+                            health: Some(37.5 + slot as f32),
+                            target_priority: (slot == 1).then_some(TargetPriority::LowestHp),
+                            officer: (slot == 0).then(crate::systems::combat::new_officer),
+                            stance: (slot == 3).then_some(CombatStance::HoldFire),
+                            ..Default::default()
+                        };
+                        materialize_npc(
+                            slot,
+                            x,
+                            200.0,
+                            job,
+                            1,
+                            0,
+                            [x, 180.0],
+                            None,
+                            -1,
+                            &overrides,
+                            &mut commands,
+                            &mut entity_map,
+                            &mut pop_stats,
+                            &mut gpu_updates,
+                            &combat_config,
+                            &[],
+                        );
+                    }
+                },
+            )
+            .unwrap();
+        let bed = original
+            .world()
+            .resource::<EntityMap>()
+            .find_by_position(Vec2::new(160.0, 160.0))
+            .unwrap()
+            .slot;
+        crate::systems::assign_npc(
+            original.world_mut(),
+            1,
+            bed,
+            crate::systems::AssignmentKind::Bed,
+        )
+        .unwrap();
+        let em = original.world().resource::<EntityMap>();
+        let (guard, charge) = (em.get_npc(0).unwrap().entity, em.get_npc(1).unwrap().entity);
+        original
+            .world_mut()
+            .entity_mut(guard)
+            .insert(Bodyguard(charge));
+        let mut tech = crate::systems::TechTree {
+            enabled: true,
+            ..Default::default()
+        };
+        tech.ensure_towns(1);
+        tech.towns[0].points = 55.0;
+        tech.unlock_tech(0, "masonry", original.world().resource::<EntityMap>())
+            .unwrap();
+        original.insert_resource(tech.clone());
+        let rally = SquadRally {
+            rally: Vec2::new(300.0, 320.0),
+            attack: Vec2::new(1200.0, 320.0),
+            issued_at: 1200.0,
+        };
+        {
+            let mut squads = original.world_mut().resource_mut::<SquadState>();
+            squads.squads[0].target = Some(rally.rally);
+            squads.squads[0].rally = Some(rally);
+        }
+
+        // Save through the quicksave path and load into a fresh world like F9 does
+        let data = original
+            .world_mut()
+            .run_system_once(
+                |ws: SaveWorldState,
+                 fs: SaveFactionState,
+                 entity_map: Res<EntityMap>,
+                 building_query: Query<(&Building, &GpuSlot, &Health), Without<Dead>>,
+                 nq: SaveNpcQueries,
+                 bld_component_q: SaveBuildingStateQuery| {
+                    gather_save_data(
+                        &ws,
+                        &fs,
+                        &entity_map,
+                        &building_query,
+                        &nq,
+                        &bld_component_q,
+                    )
+                },
+            )
+            .unwrap();
+        let (_, bytes) = encode_save(&data, true).unwrap();
+        let loaded: SaveData = decode_save(&bytes).unwrap();
+        let mut restored = save_world();
+        restored.insert_resource(GameTime::default());
+        restored
+            .world_mut()
+            .run_system_once(
+                move |mut commands: Commands,
+                      mut ws: SaveWorldState,
+                      mut fs: SaveFactionState,
+                      mut tracking: LoadNpcTracking,
+                      mut entity_map: ResMut<EntityMap>,
+                      mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+                      combat_config: Res<CombatConfig>| {
+                    restore_world_from_save(
+                        &loaded,
+                        &mut commands,
+                        &mut ws,
+                        &mut fs,
+                        &mut tracking,
+                        &mut entity_map,
+                        &mut gpu_updates,
+                        &combat_config,
+                    );
+                },
+            )
+            .unwrap();
+
+        let hash = world_state_hash(original.world());
+        assert_eq!(hash, world_state_hash(restored.world()));
+        let map = restored.world().resource::<EntityMap>();
+        assert_eq!(map.iter_npcs().filter(|n| !n.dead).count(), 3);
+        assert_eq!(map.count_for_town(world::BuildingKind::Fountain, 0), 1);
+        // The bed pin comes back with its reservation and the home to return to
+        let bed = map.find_by_position(Vec2::new(160.0, 160.0)).unwrap().slot;
+        assert_eq!(map.occupant_count(bed), 1);
+        let sleeper = map.get_npc(1).unwrap().entity;
+        let pin = restored.world().get::<AssignedBed>(sleeper).unwrap();
+        assert_eq!(Some(pin.bed), map.entities.get(&bed).copied());
+        assert_eq!(pin.prev_home, Vec2::new(340.7, 180.0));
+        // Bodyguard links are saved by slot and point at the respawned charge
+        let guard = map.get_npc(0).unwrap().entity;
+        let link = restored.world().get::<Bodyguard>(guard).unwrap();
+        assert_eq!(link.0, sleeper);
+        // Unlocked techs and leftover research survive the round trip
+        let restored_tech = restored.world().resource::<crate::systems::TechTree>();
+        assert_eq!(*restored_tech, tech);
+        assert!(restored_tech.is_unlocked(0, "masonry"));
+        assert_eq!(restored_tech.towns[0].points, 15.0);
+        // A squad still gathering keeps its rally order instead of attacking the rally point
+        let squad = &restored.world().resource::<SquadState>().squads[0];
+        assert_eq!(squad.rally, Some(rally));
+        assert_eq!(squad.target, Some(rally.rally));
+        let rng = restored.world().resource::<crate::resources::CombatRng>();
+        assert_eq!((rng.seed, rng.counter), (42, 17));
+        assert_eq!(
+            *restored
+                .world()
+                .resource::<crate::systems::HappinessConfig>(),
+            happiness_config
+        );
+        assert_eq!(
+            *restored.world().resource::<crate::systems::TownHappiness>(),
+            town_happiness
+        );
+        let priorities: Vec<_> = [0, 1, 3]
+            .map(|slot| {
+                let map = restored.world().resource::<EntityMap>();
+                let entity = map.get_npc(slot).unwrap().entity;
+                restored.world().get::<TargetPriority>(entity).copied()
+            })
+            .into();
+        assert_eq!(priorities, [None, Some(TargetPriority::LowestHp), None]);
+        let officers: Vec<_> = [0, 1, 3]
+            .map(|slot| {
+                let map = restored.world().resource::<EntityMap>();
+                let entity = map.get_npc(slot).unwrap().entity;
+                restored.world().get::<Officer>(entity).is_some()
+            })
+            .into();
+        assert_eq!(officers, [true, false, false]);
+        let stances: Vec<_> = [0, 1, 3]
+            .map(|slot| {
+                let map = restored.world().resource::<EntityMap>();
+                let entity = map.get_npc(slot).unwrap().entity;
+                let e = restored.world().entity(entity);
+                (
+                    e.get::<CombatStance>().copied(),
+                    e.contains::<StanceOverride>(),
+                )
+            })
+            .into();
+        assert_eq!(
+            stances,
+            [
+                (None, false),
+                (None, false),
+                (Some(CombatStance::HoldFire), true)
+            ]
+        );
+
+        // Float noise below the quantum doesn't count; a real change does
+        let entity = restored
+            .world()
+            .resource::<EntityMap>()
+            .get_npc(1)
+            .unwrap()
+            .entity;
+        restored.world_mut().get_mut::<Position>(entity).unwrap().x += 0.001;
+        assert_eq!(hash, world_state_hash(restored.world()));
+        restored.world_mut().get_mut::<Health>(entity).unwrap().0 -= 5.0;
+        assert_ne!(hash, world_state_hash(restored.world()));
+    }
+
+    #[test]
+    fn npc_snapshot_restores_and_guards_slot_reuse() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut app = npc_world();
+        app.add_message::<crate::messages::SquadsDirtyMsg>();
+        app.init_resource::<NpcLogCache>();
+        app.world_mut()
+            .run_system_once(
+                |mut commands: Commands,
+                 mut entity_map: ResMut<EntityMap>,
+                 mut pop_stats: ResMut<PopulationStats>,
+                 mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+                 combat_config: Res<CombatConfig>| {
+                    materialize_npc(
+                        2,
+                        100.0,
+                        200.0,
+                        1,
+                        1,
+                        0,
+                        [100.0, 180.0],
+                        None,
+                        -1,
+                        &NpcSpawnOverrides::default(),
+                        &mut commands,
+                        &mut entity_map,
+                        &mut pop_stats,
+                        &mut gpu_updates,
+                        &combat_config,
+                        &[],
+                    );
+                },
+            )
+            .unwrap();
+        let world = app.world_mut();
+        let entity = world.resource::<EntityMap>().get_npc(2).unwrap().entity;
+        let snapshot = snapshot_npc(world, 2).unwrap();
+        let health = world.get::<Health>(entity).unwrap().0;
+
+        world.get_mut::<Health>(entity).unwrap().0 = 1.0;
+        world.get_mut::<NpcStats>(entity).unwrap().xp = 500;
+        world.entity_mut(entity).insert(SquadId(3));
+        restore_npc(world, 2, &snapshot, false).unwrap();
+        assert_eq!(world.get::<Health>(entity).unwrap().0, health);
+        assert_eq!(world.get::<NpcStats>(entity).unwrap().xp, 0);
+        assert!(world.get::<SquadId>(entity).is_none());
+
+        // Another NPC took the slot since
+        world.resource_mut::<NpcLogCache>().begin_life(2);
+        assert!(restore_npc(world, 2, &snapshot, false).is_err());
+        assert!(restore_npc(world, 2, &snapshot, true).is_ok());
+        assert!(
+            restore_npc(world, 3, &snapshot, true).is_err(),
+            "no NPC there"
+        );
+
+        // Unknown future format
+        let mut newer: NpcSnapshot = serde_json::from_slice(&snapshot).unwrap();
+        newer.version = NPC_SNAPSHOT_VERSION + 1;
+        let newer = serde_json::to_vec(&newer).unwrap();
+        assert!(restore_npc(world, 2, &newer, true).is_err());
+    }
+
+    #[test]
+    fn rewind_history_caps_and_picks_nearest() {
+        let mut history = SnapshotHistory::default();
+        assert!(
+            rewind_to(&mut history, 1).is_err(),
+            "disabled outside debug"
+        );
+        history.enabled = true;
+        assert!(rewind_to(&mut history, 1).is_err(), "nothing captured yet");
+
+        let payload = serde_json::json!({"npcs": vec![0; 500]});
+        let (raw_bytes, data) = compress_json(&payload).unwrap();
+        assert!(data.len() < raw_bytes);
+        for day in 1..=(MAX_REWIND_SNAPSHOTS as i32 + 2) {
+            history.push(RewindSnapshot {
+                day,
+                hour: 0,
+                raw_bytes,
+                data: data.clone(),
+            });
+        }
+        assert_eq!(history.snapshots.len(), MAX_REWIND_SNAPSHOTS);
+        assert_eq!(history.nearest(1), Some(3), "oldest two were dropped");
+        assert_eq!(history.nearest(6), Some(6));
+
+        assert_eq!(rewind_to(&mut history, 7), Ok(7));
+        assert_eq!(history.pending_rewind, Some(7));
+        let restored: serde_json::Value = decompress_json(&history.snapshots[4].data).unwrap();
+        assert_eq!(restored, payload);
+        assert!(decompress_json::<serde_json::Value>(b"not deflate").is_err());
+    }
+
+    #[test]
+    fn read_save_rejects_cyclic_tech_tree() {
+        let save_with = |defs: serde_json::Value| {
+            let mut tech = serde_json::to_value(crate::systems::TechTree::default()).unwrap();
+            tech["defs"] = defs;
+            serde_json::json!({
+                "version": SAVE_VERSION,
+                "grid_width": 0, "grid_height": 0, "grid_cell_size": 64.0,
+                "terrain": [], "buildings": [],
+                "total_seconds": 0.0, "seconds_per_hour": 5.0, "time_scale": 1.0,
+                "farm_growth": [], "spawners": [], "upgrades": [], "squads": [],
+                "raider_respawn_timers": [], "raider_forage_timers": [], "raider_max_pop": [],
+                "faction_stats": [], "kill_stats": [0, 0], "npcs": [], "ai_players": [],
+                "tech": tech,
+            })
+        };
+        let path =
+            std::env::temp_dir().join(format!("endless_cyclic_tech_{}.json", std::process::id()));
+        let read = |save: serde_json::Value| {
+            std::fs::write(&path, serde_json::to_vec(&save).unwrap()).unwrap();
+            read_save_from(&path).map(|data| data.tech)
+        };
+
+        let acyclic = serde_json::json!([
+            {"id": "a", "cost": 1.0},
+            {"id": "b", "cost": 1.0, "requires": ["a"]},
+        ]);
+        assert!(read(save_with(acyclic)).unwrap().is_some());
+        let cyclic = serde_json::json!([
+            {"id": "a", "cost": 1.0, "requires": ["b"]},
+            {"id": "b", "cost": 1.0, "requires": ["a"]},
+        ]);
+        let result = read(save_with(cyclic));
+        let _ = std::fs::remove_file(&path);
+        let err = result.err().expect("cyclic tech tree must not load");
+        assert!(err.contains("invalid tech tree"), "{err}");
+        assert!(err.contains("cycle"), "{err}");
+    }
+
+    #[test]
+    fn saves_decode_either_format_and_reject_truncation() {
+        let payload = serde_json::json!({"version": 2, "npcs": vec![7; 2000]});
+        let (plain_bytes, plain) = encode_save(&payload, false).unwrap();
+        let (json_bytes, packed) = encode_save(&payload, true).unwrap();
+        assert_eq!(plain_bytes, plain.len());
+        assert_eq!(json_bytes, plain_bytes);
+        assert!(packed.starts_with(COMPRESSED_SAVE_MAGIC));
+        assert!(packed.len() < plain.len());
+
+        // Detected from the bytes, not from any config flag
+        assert_eq!(decode_save::<serde_json::Value>(&plain).unwrap(), payload);
+        assert_eq!(decode_save::<serde_json::Value>(&packed).unwrap(), payload);
+
+        let truncated = &packed[..packed.len() / 2];
+        assert_eq!(
+            decode_save::<serde_json::Value>(truncated),
+            Err("compressed save is truncated".into())
+        );
+        let mut corrupt = COMPRESSED_SAVE_MAGIC.to_vec();
+        corrupt.extend_from_slice(&[0xff; 64]);
+        let err = decode_save::<serde_json::Value>(&corrupt).unwrap_err();
+        assert!(err.starts_with("corrupt compressed save"), "{err}");
+        assert!(decode_save::<serde_json::Value>(COMPRESSED_SAVE_MAGIC).is_err());
+    }
 }
@@ -21,6 +21,26 @@ pub enum NpcLogMode {
     SelectedOnly,
 }
 
+/// Smoothing for the camera following the selected NPC (`camera_follow_system`).
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct CameraFollowConfig {
+    /// Fraction of the gap to the unit closed per 1/60 s. 1 = lock onto the unit.
+    pub smoothing: f32,
+    /// Half-size in screen px of the central box the unit can move in without the camera
+    /// moving. 0 = always center.
+    pub dead_zone: [f32; 2],
+}
+
+impl Default for CameraFollowConfig {
+    fn default() -> Self {
+        Self {
+            smoothing: 0.15,
+            dead_zone: [80.0, 60.0],
+        }
+    }
+}
+
 /// Groupings used by the Controls settings page.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum ControlGroup {
@@ -70,6 +90,7 @@ pub enum ControlAction {
     ToggleHelp,
     ToggleCombatLog,
     ToggleFollow,
+    SelectNextIdle,
     SquadTarget1,
     SquadTarget2,
     SquadTarget3,
@@ -89,7 +110,7 @@ pub enum ControlAction {
 }
 
 impl ControlAction {
-    pub const ALL: [Self; 32] = [
+    pub const ALL: [Self; 33] = [
         Self::PanUp,
         Self::PanDown,
         Self::PanLeft,
@@ -106,6 +127,7 @@ impl ControlAction {
         Self::ToggleHelp,
         Self::ToggleCombatLog,
         Self::ToggleFollow,
+        Self::SelectNextIdle,
         Self::SquadTarget1,
         Self::SquadTarget2,
         Self::SquadTarget3,
@@ -142,6 +164,7 @@ impl ControlAction {
             Self::ToggleHelp => "toggle_help",
             Self::ToggleCombatLog => "toggle_combat_log",
             Self::ToggleFollow => "toggle_follow",
+            Self::SelectNextIdle => "select_next_idle",
             Self::SquadTarget1 => "squad_target_1",
             Self::SquadTarget2 => "squad_target_2",
             Self::SquadTarget3 => "squad_target_3",
@@ -179,6 +202,7 @@ impl ControlAction {
             Self::ToggleHelp => "Help Tab",
             Self::ToggleCombatLog => "Combat Log",
             Self::ToggleFollow => "Follow Selected",
+            Self::SelectNextIdle => "Next Idle Unit",
             Self::SquadTarget1 => "Squad 1 Target",
             Self::SquadTarget2 => "Squad 2 Target",
             Self::SquadTarget3 => "Squad 3 Target",
@@ -214,7 +238,8 @@ impl ControlAction {
             | Self::ToggleBlackjack
             | Self::ToggleHelp
             | Self::ToggleCombatLog
-            | Self::ToggleFollow => "In-game panel and HUD shortcuts.",
+            | Self::ToggleFollow
+            | Self::SelectNextIdle => "In-game panel and HUD shortcuts.",
             Self::SquadTarget1
             | Self::SquadTarget2
             | Self::SquadTarget3
@@ -246,7 +271,8 @@ impl ControlAction {
             | Self::ToggleBlackjack
             | Self::ToggleHelp
             | Self::ToggleCombatLog
-            | Self::ToggleFollow => ControlGroup::Panels,
+            | Self::ToggleFollow
+            | Self::SelectNextIdle => ControlGroup::Panels,
             Self::SquadTarget1
             | Self::SquadTarget2
             | Self::SquadTarget3
@@ -282,6 +308,7 @@ impl ControlAction {
             Self::ToggleHelp => KeyCode::KeyH,
             Self::ToggleCombatLog => KeyCode::KeyL,
             Self::ToggleFollow => KeyCode::KeyF,
+            Self::SelectNextIdle => KeyCode::Period,
             Self::SquadTarget1 => KeyCode::Digit1,
             Self::SquadTarget2 => KeyCode::Digit2,
             Self::SquadTarget3 => KeyCode::Digit3,
@@ -309,7 +336,7 @@ pub const CAMERA_ACTIONS: [ControlAction; 4] = [
     ControlAction::PanRight,
 ];
 
-pub const PANEL_ACTIONS: [ControlAction; 11] = [
+pub const PANEL_ACTIONS: [ControlAction; 12] = [
     ControlAction::ToggleRoster,
     ControlAction::ToggleBuildMenu,
     ControlAction::ToggleUpgrades,
@@ -321,6 +348,7 @@ pub const PANEL_ACTIONS: [ControlAction; 11] = [
     ControlAction::ToggleHelp,
     ControlAction::ToggleCombatLog,
     ControlAction::ToggleFollow,
+    ControlAction::SelectNextIdle,
 ];
 
 pub const SQUAD_TARGET_ACTIONS: [ControlAction; 10] = [
@@ -608,6 +636,9 @@ pub struct UserSettings {
     pub debug_profiler: bool,
     #[serde(default)]
     pub debug_ai_decisions: bool,
+    /// Keep in-memory day snapshots for `endless/rewind` (debug; costs memory).
+    #[serde(default)]
+    pub debug_rewind: bool,
     #[serde(default = "default_true")]
     pub show_terrain_sprites: bool,
     #[serde(default)]
@@ -664,6 +695,13 @@ pub struct UserSettings {
     pub npc_interval: f32,
     #[serde(default = "default_pathfind_max_per_frame")]
     pub pathfind_max_per_frame: usize,
+    /// Seconds an NPC waits after arriving before accepting an automatic retarget (0 = off).
+    #[serde(default)]
+    pub retarget_cooldown: f32,
+    /// Starting loadouts per town kind for new games (scenario design, edit settings.json).
+    #[serde(default)]
+    pub faction_loadouts:
+        std::collections::BTreeMap<crate::constants::TownKind, crate::world::FactionLoadout>,
     #[serde(default = "default_ui_scale")]
     pub ui_scale: f32,
     #[serde(default = "default_interface_text_size")]
@@ -686,6 +724,9 @@ pub struct UserSettings {
     // Autosave interval in game-hours (0 = disabled)
     #[serde(default = "default_autosave_hours")]
     pub autosave_hours: i32,
+    /// Deflate save files (any save loads either way).
+    #[serde(default)]
+    pub compress_saves: bool,
     // Audio
     #[serde(default = "default_music_volume")]
     pub music_volume: f32,
@@ -717,6 +758,11 @@ pub struct UserSettings {
     pub zoom_max: f32,
     #[serde(default = "default_lod_transition")]
     pub lod_transition: f32,
+    /// Snap sprites to the screen pixel grid at fractional zooms (off at high zoom).
+    #[serde(default)]
+    pub pixel_snap: bool,
+    #[serde(default)]
+    pub camera_follow: CameraFollowConfig,
     /// Which NPCs get activity-logged (perf: fewer = less allocation in hot loop).
     #[serde(default)]
     pub npc_log_mode: NpcLogMode,
@@ -864,6 +910,7 @@ impl Default for UserSettings {
             debug_behavior: false,
             debug_profiler: false,
             debug_ai_decisions: false,
+            debug_rewind: false,
             show_terrain_sprites: true,
             show_all_faction_squad_lines: true,
             policy: PolicySet::default(),
@@ -879,6 +926,8 @@ impl Default for UserSettings {
             gold_mines_per_town: 2,
             npc_interval: 2.0,
             pathfind_max_per_frame: default_pathfind_max_per_frame(),
+            retarget_cooldown: 0.0,
+            faction_loadouts: Default::default(),
             ui_scale: 1.0,
             interface_text_size: 16.0,
             help_text_size: 14.0,
@@ -887,6 +936,7 @@ impl Default for UserSettings {
             auto_upgrades: Vec::new(),
             difficulty: crate::resources::Difficulty::Normal,
             autosave_hours: 12,
+            compress_saves: false,
             music_volume: 0.3,
             sfx_volume: 0.15,
             sfx_shoot_enabled: false,
@@ -902,6 +952,8 @@ impl Default for UserSettings {
             zoom_min: 0.02,
             zoom_max: 4.0,
             lod_transition: 0.25,
+            pixel_snap: false,
+            camera_follow: CameraFollowConfig::default(),
             npc_log_mode: NpcLogMode::default(),
             left_panel_tab: String::new(),
             collapsed_sections: Vec::new(),
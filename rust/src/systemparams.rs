@@ -15,6 +15,8 @@ pub struct WorldState<'w> {
     pub dirty_writers: DirtyWriters<'w>,
     pub entity_slots: ResMut<'w, GpuSlotPool>,
     pub entity_map: ResMut<'w, EntityMap>,
+    pub no_build_zones: ResMut<'w, crate::resources::NoBuildZones>,
+    pub building_costs: Res<'w, crate::resources::BuildingCosts>,
 }
 
 impl WorldState<'_> {
@@ -25,14 +27,19 @@ impl WorldState<'_> {
         town_data_idx: usize,
         world_pos: Vec2,
         cost: i32,
+        tech_locked: &[crate::world::BuildingKind],
         gpu_updates: &mut MessageWriter<GpuUpdateMsg>,
         commands: &mut Commands,
     ) -> Result<(), &'static str> {
-        // 1-per-town limit
-        if kind == crate::world::BuildingKind::Merchant
-            && self.entity_map.count_for_town(kind, town_data_idx as u32) >= 1
-        {
-            return Err("Only one Merchant per town");
+        // Per-town limit (shared with BuildCheck so menu and placement agree)
+        if crate::world::town_build_limit(kind).is_some_and(|limit| {
+            self.entity_map.count_for_town(kind, town_data_idx as u32) >= limit
+        }) {
+            return Err(crate::world::BuildBlock::TownLimit.reason());
+        }
+        // Research locks (`TownAccess::tech_locked_buildings`), same as BuildCheck
+        if tech_locked.contains(&kind) {
+            return Err(crate::world::BuildBlock::TechLocked.reason());
         }
         let faction = self
             .world_data
@@ -69,6 +76,7 @@ impl WorldState<'_> {
             Some(crate::world::BuildContext {
                 grid: &mut self.grid,
                 world_data: &self.world_data,
+                no_build: &self.no_build_zones,
                 food,
                 cost,
             }),
@@ -109,8 +117,7 @@ impl WorldState<'_> {
         }
 
         // Cost = new road cost minus old road cost
-        let upgrade_cost =
-            crate::constants::building_cost(new_kind) - crate::constants::building_cost(old_kind);
+        let upgrade_cost = self.building_costs.cost(new_kind) - self.building_costs.cost(old_kind);
         if *food < upgrade_cost {
             return Err("not enough food");
         }
@@ -234,6 +241,7 @@ pub struct TownAccess<'w, 's> {
         &'static mut crate::components::TownAreaLevel,
         With<crate::components::TownMarker>,
     >,
+    tech: Option<ResMut<'w, crate::systems::TechTree>>,
 }
 
 impl TownAccess<'_, '_> {
@@ -245,6 +253,14 @@ impl TownAccess<'_, '_> {
         &mut self.index
     }
 
+    pub fn tech(&self) -> Option<&crate::systems::TechTree> {
+        self.tech.as_deref()
+    }
+
+    pub fn tech_mut(&mut self) -> Option<&mut crate::systems::TechTree> {
+        self.tech.as_deref_mut()
+    }
+
     pub fn food(&self, town_idx: i32) -> i32 {
         self.entity(town_idx)
             .and_then(|e| self.food.get(e).ok())
@@ -310,6 +326,20 @@ impl TownAccess<'_, '_> {
             .unwrap_or_default()
     }
 
+    /// Building kinds this town's research hasn't unlocked yet.
+    pub fn tech_locked_buildings(&self, town_idx: i32) -> Vec<crate::world::BuildingKind> {
+        self.tech
+            .as_ref()
+            .map(|t| t.locked_buildings(town_idx as usize))
+            .unwrap_or_default()
+    }
+
+    pub fn upgrade_tech_locked(&self, town_idx: i32, upgrade_idx: usize) -> bool {
+        self.tech
+            .as_ref()
+            .is_some_and(|t| t.upgrade_locked(town_idx as usize, upgrade_idx))
+    }
+
     pub fn upgrade_level(&self, town_idx: i32, upgrade_idx: usize) -> u8 {
         self.entity(town_idx)
             .and_then(|e| self.upgrades.get(e).ok())
@@ -21,6 +21,7 @@ use crate::systemparams::WorldState;
 use crate::systems::stats::{
     UPGRADES, expansion_cost, upgrade_available, upgrade_cost, upgrade_node, upgrade_unlocked,
 };
+use crate::systems::threat_map::ThreatMap;
 use crate::world::{self, BuildingKind, WorldData, WorldGrid};
 
 // Rust orientation notes for readers coming from PowerShell:
@@ -827,6 +828,7 @@ fn find_inner_slot(
     area_level: i32,
     grid: &WorldGrid,
     entity_map: &EntityMap,
+    no_build: &NoBuildZones,
     personality: AiPersonality,
     road_style: RoadStyle,
 ) -> Option<(usize, usize)> {
@@ -837,7 +839,11 @@ fn find_inner_slot(
         .collect();
     world::empty_slots(town_idx, center, grid, entity_map)
         .into_iter()
-        .filter(|&(c, r)| !road_style.is_road_slot(c, r, cc, cr) && !wp_slots.contains(&(c, r)))
+        .filter(|&(c, r)| {
+            !road_style.is_road_slot(c, r, cc, cr)
+                && !wp_slots.contains(&(c, r))
+                && !no_build.contains(grid.grid_to_world(c, r))
+        })
         .min_by_key(|&(c, r)| {
             let dc = c as i32 - cc as i32;
             let dr = r as i32 - cr as i32;
@@ -849,6 +855,7 @@ fn build_town_snapshot(
     world_data: &WorldData,
     entity_map: &EntityMap,
     grid: &WorldGrid,
+    no_build: &NoBuildZones,
     town_data_idx: usize,
     town_area_level: i32,
     personality: AiPersonality,
@@ -880,7 +887,11 @@ fn build_town_snapshot(
     let wp_slots: HashSet<(usize, usize)> = waypoint_ring.iter().copied().collect();
     let empty_slots = world::empty_slots(town_data_idx, center, grid, entity_map)
         .into_iter()
-        .filter(|&(c, r)| !road_style.is_road_slot(c, r, cc, cr) && !wp_slots.contains(&(c, r)))
+        .filter(|&(c, r)| {
+            !road_style.is_road_slot(c, r, cc, cr)
+                && !wp_slots.contains(&(c, r))
+                && !no_build.contains(grid.grid_to_world(c, r))
+        })
         .collect();
 
     Some(AiTownSnapshot {
@@ -1153,6 +1164,7 @@ fn find_waypoint_slot(
     center: Vec2,
     grid: &WorldGrid,
     entity_map: &EntityMap,
+    no_build: &NoBuildZones,
     ti: u32,
     personality: AiPersonality,
     road_style: RoadStyle,
@@ -1175,7 +1187,10 @@ fn find_waypoint_slot(
         .iter()
         .copied()
         .filter(|slot| !existing.contains(slot))
-        .find(|&(c, r)| !entity_map.has_building_at(c as i32, r as i32))
+        .find(|&(c, r)| {
+            !entity_map.has_building_at(c as i32, r as i32)
+                && !no_build.contains(grid.grid_to_world(c, r))
+        })
 }
 
 fn sync_town_perimeter_waypoints(
@@ -1210,8 +1225,13 @@ fn sync_town_perimeter_waypoints(
         .map(|b| world.grid.world_to_grid(b.position))
         .collect();
 
+    // Ring slots inside a no-build zone can never be filled; don't wait on them
     let outer_complete = ideal.iter().all(|&(c, r)| {
-        existing.contains(&(c, r)) || world.entity_map.has_building_at(c as i32, r as i32)
+        existing.contains(&(c, r))
+            || world.entity_map.has_building_at(c as i32, r as i32)
+            || world
+                .no_build_zones
+                .contains(world.grid.grid_to_world(c, r))
     });
     if !outer_complete {
         return 0;
@@ -1370,6 +1390,8 @@ struct TownContext {
     tdi: usize,
     area_level: i32,
     food: i32,
+    /// Kinds this town hasn't researched yet (`TechTree::locked_buildings`).
+    tech_locked: Vec<BuildingKind>,
     has_slots: bool,
     slot_fullness: f32,
     mines: Option<MineAnalysis>,
@@ -1384,6 +1406,7 @@ impl TownContext {
         kind: AiKind,
         mining_radius: f32,
         town_area_level: i32,
+        tech_locked: Vec<BuildingKind>,
     ) -> Option<Self> {
         let town = res.world.world_data.towns.get(tdi)?;
         let center = snapshot.map(|s| s.center).unwrap_or(town.center);
@@ -1405,6 +1428,7 @@ impl TownContext {
             tdi,
             area_level,
             food,
+            tech_locked,
             has_slots: empty_count > 0,
             slot_fullness,
             mines,
@@ -1432,6 +1456,7 @@ pub fn ai_decision_system(
     mut snapshots: Local<AiTownSnapshotCache>,
     settings: Res<crate::settings::UserSettings>,
     mut snapshot_dirty: ResMut<AiSnapshotDirty>,
+    faction_list: Res<FactionList>,
 ) {
     // System timing gate:
     // runs every `decision_interval`, not every frame.
@@ -1477,6 +1502,7 @@ pub fn ai_decision_system(
                 &res.world.world_data,
                 &res.world.entity_map,
                 &res.world.grid,
+                &res.world.no_build_zones,
                 tdi,
                 town_access.area_level(tdi as i32),
                 personality,
@@ -1500,6 +1526,18 @@ pub fn ai_decision_system(
             .as_ref()
             .map(|p| p.mining_radius)
             .unwrap_or(crate::constants::DEFAULT_MINING_RADIUS);
+        // Spend research before reading locks. Raider towns earn none (research_system).
+        let researched = match kind {
+            AiKind::Builder => town_access.tech_mut().and_then(|tech| {
+                let id = tech
+                    .next_affordable_tech(tdi, &res.world.entity_map)?
+                    .to_string();
+                tech.unlock_tech(tdi, &id, &res.world.entity_map)
+                    .ok()
+                    .map(|_| id)
+            }),
+            AiKind::Raider => None,
+        };
         let Some(ctx) = TownContext::build(
             tdi,
             food,
@@ -1508,6 +1546,7 @@ pub fn ai_decision_system(
             kind,
             mining_radius,
             town_access.area_level(tdi as i32),
+            town_access.tech_locked_buildings(tdi as i32),
         ) else {
             continue;
         };
@@ -1519,7 +1558,33 @@ pub fn ai_decision_system(
             .get(tdi)
             .map(|t| t.name.clone())
             .unwrap_or_default();
-        let pname = personality.name();
+        let faction = res
+            .world
+            .world_data
+            .towns
+            .get(tdi)
+            .map(|t| t.faction)
+            .unwrap_or(0);
+        let stance = faction_list.stance(faction);
+        let plabel = personality_label(personality, stance);
+        let pname = plabel.as_str();
+
+        if let Some(id) = researched {
+            let what = format!("researched {id}");
+            log_ai(
+                &mut combat_log,
+                &game_time,
+                faction,
+                &town_name,
+                pname,
+                &what,
+            );
+            let actions = &mut ai_state.players[pi].last_actions;
+            if actions.len() >= MAX_ACTION_HISTORY {
+                actions.pop_front();
+            }
+            actions.push_back((what, game_time.day(), game_time.hour()));
+        }
 
         // Pre-compute mine_shafts before bc closure to allow mutable borrow for bootstrap.
         let mine_shafts = res
@@ -1532,7 +1597,7 @@ pub fn ai_decision_system(
         if matches!(kind, AiKind::Builder)
             && ctx.has_slots
             && mine_shafts < personality.min_miner_homes()
-            && food >= building_cost(BuildingKind::MinerHome)
+            && food >= res.world.building_costs.cost(BuildingKind::MinerHome)
         {
             if let Some(mines) = ctx
                 .mines
@@ -1549,13 +1614,6 @@ pub fn ai_decision_system(
                     road_style,
                 ) {
                     snapshots.towns.remove(&tdi);
-                    let faction = res
-                        .world
-                        .world_data
-                        .towns
-                        .get(tdi)
-                        .map(|t| t.faction)
-                        .unwrap_or(0);
                     log_ai(
                         &mut combat_log,
                         &game_time,
@@ -1578,7 +1636,7 @@ pub fn ai_decision_system(
         if matches!(kind, AiKind::Builder)
             && ctx.has_slots
             && mine_shafts < personality.min_miner_homes()
-            && food < building_cost(BuildingKind::MinerHome)
+            && food < res.world.building_costs.cost(BuildingKind::MinerHome)
         {
             if ctx
                 .mines
@@ -1601,13 +1659,6 @@ pub fn ai_decision_system(
         let xbow_homes = bc(BuildingKind::CrossbowHome);
         let waypoints = bc(BuildingKind::Waypoint);
         let total_military_homes = barracks + xbow_homes;
-        let faction = res
-            .world
-            .world_data
-            .towns
-            .get(tdi)
-            .map(|t| t.faction)
-            .unwrap_or(0);
         // Threat signal from GPU spatial grid: fountain's enemy count from readback.
         let threat = res
             .world
@@ -1657,6 +1708,12 @@ pub fn ai_decision_system(
             personality.base_mining_desire()
         };
 
+        // Faction stance override scales military pressure before the economy floor.
+        if let Some(st) = stance {
+            desires.military_desire =
+                (desires.military_desire * st.military_desire_mult()).clamp(0.0, 1.0);
+        }
+
         // Economy desire: how much the town needs to fill its buildable area.
         // Floors other desires so building scores never collapse to zero while slots remain.
         desires.economy_desire = 1.0 - ctx.slot_fullness;
@@ -1700,7 +1757,9 @@ pub fn ai_decision_system(
             match kind {
                 AiKind::Raider => {
                     // Raider AI has a smaller economy action set.
-                    if ctx.has_slots && ctx.food >= building_cost(BuildingKind::Tent) {
+                    if ctx.has_slots
+                        && ctx.food >= res.world.building_costs.cost(BuildingKind::Tent)
+                    {
                         build_scores.push((AiAction::BuildTent, 30.0));
                     }
                 }
@@ -1739,17 +1798,20 @@ pub fn ai_decision_system(
                             desires.military_desire * 0.5
                         };
 
-                        if ctx.food >= building_cost(BuildingKind::Farm) {
+                        if ctx.food >= res.world.building_costs.cost(BuildingKind::Farm) {
                             build_scores.push((AiAction::BuildFarm, fw * farm_need));
                         }
-                        if ctx.food >= building_cost(BuildingKind::FarmerHome) {
+                        if ctx.food >= res.world.building_costs.cost(BuildingKind::FarmerHome) {
                             build_scores.push((AiAction::BuildFarmerHome, hw * house_need));
                         }
-                        if ctx.food >= building_cost(BuildingKind::ArcherHome) {
+                        if ctx.food >= res.world.building_costs.cost(BuildingKind::ArcherHome) {
                             build_scores.push((AiAction::BuildArcherHome, bw * barracks_need));
                         }
                         // Crossbow homes: AI builds them once it has some archer homes established
-                        if barracks >= 2 && ctx.food >= building_cost(BuildingKind::CrossbowHome) {
+                        if barracks >= 2
+                            && ctx.food >= res.world.building_costs.cost(BuildingKind::CrossbowHome)
+                            && !ctx.tech_locked.contains(&BuildingKind::CrossbowHome)
+                        {
                             let xbow_need = if xbow_homes < barracks / 2 {
                                 desires.military_desire
                                     * barracks.saturating_sub(xbow_homes * 2) as f32
@@ -1758,7 +1820,9 @@ pub fn ai_decision_system(
                             };
                             build_scores.push((AiAction::BuildCrossbowHome, bw * 0.6 * xbow_need));
                         }
-                        if miner_deficit > 0 && ctx.food >= building_cost(BuildingKind::MinerHome) {
+                        if miner_deficit > 0
+                            && ctx.food >= res.world.building_costs.cost(BuildingKind::MinerHome)
+                        {
                             let ms_need = desires.gold_desire * miner_deficit as f32;
                             // Bootstrap boost: guarantee min miner homes per personality
                             let bootstrap = if mine_shafts < personality.min_miner_homes() {
@@ -1785,7 +1849,7 @@ pub fn ai_decision_system(
                         .map(|s| s.waypoint_ring.len())
                         .unwrap_or(total_military_homes);
                     let waypoint_target = total_military_homes.max(perimeter_target);
-                    if ctx.food >= building_cost(BuildingKind::Waypoint)
+                    if ctx.food >= res.world.building_costs.cost(BuildingKind::Waypoint)
                         && waypoints < waypoint_target
                     {
                         let gp_need =
@@ -1797,7 +1861,7 @@ pub fn ai_decision_system(
                     let rw = personality.road_weight();
                     if road_style != RoadStyle::None
                         && rw > 0.0
-                        && ctx.food >= building_cost(BuildingKind::Road) * 4
+                        && ctx.food >= res.world.building_costs.cost(BuildingKind::Road) * 4
                     {
                         let road_candidates = count_road_candidates(
                             &res.world.entity_map,
@@ -1969,10 +2033,13 @@ pub fn ai_decision_system(
                         // Delay expansion while town still has empty slots and can afford buildings.
                         // Previous check only looked at home targets — missed farms, waypoints, roads.
                         if matches!(kind, AiKind::Builder) && ctx.has_slots {
-                            let cheapest = building_cost(BuildingKind::Farm)
-                                .min(building_cost(BuildingKind::FarmerHome))
-                                .min(building_cost(BuildingKind::ArcherHome))
-                                .min(building_cost(BuildingKind::MinerHome));
+                            let cheapest = res
+                                .world
+                                .building_costs
+                                .cost(BuildingKind::Farm)
+                                .min(res.world.building_costs.cost(BuildingKind::FarmerHome))
+                                .min(res.world.building_costs.cost(BuildingKind::ArcherHome))
+                                .min(res.world.building_costs.cost(BuildingKind::MinerHome));
                             if food_after >= cheapest {
                                 continue;
                             }
@@ -2054,6 +2121,7 @@ fn try_build_at_slot(
     cost: i32,
     label: &str,
     tdi: usize,
+    tech_locked: &[BuildingKind],
     res: &mut AiBuildRes,
     food: &mut i32,
     col: usize,
@@ -2067,6 +2135,7 @@ fn try_build_at_slot(
             tdi,
             pos,
             cost,
+            tech_locked,
             &mut res.gpu_updates,
             &mut res.commands,
         )
@@ -2081,6 +2150,7 @@ fn pick_slot_from_snapshot_or_inner(
     area_level: i32,
     grid: &WorldGrid,
     entity_map: &EntityMap,
+    no_build: &NoBuildZones,
     score: fn(&AiTownSnapshot, (usize, usize)) -> i32,
     personality: AiPersonality,
     road_style: RoadStyle,
@@ -2096,6 +2166,7 @@ fn pick_slot_from_snapshot_or_inner(
         area_level,
         grid,
         entity_map,
+        no_build,
         personality,
         road_style,
     )
@@ -2106,6 +2177,7 @@ fn try_build_inner(
     cost: i32,
     label: &str,
     tdi: usize,
+    tech_locked: &[BuildingKind],
     center: Vec2,
     res: &mut AiBuildRes,
     food: &mut i32,
@@ -2119,16 +2191,18 @@ fn try_build_inner(
         area_level,
         &res.world.grid,
         &res.world.entity_map,
+        &res.world.no_build_zones,
         personality,
         road_style,
     )?;
-    try_build_at_slot(kind, cost, label, tdi, res, food, col, row)
+    try_build_at_slot(kind, cost, label, tdi, tech_locked, res, food, col, row)
 }
 
 fn try_build_scored(
     kind: BuildingKind,
     label: &str,
     tdi: usize,
+    tech_locked: &[BuildingKind],
     center: Vec2,
     res: &mut AiBuildRes,
     food: &mut i32,
@@ -2145,11 +2219,22 @@ fn try_build_scored(
         area_level,
         &res.world.grid,
         &res.world.entity_map,
+        &res.world.no_build_zones,
         score_fn,
         personality,
         road_style,
     )?;
-    try_build_at_slot(kind, building_cost(kind), label, tdi, res, food, col, row)
+    try_build_at_slot(
+        kind,
+        res.world.building_costs.cost(kind),
+        label,
+        tdi,
+        tech_locked,
+        res,
+        food,
+        col,
+        row,
+    )
 }
 
 fn try_build_miner_home(
@@ -2176,6 +2261,7 @@ fn try_build_miner_home(
                 ctx.area_level,
                 &res.world.grid,
                 &res.world.entity_map,
+                &res.world.no_build_zones,
                 personality,
                 road_style,
             )
@@ -2187,15 +2273,17 @@ fn try_build_miner_home(
             ctx.area_level,
             &res.world.grid,
             &res.world.entity_map,
+            &res.world.no_build_zones,
             personality,
             road_style,
         )
     }?;
     try_build_at_slot(
         BuildingKind::MinerHome,
-        building_cost(BuildingKind::MinerHome),
+        res.world.building_costs.cost(BuildingKind::MinerHome),
         "miner home",
         ctx.tdi,
+        &ctx.tech_locked,
         res,
         food,
         slot.0,
@@ -2278,7 +2366,7 @@ fn try_build_road_grid(
     batch_size: usize,
     road_style: RoadStyle,
 ) -> Option<String> {
-    let cost = building_cost(BuildingKind::Road);
+    let cost = res.world.building_costs.cost(BuildingKind::Road);
     let ti = ctx.ti;
     let grid = &res.world.grid;
     let (cc, cr) = grid.world_to_grid(ctx.center);
@@ -2374,6 +2462,7 @@ fn try_build_road_grid(
                 ctx.tdi,
                 pos,
                 cost,
+                &ctx.tech_locked,
                 &mut res.gpu_updates,
                 &mut res.commands,
             )
@@ -2408,9 +2497,10 @@ fn execute_action(
     match action {
         AiAction::BuildTent => try_build_inner(
             BuildingKind::Tent,
-            building_cost(BuildingKind::Tent),
+            res.world.building_costs.cost(BuildingKind::Tent),
             "tent",
             ctx.tdi,
+            &ctx.tech_locked,
             ctx.center,
             res,
             food,
@@ -2428,6 +2518,7 @@ fn execute_action(
                 BuildingKind::Farm,
                 "farm",
                 ctx.tdi,
+                &ctx.tech_locked,
                 ctx.center,
                 res,
                 food,
@@ -2448,6 +2539,7 @@ fn execute_action(
                 BuildingKind::FarmerHome,
                 "farmer home",
                 ctx.tdi,
+                &ctx.tech_locked,
                 ctx.center,
                 res,
                 food,
@@ -2462,6 +2554,7 @@ fn execute_action(
             BuildingKind::ArcherHome,
             "archer home",
             ctx.tdi,
+            &ctx.tech_locked,
             ctx.center,
             res,
             food,
@@ -2475,6 +2568,7 @@ fn execute_action(
             BuildingKind::CrossbowHome,
             "crossbow home",
             ctx.tdi,
+            &ctx.tech_locked,
             ctx.center,
             res,
             food,
@@ -2504,13 +2598,14 @@ fn execute_action(
             Some(format!("expanded mining radius to {:.0}px", new))
         }
         AiAction::BuildWaypoint => {
-            let cost = building_cost(BuildingKind::Waypoint);
+            let cost = res.world.building_costs.cost(BuildingKind::Waypoint);
             let cached_ring = snapshot.map(|s| s.waypoint_ring.as_slice());
             let (col, row) = find_waypoint_slot(
                 ctx.area_level,
                 ctx.center,
                 &res.world.grid,
                 &res.world.entity_map,
+                &res.world.no_build_zones,
                 ctx.ti,
                 personality,
                 road_style,
@@ -2525,6 +2620,7 @@ fn execute_action(
                     ctx.tdi,
                     pos,
                     cost,
+                    &ctx.tech_locked,
                     &mut res.gpu_updates,
                     &mut res.commands,
                 )
@@ -2561,6 +2657,15 @@ fn execute_action(
     }
 }
 
+/// Personality name for AI logs, with the faction stance override when set
+/// (e.g. "Balanced/passive").
+fn personality_label(personality: AiPersonality, stance: Option<FactionStance>) -> String {
+    match stance {
+        Some(st) => format!("{}/{}", personality.name(), st.label()),
+        None => personality.name().to_string(),
+    }
+}
+
 fn log_ai(
     log: &mut MessageWriter<crate::messages::CombatLogMsg>,
     gt: &GameTime,
@@ -2699,13 +2804,23 @@ impl AiPersonality {
     }
 }
 
-/// Pick nearest enemy farm as raider squad target.
-fn pick_raider_farm_target(
+/// Target score: squared distance stretched by the enemy strength around the target, so a
+/// slightly farther but undefended building beats a nearby one behind a garrison.
+/// With an empty threat map this is plain nearest-first.
+fn threat_weighted_d2(threat_map: &ThreatMap, faction: i32, pos: Vec2, d2: f32) -> f32 {
+    let threat = threat_map.enemy_strength_near(faction, pos, 1) as f32;
+    let penalty = 1.0 + threat / AI_THREAT_AVOID_STRENGTH;
+    d2 * penalty * penalty
+}
+
+/// Pick the best enemy farm as raider squad target (nearest, weighted by defending strength).
+pub(crate) fn pick_raider_farm_target(
     entity_map: &EntityMap,
+    threat_map: &ThreatMap,
     center: Vec2,
     faction: i32,
 ) -> Option<(BuildingKind, Entity, Vec2)> {
-    let mut best_d2 = f32::MAX;
+    let mut best_score = f32::MAX;
     let mut result: Option<(BuildingKind, Entity, Vec2)> = None;
     let r2 = AI_ATTACK_SEARCH_RADIUS * AI_ATTACK_SEARCH_RADIUS;
     entity_map.for_each_nearby_kind(
@@ -2722,8 +2837,12 @@ fn pick_raider_farm_target(
             let dx = inst.position.x - center.x;
             let dy = inst.position.y - center.y;
             let d2 = dx * dx + dy * dy;
-            if d2 <= r2 && d2 < best_d2 {
-                best_d2 = d2;
+            if d2 > r2 {
+                return;
+            }
+            let score = threat_weighted_d2(threat_map, faction, inst.position, d2);
+            if score < best_score {
+                best_score = score;
                 result = Some((inst.kind, uid, inst.position));
             }
         },
@@ -2733,6 +2852,7 @@ fn pick_raider_farm_target(
 
 fn pick_ai_target_unclaimed(
     entity_map: &EntityMap,
+    threat_map: &ThreatMap,
     center: Vec2,
     faction: i32,
     personality: AiPersonality,
@@ -2745,7 +2865,7 @@ fn pick_ai_target_unclaimed(
 
     let find_nearest_unclaimed =
         |allowed_kinds: &[BuildingKind]| -> Option<(BuildingKind, Entity, Vec2)> {
-            let mut best_d2 = f32::MAX;
+            let mut best_score = f32::MAX;
             let mut result: Option<(BuildingKind, Entity, Vec2)> = None;
             let r2 = AI_ATTACK_SEARCH_RADIUS * AI_ATTACK_SEARCH_RADIUS;
             for &kind in allowed_kinds {
@@ -2768,8 +2888,12 @@ fn pick_ai_target_unclaimed(
                         let dx = inst.position.x - center.x;
                         let dy = inst.position.y - center.y;
                         let d2 = dx * dx + dy * dy;
-                        if d2 <= r2 && d2 < best_d2 {
-                            best_d2 = d2;
+                        if d2 > r2 {
+                            return;
+                        }
+                        let score = threat_weighted_d2(threat_map, faction, inst.position, d2);
+                        if score < best_score {
+                            best_score = score;
                             result = Some((inst.kind, uid, inst.position));
                         }
                     },
@@ -2802,11 +2926,14 @@ pub fn ai_squad_commander_system(
     mut squad_state: ResMut<SquadState>,
     world_data: Res<WorldData>,
     entity_map: Res<EntityMap>,
+    threat_map: Res<ThreatMap>,
     mut combat_log: MessageWriter<crate::messages::CombatLogMsg>,
     game_time: Res<GameTime>,
     mut squads_dirty_w: MessageWriter<crate::messages::SquadsDirtyMsg>,
     mut timer: Local<f32>,
     military_q: Query<(&Job, &TownId), (Without<Building>, Without<Dead>)>,
+    faction_list: Res<FactionList>,
+    raid_parties: Res<RaidPartyConfig>,
 ) {
     const AI_SQUAD_HEARTBEAT: f32 = 2.0;
     let dt = game_time.delta(&time);
@@ -2835,11 +2962,18 @@ pub fn ai_squad_commander_system(
         let tdi = player.town_data_idx;
         let personality = player.personality;
         let kind = player.kind;
+        // Raid-party mode: raid_party_system sends camp raiders instead of a squad
+        if kind == AiKind::Raider && raid_parties.enabled {
+            continue;
+        }
         let Some(town) = world_data.towns.get(tdi) else {
             continue;
         };
         let center = town.center;
         let faction = town.faction;
+        // Stance is read at each heartbeat: a change only affects the next wave decision,
+        // never an in-progress wave.
+        let stance = faction_list.stance(faction);
 
         // --- Self-healing squad allocation ---
         let desired = match kind {
@@ -3008,10 +3142,11 @@ pub fn ai_squad_commander_system(
                     squad.wave_start_count = 0;
                     cmd.building_uid = None;
                     cmd.cooldown = personality.retarget_cooldown()
+                        * stance.map_or(1.0, |st| st.retarget_cooldown_mult())
                         + rand::rng().random_range(-RETARGET_JITTER..RETARGET_JITTER);
 
                     let town_name = &town.name;
-                    let pname = personality.name();
+                    let pname = personality_label(personality, stance);
                     combat_log.write(crate::messages::CombatLogMsg {
                         kind: CombatEventKind::Raid,
                         faction,
@@ -3034,9 +3169,12 @@ pub fn ai_squad_commander_system(
 
                 // Pick target based on AI kind
                 let target = match kind {
-                    AiKind::Raider => pick_raider_farm_target(&entity_map, center, faction),
+                    AiKind::Raider => {
+                        pick_raider_farm_target(&entity_map, &threat_map, center, faction)
+                    }
                     AiKind::Builder => pick_ai_target_unclaimed(
                         &entity_map,
+                        &threat_map,
                         center,
                         faction,
                         personality,
@@ -3045,6 +3183,11 @@ pub fn ai_squad_commander_system(
                     ),
                 };
 
+                // Passive never initiates; Defensive only hits targets near home.
+                let target = target.filter(|&(_, _, pos)| {
+                    stance.is_none_or(|st| st.may_initiate(pos.distance(center)))
+                });
+
                 if let Some((bk, uid, pos)) = target {
                     cmd.building_uid = Some(uid);
                     claimed_targets.insert(uid);
@@ -3055,7 +3198,7 @@ pub fn ai_squad_commander_system(
                     squad.wave_start_count = member_count;
 
                     let town_name = &town.name;
-                    let pname = personality.name();
+                    let pname = personality_label(personality, stance);
                     let unit_label = match kind {
                         AiKind::Raider => "raiders",
                         AiKind::Builder => "units",
@@ -3199,4 +3342,32 @@ mod tests {
             "PerimeterSyncDirty should stay false with no msgs"
         );
     }
+
+    #[test]
+    fn raider_target_avoids_defended_farm() {
+        let mut em = EntityMap::default();
+        em.init_spatial(8192.0);
+        let near = Vec2::new(1000.0, 1000.0);
+        let far = Vec2::new(3000.0, 1000.0);
+        for (slot, position) in [(10, near), (11, far)] {
+            em.add_instance(BuildingInstance {
+                kind: BuildingKind::Farm,
+                position,
+                slot,
+                town_idx: 1,
+                faction: 2,
+            });
+            em.set_entity(slot, Entity::from_raw_u32(slot as u32).unwrap());
+        }
+        let center = Vec2::new(0.0, 1000.0);
+        let mut threat = ThreatMap::default();
+        threat.reset(&WorldBounds::from_grid(250, 250, 32.0), 512.0);
+        let pick = |tm: &ThreatMap| pick_raider_farm_target(&em, tm, center, 3).map(|t| t.2);
+
+        // Empty map: nearest wins
+        assert_eq!(pick(&threat), Some(near));
+        // A garrison at the near farm pushes the raid to the undefended one
+        threat.add(2, near, 200);
+        assert_eq!(pick(&threat), Some(far));
+    }
 }
@@ -0,0 +1,135 @@
+//! Anchoring — NPCs settled in a resting activity stop moving and drop out of separation, so
+//! a packed barracks or fountain doesn't jostle every frame. `anchor_system` inserts/removes
+//! `Anchored` as activities change; `target_priority_system` mirrors it into the GPU flag.
+//! Damage and losing the building an NPC rests at wake it immediately (`wake_anchored`).
+
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::resources::AnchorConfig;
+
+/// Whether an NPC in this activity/combat state should be anchored.
+pub fn anchor_wanted(activity: &Activity, combat: &CombatState, config: &AnchorConfig) -> bool {
+    if combat.is_fighting() {
+        return false;
+    }
+    match (activity.kind, activity.phase) {
+        (ActivityKind::Rest, ActivityPhase::Active) => config.rest,
+        (ActivityKind::Heal, ActivityPhase::Active) => config.heal,
+        (ActivityKind::Patrol, ActivityPhase::Holding) => config.on_duty,
+        _ => false,
+    }
+}
+
+/// Un-anchor an NPC and drop it back to Idle so the next decision tick re-plans.
+pub fn wake_anchored(commands: &mut Commands, entity: Entity, activity: &mut Activity) {
+    if let Ok(mut ec) = commands.get_entity(entity) {
+        ec.remove::<Anchored>();
+    }
+    crate::systems::decision::transition_activity(
+        activity,
+        ActivityKind::Idle,
+        ActivityPhase::Ready,
+        ActivityTarget::None,
+        "anchor:woken",
+    );
+}
+
+/// Keep `Anchored` in step with each NPC's activity. Only NPCs whose activity or combat state
+/// changed are re-checked, unless `AnchorConfig` itself changed.
+pub fn anchor_system(
+    mut commands: Commands,
+    config: Res<AnchorConfig>,
+    changed_q: Query<
+        (Entity, &Activity, &CombatState, Has<Anchored>),
+        (
+            Without<Building>,
+            Without<Dead>,
+            Or<(Changed<Activity>, Changed<CombatState>)>,
+        ),
+    >,
+    all_q: Query<
+        (Entity, &Activity, &CombatState, Has<Anchored>),
+        (Without<Building>, Without<Dead>),
+    >,
+) {
+    let mut apply = |entity: Entity, activity: &Activity, combat: &CombatState, anchored: bool| {
+        let wanted = anchor_wanted(activity, combat, &config);
+        if wanted == anchored {
+            return;
+        }
+        let Ok(mut ec) = commands.get_entity(entity) else {
+            return;
+        };
+        if wanted {
+            ec.insert(Anchored);
+        } else {
+            ec.remove::<Anchored>();
+        }
+    };
+    if config.is_changed() {
+        for (entity, activity, combat, anchored) in all_q.iter() {
+            apply(entity, activity, combat, anchored);
+        }
+    } else {
+        for (entity, activity, combat, anchored) in changed_q.iter() {
+            apply(entity, activity, combat, anchored);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchor_follows_config_and_combat() {
+        let config = AnchorConfig::default();
+        let mut activity = Activity::new(ActivityKind::Rest);
+        assert!(!anchor_wanted(&activity, &CombatState::None, &config));
+        activity.phase = ActivityPhase::Active;
+        assert!(anchor_wanted(&activity, &CombatState::None, &config));
+        let fighting = CombatState::Fighting { origin: Vec2::ZERO };
+        assert!(!anchor_wanted(&activity, &fighting, &config));
+
+        let heal = Activity {
+            kind: ActivityKind::Heal,
+            phase: ActivityPhase::Active,
+            ..Default::default()
+        };
+        assert!(!anchor_wanted(&heal, &CombatState::None, &config));
+        let config = AnchorConfig {
+            heal: true,
+            ..config
+        };
+        assert!(anchor_wanted(&heal, &CombatState::None, &config));
+    }
+
+    #[test]
+    fn system_anchors_resting_and_releases_on_wake() {
+        let mut app = App::new();
+        app.init_resource::<AnchorConfig>();
+        app.add_systems(Update, anchor_system);
+        let resting = Activity {
+            kind: ActivityKind::Rest,
+            phase: ActivityPhase::Active,
+            target: ActivityTarget::Home,
+            ..Default::default()
+        };
+        let npc = app.world_mut().spawn((resting, CombatState::None)).id();
+        app.update();
+        assert!(app.world().entity(npc).contains::<Anchored>());
+
+        *app.world_mut().get_mut::<Activity>(npc).unwrap() = Activity::new(ActivityKind::Idle);
+        app.update();
+        assert!(!app.world().entity(npc).contains::<Anchored>());
+
+        // Turning rest anchoring off releases NPCs that are still asleep
+        *app.world_mut().get_mut::<Activity>(npc).unwrap() = resting;
+        app.update();
+        assert!(app.world().entity(npc).contains::<Anchored>());
+        app.world_mut().resource_mut::<AnchorConfig>().rest = false;
+        app.update();
+        assert!(!app.world().entity(npc).contains::<Anchored>());
+    }
+}
@@ -0,0 +1,546 @@
+//! Manual NPC assignments — pin a unit to one farm, bed or patrol post.
+//! Each assignment reserves the building's occupancy and attaches the matching component in
+//! one step (`AssignedFarm` + `NpcWorkState`, `AssignedBed` + `Home`, `AssignedPost` +
+//! `PatrolRoute`), so reservation and behavior state can't drift apart. Reassigning releases
+//! the old reservation first; clearing releases it and restores the automatic behavior.
+//! `death_system` releases bed/post reservations (farms go through the normal worksite release).
+//!
+//! Guard posts: unpinned patrol units walk the whole town route, so a post only goes uncovered
+//! once every guard is pinned elsewhere. `auto_assign_guards` pins free guards to uncovered
+//! posts nearest-first, but keeps one roaming unless there are guards for every post, so
+//! auto-assignment never uncovers a post; `guard_post_system` moves guards off removed posts and, for towns with
+//! the `auto_assign_guards` policy, re-runs the assignment whenever the waypoints change.
+
+use bevy::ecs::message::Messages;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::*;
+use crate::constants::building_def;
+use crate::messages::PatrolsDirtyMsg;
+use crate::resources::{EntityMap, GpuReadState};
+use crate::world::BuildingKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssignmentKind {
+    Farm,
+    Bed,
+    Post,
+}
+
+impl AssignmentKind {
+    fn building(self) -> BuildingKind {
+        match self {
+            Self::Farm => BuildingKind::Farm,
+            Self::Bed => BuildingKind::Bed,
+            Self::Post => BuildingKind::Waypoint,
+        }
+    }
+
+    /// Units one building of this kind takes by manual assignment.
+    fn capacity(self) -> i32 {
+        match self {
+            Self::Farm => building_def(BuildingKind::Farm)
+                .worksite
+                .map_or(1, |ws| ws.max_occupants),
+            Self::Bed | Self::Post => 1,
+        }
+    }
+}
+
+/// Live NPC at `slot`: (entity, job, town_idx).
+fn live_npc(world: &World, slot: usize) -> Result<(Entity, Job, i32), String> {
+    match world.resource::<EntityMap>().get_npc(slot) {
+        Some(npc) if !npc.dead => Ok((npc.entity, npc.job, npc.town_idx)),
+        _ => Err(format!("no live NPC at slot {slot}")),
+    }
+}
+
+/// Building currently reserved by `entity` for `kind`.
+fn held(world: &World, entity: Entity, kind: AssignmentKind) -> Option<Entity> {
+    match kind {
+        AssignmentKind::Farm => world.get::<NpcWorkState>(entity).and_then(|ws| ws.worksite),
+        AssignmentKind::Bed => world.get::<AssignedBed>(entity).map(|b| b.bed),
+        AssignmentKind::Post => world.get::<AssignedPost>(entity).map(|p| p.0),
+    }
+}
+
+fn release(em: &mut EntityMap, slot: usize, kind: AssignmentKind, npc: Entity) {
+    if kind == AssignmentKind::Farm {
+        em.release_for(slot, Some(npc));
+    } else {
+        em.release(slot);
+    }
+}
+
+/// Assign the NPC at `npc_slot` to the building at `building_slot`: reserve it and attach the
+/// component. Farms take farmers, posts take patrol units, beds take anyone; the building must
+/// belong to the NPC's town and have room. On failure nothing changes.
+/// Returns the building position.
+pub fn assign_npc(
+    world: &mut World,
+    npc_slot: usize,
+    building_slot: usize,
+    kind: AssignmentKind,
+) -> Result<Vec2, String> {
+    let (entity, job, town_idx) = live_npc(world, npc_slot)?;
+    match kind {
+        AssignmentKind::Farm if job != Job::Farmer => return Err("only farmers work farms".into()),
+        AssignmentKind::Post if !job.is_patrol_unit() => {
+            return Err("only patrol units hold posts".into());
+        }
+        _ => {}
+    }
+    let old = held(world, entity, kind);
+    let claimer = (kind == AssignmentKind::Farm).then_some(entity);
+
+    let mut em = world.resource_mut::<EntityMap>();
+    let old_slot = old.and_then(|e| em.slot_for_entity(e));
+    if let Some(s) = old_slot {
+        release(&mut em, s, kind, entity);
+    }
+    let Some(claimed) = em.try_claim_worksite(
+        building_slot,
+        kind.building(),
+        u32::try_from(town_idx).ok(),
+        kind.capacity(),
+        claimer,
+    ) else {
+        let reason = em.last_error().unwrap_or("claim failed");
+        // Put the old reservation back so a failed reassignment is a no-op
+        if let Some(s) = old_slot {
+            if let Some(old_kind) = em.get_instance(s).map(|i| i.kind) {
+                em.try_claim_worksite(s, old_kind, None, i32::MAX, claimer);
+            }
+        }
+        return Err(reason.into());
+    };
+    let building = em
+        .entities
+        .get(&claimed.slot)
+        .copied()
+        .ok_or("building has no entity")?;
+
+    match kind {
+        AssignmentKind::Farm => {
+            if let Some(mut ws) = world.get_mut::<NpcWorkState>(entity) {
+                ws.worksite = Some(building);
+            }
+            // Drop whatever it was doing at the old farm; decision_system re-plans toward the pin
+            if let Some(mut activity) = world.get_mut::<Activity>(entity) {
+                *activity = Activity::default();
+            }
+            world.entity_mut(entity).insert(AssignedFarm(building));
+        }
+        AssignmentKind::Bed => {
+            let prev_home = world
+                .get::<AssignedBed>(entity)
+                .map(|b| b.prev_home)
+                .or_else(|| world.get::<Home>(entity).map(|h| h.0))
+                .unwrap_or(Vec2::splat(-1.0));
+            world.entity_mut(entity).insert((
+                AssignedBed {
+                    bed: building,
+                    prev_home,
+                },
+                Home(claimed.position),
+            ));
+        }
+        AssignmentKind::Post => {
+            world.entity_mut(entity).insert((
+                AssignedPost(building),
+                PatrolRoute {
+                    posts: vec![claimed.position],
+                    current: 0,
+                },
+            ));
+        }
+    }
+    Ok(claimed.position)
+}
+
+/// Clear the NPC's manual `kind` assignment and release its reservation. Farmers go back to
+/// picking the best free farm, beds restore the previous home, post holders rejoin the town's
+/// patrol route. Returns false if there was no such assignment.
+pub fn clear_assignment(
+    world: &mut World,
+    npc_slot: usize,
+    kind: AssignmentKind,
+) -> Result<bool, String> {
+    let (entity, ..) = live_npc(world, npc_slot)?;
+    let held = match kind {
+        AssignmentKind::Farm => world.entity_mut(entity).take::<AssignedFarm>().map(|a| a.0),
+        AssignmentKind::Bed => {
+            let bed = world.entity_mut(entity).take::<AssignedBed>();
+            if let Some(b) = bed {
+                world.entity_mut(entity).insert(Home(b.prev_home));
+            }
+            bed.map(|b| b.bed)
+        }
+        AssignmentKind::Post => world.entity_mut(entity).take::<AssignedPost>().map(|p| p.0),
+    };
+    let Some(held) = held else {
+        return Ok(false);
+    };
+    // A farm is only reserved while it is still the worksite
+    let reserved = kind != AssignmentKind::Farm
+        || world
+            .get::<NpcWorkState>(entity)
+            .is_some_and(|ws| ws.worksite == Some(held));
+    if reserved {
+        let mut em = world.resource_mut::<EntityMap>();
+        if let Some(slot) = em.slot_for_entity(held) {
+            release(&mut em, slot, kind, entity);
+        }
+    }
+    match kind {
+        AssignmentKind::Farm if reserved => {
+            if let Some(mut ws) = world.get_mut::<NpcWorkState>(entity) {
+                ws.worksite = None;
+            }
+            if let Some(mut activity) = world.get_mut::<Activity>(entity) {
+                *activity = Activity::default();
+            }
+        }
+        AssignmentKind::Post => {
+            world
+                .resource_mut::<Messages<PatrolsDirtyMsg>>()
+                .write(PatrolsDirtyMsg);
+        }
+        _ => {}
+    }
+    Ok(true)
+}
+
+/// Guard post coverage for one town.
+#[derive(Debug, Default, PartialEq)]
+pub struct PostCoverage {
+    /// (waypoint slot, position, pinned guard slot), by waypoint slot.
+    pub posts: Vec<(usize, Vec2, Option<usize>)>,
+    /// Live patrol units with no post pin; they walk the whole route.
+    pub roaming: Vec<usize>,
+    /// Guards pinned to a post that no longer exists.
+    pub orphaned: Vec<usize>,
+}
+
+impl PostCoverage {
+    /// Posts nobody covers: no pinned guard and no roaming guard walking the route.
+    pub fn uncovered(&self) -> Vec<usize> {
+        if !self.roaming.is_empty() {
+            return Vec::new();
+        }
+        self.unpinned().map(|(slot, _)| slot).collect()
+    }
+
+    fn unpinned(&self) -> impl Iterator<Item = (usize, Vec2)> + '_ {
+        self.posts
+            .iter()
+            .filter(|p| p.2.is_none())
+            .map(|&(slot, pos, _)| (slot, pos))
+    }
+}
+
+/// Snapshot of `town_idx`'s posts and patrol units.
+pub fn post_coverage(world: &World, town_idx: i32) -> PostCoverage {
+    let em = world.resource::<EntityMap>();
+    let mut posts: Vec<(usize, Vec2, Option<usize>)> = u32::try_from(town_idx)
+        .map(|t| {
+            em.iter_kind_for_town(BuildingKind::Waypoint, t)
+                .map(|inst| (inst.slot, inst.position, None))
+                .collect()
+        })
+        .unwrap_or_default();
+    posts.sort_unstable_by_key(|p| p.0);
+    let mut guards: Vec<(usize, Entity)> = em
+        .npcs_for_town(town_idx)
+        .filter(|n| !n.dead && n.job.is_patrol_unit())
+        .map(|n| (n.slot, n.entity))
+        .collect();
+    guards.sort_unstable_by_key(|g| g.0);
+
+    let mut coverage = PostCoverage::default();
+    for (slot, entity) in guards {
+        let Some(post) = world.get::<AssignedPost>(entity) else {
+            coverage.roaming.push(slot);
+            continue;
+        };
+        let post_slot = em.slot_for_entity(post.0);
+        match posts.iter_mut().find(|p| Some(p.0) == post_slot) {
+            Some(p) => p.2 = Some(slot),
+            None => coverage.orphaned.push(slot),
+        }
+    }
+    coverage.posts = posts;
+    coverage
+}
+
+fn guard_pos(world: &World, slot: usize) -> Option<Vec2> {
+    let positions = &world.resource::<GpuReadState>().positions;
+    positions
+        .get(slot * 2..slot * 2 + 2)
+        .map(|p| Vec2::new(p[0], p[1]))
+        .filter(|p| p.x > -9000.0)
+}
+
+/// Pin free (roaming) guards of `town_idx` to posts without a pinned guard, nearest pair
+/// first. When there are fewer free guards than free posts, one guard keeps roaming the
+/// route so the posts nobody is pinned to stay covered. Extra guards keep roaming too.
+/// Returns the (guard slot, post slot) pins made.
+pub fn auto_assign_guards(world: &mut World, town_idx: i32) -> Vec<(usize, usize)> {
+    let coverage = post_coverage(world, town_idx);
+    let mut posts: Vec<(usize, Vec2)> = coverage.unpinned().collect();
+    let mut guards: Vec<(usize, Option<Vec2>)> = coverage
+        .roaming
+        .iter()
+        .map(|&slot| (slot, guard_pos(world, slot)))
+        .collect();
+    let budget = if guards.len() >= posts.len() {
+        posts.len()
+    } else {
+        guards.len().saturating_sub(1)
+    };
+    let mut pinned = Vec::new();
+    while pinned.len() < budget && !posts.is_empty() && !guards.is_empty() {
+        // Closest remaining pair; slot order breaks ties so the result is deterministic
+        let mut best = (f32::MAX, 0, 0);
+        for (gi, &(_, gpos)) in guards.iter().enumerate() {
+            for (pi, &(_, ppos)) in posts.iter().enumerate() {
+                let d2 = gpos.map_or(0.0, |g| g.distance_squared(ppos));
+                if d2 < best.0 {
+                    best = (d2, gi, pi);
+                }
+            }
+        }
+        let (_, gi, pi) = best;
+        let (post_slot, _) = posts.remove(pi);
+        let guard_slot = guards[gi].0;
+        if assign_npc(world, guard_slot, post_slot, AssignmentKind::Post).is_ok() {
+            guards.remove(gi);
+            pinned.push((guard_slot, post_slot));
+        }
+    }
+    pinned
+}
+
+/// Move guards whose post was removed to the nearest post without a pinned guard, or back
+/// onto the town patrol route if every remaining post is held. Returns how many moved.
+pub fn reassign_orphaned_guards(world: &mut World, town_idx: i32) -> usize {
+    let coverage = post_coverage(world, town_idx);
+    let mut posts: Vec<(usize, Vec2)> = coverage.unpinned().collect();
+    for &slot in &coverage.orphaned {
+        let from = guard_pos(world, slot);
+        let nearest = posts
+            .iter()
+            .enumerate()
+            .min_by(|a, b| {
+                let da = from.map_or(0.0, |g| g.distance_squared(a.1.1));
+                let db = from.map_or(0.0, |g| g.distance_squared(b.1.1));
+                da.total_cmp(&db)
+            })
+            .map(|(i, _)| i);
+        let moved = nearest.is_some_and(|i| {
+            let ok = assign_npc(world, slot, posts[i].0, AssignmentKind::Post).is_ok();
+            if ok {
+                posts.remove(i);
+            }
+            ok
+        });
+        if !moved {
+            let _ = clear_assignment(world, slot, AssignmentKind::Post);
+        }
+    }
+    coverage.orphaned.len()
+}
+
+/// On waypoint changes: rehome guards from removed posts, then auto-assign guards in towns
+/// whose policy asks for it. The work needs `&mut World`, so it runs as a queued command.
+pub fn guard_post_system(
+    mut patrols_dirty: MessageReader<PatrolsDirtyMsg>,
+    mut commands: Commands,
+) {
+    if patrols_dirty.read().count() == 0 {
+        return;
+    }
+    commands.queue(|world: &mut World| {
+        let towns = world.resource::<crate::world::WorldData>().towns.len() as i32;
+        for town_idx in 0..towns {
+            reassign_orphaned_guards(world, town_idx);
+            let auto = world
+                .resource::<crate::resources::TownIndex>()
+                .0
+                .get(&town_idx)
+                .and_then(|&e| world.get::<TownPolicy>(e))
+                .is_some_and(|p| p.0.auto_assign_guards);
+            if auto {
+                auto_assign_guards(world, town_idx);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::BuildingInstance;
+
+    fn setup() -> (World, Entity) {
+        let mut world = World::new();
+        world.init_resource::<Messages<PatrolsDirtyMsg>>();
+        let mut em = EntityMap::default();
+        for (slot, kind, x) in [
+            (10, BuildingKind::Bed, 0.0),
+            (11, BuildingKind::Bed, 64.0),
+            (12, BuildingKind::Farm, 128.0),
+        ] {
+            em.add_instance(BuildingInstance {
+                kind,
+                position: Vec2::new(x + 32.0, 32.0),
+                slot,
+                town_idx: 0,
+                faction: 1,
+            });
+            let e = world.spawn(Building { kind }).id();
+            em.set_entity(slot, e);
+        }
+        let npc = world
+            .spawn((
+                GpuSlot(0),
+                Home(Vec2::new(500.0, 500.0)),
+                NpcWorkState::default(),
+                Activity::default(),
+            ))
+            .id();
+        em.register_npc(0, npc, Job::Farmer, 1, 0);
+        world.insert_resource(em);
+        (world, npc)
+    }
+
+    #[test]
+    fn reassigning_moves_the_reservation() {
+        let (mut world, npc) = setup();
+        assign_npc(&mut world, 0, 10, AssignmentKind::Bed).unwrap();
+        assign_npc(&mut world, 0, 11, AssignmentKind::Bed).unwrap();
+        let em = world.resource::<EntityMap>();
+        assert_eq!(em.occupant_count(10), 0, "old bed released");
+        assert_eq!(em.occupant_count(11), 1);
+        assert_eq!(world.get::<Home>(npc).unwrap().0, Vec2::new(96.0, 32.0));
+
+        assert!(
+            assign_npc(&mut world, 0, 12, AssignmentKind::Bed).is_err(),
+            "a farm is not a bed"
+        );
+        assert_eq!(
+            world.resource::<EntityMap>().occupant_count(11),
+            1,
+            "failed reassignment keeps the old bed"
+        );
+
+        assert!(clear_assignment(&mut world, 0, AssignmentKind::Bed).unwrap());
+        assert_eq!(world.resource::<EntityMap>().occupant_count(11), 0);
+        assert_eq!(world.get::<Home>(npc).unwrap().0, Vec2::new(500.0, 500.0));
+        assert!(!clear_assignment(&mut world, 0, AssignmentKind::Bed).unwrap());
+    }
+
+    #[test]
+    fn farm_assignment_sets_worksite_and_pin() {
+        let (mut world, npc) = setup();
+        assign_npc(&mut world, 0, 12, AssignmentKind::Farm).unwrap();
+        let farm = world.resource::<EntityMap>().entities[&12];
+        assert_eq!(world.get::<NpcWorkState>(npc).unwrap().worksite, Some(farm));
+        assert_eq!(world.get::<AssignedFarm>(npc).map(|a| a.0), Some(farm));
+        assert!(assign_npc(&mut world, 0, 12, AssignmentKind::Post).is_err());
+
+        clear_assignment(&mut world, 0, AssignmentKind::Farm).unwrap();
+        assert_eq!(world.resource::<EntityMap>().occupant_count(12), 0);
+        assert!(world.get::<NpcWorkState>(npc).unwrap().worksite.is_none());
+    }
+    /// Town 0 with posts at slots 20 (x=0), 21 (x=1000), 22 (x=2000) and archers at
+    /// slots 1 (x=1900) and 2 (x=100).
+    fn guard_setup() -> World {
+        let mut world = World::new();
+        world.init_resource::<Messages<PatrolsDirtyMsg>>();
+        let mut em = EntityMap::default();
+        for (slot, x) in [(20, 0.0), (21, 1000.0), (22, 2000.0)] {
+            em.add_instance(BuildingInstance {
+                kind: BuildingKind::Waypoint,
+                position: Vec2::new(x, 0.0),
+                slot,
+                town_idx: 0,
+                faction: 1,
+            });
+            let e = world
+                .spawn(Building {
+                    kind: BuildingKind::Waypoint,
+                })
+                .id();
+            em.set_entity(slot, e);
+        }
+        let mut positions = vec![-9999.0; 6];
+        for (slot, x) in [(1, 1900.0), (2, 100.0)] {
+            let e = world.spawn(GpuSlot(slot)).id();
+            em.register_npc(slot, e, Job::Archer, 1, 0);
+            positions[slot * 2] = x;
+            positions[slot * 2 + 1] = 0.0;
+        }
+        world.insert_resource(em);
+        world.insert_resource(GpuReadState {
+            positions,
+            ..Default::default()
+        });
+        world
+    }
+
+    #[test]
+    fn auto_assign_keeps_a_roamer_until_every_post_is_held() {
+        let mut world = guard_setup();
+        // Roaming guards walk every post, so nothing is uncovered yet
+        assert!(post_coverage(&world, 0).uncovered().is_empty());
+
+        // Two guards, three posts: pin the nearest pair, the other guard keeps roaming
+        let pinned = auto_assign_guards(&mut world, 0);
+        assert_eq!(pinned, vec![(1, 22)]);
+        let coverage = post_coverage(&world, 0);
+        assert_eq!(coverage.roaming, vec![2]);
+        assert!(coverage.uncovered().is_empty(), "coverage never drops");
+        assert!(
+            auto_assign_guards(&mut world, 0).is_empty(),
+            "the last free guard stays on the route"
+        );
+
+        // A third guard means every free post can be held
+        let e = world.spawn(GpuSlot(0)).id();
+        world
+            .resource_mut::<EntityMap>()
+            .register_npc(0, e, Job::Archer, 1, 0);
+        world.resource_mut::<GpuReadState>().positions[0] = 1000.0;
+        world.resource_mut::<GpuReadState>().positions[1] = 0.0;
+        let pinned = auto_assign_guards(&mut world, 0);
+        assert_eq!(pinned, vec![(0, 21), (2, 20)]);
+        let coverage = post_coverage(&world, 0);
+        assert!(coverage.roaming.is_empty());
+        assert!(coverage.posts.iter().all(|p| p.2.is_some()));
+        assert!(coverage.uncovered().is_empty(), "coverage never drops");
+    }
+
+    #[test]
+    fn removed_post_moves_its_guard_to_a_free_post() {
+        let mut world = guard_setup();
+        assign_npc(&mut world, 1, 22, AssignmentKind::Post).unwrap();
+        assign_npc(&mut world, 2, 20, AssignmentKind::Post).unwrap();
+
+        world.resource_mut::<EntityMap>().remove_by_slot(22);
+        assert_eq!(post_coverage(&world, 0).orphaned, vec![1]);
+        assert_eq!(reassign_orphaned_guards(&mut world, 0), 1);
+        let coverage = post_coverage(&world, 0);
+        assert!(coverage.orphaned.is_empty());
+        assert_eq!(coverage.posts[1].2, Some(1), "guard took the free post");
+
+        // With every remaining post held, an orphan goes back to roaming
+        world.resource_mut::<EntityMap>().remove_by_slot(21);
+        reassign_orphaned_guards(&mut world, 0);
+        let coverage = post_coverage(&world, 0);
+        assert_eq!(coverage.roaming, vec![1]);
+        assert_eq!(coverage.posts.len(), 1);
+    }
+}
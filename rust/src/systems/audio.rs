@@ -1,10 +1,9 @@
 //! Audio systems — music jukebox and SFX playback.
 
-use crate::resources::{GameAudio, MusicTrack, PlaySfxMsg, SfxKind};
+use crate::resources::{GameAudio, MusicTrack, PlaySfxMsg, SfxKind, SfxVoice};
 use bevy::audio::Volume;
 use bevy::prelude::*;
 use rand::Rng;
-use std::collections::HashSet;
 
 /// All music track paths (embedded in release binary via bevy_embedded_assets).
 /// Add/remove entries here when the soundtrack changes.
@@ -155,15 +154,46 @@ pub fn load_sfx(mut audio: ResMut<GameAudio>, server: Res<AssetServer>) {
     );
 }
 
-/// Play SFX on message receipt — random variant, max 1 per SfxKind per frame, camera-culled.
+/// An SFX request that survived culling. `distance` is from the camera center (0 when the
+/// request has no position — UI cues always count as close).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SfxCandidate {
+    pub kind: SfxKind,
+    pub distance: f32,
+}
+
+/// Coalesce to the nearest request per kind, then keep the `budget` most important (priority,
+/// then nearest). Returns the kept requests and how many were dropped.
+pub fn select_sfx(candidates: &[SfxCandidate], budget: usize) -> (Vec<SfxCandidate>, usize) {
+    let mut nearest: Vec<SfxCandidate> = Vec::new();
+    for c in candidates {
+        match nearest.iter_mut().find(|n| n.kind == c.kind) {
+            Some(n) if c.distance < n.distance => *n = *c,
+            Some(_) => {}
+            None => nearest.push(*c),
+        }
+    }
+    nearest.sort_by(|a, b| {
+        b.kind
+            .priority()
+            .cmp(&a.kind.priority())
+            .then(a.distance.total_cmp(&b.distance))
+    });
+    nearest.truncate(budget);
+    let suppressed = candidates.len() - nearest.len();
+    (nearest, suppressed)
+}
+
+/// Play SFX on message receipt — camera-culled, one per SfxKind per frame, and only as many
+/// as there are free voices (`sfx_max_voices`), most important and nearest first.
 pub fn play_sfx_system(
     mut commands: Commands,
     mut events: MessageReader<PlaySfxMsg>,
-    audio: Res<GameAudio>,
+    mut audio: ResMut<GameAudio>,
     camera_q: Query<(&Transform, &Projection), With<crate::render::MainCamera>>,
     windows: Query<&Window>,
+    voices_q: Query<(), With<SfxVoice>>,
 ) {
-    let mut played = HashSet::new();
     let cam = camera_q.single().ok();
     // Compute world-space camera info for spatial culling
     let cam_info = cam.and_then(|(transform, projection)| {
@@ -179,6 +209,7 @@ pub fn play_sfx_system(
             scale,
         ))
     });
+    let mut candidates = Vec::new();
     for event in events.read() {
         if audio.sfx_volume <= 0.0 {
             continue;
@@ -186,27 +217,28 @@ pub fn play_sfx_system(
         if matches!(event.kind, SfxKind::ArrowShoot) && !audio.sfx_shoot_enabled {
             continue;
         }
-        // Spatial cull FIRST — don't let off-screen events consume dedup slots
-        if event.position.is_some() {
-            if let Some((cam_pos, half_w, half_h, scale)) = cam_info {
-                if scale > 2.0 {
-                    continue;
-                }
-                if let Some(pos) = event.position {
-                    let margin = 0.0;
-                    if (pos.x - cam_pos.x).abs() > half_w + margin
-                        || (pos.y - cam_pos.y).abs() > half_h + margin
-                    {
-                        continue;
-                    }
-                }
+        // Spatial cull FIRST — off-screen events don't compete for voices
+        let mut distance = 0.0;
+        if let (Some(pos), Some((cam_pos, half_w, half_h, scale))) = (event.position, cam_info) {
+            if scale > 2.0 {
+                continue;
             }
+            if (pos.x - cam_pos.x).abs() > half_w || (pos.y - cam_pos.y).abs() > half_h {
+                continue;
+            }
+            distance = pos.distance(cam_pos);
         }
-        // Dedup AFTER cull — only on-screen events count
-        if !played.insert(std::mem::discriminant(&event.kind)) {
-            continue;
-        }
-        if let Some(variants) = audio.sfx_handles.get(&event.kind) {
+        candidates.push(SfxCandidate {
+            kind: event.kind,
+            distance,
+        });
+    }
+
+    let budget = audio.sfx_max_voices.saturating_sub(voices_q.iter().count());
+    let (selected, suppressed) = select_sfx(&candidates, budget);
+    audio.sfx_suppressed = suppressed;
+    for sfx in selected {
+        if let Some(variants) = audio.sfx_handles.get(&sfx.kind) {
             if variants.is_empty() {
                 continue;
             }
@@ -214,7 +246,43 @@ pub fn play_sfx_system(
             commands.spawn((
                 AudioPlayer::new(handle.clone()),
                 PlaybackSettings::DESPAWN.with_volume(Volume::Linear(audio.sfx_volume)),
+                SfxVoice,
             ));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(kind: SfxKind, distance: f32) -> SfxCandidate {
+        SfxCandidate { kind, distance }
+    }
+
+    #[test]
+    fn select_sfx_ranks_by_priority_then_distance() {
+        let requests = [
+            c(SfxKind::ArrowShoot, 10.0),
+            c(SfxKind::Death, 300.0),
+            c(SfxKind::Death, 50.0),
+            c(SfxKind::Miss, 20.0),
+            c(SfxKind::Click, 0.0),
+        ];
+        let (kept, suppressed) = select_sfx(&requests, 3);
+        assert_eq!(
+            kept,
+            vec![
+                c(SfxKind::Click, 0.0),
+                c(SfxKind::Death, 50.0),
+                c(SfxKind::Miss, 20.0)
+            ],
+            "nearest death kept, shot dropped for budget"
+        );
+        assert_eq!(suppressed, 2);
+
+        let (kept, suppressed) = select_sfx(&requests, 0);
+        assert!(kept.is_empty());
+        assert_eq!(suppressed, requests.len());
+    }
+}
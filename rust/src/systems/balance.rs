@@ -0,0 +1,329 @@
+//! Balance file — the most-tuned numbers (separation, attack cooldown/range/damage, energy
+//! rates, building costs) in one JSON file, loaded at startup and hot-reloadable mid-session
+//! via `endless/reload_balance`. Missing fields fall back to the compiled defaults; a file that
+//! fails to parse or validate is rejected whole and the current config stays in effect.
+//!
+//! `BalanceConfig` is the source of truth: `energy_system` and `update_gpu_data` read it
+//! directly, and `apply_balance_system` pushes the rest into `CombatConfig`, the building cost
+//! overrides, and every living NPC's `CachedStats` whenever it changes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{BaseAttackType, Job, NpcEquipment, NpcStats, Personality, TownId};
+use crate::constants::{
+    BUILDING_REGISTRY, ENERGY_DRAIN_PER_HOUR, ENERGY_RECOVER_PER_HOUR, SEPARATION_RADIUS,
+    SEPARATION_STRENGTH,
+};
+use crate::messages::GpuUpdateMsg;
+use crate::resources::EntityMap;
+use crate::systems::stats::{CombatConfig, level_from_xp, re_resolve_npc_stats};
+use crate::world::BuildingKind;
+
+/// Tuning values loaded from the balance file. Field names are the JSON keys.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceConfig {
+    /// GPU separation: neighbour push radius (px) and strength.
+    pub separation_radius: f32,
+    pub separation_strength: f32,
+    /// Default melee/ranged attack cooldown (s) and range (px). Jobs with their own
+    /// `attack_override` (e.g. crossbows) keep it.
+    pub melee_cooldown: f32,
+    pub melee_range: f32,
+    pub ranged_cooldown: f32,
+    pub ranged_range: f32,
+    /// Multiplier on every NPC's resolved damage.
+    pub damage_mult: f32,
+    /// Extra damage fraction for hits from directly behind (side hits get a third, capped
+    /// at `FLANK_BONUS_CAP`). 0 = no flanking.
+    pub flank_bonus: f32,
+    /// Fatigue: below `fatigue_threshold` energy, damage and attack speed drop linearly to
+    /// `1 - fatigue_penalty` at 0 energy. `combat_modifier_floor` caps how far fatigue and
+    /// supply penalties together can cut a unit's DPS. Threshold 0 = no fatigue.
+    pub fatigue_threshold: f32,
+    pub fatigue_penalty: f32,
+    pub combat_modifier_floor: f32,
+    /// Energy per game hour: regained while resting, lost while active (before stamina).
+    pub energy_recover_per_hour: f32,
+    pub energy_drain_per_hour: f32,
+    /// Food cost overrides by building kind name (e.g. `"Farm": 3`). Unlisted kinds keep
+    /// their registry cost.
+    pub building_costs: BTreeMap<String, i32>,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        let combat = CombatConfig::default();
+        let melee = combat.attacks[&BaseAttackType::Melee];
+        let ranged = combat.attacks[&BaseAttackType::Ranged];
+        Self {
+            separation_radius: SEPARATION_RADIUS,
+            separation_strength: SEPARATION_STRENGTH,
+            melee_cooldown: melee.cooldown,
+            melee_range: melee.range,
+            ranged_cooldown: ranged.cooldown,
+            ranged_range: ranged.range,
+            damage_mult: combat.damage_mult,
+            flank_bonus: combat.flank_bonus,
+            fatigue_threshold: combat.fatigue_threshold,
+            fatigue_penalty: combat.fatigue_penalty,
+            combat_modifier_floor: combat.combat_modifier_floor,
+            energy_recover_per_hour: ENERGY_RECOVER_PER_HOUR,
+            energy_drain_per_hour: ENERGY_DRAIN_PER_HOUR,
+            building_costs: BTreeMap::new(),
+        }
+    }
+}
+
+impl BalanceConfig {
+    /// Parse balance JSON. Absent fields take their defaults; unknown building names,
+    /// negative costs, and non-finite or negative numbers are rejected.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let config: Self = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let values = [
+            ("separation_radius", self.separation_radius),
+            ("separation_strength", self.separation_strength),
+            ("melee_cooldown", self.melee_cooldown),
+            ("melee_range", self.melee_range),
+            ("ranged_cooldown", self.ranged_cooldown),
+            ("ranged_range", self.ranged_range),
+            ("damage_mult", self.damage_mult),
+            ("flank_bonus", self.flank_bonus),
+            ("fatigue_threshold", self.fatigue_threshold),
+            ("fatigue_penalty", self.fatigue_penalty),
+            ("combat_modifier_floor", self.combat_modifier_floor),
+            ("energy_recover_per_hour", self.energy_recover_per_hour),
+            ("energy_drain_per_hour", self.energy_drain_per_hour),
+        ];
+        if let Some((name, _)) = values.iter().find(|(_, v)| !v.is_finite() || *v < 0.0) {
+            return Err(format!("{name} must be a non-negative number"));
+        }
+        if self.fatigue_threshold > 100.0 {
+            return Err("fatigue_threshold must be <= 100".into());
+        }
+        let fractions = [
+            ("fatigue_penalty", self.fatigue_penalty),
+            ("combat_modifier_floor", self.combat_modifier_floor),
+        ];
+        if let Some((name, _)) = fractions.iter().find(|(_, v)| *v > 1.0) {
+            return Err(format!("{name} must be <= 1"));
+        }
+        self.cost_overrides().map(|_| ())
+    }
+
+    /// `building_costs` resolved to kinds.
+    pub fn cost_overrides(&self) -> Result<Vec<(BuildingKind, i32)>, String> {
+        self.building_costs
+            .iter()
+            .map(|(name, &cost)| {
+                let kind = BUILDING_REGISTRY
+                    .iter()
+                    .find(|d| format!("{:?}", d.kind) == *name)
+                    .map(|d| d.kind)
+                    .ok_or_else(|| format!("unknown building kind {name:?}"))?;
+                if cost < 0 {
+                    return Err(format!("cost for {name} must be >= 0"));
+                }
+                Ok((kind, cost))
+            })
+            .collect()
+    }
+}
+
+/// Where the balance file lives and how the last load went.
+#[derive(Resource, Default)]
+pub struct BalanceSource {
+    /// File loaded at startup and by path-less reloads.
+    pub path: Option<PathBuf>,
+    pub last_error: Option<String>,
+}
+
+/// `$ENDLESS_BALANCE`, else `Documents/Endless/balance.json` beside the user settings.
+pub fn default_balance_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("ENDLESS_BALANCE") {
+        return Some(PathBuf::from(path));
+    }
+    let home = std::env::var("USERPROFILE")
+        .or_else(|_| std::env::var("HOME"))
+        .ok()?;
+    Some(
+        PathBuf::from(home)
+            .join("Documents")
+            .join("Endless")
+            .join("balance.json"),
+    )
+}
+
+/// Read and apply a balance file. On any failure the current config is kept, the error is
+/// logged and remembered in `BalanceSource::last_error`. `None` re-reads the current path.
+pub fn reload_balance(world: &mut World, path: Option<&Path>) -> Result<(), String> {
+    let path = match path {
+        Some(p) => p.to_path_buf(),
+        None => world
+            .resource::<BalanceSource>()
+            .path
+            .clone()
+            .ok_or_else(|| "no balance file path".to_string())?,
+    };
+    let result = std::fs::read_to_string(&path)
+        .map_err(|e| format!("{}: {e}", path.display()))
+        .and_then(|text| {
+            BalanceConfig::from_json(&text).map_err(|e| format!("{}: {e}", path.display()))
+        });
+    let mut source = world.resource_mut::<BalanceSource>();
+    source.path = Some(path.clone());
+    match result {
+        Ok(config) => {
+            source.last_error = None;
+            info!("Loaded balance config from {}", path.display());
+            // Only a real change re-resolves every NPC
+            world.resource_mut::<BalanceConfig>().set_if_neq(config);
+            Ok(())
+        }
+        Err(e) => {
+            error!("Balance config rejected, keeping current values: {e}");
+            source.last_error = Some(e.clone());
+            Err(e)
+        }
+    }
+}
+
+/// Startup: load the default balance file if one exists; otherwise run on compiled defaults.
+pub fn load_balance_system(world: &mut World) {
+    let Some(path) = default_balance_path() else {
+        return;
+    };
+    if path.exists() {
+        let _ = reload_balance(world, Some(&path));
+    } else {
+        world.resource_mut::<BalanceSource>().path = Some(path);
+    }
+}
+
+/// Push a changed `BalanceConfig` into `CombatConfig` and the building cost overrides, then
+/// re-resolve every living NPC's stats against it.
+pub fn apply_balance_system(
+    balance: Res<BalanceConfig>,
+    mut config: ResMut<CombatConfig>,
+    mut costs: ResMut<crate::resources::BuildingCosts>,
+    entity_map: Res<EntityMap>,
+    town_access: crate::systemparams::TownAccess,
+    npc_q: Query<(
+        &NpcEquipment,
+        &Job,
+        &TownId,
+        &BaseAttackType,
+        &Personality,
+        &NpcStats,
+    )>,
+    mut cached_stats_q: Query<&mut crate::components::CachedStats>,
+    mut speed_q: Query<&mut crate::components::Speed>,
+    mut health_q: Query<&mut crate::components::Health, Without<crate::components::Building>>,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+) {
+    if let Some(melee) = config.attacks.get_mut(&BaseAttackType::Melee) {
+        melee.cooldown = balance.melee_cooldown;
+        melee.range = balance.melee_range;
+    }
+    if let Some(ranged) = config.attacks.get_mut(&BaseAttackType::Ranged) {
+        ranged.cooldown = balance.ranged_cooldown;
+        ranged.range = balance.ranged_range;
+    }
+    config.damage_mult = balance.damage_mult;
+    config.flank_bonus = balance.flank_bonus.min(crate::constants::FLANK_BONUS_CAP);
+    config.fatigue_threshold = balance.fatigue_threshold;
+    config.fatigue_penalty = balance.fatigue_penalty;
+    config.combat_modifier_floor = balance.combat_modifier_floor;
+    // Validated on load
+    costs.set_overrides(balance.cost_overrides().unwrap_or_default());
+
+    let mut town_levels: HashMap<i32, Vec<u8>> = HashMap::new();
+    for npc in entity_map.iter_npcs().filter(|n| !n.dead) {
+        let Ok((equipment, job, town_id, atk_type, personality, stats)) = npc_q.get(npc.entity)
+        else {
+            continue;
+        };
+        let levels = town_levels
+            .entry(town_id.0)
+            .or_insert_with(|| town_access.upgrade_levels(town_id.0));
+        re_resolve_npc_stats(
+            npc.entity,
+            npc.slot,
+            equipment,
+            *job,
+            *atk_type,
+            town_id.0,
+            level_from_xp(stats.xp),
+            personality,
+            &config,
+            levels,
+            &mut cached_stats_q,
+            &mut speed_q,
+            &mut health_q,
+            &mut gpu_updates,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let config =
+            BalanceConfig::from_json(r#"{ "damage_mult": 1.5, "building_costs": { "Farm": 7 } }"#)
+                .unwrap();
+        assert_eq!(config.damage_mult, 1.5);
+        assert_eq!(config.separation_radius, SEPARATION_RADIUS);
+        assert_eq!(
+            config.energy_drain_per_hour,
+            BalanceConfig::default().energy_drain_per_hour
+        );
+        assert_eq!(
+            config.cost_overrides().unwrap(),
+            vec![(BuildingKind::Farm, 7)]
+        );
+        assert_eq!(
+            BalanceConfig::from_json("{}").unwrap(),
+            BalanceConfig::default()
+        );
+    }
+
+    #[test]
+    fn malformed_file_keeps_current_config() {
+        assert!(BalanceConfig::from_json("{ not json").is_err());
+        assert!(BalanceConfig::from_json(r#"{ "melee_cooldown": -1 }"#).is_err());
+        assert!(BalanceConfig::from_json(r#"{ "fatigue_penalty": 1.5 }"#).is_err());
+        assert!(BalanceConfig::from_json(r#"{ "fatigue_threshold": 150 }"#).is_err());
+        assert!(BalanceConfig::from_json(r#"{ "building_costs": { "Castle": 5 } }"#).is_err());
+
+        let mut world = World::new();
+        let mut current = BalanceConfig::default();
+        current.damage_mult = 2.0;
+        world.insert_resource(current.clone());
+        world.init_resource::<BalanceSource>();
+        let path =
+            std::env::temp_dir().join(format!("endless_balance_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{ "damage_mult": "lots" }"#).unwrap();
+        assert!(reload_balance(&mut world, Some(&path)).is_err());
+        assert_eq!(*world.resource::<BalanceConfig>(), current);
+        assert!(world.resource::<BalanceSource>().last_error.is_some());
+
+        std::fs::write(&path, r#"{ "separation_strength": 120 }"#).unwrap();
+        assert!(reload_balance(&mut world, None).is_ok());
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(world.resource::<BalanceConfig>().separation_strength, 120.0);
+        assert_eq!(world.resource::<BalanceConfig>().damage_mult, 1.0);
+        assert!(world.resource::<BalanceSource>().last_error.is_none());
+    }
+}
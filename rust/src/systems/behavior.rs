@@ -49,6 +49,8 @@ pub struct DecisionNpcState<'w, 's> {
     pub health_q: Query<'w, 's, &'static Health, Without<Building>>,
     pub cached_stats_q: Query<'w, 's, &'static CachedStats>,
     pub activity_q: Query<'w, 's, &'static mut Activity>,
+    pub reinforcing_q: Query<'w, 's, (), With<Reinforcing>>,
+    pub last_stand_q: Query<'w, 's, (), With<LastStand>>,
 }
 
 /// Extra resources for decision_system (bundled to stay under 16 params)
@@ -56,12 +58,17 @@ pub struct DecisionNpcState<'w, 's> {
 pub struct DecisionExtras<'w> {
     pub npc_logs: ResMut<'w, NpcLogCache>,
     pub combat_log: MessageWriter<'w, CombatLogMsg>,
+    pub farm_events: MessageWriter<'w, crate::messages::FarmEventMsg>,
     pub gpu_updates: MessageWriter<'w, GpuUpdateMsg>,
     pub work_intents: MessageWriter<'w, WorkIntentMsg>,
     pub damage: MessageWriter<'w, crate::messages::DamageMsg>,
     pub squad_state: Res<'w, SquadState>,
     pub selected_npc: Res<'w, SelectedNpc>,
     pub settings: Res<'w, UserSettings>,
+    pub faction_list: Res<'w, crate::resources::FactionList>,
+    pub behavior_lod: Res<'w, crate::resources::BehaviorLod>,
+    pub energy_thresholds: Res<'w, crate::resources::EnergyThresholds>,
+    pub panic: ResMut<'w, crate::resources::PanicState>,
 }
 
 /// Incrementally maintain `ReturningSet` from `Changed<Activity>`.
@@ -79,6 +86,43 @@ pub fn sync_returning_set(
     }
 }
 
+/// Drive body sprites from `SpriteTable` by (job, `SpriteState`). Only entities whose activity
+/// or combat state changed are re-resolved (all of them when the table changes), and a
+/// `SetSpriteFrame` is emitted only if the frame differs from the last one sent for that slot.
+/// Freshly spawned NPCs start from their `NpcDef.sprite`, which spawn already uploaded.
+pub fn sprite_state_system(
+    table: Res<crate::resources::SpriteTable>,
+    npc_q: Query<(&GpuSlot, &Job, Ref<Activity>, Ref<CombatState>), Without<Dead>>,
+    mut last_sent: Local<std::collections::HashMap<usize, (f32, f32)>>,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+) {
+    let full = table.is_changed();
+    for (slot, job, activity, combat) in &npc_q {
+        if !full && !activity.is_changed() && !combat.is_changed() {
+            continue;
+        }
+        let def = crate::constants::npc_def(*job);
+        if activity.is_added() {
+            last_sent.insert(slot.0, def.sprite);
+        }
+        let frame = table.resolve(
+            *job,
+            crate::resources::SpriteState::derive(&activity, &combat),
+        );
+        let prev = last_sent.get(&slot.0).copied().unwrap_or(def.sprite);
+        if prev == frame {
+            continue;
+        }
+        last_sent.insert(slot.0, frame);
+        gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetSpriteFrame {
+            idx: slot.0,
+            col: frame.0,
+            row: frame.1,
+            atlas: def.atlas,
+        }));
+    }
+}
+
 /// Arrival system: proximity-based delivery for Returning NPCs.
 ///
 /// When a Returning NPC is within delivery radius of home, deposit CarriedLoot and go Idle.
@@ -106,6 +150,7 @@ pub fn arrival_system(
     mut returning: ResMut<crate::resources::ReturningSet>,
     _production_q: Query<&mut ProductionState>,
     _miner_cfg_q: Query<&MinerHomeConfig>,
+    mut tribute: ResMut<crate::resources::TributeState>,
 ) {
     if game_time.is_paused() {
         return;
@@ -145,6 +190,7 @@ pub fn arrival_system(
     for (idx, entity, town_idx) in deliveries {
         // Read and drain CarriedLoot
         if let Ok(mut loot) = carried_loot_q.get_mut(entity) {
+            tribute.record_income(town_idx, loot.food, loot.gold);
             if loot.food > 0 {
                 if let Some(mut f) = economy.towns.food_mut(town_idx as i32) {
                     f.0 += loot.food;
@@ -237,7 +283,8 @@ mod tests {
             .init_resource::<crate::resources::GameTime>()
             .init_resource::<crate::resources::GpuReadState>()
             .init_resource::<crate::resources::NpcLogCache>()
-            .init_resource::<crate::resources::ReturningSet>();
+            .init_resource::<crate::resources::ReturningSet>()
+            .init_resource::<crate::resources::TributeState>();
 
         let town_entity = app
             .world_mut()
@@ -379,4 +426,74 @@ mod tests {
             "despawned NPCs should be pruned from ReturningSet"
         );
     }
+
+    // -- sprite_state_system -------------------------------------------------
+
+    #[derive(Resource, Default)]
+    struct CollectedFrames(Vec<(usize, f32, f32)>);
+
+    fn collect_frames(mut reader: MessageReader<GpuUpdateMsg>, mut out: ResMut<CollectedFrames>) {
+        for msg in reader.read() {
+            if let GpuUpdate::SetSpriteFrame { idx, col, row, .. } = msg.0 {
+                out.0.push((idx, col, row));
+            }
+        }
+    }
+
+    #[test]
+    fn sprite_state_system_emits_only_on_frame_change() {
+        use crate::resources::{SpriteState, SpriteTable};
+        let mut app = App::new();
+        app.add_message::<GpuUpdateMsg>()
+            .init_resource::<SpriteTable>()
+            .init_resource::<CollectedFrames>()
+            .add_systems(Update, (sprite_state_system, collect_frames).chain());
+        app.world_mut().resource_mut::<SpriteTable>().set(
+            Job::Farmer,
+            SpriteState::Resting,
+            Some((5.0, 7.0)),
+        );
+        let npc = app
+            .world_mut()
+            .spawn((
+                GpuSlot(3),
+                Job::Farmer,
+                Activity::new(ActivityKind::Work),
+                CombatState::default(),
+            ))
+            .id();
+
+        // Unmapped state resolves to the spawn sprite: nothing to send.
+        app.update();
+        assert!(app.world().resource::<CollectedFrames>().0.is_empty());
+
+        let rest = Activity {
+            phase: ActivityPhase::Active,
+            ..Activity::new(ActivityKind::Rest)
+        };
+        *app.world_mut().get_mut::<Activity>(npc).unwrap() = rest;
+        app.update();
+        assert_eq!(
+            app.world().resource::<CollectedFrames>().0,
+            vec![(3, 5.0, 7.0)]
+        );
+
+        // Same state touched again (e.g. tick counters) does not resend.
+        app.world_mut()
+            .get_mut::<Activity>(npc)
+            .unwrap()
+            .ticks_waiting += 1;
+        app.update();
+        assert_eq!(app.world().resource::<CollectedFrames>().0.len(), 1);
+
+        // Waking back to the default frame sends it once.
+        *app.world_mut().get_mut::<Activity>(npc).unwrap() = Activity::new(ActivityKind::Idle);
+        app.update();
+        let def = crate::constants::npc_def(Job::Farmer).sprite;
+        assert_eq!(
+            app.world().resource::<CollectedFrames>().0.last(),
+            Some(&(3, def.0, def.1))
+        );
+        assert_eq!(app.world().resource::<CollectedFrames>().0.len(), 2);
+    }
 }
@@ -0,0 +1,338 @@
+//! Town blueprints — capture a town's player-built layout and stamp it onto another town.
+//! A blueprint is versioned JSON: building kinds by name plus grid offsets from the town
+//! center. Stamping goes through the normal validated placement (cost, terrain, territory,
+//! town limits, tech gates), so a blueprint can never place what the build menu couldn't;
+//! entries that fail are skipped and reported rather than aborting the whole stamp.
+
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::building_def;
+use crate::messages::GpuUpdateMsg;
+use crate::resources::EntityMap;
+use crate::systemparams::{TownAccess, WorldState};
+use crate::world::{BuildCheck, BuildingKind, WorldData, WorldGrid, footprint_cells};
+
+pub const BLUEPRINT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlueprintEntry {
+    /// `BuildingKind` variant name, kept as a string so unknown kinds parse and skip.
+    pub kind: String,
+    /// Footprint anchor cell, relative to the town center cell.
+    pub dc: i32,
+    pub dr: i32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Blueprint {
+    pub version: u32,
+    pub buildings: Vec<BlueprintEntry>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SkippedEntry {
+    #[serde(flatten)]
+    pub entry: BlueprintEntry,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StampReport {
+    pub placed: Vec<BlueprintEntry>,
+    pub skipped: Vec<SkippedEntry>,
+}
+
+fn kind_name(kind: BuildingKind) -> String {
+    format!("{kind:?}")
+}
+
+fn parse_kind(name: &str) -> Option<BuildingKind> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+/// Parse and version-check a blueprint. Unknown kinds are left for `stamp_blueprint` to skip.
+pub fn parse_blueprint(blueprint: &str) -> Result<Blueprint, String> {
+    let bp: Blueprint =
+        serde_json::from_str(blueprint).map_err(|e| format!("bad blueprint: {e}"))?;
+    if bp.version > BLUEPRINT_VERSION {
+        return Err(format!(
+            "blueprint format v{} is newer than v{BLUEPRINT_VERSION}",
+            bp.version
+        ));
+    }
+    Ok(bp)
+}
+
+/// Capture the player-buildable buildings of `town_idx` as a blueprint string. Roads come
+/// first so wilderness buildings that depend on their buildable area stamp after them.
+pub fn save_town_blueprint(world: &World, town_idx: usize) -> Result<String, String> {
+    let town = world
+        .resource::<WorldData>()
+        .towns
+        .get(town_idx)
+        .ok_or_else(|| format!("town {town_idx} out of range"))?;
+    let grid = world.resource::<WorldGrid>();
+    let (cc, cr) = grid.world_to_grid(town.center);
+    let mut buildings: Vec<(BuildingKind, BlueprintEntry)> = world
+        .resource::<EntityMap>()
+        .iter_instances()
+        .filter(|b| b.town_idx == town_idx as u32 && building_def(b.kind).player_buildable)
+        .filter_map(|b| {
+            let (gc, gr) =
+                footprint_cells(b.position, building_def(b.kind).footprint, grid.cell_size)
+                    .next()?;
+            Some((
+                b.kind,
+                BlueprintEntry {
+                    kind: kind_name(b.kind),
+                    dc: gc - cc as i32,
+                    dr: gr - cr as i32,
+                },
+            ))
+        })
+        .collect();
+    buildings.sort_by_key(|(kind, e)| (!kind.is_road(), e.dr, e.dc));
+    let bp = Blueprint {
+        version: BLUEPRINT_VERSION,
+        buildings: buildings.into_iter().map(|(_, e)| e).collect(),
+    };
+    serde_json::to_string(&bp).map_err(|e| e.to_string())
+}
+
+/// Place a blueprint relative to `town_idx`'s center, paying each building from the town's
+/// food. Unknown kinds, out-of-bounds or occupied cells, locked or unaffordable buildings are
+/// skipped with a reason; everything else is placed in blueprint order.
+pub fn stamp_blueprint(
+    world: &mut World,
+    town_idx: usize,
+    blueprint: &str,
+) -> Result<StampReport, String> {
+    let bp = parse_blueprint(blueprint)?;
+    if town_idx >= world.resource::<WorldData>().towns.len() {
+        return Err(format!("town {town_idx} out of range"));
+    }
+
+    let mut state: SystemState<(
+        WorldState,
+        TownAccess,
+        Commands,
+        MessageWriter<GpuUpdateMsg>,
+    )> = SystemState::new(world);
+    let (mut world_state, mut town_access, mut commands, mut gpu_updates) = state.get_mut(world);
+    let center = world_state.world_data.towns[town_idx].center;
+    let (cc, cr) = world_state.grid.world_to_grid(center);
+    let levels = town_access.upgrade_levels(town_idx as i32);
+    let tech_locked = town_access.tech_locked_buildings(town_idx as i32);
+    let mut food = town_access.food(town_idx as i32);
+    let mut report = StampReport::default();
+
+    for entry in bp.buildings {
+        let skip = |entry: BlueprintEntry, reason: &str| SkippedEntry {
+            entry,
+            reason: reason.to_string(),
+        };
+        let Some(kind) = parse_kind(&entry.kind) else {
+            report.skipped.push(skip(entry, "unknown building kind"));
+            continue;
+        };
+        let (col, row) = (cc as i32 + entry.dc, cr as i32 + entry.dr);
+        if col < 0
+            || row < 0
+            || col as usize >= world_state.grid.width
+            || row as usize >= world_state.grid.height
+        {
+            report.skipped.push(skip(entry, "cell out of bounds"));
+            continue;
+        }
+        let check = BuildCheck::new(
+            town_idx,
+            &world_state.world_data.towns[town_idx],
+            food,
+            &levels,
+            &world_state.grid,
+            &world_state.entity_map,
+            &world_state.building_costs,
+        )
+        .with_tech_locks(tech_locked.clone());
+        if let Err(block) = check.check(kind, &world_state.entity_map) {
+            report.skipped.push(skip(entry, block.reason()));
+            continue;
+        }
+        let pos = world_state.grid.footprint_center(
+            col as usize,
+            row as usize,
+            building_def(kind).footprint,
+        );
+        match world_state.place_building(
+            &mut food,
+            kind,
+            town_idx,
+            pos,
+            world_state.building_costs.cost(kind),
+            &tech_locked,
+            &mut gpu_updates,
+            &mut commands,
+        ) {
+            Ok(()) => report.placed.push(entry),
+            Err(reason) => report.skipped.push(skip(entry, reason)),
+        }
+    }
+
+    if let Some(mut f) = town_access.food_mut(town_idx as i32) {
+        f.0 = food;
+    }
+    state.apply(world);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::BuildingInstance;
+    use crate::world::Town;
+
+    #[test]
+    fn blueprint_offsets_are_town_relative() {
+        let mut world = World::new();
+        let grid = WorldGrid {
+            width: 100,
+            height: 100,
+            ..Default::default()
+        };
+        let center = grid.grid_to_world(50, 50);
+        let mut em = EntityMap::default();
+        for (slot, kind, col, row, town) in [
+            (1, BuildingKind::Fountain, 50, 50, 0),
+            (2, BuildingKind::Farm, 52, 49, 0),
+            (3, BuildingKind::Road, 48, 50, 0),
+            (4, BuildingKind::Farm, 10, 10, 1),
+        ] {
+            em.add_instance(BuildingInstance {
+                kind,
+                position: grid.footprint_center(col, row, building_def(kind).footprint),
+                slot,
+                town_idx: town,
+                faction: 1,
+            });
+        }
+        world.insert_resource(em);
+        world.insert_resource(WorldData {
+            towns: vec![Town {
+                name: "Home".into(),
+                center,
+                faction: 1,
+                kind: crate::constants::TownKind::Player,
+            }],
+        });
+        world.insert_resource(grid);
+
+        let bp = parse_blueprint(&save_town_blueprint(&world, 0).unwrap()).unwrap();
+        assert_eq!(bp.version, BLUEPRINT_VERSION);
+        assert_eq!(
+            bp.buildings,
+            vec![
+                BlueprintEntry {
+                    kind: "Road".into(),
+                    dc: -2,
+                    dr: 0
+                },
+                BlueprintEntry {
+                    kind: "Farm".into(),
+                    dc: 2,
+                    dr: -1
+                },
+            ],
+            "fountain and other towns' buildings are left out, roads first"
+        );
+        assert!(parse_kind("Farm").is_some());
+        assert!(parse_kind("ModdedSilo").is_none());
+
+        let newer = format!(r#"{{"version":{},"buildings":[]}}"#, BLUEPRINT_VERSION + 1);
+        assert!(parse_blueprint(&newer).is_err());
+    }
+
+    #[test]
+    fn stamp_skips_the_blocked_cell_and_places_the_rest() {
+        use crate::components::{FoodStore, TownMarker};
+        use crate::messages::*;
+        use crate::world::{Biome, WorldCell};
+        use bevy::ecs::message::Messages;
+
+        let mut world = World::new();
+        let grid = WorldGrid {
+            width: 10,
+            height: 10,
+            cell_size: crate::constants::TOWN_GRID_SPACING,
+            cells: vec![
+                WorldCell {
+                    terrain: Biome::Grass,
+                    original_terrain: Biome::Grass,
+                };
+                100
+            ],
+            town_owner: vec![0u16; 100],
+            ..Default::default()
+        };
+        let center = grid.grid_to_world(5, 5);
+        // A farm already stands one cell east of the center
+        let mut em = EntityMap::default();
+        em.add_instance(BuildingInstance {
+            kind: BuildingKind::Farm,
+            position: grid.grid_to_world(6, 5),
+            slot: 0,
+            town_idx: 0,
+            faction: 1,
+        });
+        world.insert_resource(em);
+        let mut slots = crate::resources::GpuSlotPool::default();
+        slots.alloc_reset();
+        world.insert_resource(slots);
+        world.insert_resource(grid);
+        world.insert_resource(WorldData {
+            towns: vec![Town {
+                name: "Home".into(),
+                center,
+                faction: 1,
+                kind: crate::constants::TownKind::Player,
+            }],
+        });
+        world.init_resource::<crate::resources::NoBuildZones>();
+        world.init_resource::<crate::resources::BuildingCosts>();
+        let town = world.spawn((TownMarker, FoodStore(100))).id();
+        let mut index = crate::resources::TownIndex::default();
+        index.0.insert(0, town);
+        world.insert_resource(index);
+        world.init_resource::<Messages<GpuUpdateMsg>>();
+        world.init_resource::<Messages<BuildingGridDirtyMsg>>();
+        world.init_resource::<Messages<TerrainDirtyMsg>>();
+        world.init_resource::<Messages<PatrolsDirtyMsg>>();
+        world.init_resource::<Messages<PatrolPerimeterDirtyMsg>>();
+        world.init_resource::<Messages<HealingZonesDirtyMsg>>();
+        world.init_resource::<Messages<SquadsDirtyMsg>>();
+        world.init_resource::<Messages<MiningDirtyMsg>>();
+        world.init_resource::<Messages<PatrolSwapMsg>>();
+
+        let entry = |dc, dr| BlueprintEntry {
+            kind: "Farm".into(),
+            dc,
+            dr,
+        };
+        let bp = serde_json::to_string(&Blueprint {
+            version: BLUEPRINT_VERSION,
+            buildings: vec![entry(1, 0), entry(-1, 0), entry(0, 1)],
+        })
+        .unwrap();
+        let report = stamp_blueprint(&mut world, 0, &bp).unwrap();
+
+        assert_eq!(report.placed, vec![entry(-1, 0), entry(0, 1)]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].entry, entry(1, 0));
+        assert_eq!(report.skipped[0].reason, "cell already has a building");
+        let em = world.resource::<EntityMap>();
+        assert_eq!(em.count_for_town(BuildingKind::Farm, 0), 3);
+        let farm_cost = building_def(BuildingKind::Farm).cost;
+        assert_eq!(world.get::<FoodStore>(town).unwrap().0, 100 - 2 * farm_cost);
+    }
+}
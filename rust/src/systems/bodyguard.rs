@@ -0,0 +1,246 @@
+//! Bodyguards — units pinned to protect another NPC of their faction.
+//! `assign_bodyguard` attaches `Bodyguard(charge)`. Each tick `bodyguard_system` finds who
+//! targets each charge from the GPU combat targets and hands the nearest such attacker to the
+//! guard through `BodyguardTargets`, which attack_system prefers over the guard's own pick.
+//! Nothing past `BODYGUARD_LEASH` from the charge counts: farther targets are dropped and a
+//! guard that strays that far is walked back. With no threat the guard holds a spot
+//! `BODYGUARD_OFFSET` from its charge. Guards whose charge dies are released.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::constants::{BODYGUARD_FOLLOW_SLACK, BODYGUARD_LEASH, BODYGUARD_OFFSET};
+use crate::resources::{EntityMap, GameTime, GpuReadState, MovementPriority, PathRequestQueue};
+
+/// Per-tick target overrides for bodyguards, keyed by guard slot. -1 = stand down (the
+/// guard's own target is past the leash). Rebuilt every tick by `bodyguard_system`.
+#[derive(Resource, Default)]
+pub struct BodyguardTargets(pub HashMap<usize, i32>);
+
+/// Golden-angle bearing per guard slot, so several guards spread around one charge.
+pub fn guard_offset(slot: usize) -> Vec2 {
+    Vec2::from_angle(slot as f32 * 2.399_963) * BODYGUARD_OFFSET
+}
+
+fn slot_pos(positions: &[f32], slot: usize) -> Option<Vec2> {
+    positions
+        .get(slot * 2..slot * 2 + 2)
+        .map(|p| Vec2::new(p[0], p[1]))
+        .filter(|p| p.x > -9000.0)
+}
+
+/// The attacker a guard at `guard_pos` should take on: nearest to the guard among those
+/// within `leash` of the charge. Slot order breaks ties.
+pub fn pick_threat(
+    guard_pos: Vec2,
+    charge_pos: Vec2,
+    attackers: &[usize],
+    positions: &[f32],
+    leash: f32,
+) -> Option<usize> {
+    let mut best: Option<(f32, usize)> = None;
+    for &a in attackers {
+        let Some(pos) = slot_pos(positions, a) else {
+            continue;
+        };
+        if pos.distance(charge_pos) > leash {
+            continue;
+        }
+        let d2 = pos.distance_squared(guard_pos);
+        if best.is_none_or(|(bd, bs)| d2 < bd || (d2 == bd && a < bs)) {
+            best = Some((d2, a));
+        }
+    }
+    best.map(|(_, a)| a)
+}
+
+/// Make the NPC at `guard_slot` a bodyguard of the NPC at `protected_slot`. Both must be
+/// alive and of the same faction, the guard a military unit, and guards can't end up
+/// protecting each other in a loop. Replaces any earlier charge.
+pub fn assign_bodyguard(
+    world: &mut World,
+    guard_slot: usize,
+    protected_slot: usize,
+) -> Result<(), String> {
+    if guard_slot == protected_slot {
+        return Err("a unit can't guard itself".into());
+    }
+    let em = world.resource::<EntityMap>();
+    let live = |slot: usize| {
+        em.get_npc(slot)
+            .filter(|n| !n.dead)
+            .ok_or_else(|| format!("no live NPC at slot {slot}"))
+    };
+    let guard = live(guard_slot)?;
+    let charge = live(protected_slot)?;
+    if !guard.job.is_military() {
+        return Err("only military units can be bodyguards".into());
+    }
+    if guard.faction != charge.faction {
+        return Err("bodyguard and charge must share a faction".into());
+    }
+    let (guard_entity, charge_entity) = (guard.entity, charge.entity);
+    // Follow the charge's own guard chain; reaching the guard would be a loop
+    let mut next = Some(charge_entity);
+    while let Some(e) = next {
+        if e == guard_entity {
+            return Err("guards would protect each other in a loop".into());
+        }
+        next = world.get::<Bodyguard>(e).map(|b| b.0);
+    }
+    world
+        .entity_mut(guard_entity)
+        .insert(Bodyguard(charge_entity));
+    Ok(())
+}
+
+/// Release the NPC at `guard_slot` from bodyguard duty. Returns false if it had no charge.
+pub fn clear_bodyguard(world: &mut World, guard_slot: usize) -> Result<bool, String> {
+    let entity = world
+        .resource::<EntityMap>()
+        .get_npc(guard_slot)
+        .filter(|n| !n.dead)
+        .map(|n| n.entity)
+        .ok_or_else(|| format!("no live NPC at slot {guard_slot}"))?;
+    Ok(world.entity_mut(entity).take::<Bodyguard>().is_some())
+}
+
+/// Point bodyguards at whoever threatens their charge, keep them within the leash and walk
+/// them alongside the charge. Runs before attack_system, so its intents win ties.
+pub fn bodyguard_system(
+    mut commands: Commands,
+    mut intents: ResMut<PathRequestQueue>,
+    mut targets: ResMut<BodyguardTargets>,
+    gpu_state: Res<GpuReadState>,
+    game_time: Res<GameTime>,
+    guard_q: Query<(Entity, &GpuSlot, &Bodyguard), (Without<Building>, Without<Dead>)>,
+    charge_q: Query<&GpuSlot, (Without<Building>, Without<Dead>)>,
+    mut guards: Local<Vec<(Entity, usize, usize)>>,
+    mut attackers: Local<HashMap<usize, Vec<usize>>>,
+) {
+    targets.0.clear();
+    if game_time.is_paused() {
+        return;
+    }
+    guards.clear();
+    for (entity, slot, bodyguard) in guard_q.iter() {
+        match charge_q.get(bodyguard.0) {
+            Ok(charge) => guards.push((entity, slot.0, charge.0)),
+            // Charge died or despawned: back to normal duty
+            Err(_) => {
+                commands.entity(entity).remove::<Bodyguard>();
+            }
+        }
+    }
+    if guards.is_empty() {
+        return;
+    }
+
+    // Who targets whom, for the charges only
+    let positions = &gpu_state.positions;
+    let combat_targets = &gpu_state.combat_targets;
+    let charges: HashSet<usize> = guards.iter().map(|g| g.2).collect();
+    attackers.values_mut().for_each(Vec::clear);
+    let n = gpu_state.npc_count.min(combat_targets.len());
+    for (j, &t) in combat_targets[..n].iter().enumerate() {
+        if t >= 0 && charges.contains(&(t as usize)) {
+            attackers.entry(t as usize).or_default().push(j);
+        }
+    }
+
+    for &(entity, slot, charge) in guards.iter() {
+        let (Some(guard_pos), Some(charge_pos)) =
+            (slot_pos(positions, slot), slot_pos(positions, charge))
+        else {
+            continue;
+        };
+        let threat = attackers
+            .get(&charge)
+            .and_then(|a| pick_threat(guard_pos, charge_pos, a, positions, BODYGUARD_LEASH));
+        match threat {
+            Some(a) => {
+                targets.0.insert(slot, a as i32);
+            }
+            None => {
+                let own = combat_targets.get(slot).copied().unwrap_or(-1);
+                let too_far = own >= 0
+                    && slot_pos(positions, own as usize)
+                        .is_none_or(|p| p.distance(charge_pos) > BODYGUARD_LEASH);
+                if too_far {
+                    targets.0.insert(slot, -1);
+                }
+            }
+        }
+
+        let spot = charge_pos + guard_offset(slot);
+        if guard_pos.distance(charge_pos) > BODYGUARD_LEASH {
+            // Strayed past the leash: beats the combat chase submitted later this tick
+            intents.submit(entity, spot, MovementPriority::Combat, "bodyguard:leash");
+        } else if threat.is_none() && guard_pos.distance(spot) > BODYGUARD_FOLLOW_SLACK {
+            intents.submit(entity, spot, MovementPriority::Squad, "bodyguard:follow");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threat_is_nearest_attacker_inside_the_leash() {
+        // Slots 0-3 at x = 0, 100, 200, 1000
+        let positions = [0.0, 0.0, 100.0, 0.0, 200.0, 0.0, 1000.0, 0.0];
+        let charge = Vec2::ZERO;
+        assert_eq!(
+            pick_threat(Vec2::new(150.0, 0.0), charge, &[1, 2, 3], &positions, 300.0),
+            Some(1),
+            "equally near: the lower slot wins"
+        );
+        assert_eq!(
+            pick_threat(Vec2::new(160.0, 0.0), charge, &[3, 1, 2], &positions, 300.0),
+            Some(2)
+        );
+        assert_eq!(
+            pick_threat(Vec2::new(900.0, 0.0), charge, &[3], &positions, 300.0),
+            None,
+            "an attacker past the leash is left alone"
+        );
+        assert_ne!(guard_offset(1), guard_offset(2));
+        assert!((guard_offset(7).length() - BODYGUARD_OFFSET).abs() < 1e-3);
+    }
+
+    #[test]
+    fn assignment_checks_faction_job_and_loops() {
+        let mut world = World::new();
+        let mut em = EntityMap::default();
+        for (slot, job, faction) in [
+            (0, Job::Archer, 1),
+            (1, Job::Fighter, 1),
+            (2, Job::Farmer, 1),
+            (3, Job::Archer, 2),
+        ] {
+            let e = world.spawn(GpuSlot(slot)).id();
+            em.register_npc(slot, e, job, faction, 0);
+        }
+        world.insert_resource(em);
+
+        assert!(assign_bodyguard(&mut world, 0, 0).is_err());
+        assert!(
+            assign_bodyguard(&mut world, 2, 0).is_err(),
+            "farmers can't guard"
+        );
+        assert!(assign_bodyguard(&mut world, 3, 0).is_err(), "other faction");
+        assign_bodyguard(&mut world, 0, 2).unwrap();
+        assign_bodyguard(&mut world, 1, 0).unwrap();
+        assert!(
+            assign_bodyguard(&mut world, 0, 1).is_err(),
+            "1 already guards 0"
+        );
+
+        assert_eq!(clear_bodyguard(&mut world, 1), Ok(true));
+        assert_eq!(clear_bodyguard(&mut world, 1), Ok(false));
+        assign_bodyguard(&mut world, 0, 1).unwrap();
+    }
+}
@@ -2,16 +2,22 @@
 
 use crate::components::*;
 use crate::gpu::ProjBufferWrites;
-use crate::messages::{DamageMsg, ProjGpuUpdate, ProjGpuUpdateMsg};
+use crate::messages::{
+    DamageMsg, GpuUpdate, GpuUpdateMsg, ProjGpuUpdate, ProjGpuUpdateMsg, ProjKind,
+};
 use crate::resources::{
-    CombatDebug, EntityMap, GameTime, GpuReadState, MovementPriority, PathRequestQueue,
-    ProjHitState, ProjSlotAllocator, TowerState,
+    AggroMemoryConfig, AttackRoll, CombatDebug, CombatRng, CombatSlot, DebugFlags, EntityMap,
+    GameTime, GpuReadState, LeadTargeting, MovementPriority, NpcFacing, NpcVelocities,
+    PathRequestQueue, ProjHitState, ProjSlotAllocator, TargetStickiness, TowerState,
 };
-use crate::systems::stats::resolve_town_tower_stats;
+use crate::systems::stats::{CombatConfig, level_from_xp, resolve_town_tower_stats};
 use crate::world::{BuildingKind, WorldData, is_alive};
 use bevy::prelude::*;
 
-/// Fire a projectile from source toward target. Returns true if fired.
+/// Fire a projectile from source toward target. Returns true if fired; false when point-blank,
+/// rate-limited, or the pool is full (callers fall back to direct damage or retry).
+/// An arced `arc` lobs the shot (`ballistic_launch`) to come down on `target_pos`, or as far
+/// toward it as `proj_speed` reaches; its lifetime becomes the flight time.
 fn fire_projectile(
     src: Vec2,
     target_pos: Vec2,
@@ -21,6 +27,9 @@ fn fire_projectile(
     faction: i32,
     shooter: i32,
     homing_target: i32,
+    kind: ProjKind,
+    arc: ProjArc,
+    now: f32,
     proj_alloc: &mut ProjSlotAllocator,
     proj_updates: &mut MessageWriter<ProjGpuUpdateMsg>,
     sfx_writer: &mut MessageWriter<crate::resources::PlaySfxMsg>,
@@ -30,19 +39,34 @@ fn fire_projectile(
     if dist <= 1.0 {
         return false;
     }
-    if let Some(proj_slot) = proj_alloc.alloc() {
+    if let Some(slot) = proj_alloc.alloc_combat(shooter, now) {
+        let proj_slot = slot.idx();
+        if let CombatSlot::Evicted(idx) = slot {
+            proj_updates.write(ProjGpuUpdateMsg(ProjGpuUpdate::Deactivate { idx }));
+        }
         let dir = delta / dist;
+        let (ground_speed, vz, lifetime) = match ballistic_launch(dist, proj_speed, arc.gravity) {
+            Some(shot) => (
+                shot.ground_speed,
+                shot.vz,
+                shot.flight_time + crate::constants::PROJ_ARC_LAND_MARGIN,
+            ),
+            None => (proj_speed, 0.0, lifetime),
+        };
         proj_updates.write(ProjGpuUpdateMsg(ProjGpuUpdate::Spawn {
             idx: proj_slot,
             x: src.x,
             y: src.y,
-            vx: dir.x * proj_speed,
-            vy: dir.y * proj_speed,
+            vx: dir.x * ground_speed,
+            vy: dir.y * ground_speed,
             damage,
             faction,
             shooter,
             lifetime,
             homing_target,
+            kind,
+            arc,
+            vz,
         }));
         sfx_writer.write(crate::resources::PlaySfxMsg {
             kind: crate::resources::SfxKind::ArrowShoot,
@@ -53,6 +77,81 @@ fn fire_projectile(
     false
 }
 
+/// Where to aim so a projectile at `proj_speed` from `src` meets a target at `target_pos`
+/// moving at `target_vel`. Falls back to `target_pos` when the target is effectively still
+/// (`LEAD_MIN_SPEED`), outruns the projectile, or would be met after `max_time`.
+pub fn lead_intercept(
+    src: Vec2,
+    target_pos: Vec2,
+    target_vel: Vec2,
+    proj_speed: f32,
+    max_time: f32,
+) -> Vec2 {
+    if target_vel.length() < crate::constants::LEAD_MIN_SPEED || proj_speed <= 0.0 {
+        return target_pos;
+    }
+    // |d + v*t| = s*t  =>  (v.v - s^2) t^2 + 2 (d.v) t + d.d = 0
+    let d = target_pos - src;
+    let a = target_vel.length_squared() - proj_speed * proj_speed;
+    let b = 2.0 * d.dot(target_vel);
+    let c = d.length_squared();
+    let t = if a.abs() < 1e-3 {
+        // Equal speeds: one root, only ahead when the target is closing
+        if b < 0.0 { -c / b } else { -1.0 }
+    } else {
+        let disc = b * b - 4.0 * a * c;
+        if disc < 0.0 {
+            -1.0
+        } else {
+            let root = disc.sqrt();
+            let (t1, t2) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+            let (lo, hi) = (t1.min(t2), t1.max(t2));
+            if lo > 0.0 { lo } else { hi }
+        }
+    };
+    if t <= 0.0 || t > max_time {
+        return target_pos;
+    }
+    target_pos + target_vel * t
+}
+
+/// Launch of a lobbed shot: split into speed along the ground and upward, plus air time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BallisticShot {
+    pub ground_speed: f32,
+    pub vz: f32,
+    pub flight_time: f32,
+    /// Ground distance covered: the requested distance, or the max range when that's short.
+    pub range: f32,
+}
+
+/// Solve the launch angle for a shot at `speed` under `gravity` to land `dist` away. Of the
+/// two angles that reach, takes the steep one, so shells drop onto the target like a mortar.
+/// Past the max range (`speed² / gravity`) fires at 45° and falls short. None when
+/// `gravity` or `speed` isn't positive (a flat shot).
+pub fn ballistic_launch(dist: f32, speed: f32, gravity: f32) -> Option<BallisticShot> {
+    if !(gravity > 0.0 && speed > 0.0 && gravity.is_finite() && speed.is_finite()) {
+        return None;
+    }
+    let max_range = speed * speed / gravity;
+    // range = v² sin(2θ) / g; the steep root is θ = 90° - asin(g·d / v²) / 2
+    let reach = if dist.is_finite() {
+        (dist / max_range).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let angle = std::f32::consts::FRAC_PI_2 - 0.5 * reach.asin();
+    let (sin, cos) = angle.sin_cos();
+    let (ground_speed, vz) = ((speed * cos).max(0.0), speed * sin);
+    let flight_time = 2.0 * vz / gravity;
+    Some(BallisticShot {
+        ground_speed,
+        vz,
+        flight_time,
+        range: ground_speed * flight_time,
+    })
+}
+
 pub(crate) fn fire_loot_fly(
     src: Vec2,
     killer_pos: Vec2,
@@ -76,6 +175,9 @@ pub(crate) fn fire_loot_fly(
             shooter: -1,
             lifetime: 1.5,
             homing_target: target_slot as i32,
+            kind: ProjKind::Loot,
+            arc: ProjArc::default(),
+            vz: 0.0,
         }));
     }
 }
@@ -85,6 +187,521 @@ pub(crate) fn fire_loot_fly(
 pub struct AttackQueries<'w, 's> {
     pub combat_state_q: Query<'w, 's, &'static mut CombatState>,
     pub timer_q: Query<'w, 's, &'static mut AttackTimer>,
+    pub windup_q: Query<'w, 's, (&'static AttackWindup, &'static BaseAttackType)>,
+    pub attacking_q: Query<'w, 's, (Entity, &'static mut Attacking)>,
+    pub config: Res<'w, CombatConfig>,
+    pub time: Res<'w, Time>,
+    pub combat_rng: ResMut<'w, CombatRng>,
+    pub personality_q: Query<'w, 's, &'static Personality>,
+    pub flags: Res<'w, DebugFlags>,
+    pub aggro: Res<'w, AggroMemoryConfig>,
+    pub aggro_q: Query<'w, 's, &'static mut AggroMemory>,
+    pub leash_q: Query<'w, 's, &'static LeashRange>,
+    pub engage_q: Query<'w, 's, &'static EngageZone>,
+    pub stickiness: Res<'w, TargetStickiness>,
+    pub commit_q: Query<'w, 's, &'static mut TargetCommit>,
+    pub lead: Res<'w, LeadTargeting>,
+    pub velocities: Res<'w, NpcVelocities>,
+    pub backend: Res<'w, crate::resources::ComputeBackend>,
+    pub anim: ResMut<'w, crate::resources::AttackAnimOutbox>,
+    pub anim_writer: MessageWriter<'w, crate::messages::AttackAnimMsg>,
+    pub bodyguard: Res<'w, crate::systems::bodyguard::BodyguardTargets>,
+    pub arc_q: Query<'w, 's, &'static ProjArc>,
+    pub camera_q:
+        Query<'w, 's, (&'static Transform, &'static Projection), With<crate::render::MainCamera>>,
+}
+
+/// World rect the main camera shows, grown by `ATTACK_ANIM_VIEW_MARGIN`. None without an
+/// orthographic camera (headless): every attacker counts as on screen.
+fn attack_anim_view(aq: &AttackQueries) -> Option<Rect> {
+    let (transform, projection) = aq.camera_q.single().ok()?;
+    let Projection::Orthographic(ortho) = projection else {
+        return None;
+    };
+    let center = transform.translation.truncate();
+    Some(
+        Rect::from_corners(center + ortho.area.min, center + ortho.area.max)
+            .inflate(crate::resources::ATTACK_ANIM_VIEW_MARGIN),
+    )
+}
+
+/// Animation cue for a windup step. Swings are announced when an attack starts and the
+/// attacker is on screen; an announced swing resolves as a hit (silent) or a `Whiff`.
+/// At most one cue per step: a freshly started attack can't whiff.
+pub(crate) fn attack_anim_cue(
+    outbox: &mut crate::resources::AttackAnimOutbox,
+    entity: Entity,
+    slot: usize,
+    target: usize,
+    windup: f32,
+    started: bool,
+    step: &WindupStep,
+    on_screen: bool,
+) -> Option<crate::messages::AttackAnimMsg> {
+    use crate::messages::{AttackAnimKind, AttackAnimMsg};
+    if !outbox.enabled {
+        return None;
+    }
+    match step {
+        WindupStep::Fire => {
+            outbox.swinging.remove(&entity);
+        }
+        WindupStep::Whiff => {
+            return outbox
+                .swinging
+                .remove(&entity)
+                .map(|(slot, target_slot)| AttackAnimMsg {
+                    kind: AttackAnimKind::Whiff,
+                    slot,
+                    target_slot,
+                    windup,
+                });
+        }
+        WindupStep::Hold(_) => {}
+    }
+    if !(started && on_screen) {
+        return None;
+    }
+    if matches!(step, WindupStep::Hold(_)) {
+        outbox.swinging.insert(entity, (slot, target));
+    }
+    Some(AttackAnimMsg {
+        kind: AttackAnimKind::Swing,
+        slot,
+        target_slot: target,
+        windup,
+    })
+}
+
+/// Whether a `ManualTarget::Npc` is still worth pursuing: alive and hostile to `faction`.
+/// A freed slot reused by an ally or neutral fails this too.
+pub fn manual_target_valid(entity_map: &EntityMap, target: usize, faction: i32) -> bool {
+    entity_map.get_npc(target).is_some_and(|n| {
+        !n.dead && n.faction != faction && n.faction != crate::constants::FACTION_NEUTRAL
+    })
+}
+
+/// Where a unit that lost its target should head from aggro memory. None = memory spent,
+/// or the last-known position lies past the leash from where the fight started.
+pub fn aggro_chase(origin: Vec2, memory: &AggroMemory, leash: Option<f32>) -> Option<Vec2> {
+    if memory.remaining <= 0.0 || leash.is_some_and(|l| memory.last_pos.distance(origin) > l) {
+        return None;
+    }
+    Some(memory.last_pos)
+}
+
+/// Apply CombatRng variance to one attack: attacker Sharpshot raises crit, target Swift
+/// raises dodge. Crits and misses emit a marker on the SFX bus at the target.
+/// Returns the damage to deal (0 = miss).
+fn roll_attack_damage(
+    aq: &mut AttackQueries,
+    sfx_writer: &mut MessageWriter<crate::resources::PlaySfxMsg>,
+    attacker: Entity,
+    target: Option<Entity>,
+    damage: f32,
+    target_pos: Vec2,
+) -> f32 {
+    if !aq.combat_rng.enabled() {
+        return damage;
+    }
+    let precision = aq
+        .personality_q
+        .get(attacker)
+        .map_or(0.0, |p| p.magnitude(TraitKind::Precision));
+    let agility = target
+        .and_then(|t| aq.personality_q.get(t).ok())
+        .map_or(0.0, |p| p.magnitude(TraitKind::Agility));
+    let (damage, roll) = aq.combat_rng.roll(damage, precision, agility);
+    let marker = match roll {
+        AttackRoll::Hit => None,
+        AttackRoll::Crit => Some(crate::resources::SfxKind::Crit),
+        AttackRoll::Miss => Some(crate::resources::SfxKind::Miss),
+    };
+    if let Some(kind) = marker {
+        sfx_writer.write(crate::resources::PlaySfxMsg {
+            kind,
+            position: Some(target_pos),
+        });
+    }
+    damage
+}
+
+/// Outcome of one attack_system tick for an attacker whose cooldown is ready.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum WindupStep {
+    /// Windup complete (or none): apply damage / fire projectile now.
+    Fire,
+    /// Still winding up. Only reached in range, where the caller has already submitted a
+    /// `Combat` hold-position intent, so the attacker stays put unless a higher-priority
+    /// intent (survival, manual target, direct control) moves it.
+    Hold(Attacking),
+    /// Target changed mid-windup with redirect off: swing lost, no damage.
+    Whiff,
+}
+
+/// Advance an attack windup toward `target`. `windup` is already scaled by attack speed.
+pub(crate) fn step_windup(
+    current: Option<Attacking>,
+    target: usize,
+    windup: f32,
+    dt: f32,
+    redirect: bool,
+) -> WindupStep {
+    if windup <= 0.0 {
+        return WindupStep::Fire;
+    }
+    let mut atk = current.unwrap_or(Attacking {
+        elapsed: 0.0,
+        target,
+    });
+    if atk.target != target {
+        if !redirect {
+            return WindupStep::Whiff;
+        }
+        atk.target = target;
+    }
+    atk.elapsed += dt;
+    if atk.elapsed >= windup {
+        WindupStep::Fire
+    } else {
+        WindupStep::Hold(atk)
+    }
+}
+
+/// Scaled windup for an attacker: faster attack speed (shorter cooldown) shortens it.
+fn scaled_windup(aq: &AttackQueries, entity: Entity, cooldown: f32) -> f32 {
+    let Ok((windup, attack_type)) = aq.windup_q.get(entity) else {
+        return 0.0;
+    };
+    let base = aq
+        .config
+        .attacks
+        .get(attack_type)
+        .map(|a| a.cooldown)
+        .unwrap_or(cooldown);
+    if base > 0.0 {
+        windup.0 * cooldown / base
+    } else {
+        windup.0
+    }
+}
+
+/// Run the windup state machine for `entity` and apply the result to its `Attacking`.
+/// Returns true when the attack should fire this tick.
+fn resolve_windup(
+    aq: &mut AttackQueries,
+    commands: &mut Commands,
+    winding: &mut std::collections::HashSet<Entity>,
+    entity: Entity,
+    slot: usize,
+    target: usize,
+    cooldown: f32,
+    dt: f32,
+    on_screen: bool,
+) -> bool {
+    let windup = scaled_windup(aq, entity, cooldown);
+    let current = aq.attacking_q.get(entity).ok().map(|(_, a)| *a);
+    let step = step_windup(current, target, windup, dt, aq.config.windup_redirect);
+    if let Some(cue) = attack_anim_cue(
+        &mut aq.anim,
+        entity,
+        slot,
+        target,
+        windup,
+        current.is_none(),
+        &step,
+        on_screen,
+    ) {
+        aq.anim_writer.write(cue);
+    }
+    match step {
+        WindupStep::Fire => {
+            if current.is_some() {
+                commands.entity(entity).remove::<Attacking>();
+            }
+            true
+        }
+        WindupStep::Whiff => {
+            commands.entity(entity).remove::<Attacking>();
+            false
+        }
+        WindupStep::Hold(atk) => {
+            winding.insert(entity);
+            if let Ok((_, mut a)) = aq.attacking_q.get_mut(entity) {
+                *a = atk;
+            } else {
+                commands.entity(entity).insert(atk);
+            }
+            false
+        }
+    }
+}
+
+/// Officer defaults for a fresh promotion.
+pub(crate) fn new_officer() -> Officer {
+    Officer {
+        aura_radius: crate::constants::OFFICER_AURA_RADIUS,
+        buff: crate::constants::OFFICER_AURA_BUFF,
+    }
+}
+
+/// Promote military veterans to officer and apply officer auras to same-town NPCs.
+/// Overlapping auras take the max, not the sum. AuraBuff is recomputed every tick,
+/// so a dead (or demoted) officer's aura disappears on the next tick.
+pub fn officer_aura_system(
+    mut commands: Commands,
+    entity_map: Res<EntityMap>,
+    gpu_state: Res<GpuReadState>,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+    promote_q: Query<
+        (Entity, &GpuSlot, &Job, &NpcStats),
+        (
+            Without<Officer>,
+            Without<Building>,
+            Without<Dead>,
+            Changed<NpcStats>,
+        ),
+    >,
+    officer_q: Query<(Entity, &GpuSlot, &Officer), (Without<Building>, Without<Dead>)>,
+    mut aura_q: Query<(Entity, &mut AuraBuff)>,
+    mut buffs: Local<std::collections::HashMap<Entity, f32>>,
+) {
+    for (entity, slot, job, stats) in promote_q.iter() {
+        if job.is_military() && level_from_xp(stats.xp) >= crate::constants::OFFICER_PROMOTION_LEVEL
+        {
+            commands.entity(entity).insert(new_officer());
+            gpu_updates.write(GpuUpdateMsg(GpuUpdate::MarkVisualDirty { idx: slot.0 }));
+        }
+    }
+
+    buffs.clear();
+    let positions = &gpu_state.positions;
+    for (officer, slot, aura) in officer_q.iter() {
+        let Some(town_idx) = entity_map.get_npc(slot.0).map(|n| n.town_idx) else {
+            continue;
+        };
+        let base = slot.0 * 2;
+        if base + 1 >= positions.len() || positions[base] < -9000.0 {
+            continue;
+        }
+        let center = Vec2::new(positions[base], positions[base + 1]);
+        let radius_sq = aura.aura_radius * aura.aura_radius;
+        for npc in entity_map.npcs_for_town(town_idx) {
+            if npc.dead || npc.entity == officer {
+                continue;
+            }
+            let b = npc.slot * 2;
+            if b + 1 >= positions.len() {
+                continue;
+            }
+            let pos = Vec2::new(positions[b], positions[b + 1]);
+            if pos.distance_squared(center) > radius_sq {
+                continue;
+            }
+            let best = buffs.entry(npc.entity).or_insert(0.0);
+            *best = best.max(aura.buff);
+        }
+    }
+
+    for (entity, mut current) in aura_q.iter_mut() {
+        match buffs.remove(&entity) {
+            Some(buff) if buff > 0.0 => {
+                if current.0 != buff {
+                    current.0 = buff;
+                }
+            }
+            _ => {
+                commands.entity(entity).remove::<AuraBuff>();
+            }
+        }
+    }
+    for (&entity, &buff) in buffs.iter() {
+        if buff > 0.0 {
+            commands.entity(entity).insert(AuraBuff(buff));
+        }
+    }
+}
+
+/// Threat an NPC presents to HighestThreat targeting: damage per second, boosted for
+/// officers, quantized to the 8-bit entity_flags field. Civilians present no threat.
+pub(crate) fn threat_value(job: Job, stats: &CachedStats, officer: bool) -> u32 {
+    if !job.is_military() {
+        return 0;
+    }
+    let mut dps = stats.damage / stats.cooldown.max(0.1);
+    if officer {
+        dps *= crate::constants::OFFICER_THREAT_MULT;
+    }
+    dps.round().clamp(0.0, 255.0) as u32
+}
+
+/// Damage multiplier (0..1] from low energy. Linear from 1 at `threshold` down to
+/// `1 - penalty` at 0 energy; Hardy (positive Vitality) shrinks the penalty, Frail grows it.
+pub(crate) fn fatigue_factor(energy: f32, threshold: f32, penalty: f32, vitality: f32) -> f32 {
+    if threshold <= 0.0 || energy >= threshold {
+        return 1.0;
+    }
+    let depth = (1.0 - energy.max(0.0) / threshold).clamp(0.0, 1.0);
+    let resist = (1.0 - vitality * crate::constants::HARDY_FATIGUE_RESIST).clamp(0.0, 1.5);
+    (1.0 - penalty * resist * depth).clamp(0.0, 1.0)
+}
+
+/// Damage and cooldown multipliers for an attacker after fatigue and supply penalties.
+/// Fatigue weakens hits and slows swings (DPS × fatigue²), being out of supply stretches the
+/// cooldown. If the combined DPS fraction drops below `floor`, both are eased back evenly so
+/// exhausted, cut-off units still contribute something.
+pub(crate) fn combat_modifiers(fatigue: f32, out_of_supply: bool, floor: f32) -> (f32, f32) {
+    let supply = if out_of_supply {
+        crate::constants::SUPPLY_COOLDOWN_MULT
+    } else {
+        1.0
+    };
+    let mut damage = fatigue.max(0.01);
+    let mut cooldown = supply / damage;
+    let effectiveness = damage / cooldown;
+    if effectiveness < floor {
+        let lift = (floor / effectiveness).sqrt();
+        damage = (damage * lift).min(1.0);
+        cooldown = damage / floor;
+    }
+    (damage, cooldown)
+}
+
+/// GPU entity_flags for an NPC: combat scan bit, target priority profile, threat value.
+pub(crate) fn npc_gpu_flags(job: Job, priority: TargetPriority, threat: u32) -> u32 {
+    use crate::constants::{
+        ENTITY_FLAG_COMBAT, ENTITY_FLAG_PRIORITY_SHIFT, ENTITY_FLAG_THREAT_SHIFT,
+    };
+    let combat = if job.is_military() {
+        ENTITY_FLAG_COMBAT
+    } else {
+        0
+    };
+    combat
+        | ((priority as u32) << ENTITY_FLAG_PRIORITY_SHIFT)
+        | (threat.min(255) << ENTITY_FLAG_THREAT_SHIFT)
+}
+
+/// Sync each NPC's effective target priority (unit override, else squad), threat value,
+/// stance passive bit, anchored bit, and target-commit bit into GPU entity_flags. Only
+/// re-flags NPCs whose priority, stance, provocation, stats, officer rank, squad, anchoring,
+/// or commitment changed, plus
+/// members of squads whose `target_priority` changed since last tick.
+pub fn target_priority_system(
+    changed_q: Query<
+        Entity,
+        (
+            Without<Building>,
+            Without<Dead>,
+            Or<(
+                Changed<TargetPriority>,
+                Changed<CombatStance>,
+                Added<Provoked>,
+                Changed<CachedStats>,
+                Added<Officer>,
+                Changed<SquadId>,
+                Added<Anchored>,
+                Added<TargetCommit>,
+            )>,
+        ),
+    >,
+    npc_q: Query<
+        (
+            &GpuSlot,
+            &Job,
+            &CachedStats,
+            Option<&TargetPriority>,
+            Option<&CombatStance>,
+            Has<Provoked>,
+            Option<&SquadId>,
+            Has<Officer>,
+            Has<Anchored>,
+            Has<TargetCommit>,
+        ),
+        (Without<Building>, Without<Dead>),
+    >,
+    mut removed: RemovedComponents<TargetPriority>,
+    mut removed_stance: RemovedComponents<CombatStance>,
+    mut removed_provoked: RemovedComponents<Provoked>,
+    mut removed_anchored: RemovedComponents<Anchored>,
+    mut removed_commit: RemovedComponents<TargetCommit>,
+    squad_state: Res<crate::resources::SquadState>,
+    mut last_squad: Local<Vec<TargetPriority>>,
+    mut dirty: Local<Vec<Entity>>,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+) {
+    dirty.clear();
+    dirty.extend(changed_q.iter());
+    dirty.extend(removed.read());
+    dirty.extend(removed_stance.read());
+    dirty.extend(removed_provoked.read());
+    dirty.extend(removed_anchored.read());
+    dirty.extend(removed_commit.read());
+    last_squad.resize(squad_state.squads.len(), TargetPriority::default());
+    for (squad, last) in squad_state.squads.iter().zip(last_squad.iter_mut()) {
+        if squad.target_priority != *last {
+            *last = squad.target_priority;
+            dirty.extend(squad.members.iter().copied());
+        }
+    }
+
+    for &entity in dirty.iter() {
+        let Ok((slot, job, stats, unit, stance, provoked, squad_id, officer, anchored, committed)) =
+            npc_q.get(entity)
+        else {
+            continue;
+        };
+        let priority = unit.copied().unwrap_or_else(|| {
+            squad_id
+                .and_then(|sid| squad_state.squads.get(sid.0 as usize))
+                .map(|sq| sq.target_priority)
+                .unwrap_or_default()
+        });
+        let mut flags = npc_gpu_flags(*job, priority, threat_value(*job, stats, officer));
+        if stance.is_some_and(|s| s.is_passive(provoked)) {
+            flags |= crate::constants::ENTITY_FLAG_PASSIVE;
+        }
+        if anchored {
+            flags |= crate::constants::ENTITY_FLAG_ANCHORED;
+        }
+        if committed {
+            flags |= crate::constants::ENTITY_FLAG_COMMITTED;
+        }
+        gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetFlags { idx: slot.0, flags }));
+    }
+}
+
+/// Tick down target commitments; removing `TargetCommit` clears the committed flag so the GPU
+/// re-picks by nearest/priority.
+pub fn target_commit_system(
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    mut commands: Commands,
+    mut commit_q: Query<(Entity, &mut TargetCommit)>,
+) {
+    let dt = game_time.delta(&time);
+    for (entity, mut commit) in commit_q.iter_mut() {
+        commit.remaining -= dt;
+        if commit.remaining <= 0.0 {
+            commands.entity(entity).remove::<TargetCommit>();
+        }
+    }
+}
+
+/// Tick down ReturnFire provocation; removing `Provoked` re-flags the unit passive.
+pub fn return_fire_system(
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    mut commands: Commands,
+    mut provoked_q: Query<(Entity, &mut Provoked)>,
+) {
+    let dt = game_time.delta(&time);
+    for (entity, mut provoked) in provoked_q.iter_mut() {
+        provoked.0 -= dt;
+        if provoked.0 <= 0.0 {
+            commands.entity(entity).remove::<Provoked>();
+        }
+    }
 }
 
 /// Decrement attack cooldown timers each frame.
@@ -115,6 +732,31 @@ pub fn cooldown_system(
     debug.frame_delta = dt;
 }
 
+/// Track NPC velocities from position readback for lead targeting and facing.
+pub fn npc_velocity_system(
+    gpu_state: Res<GpuReadState>,
+    game_time: Res<GameTime>,
+    lead: Res<LeadTargeting>,
+    config: Res<CombatConfig>,
+    mut velocities: ResMut<NpcVelocities>,
+    mut facing: ResMut<NpcFacing>,
+) {
+    let flanking = config.flank_bonus > 0.0;
+    if game_time.is_paused() || (lead.mode == crate::resources::LeadTargetMode::Off && !flanking) {
+        return;
+    }
+    let n = gpu_state.npc_count.min(gpu_state.positions.len() / 2);
+    velocities.observe(&gpu_state.positions[..n * 2], game_time.total_seconds);
+    if flanking {
+        facing.update(
+            &velocities,
+            &gpu_state.positions,
+            &gpu_state.combat_targets,
+            n,
+        );
+    }
+}
+
 /// Process attacks using GPU targeting results.
 /// GPU finds nearest enemy, Bevy checks range and applies damage.
 pub fn attack_system(
@@ -131,6 +773,7 @@ pub fn attack_system(
     mut commands: Commands,
     game_time: Res<GameTime>,
     mut aq: AttackQueries,
+    mut winding: Local<std::collections::HashSet<Entity>>,
     npc_q: Query<
         (
             Entity,
@@ -142,6 +785,11 @@ pub fn attack_system(
             &Health,
             Option<&SquadId>,
             Option<&ManualTarget>,
+            Option<&AuraBuff>,
+            Option<&CombatStance>,
+            Has<Provoked>,
+            Has<OutOfSupply>,
+            Option<&Energy>,
         ),
         (Without<Building>, Without<Dead>),
     >,
@@ -149,6 +797,15 @@ pub fn attack_system(
     if game_time.is_paused() {
         return;
     }
+    let dt = game_time.delta(&aq.time);
+    // The CPU compute fallback has no projectile pass: hits land instantly
+    let no_projectiles = *aq.backend == crate::resources::ComputeBackend::Cpu;
+    let anim_view = if aq.anim.enabled {
+        attack_anim_view(&aq)
+    } else {
+        None
+    };
+    winding.clear();
     let positions = &gpu_state.positions;
     let combat_targets = &gpu_state.combat_targets;
 
@@ -164,8 +821,22 @@ pub fn attack_system(
     let mut timer_ready_count = 0usize;
     let mut sample_timer = -1.0f32;
 
-    for (entity, slot, job, faction, stats, activity, health, squad_id_opt, manual_target_opt) in
-        npc_q.iter()
+    for (
+        entity,
+        slot,
+        job,
+        faction,
+        stats,
+        activity,
+        health,
+        squad_id_opt,
+        manual_target_opt,
+        aura_opt,
+        stance_opt,
+        provoked,
+        out_of_supply,
+        energy_opt,
+    ) in npc_q.iter()
     {
         let i = slot.0;
         let faction_id = faction.0;
@@ -180,7 +851,28 @@ pub fn attack_system(
         } else {
             stats.damage
         };
-        let cached_cooldown = stats.cooldown;
+        // Officer aura: applied at use, never baked into CachedStats
+        let cached_damage = cached_damage * (1.0 + aura_opt.map_or(0.0, |a| a.0));
+        // Fatigue and supply: applied at use, so resting or walking back into supply lifts them
+        let fatigue = match energy_opt {
+            Some(energy) if aq.config.fatigue_threshold > 0.0 => {
+                let vitality = aq
+                    .personality_q
+                    .get(entity)
+                    .map_or(0.0, |p| p.magnitude(TraitKind::Vitality));
+                fatigue_factor(
+                    energy.0,
+                    aq.config.fatigue_threshold,
+                    aq.config.fatigue_penalty,
+                    vitality,
+                )
+            }
+            _ => 1.0,
+        };
+        let (damage_mod, cooldown_mod) =
+            combat_modifiers(fatigue, out_of_supply, aq.config.combat_modifier_floor);
+        let cached_damage = cached_damage * damage_mod;
+        let cached_cooldown = stats.cooldown * cooldown_mod;
         let cached_proj_speed = stats.projectile_speed;
         let cached_proj_lifetime = stats.projectile_lifetime;
         let activity_skip = activity.kind.distraction() == Distraction::None;
@@ -202,12 +894,21 @@ pub fn attack_system(
             continue;
         }
 
+        // Stance mirrors the GPU passive bit so a stale combat_targets readback
+        // can't trigger a chase in the frame after the stance changes.
+        let hold = squad_id_val
+            .and_then(|sid| squad_state.squads.get(sid as usize))
+            .is_some_and(|sq| sq.hold_fire)
+            || stance_opt.is_some_and(|s| s.is_passive(provoked));
+
         // Manual target override
         let mut target_idx = if let Some(mt) = manual_target_opt {
             match mt {
                 ManualTarget::Npc(t) => {
-                    let dead = entity_map.get_npc(*t).is_none_or(|n| n.dead);
-                    if dead {
+                    if !manual_target_valid(&entity_map, *t, faction_id) {
+                        if aq.flags.combat {
+                            info!("npc #{i}: manual target #{t} dead or invalid, back to auto");
+                        }
                         commands.entity(entity).remove::<ManualTarget>();
                         combat_targets.get(i).copied().unwrap_or(-1)
                     } else {
@@ -218,15 +919,13 @@ pub fn attack_system(
                     combat_targets.get(i).copied().unwrap_or(-1)
                 }
             }
+        } else if hold {
+            -1
+        } else if let Some(&t) = aq.bodyguard.0.get(&i) {
+            // Bodyguards take on whoever targets their charge and ignore anything past the leash
+            t
         } else {
-            let hold = squad_id_val
-                .and_then(|sid| squad_state.squads.get(sid as usize))
-                .is_some_and(|sq| sq.hold_fire);
-            if hold {
-                -1
-            } else {
-                combat_targets.get(i).copied().unwrap_or(-1)
-            }
+            combat_targets.get(i).copied().unwrap_or(-1)
         };
 
         // Sticky building target
@@ -249,10 +948,46 @@ pub fn attack_system(
         }
 
         if target_idx < 0 {
-            if is_fighting {
-                if let Ok(mut cs) = aq.combat_state_q.get_mut(entity) {
-                    *cs = CombatState::None;
+            // Aggro memory: keep chasing the last-known position for a short while
+            let Ok(&CombatState::Fighting { origin }) = aq.combat_state_q.get(entity) else {
+                continue;
+            };
+            let leash = aq.leash_q.get(entity).ok().map(|l| l.0);
+            let zone = aq.engage_q.get(entity).ok().copied();
+            let mut leashed = false;
+            let chase = match aq.aggro_q.get_mut(entity) {
+                Ok(mut mem) if !hold => {
+                    mem.remaining -= dt;
+                    // Defensive squads don't follow a lost target out of their engage zone
+                    let to = aggro_chase(origin, &mem, leash)
+                        .filter(|&p| zone.is_none_or(|z| z.allows(origin, p)));
+                    leashed = to.is_none() && mem.remaining > 0.0;
+                    to
                 }
+                _ => None,
+            };
+            if let Some(last_pos) = chase {
+                intents.submit(
+                    entity,
+                    last_pos,
+                    MovementPriority::Combat,
+                    "combat:aggro_memory",
+                );
+                chases += 1;
+                continue;
+            }
+            if leashed {
+                // Chasing would overrun the leash — break off back to where the fight began
+                intents.submit(
+                    entity,
+                    origin,
+                    MovementPriority::Combat,
+                    "combat:aggro_leash",
+                );
+            }
+            commands.entity(entity).remove::<AggroMemory>();
+            if let Ok(mut cs) = aq.combat_state_q.get_mut(entity) {
+                *cs = CombatState::None;
             }
             continue;
         }
@@ -277,6 +1012,29 @@ pub fn attack_system(
         if x < -9000.0 {
             continue;
         }
+        let on_screen = anim_view.is_none_or(|r| r.contains(Vec2::new(x, y)));
+
+        // Defensive squads hold: targets outside the engage zone are left alone
+        if let Ok(zone) = aq.engage_q.get(entity) {
+            let target_pos = match entity_map.get_instance(ti) {
+                Some(inst) => Some(inst.position),
+                None => positions
+                    .get(ti * 2..ti * 2 + 2)
+                    .map(|p| Vec2::new(p[0], p[1])),
+            };
+            let origin = match aq.combat_state_q.get(entity) {
+                Ok(&CombatState::Fighting { origin }) => origin,
+                _ => Vec2::new(x, y),
+            };
+            if target_pos.is_some_and(|p| !zone.allows(origin, p)) {
+                if is_fighting {
+                    if let Ok(mut cs) = aq.combat_state_q.get_mut(entity) {
+                        *cs = CombatState::None;
+                    }
+                }
+                continue;
+            }
+        }
 
         // ── Building target ──
         if let Some(inst) = entity_map.get_instance(ti) {
@@ -307,23 +1065,50 @@ pub fn attack_system(
                 let timer = aq.timer_q.get(entity).map(|t| t.0).unwrap_or(0.0);
                 if timer <= 0.0 {
                     timer_ready_count += 1;
-                    if !fire_projectile(
-                        Vec2::new(x, y),
-                        inst_pos,
-                        cached_damage,
-                        cached_proj_speed,
-                        cached_proj_lifetime,
-                        faction_id,
-                        i as i32,
-                        -1,
-                        &mut proj_alloc,
-                        &mut proj_updates,
-                        &mut sfx_writer,
+                    if !resolve_windup(
+                        &mut aq,
+                        &mut commands,
+                        &mut winding,
+                        entity,
+                        i,
+                        ti,
+                        cached_cooldown,
+                        dt,
+                        on_screen,
                     ) {
+                        continue;
+                    }
+                    let damage = roll_attack_damage(
+                        &mut aq,
+                        &mut sfx_writer,
+                        entity,
+                        None,
+                        cached_damage,
+                        inst_pos,
+                    );
+                    if (no_projectiles
+                        || !fire_projectile(
+                            Vec2::new(x, y),
+                            inst_pos,
+                            damage,
+                            cached_proj_speed,
+                            cached_proj_lifetime,
+                            faction_id,
+                            i as i32,
+                            -1,
+                            ProjKind::Arrow,
+                            aq.arc_q.get(entity).copied().unwrap_or_default(),
+                            game_time.total_seconds,
+                            &mut proj_alloc,
+                            &mut proj_updates,
+                            &mut sfx_writer,
+                        ))
+                        && damage > 0.0
+                    {
                         if let Some(target_entity) = entity_map.entities.get(&ti).copied() {
                             damage_events.write(DamageMsg {
                                 target: target_entity,
-                                amount: cached_damage,
+                                amount: damage,
                                 attacker: i as i32,
                                 attacker_faction: faction_id,
                             });
@@ -392,6 +1177,33 @@ pub fn attack_system(
             continue;
         }
 
+        // New target: commit to it so the GPU doesn't flicker to the next-nearest
+        if aq.stickiness.secs > 0.0 {
+            let commit = TargetCommit {
+                target: ti,
+                remaining: aq.stickiness.secs,
+            };
+            match aq.commit_q.get_mut(entity) {
+                Ok(mut c) if c.target != ti => *c = commit,
+                Ok(_) => {}
+                Err(_) => {
+                    commands.entity(entity).insert(commit);
+                }
+            }
+        }
+
+        if aq.aggro.secs > 0.0 {
+            let memory = AggroMemory {
+                last_pos: Vec2::new(tx, ty),
+                remaining: aq.aggro.secs,
+            };
+            if let Ok(mut mem) = aq.aggro_q.get_mut(entity) {
+                *mem = memory;
+            } else {
+                commands.entity(entity).insert(memory);
+            }
+        }
+
         let dx = tx - x;
         let dy = ty - y;
         let dist = (dx * dx + dy * dy).sqrt();
@@ -414,35 +1226,77 @@ pub fn attack_system(
             }
             if timer <= 0.0 {
                 timer_ready_count += 1;
-                if !fire_projectile(
-                    Vec2::new(x, y),
-                    Vec2::new(tx, ty),
-                    cached_damage,
-                    cached_proj_speed,
-                    cached_proj_lifetime,
-                    faction_id,
-                    i as i32,
-                    -1,
-                    &mut proj_alloc,
-                    &mut proj_updates,
-                    &mut sfx_writer,
+                if !resolve_windup(
+                    &mut aq,
+                    &mut commands,
+                    &mut winding,
+                    entity,
+                    i,
+                    ti,
+                    cached_cooldown,
+                    dt,
+                    on_screen,
                 ) {
-                    if let Some(&target_entity) = entity_map.entities.get(&ti) {
-                        damage_events.write(DamageMsg {
-                            target: target_entity,
-                            amount: cached_damage,
-                            attacker: i as i32,
-                            attacker_faction: faction_id,
-                        });
-                    }
-                }
-                attacks += 1;
-                if let Ok(mut t) = aq.timer_q.get_mut(entity) {
-                    t.0 = cached_cooldown;
+                    continue;
                 }
-            }
-        } else {
-            intents.submit(
+                let damage = roll_attack_damage(
+                    &mut aq,
+                    &mut sfx_writer,
+                    entity,
+                    entity_map.entities.get(&ti).copied(),
+                    cached_damage,
+                    Vec2::new(tx, ty),
+                );
+                let precision = aq
+                    .personality_q
+                    .get(entity)
+                    .map_or(0.0, |p| p.magnitude(TraitKind::Precision));
+                let aim = if aq.lead.leads(precision) {
+                    lead_intercept(
+                        Vec2::new(x, y),
+                        Vec2::new(tx, ty),
+                        aq.velocities.velocity(ti),
+                        cached_proj_speed,
+                        cached_proj_lifetime,
+                    )
+                } else {
+                    Vec2::new(tx, ty)
+                };
+                if (no_projectiles
+                    || !fire_projectile(
+                        Vec2::new(x, y),
+                        aim,
+                        damage,
+                        cached_proj_speed,
+                        cached_proj_lifetime,
+                        faction_id,
+                        i as i32,
+                        -1,
+                        ProjKind::Arrow,
+                        aq.arc_q.get(entity).copied().unwrap_or_default(),
+                        game_time.total_seconds,
+                        &mut proj_alloc,
+                        &mut proj_updates,
+                        &mut sfx_writer,
+                    ))
+                    && damage > 0.0
+                {
+                    if let Some(&target_entity) = entity_map.entities.get(&ti) {
+                        damage_events.write(DamageMsg {
+                            target: target_entity,
+                            amount: damage,
+                            attacker: i as i32,
+                            attacker_faction: faction_id,
+                        });
+                    }
+                }
+                attacks += 1;
+                if let Ok(mut t) = aq.timer_q.get_mut(entity) {
+                    t.0 = cached_cooldown;
+                }
+            }
+        } else {
+            intents.submit(
                 entity,
                 Vec2::new(tx, ty),
                 MovementPriority::Combat,
@@ -452,6 +1306,26 @@ pub fn attack_system(
         }
     }
 
+    // Attackers that didn't keep winding up this tick (target lost, out of range,
+    // survival state, dead) reset cleanly instead of keeping a stuck Attacking.
+    let mut lost_swings = Vec::new();
+    for (e, _) in aq.attacking_q.iter() {
+        if !winding.contains(&e) {
+            commands.entity(e).remove::<Attacking>();
+            lost_swings.push(e);
+        }
+    }
+    for e in lost_swings {
+        if let Some((slot, target_slot)) = aq.anim.swinging.remove(&e) {
+            aq.anim_writer.write(crate::messages::AttackAnimMsg {
+                kind: crate::messages::AttackAnimKind::Whiff,
+                slot,
+                target_slot,
+                windup: 0.0,
+            });
+        }
+    }
+
     debug.attackers_queried = attackers;
     debug.targets_found = targets_found;
     debug.attacks_made = attacks;
@@ -477,17 +1351,48 @@ pub fn attack_system(
     );
 }
 
+/// Living NPCs within `radius` of `center` that a shell from `faction` hurts: not its own
+/// faction, not neutral, not hidden. Candidates come from the NPC spatial grid, so it must
+/// have been rebuilt from `positions` this frame (`EntityMap::rebuild_npc_spatial`).
+fn splash_targets(
+    entity_map: &EntityMap,
+    positions: &[f32],
+    center: Vec2,
+    radius: f32,
+    faction: i32,
+) -> Vec<Entity> {
+    let radius_sq = radius * radius;
+    let mut targets = Vec::new();
+    entity_map.for_each_npc_nearby(center, radius, |n| {
+        if n.dead || n.faction == faction || n.faction == crate::constants::FACTION_NEUTRAL {
+            return;
+        }
+        let in_range = positions.get(n.slot * 2..n.slot * 2 + 2).is_some_and(|p| {
+            p[0] > -9000.0 && Vec2::new(p[0], p[1]).distance_squared(center) <= radius_sq
+        });
+        if in_range {
+            targets.push(n.entity);
+        }
+    });
+    targets
+}
+
 /// Process GPU projectile hits: convert to unified DamageMsg events and recycle slots.
 /// Entity buffer layout: unified slot namespace (NPCs and buildings share [0..entity_count]).
 /// damage_system routes by entity_idx to NPC or building path.
+/// Arced shots report on landing (-3 when nothing was struck); one with a splash radius
+/// damages every enemy NPC around its landing point as well as whatever it struck.
 pub fn process_proj_hits(
     mut damage_events: MessageWriter<DamageMsg>,
     mut proj_updates: MessageWriter<ProjGpuUpdateMsg>,
     mut proj_alloc: ResMut<ProjSlotAllocator>,
     proj_writes: Res<ProjBufferWrites>,
     mut hit_state: ResMut<ProjHitState>,
-    entity_map: Res<crate::resources::EntityMap>,
+    mut entity_map: ResMut<crate::resources::EntityMap>,
+    gpu_state: Res<GpuReadState>,
 ) {
+    // NPC grid is rebuilt at most once per frame, on the first splash landing
+    let mut npc_grid_fresh = false;
     let max_slot = proj_alloc.next.min(hit_state.0.len());
     for (slot, hit) in hit_state.0[..max_slot].iter().enumerate() {
         if slot < proj_writes.active.len() && proj_writes.active[slot] == 0 {
@@ -497,7 +1402,8 @@ pub fn process_proj_hits(
         let hit_idx = hit[0];
         let processed = hit[1];
 
-        if hit_idx >= 0 && processed == 0 {
+        let landed = hit_idx == -3 && processed == 0;
+        if (hit_idx >= 0 && processed == 0) || landed {
             let damage = if slot < proj_writes.damages.len() {
                 proj_writes.damages[slot]
             } else {
@@ -511,9 +1417,32 @@ pub fn process_proj_hits(
                     -1
                 };
                 let attacker_faction = proj_writes.factions.get(slot).copied().unwrap_or(-1);
-                if let Some(&target_entity) = entity_map.entities.get(&(hit_idx as usize)) {
+                let splash = proj_writes.splash_radii.get(slot).copied().unwrap_or(0.0);
+                let mut targets = match proj_writes.landing(slot) {
+                    Some(center) if splash > 0.0 => {
+                        if !npc_grid_fresh {
+                            entity_map.rebuild_npc_spatial(&gpu_state.positions);
+                            npc_grid_fresh = true;
+                        }
+                        splash_targets(
+                            &entity_map,
+                            &gpu_state.positions,
+                            center,
+                            splash,
+                            attacker_faction,
+                        )
+                    }
+                    _ => Vec::new(),
+                };
+                if hit_idx >= 0
+                    && let Some(&target_entity) = entity_map.entities.get(&(hit_idx as usize))
+                    && !targets.contains(&target_entity)
+                {
+                    targets.push(target_entity);
+                }
+                for target in targets {
                     damage_events.write(DamageMsg {
-                        target: target_entity,
+                        target,
                         amount: damage,
                         attacker: shooter,
                         attacker_faction,
@@ -544,6 +1473,7 @@ pub fn building_tower_system(
     mut proj_alloc: ResMut<ProjSlotAllocator>,
     mut proj_updates: MessageWriter<ProjGpuUpdateMsg>,
     mut sfx_writer: MessageWriter<crate::resources::PlaySfxMsg>,
+    arc_q: Query<&ProjArc, With<Building>>,
     mut building_health: Query<&mut Health, (With<Building>, Without<Dead>)>,
     tower_bld_q: Query<&crate::components::TowerBuildingState, With<Building>>,
 ) {
@@ -589,6 +1519,12 @@ pub fn building_tower_system(
             .next()
             .map(|inst| inst.slot);
         let Some(bld_slot) = bld_slot else { continue };
+        let arc = entity_map
+            .entities
+            .get(&bld_slot)
+            .and_then(|&e| arc_q.get(e).ok())
+            .copied()
+            .unwrap_or_default();
 
         // Read GPU combat_targets for this tower's entity index (unified slot)
         let target = gpu_state
@@ -634,6 +1570,9 @@ pub fn building_tower_system(
             faction,
             bld_slot as i32,
             -1,
+            ProjKind::Tower,
+            arc,
+            game_time.total_seconds,
             &mut proj_alloc,
             &mut proj_updates,
             &mut sfx_writer,
@@ -727,6 +1666,9 @@ pub fn building_tower_system(
             faction,
             slot as i32,
             -1,
+            ProjKind::Tower,
+            arc_q.get(entity).copied().unwrap_or_default(),
+            game_time.total_seconds,
             &mut proj_alloc,
             &mut proj_updates,
             &mut sfx_writer,
@@ -746,12 +1688,14 @@ pub fn sync_building_hp_render(
 ) {
     render.positions.clear();
     render.health_pcts.clear();
+    render.footprints.clear();
     if !heal_state.needs_healing {
         return;
     }
     let positions = &gpu_state.positions;
     for (building, npc_idx, health) in query.iter() {
-        let max_hp = crate::constants::building_def(building.kind).hp;
+        let def = crate::constants::building_def(building.kind);
+        let max_hp = def.hp;
         if health.0 <= 0.0 || health.0 >= max_hp {
             continue;
         }
@@ -763,6 +1707,7 @@ pub fn sync_building_hp_render(
         let y = positions[idx * 2 + 1];
         render.positions.push(Vec2::new(x, y));
         render.health_pcts.push(health.0 / max_hp);
+        render.footprints.push(def.footprint);
     }
 }
 
@@ -1230,6 +2175,7 @@ mod tests {
         app.insert_resource(crate::gpu::ProjBufferWrites::default());
         app.insert_resource(ProjHitState(vec![[0, 0]]));
         app.insert_resource(crate::resources::EntityMap::default());
+        app.insert_resource(crate::resources::GpuReadState::default());
 
         let slot = app
             .world_mut()
@@ -1265,4 +2211,728 @@ mod tests {
             "hit should still recycle the projectile slot"
         );
     }
+
+    #[test]
+    fn ballistic_launch_lands_on_target_or_falls_short() {
+        // 300 px/s under 900 px/s² reaches at most 100 px
+        let shot = ballistic_launch(60.0, 300.0, 900.0).unwrap();
+        assert!((shot.range - 60.0).abs() < 0.5, "range {}", shot.range);
+        assert!(shot.vz > shot.ground_speed, "steep lob, not a flat shot");
+        let height = |t: f32| shot.vz * t - 0.5 * 900.0 * t * t;
+        assert!(height(shot.flight_time / 2.0) > 0.0);
+        assert!(height(shot.flight_time).abs() < 0.01);
+
+        let far = ballistic_launch(5000.0, 300.0, 900.0).unwrap();
+        assert!(far.range.is_finite() && far.flight_time.is_finite());
+        assert!(
+            (far.range - 100.0).abs() < 0.5,
+            "45° max range, short of the target"
+        );
+        assert!((far.vz - far.ground_speed).abs() < 0.01);
+
+        assert_eq!(
+            ballistic_launch(60.0, 300.0, 0.0),
+            None,
+            "no gravity = flat"
+        );
+        assert_eq!(ballistic_launch(60.0, 0.0, 900.0), None);
+    }
+
+    #[test]
+    fn arced_landing_splashes_enemies_around_the_landing_point() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<crate::messages::DamageMsg>();
+        app.add_message::<ProjGpuUpdateMsg>();
+        app.insert_resource(ProjSlotAllocator::default());
+        app.insert_resource(crate::gpu::ProjBufferWrites::default());
+        app.insert_resource(ProjHitState(vec![[-3, 0]]));
+        let mut em = EntityMap::default();
+        em.init_spatial(1024.0);
+        // Slot 0 friendly and 1 enemy beside the landing point (250, 0), across the 256px cell
+        // border; slot 2 enemy too far
+        let mut spawn = |slot: usize, faction: i32| {
+            let e = app.world_mut().spawn_empty().id();
+            em.register_npc(slot, e, Job::Archer, faction, 0);
+            e
+        };
+        spawn(0, 1);
+        let near = spawn(1, 2);
+        spawn(2, 2);
+        app.insert_resource(em);
+        app.insert_resource(crate::resources::GpuReadState {
+            positions: vec![250.0, 10.0, 262.0, -10.0, 450.0, 0.0],
+            ..Default::default()
+        });
+
+        let slot = app
+            .world_mut()
+            .resource_mut::<ProjSlotAllocator>()
+            .alloc()
+            .unwrap();
+        // Lobbed from the origin at 125 px/s along x under 100 px/s²: 2s in the air, lands at x=250
+        app.world_mut()
+            .resource_mut::<crate::gpu::ProjBufferWrites>()
+            .apply(&ProjGpuUpdate::Spawn {
+                idx: slot,
+                x: 0.0,
+                y: 0.0,
+                vx: 125.0,
+                vy: 0.0,
+                damage: 12.0,
+                faction: 1,
+                shooter: -1,
+                lifetime: 2.25,
+                homing_target: -1,
+                kind: ProjKind::Tower,
+                arc: ProjArc {
+                    gravity: 100.0,
+                    splash_radius: 40.0,
+                },
+                vz: 100.0,
+            });
+        assert_eq!(
+            app.world()
+                .resource::<crate::gpu::ProjBufferWrites>()
+                .landing(slot),
+            Some(Vec2::new(250.0, 0.0))
+        );
+
+        app.world_mut().run_system_once(process_proj_hits).unwrap();
+        let hits = app
+            .world_mut()
+            .run_system_once(|mut reader: MessageReader<crate::messages::DamageMsg>| {
+                reader
+                    .read()
+                    .map(|m| (m.target, m.amount))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap();
+        assert_eq!(hits, vec![(near, 12.0)]);
+        assert_eq!(app.world().resource::<ProjSlotAllocator>().alive(), 0);
+    }
+
+    // -- attack windup ------------------------------------------------------
+
+    #[test]
+    fn windup_zero_fires_immediately() {
+        assert_eq!(step_windup(None, 3, 0.0, 0.016, false), WindupStep::Fire);
+    }
+
+    #[test]
+    fn windup_holds_until_elapsed() {
+        let step = step_windup(None, 3, 0.5, 0.2, false);
+        let WindupStep::Hold(atk) = step else {
+            panic!("first tick should start windup: {step:?}");
+        };
+        assert_eq!(atk.target, 3);
+        assert!((atk.elapsed - 0.2).abs() < f32::EPSILON);
+        let step = step_windup(Some(atk), 3, 0.5, 0.2, false);
+        let WindupStep::Hold(atk) = step else {
+            panic!("should still be winding: {step:?}");
+        };
+        assert_eq!(step_windup(Some(atk), 3, 0.5, 0.2, false), WindupStep::Fire);
+    }
+
+    #[test]
+    fn windup_target_change_whiffs_without_redirect() {
+        let atk = Attacking {
+            elapsed: 0.4,
+            target: 3,
+        };
+        assert_eq!(
+            step_windup(Some(atk), 7, 0.5, 0.05, false),
+            WindupStep::Whiff
+        );
+    }
+
+    #[test]
+    fn windup_target_change_redirects_and_keeps_progress() {
+        let atk = Attacking {
+            elapsed: 0.4,
+            target: 3,
+        };
+        assert_eq!(step_windup(Some(atk), 7, 0.5, 0.2, true), WindupStep::Fire);
+        let step = step_windup(Some(atk), 7, 0.5, 0.05, true);
+        let WindupStep::Hold(next) = step else {
+            panic!("redirect should keep winding: {step:?}");
+        };
+        assert_eq!(next.target, 7);
+        assert!((next.elapsed - 0.45).abs() < 1e-5);
+    }
+
+    #[test]
+    fn attack_anim_swing_resolves_to_hit_or_whiff() {
+        use crate::messages::AttackAnimKind;
+        let mut outbox = crate::resources::AttackAnimOutbox::default();
+        let (a, b) = (
+            Entity::from_raw_u32(1).unwrap(),
+            Entity::from_raw_u32(2).unwrap(),
+        );
+        let hold = WindupStep::Hold(Attacking {
+            elapsed: 0.1,
+            target: 9,
+        });
+        assert_eq!(
+            attack_anim_cue(&mut outbox, a, 4, 9, 0.5, true, &hold, true),
+            None,
+            "off until a consumer enables it"
+        );
+        outbox.enabled = true;
+
+        // a swings and connects; b swings and loses its target
+        let swing = attack_anim_cue(&mut outbox, a, 4, 9, 0.5, true, &hold, true).unwrap();
+        assert_eq!(
+            (swing.kind, swing.slot, swing.target_slot),
+            (AttackAnimKind::Swing, 4, 9)
+        );
+        assert!(attack_anim_cue(&mut outbox, b, 5, 9, 0.5, true, &hold, true).is_some());
+        assert_eq!(
+            attack_anim_cue(&mut outbox, a, 4, 9, 0.5, false, &hold, true),
+            None
+        );
+        assert_eq!(
+            attack_anim_cue(&mut outbox, a, 4, 9, 0.5, false, &WindupStep::Fire, true),
+            None,
+            "a hit needs no follow-up"
+        );
+        let whiff =
+            attack_anim_cue(&mut outbox, b, 5, 7, 0.5, false, &WindupStep::Whiff, false).unwrap();
+        assert_eq!(
+            (whiff.kind, whiff.slot, whiff.target_slot),
+            (AttackAnimKind::Whiff, 5, 9)
+        );
+        assert!(outbox.swinging.is_empty());
+
+        // Off-screen swings are never announced, so their whiffs stay silent too
+        assert_eq!(
+            attack_anim_cue(&mut outbox, a, 4, 9, 0.5, true, &hold, false),
+            None
+        );
+        assert_eq!(
+            attack_anim_cue(&mut outbox, a, 4, 9, 0.5, false, &WindupStep::Whiff, true),
+            None
+        );
+        // Instant attacks (no windup) announce the swing without tracking it
+        let instant = attack_anim_cue(&mut outbox, a, 4, 9, 0.0, true, &WindupStep::Fire, true);
+        assert_eq!(instant.map(|m| m.kind), Some(AttackAnimKind::Swing));
+        assert!(outbox.swinging.is_empty());
+    }
+
+    // -- officer aura --------------------------------------------------------
+
+    fn setup_officer_app(positions: &[(f32, f32)]) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<GpuUpdateMsg>();
+        app.insert_resource(EntityMap::default());
+        let mut gpu = crate::resources::GpuReadState::default();
+        gpu.positions = positions.iter().flat_map(|&(x, y)| [x, y]).collect();
+        app.insert_resource(gpu);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_secs_f32(1.0),
+        ));
+        app.add_systems(FixedUpdate, officer_aura_system);
+        app
+    }
+
+    fn spawn_town_npc(app: &mut App, slot: usize, job: Job, town: i32, xp: i32) -> Entity {
+        let entity = app
+            .world_mut()
+            .spawn((
+                GpuSlot(slot),
+                job,
+                crate::components::NpcStats {
+                    name: format!("npc{slot}"),
+                    xp,
+                    ..Default::default()
+                },
+            ))
+            .id();
+        app.world_mut()
+            .resource_mut::<EntityMap>()
+            .register_npc(slot, entity, job, 1, town);
+        entity
+    }
+
+    fn make_officer(app: &mut App, entity: Entity, buff: f32) {
+        app.world_mut().entity_mut(entity).insert(Officer {
+            aura_radius: 200.0,
+            buff,
+        });
+    }
+
+    #[test]
+    fn officer_aura_takes_max_of_overlapping_auras() {
+        let mut app = setup_officer_app(&[(0.0, 0.0), (50.0, 0.0), (100.0, 0.0)]);
+        let a = spawn_town_npc(&mut app, 0, Job::Archer, 0, 0);
+        let b = spawn_town_npc(&mut app, 1, Job::Archer, 0, 0);
+        let soldier = spawn_town_npc(&mut app, 2, Job::Archer, 0, 0);
+        make_officer(&mut app, a, 0.1);
+        make_officer(&mut app, b, 0.25);
+
+        app.update();
+        app.update();
+        let aura = app.world().get::<AuraBuff>(soldier).copied();
+        assert_eq!(aura, Some(AuraBuff(0.25)), "max of overlapping auras");
+        // Officers buff each other but not themselves
+        assert_eq!(
+            app.world().get::<AuraBuff>(a).copied(),
+            Some(AuraBuff(0.25))
+        );
+        assert_eq!(app.world().get::<AuraBuff>(b).copied(), Some(AuraBuff(0.1)));
+    }
+
+    #[test]
+    fn officer_aura_ignores_other_towns_and_out_of_range() {
+        let mut app = setup_officer_app(&[(0.0, 0.0), (50.0, 0.0), (900.0, 0.0)]);
+        let officer = spawn_town_npc(&mut app, 0, Job::Archer, 0, 0);
+        let foreign = spawn_town_npc(&mut app, 1, Job::Archer, 1, 0);
+        let far = spawn_town_npc(&mut app, 2, Job::Archer, 0, 0);
+        make_officer(&mut app, officer, 0.15);
+
+        app.update();
+        app.update();
+        assert!(app.world().get::<AuraBuff>(foreign).is_none());
+        assert!(app.world().get::<AuraBuff>(far).is_none());
+    }
+
+    #[test]
+    fn officer_death_removes_aura() {
+        let mut app = setup_officer_app(&[(0.0, 0.0), (50.0, 0.0)]);
+        let officer = spawn_town_npc(&mut app, 0, Job::Archer, 0, 0);
+        let soldier = spawn_town_npc(&mut app, 1, Job::Archer, 0, 0);
+        make_officer(&mut app, officer, 0.15);
+        app.update();
+        app.update();
+        assert!(app.world().get::<AuraBuff>(soldier).is_some());
+
+        app.world_mut().entity_mut(officer).insert(Dead);
+        app.update();
+        assert!(
+            app.world().get::<AuraBuff>(soldier).is_none(),
+            "dead officer's aura should be gone"
+        );
+    }
+
+    #[test]
+    fn veteran_military_promoted_civilian_not() {
+        let xp = 100 * crate::constants::OFFICER_PROMOTION_LEVEL.pow(2);
+        let mut app = setup_officer_app(&[(0.0, 0.0), (500.0, 0.0), (900.0, 0.0)]);
+        let veteran = spawn_town_npc(&mut app, 0, Job::Archer, 0, xp);
+        let rookie = spawn_town_npc(&mut app, 1, Job::Archer, 0, xp - 1);
+        let farmer = spawn_town_npc(&mut app, 2, Job::Farmer, 0, xp);
+
+        app.update();
+        app.update();
+        assert!(app.world().get::<Officer>(veteran).is_some());
+        assert!(app.world().get::<Officer>(rookie).is_none());
+        assert!(app.world().get::<Officer>(farmer).is_none());
+    }
+
+    // -- target_priority_system ----------------------------------------------
+
+    #[derive(Resource, Default)]
+    struct CollectedFlags(Vec<(usize, u32)>);
+
+    fn collect_flags(mut reader: MessageReader<GpuUpdateMsg>, mut out: ResMut<CollectedFlags>) {
+        for msg in reader.read() {
+            if let GpuUpdate::SetFlags { idx, flags } = msg.0 {
+                out.0.push((idx, flags));
+            }
+        }
+    }
+
+    fn test_stats(damage: f32, cooldown: f32) -> CachedStats {
+        CachedStats {
+            damage,
+            range: 100.0,
+            cooldown,
+            projectile_speed: 0.0,
+            projectile_lifetime: 0.0,
+            max_health: 100.0,
+            speed: 100.0,
+            stamina: 1.0,
+            hp_regen: 0.0,
+            berserk_bonus: 0.0,
+        }
+    }
+
+    #[test]
+    fn fatigue_scales_with_energy_and_hardy() {
+        // Rested units fight at full strength
+        assert_eq!(fatigue_factor(80.0, 30.0, 0.4, 0.0), 1.0);
+        assert_eq!(fatigue_factor(30.0, 30.0, 0.4, 0.0), 1.0);
+        // Linear down to 1 - penalty at 0 energy
+        assert!((fatigue_factor(15.0, 30.0, 0.4, 0.0) - 0.8).abs() < 1e-5);
+        assert!((fatigue_factor(0.0, 30.0, 0.4, 0.0) - 0.6).abs() < 1e-5);
+        // Hardy halves the penalty, Frail makes it worse
+        assert!((fatigue_factor(0.0, 30.0, 0.4, 1.0) - 0.8).abs() < 1e-5);
+        assert!((fatigue_factor(0.0, 30.0, 0.4, -1.0) - 0.4).abs() < 1e-5);
+        // Threshold 0 turns fatigue off
+        assert_eq!(fatigue_factor(0.0, 0.0, 0.4, 0.0), 1.0);
+    }
+
+    #[test]
+    fn combined_penalties_respect_floor() {
+        assert_eq!(combat_modifiers(1.0, false, 0.35), (1.0, 1.0));
+        let (dmg, cd) = combat_modifiers(1.0, true, 0.35);
+        assert_eq!(dmg, 1.0);
+        assert_eq!(cd, crate::constants::SUPPLY_COOLDOWN_MULT);
+        // Exhausted and cut off: 0.4 * 0.4 / 1.5 ≈ 0.11 DPS, lifted to the floor
+        let (dmg, cd) = combat_modifiers(0.4, true, 0.35);
+        assert!((dmg / cd - 0.35).abs() < 1e-4);
+        assert!(dmg > 0.4 && cd < 1.5 / 0.4);
+        // Even a zero fatigue factor leaves the unit at the floor
+        let (dmg, cd) = combat_modifiers(0.0, true, 0.35);
+        assert!((dmg / cd - 0.35).abs() < 1e-4);
+    }
+
+    fn priority_bits(flags: u32) -> u32 {
+        (flags >> crate::constants::ENTITY_FLAG_PRIORITY_SHIFT) & 3
+    }
+
+    /// CPU mirror of the npc_compute.wgsl candidate comparison (profile key, then distance).
+    fn pick_target(flags: u32, candidates: &[(f32, f32, u32)]) -> Option<usize> {
+        let priority = priority_bits(flags);
+        let mut best: Option<(f32, f32, usize)> = None;
+        for (i, &(dist_sq, hp, other_flags)) in candidates.iter().enumerate() {
+            let key = match priority {
+                1 => hp,
+                2 => -(((other_flags >> crate::constants::ENTITY_FLAG_THREAT_SHIFT) & 0xFF) as f32),
+                _ => 0.0,
+            };
+            if best.is_none_or(|(bk, bd, _)| key < bk || (key == bk && dist_sq < bd)) {
+                best = Some((key, dist_sq, i));
+            }
+        }
+        best.map(|(_, _, i)| i)
+    }
+
+    #[test]
+    fn squad_lowest_hp_focuses_wounded_over_closer() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<GpuUpdateMsg>();
+        app.insert_resource(crate::resources::SquadState::default());
+        app.insert_resource(CollectedFlags::default());
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_secs_f32(1.0),
+        ));
+        app.add_systems(FixedUpdate, (target_priority_system, collect_flags).chain());
+        let archer = app
+            .world_mut()
+            .spawn((GpuSlot(3), Job::Archer, test_stats(10.0, 1.0), SquadId(0)))
+            .id();
+        app.world_mut()
+            .resource_mut::<crate::resources::SquadState>()
+            .squads[0]
+            .members
+            .push(archer);
+        app.update();
+        app.update();
+        app.world_mut().resource_mut::<CollectedFlags>().0.clear();
+
+        app.world_mut()
+            .resource_mut::<crate::resources::SquadState>()
+            .squads[0]
+            .target_priority = TargetPriority::LowestHp;
+        app.update();
+        let flags = app.world().resource::<CollectedFlags>().0.last().copied();
+        let (idx, flags) = flags.expect("squad change should re-flag members");
+        assert_eq!(idx, 3);
+        assert_eq!(flags & crate::constants::ENTITY_FLAG_COMBAT, 1);
+        assert_eq!(priority_bits(flags), TargetPriority::LowestHp as u32);
+
+        // Closer full-HP enemy vs farther wounded enemy
+        let candidates = [(100.0, 100.0, 0), (2500.0, 20.0, 0)];
+        assert_eq!(pick_target(flags, &candidates), Some(1));
+        let nearest = npc_gpu_flags(Job::Archer, TargetPriority::Nearest, 0);
+        assert_eq!(pick_target(nearest, &candidates), Some(0));
+    }
+
+    #[test]
+    fn unit_priority_overrides_squad() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<GpuUpdateMsg>();
+        let mut squads = crate::resources::SquadState::default();
+        squads.squads[0].target_priority = TargetPriority::LowestHp;
+        app.insert_resource(squads);
+        app.insert_resource(CollectedFlags::default());
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_secs_f32(1.0),
+        ));
+        app.add_systems(FixedUpdate, (target_priority_system, collect_flags).chain());
+        app.world_mut().spawn((
+            GpuSlot(0),
+            Job::Fighter,
+            test_stats(10.0, 1.0),
+            SquadId(0),
+            TargetPriority::HighestThreat,
+        ));
+        app.update();
+        app.update();
+        let (_, flags) = *app.world().resource::<CollectedFlags>().0.last().unwrap();
+        assert_eq!(priority_bits(flags), TargetPriority::HighestThreat as u32);
+    }
+
+    #[test]
+    fn stance_sets_passive_bit_until_provoked() {
+        use crate::constants::ENTITY_FLAG_PASSIVE;
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<GpuUpdateMsg>();
+        app.insert_resource(crate::resources::SquadState::default());
+        app.insert_resource(GameTime::default());
+        app.insert_resource(CollectedFlags::default());
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_secs_f32(1.0),
+        ));
+        app.add_systems(
+            FixedUpdate,
+            (return_fire_system, target_priority_system, collect_flags).chain(),
+        );
+        let archer = app
+            .world_mut()
+            .spawn((
+                GpuSlot(0),
+                Job::Archer,
+                test_stats(10.0, 1.0),
+                CombatStance::ReturnFire,
+            ))
+            .id();
+        app.update();
+        app.update();
+        let (_, flags) = *app.world().resource::<CollectedFlags>().0.last().unwrap();
+        assert_ne!(
+            flags & ENTITY_FLAG_PASSIVE,
+            0,
+            "unprovoked ReturnFire is passive"
+        );
+        assert_eq!(flags & crate::constants::ENTITY_FLAG_COMBAT, 1);
+
+        // Provocation clears the bit, then expires and restores it
+        app.world_mut().resource_mut::<CollectedFlags>().0.clear();
+        app.world_mut().entity_mut(archer).insert(Provoked(0.05));
+        app.update();
+        let seen = app.world().resource::<CollectedFlags>().0.clone();
+        assert!(seen.iter().any(|&(_, f)| f & ENTITY_FLAG_PASSIVE == 0));
+        assert_ne!(seen.last().unwrap().1 & ENTITY_FLAG_PASSIVE, 0);
+        assert!(app.world().get::<Provoked>(archer).is_none());
+
+        // HoldFire ignores provocation; FireAtWill is never passive
+        app.world_mut()
+            .entity_mut(archer)
+            .insert((CombatStance::HoldFire, Provoked(10.0)));
+        app.update();
+        let (_, flags) = *app.world().resource::<CollectedFlags>().0.last().unwrap();
+        assert_ne!(flags & ENTITY_FLAG_PASSIVE, 0);
+        app.world_mut().entity_mut(archer).remove::<CombatStance>();
+        app.update();
+        let (_, flags) = *app.world().resource::<CollectedFlags>().0.last().unwrap();
+        assert_eq!(flags & ENTITY_FLAG_PASSIVE, 0);
+    }
+
+    #[test]
+    fn target_commit_sets_flag_until_expired() {
+        use crate::constants::ENTITY_FLAG_COMMITTED;
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<GpuUpdateMsg>();
+        app.insert_resource(crate::resources::SquadState::default());
+        app.insert_resource(GameTime::default());
+        app.insert_resource(CollectedFlags::default());
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_secs_f32(1.0),
+        ));
+        app.add_systems(
+            FixedUpdate,
+            (target_commit_system, target_priority_system, collect_flags).chain(),
+        );
+        let fighter = app
+            .world_mut()
+            .spawn((
+                GpuSlot(0),
+                Job::Fighter,
+                test_stats(10.0, 1.0),
+                TargetCommit {
+                    target: 5,
+                    remaining: 0.05,
+                },
+            ))
+            .id();
+        app.update();
+        app.update();
+        let seen = app.world().resource::<CollectedFlags>().0.clone();
+        assert!(seen.iter().any(|&(_, f)| f & ENTITY_FLAG_COMMITTED != 0));
+        assert_eq!(seen.last().unwrap().1 & ENTITY_FLAG_COMMITTED, 0);
+        assert!(app.world().get::<TargetCommit>(fighter).is_none());
+    }
+
+    #[test]
+    fn anchored_bit_follows_component() {
+        use crate::constants::ENTITY_FLAG_ANCHORED;
+        let mut app = App::new();
+        app.add_message::<GpuUpdateMsg>();
+        app.insert_resource(crate::resources::SquadState::default());
+        app.insert_resource(CollectedFlags::default());
+        app.add_systems(Update, (target_priority_system, collect_flags).chain());
+        let npc = app
+            .world_mut()
+            .spawn((GpuSlot(0), Job::Farmer, test_stats(10.0, 1.0)))
+            .id();
+        app.update();
+        app.world_mut().entity_mut(npc).insert(Anchored);
+        app.update();
+        let (_, flags) = *app.world().resource::<CollectedFlags>().0.last().unwrap();
+        assert_ne!(flags & ENTITY_FLAG_ANCHORED, 0);
+        app.world_mut().entity_mut(npc).remove::<Anchored>();
+        app.update();
+        let (_, flags) = *app.world().resource::<CollectedFlags>().0.last().unwrap();
+        assert_eq!(flags & ENTITY_FLAG_ANCHORED, 0);
+    }
+
+    #[test]
+    fn threat_ranks_officers_above_peers_and_ignores_civilians() {
+        let stats = test_stats(12.0, 1.5);
+        let soldier = threat_value(Job::Archer, &stats, false);
+        let officer = threat_value(Job::Archer, &stats, true);
+        assert_eq!(soldier, 8);
+        assert!(officer > soldier);
+        assert_eq!(threat_value(Job::Farmer, &stats, false), 0);
+        assert_eq!(
+            threat_value(Job::Archer, &test_stats(9999.0, 0.1), true),
+            255
+        );
+
+        let me = npc_gpu_flags(Job::Archer, TargetPriority::HighestThreat, 0);
+        let candidates = [
+            (
+                100.0,
+                50.0,
+                npc_gpu_flags(Job::Archer, TargetPriority::Nearest, soldier),
+            ),
+            (
+                900.0,
+                50.0,
+                npc_gpu_flags(Job::Archer, TargetPriority::Nearest, officer),
+            ),
+            (
+                50.0,
+                50.0,
+                npc_gpu_flags(Job::Farmer, TargetPriority::Nearest, 0),
+            ),
+        ];
+        assert_eq!(pick_target(me, &candidates), Some(1));
+    }
+
+    #[test]
+    fn manual_target_valid_requires_alive_enemy() {
+        let mut em = EntityMap::default();
+        let e = |n| Entity::from_raw_u32(n).unwrap();
+        em.register_npc(1, e(1), Job::Raider, 2, -1);
+        em.register_npc(2, e(2), Job::Archer, 1, 0);
+        em.register_npc(3, e(3), Job::Farmer, crate::constants::FACTION_NEUTRAL, -1);
+        assert!(manual_target_valid(&em, 1, 1));
+        assert!(!manual_target_valid(&em, 2, 1), "ally");
+        assert!(!manual_target_valid(&em, 3, 1), "neutral");
+        assert!(!manual_target_valid(&em, 9, 1), "empty slot");
+        em.get_npc_mut(1).unwrap().dead = true;
+        assert!(!manual_target_valid(&em, 1, 1), "dead");
+    }
+
+    #[test]
+    fn aggro_chase_respects_timer_and_leash() {
+        let origin = Vec2::new(0.0, 0.0);
+        let mem = AggroMemory {
+            last_pos: Vec2::new(300.0, 0.0),
+            remaining: 1.5,
+        };
+        assert_eq!(aggro_chase(origin, &mem, None), Some(mem.last_pos));
+        assert_eq!(aggro_chase(origin, &mem, Some(400.0)), Some(mem.last_pos));
+        // Last-known position past the leash: break off instead of chasing
+        assert_eq!(aggro_chase(origin, &mem, Some(200.0)), None);
+        let spent = AggroMemory {
+            remaining: 0.0,
+            ..mem
+        };
+        assert_eq!(aggro_chase(origin, &spent, None), None);
+    }
+
+    #[test]
+    fn lead_intercept_meets_moving_target() {
+        let src = Vec2::ZERO;
+        let target = Vec2::new(200.0, 0.0);
+        let vel = Vec2::new(0.0, 100.0);
+        let aim = lead_intercept(src, target, vel, 200.0, 10.0);
+        // Projectile and target reach the aim point at the same time
+        let t_proj = aim.length() / 200.0;
+        let t_target = (aim - target).length() / 100.0;
+        assert!((t_proj - t_target).abs() < 1e-3);
+        assert!(aim.y > 0.0);
+
+        // Stationary (or jittering) target: aim straight at it
+        assert_eq!(
+            lead_intercept(src, target, Vec2::new(3.0, -2.0), 200.0, 10.0),
+            target
+        );
+        // Target outrunning the projectile away from us: no intercept
+        assert_eq!(
+            lead_intercept(src, target, Vec2::new(300.0, 0.0), 200.0, 10.0),
+            target
+        );
+        // Intercept exists but after the projectile expires
+        assert_eq!(lead_intercept(src, target, vel, 200.0, 0.5), target);
+    }
+
+    #[test]
+    fn npc_velocities_follow_readback_deltas() {
+        let mut v = NpcVelocities::default();
+        v.observe(&[0.0, 0.0, 50.0, 50.0], 0.0);
+        v.observe(&[10.0, 0.0, 50.0, 50.0], 0.1);
+        v.observe(&[20.0, 0.0, 50.0, 50.0], 0.2);
+        assert!((v.velocity(0).x - 75.0).abs() < 1e-3);
+        assert_eq!(v.velocity(1), Vec2::ZERO);
+        // A tick with no fresh readback keeps the estimate...
+        v.observe(&[20.0, 0.0, 50.0, 50.0], 0.3);
+        assert!(v.velocity(0).x > 0.0);
+        // ...until the target has sat still long enough
+        v.observe(&[20.0, 0.0, 50.0, 50.0], 1.0);
+        assert_eq!(v.velocity(0), Vec2::ZERO);
+        // Slot reuse or teleport is not movement
+        v.observe(&[5000.0, 0.0, 50.0, 50.0], 1.1);
+        assert_eq!(v.velocity(0), Vec2::ZERO);
+    }
+
+    #[test]
+    fn facing_follows_movement_then_target_smoothly() {
+        let mut v = NpcVelocities::default();
+        let mut facing = NpcFacing::default();
+        // Slot 0 walks east; slot 1 stands still targeting slot 0
+        v.observe(&[0.0, 0.0, 100.0, 100.0], 0.0);
+        v.observe(&[10.0, 0.0, 100.0, 100.0], 0.1);
+        let positions = [10.0, 0.0, 100.0, 100.0];
+        facing.update(&v, &positions, &[-1, 0], 2);
+        assert_eq!(facing.facing(0), Vec2::X);
+        let to_target = (Vec2::new(10.0, 0.0) - Vec2::new(100.0, 100.0)).normalize();
+        assert!(facing.facing(1).distance(to_target) < 1e-4);
+
+        // Turning around swings the heading over several ticks instead of snapping
+        v.observe(&[0.0, 0.0, 100.0, 100.0], 0.2);
+        facing.update(&v, &positions, &[-1, 0], 2);
+        let mid = facing.facing(0);
+        assert!(mid.x > -1.0 + 1e-3, "no instant flip: {mid}");
+        for _ in 0..20 {
+            facing.update(&v, &positions, &[-1, 0], 2);
+        }
+        assert!(facing.facing(0).x < -0.99);
+
+        // Stopped with no target keeps the last heading; a dead slot forgets it
+        v.observe(&[0.0, 0.0, 100.0, 100.0], 2.0);
+        facing.update(&v, &positions, &[-1, -1], 2);
+        assert!(facing.facing(0).x < -0.99);
+        facing.update(&v, &[-9999.0, -9999.0, 100.0, 100.0], &[-1, -1], 2);
+        assert_eq!(facing.facing(0), Vec2::ZERO);
+    }
 }
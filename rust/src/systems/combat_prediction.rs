@@ -0,0 +1,319 @@
+//! Combat prediction — fast aggregate estimate of a fight between two `ArmySpec`s.
+//! Not the sim: each side collapses to total HP and total expected DPS (stats from
+//! `resolve_combat_stats`, attack period = cooldown + windup, `CombatRng` miss/crit/dodge
+//! as expected values, berserk as an average over the lower half of HP), the longer-ranged
+//! side gets free fire while the other closes the range gap, then Lanchester's square law
+//! decides. Formation, pathing, focus fire, overkill and projectile travel are ignored, so
+//! real battles can diverge — calibrate against `endless/quick_battle` when tuning.
+
+use crate::components::{CachedStats, Job, TraitInstance, TraitKind};
+use crate::constants::npc_def;
+use crate::resources::CombatRng;
+use crate::systems::quick_battle::ArmySpec;
+use crate::systems::stats::{CombatConfig, resolve_combat_stats};
+
+/// Sharpness of the win-probability curve over the strength ratio.
+const WIN_CURVE_EXPONENT: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ForceTotals {
+    count: f32,
+    hp: f32,
+    dps: f32,
+    range: f32,
+    speed: f32,
+}
+
+impl ForceTotals {
+    /// Lanchester square-law fighting strength.
+    fn strength(&self) -> f32 {
+        self.hp * self.dps
+    }
+}
+
+/// A calibration fight and how far real survivor counts may drift from the prediction.
+#[derive(Clone, Debug)]
+pub struct CannedMatchup {
+    pub name: &'static str,
+    pub armies: [ArmySpec; 2],
+    /// Allowed gap (units) between predicted and actual survivors of the winner.
+    pub survivor_tolerance: usize,
+}
+
+/// Matchups the `combat-prediction` test runs as real quick battles. Mirror fights are left
+/// out: their winner is a coin flip the model doesn't call.
+pub fn canned_matchups() -> Vec<CannedMatchup> {
+    let army = |job: Job, n: usize| ArmySpec {
+        units: vec![(job, n)],
+        ..Default::default()
+    };
+    vec![
+        // Square law leaves ~17 of 20 (sqrt(3/4) * 20)
+        CannedMatchup {
+            name: "fighters 20v10",
+            armies: [army(Job::Fighter, 20), army(Job::Fighter, 10)],
+            survivor_tolerance: 4,
+        },
+        // Level and Power traits tilt an even fight
+        CannedMatchup {
+            name: "veterans 10v10",
+            armies: [
+                ArmySpec {
+                    level: 50,
+                    traits: vec![TraitInstance {
+                        kind: TraitKind::Power,
+                        magnitude: 1.0,
+                    }],
+                    ..army(Job::Fighter, 10)
+                },
+                army(Job::Fighter, 10),
+            ],
+            survivor_tolerance: 4,
+        },
+        // Free fire while the melee line closes the gap
+        CannedMatchup {
+            name: "archers 15v5",
+            armies: [army(Job::Archer, 15), army(Job::Fighter, 5)],
+            survivor_tolerance: 5,
+        },
+    ]
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CombatPrediction {
+    /// 0 = side a, 1 = side b, None = draw (or both sides empty).
+    pub winner: Option<usize>,
+    /// Estimated chance each side wins; sums to 1.
+    pub win_probability: [f32; 2],
+    /// Estimated units left standing per side (the loser is always 0).
+    pub survivors: [usize; 2],
+    /// 0..1: how decisive the matchup is, discounted for combat variance and small forces.
+    pub confidence: f32,
+}
+
+fn force_totals(
+    army: &ArmySpec,
+    enemy: &ArmySpec,
+    config: &CombatConfig,
+    rng: &CombatRng,
+) -> ForceTotals {
+    let personality = army.personality();
+    let enemy_agility = enemy.personality().magnitude(TraitKind::Agility);
+    let precision = personality.magnitude(TraitKind::Precision);
+    let hit = if rng.enabled() {
+        let miss = (rng.miss_chance + rng.swift_dodge * enemy_agility).clamp(0.0, 1.0);
+        let crit = (rng.crit_chance + rng.sharpshot_crit * precision).clamp(0.0, 1.0);
+        (1.0 - miss) * (1.0 + crit * (rng.crit_mult.max(1.0) - 1.0))
+    } else {
+        1.0
+    };
+    let mut t = ForceTotals::default();
+    for &(job, n) in &army.units {
+        if n == 0 {
+            continue;
+        }
+        let stats = unit_stats(job, army, config);
+        let n = n as f32;
+        let period = (stats.cooldown + config.attack_windup).max(0.05);
+        let berserk = 1.0 + stats.berserk_bonus * 0.5;
+        t.count += n;
+        t.hp += n * stats.max_health;
+        t.dps += n * stats.damage * berserk * hit / period;
+        t.range += n * stats.range;
+        t.speed += n * stats.speed;
+    }
+    if t.count > 0.0 {
+        t.range /= t.count;
+        t.speed /= t.count;
+    }
+    t
+}
+
+fn unit_stats(job: Job, army: &ArmySpec, config: &CombatConfig) -> CachedStats {
+    resolve_combat_stats(
+        job,
+        npc_def(job).default_attack_type,
+        -1,
+        army.level,
+        &army.personality(),
+        config,
+        &[],
+        0.0,
+        0.0,
+    )
+}
+
+/// Estimate the outcome of `a` vs `b` (no town upgrades or equipment, as in a quick battle).
+pub fn predict_combat(
+    a: &ArmySpec,
+    b: &ArmySpec,
+    config: &CombatConfig,
+    rng: &CombatRng,
+) -> CombatPrediction {
+    let mut sides = [
+        force_totals(a, b, config, rng),
+        force_totals(b, a, config, rng),
+    ];
+    let sizes = [a.size(), b.size()];
+
+    // Empty forces: nothing to estimate
+    match sizes {
+        [0, 0] => {
+            return CombatPrediction {
+                winner: None,
+                win_probability: [0.5, 0.5],
+                survivors: [0, 0],
+                confidence: 1.0,
+            };
+        }
+        [_, 0] | [0, _] => {
+            let w = usize::from(sizes[0] == 0);
+            let mut win_probability = [0.0; 2];
+            win_probability[w] = 1.0;
+            return CombatPrediction {
+                winner: Some(w),
+                win_probability,
+                survivors: sizes,
+                confidence: 1.0,
+            };
+        }
+        _ => {}
+    }
+
+    // Free fire while the shorter-ranged side closes the range difference
+    let (long, short) = if sides[0].range >= sides[1].range {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    let gap = sides[long].range - sides[short].range;
+    if gap > 0.0 && sides[short].speed > 0.0 {
+        let free_damage = sides[long].dps * gap / sides[short].speed;
+        let lost = (free_damage / sides[short].hp).min(1.0);
+        let s = &mut sides[short];
+        s.hp -= s.hp * lost;
+        s.dps -= s.dps * lost;
+        s.count -= s.count * lost;
+    }
+
+    let strength = [sides[0].strength(), sides[1].strength()];
+    let pa = if strength[0] + strength[1] <= 0.0 {
+        0.5
+    } else {
+        let (fa, fb) = (
+            strength[0].powf(WIN_CURVE_EXPONENT),
+            strength[1].powf(WIN_CURVE_EXPONENT),
+        );
+        fa / (fa + fb)
+    };
+    let winner = if strength[0] > strength[1] {
+        Some(0)
+    } else if strength[1] > strength[0] {
+        Some(1)
+    } else {
+        None
+    };
+    let mut survivors = [0; 2];
+    if let Some(w) = winner {
+        // Square law: the winner keeps sqrt(1 - F_loser / F_winner) of its force
+        let frac = (1.0 - strength[1 - w] / strength[w]).max(0.0).sqrt();
+        survivors[w] = ((sides[w].count * frac).round() as usize).clamp(1, sizes[w]);
+    }
+
+    // Variance (CombatRng) and small forces make the outcome less certain
+    let variance = if rng.enabled() {
+        (rng.miss_chance + rng.crit_chance + rng.damage_spread).clamp(0.0, 1.0) * 0.5
+    } else {
+        0.0
+    };
+    let smallest = sizes[0].min(sizes[1]) as f32;
+    let confidence = ((pa - 0.5).abs() * 2.0) * (1.0 - variance) * (smallest / (smallest + 2.0));
+
+    CombatPrediction {
+        winner,
+        win_probability: [pa, 1.0 - pa],
+        survivors,
+        confidence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn army(job: Job, n: usize) -> ArmySpec {
+        ArmySpec {
+            units: vec![(job, n)],
+            ..Default::default()
+        }
+    }
+
+    fn predict(a: &ArmySpec, b: &ArmySpec) -> CombatPrediction {
+        predict_combat(a, b, &CombatConfig::default(), &CombatRng::default())
+    }
+
+    #[test]
+    fn empty_forces() {
+        let none = ArmySpec::default();
+        let p = predict(&none, &none);
+        assert_eq!((p.winner, p.survivors), (None, [0, 0]));
+        let p = predict(&none, &army(Job::Fighter, 3));
+        assert_eq!((p.winner, p.survivors), (Some(1), [0, 3]));
+        assert_eq!(p.win_probability, [0.0, 1.0]);
+    }
+
+    /// Same-job fights follow the square law; level, traits and variance shift it.
+    #[test]
+    fn prediction_follows_square_law() {
+        let mirror = predict(&army(Job::Fighter, 10), &army(Job::Fighter, 10));
+        assert_eq!(mirror.winner, None);
+        assert!((mirror.win_probability[0] - 0.5).abs() < 1e-4);
+        assert!(mirror.confidence < 0.01);
+
+        // 20 vs 10 of the same unit: square law leaves ~17 (sqrt(3/4) * 20)
+        let two_to_one = predict(&army(Job::Fighter, 20), &army(Job::Fighter, 10));
+        assert_eq!(two_to_one.winner, Some(0));
+        assert!((15..=19).contains(&two_to_one.survivors[0]));
+        assert!(two_to_one.win_probability[0] > 0.9);
+        assert!(two_to_one.confidence > 0.7);
+
+        // Level and Power traits tilt an even fight
+        let mut strong = army(Job::Fighter, 10);
+        strong.level = 50;
+        strong.traits = vec![TraitInstance {
+            kind: TraitKind::Power,
+            magnitude: 1.0,
+        }];
+        let p = predict(&strong, &army(Job::Fighter, 10));
+        assert_eq!(p.winner, Some(0));
+        assert!(p.win_probability[0] > 0.6);
+
+        // Combat variance lowers confidence but not the favourite
+        let rng = CombatRng {
+            miss_chance: 0.2,
+            damage_spread: 0.3,
+            ..Default::default()
+        };
+        let noisy = predict_combat(
+            &army(Job::Fighter, 20),
+            &army(Job::Fighter, 10),
+            &CombatConfig::default(),
+            &rng,
+        );
+        assert_eq!(noisy.winner, Some(0));
+        assert!(noisy.confidence < two_to_one.confidence);
+    }
+
+    /// The `combat-prediction` test checks the real battles against these calls, so each
+    /// must name a winner with survivors to compare.
+    #[test]
+    fn canned_matchups_are_decisive() {
+        for m in canned_matchups() {
+            let p = predict(&m.armies[0], &m.armies[1]);
+            let winner = p
+                .winner
+                .unwrap_or_else(|| panic!("{}: no favourite", m.name));
+            assert!(p.survivors[winner] > 0, "{}", m.name);
+        }
+    }
+}
@@ -12,7 +12,9 @@
 use crate::components::*;
 use crate::constants::UpgradeStatKind;
 use crate::constants::*;
-use crate::messages::{CombatLogMsg, GpuUpdate, GpuUpdateMsg, WorkIntent, WorkIntentMsg};
+use crate::messages::{
+    CombatLogMsg, FarmEventKind, FarmEventMsg, GpuUpdate, GpuUpdateMsg, WorkIntent, WorkIntentMsg,
+};
 use crate::resources::{
     CombatEventKind, DEFAULT_LOOT_THRESHOLD, EntityMap, GameTime, GpuReadState, MovementPriority,
     OffDutyBehavior, PathRequestQueue, SquadState, WorkSchedule,
@@ -262,7 +264,10 @@ pub fn decision_system(
 
     let npc_logs = &mut extras.npc_logs;
     let combat_log = &mut extras.combat_log;
+    let farm_events = &mut extras.farm_events;
     let squad_state = &extras.squad_state;
+    let lod = &extras.behavior_lod;
+    let energy_thresholds = &extras.energy_thresholds;
     let frame = DECISION_FRAME.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     let positions = &gpu_state.positions;
 
@@ -297,13 +302,18 @@ pub fn decision_system(
                 continue;
             }
         } else {
-            if !(idx + frame).is_multiple_of(think_buckets) {
+            // Behavior LOD: off-screen NPCs think `stride`x less often (same slot bucketing).
+            let pos = positions
+                .get(idx * 2..idx * 2 + 2)
+                .map_or(Vec2::ZERO, |p| Vec2::new(p[0], p[1]));
+            if !(idx + frame).is_multiple_of(think_buckets * lod.stride_at(pos, false)) {
                 continue;
             }
         }
 
         // Full component reads — only for NPCs that passed the bucket gate
         let job = *job;
+        let energy_t = energy_thresholds.get(job);
         let town_idx_i32 = town_id.0;
         let faction_i32 = faction.0;
         let mut energy = npc_state.energy_q.get(entity).map(|e| e.0).unwrap_or(100.0);
@@ -333,6 +343,8 @@ pub fn decision_system(
             .unwrap_or(false);
         let squad_id = npc_state.squad_id_q.get(entity).ok().map(|s| s.0);
         let manual_target = npc_state.manual_target_q.get(entity).ok().cloned();
+        let reinforcing = npc_state.reinforcing_q.contains(entity);
+        let last_stand = npc_state.last_stand_q.contains(entity);
         let direct_control = npc_state
             .npc_flags_q
             .get(entity)
@@ -447,7 +459,7 @@ pub fn decision_system(
                         // Squad rest: tired squad members go home instead of entering OnDuty
                         if let Some(sid) = squad_id {
                             if let Some(squad) = squad_state.squads.get(sid as usize) {
-                                if squad.rest_when_tired && energy < ENERGY_TIRED_THRESHOLD {
+                                if squad.rest_when_tired && energy < energy_t.rest_below {
                                     if transition_to_rest(
                                         &mut activity,
                                         &mut intents,
@@ -635,6 +647,11 @@ pub fn decision_system(
                                                 let pos = entity_map
                                                     .get_instance(farm_slot)
                                                     .map_or(Vec2::ZERO, |i| i.position);
+                                                farm_events.write(FarmEventMsg {
+                                                    kind: FarmEventKind::Harvested,
+                                                    farm_slot,
+                                                    position: pos,
+                                                });
                                                 Some((
                                                     food,
                                                     ProductionState::harvest_log_msg(
@@ -802,13 +819,23 @@ pub fn decision_system(
                             });
 
                             if let Some(fp) = ready_farm_pos {
+                                let Some(farm_slot) = entity_map.slot_at_position(fp) else {
+                                    continue;
+                                };
                                 let food = entity_map
-                                    .slot_at_position(fp)
-                                    .and_then(|slot| entity_map.entities.get(&slot).copied())
+                                    .entities
+                                    .get(&farm_slot)
+                                    .copied()
                                     .and_then(|e| production_q.get_mut(e).ok())
                                     .map(|mut ps| {
                                         let f = ps.harvest(BuildingKind::Farm);
                                         if f > 0 {
+                                            // Stolen crops count as a harvest
+                                            farm_events.write(FarmEventMsg {
+                                                kind: FarmEventKind::Harvested,
+                                                farm_slot,
+                                                position: fp,
+                                            });
                                             combat_log.write(CombatLogMsg {
                                                 kind: CombatEventKind::Harvest,
                                                 faction: faction_i32,
@@ -1170,8 +1197,8 @@ pub fn decision_system(
             // ====================================================================
             if let Some(sid) = squad_id {
                 if let Some(squad) = squad_state.squads.get(sid as usize) {
-                    let squad_needs_rest = energy < ENERGY_TIRED_THRESHOLD
-                        || (energy < ENERGY_WAKE_THRESHOLD && activity.kind == ActivityKind::Rest);
+                    let squad_needs_rest = energy < energy_t.rest_below
+                        || (energy < energy_t.resume_above && activity.kind == ActivityKind::Rest);
                     if squad.rest_when_tired && squad_needs_rest {
                         if combat_state.is_fighting() {
                             combat_state = CombatState::None;
@@ -1231,7 +1258,9 @@ pub fn decision_system(
                 } else {
                     (flee_pct + flee_mods.flee_threshold_add).clamp(0.0, 1.0)
                 };
-                if flee_pct > 0.0 {
+                // Routed by panic_system: flee regardless of HP until the rout ends
+                let routed = extras.panic.is_routed(idx);
+                if flee_pct > 0.0 || routed {
                     let should_check_threat = (frame + idx).is_multiple_of(CHECK_INTERVAL);
                     let effective_threshold = if should_check_threat {
                         let packed = gpu_state.threat_counts.get(idx).copied().unwrap_or(0);
@@ -1242,7 +1271,8 @@ pub fn decision_system(
                         flee_pct
                     };
 
-                    if health / max_hp < effective_threshold {
+                    if routed || health / max_hp < effective_threshold {
+                        extras.panic.mark_fleeing(idx, game_time.total_seconds);
                         // Clean up work state if fleeing mid-work
                         if activity.kind.def().is_working {
                             let uid = worksite.and_then(|s| entity_map.entities.get(&s).copied());
@@ -1329,16 +1359,22 @@ pub fn decision_system(
                 }
 
                 // Priority 2: Should leash? (per-entity LeashRange or policy archer_leash)
-                let should_leash = match job {
-                    Job::Archer | Job::Crossbow => economy
-                        .towns
-                        .policy(town_idx_i32)
-                        .as_ref()
-                        .is_none_or(|p| p.archer_leash),
-                    _ => leash_range_val.is_some(),
-                };
+                // Faction stance: Passive/Defensive always leash, Aggressive ranges further.
+                let (stance_leash, stance_mult) = extras
+                    .faction_list
+                    .stance(faction_i32)
+                    .map_or((false, 1.0), |st| st.leash());
+                let should_leash = stance_leash
+                    || match job {
+                        Job::Archer | Job::Crossbow => economy
+                            .towns
+                            .policy(town_idx_i32)
+                            .as_ref()
+                            .is_none_or(|p| p.archer_leash),
+                        _ => leash_range_val.is_some(),
+                    };
                 if should_leash {
-                    let leash_dist = leash_range_val.unwrap_or(400.0);
+                    let leash_dist = leash_range_val.unwrap_or(400.0) * stance_mult;
                     if let CombatState::Fighting { origin } = &combat_state {
                         if let Some(pos) = npc_pos {
                             let dx = pos.x - origin.x;
@@ -1405,8 +1441,8 @@ pub fn decision_system(
                     // Skip the rest of squad sync for this NPC.
                 } else if let Some(squad) = squad_state.squads.get(sid as usize) {
                     if let Some(target) = squad.target {
-                        let squad_needs_rest = energy < ENERGY_TIRED_THRESHOLD
-                            || (energy < ENERGY_WAKE_THRESHOLD
+                        let squad_needs_rest = energy < energy_t.rest_below
+                            || (energy < energy_t.resume_above
                                 && activity.kind == ActivityKind::Rest);
                         if squad.rest_when_tired && squad_needs_rest {
                             if activity.kind != ActivityKind::Rest {
@@ -1516,6 +1552,19 @@ pub fn decision_system(
                 }
             }
 
+            // ====================================================================
+            // Reinforcing a town alert / falling back for a last stand: reinforce_system and
+            // last_stand_system own the trip and the release. Tired guards fall through to
+            // idle scoring (which drops the tag).
+            // ====================================================================
+            if (reinforcing || last_stand)
+                && squad_id.is_none()
+                && activity.kind == ActivityKind::SquadAttack
+                && energy >= energy_t.rest_below
+            {
+                break 'decide;
+            }
+
             // ====================================================================
             // Farmer en-route retarget: if target farm became occupied, find another
             // ====================================================================
@@ -1695,7 +1744,7 @@ pub fn decision_system(
             // ====================================================================
             if activity.kind == ActivityKind::Rest {
                 if activity.phase == ActivityPhase::Active {
-                    if energy >= ENERGY_WAKE_THRESHOLD {
+                    if energy >= energy_t.resume_above {
                         transition_activity(
                             &mut activity,
                             ActivityKind::Idle,
@@ -1976,6 +2025,13 @@ pub fn decision_system(
                             .get_instance(slot)
                             .map_or(Vec2::ZERO, |i| i.position);
                         let base_yield = ps.harvest(kind);
+                        if base_yield > 0 && kind == BuildingKind::Farm {
+                            farm_events.write(FarmEventMsg {
+                                kind: FarmEventKind::Harvested,
+                                farm_slot: slot,
+                                position: ws_pos,
+                            });
+                        }
                         if base_yield > 0 {
                             combat_log.write(CombatLogMsg {
                                 kind: CombatEventKind::Harvest,
@@ -2033,7 +2089,7 @@ pub fn decision_system(
                 }
 
                 // Tired check: release worksite and go idle
-                if energy < ENERGY_TIRED_THRESHOLD {
+                if energy < energy_t.rest_below {
                     let uid = worksite.and_then(|s| entity_map.entities.get(&s).copied());
                     extras
                         .work_intents
@@ -2070,7 +2126,7 @@ pub fn decision_system(
                     && squad_id
                         .and_then(|sid| squad_state.squads.get(sid as usize))
                         .is_some_and(|s| !s.rest_when_tired);
-                if energy < ENERGY_TIRED_THRESHOLD && !squad_forces_stay {
+                if energy < energy_t.rest_below && !squad_forces_stay {
                     transition_activity(
                         &mut activity,
                         ActivityKind::Idle,
@@ -2196,8 +2252,10 @@ pub fn decision_system(
                 score_count += 1;
             }
 
-            if en < ENERGY_HUNGRY && has_rest_destination(home_valid, town_center) {
-                let rest_score = (ENERGY_HUNGRY - en) * SCORE_REST_MULT * rest_m;
+            let can_rest = has_rest_destination(home_valid, town_center);
+            let rest_line = energy_t.rest_score_below();
+            if en < rest_line && can_rest {
+                let rest_score = (rest_line - en) * SCORE_REST_MULT * rest_m;
                 scores[score_count] = (Action::Rest, rest_score);
                 score_count += 1;
             }
@@ -2235,9 +2293,15 @@ pub fn decision_system(
                 } else {
                     (hp_pct - 0.3) * (1.0 / 0.7)
                 };
-                // Scale down work desire when tired so rest/eat can win before starvation
-                let energy_factor = if en < ENERGY_TIRED_THRESHOLD {
-                    en / ENERGY_TIRED_THRESHOLD
+                // Below rest_below a unit that can rest must rest (and then wake at
+                // resume_above) before working again, e.g. after combat interrupted its rest.
+                // Homeless units just scale work desire down so eat can win before starvation.
+                let energy_factor = if en < energy_t.rest_below {
+                    if can_rest {
+                        0.0
+                    } else {
+                        en / energy_t.rest_below
+                    }
                 } else {
                     1.0
                 };
@@ -7,8 +7,8 @@ use crate::components::{
 use crate::entity_map::EntityMap;
 use crate::messages::{CombatLogMsg, GpuUpdateMsg, WorkIntentMsg};
 use crate::resources::{
-    GameTime, GpuReadState, NpcDecisionConfig, NpcLogCache, PathRequestQueue, PolicySet,
-    PopulationStats, SelectedNpc, SquadState, TownIndex,
+    FactionList, GameTime, GpuReadState, NpcDecisionConfig, NpcLogCache, PathRequestQueue,
+    PolicySet, PopulationStats, SelectedNpc, SquadState, TownIndex,
 };
 use crate::world::Town;
 use bevy::ecs::system::RunSystemOnce;
@@ -18,6 +18,7 @@ fn setup_decision_app(policy: PolicySet) -> App {
     let mut app = App::new();
     app.add_plugins(MinimalPlugins);
     app.add_message::<CombatLogMsg>();
+    app.add_message::<crate::messages::FarmEventMsg>();
     app.add_message::<crate::messages::DamageMsg>();
     app.add_message::<GpuUpdateMsg>();
     app.add_message::<WorkIntentMsg>();
@@ -52,6 +53,10 @@ fn setup_decision_app(policy: PolicySet) -> App {
     app.insert_resource(EntityMap::default());
     app.insert_resource(SquadState::default());
     app.insert_resource(SelectedNpc::default());
+    app.insert_resource(FactionList::default());
+    app.insert_resource(crate::resources::BehaviorLod::default());
+    app.init_resource::<crate::resources::EnergyThresholds>();
+    app.init_resource::<crate::resources::PanicState>();
     let mut settings = crate::settings::UserSettings::default();
     settings.npc_log_mode = crate::settings::NpcLogMode::All;
     app.insert_resource(settings);
@@ -143,6 +148,55 @@ fn squad_loot_threshold_overrides_town_policy() {
     );
 }
 
+#[test]
+fn rest_wakes_at_job_resume_threshold() {
+    DECISION_FRAME.store(0, std::sync::atomic::Ordering::Relaxed);
+    let mut app = setup_decision_app(PolicySet::default());
+    let npc = app
+        .world_mut()
+        .spawn((
+            GpuSlot(0),
+            Job::Farmer,
+            TownId(0),
+            Faction(1),
+            Energy(60.0),
+            Health(100.0),
+            Home(Vec2::new(320.0, 320.0)),
+            HasEnergy,
+            // Asleep at home: only arrived NPCs reach the wake check
+            NpcFlags {
+                at_destination: true,
+                ..Default::default()
+            },
+            CombatState::None,
+            Activity {
+                kind: ActivityKind::Rest,
+                phase: ActivityPhase::Active,
+                target: ActivityTarget::Home,
+                ..Default::default()
+            },
+            test_cached_stats(),
+        ))
+        .id();
+
+    // Default resume_above (90): keeps resting
+    app.world_mut().run_system_once(decision_system).unwrap();
+    assert_eq!(
+        app.world().get::<Activity>(npc).unwrap().kind,
+        ActivityKind::Rest
+    );
+
+    // Farmers resume at 50: 60 energy is enough to get up
+    app.world_mut()
+        .resource_mut::<crate::resources::EnergyThresholds>()
+        .set(Job::Farmer, 20.0, 50.0);
+    app.world_mut().run_system_once(decision_system).unwrap();
+    assert_ne!(
+        app.world().get::<Activity>(npc).unwrap().kind,
+        ActivityKind::Rest
+    );
+}
+
 #[test]
 fn town_loot_threshold_applies_without_squad() {
     let mut policy = PolicySet::default();
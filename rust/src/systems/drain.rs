@@ -3,14 +3,16 @@
 use bevy::prelude::*;
 
 use crate::messages::*;
-use crate::resources::CombatLog;
+use crate::resources::{
+    ATTACK_ANIM_OUTBOX_CAP, AttackAnimOutbox, CombatLog, FARM_EVENT_OUTBOX_CAP, FarmEventOutbox,
+    GAME_OVER_OUTBOX_CAP, GameOverOutbox, recover_or_log,
+};
 
 /// Drain game config staging into Bevy Resource (one-shot).
 pub fn drain_game_config(mut config: ResMut<crate::resources::GameConfig>) {
-    if let Ok(mut staging) = GAME_CONFIG_STAGING.lock() {
-        if let Some(new_config) = staging.take() {
-            *config = new_config;
-        }
+    let staging = recover_or_log(&GAME_CONFIG_STAGING, "game_config_staging").take();
+    if let Some(new_config) = staging {
+        *config = new_config;
     }
 }
 
@@ -28,3 +30,45 @@ pub fn drain_combat_log(mut msgs: MessageReader<CombatLogMsg>, mut log: ResMut<C
         );
     }
 }
+
+/// Queue FarmEventMsg messages for `endless/farm_events`, keeping the newest
+/// `FARM_EVENT_OUTBOX_CAP`.
+pub fn drain_farm_events(
+    mut msgs: MessageReader<FarmEventMsg>,
+    mut outbox: ResMut<FarmEventOutbox>,
+) {
+    for msg in msgs.read() {
+        if outbox.0.len() >= FARM_EVENT_OUTBOX_CAP {
+            outbox.0.pop_front();
+        }
+        outbox.0.push_back(msg.clone());
+    }
+}
+
+/// Queue AttackAnimMsg messages for `endless/attack_events`, keeping the newest
+/// `ATTACK_ANIM_OUTBOX_CAP`.
+pub fn drain_attack_anim_events(
+    mut msgs: MessageReader<AttackAnimMsg>,
+    mut outbox: ResMut<AttackAnimOutbox>,
+) {
+    for msg in msgs.read() {
+        if outbox.events.len() >= ATTACK_ANIM_OUTBOX_CAP {
+            outbox.events.pop_front();
+        }
+        outbox.events.push_back(msg.clone());
+    }
+}
+
+/// Queue GameOverMsg messages for `endless/game_state`, keeping the newest
+/// `GAME_OVER_OUTBOX_CAP`.
+pub fn drain_game_over_events(
+    mut msgs: MessageReader<GameOverMsg>,
+    mut outbox: ResMut<GameOverOutbox>,
+) {
+    for msg in msgs.read() {
+        if outbox.0.len() >= GAME_OVER_OUTBOX_CAP {
+            outbox.0.pop_front();
+        }
+        outbox.0.push_back(msg.clone());
+    }
+}
@@ -12,7 +12,9 @@ use crate::constants::{
     MIGRATION_BASE_SIZE, RAIDER_FORAGE_RATE, RAIDER_SETTLE_RADIUS, SPAWNER_RESPAWN_HOURS,
     STARVING_HP_CAP, STARVING_SPEED_MULT, TOWN_GRID_SPACING,
 };
-use crate::messages::{CombatLogMsg, GpuUpdate, GpuUpdateMsg, SpawnNpcMsg};
+use crate::messages::{
+    CombatLogMsg, FarmEventKind, FarmEventMsg, GpuUpdate, GpuUpdateMsg, SpawnNpcMsg,
+};
 use crate::resources::*;
 use crate::systemparams::{EconomyState, WorldState};
 use crate::systems::ai_player::{AiKind, AiPersonality, AiPlayer, AiPlayerState};
@@ -152,6 +154,8 @@ pub fn growth_system(
         &mut ProductionState,
     )>,
     world_data: Res<crate::world::WorldData>,
+    happiness: Res<crate::systems::TownHappiness>,
+    mut farm_events: MessageWriter<FarmEventMsg>,
 ) {
     if game_time.is_paused() {
         return;
@@ -159,12 +163,15 @@ pub fn growth_system(
 
     let hours_elapsed = game_time.delta(&time) / game_time.seconds_per_hour;
 
-    // Precompute per-town farm yield multiplier
+    // Precompute per-town farm yield multiplier (upgrades x happiness)
     let max_towns = world_data.towns.len();
     let mut farm_mults: Vec<f32> = Vec::with_capacity(max_towns);
     for t in 0..max_towns {
         let levels = town_access.upgrade_levels(t as i32);
-        farm_mults.push(UPGRADES.stat_mult(&levels, "Farmer", UpgradeStatKind::Yield));
+        farm_mults.push(
+            UPGRADES.stat_mult(&levels, "Farmer", UpgradeStatKind::Yield)
+                * happiness.output_mult(t as i32),
+        );
     }
 
     for (gpu_slot, building, town_id, pos, construction, mut production) in &mut production_q {
@@ -187,6 +194,11 @@ pub fn growth_system(
                     if production.progress >= 1.0 {
                         production.ready = true;
                         production.progress = 1.0;
+                        farm_events.write(FarmEventMsg {
+                            kind: FarmEventKind::Ready,
+                            farm_slot: slot,
+                            position: Vec2::new(pos.x, pos.y),
+                        });
                     }
                 }
             }
@@ -223,6 +235,7 @@ pub fn raider_forage_system(
     world_data: Res<WorldData>,
     user_settings: Res<crate::settings::UserSettings>,
     mut raider_state: ResMut<crate::resources::RaiderState>,
+    mut tribute: ResMut<TributeState>,
 ) {
     let interval = user_settings.raider_forage_hours;
     if !game_time.hour_ticked || interval <= 0.0 {
@@ -240,12 +253,166 @@ pub fn raider_forage_system(
                     if let Some(mut f) = economy.towns.food_mut(town_idx as i32) {
                         f.0 += RAIDER_FORAGE_RATE;
                     }
+                    tribute.record_income(town_idx, RAIDER_FORAGE_RATE, 0);
                 }
             }
         }
     }
 }
 
+// ============================================================================
+// TRIBUTE SYSTEM
+// ============================================================================
+
+/// Hourly tribute bookkeeping. Drops relationships whose towns were defeated (fountain
+/// gone) or changed hands, then pays out every `interval_days` from accrued income.
+pub fn tribute_system(
+    game_time: Res<GameTime>,
+    mut tribute: ResMut<TributeState>,
+    mut economy: EconomyState,
+    world_data: Res<WorldData>,
+    entity_map: Res<EntityMap>,
+    mut combat_log: MessageWriter<CombatLogMsg>,
+) {
+    if !game_time.hour_ticked || tribute.tributes.is_empty() {
+        return;
+    }
+
+    let alive = |town: usize, faction: i32| {
+        world_data
+            .towns
+            .get(town)
+            .is_some_and(|t| t.faction == faction)
+            && entity_map.count_for_town(BuildingKind::Fountain, town as u32) > 0
+    };
+    tribute.tributes.retain(|t| {
+        let keep = t.from_faction != t.to_faction
+            && alive(t.from_town, t.from_faction)
+            && alive(t.to_town, t.to_faction);
+        if !keep {
+            info!("tribute {} -> {} ended", t.from_town, t.to_town);
+        }
+        keep
+    });
+
+    for t in tribute.tributes.iter_mut() {
+        t.hours += 1;
+        if t.hours < t.interval_days.max(1) * 24 {
+            continue;
+        }
+        let from = t.from_town as i32;
+        let (food, gold) = t.settle(economy.towns.food(from), economy.towns.gold(from));
+        if food == 0 && gold == 0 {
+            continue;
+        }
+        if let Some(mut f) = economy.towns.food_mut(from) {
+            f.0 -= food;
+        }
+        if let Some(mut g) = economy.towns.gold_mut(from) {
+            g.0 -= gold;
+        }
+        if let Some(mut f) = economy.towns.food_mut(t.to_town as i32) {
+            f.0 += food;
+        }
+        if let Some(mut g) = economy.towns.gold_mut(t.to_town as i32) {
+            g.0 += gold;
+        }
+        let name = |i: usize| world_data.towns.get(i).map_or("?", |t| t.name.as_str());
+        combat_log.write(CombatLogMsg {
+            kind: CombatEventKind::Loot,
+            faction: t.to_faction,
+            day: game_time.day(),
+            hour: game_time.hour(),
+            minute: game_time.minute(),
+            message: format!(
+                "{} paid tribute to {}: {} food, {} gold",
+                name(t.from_town),
+                name(t.to_town),
+                food,
+                gold
+            ),
+            location: None,
+        });
+    }
+}
+
+// ============================================================================
+// FOOD STORAGE SYSTEM
+// ============================================================================
+
+/// Enforce per-town food caps (`FoodStorageConfig`). Every tick: food above the cap is
+/// wasted — logged immediately when a lost granary lowered the cap, otherwise summed and
+/// logged hourly. On each new game day, stock above the spoilage threshold decays.
+pub fn food_storage_system(
+    config: Res<FoodStorageConfig>,
+    mut state: ResMut<FoodStorageState>,
+    game_time: Res<GameTime>,
+    mut economy: EconomyState,
+    world_data: Res<WorldData>,
+    entity_map: Res<EntityMap>,
+    mut combat_log: MessageWriter<CombatLogMsg>,
+) {
+    if !config.enabled {
+        return;
+    }
+    let n = world_data.towns.len();
+    state.caps.resize(n, 0);
+    state.spoiled_last_day.resize(n, 0);
+    state.wasted.resize(n, 0);
+    let day = game_time.day();
+    let new_day = state.last_day > 0 && day > state.last_day;
+    state.last_day = day;
+
+    for (town_idx, town) in world_data.towns.iter().enumerate() {
+        let granaries = entity_map.count_for_town(BuildingKind::Granary, town_idx as u32);
+        let cap = config.cap(granaries);
+        let cap_dropped = cap < state.caps[town_idx];
+        state.caps[town_idx] = cap;
+        let Some(mut food) = economy.towns.food_mut(town_idx as i32) else {
+            continue;
+        };
+        let mut log = |message: String| {
+            combat_log.write(CombatLogMsg {
+                kind: CombatEventKind::Spoilage,
+                faction: town.faction,
+                day,
+                hour: game_time.hour(),
+                minute: game_time.minute(),
+                message,
+                location: None,
+            });
+        };
+
+        if food.0 > cap {
+            let lost = food.0 - cap;
+            food.0 = cap;
+            if cap_dropped {
+                log(format!(
+                    "{} lost a granary: {} food spoiled (cap {})",
+                    town.name, lost, cap
+                ));
+            } else {
+                state.wasted[town_idx] += lost;
+            }
+        }
+        if game_time.hour_ticked && state.wasted[town_idx] > 0 {
+            log(format!(
+                "{} storage full: {} food wasted (cap {})",
+                town.name, state.wasted[town_idx], cap
+            ));
+            state.wasted[town_idx] = 0;
+        }
+        if new_day {
+            let spoiled = config.spoilage(food.0, cap);
+            food.0 -= spoiled;
+            state.spoiled_last_day[town_idx] = spoiled;
+            if spoiled > 0 {
+                log(format!("{} food spoiled: {}", town.name, spoiled));
+            }
+        }
+    }
+}
+
 // ============================================================================
 // STARVATION SYSTEM
 // ============================================================================
@@ -344,8 +511,38 @@ pub fn farm_visual_system(
 // BUILDING SPAWNER SYSTEM
 // ============================================================================
 
+/// Where a respawn for `town_idx`'s spawner at `spawn` appears under `mode`. Bed with no free
+/// bed, a spawn point now in another town's territory, or a spot that is off the grid or in
+/// water all fall back to the town center.
+pub(crate) fn respawn_position(
+    mode: RespawnLocation,
+    spawn: Vec2,
+    town_idx: u32,
+    town_center: Vec2,
+    entity_map: &EntityMap,
+    grid: &world::WorldGrid,
+) -> Vec2 {
+    let pick = match mode {
+        RespawnLocation::TownCenter => None,
+        RespawnLocation::OriginalSpawn => {
+            let (col, row) = grid.world_to_grid(spawn);
+            (!grid.is_foreign_territory(col, row, town_idx as u16)).then_some(spawn)
+        }
+        RespawnLocation::NearestBed => entity_map
+            .iter_kind_for_town(BuildingKind::Bed, town_idx)
+            .filter(|b| entity_map.occupant_count(b.slot) == 0)
+            .map(|b| b.position)
+            .min_by(|a, b| {
+                a.distance_squared(spawn)
+                    .total_cmp(&b.distance_squared(spawn))
+            }),
+    };
+    pick.filter(|&p| world::valid_town_site(grid, p))
+        .unwrap_or(town_center)
+}
+
 /// Detects dead NPCs linked to spawner buildings, counts down respawn timers,
-/// and spawns replacements via GpuSlotPool + SpawnNpcMsg.
+/// and spawns replacements via GpuSlotPool + SpawnNpcMsg at the faction's `RespawnPolicy` spot.
 /// Only runs when game_time.hour_ticked is true.
 pub fn spawner_respawn_system(
     game_time: Res<GameTime>,
@@ -353,9 +550,15 @@ pub fn spawner_respawn_system(
     mut slots: ResMut<GpuSlotPool>,
     mut spawn_writer: MessageWriter<SpawnNpcMsg>,
     world_data: Res<WorldData>,
+    grid: Res<world::WorldGrid>,
+    respawn_policy: Res<RespawnPolicy>,
     mut combat_log: MessageWriter<CombatLogMsg>,
     mut dirty_writers: crate::messages::DirtyWriters,
     mut spawner_q: Query<(&mut SpawnerState, Option<&MinerHomeConfig>)>,
+    mut loadouts: ResMut<world::StartingLoadouts>,
+    mut spawn_overrides: ResMut<crate::systems::spawn::SpawnOverrideQueue>,
+    mut next_loot_id: ResMut<NextLootItemId>,
+    happiness: Res<crate::systems::TownHappiness>,
 ) {
     if !game_time.hour_ticked {
         return;
@@ -388,9 +591,10 @@ pub fn spawner_respawn_system(
             }
         }
 
-        // Count down respawn timer (>= 0.0 catches newly-built spawners at 0.0)
+        // Count down respawn timer (>= 0.0 catches newly-built spawners at 0.0). Happy towns
+        // count faster, unhappy ones slower.
         if spawner.respawn_timer >= 0.0 {
-            spawner.respawn_timer -= 1.0;
+            spawner.respawn_timer -= happiness.output_mult(inst.town_idx as i32);
             if spawner.respawn_timer <= 0.0 {
                 // Spawn replacement NPC
                 let Some(slot) = slots.alloc_reset() else {
@@ -413,8 +617,23 @@ pub fn spawner_respawn_system(
                     _work_slot,
                 ) = world::resolve_spawner_npc(inst, &world_data.towns, &entity_map, assigned_mine);
 
-                let pos = inst.position;
+                let home = inst.position;
+                let pos = match world_data.towns.get(town_data_idx) {
+                    Some(town) => respawn_position(
+                        respawn_policy.mode(faction),
+                        home,
+                        inst.town_idx,
+                        town.center,
+                        &entity_map,
+                        &grid,
+                    ),
+                    None => home,
+                };
                 let is_miner_home = inst.kind == BuildingKind::MinerHome;
+                if let Some(o) = loadouts.take(town_data_idx, Job::from_i32(job), &mut next_loot_id)
+                {
+                    spawn_overrides.0.insert(slot, o);
+                }
                 spawn_writer.write(SpawnNpcMsg {
                     slot_idx: slot,
                     x: pos.x,
@@ -422,8 +641,8 @@ pub fn spawner_respawn_system(
                     job,
                     faction,
                     town_idx: town_data_idx as i32,
-                    home_x: pos.x,
-                    home_y: pos.y,
+                    home_x: home.x,
+                    home_y: home.y,
                     work_x,
                     work_y,
                     starting_post,
@@ -735,6 +954,118 @@ pub fn squad_cleanup_system(
     }
 }
 
+/// Drive rally-then-attack orders: while a squad's target is still its rally point, count
+/// living members within `RallyConfig::radius` and issue the attack once enough have
+/// gathered or the timeout passes. A target changed by anything else cancels the order.
+pub fn squad_order_system(
+    mut squad_state: ResMut<SquadState>,
+    config: Res<RallyConfig>,
+    game_time: Res<GameTime>,
+    entity_map: Res<EntityMap>,
+    gpu_state: Res<GpuReadState>,
+    world_data: Res<WorldData>,
+    mut combat_log: MessageWriter<CombatLogMsg>,
+) {
+    let now = game_time.total_seconds;
+    let radius_sq = config.radius * config.radius;
+    for (si, squad) in squad_state.squads.iter_mut().enumerate() {
+        let Some(order) = squad.rally else {
+            continue;
+        };
+        if squad.target != Some(order.rally) {
+            squad.rally = None;
+            continue;
+        }
+        let (mut alive, mut gathered) = (0, 0);
+        for &entity in &squad.members {
+            let Some(slot) = entity_map.slot_for_entity(entity) else {
+                continue;
+            };
+            if entity_map.get_npc(slot).is_none_or(|n| n.dead) {
+                continue;
+            }
+            alive += 1;
+            let Some(p) = gpu_state.positions.get(slot * 2..slot * 2 + 2) else {
+                continue;
+            };
+            if Vec2::new(p[0], p[1]).distance_squared(order.rally) <= radius_sq {
+                gathered += 1;
+            }
+        }
+        if !config.ready(gathered, alive, now - order.issued_at) {
+            continue;
+        }
+        squad.target = Some(order.attack);
+        squad.rally = None;
+        let faction = match squad.owner {
+            SquadOwner::Player => crate::constants::FACTION_PLAYER,
+            SquadOwner::Town(tdi) => world_data.towns.get(tdi).map_or(0, |t| t.faction),
+        };
+        combat_log.write(CombatLogMsg {
+            kind: CombatEventKind::Raid,
+            faction,
+            day: game_time.day(),
+            hour: game_time.hour(),
+            minute: game_time.minute(),
+            message: format!(
+                "Squad {} rallied ({gathered}/{alive}) -> attacking ({:.0},{:.0})",
+                si + 1,
+                order.attack.x,
+                order.attack.y
+            ),
+            location: Some(order.attack),
+        });
+    }
+}
+
+/// Apply each squad's stance to its members: the matching `CombatStance` and, for Defensive
+/// squads, an `EngageZone` around the squad target. Members with a `StanceOverride` keep
+/// their own stance; units that left their squad drop what it gave them.
+pub fn squad_stance_system(
+    mut commands: Commands,
+    squad_state: Res<SquadState>,
+    member_q: Query<
+        (
+            Entity,
+            &SquadId,
+            Option<&CombatStance>,
+            Option<&EngageZone>,
+            Has<SquadStanceApplied>,
+        ),
+        (Without<StanceOverride>, Without<Building>, Without<Dead>),
+    >,
+    left_q: Query<Entity, (With<SquadStanceApplied>, Without<SquadId>)>,
+) {
+    for entity in left_q.iter() {
+        commands
+            .entity(entity)
+            .remove::<(SquadStanceApplied, CombatStance, EngageZone)>();
+    }
+    for (entity, squad_id, stance, zone, applied) in member_q.iter() {
+        let Some(squad) = squad_state.squads.get(squad_id.0 as usize) else {
+            continue;
+        };
+        let want_stance = squad.stance.combat_stance();
+        let want_zone = (squad.stance == SquadStance::Defensive).then_some(EngageZone {
+            anchor: squad.target,
+            radius: squad.engage_radius,
+        });
+        if applied && stance == Some(&want_stance) && zone == want_zone.as_ref() {
+            continue;
+        }
+        let mut ec = commands.entity(entity);
+        ec.insert((want_stance, SquadStanceApplied));
+        match want_zone {
+            Some(z) => {
+                ec.insert(z);
+            }
+            None => {
+                ec.remove::<EngageZone>();
+            }
+        }
+    }
+}
+
 // ============================================================================
 // MIGRATION SYSTEMS
 // ============================================================================
@@ -750,10 +1081,66 @@ pub struct MigrationResources<'w, 's> {
     pub gpu_updates: MessageWriter<'w, GpuUpdateMsg>,
     pub npc_flags_q: Query<'w, 's, &'static mut NpcFlags>,
     pub home_q: Query<'w, 's, &'static mut Home>,
+    pub pop_stats: ResMut<'w, PopulationStats>,
+    pub path_queue: ResMut<'w, PathRequestQueue>,
+    pub path_q: Query<'w, 's, &'static NpcPath>,
+    pub npc_gpu: Res<'w, crate::gpu::EntityGpuState>,
+}
+
+/// True when a member's GPU target or active path already leads to `goal`, so the
+/// migration walk is only re-issued after something else retargets it.
+pub(crate) fn heading_to(gpu_target: Option<Vec2>, path: Option<&NpcPath>, goal: Vec2) -> bool {
+    gpu_target.is_some_and(|t| t.distance_squared(goal) <= 1.0)
+        || path
+            .is_some_and(|p| !p.waypoints.is_empty() && p.goal_world.distance_squared(goal) <= 1.0)
+}
+
+/// Move a migration member to `town_idx`/`faction`: EntityMap town index, TownId/Faction
+/// components, GPU faction, population + faction stats, Home, and the migrating flag.
+fn reassign_member(
+    slot: usize,
+    town_idx: i32,
+    faction: i32,
+    home: Vec2,
+    migrating: bool,
+    entity_map: &mut EntityMap,
+    res: &mut MigrationResources,
+    commands: &mut Commands,
+) {
+    let Some(npc) = entity_map.get_npc(slot) else {
+        return;
+    };
+    if npc.dead {
+        return;
+    }
+    let (entity, job, old_town, old_faction) = (npc.entity, npc.job, npc.town_idx, npc.faction);
+    if old_town != town_idx {
+        pop_dec_alive(&mut res.pop_stats, job, old_town);
+        pop_inc_alive(&mut res.pop_stats, job, town_idx);
+        commands.entity(entity).insert(TownId(town_idx));
+    }
+    if old_faction != faction {
+        res.faction_stats.dec_alive(old_faction);
+        res.faction_stats.inc_alive(faction);
+        commands.entity(entity).insert(Faction(faction));
+        res.gpu_updates
+            .write(GpuUpdateMsg(GpuUpdate::SetFaction { idx: slot, faction }));
+    }
+    entity_map.set_npc_town(slot, town_idx, faction);
+    if let Ok(mut h) = res.home_q.get_mut(entity) {
+        h.0 = home;
+    }
+    if let Ok(mut flags) = res.npc_flags_q.get_mut(entity) {
+        if flags.migrating != migrating {
+            flags.migrating = migrating;
+        }
+    }
 }
 
 /// Create a new AI town: allocate faction, push Town, extend all per-town
 /// resource vecs, create an inactive AiPlayer with random personality.
+/// `faction` reuses an existing faction (scripted colony) instead of allocating one;
+/// Player-kind colonies get no AiPlayer.
 /// Returns (town_data_idx, faction).
 fn create_ai_town(
     _grid: &crate::world::WorldGrid,
@@ -764,16 +1151,19 @@ fn create_ai_town(
     commands: &mut Commands,
     center: Vec2,
     town_kind: crate::constants::TownKind,
+    faction: Option<i32>,
 ) -> (usize, i32) {
     use crate::constants::town_def;
     let def = town_def(town_kind);
-    let next_faction = world_data
-        .towns
-        .iter()
-        .map(|t| t.faction)
-        .max()
-        .unwrap_or(0)
-        + 1;
+    let next_faction = faction.unwrap_or_else(|| {
+        world_data
+            .towns
+            .iter()
+            .map(|t| t.faction)
+            .max()
+            .unwrap_or(0)
+            + 1
+    });
     world_data.towns.push(world::Town {
         name: def.label.into(),
         center,
@@ -782,14 +1172,19 @@ fn create_ai_town(
     });
     let town_data_idx = world_data.towns.len() - 1;
 
-    // Register in FactionList
-    res.faction_list
-        .factions
-        .push(crate::resources::FactionData {
-            kind: town_kind.faction_kind(),
-            name: def.label.into(),
-            towns: vec![town_data_idx],
-        });
+    // Register in FactionList (existing faction: add the town to it)
+    match faction.and_then(|f| res.faction_list.factions.get_mut(f as usize)) {
+        Some(existing) => existing.towns.push(town_data_idx),
+        None => res
+            .faction_list
+            .factions
+            .push(crate::resources::FactionData {
+                kind: town_kind.faction_kind(),
+                name: def.label.into(),
+                towns: vec![town_data_idx],
+                stance: None,
+            }),
+    }
 
     // Extend per-town non-ECS resources
     let num_towns = world_data.towns.len();
@@ -801,33 +1196,38 @@ fn create_ai_town(
     res.raider_state.forage_timers.resize(num_towns, 0.0);
 
     // Create AiPlayer with random personality and road style
-    let ai_kind = if def.is_raider {
-        AiKind::Raider
+    let mut rng = rand::rng();
+    let policy = if town_kind == crate::constants::TownKind::Player {
+        PolicySet::default()
     } else {
-        AiKind::Builder
+        let ai_kind = if def.is_raider {
+            AiKind::Raider
+        } else {
+            AiKind::Builder
+        };
+        let personalities = [
+            AiPersonality::Aggressive,
+            AiPersonality::Balanced,
+            AiPersonality::Economic,
+        ];
+        let personality = personalities[rng.random_range(0..personalities.len())];
+        let road_style = super::ai_player::RoadStyle::random(&mut rng);
+        let mut policy = personality.default_policies();
+        policy.mining_radius = super::ai_player::initial_mining_radius(entity_map, center);
+        ai_state.players.push(AiPlayer {
+            town_data_idx,
+            kind: ai_kind,
+            personality,
+            road_style,
+            last_actions: std::collections::VecDeque::new(),
+            active: false,
+            build_enabled: true,
+            upgrade_enabled: true,
+            squad_indices: Vec::new(),
+            squad_cmd: HashMap::new(),
+        });
+        policy
     };
-    let mut rng = rand::rng();
-    let personalities = [
-        AiPersonality::Aggressive,
-        AiPersonality::Balanced,
-        AiPersonality::Economic,
-    ];
-    let personality = personalities[rng.random_range(0..personalities.len())];
-    let road_style = super::ai_player::RoadStyle::random(&mut rng);
-    let mut policy = personality.default_policies();
-    policy.mining_radius = super::ai_player::initial_mining_radius(entity_map, center);
-    ai_state.players.push(AiPlayer {
-        town_data_idx,
-        kind: ai_kind,
-        personality,
-        road_style,
-        last_actions: std::collections::VecDeque::new(),
-        active: false,
-        build_enabled: true,
-        upgrade_enabled: true,
-        squad_indices: Vec::new(),
-        squad_cmd: HashMap::new(),
-    });
 
     // Spawn ECS town entity
     let entity = commands
@@ -891,6 +1291,196 @@ fn pick_settle_site(
     grid.grid_to_world(gc, gr)
 }
 
+/// Validate a scripted migration. Returns the town to join on arrival: a same-faction town
+/// within `RAIDER_SETTLE_RADIUS` of `to_pos` (None = found a new town there).
+pub fn validate_migration(
+    world_data: &WorldData,
+    entity_map: &EntityMap,
+    migration_state: &MigrationState,
+    from_town: usize,
+    to_pos: Vec2,
+    member_slots: &[usize],
+) -> Result<Option<usize>, String> {
+    let pending = migration_state
+        .requests
+        .iter()
+        .any(|r| matches!(r, MigrationRequest::Start { .. }));
+    if migration_state.active.is_some() || pending {
+        return Err("a migration is already in progress".into());
+    }
+    let Some(source) = world_data.towns.get(from_town) else {
+        return Err(format!("town {from_town} out of range"));
+    };
+    if member_slots.is_empty() {
+        return Err("no members".into());
+    }
+    let mut seen = HashSet::new();
+    for &slot in member_slots {
+        if !seen.insert(slot) {
+            return Err(format!("npc #{slot} listed twice"));
+        }
+        let npc = entity_map
+            .get_npc(slot)
+            .filter(|n| !n.dead)
+            .ok_or_else(|| format!("npc #{slot} not found"))?;
+        if npc.town_idx != from_town as i32 {
+            return Err(format!("npc #{slot} does not belong to town {from_town}"));
+        }
+    }
+    let mut join = None;
+    for (i, town) in world_data.towns.iter().enumerate() {
+        if town.center.distance(to_pos) >= RAIDER_SETTLE_RADIUS {
+            continue;
+        }
+        if i == from_town {
+            return Err("destination is inside the source town".into());
+        }
+        if town.faction != source.faction {
+            return Err(format!("destination is inside foreign town {i}"));
+        }
+        join.get_or_insert(i);
+    }
+    Ok(join)
+}
+
+/// Drain scripted migration requests (`endless/migrate`, `endless/cancel_migration`).
+/// Start: members detach from their town (townless, home = destination, worksites and
+/// squads released) and `endless_system` walks and settles them. Cancel: members rejoin
+/// the source town.
+pub fn scripted_migration_system(
+    mut migration_state: ResMut<MigrationState>,
+    mut entity_map: ResMut<EntityMap>,
+    world_data: Res<WorldData>,
+    mut res: MigrationResources,
+    mut squad_state: ResMut<SquadState>,
+    mut work_intents: MessageWriter<crate::messages::WorkIntentMsg>,
+    mut squads_dirty: MessageWriter<crate::messages::SquadsDirtyMsg>,
+    work_q: Query<&NpcWorkState>,
+    mut combat_log: MessageWriter<CombatLogMsg>,
+    game_time: Res<GameTime>,
+    mut commands: Commands,
+) {
+    if migration_state.requests.is_empty() {
+        return;
+    }
+    for request in std::mem::take(&mut migration_state.requests) {
+        match request {
+            MigrationRequest::Start {
+                from_town,
+                to_pos,
+                member_slots,
+            } => {
+                let join_town = match validate_migration(
+                    &world_data,
+                    &entity_map,
+                    &migration_state,
+                    from_town,
+                    to_pos,
+                    &member_slots,
+                ) {
+                    Ok(j) => j,
+                    Err(e) => {
+                        warn!("Scripted migration rejected: {e}");
+                        continue;
+                    }
+                };
+                let source = &world_data.towns[from_town];
+                let faction = source.faction;
+                for &slot in &member_slots {
+                    let Some(entity) = entity_map.get_npc(slot).map(|n| n.entity) else {
+                        continue;
+                    };
+                    // Release worksite claim and squad membership before leaving
+                    if let Ok(work) = work_q.get(entity) {
+                        if work.worksite.is_some() {
+                            work_intents.write(crate::messages::WorkIntentMsg(
+                                crate::messages::WorkIntent::Release {
+                                    entity,
+                                    worksite: work.worksite,
+                                },
+                            ));
+                        }
+                    }
+                    for squad in squad_state.squads.iter_mut() {
+                        squad.members.retain(|&e| e != entity);
+                    }
+                    commands.entity(entity).remove::<SquadId>();
+                    reassign_member(
+                        slot,
+                        -1,
+                        faction,
+                        to_pos,
+                        true,
+                        &mut entity_map,
+                        &mut res,
+                        &mut commands,
+                    );
+                }
+                squads_dirty.write(crate::messages::SquadsDirtyMsg);
+                combat_log.write(CombatLogMsg {
+                    kind: CombatEventKind::Raid,
+                    faction,
+                    day: game_time.day(),
+                    hour: game_time.hour(),
+                    minute: game_time.minute(),
+                    message: format!("{} settlers left {}", member_slots.len(), source.name),
+                    location: Some(source.center),
+                });
+                info!(
+                    "Scripted migration: {} members from town {} to ({:.0}, {:.0}), join={:?}",
+                    member_slots.len(),
+                    from_town,
+                    to_pos.x,
+                    to_pos.y,
+                    join_town
+                );
+                migration_state.active = Some(MigrationGroup {
+                    boat_slot: None,
+                    boat_pos: source.center,
+                    settle_target: to_pos,
+                    is_raider: source.kind == crate::constants::TownKind::AiRaider,
+                    upgrade_levels: Vec::new(),
+                    starting_food: 0,
+                    starting_gold: 0,
+                    member_slots,
+                    faction,
+                    town_data_idx: None,
+                    source_town: Some(from_town),
+                    join_town,
+                });
+            }
+            MigrationRequest::Cancel => {
+                let Some(mg) = migration_state
+                    .active
+                    .take_if(|g| g.source_town.is_some() && g.town_data_idx.is_none())
+                else {
+                    continue;
+                };
+                let Some(source) = mg.source_town.and_then(|t| world_data.towns.get(t)) else {
+                    continue;
+                };
+                for &slot in &mg.member_slots {
+                    reassign_member(
+                        slot,
+                        mg.source_town.unwrap_or(0) as i32,
+                        source.faction,
+                        source.center,
+                        false,
+                        &mut entity_map,
+                        &mut res,
+                        &mut commands,
+                    );
+                }
+                squads_dirty.write(crate::messages::SquadsDirtyMsg);
+                info!(
+                    "Scripted migration cancelled: members return to {}",
+                    source.name
+                );
+            }
+        }
+    }
+}
+
 /// Endless mode lifecycle: boat → disembark → walk → settle.
 /// Phase 1: Spawn boat at map edge (no town, no NPCs)
 /// Phase 2: Sail toward settle site, disembark NPCs on shore
@@ -922,7 +1512,8 @@ pub fn endless_system(
         });
     }
 
-    if !endless.enabled {
+    // Scripted migrations (endless/migrate) walk and settle even with endless mode off
+    if !endless.enabled && migration_state.active.is_none() {
         return;
     }
 
@@ -1022,7 +1613,15 @@ pub fn endless_system(
         }
     }
 
-    // === ATTACH Migrating flag to newly spawned members ===
+    // === PRUNE — members that died en route shrink the group (all dead = wipeout below) ===
+    if let Some(mg) = &mut migration_state.active {
+        let alive = |s: &usize| world_state.entity_map.get_npc(*s).is_some_and(|n| !n.dead);
+        if mg.member_slots.iter().any(alive) {
+            mg.member_slots.retain(alive);
+        }
+    }
+
+    // === ATTACH Migrating flag to newly spawned members + walk toward settle target ===
     if let Some(mg) = &migration_state.active {
         for &slot in &mg.member_slots {
             if let Some(npc) = world_state.entity_map.get_npc(slot) {
@@ -1031,6 +1630,24 @@ pub fn endless_system(
                         flags.migrating = true;
                     }
                 }
+                let gpu_target = res
+                    .npc_gpu
+                    .targets
+                    .get(slot * 2..slot * 2 + 2)
+                    .map(|t| Vec2::new(t[0], t[1]));
+                let heading = heading_to(
+                    gpu_target,
+                    res.path_q.get(npc.entity).ok(),
+                    mg.settle_target,
+                );
+                if !npc.dead && !heading {
+                    res.path_queue.submit(
+                        npc.entity,
+                        mg.settle_target,
+                        MovementPriority::Squad,
+                        "migration",
+                    );
+                }
             }
         }
     }
@@ -1067,7 +1684,18 @@ pub fn endless_system(
             if found == 0 && !mg.member_slots.is_empty() {
                 return;
             }
-            if found > 0 {
+            if found > 0 && mg.source_town.is_some() {
+                combat_log.write(CombatLogMsg {
+                    kind: CombatEventKind::Raid,
+                    faction: mg.faction,
+                    day: game_time.day(),
+                    hour: game_time.hour(),
+                    minute: game_time.minute(),
+                    message: "The migrating settlers were wiped out!".into(),
+                    location: None,
+                });
+                info!("Scripted migration wiped out");
+            } else if found > 0 {
                 // All spawned members are dead — migration wiped out, queue replacement
                 let is_raider = mg.is_raider;
                 let kind_str = if is_raider {
@@ -1106,104 +1734,130 @@ pub fn endless_system(
             return;
         }
 
-        // === CREATE TOWN + SETTLE ===
+        // === CREATE TOWN (or join an existing one) + SETTLE ===
         let is_raider = mg.is_raider;
         let member_slots = mg.member_slots.clone();
-        let town_kind = if is_raider {
-            crate::constants::TownKind::AiRaider
+        let source_town = mg.source_town;
+        let join_town = mg
+            .join_town
+            .filter(|&t| t < world_state.world_data.towns.len());
+        let town_data_idx = if let Some(t) = join_town {
+            t
         } else {
-            crate::constants::TownKind::AiBuilder
-        };
-
-        let (town_data_idx, _faction) = create_ai_town(
-            &world_state.grid,
-            &mut world_state.world_data,
-            &world_state.entity_map,
-            &mut res,
-            &mut ai_state,
-            &mut commands,
-            mg.settle_target,
-            town_kind,
-        );
-
-        // Set starting resources on ECS town entity
-        if let Some(&entity) = res.town_index.0.get(&(town_data_idx as i32)) {
-            commands.entity(entity).insert((
-                crate::components::FoodStore(mg.starting_food),
-                crate::components::GoldStore(mg.starting_gold),
-                crate::components::TownUpgradeLevel(mg.upgrade_levels.clone()),
-            ));
-        }
+            // Scripted colonies keep the source town's kind and faction
+            let town_kind = match source_town.and_then(|s| world_state.world_data.towns.get(s)) {
+                Some(src) => src.kind,
+                None if is_raider => crate::constants::TownKind::AiRaider,
+                None => crate::constants::TownKind::AiBuilder,
+            };
+            let (town_data_idx, _faction) = create_ai_town(
+                &world_state.grid,
+                &mut world_state.world_data,
+                &world_state.entity_map,
+                &mut res,
+                &mut ai_state,
+                &mut commands,
+                mg.settle_target,
+                town_kind,
+                source_town.map(|_| mg.faction),
+            );
+
+            // Set starting resources on ECS town entity
+            if let Some(&entity) = res.town_index.0.get(&(town_data_idx as i32)) {
+                commands.entity(entity).insert((
+                    crate::components::FoodStore(mg.starting_food),
+                    crate::components::GoldStore(mg.starting_gold),
+                    crate::components::TownUpgradeLevel(mg.upgrade_levels.clone()),
+                ));
+            }
 
-        // Place buildings directly into EntityMap
-        let mut area_level = 0i32;
-        world::place_buildings(
-            &mut world_state.grid,
-            &mut world_state.world_data,
-            mg.settle_target,
-            town_data_idx as u32,
-            &config,
-            town_kind,
-            &mut world_state.entity_slots,
-            &mut world_state.entity_map,
-            &mut commands,
-            &mut res.gpu_updates,
-            &mut area_level,
-        );
-        if let Some(&entity) = res.town_index.0.get(&(town_data_idx as i32)) {
-            commands
-                .entity(entity)
-                .insert(crate::components::TownAreaLevel(area_level));
-        }
-        world::stamp_dirt(&mut world_state.grid, &[mg.settle_target]);
+            // Place buildings directly into EntityMap
+            let mut area_level = 0i32;
+            world::place_buildings(
+                &mut world_state.grid,
+                &mut world_state.world_data,
+                mg.settle_target,
+                town_data_idx as u32,
+                &config,
+                town_kind,
+                &mut world_state.entity_slots,
+                &mut world_state.entity_map,
+                &mut commands,
+                &mut res.gpu_updates,
+                &mut area_level,
+            );
+            if let Some(&entity) = res.town_index.0.get(&(town_data_idx as i32)) {
+                commands
+                    .entity(entity)
+                    .insert(crate::components::TownAreaLevel(area_level));
+            }
+            world::stamp_dirt(&mut world_state.grid, &[mg.settle_target]);
+
+            // Activate AI
+            if let Some(player) = ai_state
+                .players
+                .iter_mut()
+                .find(|p| p.town_data_idx == town_data_idx)
+            {
+                player.active = true;
+            }
 
-        // Activate AI
-        if let Some(player) = ai_state
-            .players
-            .iter_mut()
-            .find(|p| p.town_data_idx == town_data_idx)
-        {
-            player.active = true;
-        }
+            world_state
+                .dirty_writers
+                .building_grid
+                .write(crate::messages::BuildingGridDirtyMsg);
+            world_state
+                .dirty_writers
+                .terrain
+                .write(crate::messages::TerrainDirtyMsg);
+            town_data_idx
+        };
 
-        // Settle NPCs: clear migrating, set home + town_idx
+        // Settle NPCs: clear migrating, move into the town (index, faction, stats, home)
+        let (town_faction, town_center) = world_state
+            .world_data
+            .towns
+            .get(town_data_idx)
+            .map(|t| (t.faction, t.center))
+            .unwrap_or((mg.faction, mg.settle_target));
         for &slot in &member_slots {
-            if let Some(npc) = world_state.entity_map.get_npc(slot) {
-                let entity = npc.entity;
-                if let Ok(mut flags) = res.npc_flags_q.get_mut(entity) {
-                    flags.migrating = false;
-                }
-                if let Ok(mut home) = res.home_q.get_mut(entity) {
-                    home.0 = mg.settle_target;
-                }
-            }
-            // Update town_idx in the index
-            if let Some(npc) = world_state.entity_map.get_npc_mut(slot) {
-                npc.town_idx = town_data_idx as i32;
-            }
+            reassign_member(
+                slot,
+                town_data_idx as i32,
+                town_faction,
+                town_center,
+                false,
+                &mut world_state.entity_map,
+                &mut res,
+                &mut commands,
+            );
         }
-
-        world_state
-            .dirty_writers
-            .building_grid
-            .write(crate::messages::BuildingGridDirtyMsg);
         world_state
             .dirty_writers
-            .terrain
-            .write(crate::messages::TerrainDirtyMsg);
-
-        let kind_str = if is_raider {
-            "raider band"
-        } else {
-            "rival faction"
+            .squads
+            .write(crate::messages::SquadsDirtyMsg);
+
+        let message = match (source_town, join_town) {
+            (Some(_), Some(_)) => format!(
+                "{} settlers joined {}",
+                member_slots.len(),
+                world_state.world_data.towns[town_data_idx].name
+            ),
+            (Some(_), None) => format!("{} settlers founded a new town", member_slots.len()),
+            (None, _) if is_raider => "A raider band has settled nearby!".to_string(),
+            (None, _) => "A rival faction has settled nearby!".to_string(),
         };
         combat_log.write(CombatLogMsg {
             kind: CombatEventKind::Raid,
-            faction: -1,
+            faction: if source_town.is_some() {
+                town_faction
+            } else {
+                -1
+            },
             day: game_time.day(),
             hour: game_time.hour(),
             minute: game_time.minute(),
-            message: format!("A {} has settled nearby!", kind_str),
+            message,
             location: Some(mg.settle_target),
         });
         info!(
@@ -1215,7 +1869,7 @@ pub fn endless_system(
     }
 
     // === SPAWN BOAT — pick edge, allocate boat GPU slot ===
-    if endless.pending_spawns.is_empty() {
+    if !endless.enabled || endless.pending_spawns.is_empty() {
         return;
     }
 
@@ -1289,6 +1943,8 @@ pub fn endless_system(
         member_slots: Vec::new(),
         faction: 0,
         town_data_idx: None,
+        source_town: None,
+        join_town: None,
     });
 
     let kind_str = if spawn.is_raider {
@@ -5,6 +5,7 @@ use crate::components::{
 };
 use crate::messages::GpuUpdateMsg;
 use crate::resources::GameTime;
+use bevy::ecs::message::Messages;
 use bevy::time::TimeUpdateStrategy;
 
 fn test_cached_stats() -> CachedStats {
@@ -442,6 +443,7 @@ fn setup_forage_app() -> App {
         respawn_timers: vec![0.0, 0.0],
         forage_timers: vec![0.0, 0.0],
     });
+    app.init_resource::<TributeState>();
     app.insert_resource(TimeUpdateStrategy::ManualDuration(
         std::time::Duration::from_secs_f32(1.0),
     ));
@@ -575,6 +577,8 @@ fn setup_growth_app() -> App {
     app.insert_resource(TimeUpdateStrategy::ManualDuration(
         std::time::Duration::from_secs_f32(1.0),
     ));
+    app.add_message::<FarmEventMsg>();
+    app.init_resource::<crate::systems::TownHappiness>();
     app.add_systems(FixedUpdate, growth_system);
     app.update();
     app.update();
@@ -709,6 +713,33 @@ fn farm_becomes_ready() {
     );
 }
 
+#[test]
+fn farm_ready_sends_one_event() {
+    let mut app = setup_growth_app();
+    app.init_resource::<crate::resources::FarmEventOutbox>();
+    app.add_systems(
+        FixedUpdate,
+        crate::systems::drain_farm_events.after(growth_system),
+    );
+    add_farm(&mut app, 3, true);
+    let entity = app.world().resource::<EntityMap>().entities[&3];
+    app.world_mut()
+        .get_mut::<ProductionState>(entity)
+        .unwrap()
+        .progress = 0.99;
+
+    for _ in 0..50 {
+        app.update();
+    }
+    let outbox = &app
+        .world()
+        .resource::<crate::resources::FarmEventOutbox>()
+        .0;
+    assert_eq!(outbox.len(), 1, "ready is reported once, not every tick");
+    assert_eq!(outbox[0].kind, FarmEventKind::Ready);
+    assert_eq!(outbox[0].farm_slot, 3);
+}
+
 #[test]
 fn growth_paused_no_change() {
     let mut app = setup_growth_app();
@@ -1068,6 +1099,12 @@ fn setup_spawner_app() -> App {
     app.insert_resource(EntityMap::default());
     app.insert_resource(GpuSlotPool::default());
     app.insert_resource(CollectedSpawns::default());
+    app.insert_resource(crate::world::WorldGrid::default());
+    app.insert_resource(RespawnPolicy::default());
+    app.insert_resource(crate::world::StartingLoadouts::default());
+    app.insert_resource(crate::systems::spawn::SpawnOverrideQueue::default());
+    app.insert_resource(NextLootItemId::default());
+    app.init_resource::<crate::systems::TownHappiness>();
     app.insert_resource(WorldData {
         towns: vec![crate::world::Town {
             name: "TestTown".to_string(),
@@ -1180,6 +1217,56 @@ fn spawner_assigns_uid_after_spawn() {
     );
 }
 
+#[test]
+fn respawn_position_falls_back_to_town_center() {
+    let mut grid = crate::world::WorldGrid::default();
+    grid.width = 20;
+    grid.height = 20;
+    grid.cell_size = 64.0;
+    grid.cells = vec![crate::world::WorldCell::default(); 400];
+    grid.init_town_buildable();
+    let center = Vec2::new(640.0, 640.0);
+    let home = Vec2::new(200.0, 200.0);
+    let mut em = EntityMap::default();
+    let pos = |mode, em: &EntityMap, grid: &crate::world::WorldGrid| {
+        respawn_position(mode, home, 0, center, em, grid)
+    };
+
+    assert_eq!(pos(RespawnLocation::OriginalSpawn, &em, &grid), home);
+    assert_eq!(pos(RespawnLocation::TownCenter, &em, &grid), center);
+    // No beds yet
+    assert_eq!(pos(RespawnLocation::NearestBed, &em, &grid), center);
+
+    let bed = |slot, position| BuildingInstance {
+        kind: BuildingKind::Bed,
+        position,
+        town_idx: 0,
+        slot,
+        faction: 0,
+    };
+    em.add_instance(bed(1, Vec2::new(300.0, 200.0)));
+    em.add_instance(bed(2, Vec2::new(900.0, 900.0)));
+    assert_eq!(
+        pos(RespawnLocation::NearestBed, &em, &grid),
+        Vec2::new(300.0, 200.0)
+    );
+    em.set_occupancy(1, 1);
+    assert_eq!(
+        pos(RespawnLocation::NearestBed, &em, &grid),
+        Vec2::new(900.0, 900.0)
+    );
+
+    // Spawn point now inside another town's territory
+    let (col, row) = grid.world_to_grid(home);
+    grid.add_town_buildable(col, row, 1);
+    assert_eq!(pos(RespawnLocation::OriginalSpawn, &em, &grid), center);
+
+    // Never into a lake
+    let (bc, br) = grid.world_to_grid(Vec2::new(900.0, 900.0));
+    grid.cells[br * grid.width + bc].terrain = crate::world::Biome::Water;
+    assert_eq!(pos(RespawnLocation::NearestBed, &em, &grid), center);
+}
+
 // -- mining_policy_system ------------------------------------------------
 
 #[derive(Resource, Default)]
@@ -1400,3 +1487,434 @@ fn squad_cleanup_retains_alive_members() {
         "alive member should be retained"
     );
 }
+
+// -- squad_order_system --------------------------------------------------
+
+fn setup_squad_order_app(positions: &[Vec2]) -> (App, Vec<Entity>) {
+    use crate::resources::{GpuReadState, RallyConfig, SquadState};
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.insert_resource(SquadState::default());
+    app.insert_resource(RallyConfig::default());
+    app.insert_resource(GameTime::default());
+    app.insert_resource(WorldData::default());
+    app.add_message::<CombatLogMsg>();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        std::time::Duration::from_secs_f32(1.0),
+    ));
+    let mut entity_map = EntityMap::default();
+    let mut gpu = GpuReadState::default();
+    let mut members = Vec::new();
+    for (slot, pos) in positions.iter().enumerate() {
+        let entity = app.world_mut().spawn(GpuSlot(slot)).id();
+        entity_map.register_npc(slot, entity, crate::components::Job::Archer, 0, 0);
+        gpu.positions.extend([pos.x, pos.y]);
+        members.push(entity);
+    }
+    gpu.npc_count = positions.len();
+    app.insert_resource(entity_map);
+    app.insert_resource(gpu);
+    app.world_mut().resource_mut::<SquadState>().squads[0].members = members.clone();
+    app.add_systems(FixedUpdate, squad_order_system);
+    (app, members)
+}
+
+#[test]
+fn squad_rally_waits_then_attacks_when_gathered() {
+    use crate::resources::{GpuReadState, SquadState};
+    let rally = Vec2::new(1000.0, 1000.0);
+    let attack = Vec2::new(3000.0, 1000.0);
+    let (mut app, _) = setup_squad_order_app(&[rally, Vec2::new(1500.0, 1000.0)]);
+    app.world_mut()
+        .resource_mut::<SquadState>()
+        .rally_then_attack(0, rally, attack, 0.0);
+    app.update();
+    app.update();
+    assert_eq!(
+        app.world().resource::<SquadState>().squads[0].target,
+        Some(rally),
+        "1 of 2 gathered is below the 75% threshold"
+    );
+
+    app.world_mut().resource_mut::<GpuReadState>().positions[2] = 1050.0;
+    app.update();
+    let squad = &app.world().resource::<SquadState>().squads[0];
+    assert_eq!(squad.target, Some(attack));
+    assert!(squad.rally.is_none());
+}
+
+#[test]
+fn squad_rally_stragglers_do_not_stall() {
+    use crate::resources::{GpuReadState, SquadState};
+    let rally = Vec2::new(1000.0, 1000.0);
+    let attack = Vec2::new(3000.0, 1000.0);
+    let (mut app, members) = setup_squad_order_app(&[rally, Vec2::new(5000.0, 5000.0)]);
+
+    // Dead straggler: the lone survivor is everyone
+    app.world_mut()
+        .resource_mut::<SquadState>()
+        .rally_then_attack(0, rally, attack, 0.0);
+    app.world_mut()
+        .resource_mut::<EntityMap>()
+        .get_npc_mut(1)
+        .unwrap()
+        .dead = true;
+    app.update();
+    app.update();
+    assert_eq!(
+        app.world().resource::<SquadState>().squads[0].target,
+        Some(attack)
+    );
+
+    // Stuck straggler: the timeout fires
+    app.world_mut()
+        .resource_mut::<EntityMap>()
+        .get_npc_mut(1)
+        .unwrap()
+        .dead = false;
+    app.world_mut().resource_mut::<SquadState>().squads[0].members = members;
+    app.world_mut()
+        .resource_mut::<GpuReadState>()
+        .positions
+        .copy_from_slice(&[5000.0, 5000.0, 5000.0, 5000.0]);
+    app.world_mut()
+        .resource_mut::<SquadState>()
+        .rally_then_attack(0, rally, attack, -120.0);
+    app.update();
+    assert_eq!(
+        app.world().resource::<SquadState>().squads[0].target,
+        Some(attack)
+    );
+
+    // Retargeting by hand cancels the order
+    let manual = Vec2::new(200.0, 200.0);
+    {
+        let mut ss = app.world_mut().resource_mut::<SquadState>();
+        ss.rally_then_attack(0, rally, attack, 0.0);
+        ss.squads[0].target = Some(manual);
+    }
+    app.update();
+    let squad = &app.world().resource::<SquadState>().squads[0];
+    assert_eq!(squad.target, Some(manual));
+    assert!(squad.rally.is_none());
+}
+
+// -- squad_stance_system -------------------------------------------------
+
+#[test]
+fn squad_stance_applies_unless_overridden_and_reverts_on_leave() {
+    use crate::components::{
+        CombatStance, EngageZone, SquadId, SquadStance, SquadStanceApplied, StanceOverride,
+    };
+    use crate::resources::SquadState;
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.insert_resource(SquadState::default());
+    app.add_systems(FixedUpdate, squad_stance_system);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        std::time::Duration::from_secs_f32(1.0),
+    ));
+    let member = app.world_mut().spawn(SquadId(1)).id();
+    let loner = app
+        .world_mut()
+        .spawn((SquadId(1), CombatStance::HoldFire, StanceOverride))
+        .id();
+    let hold = Vec2::new(1000.0, 1000.0);
+    {
+        let mut ss = app.world_mut().resource_mut::<SquadState>();
+        ss.squads[1].target = Some(hold);
+        assert!(ss.set_squad_stance(1, SquadStance::Defensive, 300.0));
+        assert!(!ss.set_squad_stance(99, SquadStance::Passive, 300.0));
+    }
+    app.update();
+    app.update();
+    let zone = *app.world().get::<EngageZone>(member).unwrap();
+    assert_eq!(
+        app.world().get::<CombatStance>(member),
+        Some(&CombatStance::FireAtWill)
+    );
+    assert_eq!(zone.anchor, Some(hold));
+    assert!(zone.allows(Vec2::ZERO, hold + Vec2::new(299.0, 0.0)));
+    assert!(
+        !zone.allows(hold, Vec2::ZERO),
+        "the squad target anchors, not the fight"
+    );
+    assert_eq!(
+        app.world().get::<CombatStance>(loner),
+        Some(&CombatStance::HoldFire),
+        "a unit's own stance wins over its squad's"
+    );
+    assert!(app.world().get::<EngageZone>(loner).is_none());
+
+    app.world_mut()
+        .resource_mut::<SquadState>()
+        .set_squad_stance(1, SquadStance::Passive, 300.0);
+    app.update();
+    app.update();
+    assert_eq!(
+        app.world().get::<CombatStance>(member),
+        Some(&CombatStance::ReturnFire)
+    );
+    assert!(app.world().get::<EngageZone>(member).is_none());
+
+    // Leaving the squad drops its stance: back to the default
+    app.world_mut().entity_mut(member).remove::<SquadId>();
+    app.update();
+    app.update();
+    let e = app.world().entity(member);
+    assert!(!e.contains::<CombatStance>());
+    assert!(!e.contains::<SquadStanceApplied>());
+    assert_eq!(
+        app.world().get::<CombatStance>(loner),
+        Some(&CombatStance::HoldFire)
+    );
+}
+
+// ============================================================================
+// SCRIPTED MIGRATION
+// ============================================================================
+
+#[test]
+fn migration_walk_only_reissued_when_off_course() {
+    let goal = Vec2::new(500.0, 500.0);
+    let pathing = NpcPath {
+        waypoints: vec![IVec2::new(1, 1)],
+        goal_world: goal,
+        ..Default::default()
+    };
+    assert!(heading_to(Some(goal), None, goal));
+    assert!(heading_to(
+        Some(Vec2::new(40.0, 40.0)),
+        Some(&pathing),
+        goal
+    ));
+    // Finished path (no waypoints) and a GPU target elsewhere: retargeted, submit again
+    let done = NpcPath {
+        waypoints: Vec::new(),
+        ..pathing.clone()
+    };
+    assert!(!heading_to(Some(Vec2::new(40.0, 40.0)), Some(&done), goal));
+    assert!(!heading_to(None, None, goal));
+}
+
+fn migration_fixture() -> (WorldData, EntityMap) {
+    let mut wd = WorldData::default();
+    for (center, faction) in [
+        (Vec2::new(1000.0, 1000.0), 1),
+        (Vec2::new(4000.0, 1000.0), 1),
+        (Vec2::new(1000.0, 4000.0), 2),
+    ] {
+        wd.towns.push(world::Town {
+            name: "T".into(),
+            center,
+            faction,
+            kind: crate::constants::TownKind::Player,
+        });
+    }
+    let mut em = EntityMap::default();
+    em.register_npc(5, Entity::from_raw_u32(5).unwrap(), Job::Farmer, 1, 0);
+    em.register_npc(6, Entity::from_raw_u32(6).unwrap(), Job::Archer, 1, 0);
+    em.register_npc(7, Entity::from_raw_u32(7).unwrap(), Job::Farmer, 2, 2);
+    (wd, em)
+}
+
+#[test]
+fn migration_validates_members_and_destination() {
+    let (wd, em) = migration_fixture();
+    let ms = MigrationState::default();
+    let open = Vec2::new(2500.0, 2500.0);
+    // Open land: found a new town
+    assert_eq!(
+        validate_migration(&wd, &em, &ms, 0, open, &[5, 6]),
+        Ok(None)
+    );
+    // Near a same-faction town: join it
+    assert_eq!(
+        validate_migration(&wd, &em, &ms, 0, Vec2::new(4100.0, 1000.0), &[5]),
+        Ok(Some(1))
+    );
+    // Foreign town, own town, foreign member, duplicates, empty group
+    assert!(validate_migration(&wd, &em, &ms, 0, Vec2::new(1000.0, 3900.0), &[5]).is_err());
+    assert!(validate_migration(&wd, &em, &ms, 0, Vec2::new(1050.0, 1000.0), &[5]).is_err());
+    assert!(validate_migration(&wd, &em, &ms, 0, open, &[7]).is_err());
+    assert!(validate_migration(&wd, &em, &ms, 0, open, &[5, 5]).is_err());
+    assert!(validate_migration(&wd, &em, &ms, 0, open, &[]).is_err());
+    assert!(validate_migration(&wd, &em, &ms, 9, open, &[5]).is_err());
+}
+
+#[test]
+fn migration_rejected_while_one_is_pending() {
+    let (wd, em) = migration_fixture();
+    let mut ms = MigrationState::default();
+    ms.requests.push(MigrationRequest::Start {
+        from_town: 0,
+        to_pos: Vec2::new(2500.0, 2500.0),
+        member_slots: vec![5],
+    });
+    assert!(validate_migration(&wd, &em, &ms, 0, Vec2::new(2500.0, 2500.0), &[6]).is_err());
+}
+
+#[test]
+fn set_npc_town_moves_town_index() {
+    let (_, mut em) = migration_fixture();
+    em.set_npc_town(5, -1, 1);
+    assert!(!em.slots_for_town(0).contains(&5));
+    assert!(em.slots_for_town(-1).contains(&5));
+    em.set_npc_town(5, 1, 1);
+    assert_eq!(em.slots_for_town(1), &[5]);
+    assert_eq!(em.get_npc(5).unwrap().town_idx, 1);
+}
+
+#[test]
+fn food_storage_cap_and_spoilage_math() {
+    let config = FoodStorageConfig {
+        enabled: true,
+        cap_base: 100,
+        cap_per_granary: 50,
+        spoilage_fraction: 0.5,
+        spoil_above: 0.5,
+    };
+    assert_eq!(config.cap(0), 100);
+    assert_eq!(config.cap(2), 200);
+    // Threshold 50: half of the 40 above it spoils
+    assert_eq!(config.spoilage(90, 100), 20);
+    // Nothing above threshold, nothing spoils; never negative
+    assert_eq!(config.spoilage(30, 100), 0);
+    assert_eq!(config.spoilage(-5, 100), 0);
+    let all = FoodStorageConfig {
+        spoilage_fraction: 1.0,
+        spoil_above: 0.0,
+        ..config
+    };
+    assert_eq!(all.spoilage(70, 100), 70);
+}
+
+#[test]
+fn food_storage_system_clamps_logs_and_spoils() {
+    let mut app = App::new();
+    app.insert_resource(GameTime::default());
+    app.insert_resource(PopulationStats::default());
+    app.insert_resource(WorldData {
+        towns: vec![crate::world::Town {
+            name: "Player".into(),
+            center: Vec2::ZERO,
+            faction: 1,
+            kind: crate::constants::TownKind::Player,
+        }],
+    });
+    app.init_resource::<EntityMap>();
+    app.init_resource::<FoodStorageState>();
+    app.insert_resource(FoodStorageConfig {
+        enabled: true,
+        cap_base: 100,
+        spoilage_fraction: 0.5,
+        ..Default::default()
+    });
+    app.add_message::<CombatLogMsg>();
+    let town = app
+        .world_mut()
+        .spawn((
+            crate::components::TownMarker,
+            crate::components::FoodStore(150),
+            crate::components::GoldStore(0),
+            crate::components::TownPolicy::default(),
+            crate::components::TownUpgradeLevel::default(),
+            crate::components::TownEquipment::default(),
+        ))
+        .id();
+    let mut town_index = crate::resources::TownIndex::default();
+    town_index.0.insert(0, town);
+    app.insert_resource(town_index);
+    app.add_systems(Update, food_storage_system);
+    let food = |app: &App| {
+        app.world()
+            .get::<crate::components::FoodStore>(town)
+            .unwrap()
+            .0
+    };
+
+    // Overflow is wasted and held for the hourly log
+    app.update();
+    assert_eq!(food(&app), 100);
+    assert_eq!(app.world().resource::<FoodStorageState>().wasted[0], 50);
+
+    // Lowering the cap trims at once
+    app.world_mut().resource_mut::<FoodStorageConfig>().cap_base = 80;
+    app.update();
+    assert_eq!(food(&app), 80);
+
+    // Next day: half of the 40 above the 40-food threshold spoils
+    app.world_mut().resource_mut::<GameTime>().total_seconds += 24.0 * 5.0;
+    app.update();
+    assert_eq!(food(&app), 60);
+    assert_eq!(
+        app.world().resource::<FoodStorageState>().spoiled_last_day[0],
+        20
+    );
+
+    // Waste and spoilage log as Spoilage, never as a harvest
+    let msgs = app.world().resource::<Messages<CombatLogMsg>>();
+    let logged: Vec<_> = msgs.iter_current_update_messages().collect();
+    assert!(!logged.is_empty());
+    assert!(logged.iter().all(|m| m.kind == CombatEventKind::Spoilage));
+}
+
+// ========================================================================
+// worksite claim errors
+// ========================================================================
+
+#[test]
+fn failed_worksite_claim_reports_why() {
+    let mut em = EntityMap::default();
+    em.add_instance(test_building_instance(0, BuildingKind::Farm, 0.0));
+    em.set_occupancy(0, 1);
+
+    assert!(
+        em.try_claim_worksite(0, BuildingKind::Farm, Some(0), 1, None)
+            .is_none()
+    );
+    assert_eq!(em.last_error(), Some("worksite already fully occupied"));
+    assert!(
+        em.try_claim_worksite(0, BuildingKind::GoldMine, Some(0), 2, None)
+            .is_none()
+    );
+    assert_eq!(
+        em.last_error(),
+        Some("worksite is a different building kind")
+    );
+    assert!(
+        em.try_claim_worksite(0, BuildingKind::Farm, Some(3), 2, None)
+            .is_none()
+    );
+    assert_eq!(em.last_error(), Some("worksite belongs to another town"));
+    assert!(
+        em.try_claim_worksite(7, BuildingKind::Farm, Some(0), 2, None)
+            .is_none()
+    );
+    assert_eq!(em.last_error(), Some("worksite slot has no building"));
+
+    // Bed claims name the bed
+    em.add_instance(test_building_instance(1, BuildingKind::Bed, 0.0));
+    assert!(
+        em.try_claim_worksite(1, BuildingKind::Bed, Some(0), 1, None)
+            .is_some()
+    );
+    assert_eq!(em.last_error(), None);
+    assert!(
+        em.try_claim_worksite(1, BuildingKind::Bed, Some(0), 1, None)
+            .is_none()
+    );
+    assert_eq!(em.last_error(), Some("bed already occupied"));
+    assert!(
+        em.try_claim_worksite(0, BuildingKind::Bed, Some(0), 1, None)
+            .is_none()
+    );
+    assert_eq!(em.last_error(), Some("building is not a bed"));
+
+    // Success clears it
+    assert!(
+        em.try_claim_worksite(0, BuildingKind::Farm, Some(0), 2, None)
+            .is_some()
+    );
+    assert_eq!(em.last_error(), None);
+}
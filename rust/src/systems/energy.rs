@@ -2,36 +2,65 @@
 
 use bevy::prelude::*;
 
-use crate::components::{Activity, Building, CachedStats, Dead, Energy, GpuSlot};
-use crate::resources::GameTime;
-
-/// Energy recovery/drain rates (per game hour)
-const ENERGY_RECOVER_PER_HOUR: f32 = 100.0 / 6.0; // 6 hours to full (resting)
-const ENERGY_DRAIN_PER_HOUR: f32 = 100.0 / 24.0; // 24 hours to empty (active)
+use crate::components::{
+    Activity, Building, CachedStats, CombatState, Dead, Energy, GpuSlot, OutOfSupply,
+};
+use crate::resources::{BehaviorLod, GameTime, GpuReadState};
+use crate::systems::balance::BalanceConfig;
 
 /// Energy system: drain while active, recover while resting or healing at fountain.
 /// Uses game time so it respects time_scale.
 /// State transitions (wake-up, stop working) are handled in decision_system.
+/// Behavior LOD: far, non-fighting NPCs update once per `stride` ticks with `stride`x the delta.
 pub fn energy_system(
     time: Res<Time>,
     game_time: Res<GameTime>,
+    lod: Res<BehaviorLod>,
+    gpu_state: Res<GpuReadState>,
+    balance: Res<BalanceConfig>,
+    mut frame: Local<usize>,
     mut energy_q: Query<
-        (&GpuSlot, &mut Energy, &Activity, &CachedStats),
+        (
+            &GpuSlot,
+            &mut Energy,
+            &Activity,
+            &CachedStats,
+            Option<&CombatState>,
+            Has<OutOfSupply>,
+        ),
         (Without<Building>, Without<Dead>),
     >,
 ) {
     if game_time.is_paused() {
         return;
     }
+    *frame = frame.wrapping_add(1);
 
     // Convert delta to game hours
-    let hours_elapsed = game_time.delta(&time) / game_time.seconds_per_hour;
+    let hours_per_tick = game_time.delta(&time) / game_time.seconds_per_hour;
+    let positions = &gpu_state.positions;
 
-    for (_es, mut energy, activity, stats) in energy_q.iter_mut() {
+    for (es, mut energy, activity, stats, combat, out_of_supply) in energy_q.iter_mut() {
+        let idx = es.0;
+        let pos = positions
+            .get(idx * 2..idx * 2 + 2)
+            .map_or(Vec2::ZERO, |p| Vec2::new(p[0], p[1]));
+        let stride = lod.stride_at(pos, combat.is_some_and(|c| c.is_fighting()));
+        if !(idx + *frame).is_multiple_of(stride) {
+            continue;
+        }
+        let hours_elapsed = hours_per_tick * stride as f32;
         if activity.kind.def().is_restful {
-            energy.0 = (energy.0 + ENERGY_RECOVER_PER_HOUR * hours_elapsed).min(100.0);
+            energy.0 = (energy.0 + balance.energy_recover_per_hour * hours_elapsed).min(100.0);
         } else {
-            energy.0 = (energy.0 - ENERGY_DRAIN_PER_HOUR * stats.stamina * hours_elapsed).max(0.0);
+            let supply_mult = if out_of_supply {
+                crate::constants::SUPPLY_ENERGY_DRAIN_MULT
+            } else {
+                1.0
+            };
+            energy.0 = (energy.0
+                - balance.energy_drain_per_hour * stats.stamina * supply_mult * hours_elapsed)
+                .max(0.0);
         }
     }
 }
@@ -65,6 +94,9 @@ mod tests {
         let mut app = App::new();
         app.add_plugins(MinimalPlugins);
         app.insert_resource(GameTime::default());
+        app.init_resource::<BehaviorLod>();
+        app.init_resource::<GpuReadState>();
+        app.init_resource::<BalanceConfig>();
         app.insert_resource(TimeUpdateStrategy::ManualDuration(
             std::time::Duration::from_secs_f32(1.0),
         ));
@@ -221,4 +253,38 @@ mod tests {
             "dead NPC energy should not change: {energy}"
         );
     }
+
+    #[test]
+    fn lod_far_npc_converges_with_near_npc() {
+        let mut app = setup_app();
+        {
+            let mut lod = app.world_mut().resource_mut::<BehaviorLod>();
+            lod.enabled = true;
+            lod.camera_known = true;
+            lod.camera_center = Vec2::ZERO;
+            lod.view_half = Vec2::splat(100.0);
+        }
+        app.world_mut().resource_mut::<GpuReadState>().positions = vec![10_000.0, 0.0, 0.0, 0.0];
+        let far = spawn_npc(&mut app, Activity::new(ActivityKind::Work), 100.0);
+        let near = app
+            .world_mut()
+            .spawn((
+                GpuSlot(1),
+                Energy(100.0),
+                Activity::new(ActivityKind::Work),
+                test_cached_stats(),
+            ))
+            .id();
+
+        for _ in 0..4 {
+            app.update();
+        }
+        let far_drain = 100.0 - app.world().get::<Energy>(far).unwrap().0;
+        let near_drain = 100.0 - app.world().get::<Energy>(near).unwrap().0;
+        assert!(far_drain > 0.0, "far NPC should still update");
+        assert!(
+            (far_drain - near_drain).abs() <= near_drain * 0.25 + 1e-4,
+            "staggered drain should converge: far={far_drain} near={near_drain}"
+        );
+    }
 }
@@ -0,0 +1,400 @@
+//! Fast-forward — run the fixed-timestep simulation headlessly until a target game day.
+//! Each tick advances `Time<Fixed>` by one timestep and runs `FixedMain` directly, the same
+//! schedule a rendered frame drives, so a run from the same save/seed lands in the same state.
+//! Nothing renders and no GPU compute dispatches while it runs. Movement and combat targeting
+//! step on the CPU fallback path instead (`cpu_compute::cpu_step`: straight-line movement, no
+//! separation), capped at `CPU_FALLBACK_MAX_ENTITIES` slots; slots past the cap stay frozen.
+//! Attacks deal direct damage, as on the CPU backend. The GPU buffers are re-uploaded in bulk
+//! on the next frame.
+//! Only message buffers are swapped between ticks; `First` is not run, so `Time<Virtual>` and
+//! the real-time clock stay where the paused game left them.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use bevy::app::FixedMain;
+use bevy::ecs::change_detection::Tick;
+use bevy::ecs::message::{MessageCursor, MessageRegistry};
+use bevy::prelude::*;
+
+use crate::AppState;
+use crate::components::Job;
+use crate::constants::CPU_FALLBACK_MAX_ENTITIES;
+use crate::gpu::{EntityGpuState, RenderFrameConfig};
+use crate::messages::GpuUpdateMsg;
+use crate::resources::{
+    ComputeBackend, EntityMap, GameTime, GpuReadState, GpuSlotPool, NpcTargetThrashDebug,
+    TownIndex, WorldBounds,
+};
+use crate::world::BuildingKind;
+
+/// Default wall-clock budget (real seconds) for one fast-forward call.
+pub const FAST_FORWARD_MAX_SECS: f32 = 30.0;
+/// Upper bound on the wall-clock budget a caller may request.
+pub const FAST_FORWARD_MAX_SECS_CAP: f32 = 600.0;
+/// Ticks between wall-clock checks.
+const WALL_CHECK_TICKS: u64 = 64;
+
+/// Re-entrancy guard: set while a fast-forward is stepping the world.
+#[derive(Resource, Default)]
+pub struct FastForward {
+    pub running: bool,
+}
+
+/// Outcome of one `fast_forward_to_day` call plus an end-of-run snapshot.
+#[derive(Clone, Debug, Default)]
+pub struct FastForwardReport {
+    pub ticks: u64,
+    pub day: i32,
+    /// False when the wall-clock budget ran out first.
+    pub reached: bool,
+    pub wall_secs: f32,
+    /// Entity slots whose movement and targeting were stepped (0 = no compute state, movement
+    /// frozen). Less than `total_slots` when the CPU fallback cap cut the run short.
+    pub stepped_slots: usize,
+    pub total_slots: usize,
+    /// Living NPCs by (faction, job).
+    pub population: BTreeMap<(i32, Job), usize>,
+    /// (food, gold) per town index.
+    pub stockpiles: Vec<(i32, i32)>,
+    pub buildings: BTreeMap<BuildingKind, usize>,
+}
+
+/// Step the simulation at max speed until `GameTime.day() >= day` or `max_secs` of real time
+/// pass. Allowed in test runs, or in a real game only while it is paused (the player isn't
+/// driving it). Pause and time scale are restored afterwards; ticks run at 1x for determinism.
+pub fn fast_forward_to_day(
+    world: &mut World,
+    day: i32,
+    max_secs: f32,
+) -> Result<FastForwardReport, &'static str> {
+    match world.get_resource::<State<AppState>>().map(|s| *s.get()) {
+        Some(AppState::Running) => {}
+        Some(AppState::Playing) => {
+            if !world.resource::<GameTime>().paused {
+                return Err("pause the game before fast-forwarding");
+            }
+        }
+        _ => return Err("no simulation to fast-forward"),
+    }
+    if world.resource::<FastForward>().running {
+        return Err("fast-forward already running");
+    }
+    if !max_secs.is_finite() || max_secs <= 0.0 {
+        return Err("max_secs must be positive");
+    }
+
+    world.resource_mut::<FastForward>().running = true;
+    let (was_paused, was_scale) = {
+        let mut gt = world.resource_mut::<GameTime>();
+        let saved = (gt.paused, gt.time_scale);
+        gt.paused = false;
+        gt.time_scale = 1.0;
+        saved
+    };
+
+    let budget = Duration::from_secs_f32(max_secs.min(FAST_FORWARD_MAX_SECS_CAP));
+    let step = world.resource::<Time<Fixed>>().timestep();
+    let started = Instant::now();
+    let mut ticks = 0u64;
+    let mut reached = world.resource::<GameTime>().day() >= day;
+    let mut compute = CpuCompute::begin(world);
+    let mut last_message_tick = world.increment_change_tick();
+    while !reached {
+        world.resource_mut::<Time<Fixed>>().advance_by(step);
+        *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+        world.run_schedule(FixedMain);
+        if let Some(compute) = &mut compute {
+            compute.step(world, step.as_secs_f32());
+        }
+        last_message_tick = swap_message_buffers(world, last_message_tick);
+        ticks += 1;
+        reached = world.resource::<GameTime>().day() >= day;
+        if ticks.is_multiple_of(WALL_CHECK_TICKS) && started.elapsed() >= budget {
+            break;
+        }
+    }
+
+    let stepped_slots = compute.map_or(0, |c| c.finish(world));
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+    {
+        let mut gt = world.resource_mut::<GameTime>();
+        gt.paused = was_paused;
+        gt.time_scale = was_scale;
+    }
+    world.resource_mut::<FastForward>().running = false;
+
+    let mut report = snapshot(world);
+    report.ticks = ticks;
+    report.reached = reached;
+    report.wall_secs = started.elapsed().as_secs_f32();
+    report.stepped_slots = stepped_slots;
+    report.total_slots = world.get_resource::<GpuSlotPool>().map_or(0, |s| s.count());
+    Ok(report)
+}
+
+/// CPU movement/targeting for the fast-forward loop, standing in for the frame's
+/// `populate_gpu_state` + GPU compute (or `cpu_compute_system`). None without GPU state
+/// (minimal test apps).
+struct CpuCompute {
+    cursor: MessageCursor<GpuUpdateMsg>,
+    backend: ComputeBackend,
+    stepped: usize,
+}
+
+impl CpuCompute {
+    fn begin(world: &mut World) -> Option<Self> {
+        if !world.contains_resource::<EntityGpuState>()
+            || !world.contains_resource::<GpuReadState>()
+            || !world.contains_resource::<GpuSlotPool>()
+        {
+            return None;
+        }
+        // Messages already buffered were applied by the last frame's populate_gpu_state
+        let cursor = world
+            .get_resource::<Messages<GpuUpdateMsg>>()?
+            .get_cursor_current();
+        // CPU backend for the run: attacks skip projectiles (nothing would fly them) and stale
+        // GPU readbacks can't overwrite the stepped positions
+        let mut backend = world.get_resource_or_init::<ComputeBackend>();
+        let was = *backend;
+        *backend = ComputeBackend::Cpu;
+        Some(Self {
+            cursor,
+            backend: was,
+            stepped: 0,
+        })
+    }
+
+    /// Apply this tick's GPU updates, then step movement and targeting by `dt`.
+    fn step(&mut self, world: &mut World, dt: f32) {
+        let updates: Vec<GpuUpdateMsg> = self
+            .cursor
+            .read(world.resource::<Messages<GpuUpdateMsg>>())
+            .cloned()
+            .collect();
+        let bounds = world
+            .get_resource::<WorldBounds>()
+            .copied()
+            .unwrap_or_default();
+        let sink_window_key = world
+            .get_resource::<Time<Real>>()
+            .map_or(0, |t| t.elapsed_secs_f64().floor() as i64);
+        let (mut params, mode) = world
+            .get_resource::<RenderFrameConfig>()
+            .map(|c| (c.npc.clone(), c.compute_mode))
+            .unwrap_or_default();
+        params.delta = dt;
+        world.resource_scope(|world, mut state: Mut<EntityGpuState>| {
+            world.resource_scope(|world, mut slots: Mut<GpuSlotPool>| {
+                let mut thrash = world.get_resource_or_init::<NpcTargetThrashDebug>();
+                crate::gpu::apply_gpu_frame(
+                    &mut state,
+                    &mut thrash,
+                    &mut slots,
+                    &bounds,
+                    updates.iter(),
+                    sink_window_key,
+                    dt,
+                );
+            });
+            // No extract runs between ticks: visual changes are rebuilt in full afterwards
+            state.visual_dirty_indices.clear();
+            state.flash_only_indices.clear();
+            let count = world
+                .resource::<GpuSlotPool>()
+                .count()
+                .min(CPU_FALLBACK_MAX_ENTITIES);
+            let mut read = world.resource_mut::<GpuReadState>();
+            crate::cpu_compute::cpu_step(&mut state, &mut read, &params, mode, count);
+            self.stepped = count;
+        });
+    }
+
+    /// Restore the backend and hand the stepped state back to the frame pipeline. Returns the
+    /// number of slots stepped on the last tick.
+    fn finish(self, world: &mut World) -> usize {
+        // The loop already applied these; the frame's reader must not apply them again
+        let mut messages = world.resource_mut::<Messages<GpuUpdateMsg>>();
+        messages.update();
+        messages.update();
+        let mut state = world.resource_mut::<EntityGpuState>();
+        state.full_upload = true;
+        state.visual_full_rebuild = true;
+        *world.resource_mut::<ComputeBackend>() = self.backend;
+        self.stepped
+    }
+}
+
+/// Swap every message double-buffer, as `message_update_system` does in `First` each frame.
+/// Running `First` itself would also tick `Time<Virtual>` from the wall clock. Returns the
+/// tick to pass next time; writes after this call are strictly newer than it.
+fn swap_message_buffers(world: &mut World, last_tick: Tick) -> Tick {
+    world.try_resource_scope(|world, mut registry: Mut<MessageRegistry>| {
+        registry.run_updates(world, last_tick);
+    });
+    world.increment_change_tick()
+}
+
+fn snapshot(world: &World) -> FastForwardReport {
+    let entity_map = world.resource::<EntityMap>();
+    let mut population = BTreeMap::new();
+    for npc in entity_map.iter_npcs().filter(|n| !n.dead) {
+        *population.entry((npc.faction, npc.job)).or_default() += 1;
+    }
+    let mut buildings = BTreeMap::new();
+    for inst in entity_map.iter_instances() {
+        *buildings.entry(inst.kind).or_default() += 1;
+    }
+    let town_index = world.resource::<TownIndex>();
+    let town_count = town_index
+        .0
+        .keys()
+        .map(|&t| t + 1)
+        .max()
+        .unwrap_or(0)
+        .max(0) as usize;
+    let stockpiles = (0..town_count)
+        .map(|t| {
+            let entity = town_index.0.get(&(t as i32)).copied();
+            let food = entity
+                .and_then(|e| world.get::<crate::components::FoodStore>(e))
+                .map_or(0, |f| f.0);
+            let gold = entity
+                .and_then(|e| world.get::<crate::components::GoldStore>(e))
+                .map_or(0, |g| g.0);
+            (food, gold)
+        })
+        .collect();
+    FastForwardReport {
+        day: world.resource::<GameTime>().day(),
+        population,
+        stockpiles,
+        buildings,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_app(state: AppState) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(State::new(state));
+        app.init_resource::<GameTime>();
+        app.init_resource::<FastForward>();
+        app.init_resource::<EntityMap>();
+        app.init_resource::<TownIndex>();
+        app.add_systems(FixedUpdate, crate::systems::game_time_system);
+        app
+    }
+
+    #[test]
+    fn runs_to_target_day_and_restores_clock() {
+        let mut app = setup_app(AppState::Running);
+        app.world_mut().resource_mut::<GameTime>().time_scale = 4.0;
+        let report = fast_forward_to_day(app.world_mut(), 3, 60.0).unwrap();
+        assert!(report.reached);
+        assert_eq!(report.day, 3);
+        assert!(report.ticks > 0);
+        let gt = app.world().resource::<GameTime>();
+        assert_eq!(gt.time_scale, 4.0);
+        assert!(!app.world().resource::<FastForward>().running);
+
+        // Already there: no ticks
+        let again = fast_forward_to_day(app.world_mut(), 2, 60.0).unwrap();
+        assert_eq!(again.ticks, 0);
+        assert!(again.reached);
+    }
+
+    #[derive(Message)]
+    struct TickMsg;
+
+    fn write_tick_msg(mut writer: MessageWriter<TickMsg>) {
+        writer.write(TickMsg);
+    }
+
+    #[test]
+    fn swaps_messages_without_ticking_virtual_time() {
+        let mut app = setup_app(AppState::Running);
+        app.add_message::<TickMsg>();
+        app.add_systems(FixedUpdate, write_tick_msg);
+        app.update();
+        let virtual_before = app.world().resource::<Time<Virtual>>().elapsed();
+
+        let report = fast_forward_to_day(app.world_mut(), 2, 60.0).unwrap();
+        assert!(report.ticks > 2);
+        assert_eq!(
+            app.world().resource::<Time<Virtual>>().elapsed(),
+            virtual_before,
+            "virtual clock must not advance during a fast-forward"
+        );
+        // Double buffer: at most this tick's and the previous tick's messages survive
+        assert!(app.world().resource::<Messages<TickMsg>>().len() <= 2);
+    }
+
+    #[test]
+    fn steps_movement_on_the_cpu_and_flags_a_full_upload() {
+        let mut app = setup_app(AppState::Running);
+        app.init_resource::<EntityGpuState>();
+        app.init_resource::<GpuReadState>();
+        app.init_resource::<GpuSlotPool>();
+        app.add_message::<GpuUpdateMsg>();
+        app.world_mut().resource_mut::<GpuSlotPool>().set_next(1);
+        {
+            let mut state = app.world_mut().resource_mut::<EntityGpuState>();
+            state.positions[..2].copy_from_slice(&[100.0, 100.0]);
+            state.targets[..2].copy_from_slice(&[600.0, 100.0]);
+            state.speeds[0] = 100.0;
+            state.factions[0] = 1;
+            state.healths[0] = 1.0;
+        }
+        // A goal sent mid-run goes through the loop's own message cursor
+        app.add_systems(
+            FixedUpdate,
+            |mut sent: Local<bool>, mut writer: MessageWriter<GpuUpdateMsg>| {
+                if !std::mem::replace(&mut *sent, true) {
+                    writer.write(GpuUpdateMsg(crate::messages::GpuUpdate::SetTarget {
+                        idx: 0,
+                        x: 100.0,
+                        y: 400.0,
+                    }));
+                }
+            },
+        );
+
+        let report = fast_forward_to_day(app.world_mut(), 2, 60.0).unwrap();
+        assert_eq!((report.stepped_slots, report.total_slots), (1, 1));
+        let read = app.world().resource::<GpuReadState>();
+        let pos = Vec2::new(read.positions[0], read.positions[1]);
+        assert!(
+            pos.distance(Vec2::new(100.0, 400.0)) < 10.0,
+            "walked to {pos}"
+        );
+        assert!(app.world().resource::<EntityGpuState>().full_upload);
+        assert_eq!(
+            *app.world().resource::<ComputeBackend>(),
+            ComputeBackend::Gpu,
+            "backend restored"
+        );
+        assert!(app.world().resource::<Messages<GpuUpdateMsg>>().is_empty());
+    }
+
+    #[test]
+    fn refuses_live_game_and_reentry() {
+        let mut app = setup_app(AppState::Playing);
+        assert!(fast_forward_to_day(app.world_mut(), 2, 5.0).is_err());
+        app.world_mut().resource_mut::<GameTime>().paused = true;
+        app.world_mut().resource_mut::<FastForward>().running = true;
+        assert!(fast_forward_to_day(app.world_mut(), 2, 5.0).is_err());
+        app.world_mut().resource_mut::<FastForward>().running = false;
+        let report = fast_forward_to_day(app.world_mut(), 2, 5.0).unwrap();
+        assert!(report.reached);
+        assert!(app.world().resource::<GameTime>().paused);
+
+        let mut menu = setup_app(AppState::MainMenu);
+        assert!(fast_forward_to_day(menu.world_mut(), 2, 5.0).is_err());
+    }
+}
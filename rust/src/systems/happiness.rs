@@ -0,0 +1,272 @@
+//! Town happiness — an optional 0-100 meter per town that scales farm output and respawns.
+//! Once a game day `happiness_system` scores each town's food stock per resident, deaths since
+//! the day before and residents per home into `HappinessFactors`, signed points around
+//! `HAPPINESS_BASE`. Happiness then closes only `daily_rate` of the gap to that target, so
+//! one bad day dents a town instead of tanking it. At 0 or 100, farm growth and spawner
+//! respawn countdowns run `effect` slower or faster. Off by default (every town at 1x).
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{FoodStore, TownMarker};
+use crate::constants::{
+    HAPPINESS_BASE, HAPPINESS_CROWDING_MAX, HAPPINESS_CROWDING_WEIGHT, HAPPINESS_DEATH_MAX,
+    HAPPINESS_DEATH_WEIGHT, HAPPINESS_FOOD_COMFORT, HAPPINESS_FOOD_MAX,
+};
+use crate::resources::{EntityMap, GameTime, PopulationStats, TownIndex};
+use crate::world::WorldData;
+
+/// Happiness settings (`endless/happiness`). Saved with the game.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HappinessConfig {
+    pub enabled: bool,
+    /// Fraction of the gap to the day's target closed each day (0-1).
+    pub daily_rate: f32,
+    /// Output/respawn swing at 0 or 100 happiness: 0.25 = 0.75x to 1.25x.
+    pub effect: f32,
+}
+
+impl Default for HappinessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            daily_rate: 0.25,
+            effect: 0.25,
+        }
+    }
+}
+
+/// Why a town's happiness is heading where it is: signed points around `HAPPINESS_BASE`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HappinessFactors {
+    /// Food stock per resident against `HAPPINESS_FOOD_COMFORT`.
+    pub food: f32,
+    /// Residents killed since the previous day (never positive).
+    pub deaths: f32,
+    /// Residents beyond one per home (never positive).
+    pub crowding: f32,
+}
+
+impl HappinessFactors {
+    /// Score a town from its food stock, living residents, deaths today and homes.
+    pub fn from_inputs(food: i32, residents: i32, deaths: i32, homes: usize) -> Self {
+        if residents <= 0 {
+            return Self {
+                deaths: deaths_points(deaths, deaths),
+                ..Default::default()
+            };
+        }
+        let per_resident = food.max(0) as f32 / residents as f32;
+        let crowding = if homes == 0 {
+            -HAPPINESS_CROWDING_MAX
+        } else {
+            let over = residents as f32 / homes as f32 - 1.0;
+            -(over * HAPPINESS_CROWDING_WEIGHT).clamp(0.0, HAPPINESS_CROWDING_MAX)
+        };
+        Self {
+            food: (per_resident / HAPPINESS_FOOD_COMFORT - 1.0).clamp(-1.0, 1.0)
+                * HAPPINESS_FOOD_MAX,
+            // Today's dead were residents this morning
+            deaths: deaths_points(deaths, residents + deaths),
+            crowding,
+        }
+    }
+
+    /// Where happiness settles if nothing changes.
+    pub fn target(&self) -> f32 {
+        (HAPPINESS_BASE + self.food + self.deaths + self.crowding).clamp(0.0, 100.0)
+    }
+}
+
+fn deaths_points(deaths: i32, residents: i32) -> f32 {
+    if deaths <= 0 || residents <= 0 {
+        return 0.0;
+    }
+    -(deaths as f32 / residents as f32 * HAPPINESS_DEATH_WEIGHT).min(HAPPINESS_DEATH_MAX)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Happiness {
+    pub value: f32,
+    /// Breakdown from the latest daily update.
+    pub factors: HappinessFactors,
+}
+
+impl Default for Happiness {
+    fn default() -> Self {
+        Self {
+            value: HAPPINESS_BASE,
+            factors: HappinessFactors::default(),
+        }
+    }
+}
+
+impl Happiness {
+    /// One day's move toward `factors.target()`, closing `rate` of the gap.
+    pub fn step(&mut self, factors: HappinessFactors, rate: f32) {
+        let rate = rate.clamp(0.0, 1.0);
+        self.value = (self.value + (factors.target() - self.value) * rate).clamp(0.0, 100.0);
+        self.factors = factors;
+    }
+}
+
+/// Per-town happiness, indexed by town. Empty while the feature is off. Saved with the game.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TownHappiness {
+    pub towns: Vec<Happiness>,
+    /// Each town's dead count at the last update, to tell today's deaths.
+    last_dead: Vec<i32>,
+    last_day: i32,
+    /// `HappinessConfig.effect` in force (0 while off).
+    effect: f32,
+}
+
+impl TownHappiness {
+    pub fn get_town_happiness(&self, town_idx: usize) -> Option<&Happiness> {
+        self.towns.get(town_idx)
+    }
+
+    /// Farm output and respawn speed multiplier for a town. 1.0 while off or unknown.
+    pub fn output_mult(&self, town_idx: i32) -> f32 {
+        usize::try_from(town_idx)
+            .ok()
+            .and_then(|t| self.towns.get(t))
+            .map_or(1.0, |h| {
+                1.0 + (h.value - HAPPINESS_BASE) / HAPPINESS_BASE * self.effect
+            })
+    }
+}
+
+/// Update each town's happiness once per game day (and score new towns when they appear).
+pub fn happiness_system(
+    config: Res<HappinessConfig>,
+    mut happiness: ResMut<TownHappiness>,
+    game_time: Res<GameTime>,
+    world_data: Res<WorldData>,
+    entity_map: Res<EntityMap>,
+    pop_stats: Res<PopulationStats>,
+    town_index: Res<TownIndex>,
+    food_q: Query<&FoodStore, With<TownMarker>>,
+) {
+    if !config.enabled {
+        if !happiness.towns.is_empty() || happiness.effect != 0.0 {
+            *happiness = TownHappiness::default();
+        }
+        return;
+    }
+    happiness.effect = config.effect.clamp(0.0, 1.0);
+    let day = game_time.day();
+    // A clock behind the last update is a new game or a load: start over
+    if day < happiness.last_day {
+        happiness.towns.clear();
+        happiness.last_dead.clear();
+    }
+    let n = world_data.towns.len();
+    let new_day = day != happiness.last_day;
+    if !new_day && happiness.towns.len() >= n {
+        return;
+    }
+    happiness.last_day = day;
+
+    let mut alive = vec![0; n];
+    let mut dead = vec![0; n];
+    for (&(_, town), stats) in &pop_stats.0 {
+        if let Ok(t) = usize::try_from(town)
+            && t < n
+        {
+            alive[t] += stats.alive;
+            dead[t] += stats.dead;
+        }
+    }
+    let mut homes = vec![0usize; n];
+    for &slot in entity_map.spawner_slots() {
+        if let Some(inst) = entity_map.get_instance(slot)
+            && (inst.town_idx as usize) < n
+        {
+            homes[inst.town_idx as usize] += 1;
+        }
+    }
+
+    let known = happiness.towns.len().min(n);
+    let rate = config.daily_rate;
+    for t in 0..n {
+        if t < known && !new_day {
+            continue;
+        }
+        let food = town_index
+            .0
+            .get(&(t as i32))
+            .and_then(|&e| food_q.get(e).ok())
+            .map_or(0, |f| f.0);
+        let deaths = if t < known {
+            dead[t] - happiness.last_dead[t]
+        } else {
+            0
+        };
+        let factors = HappinessFactors::from_inputs(food, alive[t], deaths, homes[t]);
+        if t < known {
+            happiness.towns[t].step(factors, rate);
+            happiness.last_dead[t] = dead[t];
+        } else {
+            // New towns start neutral and drift like everyone else
+            happiness.towns.push(Happiness {
+                value: HAPPINESS_BASE,
+                factors,
+            });
+            happiness.last_dead.push(dead[t]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factors_explain_the_target() {
+        // 10 residents in 10 homes with 50 food: everything neutral
+        let calm = HappinessFactors::from_inputs(50, 10, 0, 10);
+        assert_eq!(calm, HappinessFactors::default());
+        assert_eq!(calm.target(), HAPPINESS_BASE);
+
+        let rich = HappinessFactors::from_inputs(1000, 10, 0, 10);
+        assert_eq!(rich.food, HAPPINESS_FOOD_MAX);
+        let starving = HappinessFactors::from_inputs(0, 10, 0, 10);
+        assert_eq!(starving.food, -HAPPINESS_FOOD_MAX);
+
+        // 1 of 10 residents died today: -15
+        let grieving = HappinessFactors::from_inputs(45, 9, 1, 10);
+        assert!((grieving.deaths + 15.0).abs() < 1e-3);
+        // 15 residents in 10 homes: half over, -25
+        let crowded = HappinessFactors::from_inputs(75, 15, 0, 10);
+        assert_eq!(crowded.crowding, -HAPPINESS_CROWDING_MAX);
+        assert_eq!(crowded.target(), HAPPINESS_BASE - HAPPINESS_CROWDING_MAX);
+    }
+
+    #[test]
+    fn happiness_moves_gradually() {
+        let mut h = Happiness::default();
+        let awful = HappinessFactors::from_inputs(0, 10, 5, 2);
+        assert_eq!(awful.target(), 0.0);
+        h.step(awful, 0.25);
+        assert!(h.value > 35.0, "one bad day only dents it: {}", h.value);
+        assert_eq!(h.factors, awful);
+        for _ in 0..20 {
+            h.step(awful, 0.25);
+        }
+        assert!(h.value < 1.0, "a long slump still gets there: {}", h.value);
+
+        let mut towns = TownHappiness {
+            towns: vec![Happiness::default(), h],
+            effect: 0.25,
+            ..Default::default()
+        };
+        assert_eq!(towns.output_mult(0), 1.0);
+        assert!((towns.output_mult(1) - 0.75).abs() < 0.01);
+        assert_eq!(towns.output_mult(7), 1.0, "unknown town");
+        towns.effect = 0.0;
+        assert_eq!(towns.output_mult(1), 1.0, "off");
+    }
+}
@@ -1,13 +1,15 @@
 //! Health systems - Damage, death detection, cleanup, healing aura
 
 use crate::components::*;
-use crate::constants::STARVING_HP_CAP;
+use crate::constants::{DEATH_KNOCKBACK_MAX, DEATH_KNOCKBACK_SECS, STARVING_HP_CAP};
 use crate::messages::CombatLogMsg;
-use crate::messages::{DamageMsg, DirtyWriters, GpuUpdate, GpuUpdateMsg, ProjGpuUpdateMsg};
+use crate::messages::{
+    BuildingGridDirtyMsg, DamageMsg, DirtyWriters, GpuUpdate, GpuUpdateMsg, ProjGpuUpdateMsg,
+};
 use crate::resources::{
-    ActiveHealingSlots, BuildingHealState, CombatEventKind, EndlessMode, EntityMap, FactionStats,
-    GameTime, GpuReadState, GpuSlotPool, HealingZoneCache, HealthDebug, KillStats, PopulationStats,
-    SelectedBuilding, SelectedNpc, SquadState,
+    ActiveHealingSlots, BuildingHealState, CombatEventKind, CombatZones, EndlessMode, EntityMap,
+    FactionStats, GameTime, GpuReadState, GpuSlotPool, HealingZoneCache, HealthDebug, KillStats,
+    NpcFacing, PopulationStats, SelectedBuilding, SelectedNpc, SquadState, WorldBounds,
 };
 use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
@@ -22,6 +24,54 @@ const MAX_DEATHS_PER_FRAME: usize = 2000;
 pub struct DeathQueue {
     pub pending: Vec<usize>, // GPU slots of NPCs waiting to be processed
 }
+
+/// A body sliding away from the killing blow. Its slot stays allocated (and drawn) until the
+/// slide ends, so it can't be handed to a new NPC mid-animation.
+#[derive(Clone, Copy, Debug)]
+pub struct Corpse {
+    pub slot: usize,
+    pub from: Vec2,
+    pub to: Vec2,
+    pub elapsed: f32,
+}
+
+impl Corpse {
+    /// Ease-out slide: fast off the hit, settling at `to` after DEATH_KNOCKBACK_SECS.
+    pub fn position(&self) -> Vec2 {
+        let t = (self.elapsed / DEATH_KNOCKBACK_SECS).clamp(0.0, 1.0);
+        self.from.lerp(self.to, 1.0 - (1.0 - t) * (1.0 - t))
+    }
+}
+
+/// Death knockback: killed NPCs slide `strength` px away from whoever last hit them (unit or
+/// tower) before the slot is hidden and freed (`corpse_knockback_system`). 0 = off, bodies
+/// vanish on death. Deaths with no attacker on record and mass-death frames never slide.
+#[derive(Resource, Default)]
+pub struct DeathKnockback {
+    pub strength: f32,
+    pub corpses: Vec<Corpse>,
+}
+
+impl DeathKnockback {
+    /// Slide distance in px, clamped to 0..=DEATH_KNOCKBACK_MAX (non-finite = off).
+    pub fn set_death_knockback(&mut self, strength: f32) {
+        self.strength = if strength.is_finite() {
+            strength.clamp(0.0, DEATH_KNOCKBACK_MAX)
+        } else {
+            0.0
+        };
+    }
+
+    /// Where a body at `pos` struck from `from` comes to rest, kept inside `bounds`. None when
+    /// knockback is off or the blow has no direction (killer standing on the victim).
+    pub fn landing(&self, pos: Vec2, from: Vec2, bounds: &WorldBounds) -> Option<Vec2> {
+        if self.strength <= 0.0 {
+            return None;
+        }
+        let dir = (pos - from).try_normalize()?;
+        Some(bounds.clamp(pos + dir * self.strength))
+    }
+}
 use crate::systems::economy::*;
 use crate::systems::stats::{CombatConfig, UPGRADES, level_from_xp, resolve_combat_stats};
 use crate::world::{BuildingKind, WorldData, WorldGrid};
@@ -64,31 +114,82 @@ pub struct DeathResources<'w, 's> {
     pub personality_q: Query<'w, 's, &'static crate::components::Personality>,
     pub work_state_q: Query<'w, 's, &'static crate::components::NpcWorkState>,
     pub carried_loot_q: Query<'w, 's, &'static mut crate::components::CarriedLoot>,
+    pub inventory_q: Query<'w, 's, &'static crate::components::Inventory>,
     pub sfx_writer: MessageWriter<'w, crate::resources::PlaySfxMsg>,
     pub proj_updates: MessageWriter<'w, ProjGpuUpdateMsg>,
     pub work_intents: MessageWriter<'w, crate::messages::WorkIntentMsg>,
     pub gpu_state: Res<'w, crate::gpu::EntityGpuState>,
     pub proj_alloc: ResMut<'w, crate::resources::ProjSlotAllocator>,
     pub next_loot_id: ResMut<'w, crate::resources::NextLootItemId>,
+    pub loot_config: Res<'w, crate::resources::LootConfig>,
     pub equipment_q: Query<'w, 's, &'static crate::components::NpcEquipment>,
     pub reputation: ResMut<'w, crate::resources::Reputation>,
+    pub faction_mvp: ResMut<'w, crate::resources::FactionMvp>,
     pub spawner_q: Query<'w, 's, &'static crate::components::SpawnerState, With<Building>>,
     pub tower_bld_q:
         Query<'w, 's, &'static mut crate::components::TowerBuildingState, With<Building>>,
+    pub anchored_q: Query<'w, 's, (Entity, &'static GpuSlot), With<crate::components::Anchored>>,
+    pub assignment_q: Query<'w, 's, (Option<&'static AssignedBed>, Option<&'static AssignedPost>)>,
+    pub militia_q: Query<'w, 's, &'static Militia>,
+    pub death_knockback: ResMut<'w, DeathKnockback>,
+    pub bounds: Res<'w, WorldBounds>,
+    pub panic: ResMut<'w, crate::resources::PanicState>,
+    pub building_costs: Res<'w, crate::resources::BuildingCosts>,
+}
+
+/// Keep the `CombatZones` cell table current: full rebuild after world init/load or a
+/// modifier change, wall/road overlay restamp on building changes.
+pub fn sync_combat_zones_system(
+    mut grid_dirty: MessageReader<BuildingGridDirtyMsg>,
+    mut zones: ResMut<CombatZones>,
+    grid: Res<WorldGrid>,
+    entity_map: Res<EntityMap>,
+) {
+    if grid_dirty.read().count() > 0 || zones.needs_rebuild(&grid) {
+        zones.sync(&grid, &entity_map);
+    }
 }
 
 /// Unified damage system: applies damage to both NPCs and buildings.
 /// entity_idx = unified slot (same as GPU index, no offset arithmetic).
+/// NPC damage is scaled by the `CombatZones` multiplier of the defender's cell.
+/// Damage multiplier for a hit on a defender at `defender` facing `facing` from `attacker`:
+/// 1 inside the frontal arc (`FLANK_FRONT_DOT`), `1 + bonus / 3` from the side, `1 + bonus`
+/// straight from behind. Unknown facing (zero) gives 1; `bonus` is capped at `FLANK_BONUS_CAP`.
+pub fn flank_multiplier(facing: Vec2, defender: Vec2, attacker: Vec2, bonus: f32) -> f32 {
+    use crate::constants::{FLANK_BONUS_CAP, FLANK_FRONT_DOT};
+    let bonus = bonus.min(FLANK_BONUS_CAP);
+    let to_attacker = (attacker - defender).normalize_or_zero();
+    if bonus <= 0.0 || facing == Vec2::ZERO || to_attacker == Vec2::ZERO {
+        return 1.0;
+    }
+    let exposure =
+        ((FLANK_FRONT_DOT - facing.dot(to_attacker)) / (FLANK_FRONT_DOT + 1.0)).clamp(0.0, 1.0);
+    1.0 + bonus * exposure
+}
+
 pub fn damage_system(
     mut commands: Commands,
     mut events: MessageReader<DamageMsg>,
     entity_map: Res<EntityMap>,
     mut npc_health_q: Query<&mut Health, Without<Building>>,
     mut building_query: Query<&mut Health, With<Building>>,
+    stance_q: Query<&CombatStance>,
+    mut anchored_q: Query<&mut Activity, (With<Anchored>, Without<Building>)>,
     mut debug: ResMut<HealthDebug>,
     mut gpu_updates: MessageWriter<GpuUpdateMsg>,
     mut heal_state: ResMut<BuildingHealState>,
+    gpu_state: Res<GpuReadState>,
+    zones: Res<CombatZones>,
+    config: Res<CombatConfig>,
+    facing: Res<NpcFacing>,
 ) {
+    let pos_at = |slot: usize| {
+        gpu_state
+            .positions
+            .get(slot * 2..slot * 2 + 2)
+            .map(|p| Vec2::new(p[0], p[1]))
+    };
     let mut damage_count = 0;
     for event in events.read() {
         damage_count += 1;
@@ -104,12 +205,32 @@ pub fn damage_system(
             let Ok(mut health) = npc_health_q.get_mut(npc.entity) else {
                 continue;
             };
-            health.0 = (health.0 - event.amount).max(0.0);
+            // Cover/exposure of the defender's current cell, then flanking by the attacker
+            let amount = pos_at(idx).map_or(event.amount, |pos| {
+                let flank = usize::try_from(event.attacker)
+                    .ok()
+                    .and_then(pos_at)
+                    .map_or(1.0, |from| {
+                        flank_multiplier(facing.facing(idx), pos, from, config.flank_bonus)
+                    });
+                event.amount * zones.at(pos) * flank
+            });
+            health.0 = (health.0 - amount).max(0.0);
             if event.attacker >= 0 {
                 if let Ok(mut ec) = commands.get_entity(npc.entity) {
                     ec.insert(LastHitBy(event.attacker));
                 }
             }
+            // ReturnFire: any hit (re)opens the engagement window
+            if matches!(stance_q.get(npc.entity), Ok(CombatStance::ReturnFire)) {
+                if let Ok(mut ec) = commands.get_entity(npc.entity) {
+                    ec.insert(Provoked(crate::constants::RETURN_FIRE_WINDOW));
+                }
+            }
+            // Anchored sleepers wake on the first hit
+            if let Ok(mut activity) = anchored_q.get_mut(npc.entity) {
+                crate::systems::wake_anchored(&mut commands, npc.entity, &mut activity);
+            }
             // Mark dead immediately so death_system doesn't need a full scan
             if health.0 <= 0.0 {
                 if let Ok(mut ec) = commands.get_entity(npc.entity) {
@@ -304,6 +425,21 @@ pub fn death_system(
                 }
             }
 
+            // NPCs anchored at the destroyed building wake up
+            let wake_r2 = crate::constants::TOWN_GRID_SPACING * crate::constants::TOWN_GRID_SPACING;
+            for (npc_entity, npc_slot) in res.anchored_q.iter() {
+                let base = npc_slot.0 * 2;
+                let Some(p) = res.gpu_state.positions.get(base..base + 2) else {
+                    continue;
+                };
+                if Vec2::new(p[0], p[1]).distance_squared(pos) > wake_r2 {
+                    continue;
+                }
+                if let Ok(mut activity) = res.activity_q.get_mut(npc_entity) {
+                    crate::systems::wake_anchored(&mut commands, npc_entity, &mut activity);
+                }
+            }
+
             let town_name = res
                 .world_data
                 .towns
@@ -434,7 +570,7 @@ pub fn death_system(
             }
 
             // Loot: attacker picks up building loot and returns home
-            if let Some(drop) = building_def(kind).loot_drop() {
+            if let Some(drop) = building_def(kind).loot_drop(&res.building_costs) {
                 let amount = if drop.min == drop.max {
                     drop.min
                 } else {
@@ -593,6 +729,44 @@ pub fn death_system(
         commands.entity(entity).despawn();
         despawn_count += 1;
 
+        // Corpse loot: lies where the body fell for anyone to claim (loot_system)
+        let items = if res.loot_config.drop_inventory {
+            res.inventory_q
+                .get(entity)
+                .map(|inv| inv.0.clone())
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        if res.loot_config.enabled || !items.is_empty() {
+            let seed = ((slot as u64) << 32) ^ game_time.total_seconds.to_bits() as u64;
+            let pos = res
+                .gpu_state
+                .positions
+                .get(slot * 2..slot * 2 + 2)
+                .map(|p| Vec2::new(p[0], p[1]));
+            let roll = if res.loot_config.enabled {
+                res.loot_config.roll(job, seed)
+            } else {
+                None
+            };
+            if let Some(pos) = pos.filter(|_| roll.is_some() || !items.is_empty()) {
+                let roll = roll.unwrap_or_default();
+                let item = roll.item.then(|| {
+                    let id = res.next_loot_id.alloc();
+                    crate::constants::roll_loot_item(id, seed as u32)
+                });
+                commands.spawn(crate::components::GroundLoot {
+                    pos,
+                    food: roll.food,
+                    gold: roll.gold,
+                    item,
+                    items,
+                    dropped_at: game_time.total_seconds,
+                });
+            }
+        }
+
         // XP grant: reward killer with XP, level-up, and NPC kill loot
         if last_hit_by >= 0 {
             // Only clone equipment when there's actually something to transfer
@@ -621,6 +795,19 @@ pub fn death_system(
                 Vec::new()
             };
             let killer_slot = last_hit_by as usize;
+            let victim_name = npc_stats_q
+                .get(entity)
+                .map(|s| s.name.clone())
+                .unwrap_or_default();
+            let feed_entry = |killer_name: &str| crate::resources::KillFeedEntry {
+                killer_slot,
+                killer_name: killer_name.to_string(),
+                victim_name: victim_name.clone(),
+                victim_faction: faction,
+                day: game_time.day(),
+                hour: game_time.hour(),
+                minute: game_time.minute(),
+            };
             if let Some(killer) = res.entity_map.get_npc(killer_slot) {
                 let k_slot = killer.slot;
                 let k_entity = killer.entity;
@@ -632,6 +819,10 @@ pub fn death_system(
                 let (old_xp, new_xp) = if let Ok(mut stats) = npc_stats_q.get_mut(k_entity) {
                     let old = stats.xp;
                     stats.xp += 100;
+                    stats.kills += 1;
+                    res.faction_mvp.on_kill(k_faction, k_slot, stats.kills);
+                    let entry = feed_entry(&stats.name);
+                    res.faction_mvp.push_feed(k_faction, entry);
                     (old, stats.xp)
                 } else {
                     (0, 100)
@@ -911,6 +1102,8 @@ pub fn death_system(
                     continue;
                 };
                 tbs.kills += 1;
+                res.faction_mvp
+                    .push_feed(tower_faction, feed_entry(kind_name));
                 let old_xp = tbs.xp;
                 tbs.xp += 100;
                 let old_level = level_from_xp(old_xp);
@@ -1020,11 +1213,12 @@ pub fn death_system(
             }
         }
 
-        // NPC cleanup
-        pop_dec_alive(&mut res.pop_stats, job, town_idx);
-        pop_inc_dead(&mut res.pop_stats, job, town_idx);
+        // NPC cleanup (militia count under the job they were conscripted from)
+        let pop_job = res.militia_q.get(entity).map_or(job, |m| m.prev_job);
+        pop_dec_alive(&mut res.pop_stats, pop_job, town_idx);
+        pop_inc_dead(&mut res.pop_stats, pop_job, town_idx);
         if activity.kind.def().is_working {
-            pop_dec_working(&mut res.pop_stats, job, town_idx);
+            pop_dec_working(&mut res.pop_stats, pop_job, town_idx);
         }
 
         // Defer worksite release to centralized resolver
@@ -1034,6 +1228,14 @@ pub fn death_system(
                 worksite: worksite_uid,
             },
         ));
+        // Manual bed/post assignments hold their own reservations
+        if let Ok((bed, post)) = res.assignment_q.get(entity) {
+            for held in bed.map(|b| b.bed).into_iter().chain(post.map(|p| p.0)) {
+                if let Some(held_slot) = res.entity_map.slot_for_entity(held) {
+                    res.entity_map.release(held_slot);
+                }
+            }
+        }
         if job == Job::Miner {
             res.dirty_writers
                 .mining
@@ -1087,9 +1289,45 @@ pub fn death_system(
 
         res.faction_stats.dec_alive(faction);
         res.faction_stats.inc_dead(faction);
-
-        // npc_by_town cleanup handled by unregister_npc inside hide_npc
-        hide_npc(slot, &mut res.entity_map, &mut res.slots, &mut gpu_updates);
+        res.faction_mvp.on_death(faction, slot);
+
+        // Death knockback: slide the body off the killing blow; the slot is freed when it stops
+        let pos_at = |s: usize| {
+            res.gpu_state
+                .positions
+                .get(s * 2..s * 2 + 2)
+                .map(|p| Vec2::new(p[0], p[1]))
+        };
+        let slide = usize::try_from(last_hit_by)
+            .ok()
+            .filter(|_| !mass_death)
+            .and_then(|killer| Some((pos_at(slot)?, pos_at(killer)?)))
+            .and_then(|(pos, from)| {
+                let to = res.death_knockback.landing(pos, from, &res.bounds)?;
+                Some((pos, to))
+            });
+        // A corpse neither panics nor spreads panic; the next occupant starts calm
+        res.panic.forget(slot);
+        if let Some((from, to)) = slide {
+            res.entity_map.unregister_npc(slot);
+            gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetSpeed {
+                idx: slot,
+                speed: 0.0,
+            }));
+            gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetFlags {
+                idx: slot,
+                flags: crate::constants::ENTITY_FLAG_INACTIVE,
+            }));
+            res.death_knockback.corpses.push(Corpse {
+                slot,
+                from,
+                to,
+                elapsed: 0.0,
+            });
+        } else {
+            // npc_by_town cleanup handled by unregister_npc inside hide_npc
+            hide_npc(slot, &mut res.entity_map, &mut res.slots, &mut gpu_updates);
+        }
     }
 
     // Mass death summary log (replaces N individual messages when >50 die in one frame)
@@ -1106,6 +1344,121 @@ pub fn death_system(
     }
 
     res.debug.despawned_this_frame = despawn_count;
+
+    // Lazy MVP rescan for factions whose MVP died (rare — full scan of that faction)
+    if !res.faction_mvp.stale.is_empty() {
+        let stale: Vec<i32> = res.faction_mvp.stale.iter().copied().collect();
+        for f in stale {
+            let living: Vec<(usize, i32)> = res
+                .entity_map
+                .iter_npcs()
+                .filter(|n| !n.dead && n.faction == f)
+                .filter_map(|n| npc_stats_q.get(n.entity).ok().map(|s| (n.slot, s.kills)))
+                .collect();
+            res.faction_mvp.rescan(f, living);
+        }
+    }
+}
+
+/// Move death-knockback corpses along their slide and hide + free each slot once it lands.
+/// Chained after death_system, so a body starts moving on the tick it fell.
+pub fn corpse_knockback_system(
+    mut knockback: ResMut<DeathKnockback>,
+    mut slots: ResMut<GpuSlotPool>,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+) {
+    if knockback.corpses.is_empty() {
+        return;
+    }
+    let dt = game_time.delta(&time);
+    if dt <= 0.0 {
+        return;
+    }
+    knockback.corpses.retain_mut(|corpse| {
+        corpse.elapsed += dt;
+        if corpse.elapsed >= DEATH_KNOCKBACK_SECS {
+            slots.free(corpse.slot);
+            return false;
+        }
+        let pos = corpse.position();
+        gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetPosition {
+            idx: corpse.slot,
+            x: pos.x,
+            y: pos.y,
+        }));
+        true
+    });
+}
+
+/// Scripted NPC removal (migration, events, BRP) — not a death: no XP, loot, kill or dead counts.
+/// Fully reclaims the slot so the next occupant starts clean: despawns the entity, releases
+/// worksite claims, clears per-slot log/healing caches, unregisters from EntityMap (incl.
+/// npc_by_town), and frees the GpuSlotPool slot (queues GPU hide + reset on realloc).
+pub fn despawn_npc_system(
+    mut commands: Commands,
+    mut events: MessageReader<crate::messages::DespawnNpcMsg>,
+    mut res: DeathResources,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+    mut selected: ResMut<SelectedNpc>,
+    mut npc_logs: ResMut<crate::resources::NpcLogCache>,
+    mut active_healing: ResMut<ActiveHealingSlots>,
+) {
+    for msg in events.read() {
+        let slot = msg.slot;
+        let Some(npc) = res.entity_map.get_npc(slot) else {
+            continue;
+        };
+        // Already dying — death_system owns cleanup
+        if npc.dead {
+            continue;
+        }
+        let (entity, faction, town_idx, job) = (npc.entity, npc.faction, npc.town_idx, npc.job);
+        let is_working = res
+            .activity_q
+            .get(entity)
+            .is_ok_and(|a| a.kind.def().is_working);
+        let worksite = res.work_state_q.get(entity).ok().and_then(|ws| ws.worksite);
+
+        let pop_job = res.militia_q.get(entity).map_or(job, |m| m.prev_job);
+        pop_dec_alive(&mut res.pop_stats, pop_job, town_idx);
+        if is_working {
+            pop_dec_working(&mut res.pop_stats, pop_job, town_idx);
+        }
+        res.faction_stats.dec_alive(faction);
+        // Rescanned by the next death_system pass
+        res.faction_mvp.on_death(faction, slot);
+
+        res.work_intents.write(crate::messages::WorkIntentMsg(
+            crate::messages::WorkIntent::Release { entity, worksite },
+        ));
+        if job == Job::Miner {
+            res.dirty_writers
+                .mining
+                .write(crate::messages::MiningDirtyMsg);
+        }
+        // Squad membership pruned by squad_cleanup_system
+        res.dirty_writers
+            .squads
+            .write(crate::messages::SquadsDirtyMsg);
+
+        // Per-slot caches
+        res.death_queue.pending.retain(|&s| s != slot);
+        npc_logs.clear_slot(slot);
+        npc_logs.set_slot_faction(slot, -1);
+        if slot < active_healing.mark.len() && active_healing.mark[slot] == 1 {
+            active_healing.mark[slot] = 0;
+            active_healing.slots.retain(|&s| s != slot);
+        }
+        res.panic.forget(slot);
+        if selected.0 == slot as i32 {
+            selected.0 = -1;
+        }
+
+        commands.entity(entity).despawn();
+        hide_npc(slot, &mut res.entity_map, &mut res.slots, &mut gpu_updates);
+    }
 }
 
 /// Rebuild healing zone cache when dirty (upgrade purchased, town changed, save loaded).
@@ -1422,6 +1775,90 @@ pub fn healing_system(
     debug.healing_exits = exit_count;
 }
 
+/// Crowd press damage for one application: `per_tick`, clamped so HP never drops below
+/// `TRAMPLE_HP_FLOOR` of max.
+pub(crate) fn trample_amount(hp: f32, max_hp: f32, per_tick: f32) -> f32 {
+    let floor = max_hp * crate::constants::TRAMPLE_HP_FLOOR;
+    per_tick.min(hp - floor).max(0.0)
+}
+
+/// Crowd press: NPCs in spatial grid cells holding more than `GridConfig.max_per_cell` NPCs take
+/// `CombatConfig.trample_damage` every `TRAMPLE_INTERVAL_SECS` (routed through DamageMsg).
+/// NPCs inside any town's healing zone are exempt so idle crowds at a fountain are safe.
+/// Cells are binned CPU-side from GPU readback positions using the GPU grid's cell size.
+pub fn trample_system(
+    entity_map: Res<EntityMap>,
+    gpu_state: Res<GpuReadState>,
+    cache: Res<HealingZoneCache>,
+    config: Res<CombatConfig>,
+    npc_q: Query<(&Health, &CachedStats), (Without<Building>, Without<Dead>)>,
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    mut damage_writer: MessageWriter<DamageMsg>,
+    grid: Res<crate::gpu::GridConfig>,
+    mut timer: Local<f32>,
+) {
+    if config.trample_damage <= 0.0 {
+        *timer = 0.0;
+        return;
+    }
+    *timer += game_time.delta(&time);
+    if *timer < crate::constants::TRAMPLE_INTERVAL_SECS {
+        return;
+    }
+    *timer = 0.0;
+
+    let positions = &gpu_state.positions;
+    let cell_size = grid.cell_size;
+    let mut crowd: Vec<(Entity, Vec2, (i32, i32))> = Vec::new();
+    let mut counts: std::collections::HashMap<(i32, i32), u32> = std::collections::HashMap::new();
+    for npc in entity_map.iter_npcs() {
+        if npc.dead {
+            continue;
+        }
+        let base = npc.slot * 2;
+        if base + 1 >= positions.len() {
+            continue;
+        }
+        let pos = Vec2::new(positions[base], positions[base + 1]);
+        if pos.x < -9000.0 {
+            continue;
+        }
+        let cell = (
+            (pos.x / cell_size).floor() as i32,
+            (pos.y / cell_size).floor() as i32,
+        );
+        *counts.entry(cell).or_insert(0) += 1;
+        crowd.push((npc.entity, pos, cell));
+    }
+
+    for (entity, pos, cell) in crowd {
+        if counts.get(&cell).copied().unwrap_or(0) <= grid.max_per_cell {
+            continue;
+        }
+        let in_zone = cache
+            .by_faction
+            .iter()
+            .flatten()
+            .any(|zone| pos.distance_squared(zone.center) <= zone.enter_radius_sq);
+        if in_zone {
+            continue;
+        }
+        let Ok((health, cached)) = npc_q.get(entity) else {
+            continue;
+        };
+        let amount = trample_amount(health.0, cached.max_health, config.trample_damage);
+        if amount > 0.0 {
+            damage_writer.write(DamageMsg {
+                target: entity,
+                amount,
+                attacker: -1,
+                attacker_faction: -1,
+            });
+        }
+    }
+}
+
 /// Passive HP regen for NPCs with hp_regen upgrade (outside fountain healing).
 pub fn npc_regen_system(
     mut npc_q: Query<(&mut Health, &CachedStats), (Without<Building>, Without<Dead>)>,
@@ -1613,6 +2050,10 @@ mod tests {
         app.insert_resource(HealthDebug::default());
         app.insert_resource(BuildingHealState::default());
         app.insert_resource(PendingDamage::default());
+        app.init_resource::<GpuReadState>();
+        app.init_resource::<CombatZones>();
+        app.init_resource::<CombatConfig>();
+        app.init_resource::<NpcFacing>();
         app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
             std::time::Duration::from_secs_f32(1.0),
         ));
@@ -1655,6 +2096,101 @@ mod tests {
         );
     }
 
+    #[test]
+    fn damage_provokes_return_fire_units_only() {
+        let mut app = setup_damage_app();
+        let ambusher = spawn_damageable_npc(&mut app, 0, 1, 100.0);
+        let regular = spawn_damageable_npc(&mut app, 1, 2, 100.0);
+        app.world_mut()
+            .entity_mut(ambusher)
+            .insert(CombatStance::ReturnFire);
+        for target in [ambusher, regular] {
+            app.world_mut()
+                .resource_mut::<PendingDamage>()
+                .0
+                .push(DamageMsg {
+                    target,
+                    amount: 5.0,
+                    attacker: -1,
+                    attacker_faction: 0,
+                });
+        }
+
+        app.update();
+        let provoked = app.world().get::<Provoked>(ambusher).map(|p| p.0);
+        assert_eq!(provoked, Some(crate::constants::RETURN_FIRE_WINDOW));
+        assert!(app.world().get::<Provoked>(regular).is_none());
+    }
+
+    #[test]
+    fn damage_scaled_by_defender_cell() {
+        let mut app = setup_damage_app();
+        let mut grid = WorldGrid::default();
+        grid.width = 10;
+        grid.height = 10;
+        grid.cell_size = 64.0;
+        grid.cells = vec![crate::world::WorldCell::default(); 100];
+        grid.cells[7 * 10 + 7].terrain = crate::world::Biome::Water;
+        let mut em = EntityMap::default();
+        for (slot, col) in [(100, 2), (101, 3), (102, 4)] {
+            em.add_instance(crate::resources::BuildingInstance {
+                kind: BuildingKind::Wall,
+                position: Vec2::new(col as f32 * 64.0 + 32.0, 2.0 * 64.0 + 32.0),
+                slot,
+                town_idx: 0,
+                faction: 0,
+            });
+        }
+        let mut zones = CombatZones::default();
+        zones.sync(&grid, &em);
+        // Cell (3,3) touches all three walls: 0.85^3 = 0.61, just above the floor
+        assert!((zones.at(Vec2::new(3.5 * 64.0, 3.5 * 64.0)) - 0.614).abs() < 0.01);
+        zones.set_zone_modifier(crate::resources::ZoneKind::Wall, 0.5);
+        zones.sync(&grid, &em);
+        assert_eq!(
+            zones.at(Vec2::new(3.5 * 64.0, 3.5 * 64.0)),
+            crate::constants::COMBAT_COVER_FLOOR,
+            "stacked cover is capped"
+        );
+        app.insert_resource(zones);
+        app.insert_resource(GpuReadState {
+            positions: vec![
+                2.5 * 64.0,
+                3.5 * 64.0,
+                7.5 * 64.0,
+                7.5 * 64.0,
+                0.5,
+                9.5 * 64.0,
+            ],
+            ..Default::default()
+        });
+
+        let covered = spawn_damageable_npc(&mut app, 0, 1, 100.0);
+        let swimming = spawn_damageable_npc(&mut app, 1, 2, 100.0);
+        let open = spawn_damageable_npc(&mut app, 2, 3, 100.0);
+        for target in [covered, swimming, open] {
+            app.world_mut()
+                .resource_mut::<PendingDamage>()
+                .0
+                .push(DamageMsg {
+                    target,
+                    amount: 20.0,
+                    attacker: -1,
+                    attacker_faction: 0,
+                });
+        }
+        app.update();
+        let hp = |e| app.world().get::<Health>(e).unwrap().0;
+        // Two walls at 0.5 each: capped at 0.6
+        assert!((hp(covered) - 88.0).abs() < 0.01, "cover: {}", hp(covered));
+        assert!(
+            (hp(swimming) - 75.0).abs() < 0.01,
+            "water: {}",
+            hp(swimming)
+        );
+        assert!((hp(open) - 80.0).abs() < 0.01, "open ground: {}", hp(open));
+    }
+
     #[test]
     fn damage_floors_at_zero() {
         let mut app = setup_damage_app();
@@ -1968,4 +2504,294 @@ mod tests {
             "faction 2 should have a zone"
         );
     }
+
+    // ========================================================================
+    // trample_system tests
+    // ========================================================================
+
+    #[test]
+    fn trample_amount_respects_hp_floor() {
+        assert_eq!(trample_amount(100.0, 100.0, 5.0), 5.0);
+        assert_eq!(trample_amount(27.0, 100.0, 5.0), 2.0);
+        assert_eq!(trample_amount(20.0, 100.0, 5.0), 0.0);
+    }
+
+    fn setup_trample_app(npc_count: usize, pos: Vec2, zone: Option<Vec2>) -> (App, Vec<Entity>) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(GameTime::default());
+        app.insert_resource(EntityMap::default());
+        app.insert_resource(HealthDebug::default());
+        app.insert_resource(BuildingHealState::default());
+        app.init_resource::<crate::gpu::GridConfig>();
+        app.init_resource::<CombatZones>();
+        app.insert_resource(CombatConfig {
+            trample_damage: 5.0,
+            ..Default::default()
+        });
+        let mut cache = HealingZoneCache::default();
+        if let Some(center) = zone {
+            cache.by_faction = vec![vec![crate::resources::HealingZone {
+                center,
+                enter_radius_sq: 300.0 * 300.0,
+                exit_radius_sq: 330.0 * 330.0,
+                heal_rate: 5.0,
+                town_idx: 0,
+                faction: 0,
+            }]];
+        }
+        app.insert_resource(cache);
+        let mut positions = Vec::new();
+        for _ in 0..npc_count {
+            positions.extend_from_slice(&[pos.x, pos.y]);
+        }
+        app.insert_resource(GpuReadState {
+            positions,
+            ..Default::default()
+        });
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_secs_f32(1.0),
+        ));
+        app.add_message::<DamageMsg>();
+        app.add_message::<GpuUpdateMsg>();
+        app.init_resource::<NpcFacing>();
+        app.add_systems(FixedUpdate, (trample_system, damage_system).chain());
+
+        let mut entities = Vec::new();
+        for slot in 0..npc_count {
+            let entity = app
+                .world_mut()
+                .spawn((GpuSlot(slot), Health(100.0), stats_with_regen(0.0)))
+                .id();
+            app.world_mut().resource_mut::<EntityMap>().register_npc(
+                slot,
+                entity,
+                crate::components::Job::Fighter,
+                0,
+                0,
+            );
+            entities.push(entity);
+        }
+        (app, entities)
+    }
+
+    #[test]
+    fn death_knockback_slides_away_from_killer_inside_bounds() {
+        let bounds = WorldBounds {
+            min: Vec2::ZERO,
+            max: Vec2::splat(1000.0),
+        };
+        let mut kb = DeathKnockback::default();
+        let pos = Vec2::new(500.0, 500.0);
+        assert_eq!(kb.landing(pos, Vec2::ZERO, &bounds), None, "off by default");
+
+        kb.set_death_knockback(1e6);
+        assert_eq!(kb.strength, DEATH_KNOCKBACK_MAX);
+        kb.set_death_knockback(80.0);
+        assert_eq!(
+            kb.landing(pos, Vec2::new(400.0, 500.0), &bounds),
+            Some(Vec2::new(580.0, 500.0))
+        );
+        assert_eq!(
+            kb.landing(pos, pos, &bounds),
+            None,
+            "no direction, no slide"
+        );
+        // Struck toward the edge: stops at the world border
+        let edge = Vec2::new(990.0, 500.0);
+        assert_eq!(
+            kb.landing(edge, Vec2::new(900.0, 500.0), &bounds),
+            Some(Vec2::new(1000.0, 500.0))
+        );
+
+        let mut corpse = Corpse {
+            slot: 0,
+            from: pos,
+            to: Vec2::new(580.0, 500.0),
+            elapsed: DEATH_KNOCKBACK_SECS * 0.5,
+        };
+        assert!(
+            corpse.position().x > 540.0,
+            "eases out: past halfway at half time"
+        );
+        corpse.elapsed = DEATH_KNOCKBACK_SECS * 2.0;
+        assert_eq!(corpse.position(), corpse.to);
+    }
+
+    #[test]
+    fn flank_bonus_by_attack_angle() {
+        let at = Vec2::new(100.0, 100.0);
+        let m = |from: Vec2| flank_multiplier(Vec2::X, at, at + from, 0.3);
+        assert_eq!(m(Vec2::new(50.0, 0.0)), 1.0, "frontal");
+        assert_eq!(m(Vec2::new(30.0, 30.0)), 1.0, "inside the frontal arc");
+        assert!((m(Vec2::new(0.0, 50.0)) - 1.1).abs() < 1e-5, "side");
+        assert!((m(Vec2::new(-50.0, 0.0)) - 1.3).abs() < 1e-5, "rear");
+        // Unknown facing, no bonus, or an over-cap bonus
+        assert_eq!(flank_multiplier(Vec2::ZERO, at, at - Vec2::X, 0.3), 1.0);
+        assert_eq!(flank_multiplier(Vec2::X, at, at - Vec2::X, 0.0), 1.0);
+        assert_eq!(
+            flank_multiplier(Vec2::X, at, at - Vec2::X, 5.0),
+            1.0 + crate::constants::FLANK_BONUS_CAP
+        );
+    }
+
+    #[test]
+    fn trample_damages_overfull_cell() {
+        let over = crate::gpu::MAX_PER_CELL as usize + 1;
+        let (mut app, npcs) = setup_trample_app(over, Vec2::new(1000.0, 1000.0), None);
+        // Virtual time advances at most 0.25s per update: 8 updates span a trample interval
+        for _ in 0..8 {
+            app.update();
+        }
+        let hp = app.world().get::<Health>(npcs[0]).unwrap().0;
+        assert!(hp < 100.0, "overfull cell should trample: {hp}");
+        assert!(
+            hp >= 100.0 * crate::constants::TRAMPLE_HP_FLOOR,
+            "trample must not drop below floor: {hp}"
+        );
+    }
+
+    #[test]
+    fn trample_ignores_cell_at_capacity() {
+        let at_cap = crate::gpu::MAX_PER_CELL as usize;
+        let (mut app, npcs) = setup_trample_app(at_cap, Vec2::new(1000.0, 1000.0), None);
+        for _ in 0..8 {
+            app.update();
+        }
+        let hp = app.world().get::<Health>(npcs[0]).unwrap().0;
+        assert_eq!(hp, 100.0, "cell at capacity should not trample");
+    }
+
+    #[test]
+    fn trample_exempts_healing_zone() {
+        let over = crate::gpu::MAX_PER_CELL as usize + 1;
+        let pos = Vec2::new(1000.0, 1000.0);
+        let (mut app, npcs) = setup_trample_app(over, pos, Some(pos));
+        for _ in 0..8 {
+            app.update();
+        }
+        let hp = app.world().get::<Health>(npcs[0]).unwrap().0;
+        assert_eq!(hp, 100.0, "crowds inside a healing zone are exempt");
+    }
+
+    // ========================================================================
+    // despawn_npc_system tests
+    // ========================================================================
+
+    #[derive(Resource, Default)]
+    struct PendingDespawn(Vec<usize>);
+
+    fn send_despawn(
+        mut writer: MessageWriter<crate::messages::DespawnNpcMsg>,
+        mut pending: ResMut<PendingDespawn>,
+    ) {
+        for slot in pending.0.drain(..) {
+            writer.write(crate::messages::DespawnNpcMsg { slot });
+        }
+    }
+
+    fn setup_despawn_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<PendingDespawn>()
+            .init_resource::<DeathQueue>()
+            .init_resource::<EntityMap>()
+            .init_resource::<PopulationStats>()
+            .init_resource::<FactionStats>()
+            .init_resource::<HealthDebug>()
+            .init_resource::<KillStats>()
+            .init_resource::<GpuSlotPool>()
+            .init_resource::<WorldGrid>()
+            .init_resource::<WorldData>()
+            .init_resource::<SelectedBuilding>()
+            .init_resource::<SelectedNpc>()
+            .init_resource::<crate::systems::AiPlayerState>()
+            .init_resource::<EndlessMode>()
+            .init_resource::<crate::gpu::EntityGpuState>()
+            .init_resource::<crate::resources::ProjSlotAllocator>()
+            .init_resource::<crate::resources::NextLootItemId>()
+            .init_resource::<crate::resources::LootConfig>()
+            .init_resource::<DeathKnockback>()
+            .init_resource::<WorldBounds>()
+            .init_resource::<crate::resources::Reputation>()
+            .init_resource::<crate::resources::FactionMvp>()
+            .init_resource::<crate::resources::NpcLogCache>()
+            .init_resource::<crate::resources::PanicState>()
+            .init_resource::<ActiveHealingSlots>();
+        app.add_message::<crate::messages::DespawnNpcMsg>()
+            .add_message::<GpuUpdateMsg>()
+            .add_message::<ProjGpuUpdateMsg>()
+            .add_message::<crate::messages::WorkIntentMsg>()
+            .add_message::<crate::resources::PlaySfxMsg>()
+            .add_message::<crate::messages::BuildingGridDirtyMsg>()
+            .add_message::<crate::messages::TerrainDirtyMsg>()
+            .add_message::<crate::messages::PatrolsDirtyMsg>()
+            .add_message::<crate::messages::PatrolPerimeterDirtyMsg>()
+            .add_message::<crate::messages::HealingZonesDirtyMsg>()
+            .add_message::<crate::messages::SquadsDirtyMsg>()
+            .add_message::<crate::messages::MiningDirtyMsg>()
+            .add_message::<crate::messages::PatrolSwapMsg>();
+        app.add_systems(Update, (send_despawn, despawn_npc_system).chain());
+        app
+    }
+
+    #[test]
+    fn despawn_npc_reclaims_slot() {
+        let mut app = setup_despawn_app();
+        let slot = app
+            .world_mut()
+            .resource_mut::<GpuSlotPool>()
+            .alloc_reset()
+            .unwrap();
+        let entity = app.world_mut().spawn((GpuSlot(slot), Health(100.0))).id();
+        app.world_mut()
+            .resource_mut::<EntityMap>()
+            .register_npc(slot, entity, Job::Farmer, 1, 0);
+        app.world_mut().resource_mut::<SelectedNpc>().0 = slot as i32;
+        {
+            let mut panic = app
+                .world_mut()
+                .resource_mut::<crate::resources::PanicState>();
+            panic.levels.insert(slot, 0.8);
+            panic.mark_fleeing(slot, 0.0);
+            panic.routed.insert(slot, 5.0);
+        }
+        app.world_mut()
+            .resource_mut::<PendingDespawn>()
+            .0
+            .push(slot);
+        app.update();
+
+        assert!(app.world().get_entity(entity).is_err(), "entity despawned");
+        let panic = app.world().resource::<crate::resources::PanicState>();
+        assert_eq!(panic.level(slot), 0.0, "panic forgotten with the slot");
+        assert!(!panic.is_routed(slot) && !panic.fleeing.contains_key(&slot));
+        let entity_map = app.world().resource::<EntityMap>();
+        assert!(entity_map.get_npc(slot).is_none(), "unregistered");
+        assert!(entity_map.slot_for_entity(entity).is_none());
+        assert_eq!(app.world().resource::<SelectedNpc>().0, -1);
+
+        // Freed slot is reused and queued for a full GPU reset (no ghost state)
+        let mut pool = app.world_mut().resource_mut::<GpuSlotPool>();
+        assert!(pool.take_pending_frees().contains(&slot));
+        assert_eq!(pool.alloc_reset(), Some(slot));
+        assert!(pool.take_pending_resets().contains(&slot));
+    }
+
+    #[test]
+    fn despawn_npc_skips_dead_npc() {
+        let mut app = setup_despawn_app();
+        let entity = app.world_mut().spawn((GpuSlot(0), Health(0.0))).id();
+        {
+            let mut entity_map = app.world_mut().resource_mut::<EntityMap>();
+            entity_map.register_npc(0, entity, Job::Farmer, 1, 0);
+            entity_map.get_npc_mut(0).unwrap().dead = true;
+        }
+        app.world_mut().resource_mut::<PendingDespawn>().0.push(0);
+        app.update();
+        assert!(
+            app.world().resource::<EntityMap>().get_npc(0).is_some(),
+            "dead NPCs are left to death_system"
+        );
+    }
 }
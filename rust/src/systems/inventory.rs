@@ -0,0 +1,84 @@
+//! NPC inventories — carried items (potions, quest items) outside equipped gear.
+//! Items arrive via `endless/give_item` or picked-up corpse drops (`loot_system`) and leave via
+//! `endless/take_item`, death (`LootConfig::drop_inventory`), or behaviors like
+//! `potion_use_system`, which drinks a healing potion when an NPC falls low.
+
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::constants::{HEALING_POTION_HEAL, HEALING_POTION_HP_FRAC, HEALING_POTION_ID};
+use crate::messages::{GpuUpdate, GpuUpdateMsg};
+use crate::resources::{GameTime, NpcLogCache};
+
+/// Whether an NPC at `hp` of `max_hp` should drink a healing potion.
+pub(crate) fn wants_potion(hp: f32, max_hp: f32) -> bool {
+    hp > 0.0 && hp < max_hp * HEALING_POTION_HP_FRAC
+}
+
+/// Drink one healing potion per low-HP NPC per tick.
+pub fn potion_use_system(
+    game_time: Res<GameTime>,
+    mut npc_logs: ResMut<NpcLogCache>,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+    mut npc_q: Query<
+        (&GpuSlot, &mut Health, &CachedStats, &mut Inventory),
+        (Without<Building>, Without<Dead>),
+    >,
+) {
+    if game_time.is_paused() {
+        return;
+    }
+    for (slot, mut health, stats, mut inv) in &mut npc_q {
+        // Read before take(): a mutable borrow would flag every inventory as changed
+        if inv.count(HEALING_POTION_ID) == 0 || !wants_potion(health.0, stats.max_health) {
+            continue;
+        }
+        inv.take(HEALING_POTION_ID, 1);
+        health.0 = (health.0 + HEALING_POTION_HEAL).min(stats.max_health);
+        gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetHealth {
+            idx: slot.0,
+            health: health.0,
+        }));
+        npc_logs.push(
+            slot.0,
+            game_time.day(),
+            game_time.hour(),
+            game_time.minute(),
+            "Drank a healing potion",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn give_overflows_and_take_falls_short() {
+        let mut inv = Inventory::default();
+        assert_eq!(inv.give("healing_potion", 3, 5), 3);
+        assert_eq!(inv.give("key", 4, 5), 2, "only 2 of 4 fit");
+        assert_eq!(inv.total(), 5);
+        assert_eq!(inv.give("key", 1, 5), 0);
+
+        assert_eq!(inv.take("healing_potion", 10), 3, "takes what is there");
+        assert_eq!(inv.count("healing_potion"), 0);
+        assert_eq!(inv.0.len(), 1, "emptied stacks are removed");
+        assert_eq!(inv.take("missing", 1), 0);
+        assert_eq!(inv.give("key", 1, 5), 1, "stacks merge by id");
+        assert_eq!(
+            inv.0,
+            vec![ItemStack {
+                id: "key".into(),
+                count: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn potions_only_when_low_and_alive() {
+        assert!(wants_potion(20.0, 100.0));
+        assert!(!wants_potion(50.0, 100.0));
+        assert!(!wants_potion(0.0, 100.0));
+    }
+}
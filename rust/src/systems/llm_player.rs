@@ -436,7 +436,7 @@ pub fn llm_player_system(
         Disconnected,
     }
     let poll = if let Some(ref receiver_mutex) = state.receiver {
-        let receiver = receiver_mutex.lock().expect("llm receiver lock");
+        let receiver = crate::resources::recover_or_log(receiver_mutex, "llm_receiver");
         match receiver.try_recv() {
             Ok(response) => PollResult::Response(response),
             Err(mpsc::TryRecvError::Empty) => PollResult::Waiting,
@@ -667,6 +667,51 @@ fn execute_actions(
                                         v.clamp(1, crate::resources::MAX_LOOT_THRESHOLD);
                                 }
                             }
+                            "auto_expand" => {
+                                if let Ok(v) = val.parse::<bool>() {
+                                    policy.0.auto_expand = v;
+                                }
+                            }
+                            "auto_assign_guards" => {
+                                if let Ok(v) = val.parse::<bool>() {
+                                    policy.0.auto_assign_guards = v;
+                                }
+                            }
+                            "reinforce_enabled" => {
+                                if let Ok(v) = val.parse::<bool>() {
+                                    policy.0.reinforce_enabled = v;
+                                }
+                            }
+                            "reinforce_radius" => {
+                                if let Ok(v) = val.parse::<f32>() {
+                                    policy.0.reinforce_radius = v.clamp(0.0, 5000.0);
+                                }
+                            }
+                            "reinforce_reserve" => {
+                                if let Ok(v) = val.parse::<f32>() {
+                                    policy.0.reinforce_reserve = v.clamp(0.0, 1.0);
+                                }
+                            }
+                            "last_stand_ratio" => {
+                                if let Ok(v) = val.parse::<f32>() {
+                                    policy.0.last_stand_ratio = v.clamp(0.0, 10.0);
+                                }
+                            }
+                            "panic_spread" => {
+                                if let Ok(v) = val.parse::<f32>() {
+                                    policy.0.panic_spread = v.clamp(0.0, 5.0);
+                                }
+                            }
+                            "panic_threshold" => {
+                                if let Ok(v) = val.parse::<f32>() {
+                                    policy.0.panic_threshold = v.clamp(0.1, 10.0);
+                                }
+                            }
+                            "supply_radius" => {
+                                if let Ok(v) = val.parse::<f32>() {
+                                    policy.0.supply_radius = v.clamp(0.0, 5000.0);
+                                }
+                            }
                             _ => {}
                         }
                     }
@@ -0,0 +1,195 @@
+//! Corpse loot — ground drops left by `death_system` when `LootConfig::enabled`.
+//! `loot_system` expires old drops, caps the active count, and hands each remaining drop to
+//! the nearest living town NPC within the pickup radius (any faction — contested drops go to
+//! whoever reaches them first). Food/gold land in the picker's town storage, items in its
+//! town equipment, and a dropped inventory in the picker's own `Inventory` — whatever does not
+//! fit stays on the ground.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::components::{GroundLoot, Inventory};
+use crate::constants::INVENTORY_CAPACITY;
+use crate::resources::*;
+
+/// For each drop, the index of the nearest picker within `radius` (ties → lower index).
+/// Drops are bucketed by `radius`-sized cells so each picker checks only its 3x3 neighbourhood.
+pub fn assign_pickups(drops: &[Vec2], pickers: &[Vec2], radius: f32) -> Vec<Option<usize>> {
+    if drops.is_empty() || radius <= 0.0 {
+        return vec![None; drops.len()];
+    }
+    let mut best: Vec<Option<(usize, f32)>> = vec![None; drops.len()];
+    let cell = |p: Vec2| ((p.x / radius).floor() as i32, (p.y / radius).floor() as i32);
+    let mut buckets: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+    for (i, &d) in drops.iter().enumerate() {
+        buckets.entry(cell(d)).or_default().push(i);
+    }
+    let r2 = radius * radius;
+    for (pi, &p) in pickers.iter().enumerate() {
+        let (cx, cy) = cell(p);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let Some(bucket) = buckets.get(&(cx + dx, cy + dy)) else {
+                    continue;
+                };
+                for &di in bucket {
+                    let d2 = p.distance_squared(drops[di]);
+                    if d2 <= r2 && best[di].is_none_or(|(_, b)| d2 < b) {
+                        best[di] = Some((pi, d2));
+                    }
+                }
+            }
+        }
+    }
+    best.into_iter().map(|b| b.map(|(i, _)| i)).collect()
+}
+
+/// Expire, cap, and hand out ground loot.
+pub fn loot_system(
+    mut commands: Commands,
+    config: Res<LootConfig>,
+    game_time: Res<GameTime>,
+    entity_map: Res<EntityMap>,
+    gpu_state: Res<GpuReadState>,
+    mut town_access: crate::systemparams::TownAccess,
+    loot_q: Query<(Entity, &GroundLoot)>,
+    mut inventory_q: Query<&mut Inventory>,
+) {
+    if loot_q.is_empty() {
+        return;
+    }
+    let now = game_time.total_seconds;
+
+    // Oldest first so the cap trims from the front
+    let mut live: Vec<(Entity, &GroundLoot)> = Vec::new();
+    for (entity, loot) in loot_q.iter() {
+        if now - loot.dropped_at >= config.lifetime {
+            commands.entity(entity).despawn();
+        } else {
+            live.push((entity, loot));
+        }
+    }
+    live.sort_by(|a, b| a.1.dropped_at.total_cmp(&b.1.dropped_at));
+    let excess = live.len().saturating_sub(config.max_active);
+    for (entity, _) in live.drain(..excess) {
+        commands.entity(entity).despawn();
+    }
+    if live.is_empty() {
+        return;
+    }
+
+    let mut picker_pos = Vec::new();
+    let mut picker_town = Vec::new();
+    let mut picker_entity = Vec::new();
+    for npc in entity_map.iter_npcs() {
+        if npc.dead || npc.town_idx < 0 {
+            continue;
+        }
+        let Some(p) = gpu_state.positions.get(npc.slot * 2..npc.slot * 2 + 2) else {
+            continue;
+        };
+        if p[0] < -9000.0 {
+            continue;
+        }
+        picker_pos.push(Vec2::new(p[0], p[1]));
+        picker_town.push(npc.town_idx);
+        picker_entity.push(npc.entity);
+    }
+    let drops: Vec<Vec2> = live.iter().map(|(_, l)| l.pos).collect();
+    let winners = assign_pickups(&drops, &picker_pos, config.pickup_radius);
+
+    for ((entity, loot), winner) in live.into_iter().zip(winners) {
+        let Some(pi) = winner else {
+            continue;
+        };
+        let town = picker_town[pi];
+        if loot.food > 0 {
+            if let Some(mut f) = town_access.food_mut(town) {
+                f.0 += loot.food;
+            }
+        }
+        if loot.gold > 0 {
+            if let Some(mut g) = town_access.gold_mut(town) {
+                g.0 += loot.gold;
+            }
+        }
+        if let Some(item) = loot.item.clone() {
+            if let Some(mut eq) = town_access.equipment_mut(town) {
+                eq.0.push(item);
+            }
+        }
+        let mut left = loot.items.clone();
+        if let Ok(mut inv) = inventory_q.get_mut(picker_entity[pi]) {
+            for stack in &mut left {
+                stack.count -= inv.give(&stack.id, stack.count, INVENTORY_CAPACITY);
+            }
+            left.retain(|s| s.count > 0);
+        }
+        if left.is_empty() {
+            commands.entity(entity).despawn();
+        } else if left != loot.items || loot.food > 0 || loot.gold > 0 || loot.item.is_some() {
+            // Picker's inventory is full: the overflow stays where it lies
+            commands.entity(entity).insert(GroundLoot {
+                food: 0,
+                gold: 0,
+                item: None,
+                items: left,
+                ..loot.clone()
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_picker_in_radius_wins() {
+        let drops = [Vec2::new(100.0, 100.0), Vec2::new(500.0, 500.0)];
+        let pickers = [
+            Vec2::new(140.0, 100.0),
+            Vec2::new(110.0, 100.0),
+            Vec2::new(900.0, 900.0),
+        ];
+        let won = assign_pickups(&drops, &pickers, 48.0);
+        // Picker 1 is closer than picker 0; nobody is near the second drop
+        assert_eq!(won, vec![Some(1), None]);
+
+        // Across a cell boundary still counts
+        let won = assign_pickups(&[Vec2::new(47.0, 0.0)], &[Vec2::new(50.0, 0.0)], 48.0);
+        assert_eq!(won, vec![Some(0)]);
+    }
+
+    #[test]
+    fn loot_roll_is_deterministic_and_respects_rule() {
+        let mut config = LootConfig::default();
+        let never = LootDropRule {
+            chance: 0.0,
+            ..Default::default()
+        };
+        config.per_job.insert(crate::components::Job::Farmer, never);
+        assert!((0..200).all(|s| config.roll(crate::components::Job::Farmer, s).is_none()));
+
+        let always = LootDropRule {
+            chance: 1.0,
+            food: (2, 4),
+            gold: (0, 0),
+            item_chance: 0.0,
+        };
+        config
+            .per_job
+            .insert(crate::components::Job::Raider, always);
+        for seed in 0..200 {
+            let roll = config.roll(crate::components::Job::Raider, seed).unwrap();
+            assert!((2..=4).contains(&roll.food));
+            assert_eq!(roll.gold, 0);
+            assert!(!roll.item);
+            assert_eq!(
+                config.roll(crate::components::Job::Raider, seed),
+                Some(roll)
+            );
+        }
+    }
+}
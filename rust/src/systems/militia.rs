@@ -0,0 +1,333 @@
+//! Emergency militia — `conscript` retrains a town's idle farmers as weakened Fighters and
+//! `demobilize` turns the survivors back. The `Militia` component remembers the original job
+//! and farm pin; beds and homes are never touched, so demobilized farmers go back to the same
+//! house. Population stats keep counting militia under their original job (death_system and
+//! despawn use `Militia::prev_job`), and the farmer home respawns a farmer if one falls.
+
+use bevy::ecs::message::Messages;
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::constants::{MILITIA_STAT_MULT, npc_def};
+use crate::messages::{GpuUpdate, GpuUpdateMsg, PatrolsDirtyMsg, SquadsDirtyMsg};
+use crate::resources::{EntityMap, PopulationStats};
+use crate::systems::economy::pop_dec_working;
+use crate::systems::stats::resolve_npc_stats;
+use crate::systems::{AssignmentKind, assign_npc};
+
+/// How much conscripting this farmer costs: 0 idle, 1 heading out to (or holding) a farm,
+/// 2 mid-harvest or hauling food home.
+fn busy_rank(world: &World, entity: Entity) -> u8 {
+    let activity = world.get::<Activity>(entity).copied().unwrap_or_default();
+    let carrying = world.get::<CarriedLoot>(entity).is_some_and(|c| c.food > 0);
+    if carrying || (activity.kind.def().is_working && activity.phase == ActivityPhase::Active) {
+        2
+    } else if activity.kind.def().is_working
+        || world
+            .get::<NpcWorkState>(entity)
+            .is_some_and(|ws| ws.worksite.is_some())
+    {
+        1
+    } else {
+        0
+    }
+}
+
+/// Scale freshly resolved Fighter stats down to militia strength, rescaling current HP with
+/// max HP.
+fn scale_militia(stats: &mut CachedStats, militia: &mut Militia, health: &mut f32) {
+    let old_max = stats.max_health;
+    stats.damage *= MILITIA_STAT_MULT;
+    stats.max_health *= MILITIA_STAT_MULT;
+    if old_max > 0.0 {
+        *health *= stats.max_health / old_max;
+    }
+    militia.scaled = [stats.damage, stats.max_health];
+}
+
+/// Switch the NPC at `slot` to `job`: EntityMap + `Job`, re-resolved stats, speed, sprite and
+/// leash. Returns the new stats (and HP) for the caller to adjust before they are written.
+fn retrain(world: &mut World, slot: usize, entity: Entity, job: Job) -> (CachedStats, f32) {
+    let def = npc_def(job);
+    let stats = resolve_npc_stats(world, entity, job);
+    let old_max = world
+        .get::<CachedStats>(entity)
+        .map_or(stats.max_health, |s| s.max_health);
+    let health = world
+        .get::<Health>(entity)
+        .map_or(stats.max_health, |h| h.0);
+    let health = if old_max > 0.0 {
+        health * stats.max_health / old_max
+    } else {
+        stats.max_health
+    };
+
+    if let Some(npc) = world.resource_mut::<EntityMap>().get_npc_mut(slot) {
+        npc.job = job;
+    }
+    let mut e = world.entity_mut(entity);
+    e.insert((job, def.default_attack_type, Activity::default()));
+    match def.leash_range {
+        Some(range) => e.insert(LeashRange(range)),
+        None => e.remove::<LeashRange>(),
+    };
+
+    let (col, row) = def.sprite;
+    let mut gpu = world.resource_mut::<Messages<GpuUpdateMsg>>();
+    gpu.write(GpuUpdateMsg(GpuUpdate::SetSpriteFrame {
+        idx: slot,
+        col,
+        row,
+        atlas: def.atlas,
+    }));
+    gpu.write(GpuUpdateMsg(GpuUpdate::MarkVisualDirty { idx: slot }));
+    (stats, health)
+}
+
+/// Write stats from `retrain` to the entity and GPU.
+fn apply_stats(world: &mut World, slot: usize, entity: Entity, stats: CachedStats, health: f32) {
+    let (speed, max_health) = (stats.speed, stats.max_health);
+    world
+        .entity_mut(entity)
+        .insert((stats, Speed(speed), Health(health)));
+    let mut gpu = world.resource_mut::<Messages<GpuUpdateMsg>>();
+    gpu.write(GpuUpdateMsg(GpuUpdate::SetSpeed { idx: slot, speed }));
+    gpu.write(GpuUpdateMsg(GpuUpdate::SetMaxHealth {
+        idx: slot,
+        max_health,
+    }));
+    gpu.write(GpuUpdateMsg(GpuUpdate::SetHealth { idx: slot, health }));
+}
+
+fn mark_military_dirty(world: &mut World) {
+    world
+        .resource_mut::<Messages<SquadsDirtyMsg>>()
+        .write(SquadsDirtyMsg);
+    world
+        .resource_mut::<Messages<PatrolsDirtyMsg>>()
+        .write(PatrolsDirtyMsg);
+}
+
+/// Retrain up to `count` of the town's farmers as militia, idle ones first so harvests in
+/// progress aren't thrown away. Releases their farm (remembering a pin) and sends them onto
+/// the patrol route. Returns the conscripted slots.
+pub fn conscript(world: &mut World, town_idx: i32, count: usize) -> Result<Vec<usize>, String> {
+    if town_idx < 0 || town_idx as usize >= world.resource::<crate::world::WorldData>().towns.len()
+    {
+        return Err(format!("no town {town_idx}"));
+    }
+    let mut candidates: Vec<(u8, usize, Entity)> = world
+        .resource::<EntityMap>()
+        .npcs_for_town(town_idx)
+        .filter(|n| !n.dead && n.job == Job::Farmer && world.get::<Militia>(n.entity).is_none())
+        .map(|n| (busy_rank(world, n.entity), n.slot, n.entity))
+        .collect();
+    candidates.sort_unstable_by_key(|&(rank, slot, _)| (rank, slot));
+    candidates.truncate(count);
+
+    let mut conscripted = Vec::with_capacity(candidates.len());
+    for (_, slot, entity) in candidates {
+        if world
+            .get::<Activity>(entity)
+            .is_some_and(|a| a.kind.def().is_working)
+        {
+            pop_dec_working(
+                &mut world.resource_mut::<PopulationStats>(),
+                Job::Farmer,
+                town_idx,
+            );
+        }
+        let farm = world.entity_mut(entity).take::<AssignedFarm>().map(|a| a.0);
+        if let Some(worksite) = world
+            .get_mut::<NpcWorkState>(entity)
+            .and_then(|mut ws| ws.worksite.take())
+        {
+            let mut em = world.resource_mut::<EntityMap>();
+            if let Some(ws_slot) = em.slot_for_entity(worksite) {
+                em.release_for(ws_slot, Some(entity));
+            }
+        }
+
+        let (mut stats, mut health) = retrain(world, slot, entity, Job::Fighter);
+        let mut militia = Militia {
+            prev_job: Job::Farmer,
+            farm,
+            scaled: [0.0; 2],
+        };
+        scale_militia(&mut stats, &mut militia, &mut health);
+        apply_stats(world, slot, entity, stats, health);
+        world.entity_mut(entity).insert(militia);
+        conscripted.push(slot);
+    }
+    if !conscripted.is_empty() {
+        mark_military_dirty(world);
+    }
+    Ok(conscripted)
+}
+
+/// Return the town's surviving militia to their original job, restoring farm pins where the
+/// farm is still free. Returns the demobilized slots.
+pub fn demobilize(world: &mut World, town_idx: i32) -> Vec<usize> {
+    let militia: Vec<(usize, Entity, Militia)> = world
+        .resource::<EntityMap>()
+        .npcs_for_town(town_idx)
+        .filter(|n| !n.dead)
+        .filter_map(|n| {
+            world
+                .get::<Militia>(n.entity)
+                .map(|m| (n.slot, n.entity, *m))
+        })
+        .collect();
+
+    let mut demobilized = Vec::with_capacity(militia.len());
+    for (slot, entity, m) in militia {
+        world
+            .entity_mut(entity)
+            .remove::<(Militia, PatrolRoute, SquadId)>();
+        let (stats, health) = retrain(world, slot, entity, m.prev_job);
+        apply_stats(world, slot, entity, stats, health);
+        if let Some(farm) = m.farm {
+            let farm_slot = world.resource::<EntityMap>().slot_for_entity(farm);
+            if let Some(farm_slot) = farm_slot {
+                // Taken or destroyed meanwhile: the farmer picks the best free farm instead
+                let _ = assign_npc(world, slot, farm_slot, AssignmentKind::Farm);
+            }
+        }
+        demobilized.push(slot);
+    }
+    if !demobilized.is_empty() {
+        mark_military_dirty(world);
+    }
+    demobilized
+}
+
+/// Living militia in the town.
+pub fn militia_count(world: &World, town_idx: i32) -> usize {
+    world
+        .resource::<EntityMap>()
+        .npcs_for_town(town_idx)
+        .filter(|n| !n.dead && world.get::<Militia>(n.entity).is_some())
+        .count()
+}
+
+/// Re-apply the militia penalty when an upgrade, equip or level-up re-resolves a militia
+/// unit's stats back to full Fighter strength.
+pub fn militia_stats_system(
+    mut q: Query<(&GpuSlot, &mut CachedStats, &mut Militia, &mut Health), Changed<CachedStats>>,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+) {
+    for (slot, mut stats, mut militia, mut health) in &mut q {
+        if militia.scaled == [stats.damage, stats.max_health] {
+            continue;
+        }
+        let mut hp = health.0;
+        scale_militia(&mut stats, &mut militia, &mut hp);
+        health.0 = hp;
+        gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetMaxHealth {
+            idx: slot.0,
+            max_health: stats.max_health,
+        }));
+        gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetHealth {
+            idx: slot.0,
+            health: hp,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::TownIndex;
+    use crate::systems::stats::{CombatConfig, resolve_combat_stats};
+    use crate::world::{Town, WorldData};
+
+    fn setup() -> World {
+        let mut world = World::new();
+        world.init_resource::<Messages<GpuUpdateMsg>>();
+        world.init_resource::<Messages<SquadsDirtyMsg>>();
+        world.init_resource::<Messages<PatrolsDirtyMsg>>();
+        world.init_resource::<PopulationStats>();
+        world.init_resource::<TownIndex>();
+        world.insert_resource(CombatConfig::default());
+        let mut data = WorldData::default();
+        data.towns.push(Town {
+            name: "T".into(),
+            center: Vec2::ZERO,
+            faction: 1,
+            kind: crate::constants::TownKind::Player,
+        });
+        world.insert_resource(data);
+
+        let farmer = resolve_combat_stats(
+            Job::Farmer,
+            npc_def(Job::Farmer).default_attack_type,
+            0,
+            0,
+            &Personality::default(),
+            &CombatConfig::default(),
+            &[],
+            0.0,
+            0.0,
+        );
+        let mut em = EntityMap::default();
+        for slot in 0..3 {
+            let mut activity = Activity::default();
+            if slot == 0 {
+                // Mid-harvest: conscripted last
+                activity = Activity::new(ActivityKind::Work);
+                activity.phase = ActivityPhase::Active;
+            }
+            let e = world
+                .spawn((
+                    GpuSlot(slot),
+                    Job::Farmer,
+                    TownId(0),
+                    activity,
+                    NpcWorkState::default(),
+                    farmer.clone(),
+                    Health(farmer.max_health),
+                ))
+                .id();
+            em.register_npc(slot, e, Job::Farmer, 1, 0);
+        }
+        world.insert_resource(em);
+        world
+    }
+
+    #[test]
+    fn conscript_prefers_idle_farmers_and_demobilize_restores() {
+        let mut world = setup();
+        let picked = conscript(&mut world, 0, 2).unwrap();
+        assert_eq!(picked, vec![1, 2], "harvesting farmer is spared");
+        assert_eq!(militia_count(&world, 0), 2);
+
+        let e = world.resource::<EntityMap>().get_npc(1).unwrap().entity;
+        assert_eq!(
+            world.resource::<EntityMap>().get_npc(1).unwrap().job,
+            Job::Fighter
+        );
+        let full = resolve_combat_stats(
+            Job::Fighter,
+            npc_def(Job::Fighter).default_attack_type,
+            0,
+            0,
+            &Personality::default(),
+            &CombatConfig::default(),
+            &[],
+            0.0,
+            0.0,
+        );
+        let stats = world.get::<CachedStats>(e).unwrap();
+        assert!((stats.max_health - full.max_health * MILITIA_STAT_MULT).abs() < 1e-3);
+        assert!((stats.damage - full.damage * MILITIA_STAT_MULT).abs() < 1e-3);
+
+        assert_eq!(demobilize(&mut world, 0), vec![1, 2]);
+        assert_eq!(militia_count(&world, 0), 0);
+        assert_eq!(*world.get::<Job>(e).unwrap(), Job::Farmer);
+        assert_eq!(
+            world.resource::<EntityMap>().get_npc(1).unwrap().job,
+            Job::Farmer
+        );
+        assert!(conscript(&mut world, 3, 1).is_err());
+    }
+}
@@ -1,38 +1,76 @@
 //! Bevy ECS Systems - Game logic that operates on components
 
 pub mod ai_player;
+mod anchor;
+mod assignment;
 pub mod audio;
+pub mod balance;
 pub(crate) mod behavior;
+mod blueprint;
+pub mod bodyguard;
 mod combat;
+pub mod combat_prediction;
 mod decision;
 mod drain;
 mod economy;
 mod energy;
+pub mod fast_forward;
+pub mod happiness;
 mod health;
+mod inventory;
 pub mod llm_player;
+mod loot;
+mod militia;
 mod movement;
+pub mod npc_log_file;
+mod panic;
 pub mod pathfinding;
 mod patrol;
+pub mod quick_battle;
+mod raid_party;
+mod reinforce;
 pub mod remote;
 pub(crate) mod spawn;
 pub mod stats;
+mod supply;
+pub mod tech;
+pub mod threat_map;
+pub mod victory;
 pub mod work_targeting;
 pub use ai_player::{
     AiKind, AiPersonality, AiPlayer, AiPlayerConfig, AiPlayerState, ai_decision_system,
     ai_squad_commander_system, rebuild_squad_indices, sync_patrol_perimeter_system,
 };
+pub use anchor::{anchor_system, wake_anchored};
+pub use assignment::{
+    AssignmentKind, PostCoverage, assign_npc, auto_assign_guards, clear_assignment,
+    guard_post_system, post_coverage,
+};
 pub use behavior::*;
+pub use blueprint::{save_town_blueprint, stamp_blueprint};
+pub use bodyguard::{BodyguardTargets, assign_bodyguard, bodyguard_system, clear_bodyguard};
 pub use combat::*;
 pub use decision::decision_system;
 pub use drain::*;
 pub use economy::*;
 pub use energy::*;
+pub use happiness::{HappinessConfig, TownHappiness, happiness_system};
 pub use health::*;
+pub use inventory::potion_use_system;
+pub use loot::loot_system;
+pub use militia::{conscript, demobilize, militia_count, militia_stats_system};
 pub use movement::*;
+pub use panic::panic_system;
 pub use patrol::{on_duty_tick_system, rebuild_patrol_routes_system};
+pub use raid_party::raid_party_system;
+pub use reinforce::{last_stand_system, reinforce_system, town_alert_system};
 pub use spawn::*;
 pub use stats::{
     CombatConfig, UPGRADES, UpgradeMsg, auto_upgrade_system, expansion_cost, level_from_xp,
     process_upgrades_system, resolve_combat_stats, resolve_tower_instance_stats, upgrade_cost,
     upgrade_count,
 };
+pub use supply::{is_supplied, supply_system};
+pub use tech::{TechDef, TechTree, research_system};
+pub use threat_map::{ThreatMap, threat_map_system};
+pub use victory::{WinCondition, victory_system};
@@ -13,7 +13,7 @@ use crate::gpu::EntityGpuState;
 use crate::messages::{GpuUpdate, GpuUpdateMsg};
 use crate::resources::{
     GameTime, GpuReadState, NpcTargetThrashDebug, PathRequest, PathRequestQueue, PathSource,
-    PathfindConfig, PathfindStats,
+    PathfindConfig, PathfindStats, PositionSync,
 };
 use crate::systems::pathfinding::{
     collect_path_chunks, line_of_sight, pathfind_hpa, pathfind_on_grid,
@@ -22,18 +22,37 @@ use crate::world::WorldGrid;
 
 /// Read positions from GPU readback buffer → ECS Position + arrival detection.
 /// GPU is movement authority; ECS Position is read-model synced here.
+/// Position is only rewritten past `PositionSync.threshold` (spawns/teleports always sync).
+/// Arriving at a final destination starts the NPC's retarget cooldown.
 /// Query-first: iterates ECS archetypes, not HashMap.
 pub fn gpu_position_readback(
     gpu_state: Res<GpuReadState>,
     buffer_writes: Res<EntityGpuState>,
-    mut npc_q: Query<(&GpuSlot, &mut Position, &mut NpcFlags, &NpcPath, &Activity)>,
+    config: Res<PathfindConfig>,
+    mut sync: ResMut<PositionSync>,
+    mut gpu_updates: MessageReader<GpuUpdateMsg>,
+    mut npc_q: Query<(
+        &GpuSlot,
+        &mut Position,
+        &mut NpcFlags,
+        &mut NpcPath,
+        &Activity,
+    )>,
 ) {
+    for msg in gpu_updates.read() {
+        if let GpuUpdate::SetPosition { idx, .. } = msg.0 {
+            sync.invalidate(idx);
+        }
+    }
+    let mut synced = 0usize;
+    let mut skipped = 0usize;
+
     let positions = &gpu_state.positions;
     let targets = &buffer_writes.targets;
     let threshold_sq = ARRIVAL_THRESHOLD * ARRIVAL_THRESHOLD;
     let intermediate_sq = INTERMEDIATE_ARRIVAL_THRESHOLD * INTERMEDIATE_ARRIVAL_THRESHOLD;
 
-    for (es, mut pos, mut flags, path, _activity) in npc_q.iter_mut() {
+    for (es, mut pos, mut flags, mut path, _activity) in npc_q.iter_mut() {
         let i = es.0;
         if i * 2 + 1 >= positions.len() {
             continue;
@@ -46,8 +65,13 @@ pub fn gpu_position_readback(
             continue;
         }
 
-        pos.x = gpu_x;
-        pos.y = gpu_y;
+        if sync.should_sync(i, Vec2::new(gpu_x, gpu_y)) {
+            pos.x = gpu_x;
+            pos.y = gpu_y;
+            synced += 1;
+        } else {
+            skipped += 1;
+        }
 
         // CPU-side arrival detection for transit states.
         // Works for both waypoint movement and direct LOS SetTarget movement.
@@ -68,10 +92,15 @@ pub fn gpu_position_readback(
                 };
                 if dist_sq <= thresh_sq {
                     flags.at_destination = true;
+                    if !is_intermediate {
+                        path.retarget_cooldown = config.retarget_cooldown;
+                    }
                 }
             }
         }
     }
+    sync.synced = synced;
+    sync.skipped = skipped;
 }
 
 /// Advance NPC path waypoints when at_destination triggers and more waypoints remain.
@@ -96,6 +125,9 @@ pub fn advance_waypoints_system(
         if path.path_cooldown > 0.0 {
             path.path_cooldown = (path.path_cooldown - dt).max(0.0);
         }
+        if path.retarget_cooldown > 0.0 {
+            path.retarget_cooldown = (path.retarget_cooldown - dt).max(0.0);
+        }
         if !flags.at_destination {
             continue;
         }
@@ -158,6 +190,14 @@ pub fn resolve_movement_system(
 
         let i = idx * 2;
 
+        // Just arrived: hold automatic retargets until the cooldown runs out
+        if !intent.priority.bypasses_retarget_cooldown()
+            && path_q.get(entity).is_ok_and(|p| p.retarget_cooldown > 0.0)
+        {
+            path_queue.defer(entity, intent);
+            continue;
+        }
+
         // "Stop in place" — intent target ≈ current position: skip cooldown, write directly
         if i + 1 < positions.len() {
             let dx = positions[i] - intent.target.x;
@@ -455,6 +495,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn retarget_cooldown_defers_routine_moves_only() {
+        let mut app = setup_movement_app();
+        let entity = app
+            .world_mut()
+            .spawn((
+                GpuSlot(0),
+                NpcPath {
+                    retarget_cooldown: 5.0,
+                    ..default()
+                },
+            ))
+            .id();
+        app.world_mut().resource_mut::<EntityGpuState>().targets = vec![0.0, 0.0];
+        app.world_mut().resource_mut::<PathRequestQueue>().submit(
+            entity,
+            Vec2::new(100.0, 200.0),
+            MovementPriority::JobRoute,
+            "test",
+        );
+        app.update();
+        assert!(
+            app.world().resource::<CollectedGpuUpdates>().0.is_empty(),
+            "automatic retarget waits out the cooldown"
+        );
+
+        // Fleeing right after arrival must not be held back
+        app.world_mut().resource_mut::<PathRequestQueue>().submit(
+            entity,
+            Vec2::new(150.0, 250.0),
+            MovementPriority::Survival,
+            "test",
+        );
+        app.update();
+        let collected = app.world().resource::<CollectedGpuUpdates>();
+        assert!(
+            collected.0.iter().any(|u| matches!(u, GpuUpdate::SetTarget { idx: 0, x, y } if (*x - 150.0).abs() < 0.1 && (*y - 250.0).abs() < 0.1)),
+            "flee bypasses the cooldown, got {:?}", collected.0
+        );
+        for priority in [MovementPriority::Squad, MovementPriority::Combat] {
+            assert!(priority.bypasses_retarget_cooldown(), "{priority:?}");
+        }
+        assert!(!MovementPriority::Wander.bypasses_retarget_cooldown());
+
+        app.world_mut().resource_mut::<PathRequestQueue>().submit(
+            entity,
+            Vec2::new(300.0, 400.0),
+            MovementPriority::ManualTarget,
+            "test",
+        );
+        app.update();
+        let collected = app.world().resource::<CollectedGpuUpdates>();
+        assert!(
+            collected.0.iter().any(|u| matches!(u, GpuUpdate::SetTarget { idx: 0, x, y } if (*x - 300.0).abs() < 0.1 && (*y - 400.0).abs() < 0.1)),
+            "player-issued target bypasses the cooldown, got {:?}", collected.0
+        );
+    }
+
     #[test]
     fn resolve_movement_paused_no_resolve() {
         let mut app = setup_movement_app();
@@ -479,6 +577,9 @@ mod tests {
         app.add_plugins(MinimalPlugins);
         app.insert_resource(GpuReadState::default());
         app.insert_resource(EntityGpuState::default());
+        app.insert_resource(PositionSync::default());
+        app.insert_resource(PathfindConfig::default());
+        app.add_message::<GpuUpdateMsg>();
         app.insert_resource(TimeUpdateStrategy::ManualDuration(
             std::time::Duration::from_secs_f32(1.0),
         ));
@@ -565,6 +666,89 @@ mod tests {
         );
     }
 
+    fn readback_pos(app: &mut App) -> Position {
+        *app.world_mut()
+            .query::<&Position>()
+            .single(app.world())
+            .unwrap()
+    }
+
+    fn spawn_readback_npc(app: &mut App) {
+        app.world_mut().spawn((
+            GpuSlot(0),
+            Position { x: 0.0, y: 0.0 },
+            Activity::default(),
+            NpcFlags::default(),
+            NpcPath::default(),
+        ));
+    }
+
+    #[test]
+    fn readback_skips_moves_under_threshold() {
+        let mut app = setup_readback_app();
+        app.world_mut().resource_mut::<PositionSync>().threshold = 2.0;
+        app.world_mut().resource_mut::<GpuReadState>().positions = vec![40.0, 80.0];
+        spawn_readback_npc(&mut app);
+        app.update();
+        assert!(
+            (readback_pos(&mut app).x - 40.0).abs() < 0.01,
+            "first readback syncs"
+        );
+
+        app.world_mut().resource_mut::<GpuReadState>().positions = vec![41.0, 80.0];
+        app.update();
+        assert!(
+            (readback_pos(&mut app).x - 40.0).abs() < 0.01,
+            "sub-threshold drift should not rewrite Position"
+        );
+        assert!(app.world().resource::<PositionSync>().skipped > 0);
+
+        app.world_mut().resource_mut::<GpuReadState>().positions = vec![45.0, 80.0];
+        app.update();
+        assert!((readback_pos(&mut app).x - 45.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn readback_set_position_forces_sync() {
+        let mut app = setup_readback_app();
+        app.world_mut().resource_mut::<PositionSync>().threshold = 50.0;
+        app.world_mut().resource_mut::<GpuReadState>().positions = vec![40.0, 80.0];
+        spawn_readback_npc(&mut app);
+        app.update();
+
+        app.world_mut().resource_mut::<GpuReadState>().positions = vec![45.0, 80.0];
+        app.world_mut()
+            .write_message(GpuUpdateMsg(GpuUpdate::SetPosition {
+                idx: 0,
+                x: 45.0,
+                y: 80.0,
+            }));
+        app.update();
+        assert!(
+            (readback_pos(&mut app).x - 45.0).abs() < 0.01,
+            "teleport must sync regardless of threshold"
+        );
+    }
+
+    #[test]
+    fn position_sync_reset_forces_full_resync() {
+        let mut sync = PositionSync {
+            threshold: 10.0,
+            ..Default::default()
+        };
+        assert!(
+            sync.should_sync(3, Vec2::new(1.0, 1.0)),
+            "unseen slot syncs"
+        );
+        assert!(!sync.should_sync(3, Vec2::new(2.0, 1.0)));
+        sync.reset();
+        assert!(sync.should_sync(3, Vec2::new(2.0, 1.0)), "post-reset syncs");
+        assert!(
+            (sync.threshold - 10.0).abs() < f32::EPSILON,
+            "reset keeps threshold"
+        );
+    }
+
     #[test]
     fn readback_sets_arrival_flag() {
         let mut app = setup_readback_app();
@@ -0,0 +1,219 @@
+//! NPC log file — optional on-disk trail of `NpcLogCache` entries for post-mortem analysis.
+//! The in-memory rings keep only the last 100 entries per NPC; with disk logging on, entries
+//! pushed out of a ring (or every entry, in `All` mode) are appended to a per-session file.
+//!
+//! `NpcLogCache::push` only queues records; `npc_log_flush_system` formats a batch every
+//! `NPC_LOG_FLUSH_SECS` of real time and hands it to a writer thread, so no system waits on
+//! disk I/O. Lines read `d<day> hh:mm #<slot>.<generation> <message>`: the generation counts
+//! spawns into the slot, since slot ids are recycled once an NPC dies.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use bevy::prelude::*;
+
+use crate::resources::{NpcLogCache, NpcLogCapture, NpcLogRecord};
+
+/// Real seconds between batched writes.
+pub const NPC_LOG_FLUSH_SECS: f32 = 2.0;
+
+/// Disk logging state. Lives outside `NpcLogCache` so it survives game cleanup.
+#[derive(Resource, Default)]
+pub struct NpcLogFile {
+    pub capture: NpcLogCapture,
+    /// File being appended to (kept after disabling, for display).
+    pub path: Option<PathBuf>,
+    pub last_error: Option<String>,
+    pub lines_written: u64,
+    sender: Option<mpsc::Sender<String>>,
+    last_flush: f32,
+}
+
+impl NpcLogFile {
+    pub fn enabled(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Turn disk logging on (optionally to `path`, optionally capturing every entry) or off.
+    pub fn set_npc_logging(
+        &mut self,
+        enabled: bool,
+        path: Option<PathBuf>,
+        all: bool,
+    ) -> Result<(), String> {
+        if enabled {
+            self.enable(path, all).map(|_| ())
+        } else {
+            self.disable();
+            Ok(())
+        }
+    }
+
+    /// Start (or redirect) disk logging. `None` picks a fresh per-session file. The file is
+    /// opened here so a bad path is reported to the caller; writes happen on a background thread.
+    pub fn enable(&mut self, path: Option<PathBuf>, all: bool) -> Result<PathBuf, String> {
+        let path = match path {
+            Some(p) => p,
+            None => default_npc_log_path().ok_or("no log directory (HOME unset)")?,
+        };
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+
+        let (tx, rx) = mpsc::channel::<String>();
+        let thread_path = path.clone();
+        std::thread::spawn(move || {
+            use std::io::Write;
+            let mut out = std::io::BufWriter::new(file);
+            // Ends when the sender is dropped (disable, redirect, app exit)
+            for batch in rx {
+                if let Err(e) = out.write_all(batch.as_bytes()).and_then(|_| out.flush()) {
+                    error!("NPC log write to {} failed: {e}", thread_path.display());
+                    return;
+                }
+            }
+        });
+        let _ = tx.send(format!(
+            "# NPC log opened ({} entries). Format: d<day> hh:mm #<slot>.<generation> <message>\n",
+            if all { "all" } else { "evicted" }
+        ));
+
+        self.sender = Some(tx);
+        self.capture = if all {
+            NpcLogCapture::All
+        } else {
+            NpcLogCapture::Evicted
+        };
+        self.path = Some(path.clone());
+        self.last_error = None;
+        info!("NPC logging to {}", path.display());
+        Ok(path)
+    }
+
+    /// Stop disk logging. Entries captured but not yet flushed are dropped.
+    pub fn disable(&mut self) {
+        self.sender = None;
+        self.capture = NpcLogCapture::Off;
+    }
+}
+
+/// `Documents/Endless/logs/npc_log_<unix secs>.txt` beside the user settings.
+pub fn default_npc_log_path() -> Option<PathBuf> {
+    let home = std::env::var("USERPROFILE")
+        .or_else(|_| std::env::var("HOME"))
+        .ok()?;
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    Some(
+        PathBuf::from(home)
+            .join("Documents")
+            .join("Endless")
+            .join("logs")
+            .join(format!("npc_log_{stamp}.txt")),
+    )
+}
+
+/// Append records as log lines.
+pub fn format_npc_log_records(records: &[NpcLogRecord], out: &mut String) {
+    for r in records {
+        let _ = writeln!(
+            out,
+            "d{} {:02}:{:02} #{}.{} {}",
+            r.entry.day, r.entry.hour, r.entry.minute, r.slot, r.generation, r.entry.message
+        );
+    }
+}
+
+/// Keep `NpcLogCache.capture` in step with `NpcLogFile` and ship captured entries to the
+/// writer thread every `NPC_LOG_FLUSH_SECS`.
+pub fn npc_log_flush_system(
+    time: Res<Time<Real>>,
+    mut file: ResMut<NpcLogFile>,
+    mut npc_logs: ResMut<NpcLogCache>,
+) {
+    if npc_logs.capture != file.capture {
+        npc_logs.capture = file.capture;
+        if file.capture == NpcLogCapture::Off {
+            npc_logs.take_pending();
+        }
+    }
+    if !file.enabled() || !npc_logs.has_pending() {
+        return;
+    }
+    let now = time.elapsed_secs();
+    if now - file.last_flush < NPC_LOG_FLUSH_SECS {
+        return;
+    }
+    file.last_flush = now;
+
+    let records = npc_logs.take_pending();
+    let mut batch = String::with_capacity(records.len() * 48);
+    format_npc_log_records(&records, &mut batch);
+    let sent = file
+        .sender
+        .as_ref()
+        .is_some_and(|tx| tx.send(batch).is_ok());
+    if sent {
+        file.lines_written += records.len() as u64;
+    } else {
+        // Writer thread gave up (disk full, file removed); its error is already logged
+        file.last_error = Some("log writer stopped".into());
+        file.disable();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicted_and_reused_slots_are_distinguishable() {
+        let mut logs = NpcLogCache::default();
+        logs.mode = crate::settings::NpcLogMode::All;
+        logs.capture = NpcLogCapture::Evicted;
+        logs.begin_life(7);
+        for i in 0..101 {
+            logs.push(7, 1, 6, i % 60, format!("first {i}"));
+        }
+        // Only the overflowed entry is captured; the ring keeps the newest 100
+        assert_eq!(logs.logs[7].len(), 100);
+        let first = logs.take_pending();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].entry.message, "first 0");
+
+        // The slot is recycled: the rest of the old ring is flushed under the old generation
+        logs.begin_life(7);
+        logs.push(7, 2, 8, 5, "second 0");
+        let mut evicted = logs.take_pending();
+        assert_eq!(evicted.len(), 100);
+        assert!(evicted.iter().all(|r| r.generation == first[0].generation));
+        assert!(logs.generation(7) != first[0].generation);
+        assert_eq!(logs.logs[7].len(), 1);
+
+        evicted.truncate(1);
+        let mut out = String::new();
+        format_npc_log_records(&evicted, &mut out);
+        assert_eq!(out, "d1 06:01 #7.1 first 1\n");
+    }
+
+    #[test]
+    fn all_mode_captures_outside_the_ui_filter() {
+        let mut logs = NpcLogCache::default();
+        logs.capture = NpcLogCapture::All;
+        // SelectedOnly with nothing selected: the ring stays empty, the disk still gets it
+        logs.push(3, 1, 0, 0, "hello");
+        assert!(logs.logs[3].is_empty());
+        assert_eq!(logs.take_pending().len(), 1);
+
+        logs.capture = NpcLogCapture::Off;
+        logs.push(3, 1, 0, 1, "quiet");
+        assert!(!logs.has_pending());
+    }
+}
@@ -0,0 +1,244 @@
+//! Panic spread — morale collapses that cascade into routs.
+//! Units fleeing combat (low HP, a last stand, or a rout) raise the panic of same-town
+//! neighbours within `PANIC_RADIUS`. Panic decays continuously, so one casualty fades out and
+//! only sustained local fleeing outpaces the decay. A unit whose panic reaches the town's
+//! `panic_threshold` routs home for `PANIC_ROUT_SECS` and spreads panic in turn. Brave units
+//! never panic, Berserkers take it on slower, and nobody inside an officer aura gains any, so
+//! an officer can hold a line while the rout breaks around it.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::constants::{
+    PANIC_CHECK_SECS, PANIC_DECAY_PER_SEC, PANIC_MAX_SOURCES, PANIC_RADIUS, PANIC_ROUT_SECS,
+};
+use crate::messages::{WorkIntent, WorkIntentMsg};
+use crate::resources::*;
+use crate::systems::decision::transition_activity;
+
+/// Panic after `dt` seconds next to `sources` fleeing neighbours: `spread` per source (at most
+/// `PANIC_MAX_SOURCES`) scaled by `resist`, minus `PANIC_DECAY_PER_SEC`. Never below 0.
+pub fn panic_step(level: f32, sources: usize, spread: f32, resist: f32, dt: f32) -> f32 {
+    let gain = spread * sources.min(PANIC_MAX_SOURCES) as f32 * resist;
+    (level + (gain - PANIC_DECAY_PER_SEC) * dt).max(0.0)
+}
+
+/// How strongly a unit takes on panic (1 = normal). 0 for Brave units and anyone inside an
+/// officer aura; otherwise the trait flee multiplier (Berserker < 1, Timid > 1).
+pub fn panic_resist(personality: Option<&Personality>, in_aura: bool) -> f32 {
+    if in_aura {
+        return 0.0;
+    }
+    let Some(personality) = personality else {
+        return 1.0;
+    };
+    let mods = personality.get_behavior_mods();
+    if mods.never_flees { 0.0 } else { mods.flee }
+}
+
+/// Spread panic from fleeing units to their neighbours and rout those pushed past the
+/// threshold. Runs every `PANIC_CHECK_SECS`, before decision_system.
+pub fn panic_system(
+    mut panic: ResMut<PanicState>,
+    town_access: crate::systemparams::TownAccess,
+    entity_map: Res<EntityMap>,
+    gpu_state: Res<GpuReadState>,
+    game_time: Res<GameTime>,
+    mut intents: ResMut<PathRequestQueue>,
+    mut npc_logs: ResMut<NpcLogCache>,
+    mut work_intents: MessageWriter<WorkIntentMsg>,
+    last_stand_q: Query<&GpuSlot, (With<LastStand>, Without<Dead>)>,
+    mut npc_q: Query<
+        (
+            &Job,
+            &Home,
+            Option<&Personality>,
+            Has<AuraBuff>,
+            Has<LastStand>,
+            &mut Activity,
+            &mut CombatState,
+            &mut NpcWorkState,
+        ),
+        (Without<Building>, Without<Dead>, Without<ManualTarget>),
+    >,
+) {
+    let now = game_time.total_seconds;
+    if now < panic.last_update + PANIC_CHECK_SECS {
+        // Also covers a clock that restarted behind us (new game, load)
+        if now < panic.last_update {
+            panic.last_update = now;
+        }
+        return;
+    }
+    let dt = (now - panic.last_update).min(2.0 * PANIC_CHECK_SECS);
+    panic.last_update = now;
+
+    let alive = |slot: &usize| entity_map.get_npc(*slot).is_some_and(|n| !n.dead);
+    panic
+        .fleeing
+        .retain(|slot, until| *until > now && alive(slot));
+    panic
+        .routed
+        .retain(|slot, until| *until > now && alive(slot));
+    panic.levels.retain(|slot, _| alive(slot));
+
+    let pos_of = |slot: usize| {
+        gpu_state
+            .positions
+            .get(slot * 2..slot * 2 + 2)
+            .map(|p| Vec2::new(p[0], p[1]))
+            .filter(|p| p.x > -9000.0)
+    };
+
+    // Fleeing neighbours per slot
+    let fleeing: HashSet<usize> = panic
+        .fleeing
+        .keys()
+        .chain(panic.routed.keys())
+        .copied()
+        .chain(last_stand_q.iter().map(|s| s.0))
+        .collect();
+    let r2 = PANIC_RADIUS * PANIC_RADIUS;
+    let mut sources: HashMap<usize, usize> = HashMap::new();
+    for &src in &fleeing {
+        let Some(npc) = entity_map.get_npc(src).filter(|n| !n.dead) else {
+            continue;
+        };
+        let Some(center) = pos_of(src) else {
+            continue;
+        };
+        for other in entity_map.npcs_for_town(npc.town_idx) {
+            if other.dead || fleeing.contains(&other.slot) {
+                continue;
+            }
+            if pos_of(other.slot).is_some_and(|p| p.distance_squared(center) <= r2) {
+                *sources.entry(other.slot).or_default() += 1;
+            }
+        }
+    }
+
+    // Sorted so routs (and their log lines) come out in the same order every run
+    let slots: BTreeSet<usize> = sources.keys().chain(panic.levels.keys()).copied().collect();
+    let mut town_cfg: HashMap<i32, (f32, f32)> = HashMap::new();
+    for slot in slots {
+        let Some(npc) = entity_map.get_npc(slot) else {
+            continue;
+        };
+        let Ok((job, home, personality, in_aura, last_stand, mut activity, mut combat, mut work)) =
+            npc_q.get_mut(npc.entity)
+        else {
+            panic.levels.remove(&slot);
+            continue;
+        };
+        let (spread, threshold) = *town_cfg.entry(npc.town_idx).or_insert_with(|| {
+            town_access
+                .policy(npc.town_idx)
+                .map_or((0.0, f32::MAX), |p| (p.panic_spread, p.panic_threshold))
+        });
+        // Last-stand defenders are already falling back under orders
+        let resist = if *job == Job::Boat || last_stand {
+            0.0
+        } else {
+            panic_resist(personality, in_aura)
+        };
+        let count = sources.get(&slot).copied().unwrap_or(0);
+        let level = panic_step(panic.level(slot), count, spread, resist, dt);
+        if level <= 0.0 {
+            panic.levels.remove(&slot);
+            continue;
+        }
+        if level < threshold || panic.is_routed(slot) {
+            panic.levels.insert(slot, level);
+            continue;
+        }
+
+        // Rout: drop everything and run home
+        panic.levels.remove(&slot);
+        panic.routed.insert(slot, now + PANIC_ROUT_SECS);
+        panic.mark_fleeing(slot, now);
+        if activity.kind.def().is_working {
+            work_intents.write(WorkIntentMsg(WorkIntent::Release {
+                entity: npc.entity,
+                worksite: work.worksite.take(),
+            }));
+        }
+        *combat = CombatState::None;
+        transition_activity(
+            &mut activity,
+            ActivityKind::ReturnLoot,
+            ActivityPhase::Transit,
+            ActivityTarget::Dropoff,
+            "panic:rout",
+        );
+        intents.submit(npc.entity, home.0, MovementPriority::Survival, "panic:rout");
+        npc_logs.push(
+            slot,
+            game_time.day(),
+            game_time.hour(),
+            game_time.minute(),
+            "Panicked and fled",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(mut level: f32, sources: usize, resist: f32, secs: f32) -> f32 {
+        let steps = (secs / PANIC_CHECK_SECS) as usize;
+        for _ in 0..steps {
+            level = panic_step(level, sources, 0.2, resist, PANIC_CHECK_SECS);
+        }
+        level
+    }
+
+    #[test]
+    fn one_casualty_never_routs() {
+        // A single fleeing neighbour never outpaces the decay, however long it lasts
+        assert_eq!(run(0.0, 1, 1.0, 60.0), 0.0);
+        assert_eq!(run(0.0, 2, 1.0, 60.0), 0.0);
+        // Existing panic bleeds off once the fleeing stops
+        assert_eq!(run(0.9, 0, 1.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn sustained_fleeing_cascades_at_a_capped_rate() {
+        // 4 neighbours fleeing: +0.3/s, so about 3.5s to reach 1.0
+        assert!(run(0.0, 4, 1.0, 3.0) < 1.0);
+        assert!(run(0.0, 4, 1.0, 4.0) >= 1.0);
+        // A whole army breaking nearby is capped at PANIC_MAX_SOURCES
+        assert_eq!(
+            run(0.0, 50, 1.0, 1.0),
+            run(0.0, PANIC_MAX_SOURCES, 1.0, 1.0)
+        );
+        assert!(run(0.0, 50, 1.0, 1.5) < 1.0);
+    }
+
+    #[test]
+    fn brave_and_officer_aura_resist() {
+        let brave = Personality {
+            trait1: Some(TraitInstance {
+                kind: TraitKind::Courage,
+                magnitude: 1.0,
+            }),
+            trait2: None,
+        };
+        let berserker = Personality {
+            trait1: Some(TraitInstance {
+                kind: TraitKind::Ferocity,
+                magnitude: 1.0,
+            }),
+            trait2: None,
+        };
+        assert_eq!(panic_resist(Some(&brave), false), 0.0);
+        assert_eq!(panic_resist(None, true), 0.0);
+        assert_eq!(panic_resist(None, false), 1.0);
+        let r = panic_resist(Some(&berserker), false);
+        assert!(r > 0.0 && r < 1.0);
+        assert_eq!(run(0.0, 5, 0.0, 30.0), 0.0);
+        assert!(run(0.0, 5, r, 4.0) < run(0.0, 5, 1.0, 4.0));
+    }
+}
@@ -27,13 +27,17 @@ pub fn on_duty_tick_system(
 }
 
 /// Rebuild all guards' patrol routes when WorldData changes (waypoint added/removed/reordered).
+/// Units pinned to a single post (`AssignedPost`) keep their route.
 pub fn rebuild_patrol_routes_system(
     entity_map: Res<crate::entity_map::EntityMap>,
     mut patrols_dirty: MessageReader<crate::messages::PatrolsDirtyMsg>,
     mut patrol_swaps: MessageReader<crate::messages::PatrolSwapMsg>,
     mut patrol_route_q: Query<&mut PatrolRoute>,
     mut commands: Commands,
-    patrol_npc_q: Query<(Entity, &GpuSlot, &Job, &TownId), (Without<Building>, Without<Dead>)>,
+    patrol_npc_q: Query<
+        (Entity, &GpuSlot, &Job, &TownId),
+        (Without<Building>, Without<Dead>, Without<AssignedPost>),
+    >,
     mut waypoint_q: Query<&mut WaypointOrder, With<Building>>,
 ) {
     if patrols_dirty.read().count() == 0 {
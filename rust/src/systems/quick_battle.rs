@@ -0,0 +1,337 @@
+//! Quick battle — deterministic two-army scenario for balance iteration.
+//! `endless/quick_battle` queues a request; `quick_battle_system` allocates slots, stamps
+//! level/trait overrides and writes `SpawnNpcMsg`s (spawn_npc_system materializes them).
+//! No economy, pop cap or town gates apply. Teardown despawns every surviving unit.
+
+use bevy::prelude::*;
+
+use crate::components::{Job, Personality, TraitInstance};
+use crate::messages::{DespawnNpcMsg, SpawnNpcMsg};
+use crate::resources::*;
+use crate::systems::spawn::{NpcSpawnOverrides, SpawnOverrideQueue};
+
+/// Max units per rank before the line wraps to a rank behind it.
+pub const QUICK_BATTLE_LINE_WIDTH: usize = 16;
+/// Distance (px) between neighbouring units and between ranks.
+pub const QUICK_BATTLE_SPACING: f32 = 24.0;
+/// Default distance (px) between the two front ranks.
+pub const QUICK_BATTLE_GAP: f32 = 160.0;
+/// Default combat RNG seed when the caller doesn't pass one.
+pub const QUICK_BATTLE_SEED: u64 = 1;
+/// Highest army level a quick battle accepts; keeps the level's XP (100 * level²) in i32.
+pub const QUICK_BATTLE_MAX_LEVEL: i32 = 1000;
+
+/// One side of a quick battle. Units are placed in spec order, front rank first.
+#[derive(Clone, Debug, Default)]
+pub struct ArmySpec {
+    pub units: Vec<(Job, usize)>,
+    pub level: i32,
+    /// Up to two traits applied to every unit (no slot-random personality).
+    pub traits: Vec<TraitInstance>,
+}
+
+impl ArmySpec {
+    pub fn size(&self) -> usize {
+        self.units.iter().map(|(_, n)| n).sum()
+    }
+
+    /// Level clamped to `0..=QUICK_BATTLE_MAX_LEVEL`.
+    pub(crate) fn clamped_level(&self) -> i32 {
+        self.level.clamp(0, QUICK_BATTLE_MAX_LEVEL)
+    }
+
+    /// XP that puts a unit exactly at `clamped_level`.
+    pub(crate) fn xp(&self) -> i32 {
+        let level = self.clamped_level();
+        100 * level * level
+    }
+
+    pub(crate) fn personality(&self) -> Personality {
+        Personality {
+            trait1: self.traits.first().copied(),
+            trait2: self.traits.get(1).copied(),
+        }
+    }
+}
+
+/// Queued setup, consumed by `quick_battle_system` next tick.
+pub struct BattleRequest {
+    pub id: u32,
+    pub armies: [ArmySpec; 2],
+    pub center: Vec2,
+    pub gap: f32,
+    pub seed: u64,
+}
+
+/// A deployed battle. Side 0 is the west line, side 1 the east line.
+#[derive(Clone, Debug)]
+pub struct ActiveBattle {
+    pub id: u32,
+    pub seed: u64,
+    pub center: Vec2,
+    /// Dedicated factions past the end of FactionList (never shared with real towns).
+    pub factions: [i32; 2],
+    pub slots: [Vec<usize>; 2],
+    /// Game seconds at deploy.
+    pub started: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct QuickBattle {
+    next_id: u32,
+    pub pending: Option<BattleRequest>,
+    pub active: Option<ActiveBattle>,
+    /// Teardown requested — despawn survivors next tick.
+    pub reset_pending: bool,
+}
+
+impl QuickBattle {
+    /// Queue a new battle (replacing any running one). Returns its id.
+    pub fn request(&mut self, armies: [ArmySpec; 2], center: Vec2, gap: f32, seed: u64) -> u32 {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.reset_pending = self.active.is_some();
+        self.pending = Some(BattleRequest {
+            id,
+            armies,
+            center,
+            gap,
+            seed,
+        });
+        id
+    }
+
+    /// Queue teardown of battle `id`. False if it isn't the current battle.
+    pub fn reset(&mut self, id: u32) -> bool {
+        if self.pending.as_ref().is_some_and(|r| r.id == id) {
+            self.pending = None;
+            return true;
+        }
+        if self.active.as_ref().is_some_and(|b| b.id == id) {
+            self.reset_pending = true;
+            return true;
+        }
+        false
+    }
+
+    /// Id of the queued or running battle.
+    pub fn current_id(&self) -> Option<u32> {
+        self.pending
+            .as_ref()
+            .map(|r| r.id)
+            .or(self.active.as_ref().map(|b| b.id))
+    }
+}
+
+/// Positions for both lines, mirrored across `center.x`. Side 0 faces east, side 1 west.
+/// Each rank holds up to `QUICK_BATTLE_LINE_WIDTH` units centered on `center.y`;
+/// later ranks stand further back from the gap.
+pub fn battle_lines(armies: &[ArmySpec; 2], center: Vec2, gap: f32) -> [Vec<(Job, Vec2)>; 2] {
+    let place = |army: &ArmySpec, dir: f32| -> Vec<(Job, Vec2)> {
+        let total = army.size();
+        army.units
+            .iter()
+            .flat_map(|&(job, n)| std::iter::repeat_n(job, n))
+            .enumerate()
+            .map(|(i, job)| {
+                let rank = i / QUICK_BATTLE_LINE_WIDTH;
+                let file = i % QUICK_BATTLE_LINE_WIDTH;
+                let in_rank = (total - rank * QUICK_BATTLE_LINE_WIDTH).min(QUICK_BATTLE_LINE_WIDTH);
+                let x = center.x + dir * (gap * 0.5 + rank as f32 * QUICK_BATTLE_SPACING);
+                let y =
+                    center.y + (file as f32 - (in_rank - 1) as f32 * 0.5) * QUICK_BATTLE_SPACING;
+                (job, Vec2::new(x, y))
+            })
+            .collect()
+    };
+    [place(&armies[0], -1.0), place(&armies[1], 1.0)]
+}
+
+/// Deploys queued battles and tears down finished ones. Runs before spawn_npc_system so
+/// fresh spawns materialize the same tick.
+pub fn quick_battle_system(
+    mut battle: ResMut<QuickBattle>,
+    mut slots: ResMut<GpuSlotPool>,
+    mut overrides: ResMut<SpawnOverrideQueue>,
+    mut spawn_writer: MessageWriter<SpawnNpcMsg>,
+    mut despawn_writer: MessageWriter<DespawnNpcMsg>,
+    mut faction_stats: ResMut<FactionStats>,
+    mut combat_rng: ResMut<CombatRng>,
+    entity_map: Res<EntityMap>,
+    faction_list: Res<FactionList>,
+    game_time: Res<GameTime>,
+) {
+    if battle.reset_pending {
+        battle.reset_pending = false;
+        if let Some(old) = battle.active.take() {
+            for (side, side_slots) in old.slots.iter().enumerate() {
+                for &slot in side_slots {
+                    // Dead units are freed by death_system; a reused slot belongs to someone else
+                    let ours = entity_map
+                        .get_npc(slot)
+                        .is_some_and(|n| !n.dead && n.faction == old.factions[side]);
+                    if ours {
+                        despawn_writer.write(DespawnNpcMsg { slot });
+                    }
+                }
+            }
+            info!("quick battle #{} torn down", old.id);
+        }
+    }
+
+    let Some(req) = battle.pending.take() else {
+        return;
+    };
+    let total: usize = req.armies.iter().map(|a| a.size()).sum();
+    let mut allocated = Vec::with_capacity(total);
+    for _ in 0..total {
+        let Some(slot) = slots.alloc_reset() else {
+            break;
+        };
+        allocated.push(slot);
+    }
+    if allocated.len() < total {
+        warn!(
+            "quick battle #{}: needs {} slots, only {} free",
+            req.id,
+            total,
+            allocated.len()
+        );
+        for slot in allocated {
+            slots.free(slot);
+        }
+        return;
+    }
+
+    // Faction 0 is neutral (never targeted), so an empty list still starts at 1
+    let base = faction_list.factions.len().max(1) as i32;
+    let factions = [base, base + 1];
+    faction_stats.ensure_size(base as usize + 2);
+    combat_rng.seed = req.seed;
+    combat_rng.counter = 0;
+
+    let lines = battle_lines(&req.armies, req.center, req.gap);
+    let mut free_slots = allocated.into_iter();
+    let mut side_slots: [Vec<usize>; 2] = Default::default();
+    for (side, line) in lines.iter().enumerate() {
+        let army = &req.armies[side];
+        for &(job, pos) in line {
+            let Some(slot) = free_slots.next() else {
+                break;
+            };
+            overrides.0.insert(
+                slot,
+                NpcSpawnOverrides {
+                    personality: Some(army.personality()),
+                    level: Some(army.clamped_level()),
+                    xp: Some(army.xp()),
+                    ..Default::default()
+                },
+            );
+            spawn_writer.write(SpawnNpcMsg {
+                slot_idx: slot,
+                x: pos.x,
+                y: pos.y,
+                job: job as i32,
+                faction: factions[side],
+                town_idx: -1,
+                home_x: pos.x,
+                home_y: pos.y,
+                work_x: -1.0,
+                work_y: -1.0,
+                starting_post: -1,
+                entity_override: None,
+            });
+            side_slots[side].push(slot);
+        }
+    }
+
+    info!(
+        "quick battle #{}: {} vs {} at ({:.0},{:.0}) seed {}",
+        req.id,
+        side_slots[0].len(),
+        side_slots[1].len(),
+        req.center.x,
+        req.center.y,
+        req.seed
+    );
+    battle.active = Some(ActiveBattle {
+        id: req.id,
+        seed: req.seed,
+        center: req.center,
+        factions,
+        slots: side_slots,
+        started: game_time.total_seconds,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn army(units: Vec<(Job, usize)>) -> ArmySpec {
+        ArmySpec {
+            units,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn battle_lines_are_mirrored_and_spaced() {
+        let armies = [
+            army(vec![(Job::Archer, 10), (Job::Crossbow, 10)]),
+            army(vec![(Job::Raider, 20)]),
+        ];
+        let center = Vec2::new(1000.0, 500.0);
+        let [west, east] = battle_lines(&armies, center, 160.0);
+        assert_eq!(west.len(), 20);
+        assert_eq!(east.len(), 20);
+
+        // Front rank is full width, second rank holds the remaining 4
+        let front: Vec<_> = west.iter().filter(|(_, p)| p.x == 920.0).collect();
+        assert_eq!(front.len(), QUICK_BATTLE_LINE_WIDTH);
+        let back: Vec<_> = west
+            .iter()
+            .filter(|(_, p)| p.x == 920.0 - QUICK_BATTLE_SPACING)
+            .collect();
+        assert_eq!(back.len(), 4);
+        assert!(back.iter().all(|(job, _)| *job == Job::Crossbow));
+
+        // Each rank is centered on the line and evenly spaced
+        let ys: Vec<f32> = front.iter().map(|(_, p)| p.y).collect();
+        assert_eq!(ys[0] + ys[15], 2.0 * center.y);
+        assert_eq!(ys[1] - ys[0], QUICK_BATTLE_SPACING);
+
+        // Same unit count → positions are mirrored across center.x
+        for ((_, w), (_, e)) in west.iter().zip(&east) {
+            assert_eq!(w.x + e.x, 2.0 * center.x);
+            assert_eq!(w.y, e.y);
+        }
+    }
+
+    #[test]
+    fn level_xp_is_clamped_instead_of_overflowing() {
+        let mut spec = army(vec![(Job::Fighter, 1)]);
+        spec.level = 3;
+        assert_eq!(spec.xp(), 900);
+        spec.level = i32::MAX;
+        assert_eq!(spec.clamped_level(), QUICK_BATTLE_MAX_LEVEL);
+        assert_eq!(
+            spec.xp(),
+            100 * QUICK_BATTLE_MAX_LEVEL * QUICK_BATTLE_MAX_LEVEL
+        );
+        spec.level = -5;
+        assert_eq!(spec.xp(), 0);
+    }
+
+    #[test]
+    fn reset_matches_current_battle_only() {
+        let mut qb = QuickBattle::default();
+        let id = qb.request(Default::default(), Vec2::ZERO, 100.0, 7);
+        assert_eq!(qb.current_id(), Some(id));
+        assert!(!qb.reset(id + 1));
+        assert!(qb.reset(id));
+        assert!(qb.pending.is_none());
+        assert_eq!(qb.current_id(), None);
+    }
+}
@@ -0,0 +1,241 @@
+//! Raid parties — raider camps raid with the raiders they actually have.
+//! With `RaidPartyConfig.enabled`, camps leave the squad commander: every heartbeat the camp's
+//! idle raiders are counted, and once at least `min_party` are home (and the camp's cooldown
+//! has passed) up to `max_party` of them are sent to the nearest enemy farm as `Raid`. The
+//! arrival system steals and turns them for home (`ReturnLoot`); back home they idle and are
+//! available again. A camp bled of raiders waits and accumulates instead of throwing away a
+//! token force, so clearing camps weakens the raids they send.
+
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::constants::TownKind;
+use crate::messages::CombatLogMsg;
+use crate::resources::*;
+use crate::systems::ai_player::pick_raider_farm_target;
+use crate::systems::decision::transition_activity;
+use crate::systems::threat_map::ThreatMap;
+use crate::world::WorldData;
+
+/// Game seconds between camp heartbeats.
+const RAID_PARTY_HEARTBEAT: f32 = 2.0;
+
+/// What a camp raider is doing, for `CampStatus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CampRole {
+    Available,
+    Raiding,
+    Returning,
+    /// Resting, healing, fighting, or still in a squad.
+    Busy,
+}
+
+pub(crate) fn camp_role(activity: &Activity, fighting: bool, in_squad: bool) -> CampRole {
+    match activity.kind {
+        ActivityKind::Raid => CampRole::Raiding,
+        ActivityKind::ReturnLoot => CampRole::Returning,
+        ActivityKind::Idle | ActivityKind::Wander if !fighting && !in_squad => CampRole::Available,
+        _ => CampRole::Busy,
+    }
+}
+
+/// The raiders to send from `available`: the first `max_party`, or none below `min_party`.
+pub(crate) fn assemble_party<'a, T>(
+    available: &'a [T],
+    config: &RaidPartyConfig,
+) -> Option<&'a [T]> {
+    if available.is_empty() || available.len() < config.min_party.max(1) {
+        return None;
+    }
+    Some(&available[..available.len().min(config.max_party.max(1))])
+}
+
+/// Track camp populations and dispatch raid parties. Runs before decision_system.
+pub fn raid_party_system(
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    config: Res<RaidPartyConfig>,
+    mut parties: ResMut<RaidParties>,
+    world_data: Res<WorldData>,
+    entity_map: Res<EntityMap>,
+    threat_map: Res<ThreatMap>,
+    faction_list: Res<FactionList>,
+    mut squad_state: ResMut<SquadState>,
+    mut intents: ResMut<PathRequestQueue>,
+    mut npc_logs: ResMut<NpcLogCache>,
+    mut combat_log: MessageWriter<CombatLogMsg>,
+    mut commands: Commands,
+    mut timer: Local<f32>,
+    mut raider_q: Query<
+        (&mut Activity, &CombatState, Has<SquadId>),
+        (Without<Building>, Without<Dead>),
+    >,
+) {
+    *timer += game_time.delta(&time);
+    if *timer < RAID_PARTY_HEARTBEAT {
+        return;
+    }
+    *timer = 0.0;
+    let now_h = game_time.elapsed_hours();
+
+    parties.camps.retain(|&idx, _| {
+        world_data
+            .towns
+            .get(idx)
+            .is_some_and(|t| t.kind == TownKind::AiRaider)
+    });
+    for (tdi, town) in world_data.towns.iter().enumerate() {
+        if town.kind != TownKind::AiRaider {
+            continue;
+        }
+
+        // Party mode owns the camp's raiders: dissolve the commander's attack squad
+        if config.enabled {
+            for squad in squad_state
+                .squads
+                .iter_mut()
+                .filter(|s| s.owner == SquadOwner::Town(tdi) && !s.members.is_empty())
+            {
+                for &e in &squad.members {
+                    commands.entity(e).remove::<SquadId>();
+                }
+                squad.members.clear();
+                squad.target = None;
+                squad.target_size = 0;
+                squad.wave_active = false;
+            }
+        }
+
+        let mut status = CampStatus {
+            next_party_hour: parties
+                .get_camp_status(tdi)
+                .map_or(0.0, |c| c.next_party_hour),
+            ..default()
+        };
+        let mut available: Vec<(usize, Entity)> = Vec::new();
+        for npc in entity_map.npcs_for_town(tdi as i32) {
+            if npc.dead || npc.job != Job::Raider {
+                continue;
+            }
+            let Ok((activity, combat, in_squad)) = raider_q.get(npc.entity) else {
+                continue;
+            };
+            status.total += 1;
+            match camp_role(activity, combat.is_fighting(), in_squad) {
+                CampRole::Available => available.push((npc.slot, npc.entity)),
+                CampRole::Raiding => status.raiding += 1,
+                CampRole::Returning => status.returning += 1,
+                CampRole::Busy => {}
+            }
+        }
+        available.sort_unstable_by_key(|&(slot, _)| slot);
+        status.available = available.len();
+
+        if config.enabled && now_h >= status.next_party_hour {
+            let party = assemble_party(&available, &config);
+            let stance = faction_list.stance(town.faction);
+            let target = party.and_then(|_| {
+                pick_raider_farm_target(&entity_map, &threat_map, town.center, town.faction).filter(
+                    |&(_, _, pos)| {
+                        stance.is_none_or(|st| st.may_initiate(pos.distance(town.center)))
+                    },
+                )
+            });
+            if let (Some(party), Some((kind, _, pos))) = (party, target) {
+                for &(slot, entity) in party {
+                    let Ok((mut activity, _, _)) = raider_q.get_mut(entity) else {
+                        continue;
+                    };
+                    transition_activity(
+                        &mut activity,
+                        ActivityKind::Raid,
+                        ActivityPhase::Transit,
+                        ActivityTarget::RaidPoint(pos),
+                        "raid:party",
+                    );
+                    intents.submit(entity, pos, MovementPriority::JobRoute, "raid:party");
+                    npc_logs.push(
+                        slot,
+                        game_time.day(),
+                        game_time.hour(),
+                        game_time.minute(),
+                        "Joined raid party",
+                    );
+                }
+                status.available -= party.len();
+                status.raiding += party.len();
+                status.next_party_hour = now_h + config.cooldown_hours.max(0.0);
+                combat_log.write(CombatLogMsg {
+                    kind: CombatEventKind::Raid,
+                    faction: town.faction,
+                    day: game_time.day(),
+                    hour: game_time.hour(),
+                    minute: game_time.minute(),
+                    message: format!(
+                        "{} raid party: {} of {} raiders -> {}",
+                        town.name,
+                        party.len(),
+                        status.total,
+                        crate::constants::building_def(kind).label
+                    ),
+                    location: Some(pos),
+                });
+            }
+        }
+        parties.camps.insert(tdi, status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_camps_wait_and_parties_are_capped() {
+        let config = RaidPartyConfig {
+            enabled: true,
+            min_party: 3,
+            max_party: 5,
+            cooldown_hours: 2.0,
+        };
+        assert_eq!(assemble_party(&[1, 2], &config), None);
+        assert_eq!(assemble_party(&[1, 2, 3], &config), Some(&[1, 2, 3][..]));
+        let camp: Vec<usize> = (0..9).collect();
+        assert_eq!(assemble_party(&camp, &config).map(<[_]>::len), Some(5));
+        // min_party 0 still never sends an empty party
+        let any = RaidPartyConfig {
+            min_party: 0,
+            ..config
+        };
+        assert_eq!(assemble_party::<usize>(&[], &any), None);
+    }
+
+    #[test]
+    fn only_idle_free_raiders_are_available() {
+        let act = |kind| Activity { kind, ..default() };
+        assert_eq!(
+            camp_role(&act(ActivityKind::Wander), false, false),
+            CampRole::Available
+        );
+        assert_eq!(
+            camp_role(&act(ActivityKind::Idle), true, false),
+            CampRole::Busy
+        );
+        assert_eq!(
+            camp_role(&act(ActivityKind::Idle), false, true),
+            CampRole::Busy
+        );
+        assert_eq!(
+            camp_role(&act(ActivityKind::Rest), false, false),
+            CampRole::Busy
+        );
+        assert_eq!(
+            camp_role(&act(ActivityKind::Raid), true, false),
+            CampRole::Raiding
+        );
+        assert_eq!(
+            camp_role(&act(ActivityKind::ReturnLoot), false, false),
+            CampRole::Returning
+        );
+    }
+}
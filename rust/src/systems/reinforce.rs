@@ -0,0 +1,436 @@
+//! Town alerts and reinforcement.
+//! `town_alert_system` raises a per-town alert when enemy damage lands on a town's NPCs or
+//! buildings. `reinforce_system` sends idle/patrolling guards within the policy radius toward
+//! the alert, holding back `reinforce_reserve` of the town's guards, and walks them back to
+//! their patrol posts once the alert clears. `last_stand_system` pulls a town's defenders
+//! back into a tight ring around the town center when enemies near the alert outnumber them
+//! past the policy ratio, and releases them once the odds recover.
+
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::messages::DamageMsg;
+use crate::resources::*;
+use crate::systems::decision::transition_activity;
+use crate::world::WorldData;
+
+/// How many more guards a town may send: everything above the reserve, minus those already out.
+pub fn reinforce_budget(defenders: usize, reinforcing: usize, reserve: f32) -> usize {
+    let keep = (defenders as f32 * reserve.clamp(0.0, 1.0)).ceil() as usize;
+    defenders.saturating_sub(keep).saturating_sub(reinforcing)
+}
+
+/// Whether a town should be (or stay) in a last stand. Entering needs `enemies >= ratio ×
+/// defenders`; once retreating, the order only lifts below `LAST_STAND_RECOVER` of that, so
+/// a ratio hovering at the threshold doesn't flip it every check. ratio <= 0 = disabled.
+pub fn last_stand_verdict(enemies: usize, defenders: usize, ratio: f32, retreating: bool) -> bool {
+    if ratio <= 0.0 || defenders == 0 {
+        return false;
+    }
+    let needed = if retreating {
+        ratio * crate::constants::LAST_STAND_RECOVER
+    } else {
+        ratio
+    };
+    enemies as f32 >= needed * defenders as f32
+}
+
+/// Formation slot `index` around `center`: slot 0 on the center, then rings of 6, 12, 18...
+/// at `spacing` px steps.
+pub fn rally_slot(center: Vec2, index: usize, spacing: f32) -> Vec2 {
+    if index == 0 {
+        return center;
+    }
+    let (mut ring, mut first) = (1usize, 1usize);
+    while index >= first + 6 * ring {
+        first += 6 * ring;
+        ring += 1;
+    }
+    let angle = (index - first) as f32 / (6 * ring) as f32 * std::f32::consts::TAU;
+    center + Vec2::from_angle(angle) * ring as f32 * spacing
+}
+
+/// Raise/refresh town alerts from this tick's enemy damage, then expire stale ones.
+pub fn town_alert_system(
+    mut damage: MessageReader<DamageMsg>,
+    mut alerts: ResMut<TownAlerts>,
+    entity_map: Res<EntityMap>,
+    gpu_state: Res<GpuReadState>,
+    game_time: Res<GameTime>,
+) {
+    let now = game_time.total_seconds;
+    for msg in damage.read() {
+        let Some(slot) = entity_map.slot_for_entity(msg.target) else {
+            continue;
+        };
+        let (town, faction, pos) = if let Some(npc) = entity_map.get_npc(slot) {
+            let Some(pos) = gpu_state
+                .positions
+                .get(slot * 2..slot * 2 + 2)
+                .map(|p| Vec2::new(p[0], p[1]))
+            else {
+                continue;
+            };
+            (npc.town_idx, npc.faction, pos)
+        } else if let Some(inst) = entity_map.get_instance(slot) {
+            (inst.town_idx as i32, inst.faction, inst.position)
+        } else {
+            continue;
+        };
+        if town < 0 || msg.attacker_faction == faction || pos.x < -9000.0 {
+            continue;
+        }
+        alerts.raise(town as usize, pos, now);
+    }
+    alerts.expire(now);
+}
+
+/// Dispatch reinforcements toward active alerts and recall them when alerts clear.
+/// Squad members, units with a `ManualTarget`, HoldFire units and units already in combat
+/// are left alone.
+pub fn reinforce_system(
+    alerts: Res<TownAlerts>,
+    last_stand: Res<LastStandState>,
+    town_access: crate::systemparams::TownAccess,
+    world_data: Res<WorldData>,
+    gpu_state: Res<GpuReadState>,
+    mut intents: ResMut<PathRequestQueue>,
+    mut commands: Commands,
+    mut npc_q: Query<
+        (
+            Entity,
+            &GpuSlot,
+            &Job,
+            &TownId,
+            &mut Activity,
+            &CombatState,
+            Option<&CombatStance>,
+            Option<&Reinforcing>,
+            Option<&PatrolRoute>,
+        ),
+        (
+            Without<Building>,
+            Without<Dead>,
+            Without<SquadId>,
+            Without<ManualTarget>,
+        ),
+    >,
+    tagged_q: Query<(), With<Reinforcing>>,
+) {
+    if alerts.alerts.iter().all(Option::is_none) && tagged_q.is_empty() {
+        return;
+    }
+    let towns = world_data.towns.len();
+    let mut defenders = vec![0usize; towns];
+    let mut reinforcing = vec![0usize; towns];
+    // (town, dist², entity)
+    let mut candidates: Vec<(usize, f32, Entity)> = Vec::new();
+
+    for (entity, slot, job, town_id, mut activity, combat, stance, tag, route) in npc_q.iter_mut() {
+        if !job.is_patrol_unit() || town_id.0 < 0 || town_id.0 as usize >= towns {
+            continue;
+        }
+        let town = town_id.0 as usize;
+        defenders[town] += 1;
+
+        if let Some(tag) = tag {
+            let alert_active = alerts.get(tag.town).is_some();
+            if alert_active && activity.kind == ActivityKind::SquadAttack {
+                reinforcing[town] += 1;
+                continue;
+            }
+            commands.entity(entity).remove::<Reinforcing>();
+            // Decision system took over (flee, rest, heal) — leave its choice alone
+            if activity.kind != ActivityKind::SquadAttack {
+                continue;
+            }
+            let post = route.filter(|r| !r.posts.is_empty()).map(|r| {
+                (
+                    r.current % r.posts.len(),
+                    r.posts[r.current % r.posts.len()],
+                )
+            });
+            if let Some((index, post)) = post {
+                transition_activity(
+                    &mut activity,
+                    ActivityKind::Patrol,
+                    ActivityPhase::Transit,
+                    ActivityTarget::PatrolPost {
+                        route: 0,
+                        index: index as u16,
+                    },
+                    "reinforce:return",
+                );
+                intents.submit(entity, post, MovementPriority::JobRoute, "reinforce:return");
+            } else {
+                transition_activity(
+                    &mut activity,
+                    ActivityKind::Idle,
+                    ActivityPhase::Ready,
+                    ActivityTarget::None,
+                    "reinforce:return",
+                );
+            }
+            continue;
+        }
+
+        let Some(alert) = alerts.get(town) else {
+            continue;
+        };
+        let Some(policy) = town_access.policy(town as i32) else {
+            continue;
+        };
+        let eligible = policy.reinforce_enabled
+            && !last_stand.is_retreating(town)
+            && matches!(activity.kind, ActivityKind::Idle | ActivityKind::Patrol)
+            && !combat.is_fighting()
+            && !matches!(stance, Some(CombatStance::HoldFire));
+        if !eligible {
+            continue;
+        }
+        let i = slot.0;
+        let Some(pos) = gpu_state
+            .positions
+            .get(i * 2..i * 2 + 2)
+            .map(|p| Vec2::new(p[0], p[1]))
+        else {
+            continue;
+        };
+        let d2 = pos.distance_squared(alert.pos);
+        if d2 <= policy.reinforce_radius * policy.reinforce_radius {
+            candidates.push((town, d2, entity));
+        }
+    }
+
+    // Closest first, up to each town's budget
+    candidates.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    let mut sent = vec![0usize; towns];
+    for (town, _, entity) in candidates {
+        let (Some(alert), Some(policy)) = (alerts.get(town), town_access.policy(town as i32))
+        else {
+            continue;
+        };
+        let budget = reinforce_budget(defenders[town], reinforcing[town], policy.reinforce_reserve);
+        if sent[town] >= budget {
+            continue;
+        }
+        let Ok((_, _, _, _, mut activity, ..)) = npc_q.get_mut(entity) else {
+            continue;
+        };
+        transition_activity(
+            &mut activity,
+            ActivityKind::SquadAttack,
+            ActivityPhase::Transit,
+            ActivityTarget::SquadPoint(alert.pos),
+            "reinforce:alert",
+        );
+        intents.submit(
+            entity,
+            alert.pos,
+            MovementPriority::Squad,
+            "reinforce:alert",
+        );
+        commands.entity(entity).insert(Reinforcing { town });
+        sent[town] += 1;
+    }
+}
+
+/// Re-check each alerted town's local force ratio on a cooldown; order a coordinated
+/// fall-back to the town center when it turns bad, and release defenders when it recovers
+/// or the alert clears. Between checks, keeps retreating defenders moving (Survival
+/// priority beats combat chase) until they reach their formation slot.
+pub fn last_stand_system(
+    mut state: ResMut<LastStandState>,
+    alerts: Res<TownAlerts>,
+    town_access: crate::systemparams::TownAccess,
+    world_data: Res<WorldData>,
+    entity_map: Res<EntityMap>,
+    gpu_state: Res<GpuReadState>,
+    game_time: Res<GameTime>,
+    mut intents: ResMut<PathRequestQueue>,
+    mut commands: Commands,
+    mut npc_q: Query<
+        (
+            Entity,
+            &Job,
+            &TownId,
+            &mut Activity,
+            &mut CombatState,
+            Option<&LastStand>,
+        ),
+        (
+            Without<Building>,
+            Without<Dead>,
+            Without<SquadId>,
+            Without<ManualTarget>,
+        ),
+    >,
+) {
+    let now = game_time.total_seconds;
+    if now < state.next_check {
+        if !state.retreating.iter().any(|&r| r) {
+            return;
+        }
+        for (entity, _, _, activity, _, tag) in npc_q.iter() {
+            if tag.is_none() || activity.phase != ActivityPhase::Transit {
+                continue;
+            }
+            if let ActivityTarget::SquadPoint(slot) = activity.target {
+                intents.submit(entity, slot, MovementPriority::Survival, "last_stand:rally");
+            }
+        }
+        return;
+    }
+    state.next_check = now + crate::constants::LAST_STAND_CHECK_SECS;
+
+    let towns = world_data.towns.len();
+    state.retreating.resize(towns, false);
+    let r2 = crate::constants::LAST_STAND_RADIUS * crate::constants::LAST_STAND_RADIUS;
+    let pos_of = |slot: usize| {
+        gpu_state
+            .positions
+            .get(slot * 2..slot * 2 + 2)
+            .map(|p| Vec2::new(p[0], p[1]))
+    };
+    for town in 0..towns {
+        let was = state.retreating[town];
+        let ratio = town_access
+            .policy(town as i32)
+            .map_or(0.0, |p| p.last_stand_ratio);
+        let now_retreating = match alerts.get(town) {
+            Some(alert) if ratio > 0.0 => {
+                let faction = world_data.towns[town].faction;
+                let (mut enemies, mut defenders) = (0usize, 0usize);
+                for npc in entity_map.iter_npcs() {
+                    if npc.dead {
+                        continue;
+                    }
+                    let Some(pos) = pos_of(npc.slot) else {
+                        continue;
+                    };
+                    if pos.distance_squared(alert.pos) > r2 {
+                        continue;
+                    }
+                    if npc.town_idx == town as i32 && npc.job.is_patrol_unit() {
+                        defenders += 1;
+                    } else if npc.faction != faction
+                        && npc.faction != crate::constants::FACTION_NEUTRAL
+                    {
+                        enemies += 1;
+                    }
+                }
+                last_stand_verdict(enemies, defenders, ratio, was)
+            }
+            _ => false,
+        };
+        if now_retreating == was {
+            continue;
+        }
+        state.retreating[town] = now_retreating;
+        let center = world_data.towns[town].center;
+        let mut index = 0usize;
+        for (entity, job, town_id, mut activity, mut combat, tag) in npc_q.iter_mut() {
+            if town_id.0 != town as i32 || !job.is_patrol_unit() {
+                continue;
+            }
+            if now_retreating {
+                // Resting/healing/hauling defenders stay out of it
+                if activity.kind.distraction() == Distraction::None {
+                    continue;
+                }
+                let slot = rally_slot(center, index, crate::constants::LAST_STAND_SPACING);
+                index += 1;
+                *combat = CombatState::None;
+                transition_activity(
+                    &mut activity,
+                    ActivityKind::SquadAttack,
+                    ActivityPhase::Transit,
+                    ActivityTarget::SquadPoint(slot),
+                    "last_stand:rally",
+                );
+                intents.submit(entity, slot, MovementPriority::Survival, "last_stand:rally");
+                commands
+                    .entity(entity)
+                    .remove::<Reinforcing>()
+                    .insert(LastStand { town });
+            } else if tag.is_some() {
+                commands.entity(entity).remove::<LastStand>();
+                if activity.kind == ActivityKind::SquadAttack {
+                    transition_activity(
+                        &mut activity,
+                        ActivityKind::Idle,
+                        ActivityPhase::Ready,
+                        ActivityTarget::None,
+                        "last_stand:release",
+                    );
+                }
+            }
+        }
+        info!(
+            "town {town}: last stand {}",
+            if now_retreating { "ordered" } else { "lifted" }
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_keeps_reserve_home() {
+        // 10 guards, a third held back → 6 may go
+        assert_eq!(reinforce_budget(10, 0, 0.34), 6);
+        assert_eq!(reinforce_budget(10, 4, 0.34), 2);
+        assert_eq!(reinforce_budget(10, 8, 0.34), 0);
+        // A lone guard with any reserve stays put
+        assert_eq!(reinforce_budget(1, 0, 0.1), 0);
+        assert_eq!(reinforce_budget(3, 0, 0.0), 3);
+    }
+
+    #[test]
+    fn last_stand_has_hysteresis() {
+        // 2:1 trigger — 10 enemies on 5 defenders falls back, 9 doesn't
+        assert!(last_stand_verdict(10, 5, 2.0, false));
+        assert!(!last_stand_verdict(9, 5, 2.0, false));
+        // Once retreating, holds until below 0.75 × 2 = 1.5:1
+        assert!(last_stand_verdict(8, 5, 2.0, true));
+        assert!(!last_stand_verdict(7, 5, 2.0, true));
+        // Reinforcements arriving shift the ratio back
+        assert!(!last_stand_verdict(10, 8, 2.0, true));
+        // Disabled, or nobody left to rally
+        assert!(!last_stand_verdict(50, 1, 0.0, false));
+        assert!(!last_stand_verdict(50, 0, 2.0, false));
+    }
+
+    #[test]
+    fn rally_slots_fill_rings_around_center() {
+        let c = Vec2::new(100.0, 100.0);
+        assert_eq!(rally_slot(c, 0, 20.0), c);
+        for i in 1..=6 {
+            assert!((rally_slot(c, i, 20.0).distance(c) - 20.0).abs() < 1e-3);
+        }
+        for i in 7..=18 {
+            assert!((rally_slot(c, i, 20.0).distance(c) - 40.0).abs() < 1e-3);
+        }
+        assert!((rally_slot(c, 19, 20.0).distance(c) - 60.0).abs() < 1e-3);
+        // Slots in a ring are distinct
+        assert!(rally_slot(c, 1, 20.0).distance(rally_slot(c, 2, 20.0)) > 1.0);
+    }
+
+    #[test]
+    fn alerts_expire_after_quiet_period() {
+        let mut alerts = TownAlerts::default();
+        alerts.raise(2, Vec2::new(10.0, 20.0), 5.0);
+        assert!(alerts.get(0).is_none());
+        assert!(
+            alerts
+                .expire(5.0 + crate::constants::TOWN_ALERT_CLEAR_SECS)
+                .is_empty()
+        );
+        alerts.raise(2, Vec2::new(30.0, 20.0), 12.0);
+        assert_eq!(alerts.get(2).unwrap().pos, Vec2::new(30.0, 20.0));
+        assert_eq!(alerts.expire(30.0), vec![2]);
+        assert!(alerts.get(2).is_none());
+    }
+}
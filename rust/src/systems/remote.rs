@@ -9,12 +9,12 @@ use serde_json::{Value, json};
 use std::collections::BTreeMap;
 
 use crate::components::{
-    Activity, CachedStats, CarriedLoot, CombatState, Energy, Faction, GpuSlot, Health, Home, Job,
-    ManualTarget, NpcEquipment, NpcFlags, NpcWorkState, PatrolRoute, Personality, Speed, SquadId,
-    TownId,
+    Activity, CachedStats, CarriedLoot, CombatStance, CombatState, Energy, EngageZone, Faction,
+    GpuSlot, Health, Home, Job, ManualTarget, NpcEquipment, NpcFlags, NpcStats, NpcWorkState,
+    Officer, OutOfSupply, PatrolRoute, Personality, Provoked, Speed, SquadId, SquadStance,
+    SquadStanceApplied, StanceOverride, TargetPriority, TownId,
 };
-use crate::constants::building_cost;
-use crate::messages::{CombatLogMsg, GpuUpdateMsg};
+use crate::messages::{CombatLogMsg, GpuUpdate, GpuUpdateMsg};
 use crate::resources::SquadOwner;
 use crate::resources::*;
 use crate::systemparams::WorldState;
@@ -74,6 +74,14 @@ pub struct RemoteUpgrade {
     pub upgrade_idx: usize,
 }
 
+/// NPC slots queued for scripted despawn (drained into `DespawnNpcMsg`).
+#[derive(Resource, Default)]
+pub struct RemoteDespawnQueue(pub Vec<usize>);
+
+/// Scripted area damage queued by `endless/damage_area` (drained into `DamageMsg`).
+#[derive(Resource, Default)]
+pub struct RemoteDamageQueue(pub Vec<(Entity, f32)>);
+
 #[derive(Resource, Default)]
 pub struct RemoteLlmLogQueue(pub Vec<CombatLogMsg>);
 
@@ -117,6 +125,12 @@ fn brp_err(msg: impl Into<String>) -> BrpError {
     }
 }
 
+/// `parse_some` for handlers whose params are all optional: a bare call reads as `{}`, so
+/// omitting params still reads state, but malformed params are an error instead of a no-op.
+fn parse_optional<T: serde::de::DeserializeOwned>(params: Option<Value>) -> Result<T, BrpError> {
+    parse_some(Some(params.unwrap_or_else(|| json!({}))))
+}
+
 fn check_town_allowed(world: &World, town: usize) -> Result<(), BrpError> {
     let allowed = world.resource::<RemoteAllowedTowns>();
     if allowed.towns.is_empty() || allowed.towns.contains(&town) {
@@ -130,6 +144,18 @@ fn check_town_allowed(world: &World, town: usize) -> Result<(), BrpError> {
     }
 }
 
+/// `check_town_allowed` for the town owning NPC `slot`; townless or unknown NPCs pass.
+fn check_npc_allowed(world: &World, slot: usize) -> Result<(), BrpError> {
+    let town = world
+        .resource::<EntityMap>()
+        .get_npc(slot)
+        .map(|n| n.town_idx);
+    match town.and_then(|t| usize::try_from(t).ok()) {
+        Some(t) => check_town_allowed(world, t),
+        None => Ok(()),
+    }
+}
+
 pub fn parse_building_kind(s: &str) -> Option<BuildingKind> {
     match s {
         "Fountain" => Some(BuildingKind::Fountain),
@@ -150,6 +176,7 @@ pub fn parse_building_kind(s: &str) -> Option<BuildingKind> {
         "Tower" => Some(BuildingKind::Tower),
         "Merchant" => Some(BuildingKind::Merchant),
         "Casino" => Some(BuildingKind::Casino),
+        "Granary" => Some(BuildingKind::Granary),
         _ => None,
     }
 }
@@ -174,6 +201,8 @@ struct SummaryResponse {
     gold: i32,
     factions: Vec<(usize, i32, i32, i32)>,
     buildings: Vec<(String, usize, usize)>,
+    /// Guard posts (col, row) with no pinned guard and no free guard walking the route.
+    uncovered_posts: Vec<(usize, usize)>,
     squads: Vec<(usize, usize, Option<i32>, Option<i32>)>,
     upgrades: Vec<(usize, String, u8, String, String)>,
     combat_log: Vec<(i32, i32, i32, String)>,
@@ -335,6 +364,13 @@ pub fn summary_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpR
     } else {
         Vec::new()
     };
+    let uncovered_posts: Vec<(usize, usize)> =
+        crate::systems::post_coverage(world, target_town as i32)
+            .uncovered()
+            .into_iter()
+            .filter_map(|slot| entity_map.get_instance(slot))
+            .map(|inst| grid.world_to_grid(inst.position))
+            .collect();
 
     // Squads
     let squads: Vec<(usize, usize, Option<i32>, Option<i32>)> = squad_state
@@ -402,6 +438,7 @@ pub fn summary_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpR
         gold: town_gold,
         factions,
         buildings,
+        uncovered_posts,
         squads,
         upgrades,
         combat_log,
@@ -439,6 +476,26 @@ pub fn build_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpRes
         )));
     }
 
+    // Town-level availability up front so callers get the reason, not a silent no-op
+    let (food, levels) = town_build_inputs(world, p.town);
+    {
+        let world_data = world.resource::<WorldData>();
+        let entity_map = world.resource::<EntityMap>();
+        let check = crate::world::BuildCheck::new(
+            p.town,
+            &world_data.towns[p.town],
+            food,
+            &levels,
+            world.resource::<crate::world::WorldGrid>(),
+            entity_map,
+            world.resource::<crate::resources::BuildingCosts>(),
+        )
+        .with_tech_locks(town_tech_locks(world, p.town));
+        check
+            .check(kind, entity_map)
+            .map_err(|block| brp_err(format!("cannot build {}: {}", p.kind, block.reason())))?;
+    }
+
     let pos = world
         .resource::<crate::world::WorldGrid>()
         .grid_to_world(p.col, p.row);
@@ -462,6 +519,127 @@ pub fn build_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpRes
     toon_ok(json!({"status": "queued", "kind": p.kind, "town": p.town, "col": p.col, "row": p.row}))
 }
 
+// --- endless/buildable_status ----------------------------------------------
+
+#[derive(Deserialize)]
+struct BuildableStatusParams {
+    town: usize,
+}
+
+/// Food + tech levels for a town, read from its ECS entity.
+fn town_build_inputs(world: &World, town: usize) -> (i32, Vec<u8>) {
+    let town_entity = world
+        .resource::<crate::resources::TownIndex>()
+        .0
+        .get(&(town as i32))
+        .copied();
+    let food = town_entity
+        .and_then(|e| world.get::<crate::components::FoodStore>(e))
+        .map(|f| f.0)
+        .unwrap_or(0);
+    let levels = town_entity
+        .and_then(|e| world.get::<crate::components::TownUpgradeLevel>(e))
+        .map(|u| u.0.clone())
+        .unwrap_or_default();
+    (food, levels)
+}
+
+/// Kinds the town's research hasn't unlocked yet.
+fn town_tech_locks(world: &World, town: usize) -> Vec<crate::world::BuildingKind> {
+    world
+        .get_resource::<crate::systems::TechTree>()
+        .map(|t| t.locked_buildings(town))
+        .unwrap_or_default()
+}
+
+pub fn buildable_status_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: BuildableStatusParams = parse_some(params)?;
+    let world_data = world.resource::<WorldData>();
+    let town = world_data.towns.get(p.town).ok_or_else(|| {
+        brp_err(format!(
+            "town {} out of range (max {})",
+            p.town,
+            world_data.towns.len().saturating_sub(1)
+        ))
+    })?;
+    let entity_map = world.resource::<EntityMap>();
+    let costs = world.resource::<crate::resources::BuildingCosts>();
+    let (food, levels) = town_build_inputs(world, p.town);
+    let check = crate::world::BuildCheck::new(
+        p.town,
+        town,
+        food,
+        &levels,
+        world.resource::<crate::world::WorldGrid>(),
+        entity_map,
+        costs,
+    )
+    .with_tech_locks(town_tech_locks(world, p.town));
+
+    let kinds: Vec<Value> = crate::constants::BUILDING_REGISTRY
+        .iter()
+        .filter_map(|def| {
+            let status = check.check(def.kind, entity_map);
+            if status == Err(crate::world::BuildBlock::NotAvailable) {
+                return None;
+            }
+            Some(json!({
+                "kind": format!("{:?}", def.kind),
+                "cost": costs.cost(def.kind),
+                "affordable": status.is_ok(),
+                "reason": status.err().map(|b| b.reason()),
+            }))
+        })
+        .collect();
+    toon_ok(json!({
+        "town": p.town,
+        "food": food,
+        "has_slots": check.has_slots,
+        "kinds": kinds,
+    }))
+}
+
+// --- endless/build_validity --------------------------------------------------
+
+#[derive(Deserialize)]
+struct BuildValidityParams {
+    town: usize,
+    kind: String,
+    x: f32,
+    y: f32,
+}
+
+/// Why a building can or can't go at a world position: the same cell check as the build
+/// ghost and placement. Town-level gates are `endless/buildable_status`.
+pub fn build_validity_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: BuildValidityParams = parse_some(params)?;
+    let kind = parse_building_kind(&p.kind)
+        .ok_or_else(|| brp_err(format!("unknown building kind: {}", p.kind)))?;
+    let world_data = world.resource::<WorldData>();
+    if p.town >= world_data.towns.len() {
+        return Err(brp_err(format!("town {} out of range", p.town)));
+    }
+    let (food, _) = town_build_inputs(world, p.town);
+    let validity = crate::world::get_build_validity(
+        kind,
+        Vec2::new(p.x, p.y),
+        p.town,
+        world.resource::<crate::world::WorldGrid>(),
+        world_data,
+        world.resource::<EntityMap>(),
+        world.resource::<crate::resources::NoBuildZones>(),
+        food,
+        world
+            .resource::<crate::resources::BuildingCosts>()
+            .cost(kind),
+    );
+    toon_ok(json!({
+        "valid": validity.is_ok(),
+        "reason": validity.err().map(crate::world::CellBlock::code),
+        "message": validity.err().map(crate::world::CellBlock::message),
+    }))
+}
+
 // --- endless/destroy --------------------------------------------------------
 
 #[derive(Deserialize)]
@@ -562,6 +740,24 @@ struct PolicyParams {
     mining_radius: Option<f32>,
     #[serde(default)]
     loot_threshold: Option<usize>,
+    #[serde(default)]
+    auto_expand: Option<bool>,
+    #[serde(default)]
+    reinforce_enabled: Option<bool>,
+    #[serde(default)]
+    reinforce_radius: Option<f32>,
+    #[serde(default)]
+    reinforce_reserve: Option<f32>,
+    #[serde(default)]
+    last_stand_ratio: Option<f32>,
+    #[serde(default)]
+    panic_spread: Option<f32>,
+    #[serde(default)]
+    panic_threshold: Option<f32>,
+    #[serde(default)]
+    supply_radius: Option<f32>,
+    #[serde(default)]
+    auto_assign_guards: Option<bool>,
 }
 
 pub fn policy_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
@@ -647,6 +843,66 @@ pub fn policy_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpRe
             }
             policy.loot_threshold = v;
         }
+        if let Some(v) = p.auto_expand {
+            if v != policy.auto_expand {
+                parts.push(format!("auto_expand={v}"));
+            }
+            policy.auto_expand = v;
+        }
+        if let Some(v) = p.auto_assign_guards {
+            if v != policy.auto_assign_guards {
+                parts.push(format!("auto_assign_guards={v}"));
+            }
+            policy.auto_assign_guards = v;
+        }
+        if let Some(v) = p.reinforce_enabled {
+            if v != policy.reinforce_enabled {
+                parts.push(format!("reinforce_enabled={v}"));
+            }
+            policy.reinforce_enabled = v;
+        }
+        if let Some(v) = p.reinforce_radius {
+            let v = v.clamp(0.0, 5000.0);
+            if (v - policy.reinforce_radius).abs() > f32::EPSILON {
+                parts.push(format!("reinforce_radius={v:.0}"));
+            }
+            policy.reinforce_radius = v;
+        }
+        if let Some(v) = p.reinforce_reserve {
+            let v = v.clamp(0.0, 1.0);
+            if (v - policy.reinforce_reserve).abs() > f32::EPSILON {
+                parts.push(format!("reinforce_reserve={v:.2}"));
+            }
+            policy.reinforce_reserve = v;
+        }
+        if let Some(v) = p.last_stand_ratio {
+            let v = v.clamp(0.0, 10.0);
+            if (v - policy.last_stand_ratio).abs() > f32::EPSILON {
+                parts.push(format!("last_stand_ratio={v:.2}"));
+            }
+            policy.last_stand_ratio = v;
+        }
+        if let Some(v) = p.panic_spread {
+            let v = v.clamp(0.0, 5.0);
+            if (v - policy.panic_spread).abs() > f32::EPSILON {
+                parts.push(format!("panic_spread={v:.2}"));
+            }
+            policy.panic_spread = v;
+        }
+        if let Some(v) = p.panic_threshold {
+            let v = v.clamp(0.1, 10.0);
+            if (v - policy.panic_threshold).abs() > f32::EPSILON {
+                parts.push(format!("panic_threshold={v:.2}"));
+            }
+            policy.panic_threshold = v;
+        }
+        if let Some(v) = p.supply_radius {
+            let v = v.clamp(0.0, 5000.0);
+            if (v - policy.supply_radius).abs() > f32::EPSILON {
+                parts.push(format!("supply_radius={v:.0}"));
+            }
+            policy.supply_radius = v;
+        }
         parts
     };
     if !parts.is_empty() {
@@ -684,6 +940,52 @@ pub fn time_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResu
     }))
 }
 
+// --- endless/time_config ----------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct TimeConfigParams {
+    #[serde(default)]
+    day_length: Option<f32>,
+    #[serde(default)]
+    dawn: Option<i32>,
+    #[serde(default)]
+    dusk: Option<i32>,
+}
+
+/// Get/set day length (real seconds per game day at 1x) and the daylight window.
+/// Independent of time_scale; changing day length keeps the clock where it is.
+pub fn time_config_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: TimeConfigParams = parse_optional(params)?;
+
+    let mut gt = world.resource_mut::<GameTime>();
+    if let Some(secs) = p.day_length {
+        if secs.is_nan() || secs <= 0.0 {
+            return Err(brp_err("day_length must be > 0"));
+        }
+        gt.set_day_length(secs);
+    }
+    if p.dawn.is_some() || p.dusk.is_some() {
+        let dawn = p.dawn.unwrap_or(gt.dawn_hour);
+        let dusk = p.dusk.unwrap_or(gt.dusk_hour);
+        if !(0..24).contains(&dawn) || !(1..=24).contains(&dusk) || dawn >= dusk {
+            return Err(brp_err("need 0 <= dawn < dusk <= 24"));
+        }
+        gt.set_daylight(dawn, dusk);
+    }
+
+    toon_ok(json!({
+        "day_length": r2(gt.day_length()),
+        "seconds_per_hour": r2(gt.seconds_per_hour),
+        "game_hours_per_real_second": r2(1.0 / gt.seconds_per_hour),
+        "dawn": gt.dawn_hour,
+        "dusk": gt.dusk_hour,
+        "day": gt.day(),
+        "hour": gt.hour(),
+        "minute": gt.minute(),
+        "daytime": gt.is_daytime(),
+    }))
+}
+
 // --- endless/squad_target ---------------------------------------------------
 
 #[derive(Deserialize)]
@@ -711,6 +1013,14 @@ pub fn squad_target_handler(In(params): In<Option<Value>>, world: &mut World) ->
         check_town_allowed(world, town)?;
     }
 
+    let bounds = *world.resource::<crate::resources::WorldBounds>();
+    if bounds.is_set() && !bounds.contains(Vec2::new(p.x, p.y)) {
+        return Err(brp_err(format!(
+            "target ({:.0},{:.0}) outside world bounds ({:.0},{:.0})-({:.0},{:.0})",
+            p.x, p.y, bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y
+        )));
+    }
+
     queue_llm_log(
         world,
         town,
@@ -728,6 +1038,243 @@ pub fn squad_target_handler(In(params): In<Option<Value>>, world: &mut World) ->
     toon_ok(json!({"status": "ok", "squad": p.squad, "target_x": p.x, "target_y": p.y}))
 }
 
+// --- endless/squad_rally ----------------------------------------------------
+
+#[derive(Deserialize)]
+struct SquadRallyParams {
+    squad: usize,
+    rally_x: f32,
+    rally_y: f32,
+    x: f32,
+    y: f32,
+}
+
+pub fn squad_rally_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: SquadRallyParams = parse_some(params)?;
+
+    let town = {
+        let state = world.resource::<SquadState>();
+        let squad = state
+            .squads
+            .get(p.squad)
+            .ok_or_else(|| brp_err(format!("squad {} out of range", p.squad)))?;
+        match squad.owner {
+            SquadOwner::Player => 0,
+            SquadOwner::Town(tdi) => tdi,
+        }
+    };
+    check_town_allowed(world, town)?;
+
+    let rally = Vec2::new(p.rally_x, p.rally_y);
+    let attack = Vec2::new(p.x, p.y);
+    let bounds = *world.resource::<crate::resources::WorldBounds>();
+    if bounds.is_set()
+        && let Some(out) = [rally, attack].into_iter().find(|v| !bounds.contains(*v))
+    {
+        return Err(brp_err(format!(
+            "point ({:.0},{:.0}) outside world bounds ({:.0},{:.0})-({:.0},{:.0})",
+            out.x, out.y, bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y
+        )));
+    }
+
+    queue_llm_log(
+        world,
+        town,
+        format!(
+            "squad {} rally ({:.0},{:.0}) then attack ({:.0},{:.0})",
+            p.squad, p.rally_x, p.rally_y, p.x, p.y
+        ),
+        Some(attack),
+    );
+
+    let now = world.resource::<GameTime>().total_seconds;
+    world
+        .resource_mut::<SquadState>()
+        .rally_then_attack(p.squad, rally, attack, now);
+
+    toon_ok(json!({
+        "status": "ok",
+        "squad": p.squad,
+        "rally_x": p.rally_x,
+        "rally_y": p.rally_y,
+        "target_x": p.x,
+        "target_y": p.y,
+    }))
+}
+
+// --- endless/faction_mvp ----------------------------------------------------
+
+#[derive(Deserialize)]
+struct FactionMvpParams {
+    faction: i32,
+}
+
+pub fn faction_mvp_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: FactionMvpParams = parse_some(params)?;
+    let mvp = world.resource::<FactionMvp>().mvp.get(&p.faction).copied();
+    let top = mvp.and_then(|m| {
+        let npc = world.resource::<EntityMap>().get_npc(m.slot)?;
+        let stats = world.get::<NpcStats>(npc.entity)?;
+        Some(json!({
+            "slot": m.slot,
+            "name": stats.name,
+            "kills": m.kills,
+            "level": crate::systems::stats::level_from_xp(stats.xp),
+        }))
+    });
+    let feed: Vec<Value> = world
+        .resource::<FactionMvp>()
+        .feeds
+        .get(&p.faction)
+        .map(|feed| {
+            feed.iter()
+                .map(|e| {
+                    json!({
+                        "killer_slot": e.killer_slot,
+                        "killer": e.killer_name,
+                        "victim": e.victim_name,
+                        "victim_faction": e.victim_faction,
+                        "day": e.day,
+                        "hour": e.hour,
+                        "minute": e.minute,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    toon_ok(json!({
+        "faction": p.faction,
+        "mvp": top,
+        "feed": feed,
+    }))
+}
+
+// --- endless/world_hash -----------------------------------------------------
+
+pub fn world_hash_handler(In(_params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let hash = crate::save::world_state_hash(world);
+    let game_time = world.resource::<GameTime>();
+    toon_ok(json!({
+        "hash": format!("{hash:016x}"),
+        "day": game_time.day(),
+        "hour": game_time.hour(),
+        "minute": game_time.minute(),
+        "total_seconds": r2(game_time.total_seconds),
+    }))
+}
+
+// --- endless/no_build_zone / clear_no_build_zones ----------------------------
+
+#[derive(Deserialize)]
+struct NoBuildZoneParams {
+    /// Bottom-left corner in world coordinates.
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+fn no_build_zones_json(zones: &NoBuildZones) -> Vec<Value> {
+    zones
+        .0
+        .iter()
+        .map(|r| json!({"x": r2(r.min.x), "y": r2(r.min.y), "w": r2(r.width()), "h": r2(r.height())}))
+        .collect()
+}
+
+/// Forbid new building inside a world-space rectangle. Buildings already inside stay.
+pub fn no_build_zone_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: NoBuildZoneParams = parse_some(params)?;
+    if ![p.x, p.y, p.w, p.h].iter().all(|v| v.is_finite()) || p.w <= 0.0 || p.h <= 0.0 {
+        return Err(brp_err("x/y must be finite and w/h positive"));
+    }
+    let rect = Rect::new(p.x, p.y, p.x + p.w, p.y + p.h);
+    let mut zones = world.resource_mut::<NoBuildZones>();
+    zones.add_no_build_zone(rect);
+    toon_ok(json!({"status": "ok", "zones": no_build_zones_json(&zones)}))
+}
+
+pub fn clear_no_build_zones_handler(
+    In(_params): In<Option<Value>>,
+    world: &mut World,
+) -> BrpResult {
+    let mut zones = world.resource_mut::<NoBuildZones>();
+    let cleared = zones.0.len();
+    zones.clear_no_build_zones();
+    toon_ok(json!({"status": "ok", "cleared": cleared}))
+}
+
+// --- endless/npc_logging -----------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct NpcLoggingParams {
+    enabled: Option<bool>,
+    path: Option<String>,
+    #[serde(default)]
+    all: bool,
+}
+
+/// Turn on-disk NPC logging on/off (`enabled`), or just report its state when omitted.
+/// Evicted ring entries are appended by default; `all` appends every entry for every NPC.
+pub fn npc_logging_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    use crate::systems::npc_log_file::NpcLogFile;
+    let p: NpcLoggingParams = match params {
+        Some(v) => serde_json::from_value(v).map_err(|e| brp_err(e.to_string()))?,
+        None => NpcLoggingParams::default(),
+    };
+    let mut file = world.resource_mut::<NpcLogFile>();
+    if let Some(enabled) = p.enabled {
+        file.set_npc_logging(enabled, p.path.map(std::path::PathBuf::from), p.all)
+            .map_err(brp_err)?;
+    }
+    toon_ok(json!({
+        "enabled": file.enabled(),
+        "capture": format!("{:?}", file.capture),
+        "path": file.path.as_ref().map(|p| p.display().to_string()),
+        "lines_written": file.lines_written,
+        "last_error": file.last_error,
+    }))
+}
+
+// --- endless/grid_config -----------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct GridConfigParams {
+    width: Option<u32>,
+    height: Option<u32>,
+    cell_size: Option<f32>,
+    max_per_cell: Option<u32>,
+}
+
+/// Read or reshape the GPU spatial grid. Omitted fields keep their current value; the grid
+/// buffers are reallocated on the next frame.
+pub fn grid_config_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: GridConfigParams = parse_optional(params)?;
+    let current = *world.resource::<crate::gpu::GridConfig>();
+    let mut warning = None;
+    if p.width.is_some() || p.height.is_some() || p.cell_size.is_some() || p.max_per_cell.is_some()
+    {
+        warning = crate::gpu::set_grid_config(
+            world,
+            p.width.unwrap_or(current.width),
+            p.height.unwrap_or(current.height),
+            p.cell_size.unwrap_or(current.cell_size),
+            p.max_per_cell.unwrap_or(current.max_per_cell),
+        )
+        .map_err(brp_err)?;
+    }
+    let grid = *world.resource::<crate::gpu::GridConfig>();
+    let extent = grid.extent();
+    toon_ok(json!({
+        "width": grid.width,
+        "height": grid.height,
+        "cell_size": r2(grid.cell_size),
+        "max_per_cell": grid.max_per_cell,
+        "extent": [r2(extent.x), r2(extent.y)],
+        "warning": warning,
+    }))
+}
+
 // --- endless/ai_manager -----------------------------------------------------
 
 #[derive(Deserialize)]
@@ -827,43 +1374,209 @@ pub fn ai_manager_handler(In(params): In<Option<Value>>, world: &mut World) -> B
     }))
 }
 
-// --- endless/chat ------------------------------------------------------------
+// --- endless/faction_stance --------------------------------------------------
 
 #[derive(Deserialize)]
-struct ChatParams {
-    town: usize,
-    to: usize,
-    message: String,
+struct FactionStanceParams {
+    faction: i32,
+    /// "Passive" | "Defensive" | "Aggressive" | "None" (clear). Omit to query.
+    #[serde(default)]
+    stance: Option<String>,
 }
 
-pub fn chat_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
-    let p: ChatParams = parse_some(params)?;
-    check_town_allowed(world, p.town)?;
+fn parse_faction_stance(s: &str) -> Option<Option<FactionStance>> {
+    match s {
+        "Passive" => Some(Some(FactionStance::Passive)),
+        "Defensive" => Some(Some(FactionStance::Defensive)),
+        "Aggressive" => Some(Some(FactionStance::Aggressive)),
+        "None" => Some(None),
+        _ => None,
+    }
+}
 
-    let gt = world.resource::<GameTime>();
-    let (day, hour, minute) = (gt.day(), gt.hour(), gt.minute());
+pub fn faction_stance_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: FactionStanceParams = parse_some(params)?;
+    let towns = {
+        let faction_list = world.resource::<FactionList>();
+        if faction_list.is_neutral(p.faction) {
+            return Err(brp_err("neutral faction has no stance"));
+        }
+        faction_list
+            .factions
+            .get(p.faction as usize)
+            .map(|f| f.towns.clone())
+            .ok_or_else(|| brp_err(format!("faction {} out of range", p.faction)))?
+    };
 
-    let from_name = world
-        .resource::<WorldData>()
-        .towns
-        .get(p.town)
-        .map(|t| t.name.clone())
-        .unwrap_or_default();
-    queue_llm_log(
-        world,
-        p.town,
-        format!("[chat to F{}] {}", p.to, p.message),
-        None,
-    );
+    if let Some(ref s) = p.stance {
+        let stance =
+            parse_faction_stance(s).ok_or_else(|| brp_err(format!("unknown stance: {s}")))?;
+        for &t in &towns {
+            check_town_allowed(world, t)?;
+        }
+        if let Some(&t) = towns.first() {
+            queue_llm_log(world, t, format!("faction_stance: {s}"), None);
+        }
+        // Read by AI decision/commander ticks and decision_system leash; an in-progress
+        // wave keeps going.
+        world.resource_mut::<FactionList>().factions[p.faction as usize].stance = stance;
+    }
 
-    world.resource_mut::<ChatInbox>().push(ChatMessage {
-        from_town: p.town,
-        to_town: p.to,
-        text: p.message.clone(),
-        day,
-        hour,
-        minute,
-        sent_to_llm: false,
+    let stance = world.resource::<FactionList>().stance(p.faction);
+    toon_ok(json!({
+        "status": "ok",
+        "faction": p.faction,
+        "stance": stance.map_or("None".to_string(), |s| format!("{s:?}")),
+        "towns": towns,
+    }))
+}
+
+// --- endless/tribute / set_tribute -------------------------------------------
+
+fn tribute_json(t: &Tribute) -> Value {
+    json!({
+        "from_town": t.from_town,
+        "to_town": t.to_town,
+        "fraction": r2(t.fraction),
+        "interval_days": t.interval_days,
+        "hours_until_due": (t.interval_days.max(1) * 24).saturating_sub(t.hours),
+        "accrued_food": t.accrued_food,
+        "accrued_gold": t.accrued_gold,
+        "paid_food": t.paid_food,
+        "paid_gold": t.paid_gold,
+    })
+}
+
+#[derive(Deserialize)]
+struct TributeParams {
+    town: usize,
+}
+
+pub fn tribute_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: TributeParams = parse_some(params)?;
+    if p.town >= world.resource::<WorldData>().towns.len() {
+        return Err(brp_err(format!("town {} out of range", p.town)));
+    }
+    let state = world.resource::<TributeState>();
+    toon_ok(json!({
+        "town": p.town,
+        "pays": state.paid_by(p.town).map(tribute_json),
+        "receives": state.received_by(p.town).map(tribute_json).collect::<Vec<_>>(),
+    }))
+}
+
+#[derive(Deserialize)]
+struct SetTributeParams {
+    /// Paying (subject) town.
+    from_town: usize,
+    /// Receiving town. Required when starting a new tribute.
+    to_town: Option<usize>,
+    fraction: Option<f32>,
+    interval_days: Option<u32>,
+    /// End the tribute `from_town` pays.
+    #[serde(default)]
+    clear: bool,
+}
+
+pub fn set_tribute_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: SetTributeParams = parse_some(params)?;
+    let existing = world
+        .resource::<TributeState>()
+        .paid_by(p.from_town)
+        .cloned();
+    // The receiving town sets the terms; a clear is gated by whoever currently receives
+    if let Some(to) = p.to_town.or(existing.as_ref().map(|t| t.to_town)) {
+        check_town_allowed(world, to)?;
+    }
+    if p.clear {
+        let cleared = world.resource_mut::<TributeState>().clear(p.from_town);
+        return toon_ok(json!({"status": "ok", "cleared": cleared}));
+    }
+
+    let mut tribute = match (existing, p.to_town) {
+        (Some(t), None) => t,
+        (Some(t), Some(to)) if t.to_town == to => t,
+        (_, Some(to)) => {
+            let towns = &world.resource::<WorldData>().towns;
+            let faction = |i: usize| {
+                towns
+                    .get(i)
+                    .map(|t| t.faction)
+                    .ok_or_else(|| brp_err(format!("town {i} out of range")))
+            };
+            let (from_faction, to_faction) = (faction(p.from_town)?, faction(to)?);
+            if from_faction == to_faction {
+                return Err(brp_err(
+                    "towns of the same faction can't pay each other tribute",
+                ));
+            }
+            Tribute::new(p.from_town, to, from_faction, to_faction)
+        }
+        (None, None) => {
+            return Err(brp_err(format!(
+                "town {} pays no tribute (to_town required)",
+                p.from_town
+            )));
+        }
+    };
+    if let Some(f) = p.fraction {
+        tribute.fraction = f.clamp(0.0, 1.0);
+    }
+    if let Some(d) = p.interval_days {
+        tribute.interval_days = d.max(1);
+    }
+    let data = tribute_json(&tribute);
+    queue_llm_log(
+        world,
+        p.from_town,
+        format!(
+            "tribute to town {}: {:.0}%",
+            tribute.to_town,
+            tribute.fraction * 100.0
+        ),
+        None,
+    );
+    world.resource_mut::<TributeState>().set(tribute);
+    toon_ok(json!({"status": "ok", "tribute": data}))
+}
+
+// --- endless/chat ------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct ChatParams {
+    town: usize,
+    to: usize,
+    message: String,
+}
+
+pub fn chat_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: ChatParams = parse_some(params)?;
+    check_town_allowed(world, p.town)?;
+
+    let gt = world.resource::<GameTime>();
+    let (day, hour, minute) = (gt.day(), gt.hour(), gt.minute());
+
+    let from_name = world
+        .resource::<WorldData>()
+        .towns
+        .get(p.town)
+        .map(|t| t.name.clone())
+        .unwrap_or_default();
+    queue_llm_log(
+        world,
+        p.town,
+        format!("[chat to F{}] {}", p.to, p.message),
+        None,
+    );
+
+    world.resource_mut::<ChatInbox>().push(ChatMessage {
+        from_town: p.town,
+        to_town: p.to,
+        text: p.message.clone(),
+        day,
+        hour,
+        minute,
+        sent_to_llm: false,
         has_reply: false,
     });
 
@@ -963,6 +1676,7 @@ fn debug_npc(world: &mut World, target_entity: Entity, slot: usize) -> BrpResult
         ),
     )>();
 
+    let energy_thresholds = world.resource::<crate::resources::EnergyThresholds>();
     let mut npc_data: Option<Value> = None;
     for (
         entity,
@@ -1038,6 +1752,7 @@ fn debug_npc(world: &mut World, target_entity: Entity, slot: usize) -> BrpResult
 
         // Personality traits
         let trait_str = personality.trait_summary();
+        let energy_t = energy_thresholds.get(*job);
 
         npc_data = Some(json!({
             "entity": target_entity.to_bits(),
@@ -1052,6 +1767,8 @@ fn debug_npc(world: &mut World, target_entity: Entity, slot: usize) -> BrpResult
             "hp": health.0,
             "max_hp": stats.max_health,
             "energy": ((energy.0 as f64 * 10.0).round() / 10.0),
+            "rest_below": r2(energy_t.rest_below),
+            "resume_above": r2(energy_t.resume_above),
             "speed": stats.speed,
             "home_x": home.0.x as i32,
             "home_y": home.0.y as i32,
@@ -1126,8 +1843,10 @@ fn debug_npc(world: &mut World, target_entity: Entity, slot: usize) -> BrpResult
             data["policy_farmer_flee"] = json!(r2(p.farmer_flee_hp));
             data["policy_heal_prio"] = json!(p.prioritize_healing);
             data["policy_recovery"] = json!(r2(p.recovery_hp));
+            data["policy_supply_radius"] = json!(r2(p.supply_radius));
         }
     }
+    data["supplied"] = json!(world.get::<OutOfSupply>(target_entity).is_none());
 
     // Readback: actual rendered position, movement target, combat target
     let i2 = slot * 2;
@@ -1191,6 +1910,28 @@ fn debug_npc(world: &mut World, target_entity: Entity, slot: usize) -> BrpResult
         }
     }
 
+    // Target priority: unit override, else squad profile
+    let squad_priority = data["squad_id"]
+        .as_i64()
+        .and_then(|sq| squad_state.squads.get(sq as usize))
+        .map(|s| s.target_priority)
+        .unwrap_or_default();
+    let priority = world
+        .get::<TargetPriority>(target_entity)
+        .copied()
+        .unwrap_or(squad_priority);
+    data["target_priority"] = json!(priority.label());
+    data["combat_stance"] = json!(
+        world
+            .get::<CombatStance>(target_entity)
+            .copied()
+            .unwrap_or_default()
+            .label()
+    );
+    if let Some(p) = world.get::<Provoked>(target_entity) {
+        data["provoked_secs"] = json!(r2(p.0));
+    }
+
     // Squad detail
     if let Some(sq_val) = data["squad_id"].as_i64() {
         let sq = sq_val as usize;
@@ -1200,6 +1941,8 @@ fn debug_npc(world: &mut World, target_entity: Entity, slot: usize) -> BrpResult
             data["squad_target_x"] = json!(s.target.map(|v| v.x as i32));
             data["squad_target_y"] = json!(s.target.map(|v| v.y as i32));
             data["squad_hold_fire"] = json!(s.hold_fire);
+            data["squad_target_priority"] = json!(s.target_priority.label());
+            data["squad_stance"] = json!(s.stance.label());
             data["squad_patrol"] = json!(s.patrol_enabled);
             data["squad_rest"] = json!(s.rest_when_tired);
         }
@@ -1258,6 +2001,7 @@ fn debug_npc(world: &mut World, target_entity: Entity, slot: usize) -> BrpResult
         data["visual_row"] = json!(visual_upload.visual_data[vb + 1]);
         data["visual_atlas"] = json!(visual_upload.visual_data[vb + 2]);
         data["visual_flash"] = json!(visual_upload.visual_data[vb + 3]);
+        data["visual_highlight"] = json!(visual_upload.visual_data[vb + 7]);
     }
 
     // Active projectiles fired by this NPC
@@ -1366,6 +2110,10 @@ fn debug_building(world: &mut World, _entity: Entity, slot: usize) -> BrpResult
         "world_y": inst.position.y as i32,
         "grid_col": col,
         "grid_row": row,
+        "footprint": [def.footprint.0, def.footprint.1],
+        "cells": crate::world::footprint_cells(inst.position, def.footprint, grid.cell_size)
+            .map(|(c, r)| [c, r])
+            .collect::<Vec<_>>(),
         "hp": hp,
         "max_hp": def.hp,
         "town_idx": inst.town_idx,
@@ -1506,6 +2254,9 @@ fn debug_squad(world: &mut World, idx: usize) -> BrpResult {
         "wave_retreat_below_pct": squad.wave_retreat_below_pct,
         "owner": format!("{:?}", squad.owner),
         "hold_fire": squad.hold_fire,
+        "target_priority": squad.target_priority.label(),
+        "stance": squad.stance.label(),
+        "engage_radius": r2(squad.engage_radius),
         "day": day, "hour": hour, "minute": minute,
     });
     toon_ok(data)
@@ -1529,6 +2280,13 @@ fn debug_town(world: &mut World, idx: usize) -> BrpResult {
         .and_then(|e| world.get::<crate::components::TownAreaLevel>(e))
         .map(|a| a.0)
         .unwrap_or(0);
+    let (build_used, build_slots) = crate::world::town_build_utilization(
+        world.resource::<crate::world::WorldGrid>(),
+        world.resource::<EntityMap>(),
+        town,
+        idx,
+        area_level,
+    );
     let food = town_entity
         .and_then(|e| world.get::<crate::components::FoodStore>(e))
         .map(|f| f.0)
@@ -1613,6 +2371,8 @@ fn debug_town(world: &mut World, idx: usize) -> BrpResult {
         "center_x": center[0] as i32,
         "center_y": center[1] as i32,
         "area_level": area_level,
+        "build_used": build_used,
+        "build_slots": build_slots,
         "food": food,
         "gold": gold,
         "npcs": npcs_str,
@@ -1668,6 +2428,14 @@ fn debug_policy(world: &mut World, idx: usize) -> BrpResult {
         "farmer_fight_back": p.farmer_fight_back,
         "prioritize_healing": p.prioritize_healing,
         "recovery_hp": r2(p.recovery_hp),
+        "auto_expand": p.auto_expand,
+        "auto_assign_guards": p.auto_assign_guards,
+        "reinforce_enabled": p.reinforce_enabled,
+        "reinforce_radius": r2(p.reinforce_radius),
+        "reinforce_reserve": r2(p.reinforce_reserve),
+        "last_stand_ratio": r2(p.last_stand_ratio),
+        "panic_spread": r2(p.panic_spread),
+        "panic_threshold": r2(p.panic_threshold),
         "day": game_time.day(), "hour": game_time.hour(), "minute": game_time.minute(),
     });
     toon_ok(data)
@@ -1680,7 +2448,7 @@ pub fn perf_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult
     let ups = world.resource::<crate::resources::UpsCounter>();
     let faction_stats = world.resource::<FactionStats>();
 
-    let frame_ms = timings.frame_ms.lock().map(|v| *v).unwrap_or(0.0);
+    let frame_ms = timings.get_frame_ms();
     let fps = if frame_ms > 0.0 {
         1000.0 / frame_ms
     } else {
@@ -1697,6 +2465,14 @@ pub fn perf_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult
         "entity_count": entity_count,
     });
 
+    let sync = world.resource::<crate::resources::PositionSync>();
+    response["position_sync"] = json!({
+        "threshold": r2(sync.threshold),
+        "synced": sync.synced,
+        "skipped": sync.skipped,
+    });
+    response["separation"] = separation_json(world);
+
     // Include per-system timings if profiling is enabled
     if timings.enabled {
         let system_timings = timings.get_timings();
@@ -1714,135 +2490,3980 @@ pub fn perf_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult
     toon_ok(response)
 }
 
-// ============================================================================
-// DRAIN SYSTEM
-// ============================================================================
+// --- endless/npc_counts ------------------------------------------------------
 
-pub fn drain_remote_queues(
-    mut build_q: ResMut<RemoteBuildQueue>,
-    mut destroy_q: ResMut<RemoteDestroyQueue>,
-    mut upgrade_q: ResMut<RemoteUpgradeQueue>,
-    mut llm_log_q: ResMut<RemoteLlmLogQueue>,
-    mut log_ring: ResMut<RemoteCombatLogRing>,
-    mut world_state: WorldState,
-    mut town_access: crate::systemparams::TownAccess,
-    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
-    mut combat_log: MessageWriter<crate::messages::CombatLogMsg>,
-    mut damage_writer: MessageWriter<crate::messages::DamageMsg>,
-    mut commands: Commands,
-    mut upgrade_writer: MessageWriter<crate::systems::stats::UpgradeMsg>,
-    game_time: Res<GameTime>,
-) {
-    // Drain build queue
-    let builds: Vec<RemoteBuild> = build_q.0.drain(..).collect();
-    for build in builds {
-        if build.town >= world_state.world_data.towns.len() {
-            continue;
-        }
-        let pos = world_state.grid.grid_to_world(build.col, build.row);
-        let cost = building_cost(build.kind);
+pub fn npc_counts_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult {
+    let counts = world
+        .resource::<EntityMap>()
+        .get_npc_counts(world.resource::<GpuSlotPool>());
+    toon_ok(json!({
+        "allocated": counts.allocated,
+        "alive": counts.alive,
+        "dead": counts.dead,
+        "hidden": counts.hidden,
+        "buildings": counts.buildings,
+        "free_slots": counts.free_slots,
+    }))
+}
 
-        let mut food_val = town_access.food(build.town as i32);
-        let _ = world_state.place_building(
-            &mut food_val,
-            build.kind,
-            build.town,
-            pos,
-            cost,
-            &mut gpu_updates,
-            &mut commands,
-        );
-        if let Some(mut f) = town_access.food_mut(build.town as i32) {
-            f.0 = food_val;
-        }
+// --- endless/perf_history ----------------------------------------------------
+
+/// Rolling per-frame timings for graphing. Parallel arrays (oldest first) plus
+/// min/max/mean per series. Empty unless the profiler (`debug_profiler`) is on.
+pub fn perf_history_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult {
+    use crate::resources::PerfStats;
+    let timings = world.resource::<crate::resources::SystemTimings>();
+    let snap = timings.get_perf_history();
+    let round = |v: &[f32]| v.iter().map(|&x| r2(x)).collect::<Vec<f64>>();
+    let stats = |v: &[f32]| {
+        let s = PerfStats::of(v);
+        json!({ "min": r2(s.min), "max": r2(s.max), "mean": r2(s.mean) })
+    };
+    toon_ok(json!({
+        "enabled": timings.enabled,
+        "capacity": crate::resources::PERF_HISTORY_LEN,
+        "frames": snap.frame_ms.len(),
+        "frame_ms": round(&snap.frame_ms),
+        "game_ms": round(&snap.game_ms),
+        "engine_ms": round(&snap.engine_ms),
+        "render_ms": round(&snap.render_ms),
+        "stats": {
+            "frame_ms": stats(&snap.frame_ms),
+            "game_ms": stats(&snap.game_ms),
+            "engine_ms": stats(&snap.engine_ms),
+            "render_ms": stats(&snap.render_ms),
+        },
+    }))
+}
+
+// --- endless/trample ---------------------------------------------------------
+
+#[derive(Deserialize)]
+struct TrampleParams {
+    damage: Option<f32>,
+}
+
+pub fn trample_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: TrampleParams = parse_some(params)?;
+
+    let max_per_cell = world.resource::<crate::gpu::GridConfig>().max_per_cell;
+    let mut config = world.resource_mut::<crate::systems::stats::CombatConfig>();
+    if let Some(v) = p.damage {
+        config.trample_damage = v.max(0.0);
     }
 
-    // Drain destroy queue
-    let destroys: Vec<RemoteDestroy> = destroy_q.0.drain(..).collect();
-    for destroy in destroys {
-        let Some(town) = world_state.world_data.towns.get(destroy.town) else {
-            continue;
-        };
-        let town_name = town.name.clone();
+    toon_ok(json!({
+        "status": "ok",
+        "trample_damage": r2(config.trample_damage),
+        "interval_secs": crate::constants::TRAMPLE_INTERVAL_SECS,
+        "max_per_cell": max_per_cell,
+    }))
+}
 
-        // Look up building at grid cell
-        let Some(inst) = world_state
-            .entity_map
-            .get_at_grid(destroy.col as i32, destroy.row as i32)
-        else {
-            continue;
-        };
-        // Validate: not Fountain/GoldMine, belongs to requesting town
-        if matches!(inst.kind, BuildingKind::Fountain | BuildingKind::GoldMine) {
-            continue;
+// --- endless/attack_windup ---------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct AttackWindupParams {
+    secs: Option<f32>,
+    redirect: Option<bool>,
+}
+
+/// Read or set the base attack windup (0 = instant attacks) and mid-windup redirect.
+/// A new `secs` also applies to living NPCs, not just future spawns.
+pub fn attack_windup_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: AttackWindupParams = parse_optional(params)?;
+    if let Some(v) = p.secs {
+        if !v.is_finite() || v < 0.0 {
+            return Err(brp_err("secs must be a finite number >= 0"));
         }
-        if inst.town_idx as usize != destroy.town {
-            continue;
+    }
+    let mut config = world.resource_mut::<crate::systems::stats::CombatConfig>();
+    if let Some(v) = p.redirect {
+        config.windup_redirect = v;
+    }
+    if let Some(v) = p.secs {
+        config.attack_windup = v;
+        let npcs: Vec<Entity> = world
+            .query_filtered::<Entity, (
+                With<GpuSlot>,
+                With<Job>,
+                Without<crate::components::Building>,
+            )>()
+            .iter(world)
+            .collect();
+        for entity in npcs {
+            let mut ecmds = world.entity_mut(entity);
+            if v > 0.0 {
+                ecmds.insert(crate::components::AttackWindup(v));
+            } else {
+                ecmds.remove::<crate::components::AttackWindup>();
+            }
         }
-        let bld_kind = inst.kind;
-        let slot = inst.slot;
+    }
+    let config = world.resource::<crate::systems::stats::CombatConfig>();
+    toon_ok(json!({
+        "secs": r2(config.attack_windup),
+        "redirect": config.windup_redirect,
+    }))
+}
 
-        // Send lethal damage so death_system handles entity despawn
-        let Some(&target_entity) = world_state.entity_map.entities.get(&slot) else {
-            continue;
-        };
-        damage_writer.write(crate::messages::DamageMsg {
-            target: target_entity,
-            amount: f32::MAX,
-            attacker: -1,
-            attacker_faction: 0,
-        });
+// --- endless/combat_rng ------------------------------------------------------
 
-        let _ = world_state.destroy_building(
-            &mut combat_log,
-            &game_time,
-            destroy.col,
-            destroy.row,
-            &format!("Destroyed building in {}", town_name),
-            &mut gpu_updates,
-        );
-        world_state.dirty_writers.mark_building_changed(bld_kind);
+#[derive(Deserialize)]
+struct CombatRngParams {
+    seed: Option<u64>,
+    miss_chance: Option<f32>,
+    crit_chance: Option<f32>,
+    crit_mult: Option<f32>,
+    damage_spread: Option<f32>,
+    sharpshot_crit: Option<f32>,
+    swift_dodge: Option<f32>,
+}
+
+pub fn combat_rng_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: CombatRngParams = parse_some(params)?;
+
+    let mut rng = world.resource_mut::<CombatRng>();
+    if let Some(v) = p.seed {
+        rng.seed = v;
+        rng.counter = 0;
+    }
+    if let Some(v) = p.miss_chance {
+        rng.miss_chance = v.clamp(0.0, 1.0);
+    }
+    if let Some(v) = p.crit_chance {
+        rng.crit_chance = v.clamp(0.0, 1.0);
+    }
+    if let Some(v) = p.crit_mult {
+        rng.crit_mult = v.max(1.0);
+    }
+    if let Some(v) = p.damage_spread {
+        rng.damage_spread = v.clamp(0.0, 1.0);
+    }
+    if let Some(v) = p.sharpshot_crit {
+        rng.sharpshot_crit = v.max(0.0);
+    }
+    if let Some(v) = p.swift_dodge {
+        rng.swift_dodge = v.max(0.0);
     }
 
-    // Drain upgrade queue
-    for upgrade in upgrade_q.0.drain(..) {
-        upgrade_writer.write(crate::systems::stats::UpgradeMsg {
-            town_idx: upgrade.town,
-            upgrade_idx: upgrade.upgrade_idx,
-        });
+    toon_ok(json!({
+        "status": "ok",
+        "enabled": rng.enabled(),
+        "seed": rng.seed,
+        "miss_chance": r2(rng.miss_chance),
+        "crit_chance": r2(rng.crit_chance),
+        "crit_mult": r2(rng.crit_mult),
+        "damage_spread": r2(rng.damage_spread),
+        "sharpshot_crit": r2(rng.sharpshot_crit),
+        "swift_dodge": r2(rng.swift_dodge),
+        "rolls": rng.counter,
+    }))
+}
+
+// --- endless/aggro_memory ----------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct AggroMemoryParams {
+    secs: Option<f32>,
+}
+
+/// Read or set how long units keep chasing an enemy that slipped out of range (0 = off).
+pub fn aggro_memory_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: AggroMemoryParams = parse_optional(params)?;
+    let mut config = world.resource_mut::<crate::resources::AggroMemoryConfig>();
+    if let Some(v) = p.secs {
+        if !v.is_finite() {
+            return Err(brp_err("secs must be finite"));
+        }
+        config.secs = v.max(0.0);
     }
+    toon_ok(json!({ "secs": r2(config.secs) }))
+}
 
-    // Drain LLM log queue — write to both combat log and ring buffer
-    for msg in llm_log_q.0.drain(..) {
-        log_ring.push(msg.clone());
-        combat_log.write(msg);
+// --- endless/target_stickiness -----------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct TargetStickinessParams {
+    secs: Option<f32>,
+}
+
+/// Read or set how long units stay committed to a newly acquired target (0 = off).
+pub fn target_stickiness_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: TargetStickinessParams = parse_optional(params)?;
+    let mut config = world.resource_mut::<crate::resources::TargetStickiness>();
+    if let Some(v) = p.secs {
+        if !v.is_finite() {
+            return Err(brp_err("secs must be finite"));
+        }
+        config.secs = v.max(0.0);
     }
+    toon_ok(json!({ "secs": r2(config.secs) }))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::components::{
-        Activity, ActivityKind, ActivityPhase, ActivityTarget, CachedStats, CarriedLoot,
-        CombatState, Faction, GpuSlot, Health, Home, Job, NpcEquipment, NpcFlags, NpcWorkState,
-        Personality, Speed, TownId,
-    };
-    use crate::gpu::{EntityGpuState, NpcVisualUpload, ProjBufferWrites};
-    use crate::resources::{
-        EntityMap, GameTime, GpuReadState, NpcLogCache, NpcTargetThrashDebug, ProjPositionState,
-        SquadState, TownIndex,
-    };
-    use crate::world::WorldData;
+// --- endless/lead_targeting -------------------------------------------------
 
-    fn decode_toon(response: Value) -> Value {
-        let encoded = response
-            .as_str()
-            .expect("debug response should be TOON text");
-        serde_toon2::from_str(encoded).expect("debug response should decode from TOON")
+#[derive(Deserialize, Default)]
+struct LeadTargetingParams {
+    mode: Option<crate::resources::LeadTargetMode>,
+}
+
+/// Read or set which shooters lead moving targets (`off`, `sharpshot`, `all`).
+pub fn lead_targeting_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: LeadTargetingParams = parse_optional(params)?;
+    let mut config = world.resource_mut::<crate::resources::LeadTargeting>();
+    if let Some(mode) = p.mode {
+        config.mode = mode;
     }
+    toon_ok(json!({ "mode": config.mode }))
+}
 
-    fn setup_debug_world(activity: Activity) -> (World, Entity, usize) {
-        let mut world = World::default();
+// --- endless/raid_parties ---------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct RaidPartiesParams {
+    enabled: Option<bool>,
+    min_party: Option<usize>,
+    max_party: Option<usize>,
+    cooldown_hours: Option<f32>,
+}
+
+/// Read or set raid-party mode for raider camps.
+pub fn raid_parties_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: RaidPartiesParams = parse_optional(params)?;
+    if p.cooldown_hours.is_some_and(|h| !h.is_finite()) {
+        return Err(brp_err("cooldown_hours must be finite"));
+    }
+    let mut config = world.resource_mut::<crate::resources::RaidPartyConfig>();
+    if let Some(v) = p.enabled {
+        config.enabled = v;
+    }
+    if let Some(v) = p.min_party {
+        config.min_party = v.max(1);
+    }
+    if let Some(v) = p.max_party {
+        config.max_party = v.max(1);
+    }
+    if let Some(v) = p.cooldown_hours {
+        config.cooldown_hours = v.max(0.0);
+    }
+    toon_ok(json!({
+        "enabled": config.enabled,
+        "min_party": config.min_party,
+        "max_party": config.max_party,
+        "cooldown_hours": r2(config.cooldown_hours),
+    }))
+}
+
+// --- endless/camp_status -----------------------------------------------------
+
+#[derive(Deserialize)]
+struct CampStatusParams {
+    camp: usize,
+}
+
+/// Population of one raider camp as of the last raid-party heartbeat.
+pub fn camp_status_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: CampStatusParams = parse_some(params)?;
+    let Some(status) = world
+        .resource::<crate::resources::RaidParties>()
+        .get_camp_status(p.camp)
+    else {
+        return Err(brp_err(format!("town {} is not a raider camp", p.camp)));
+    };
+    toon_ok(json!({
+        "camp": p.camp,
+        "total": status.total,
+        "available": status.available,
+        "raiding": status.raiding,
+        "returning": status.returning,
+        "next_party_hour": r2(status.next_party_hour),
+        "min_party": world.resource::<crate::resources::RaidPartyConfig>().min_party,
+    }))
+}
+
+// --- endless/give_item / take_item / inventory -------------------------------
+
+#[derive(Deserialize)]
+struct ItemTransferParams {
+    slot: usize,
+    item: String,
+    #[serde(default = "default_item_count")]
+    count: u32,
+}
+
+fn default_item_count() -> u32 {
+    1
+}
+
+#[derive(Deserialize)]
+struct InventoryParams {
+    slot: usize,
+}
+
+fn inventory_json(slot: usize, inv: &crate::components::Inventory) -> Value {
+    let items: Vec<Value> = inv
+        .0
+        .iter()
+        .map(|s| json!({"item": s.id, "count": s.count}))
+        .collect();
+    json!({
+        "slot": slot,
+        "total": inv.total(),
+        "capacity": crate::constants::INVENTORY_CAPACITY,
+        "items": items,
+    })
+}
+
+/// Mutable inventory of the living NPC at `slot`.
+fn npc_inventory(
+    world: &mut World,
+    slot: usize,
+) -> Result<Mut<'_, crate::components::Inventory>, BrpError> {
+    let entity = world
+        .resource::<EntityMap>()
+        .get_npc(slot)
+        .filter(|n| !n.dead)
+        .map(|n| n.entity)
+        .ok_or_else(|| brp_err(format!("no living NPC at slot {slot}")))?;
+    world
+        .get_mut::<crate::components::Inventory>(entity)
+        .ok_or_else(|| brp_err(format!("NPC at slot {slot} has no inventory")))
+}
+
+/// Give `count` of `item` to an NPC. Accepts what fits the capacity; the rest is `overflow`.
+pub fn give_item_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: ItemTransferParams = parse_some(params)?;
+    if p.item.is_empty() {
+        return Err(brp_err("item must be non-empty"));
+    }
+    let mut inv = npc_inventory(world, p.slot)?;
+    let accepted = inv.give(&p.item, p.count, crate::constants::INVENTORY_CAPACITY);
+    let mut out = inventory_json(p.slot, &inv);
+    out["accepted"] = json!(accepted);
+    out["overflow"] = json!(p.count - accepted);
+    toon_ok(out)
+}
+
+/// Take `count` of `item` from an NPC. Takes what it holds; the rest is `shortfall`.
+pub fn take_item_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: ItemTransferParams = parse_some(params)?;
+    let mut inv = npc_inventory(world, p.slot)?;
+    let taken = inv.take(&p.item, p.count);
+    let mut out = inventory_json(p.slot, &inv);
+    out["taken"] = json!(taken);
+    out["shortfall"] = json!(p.count - taken);
+    toon_ok(out)
+}
+
+/// Read an NPC's inventory.
+pub fn inventory_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: InventoryParams = parse_some(params)?;
+    let inv = npc_inventory(world, p.slot)?;
+    toon_ok(inventory_json(p.slot, &inv))
+}
+
+// --- endless/respawn_location ------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct RespawnLocationParams {
+    faction: Option<i32>,
+    mode: Option<crate::resources::RespawnLocation>,
+}
+
+/// Read or set where a faction's spawner respawns appear. `faction` + `mode` sets;
+/// `faction` alone reads it; no params lists every override.
+pub fn respawn_location_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: RespawnLocationParams = parse_optional(params)?;
+    if let Some(faction) = p.faction {
+        let mut policy = world.resource_mut::<crate::resources::RespawnPolicy>();
+        if let Some(mode) = p.mode {
+            policy.by_faction.insert(faction, mode);
+        }
+        return toon_ok(json!({"faction": faction, "mode": policy.mode(faction)}));
+    }
+    if p.mode.is_some() {
+        return Err(brp_err("mode requires faction"));
+    }
+    let policy = world.resource::<crate::resources::RespawnPolicy>();
+    let mut factions: Vec<(i32, crate::resources::RespawnLocation)> =
+        policy.by_faction.iter().map(|(&f, &m)| (f, m)).collect();
+    factions.sort_unstable_by_key(|&(f, _)| f);
+    let list: Vec<Value> = factions
+        .into_iter()
+        .map(|(f, m)| json!({"faction": f, "mode": m}))
+        .collect();
+    toon_ok(json!({"default": crate::resources::RespawnLocation::default(), "factions": list}))
+}
+
+// --- endless/player_focus ----------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct PlayerFocusParams {
+    town: Option<usize>,
+}
+
+/// Read or switch which player-owned town the build menu, economy panel and policies act on.
+/// `town` must belong to the player; the camera and build context follow next frame.
+pub fn player_focus_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: PlayerFocusParams = parse_optional(params)?;
+    let towns = &world.resource::<WorldData>().towns;
+    if let Some(t) = p.town {
+        match towns.get(t) {
+            None => return Err(brp_err(format!("no town {t}"))),
+            Some(town) if town.faction != crate::constants::FACTION_PLAYER => {
+                return Err(brp_err(format!("town {t} is not player-owned")));
+            }
+            Some(_) => {}
+        }
+    }
+    let owned: Vec<usize> = (0..towns.len())
+        .filter(|&i| towns[i].faction == crate::constants::FACTION_PLAYER)
+        .collect();
+    let mut focus = world.resource_mut::<crate::resources::PlayerFocus>();
+    if p.town.is_some() {
+        focus.0 = p.town;
+    }
+    let focus = *focus;
+    let towns = &world.resource::<WorldData>().towns;
+    let town = focus.town(towns);
+    toon_ok(json!({
+        "town": town,
+        "name": town.map(|t| towns[t].name.as_str()),
+        "owned": owned,
+    }))
+}
+
+// --- endless/combat_zone -----------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct CombatZoneParams {
+    kind: Option<crate::resources::ZoneKind>,
+    mult: Option<f32>,
+}
+
+/// Read or set the incoming-damage multiplier of a combat zone kind (`wall`, `road`, `water`).
+pub fn combat_zone_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    use crate::resources::{CombatZones, ZoneKind};
+    let p: CombatZoneParams = parse_optional(params)?;
+    let mut zones = world.resource_mut::<CombatZones>();
+    match (p.kind, p.mult) {
+        (Some(kind), Some(mult)) => zones.set_zone_modifier(kind, mult),
+        (None, Some(_)) => return Err(brp_err("mult requires kind")),
+        _ => {}
+    }
+    toon_ok(json!({
+        "wall": r2(zones.zone_modifier(ZoneKind::Wall)),
+        "road": r2(zones.zone_modifier(ZoneKind::Road)),
+        "water": r2(zones.zone_modifier(ZoneKind::Water)),
+        "cover_floor": crate::constants::COMBAT_COVER_FLOOR,
+    }))
+}
+
+// --- endless/projectile_visual -----------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct ProjectileVisualParams {
+    kind: Option<crate::messages::ProjKind>,
+    size_x: Option<f32>,
+    size_y: Option<f32>,
+    r: Option<f32>,
+    g: Option<f32>,
+    b: Option<f32>,
+    /// Drop the fixed tint and go back to the shooter's faction color.
+    faction_color: Option<bool>,
+    trail_len: Option<usize>,
+}
+
+/// Read or set per-kind projectile size, tint and trail (`arrow`, `tower`, `loot`).
+/// Omitted fields keep their current value.
+pub fn projectile_visual_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    use crate::messages::ProjKind;
+    use crate::resources::ProjectileVisualConfig;
+    let p: ProjectileVisualParams = parse_optional(params)?;
+    let mut config = world.resource_mut::<ProjectileVisualConfig>();
+    if let Some(kind) = p.kind {
+        let cur = *config.get(kind);
+        let color = match (p.r, p.g, p.b) {
+            (Some(r), Some(g), Some(b)) => Some([r, g, b]),
+            (None, None, None) if p.faction_color == Some(true) => None,
+            (None, None, None) => cur.color,
+            _ => return Err(brp_err("r, g and b must be given together")),
+        };
+        config.set_projectile_visual(
+            kind,
+            p.size_x.unwrap_or(cur.size.x),
+            p.size_y.unwrap_or(cur.size.y),
+            color,
+            p.trail_len.unwrap_or(cur.trail_len),
+        );
+    } else if p.size_x.is_some() || p.size_y.is_some() || p.trail_len.is_some() {
+        return Err(brp_err("kind required"));
+    }
+    let kinds: Vec<Value> = ProjKind::ALL
+        .iter()
+        .map(|&kind| {
+            let v = config.get(kind);
+            json!({
+                "kind": kind,
+                "size_x": r2(v.size.x),
+                "size_y": r2(v.size.y),
+                "color": v.color.map(|c| c.map(r2)),
+                "trail_len": v.trail_len,
+            })
+        })
+        .collect();
+    toon_ok(json!({"kinds": kinds, "trail_max": crate::constants::PROJ_TRAIL_MAX}))
+}
+
+// --- endless/projectile_arc --------------------------------------------------
+
+#[derive(Deserialize)]
+struct ProjectileArcParams {
+    slot: usize,
+    gravity: Option<f32>,
+    splash_radius: Option<f32>,
+}
+
+/// Read or set one shooter's ballistic arc: a live NPC, tower or fountain by slot. Its shots
+/// each carry the arc they were fired with. `gravity` 0 flies flat and drops the arc.
+/// Omitted fields keep their current value.
+pub fn projectile_arc_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    use crate::components::ProjArc;
+    let p: ProjectileArcParams = parse_some(params)?;
+    let em = world.resource::<EntityMap>();
+    let shooter = match (em.get_npc(p.slot), em.get_instance(p.slot)) {
+        (Some(n), _) if !n.dead => Some(n.entity),
+        (None, Some(inst)) if matches!(inst.kind, BuildingKind::Tower | BuildingKind::Fountain) => {
+            em.entities.get(&p.slot).copied()
+        }
+        _ => None,
+    };
+    let Some(entity) = shooter else {
+        return Err(brp_err(format!(
+            "no live NPC, tower or fountain at slot {}",
+            p.slot
+        )));
+    };
+    let cur = world.get::<ProjArc>(entity).copied().unwrap_or_default();
+    let arc = ProjArc::new(
+        p.gravity.unwrap_or(cur.gravity),
+        p.splash_radius.unwrap_or(cur.splash_radius),
+    );
+    if arc.is_arced() {
+        world.entity_mut(entity).insert(arc);
+    } else {
+        world.entity_mut(entity).remove::<ProjArc>();
+    }
+    toon_ok(json!({
+        "slot": p.slot,
+        "arced": arc.is_arced(),
+        "gravity": r2(arc.gravity),
+        "splash_radius": r2(arc.splash_radius),
+        "splash_max": crate::constants::PROJ_ARC_SPLASH_MAX,
+    }))
+}
+
+// --- endless/assign_npc / clear_assignment ----------------------------------
+
+#[derive(Deserialize)]
+struct AssignNpcParams {
+    slot: usize,
+    kind: crate::systems::AssignmentKind,
+    building: Option<usize>,
+}
+
+/// Pin an NPC to a farm, bed or post (`kind`), reserving the building slot in one step.
+pub fn assign_npc_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: AssignNpcParams = parse_some(params)?;
+    let building = p.building.ok_or_else(|| brp_err("building required"))?;
+    check_npc_allowed(world, p.slot)?;
+    let pos = crate::systems::assign_npc(world, p.slot, building, p.kind).map_err(brp_err)?;
+    toon_ok(
+        json!({"slot": p.slot, "kind": p.kind, "building": building, "x": r2(pos.x), "y": r2(pos.y)}),
+    )
+}
+
+/// Clear an NPC's manual `kind` assignment and release the reservation.
+pub fn clear_assignment_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: AssignNpcParams = parse_some(params)?;
+    check_npc_allowed(world, p.slot)?;
+    let cleared = crate::systems::clear_assignment(world, p.slot, p.kind).map_err(brp_err)?;
+    toon_ok(json!({"slot": p.slot, "kind": p.kind, "cleared": cleared}))
+}
+
+// --- endless/assign_bodyguard / clear_bodyguard -------------------------------
+
+#[derive(Deserialize)]
+struct BodyguardParams {
+    guard: usize,
+    protected: Option<usize>,
+}
+
+/// Make `guard` a bodyguard of `protected` (both NPC slots, same faction).
+pub fn assign_bodyguard_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: BodyguardParams = parse_some(params)?;
+    let protected = p.protected.ok_or_else(|| brp_err("protected required"))?;
+    check_npc_allowed(world, p.guard)?;
+    crate::systems::assign_bodyguard(world, p.guard, protected).map_err(brp_err)?;
+    toon_ok(json!({"guard": p.guard, "protected": protected}))
+}
+
+/// Release `guard` from bodyguard duty.
+pub fn clear_bodyguard_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: BodyguardParams = parse_some(params)?;
+    check_npc_allowed(world, p.guard)?;
+    let cleared = crate::systems::clear_bodyguard(world, p.guard).map_err(brp_err)?;
+    toon_ok(json!({"guard": p.guard, "cleared": cleared}))
+}
+
+// --- endless/auto_assign_guards ----------------------------------------------
+
+#[derive(Deserialize)]
+struct AutoAssignGuardsParams {
+    town: i32,
+}
+
+/// Pin a town's free guards to posts nobody holds, nearest first.
+pub fn auto_assign_guards_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: AutoAssignGuardsParams = parse_some(params)?;
+    let towns = world.resource::<WorldData>().towns.len();
+    if usize::try_from(p.town).map_or(true, |t| t >= towns) {
+        return Err(brp_err(format!("no town {}", p.town)));
+    }
+    check_town_allowed(world, p.town as usize)?;
+    let pinned: Vec<Value> = crate::systems::auto_assign_guards(world, p.town)
+        .into_iter()
+        .map(|(guard, post)| json!({"guard": guard, "post": post}))
+        .collect();
+    let uncovered = crate::systems::post_coverage(world, p.town).uncovered();
+    toon_ok(json!({"town": p.town, "assigned": pinned, "uncovered": uncovered}))
+}
+
+// --- endless/conscript / demobilize / militia --------------------------------
+
+#[derive(Deserialize)]
+struct ConscriptParams {
+    town: i32,
+    count: Option<usize>,
+}
+
+/// Retrain up to `count` (default 1) idle farmers of a town as militia.
+pub fn conscript_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: ConscriptParams = parse_some(params)?;
+    let town = usize::try_from(p.town).map_err(|_| brp_err(format!("no town {}", p.town)))?;
+    check_town_allowed(world, town)?;
+    let slots = crate::systems::conscript(world, p.town, p.count.unwrap_or(1)).map_err(brp_err)?;
+    let militia = crate::systems::militia_count(world, p.town);
+    toon_ok(json!({"town": p.town, "conscripted": slots, "militia": militia}))
+}
+
+#[derive(Deserialize)]
+struct MilitiaTownParams {
+    town: i32,
+}
+
+/// Return a town's surviving militia to farming.
+pub fn demobilize_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: MilitiaTownParams = parse_some(params)?;
+    let town = usize::try_from(p.town).map_err(|_| brp_err(format!("no town {}", p.town)))?;
+    check_town_allowed(world, town)?;
+    let slots = crate::systems::demobilize(world, p.town);
+    toon_ok(json!({"town": p.town, "demobilized": slots}))
+}
+
+/// Living militia in a town.
+pub fn militia_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: MilitiaTownParams = parse_some(params)?;
+    toon_ok(json!({"town": p.town, "militia": crate::systems::militia_count(world, p.town)}))
+}
+
+// --- endless/npc_snapshot / npc_restore ---------------------------------------
+
+#[derive(Deserialize)]
+struct NpcSnapshotParams {
+    slot: usize,
+}
+
+/// Capture one NPC's full state as a snapshot string for `endless/npc_restore`.
+pub fn npc_snapshot_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: NpcSnapshotParams = parse_some(params)?;
+    let bytes = crate::save::snapshot_npc(world, p.slot).map_err(brp_err)?;
+    let snapshot = String::from_utf8(bytes).map_err(|e| brp_err(e.to_string()))?;
+    toon_ok(json!({"slot": p.slot, "snapshot": snapshot}))
+}
+
+#[derive(Deserialize)]
+struct NpcRestoreParams {
+    slot: usize,
+    snapshot: String,
+    #[serde(default)]
+    force: bool,
+}
+
+/// Put an `endless/npc_snapshot` capture back. `force` overwrites a reallocated slot.
+pub fn npc_restore_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: NpcRestoreParams = parse_some(params)?;
+    crate::save::restore_npc(world, p.slot, p.snapshot.as_bytes(), p.force).map_err(brp_err)?;
+    toon_ok(json!({"slot": p.slot, "restored": true}))
+}
+
+// --- endless/npc_engagement --------------------------------------------------
+
+#[derive(Deserialize)]
+struct NpcEngagementParams {
+    slot: usize,
+}
+
+/// `{value, source}` — source is "default" when `value` matches the job/global default.
+fn sourced(value: Option<f32>, default: Option<f32>) -> Value {
+    let source = if value == default {
+        "default"
+    } else {
+        "override"
+    };
+    json!({"value": value.map(r2), "source": source})
+}
+
+/// Radii a selected unit engages with, for drawing around it: target acquisition (the GPU
+/// combat scan), leash, preferred attack range, plus its current combat target.
+pub fn npc_engagement_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: NpcEngagementParams = parse_some(params)?;
+    let (entity, job) = match world.resource::<EntityMap>().get_npc(p.slot) {
+        Some(n) if !n.dead => (n.entity, n.job),
+        _ => return Err(brp_err(format!("no live NPC at slot {}", p.slot))),
+    };
+    let def = crate::constants::npc_def(job);
+
+    // One global scan radius on the GPU; no per-unit override exists
+    let acquire = crate::gpu::EntityGpuData::default().combat_range;
+    let acquire_now = world
+        .get_resource::<crate::gpu::RenderFrameConfig>()
+        .map_or(acquire, |c| c.npc.combat_range);
+    let stance = world
+        .get::<CombatStance>(entity)
+        .copied()
+        .unwrap_or_default();
+    let provoked = world.get::<Provoked>(entity).is_some();
+
+    let leash = world
+        .get::<crate::components::LeashRange>(entity)
+        .map(|l| l.0);
+    let attack_type = world
+        .get::<crate::components::BaseAttackType>(entity)
+        .copied()
+        .unwrap_or(def.default_attack_type);
+    let base_range = def.attack_override.as_ref().map(|a| a.range).or_else(|| {
+        world
+            .resource::<crate::systems::stats::CombatConfig>()
+            .attacks
+            .get(&attack_type)
+            .map(|a| a.range)
+    });
+    let range = world.get::<CachedStats>(entity).map(|s| s.range);
+
+    let manual = world.get::<ManualTarget>(entity).map(|t| match t {
+        ManualTarget::Npc(slot) => json!({"npc": slot}),
+        ManualTarget::Building(pos) => json!({"building": [r2(pos.x), r2(pos.y)]}),
+        ManualTarget::Position(pos) => json!({"position": [r2(pos.x), r2(pos.y)]}),
+    });
+    let gpu = world.resource::<GpuReadState>();
+    let target = gpu
+        .combat_targets
+        .get(p.slot)
+        .and_then(|&t| usize::try_from(t).ok())
+        .map(|t| {
+            let pos = gpu.positions.get(t * 2..t * 2 + 2).unwrap_or(&[0.0, 0.0]);
+            let em = world.resource::<EntityMap>();
+            let kind = if em.get_npc(t).is_some() {
+                "npc"
+            } else {
+                "building"
+            };
+            json!({"slot": t, "kind": kind, "x": r2(pos[0]), "y": r2(pos[1])})
+        });
+
+    toon_ok(json!({
+        "slot": p.slot,
+        "acquisition_radius": sourced(Some(acquire_now), Some(acquire)),
+        "acquiring": !stance.is_passive(provoked),
+        "stance": stance.label(),
+        "leash_radius": sourced(leash, def.leash_range),
+        "preferred_range": sourced(range, base_range),
+        "combat_state": world.get::<CombatState>(entity).map_or("", |c| c.name()),
+        "target": target,
+        "manual_target": manual,
+    }))
+}
+
+// --- endless/farm_events -----------------------------------------------------
+
+/// Drain queued farm transitions, oldest first: `{type, farm_idx, x, y}` with `type` one of
+/// `farm_ready` / `farm_harvested`.
+pub fn farm_events_handler(In(_params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let events: Vec<Value> = world
+        .resource_mut::<FarmEventOutbox>()
+        .0
+        .drain(..)
+        .map(|e| {
+            json!({
+                "type": e.kind.label(),
+                "farm_idx": e.farm_slot,
+                "x": r2(e.position.x),
+                "y": r2(e.position.y),
+            })
+        })
+        .collect();
+    toon_ok(json!({ "events": events }))
+}
+
+// --- endless/save_blueprint / stamp_blueprint --------------------------------
+
+#[derive(Deserialize)]
+struct BlueprintParams {
+    town: usize,
+    blueprint: Option<String>,
+}
+
+/// Capture a town's player-built layout as a versioned blueprint string.
+pub fn save_blueprint_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: BlueprintParams = parse_some(params)?;
+    let blueprint = crate::systems::save_town_blueprint(world, p.town).map_err(brp_err)?;
+    toon_ok(json!({"town": p.town, "blueprint": blueprint}))
+}
+
+/// Stamp a blueprint onto a town, paying costs; invalid entries are skipped with reasons.
+pub fn stamp_blueprint_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: BlueprintParams = parse_some(params)?;
+    check_town_allowed(world, p.town)?;
+    let blueprint = p.blueprint.ok_or_else(|| brp_err("blueprint required"))?;
+    let report = crate::systems::stamp_blueprint(world, p.town, &blueprint).map_err(brp_err)?;
+    queue_llm_log(
+        world,
+        p.town,
+        format!(
+            "stamp blueprint: {} placed, {} skipped",
+            report.placed.len(),
+            report.skipped.len()
+        ),
+        None,
+    );
+    toon_ok(json!({"town": p.town, "placed": report.placed, "skipped": report.skipped}))
+}
+
+// --- endless/predict_combat --------------------------------------------------
+
+#[derive(Deserialize)]
+struct PredictCombatParams {
+    a: ArmyParams,
+    b: ArmyParams,
+}
+
+/// Aggregate estimate of an `a` vs `b` fight (same army format as quick_battle). An
+/// estimate only — see `combat_prediction` for what the model ignores.
+pub fn predict_combat_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: PredictCombatParams = parse_some(params)?;
+    let (a, b) = (parse_army(&p.a)?, parse_army(&p.b)?);
+    let prediction = crate::systems::combat_prediction::predict_combat(
+        &a,
+        &b,
+        world.resource::<crate::systems::stats::CombatConfig>(),
+        world.resource::<crate::resources::CombatRng>(),
+    );
+    let side = |w: usize| if w == 0 { "a" } else { "b" };
+    toon_ok(json!({
+        "winner": prediction.winner.map_or("draw", side),
+        "win_probability": {
+            "a": r2(prediction.win_probability[0]),
+            "b": r2(prediction.win_probability[1]),
+        },
+        "survivors": {"a": prediction.survivors[0], "b": prediction.survivors[1]},
+        "confidence": r2(prediction.confidence),
+    }))
+}
+
+// --- endless/compute_backend -------------------------------------------------
+
+/// Which backend runs NPC movement/targeting: `gpu`, or `cpu` after a GPU compute failure
+/// (or `--cpu-compute`). The CPU fallback only steps the first `cpu_entity_cap` slots.
+pub fn compute_backend_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult {
+    let backend = *world.resource::<crate::resources::ComputeBackend>();
+    toon_ok(json!({
+        "backend": backend,
+        "cpu_entity_cap": crate::constants::CPU_FALLBACK_MAX_ENTITIES,
+    }))
+}
+
+// --- endless/rewind ----------------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct SnapshotParams {
+    #[serde(default)]
+    capture: bool,
+}
+
+/// List retained rewind snapshots; `capture: true` also queues one for the next frame.
+/// Debug-only (`debug_rewind` setting).
+pub fn snapshot_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: SnapshotParams = match params {
+        Some(v) => serde_json::from_value(v).map_err(|e| brp_err(e.to_string()))?,
+        None => SnapshotParams::default(),
+    };
+    let mut history = world.resource_mut::<crate::save::SnapshotHistory>();
+    if !history.enabled {
+        return Err(brp_err(
+            "rewind is disabled (enable debug_rewind in settings)",
+        ));
+    }
+    history.capture_requested |= p.capture;
+    let snapshots: Vec<Value> = history
+        .snapshots
+        .iter()
+        .map(|s| {
+            json!({
+                "day": s.day,
+                "hour": s.hour,
+                "kb": s.data.len() / 1024,
+                "raw_kb": s.raw_bytes / 1024,
+            })
+        })
+        .collect();
+    toon_ok(json!({
+        "capture_queued": history.capture_requested,
+        "max": crate::save::MAX_REWIND_SNAPSHOTS,
+        "snapshots": snapshots,
+    }))
+}
+
+#[derive(Deserialize)]
+struct RewindParams {
+    day: i32,
+}
+
+/// Restore the retained snapshot nearest `day` on the next frame (same path as a load).
+pub fn rewind_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: RewindParams = parse_some(params)?;
+    let mut history = world.resource_mut::<crate::save::SnapshotHistory>();
+    let day = crate::save::rewind_to(&mut history, p.day).map_err(brp_err)?;
+    toon_ok(json!({"queued": true, "day": day}))
+}
+
+// --- endless/save_info -------------------------------------------------------
+
+/// Read-only. Save compression setting and the size/cost of the last save written.
+pub fn save_info_handler(In(_params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let config = world.resource::<crate::save::SaveConfig>();
+    let last = config.get_last_save_info().map(|s| {
+        json!({
+            "path": s.path.display().to_string(),
+            "compressed": s.compressed,
+            "json_kb": s.json_bytes / 1024,
+            "file_kb": s.file_bytes / 1024,
+            "write_ms": r2(s.write_ms),
+        })
+    });
+    toon_ok(json!({
+        "compress": config.compress,
+        "last_save": last,
+    }))
+}
+
+// --- endless/attack_events ---------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct AttackEventsParams {
+    enabled: Option<bool>,
+}
+
+/// Drain queued attack animation cues, oldest first: `{type, slot, target_slot, windup}`
+/// with `type` one of `attack_swing` / `attack_whiff`. `enabled` turns recording on/off
+/// (off by default); disabling also forgets swings still in flight.
+pub fn attack_events_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: AttackEventsParams = match params {
+        Some(v) => serde_json::from_value(v).map_err(|e| brp_err(e.to_string()))?,
+        None => AttackEventsParams::default(),
+    };
+    let mut outbox = world.resource_mut::<crate::resources::AttackAnimOutbox>();
+    if let Some(enabled) = p.enabled {
+        outbox.enabled = enabled;
+        if !enabled {
+            outbox.swinging.clear();
+        }
+    }
+    let events: Vec<Value> = outbox
+        .events
+        .drain(..)
+        .map(|e| {
+            json!({
+                "type": e.kind.label(),
+                "slot": e.slot,
+                "target_slot": e.target_slot,
+                "windup": r2(e.windup),
+            })
+        })
+        .collect();
+    toon_ok(json!({ "enabled": outbox.enabled, "events": events }))
+}
+
+// --- endless/spawn_grid ------------------------------------------------------
+
+#[derive(Deserialize)]
+struct SpawnGridParams {
+    count: usize,
+    job: String,
+    faction: i32,
+    #[serde(default = "default_grid_spacing")]
+    spacing: f32,
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+}
+
+fn default_grid_spacing() -> f32 {
+    16.0
+}
+
+/// Queue `count` townless NPCs in a deterministic square grid (stress scenarios). Returns the
+/// allocated slots; fewer than `count` when the slot pool runs out.
+pub fn spawn_grid_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: SpawnGridParams = parse_some(params)?;
+    let job = parse_job(&p.job).ok_or_else(|| brp_err(format!("unknown job: {}", p.job)))?;
+    if !p.spacing.is_finite() || p.spacing <= 0.0 || !p.x.is_finite() || !p.y.is_finite() {
+        return Err(brp_err("spacing must be positive; x/y finite"));
+    }
+    if p.count > crate::constants::MAX_NPC_COUNT {
+        return Err(brp_err(format!(
+            "count {} exceeds MAX_NPC_COUNT {}",
+            p.count,
+            crate::constants::MAX_NPC_COUNT
+        )));
+    }
+    let slots = crate::systems::spawn::spawn_grid(
+        world,
+        p.count,
+        job,
+        p.faction,
+        p.spacing,
+        Vec2::new(p.x, p.y),
+    );
+    toon_ok(json!({
+        "requested": p.count,
+        "spawned": slots.len(),
+        "slots": slots,
+    }))
+}
+
+// --- endless/anchor ----------------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct AnchorParams {
+    rest: Option<bool>,
+    heal: Option<bool>,
+    on_duty: Option<bool>,
+}
+
+/// Read or set which settled activities anchor NPCs (no movement, no separation).
+pub fn anchor_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: AnchorParams = parse_optional(params)?;
+    let mut config = world.resource_mut::<crate::resources::AnchorConfig>();
+    if let Some(v) = p.rest {
+        config.rest = v;
+    }
+    if let Some(v) = p.heal {
+        config.heal = v;
+    }
+    if let Some(v) = p.on_duty {
+        config.on_duty = v;
+    }
+    let config = config.clone();
+    let anchored = world
+        .query_filtered::<(), With<crate::components::Anchored>>()
+        .iter(world)
+        .count();
+    toon_ok(json!({
+        "rest": config.rest,
+        "heal": config.heal,
+        "on_duty": config.on_duty,
+        "anchored": anchored,
+    }))
+}
+
+// --- endless/fast_forward ----------------------------------------------------
+
+#[derive(Deserialize)]
+struct FastForwardParams {
+    day: i32,
+    max_secs: Option<f32>,
+}
+
+/// Run the simulation headlessly to the start of `day`, then report population, stockpiles
+/// and buildings. Requires a paused game (or a test run); bounded by `max_secs` of real time.
+pub fn fast_forward_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    use crate::systems::fast_forward::{FAST_FORWARD_MAX_SECS, fast_forward_to_day};
+    let p: FastForwardParams = parse_some(params)?;
+    let report = fast_forward_to_day(world, p.day, p.max_secs.unwrap_or(FAST_FORWARD_MAX_SECS))
+        .map_err(brp_err)?;
+
+    let population: Vec<Value> = report
+        .population
+        .iter()
+        .map(|(&(faction, job), &n)| json!({ "faction": faction, "job": format!("{job:?}"), "n": n }))
+        .collect();
+    let stockpiles: Vec<Value> = report
+        .stockpiles
+        .iter()
+        .enumerate()
+        .map(|(town, &(food, gold))| json!({ "town": town, "food": food, "gold": gold }))
+        .collect();
+    let buildings: Vec<Value> = report
+        .buildings
+        .iter()
+        .map(|(&kind, &n)| json!({ "kind": crate::constants::building_def(kind).label, "n": n }))
+        .collect();
+    toon_ok(json!({
+        "day": report.day,
+        "reached": report.reached,
+        "ticks": report.ticks,
+        "wall_secs": r2(report.wall_secs),
+        "stepped_slots": report.stepped_slots,
+        "total_slots": report.total_slots,
+        "population": population,
+        "stockpiles": stockpiles,
+        "buildings": buildings,
+    }))
+}
+
+// --- endless/despawn_npc -----------------------------------------------------
+
+#[derive(Deserialize)]
+struct DespawnNpcParams {
+    entity: String,
+}
+
+pub fn despawn_npc_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: DespawnNpcParams = parse_some(params)?;
+    let entity = parse_entity_str(&p.entity)?;
+
+    let (slot, town) = {
+        let entity_map = world.resource::<EntityMap>();
+        let slot = entity_map
+            .slot_for_entity(entity)
+            .ok_or_else(|| brp_err(format!("no entity for {entity:?}")))?;
+        let npc = entity_map
+            .get_npc(slot)
+            .ok_or_else(|| brp_err(format!("entity {} is not an NPC", p.entity)))?;
+        (slot, npc.town_idx)
+    };
+    if town >= 0 {
+        check_town_allowed(world, town as usize)?;
+        queue_llm_log(world, town as usize, format!("despawn npc #{slot}"), None);
+    }
+
+    world.resource_mut::<RemoteDespawnQueue>().0.push(slot);
+
+    toon_ok(json!({"status": "queued", "slot": slot}))
+}
+
+// --- endless/promote_npc -----------------------------------------------------
+
+#[derive(Deserialize)]
+struct PromoteNpcParams {
+    entity: String,
+}
+
+pub fn promote_npc_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: PromoteNpcParams = parse_some(params)?;
+    let entity = parse_entity_str(&p.entity)?;
+
+    let (slot, town) = {
+        let entity_map = world.resource::<EntityMap>();
+        let slot = entity_map
+            .slot_for_entity(entity)
+            .ok_or_else(|| brp_err(format!("no entity for {entity:?}")))?;
+        let npc = entity_map
+            .get_npc(slot)
+            .ok_or_else(|| brp_err(format!("entity {} is not an NPC", p.entity)))?;
+        if npc.dead {
+            return Err(brp_err(format!("npc #{slot} is dead")));
+        }
+        (slot, npc.town_idx)
+    };
+    if town >= 0 {
+        check_town_allowed(world, town as usize)?;
+    }
+    if world.get::<Officer>(entity).is_some() {
+        return Err(brp_err(format!("npc #{slot} is already an officer")));
+    }
+
+    let officer = crate::systems::new_officer();
+    world.entity_mut(entity).insert(officer);
+    world
+        .resource_mut::<crate::gpu::EntityGpuState>()
+        .visual_dirty_indices
+        .push(slot);
+    if town >= 0 {
+        queue_llm_log(world, town as usize, format!("promote npc #{slot}"), None);
+    }
+
+    toon_ok(json!({
+        "status": "ok",
+        "slot": slot,
+        "aura_radius": officer.aura_radius,
+        "buff": r2(officer.buff),
+    }))
+}
+
+// --- endless/officers --------------------------------------------------------
+
+#[derive(Deserialize)]
+struct OfficersParams {
+    town: usize,
+}
+
+pub fn officers_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: OfficersParams = parse_some(params)?;
+    let town_count = world.resource::<WorldData>().towns.len();
+    if p.town >= town_count {
+        return Err(brp_err(format!("town {} out of range", p.town)));
+    }
+
+    let mut slots: Vec<(usize, Entity)> = world
+        .resource::<EntityMap>()
+        .npcs_for_town(p.town as i32)
+        .filter(|n| !n.dead)
+        .map(|n| (n.slot, n.entity))
+        .collect();
+    slots.sort_unstable_by_key(|&(slot, _)| slot);
+
+    let positions = &world.resource::<GpuReadState>().positions;
+    let officers: Vec<Value> = slots
+        .iter()
+        .filter_map(|&(slot, entity)| {
+            let officer = world.get::<Officer>(entity)?;
+            let name = world
+                .get::<NpcStats>(entity)
+                .map(|s| s.name.clone())
+                .unwrap_or_default();
+            let level = world
+                .get::<NpcStats>(entity)
+                .map(|s| crate::systems::stats::level_from_xp(s.xp))
+                .unwrap_or(0);
+            let x = positions.get(slot * 2).copied().unwrap_or(0.0);
+            let y = positions.get(slot * 2 + 1).copied().unwrap_or(0.0);
+            Some(json!({
+                "entity": entity.to_string(),
+                "slot": slot,
+                "name": name,
+                "level": level,
+                "x": x as i32,
+                "y": y as i32,
+                "aura_radius": officer.aura_radius,
+                "buff": r2(officer.buff),
+            }))
+        })
+        .collect();
+
+    toon_ok(json!({"town": p.town, "officers": officers}))
+}
+
+// --- endless/position_sync ---------------------------------------------------
+
+#[derive(Deserialize)]
+struct PositionSyncParams {
+    threshold: Option<f32>,
+}
+
+pub fn position_sync_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: PositionSyncParams = parse_some(params)?;
+    let mut sync = world.resource_mut::<crate::resources::PositionSync>();
+    if let Some(v) = p.threshold {
+        sync.threshold = v.max(0.0);
+    }
+
+    toon_ok(json!({
+        "status": "ok",
+        "threshold": r2(sync.threshold),
+        "synced": sync.synced,
+        "skipped": sync.skipped,
+    }))
+}
+
+// --- endless/energy_thresholds ----------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct EnergyThresholdsParams {
+    job: Option<String>,
+    rest_below: Option<f32>,
+    resume_above: Option<f32>,
+    #[serde(default)]
+    reset: bool,
+}
+
+/// Read or set per-job rest/resume energy thresholds. With `job`, omitted values keep the
+/// job's current setting; invalid bands are clamped (`clamped: true`). No params = read all.
+pub fn energy_thresholds_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: EnergyThresholdsParams = parse_optional(params)?;
+    let mut thresholds = world.resource_mut::<crate::resources::EnergyThresholds>();
+    let mut clamped = false;
+    if let Some(ref name) = p.job {
+        let job = parse_job(name).ok_or_else(|| brp_err(format!("unknown job: {name}")))?;
+        if p.reset {
+            thresholds.per_job.remove(&job);
+        } else if p.rest_below.is_some() || p.resume_above.is_some() {
+            let cur = thresholds.get(job);
+            let rest_below = p.rest_below.unwrap_or(cur.rest_below);
+            let resume_above = p.resume_above.unwrap_or(cur.resume_above);
+            if !rest_below.is_finite() || !resume_above.is_finite() {
+                return Err(brp_err("thresholds must be finite"));
+            }
+            clamped = thresholds.set(job, rest_below, resume_above).1;
+        }
+    }
+
+    let jobs: Vec<Value> = crate::constants::NPC_REGISTRY
+        .iter()
+        .map(|d| {
+            let t = thresholds.get(d.job);
+            json!({
+                "job": format!("{:?}", d.job),
+                "rest_below": r2(t.rest_below),
+                "resume_above": r2(t.resume_above),
+                "custom": thresholds.per_job.contains_key(&d.job),
+            })
+        })
+        .collect();
+    toon_ok(json!({ "clamped": clamped, "jobs": jobs }))
+}
+
+// --- endless/food_storage ----------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct FoodStorageParams {
+    enabled: Option<bool>,
+    cap_base: Option<i32>,
+    cap_per_granary: Option<i32>,
+    spoilage_fraction: Option<f32>,
+    spoil_above: Option<f32>,
+}
+
+/// Read or set per-town food storage caps and daily spoilage. No params = read.
+pub fn food_storage_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: FoodStorageParams = parse_optional(params)?;
+    for v in [p.spoilage_fraction, p.spoil_above].into_iter().flatten() {
+        if !(0.0..=1.0).contains(&v) {
+            return Err(brp_err("fractions must be within 0..1"));
+        }
+    }
+    let mut config = world.resource_mut::<crate::resources::FoodStorageConfig>();
+    if let Some(v) = p.enabled {
+        config.enabled = v;
+    }
+    if let Some(v) = p.cap_base {
+        config.cap_base = v.max(0);
+    }
+    if let Some(v) = p.cap_per_granary {
+        config.cap_per_granary = v.max(0);
+    }
+    if let Some(v) = p.spoilage_fraction {
+        config.spoilage_fraction = v;
+    }
+    if let Some(v) = p.spoil_above {
+        config.spoil_above = v;
+    }
+    toon_ok(json!({
+        "enabled": config.enabled,
+        "cap_base": config.cap_base,
+        "cap_per_granary": config.cap_per_granary,
+        "spoilage_fraction": r2(config.spoilage_fraction),
+        "spoil_above": r2(config.spoil_above),
+    }))
+}
+
+// --- endless/happiness -------------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct HappinessParams {
+    enabled: Option<bool>,
+    daily_rate: Option<f32>,
+    effect: Option<f32>,
+}
+
+/// Read or set town happiness settings, with each town's value and what drives it.
+pub fn happiness_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: HappinessParams = parse_optional(params)?;
+    for v in [p.daily_rate, p.effect].into_iter().flatten() {
+        if !(0.0..=1.0).contains(&v) {
+            return Err(brp_err("daily_rate and effect must be within 0..1"));
+        }
+    }
+    let mut config = world.resource_mut::<crate::systems::HappinessConfig>();
+    if let Some(v) = p.enabled {
+        config.enabled = v;
+    }
+    if let Some(v) = p.daily_rate {
+        config.daily_rate = v;
+    }
+    if let Some(v) = p.effect {
+        config.effect = v;
+    }
+    let config = config.clone();
+    let happiness = world.resource::<crate::systems::TownHappiness>();
+    let towns: Vec<Value> = happiness
+        .towns
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            json!({
+                "town": i,
+                "happiness": r2(h.value),
+                "target": r2(h.factors.target()),
+                "food": r2(h.factors.food),
+                "deaths": r2(h.factors.deaths),
+                "crowding": r2(h.factors.crowding),
+                "mult": r2(happiness.output_mult(i as i32)),
+            })
+        })
+        .collect();
+    toon_ok(json!({
+        "enabled": config.enabled,
+        "daily_rate": r2(config.daily_rate),
+        "effect": r2(config.effect),
+        "towns": towns,
+    }))
+}
+
+// --- endless/tech ------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct TechParams {
+    town: usize,
+    enabled: Option<bool>,
+    research_per_hour: Option<f32>,
+    research_per_building: Option<f32>,
+    defs: Option<Vec<crate::systems::TechDef>>,
+    unlock: Option<String>,
+}
+
+/// Read a town's tech status; optionally change settings, replace definitions or unlock a tech.
+pub fn tech_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: TechParams = parse_some(params)?;
+    let town_count = world.resource::<WorldData>().towns.len();
+    if p.town >= town_count {
+        return Err(brp_err(format!("no town {}", p.town)));
+    }
+    let mutates = p.enabled.is_some()
+        || p.research_per_hour.is_some()
+        || p.research_per_building.is_some()
+        || p.defs.is_some()
+        || p.unlock.is_some();
+    if mutates {
+        check_town_allowed(world, p.town)?;
+    }
+    for v in [p.research_per_hour, p.research_per_building]
+        .into_iter()
+        .flatten()
+    {
+        if !v.is_finite() || v < 0.0 {
+            return Err(brp_err("research rates must be >= 0"));
+        }
+    }
+    world.resource_scope(|world, mut tech: Mut<crate::systems::TechTree>| {
+        if let Some(defs) = p.defs {
+            tech.set_tech_defs(defs).map_err(brp_err)?;
+        }
+        if let Some(v) = p.enabled {
+            tech.enabled = v;
+        }
+        if let Some(v) = p.research_per_hour {
+            tech.research_per_hour = v;
+        }
+        if let Some(v) = p.research_per_building {
+            tech.research_per_building = v;
+        }
+        tech.ensure_towns(town_count);
+        if let Some(id) = &p.unlock {
+            tech.unlock_tech(p.town, id, world.resource::<EntityMap>())
+                .map_err(brp_err)?;
+        }
+        let entity_map = world.resource::<EntityMap>();
+        let techs: Vec<Value> = tech
+            .get_tech_status(p.town, entity_map)
+            .into_iter()
+            .map(|s| {
+                json!({
+                    "id": s.id,
+                    "cost": r2(s.cost),
+                    "unlocked": s.unlocked,
+                    "available": s.available,
+                    "missing": s.missing,
+                })
+            })
+            .collect();
+        let locked: Vec<String> = tech
+            .locked_buildings(p.town)
+            .iter()
+            .map(|k| format!("{k:?}"))
+            .collect();
+        toon_ok(json!({
+            "town": p.town,
+            "enabled": tech.enabled,
+            "research": r2(tech.towns[p.town].points),
+            "research_per_hour": r2(tech.research_per_hour),
+            "research_per_building": r2(tech.research_per_building),
+            "research_building": tech.research_building.map(|k| format!("{k:?}")),
+            "locked_buildings": locked,
+            "techs": techs,
+        }))
+    })
+}
+
+// --- endless/town_storage ----------------------------------------------------
+
+#[derive(Deserialize)]
+struct TownStorageParams {
+    town: usize,
+}
+
+/// One town's food against its storage cap and the last day's spoilage.
+pub fn town_storage_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: TownStorageParams = parse_some(params)?;
+    if p.town >= world.resource::<WorldData>().towns.len() {
+        return Err(brp_err(format!("no town {}", p.town)));
+    }
+    let config = world.resource::<crate::resources::FoodStorageConfig>();
+    let state = world.resource::<crate::resources::FoodStorageState>();
+    let granaries = world
+        .resource::<EntityMap>()
+        .count_for_town(BuildingKind::Granary, p.town as u32);
+    let food = world
+        .resource::<crate::resources::TownIndex>()
+        .0
+        .get(&(p.town as i32))
+        .and_then(|&e| world.get::<crate::components::FoodStore>(e))
+        .map_or(0, |f| f.0);
+    toon_ok(json!({
+        "town": p.town,
+        "food": food,
+        "cap": config.cap(granaries),
+        "capped": config.enabled,
+        "granaries": granaries,
+        "spoilage_last_day": state.spoiled_last_day.get(p.town).copied().unwrap_or(0),
+    }))
+}
+
+// --- endless/reload_balance --------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct ReloadBalanceParams {
+    path: Option<String>,
+}
+
+/// Re-read the balance file (default: the last one loaded) and return the config in effect.
+/// A file that fails to parse or validate is rejected and the current values stay.
+pub fn reload_balance_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    use crate::systems::balance::{BalanceConfig, BalanceSource, reload_balance};
+    let p: ReloadBalanceParams = parse_optional(params)?;
+    reload_balance(world, p.path.as_deref().map(std::path::Path::new)).map_err(brp_err)?;
+    let config = world.resource::<BalanceConfig>();
+    let path = world
+        .resource::<BalanceSource>()
+        .path
+        .as_ref()
+        .map(|p| p.display().to_string());
+    toon_ok(json!({ "path": path, "config": config }))
+}
+
+// --- endless/npc_highlight ---------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct NpcHighlightParams {
+    slot: Option<usize>,
+    on: Option<bool>,
+    selection: Option<bool>,
+    hover: Option<bool>,
+}
+
+/// Scripted body highlight on one NPC (e.g. a tutorial pointing at a unit), plus the
+/// selection/hover highlight toggles. Scripted highlights clear when the NPC dies.
+pub fn npc_highlight_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: NpcHighlightParams = parse_optional(params)?;
+    if let Some(slot) = p.slot {
+        if world
+            .resource::<EntityMap>()
+            .get_npc(slot)
+            .is_none_or(|n| n.dead)
+        {
+            return Err(brp_err(format!("no living NPC at slot {slot}")));
+        }
+        let mut hl = world.resource_mut::<NpcHighlight>();
+        if p.on.unwrap_or(true) {
+            hl.scripted.insert(slot);
+        } else {
+            hl.scripted.remove(&slot);
+        }
+    }
+    let mut hl = world.resource_mut::<NpcHighlight>();
+    if let Some(v) = p.selection {
+        hl.selection = v;
+    }
+    if let Some(v) = p.hover {
+        hl.hover = v;
+    }
+    let mut scripted: Vec<usize> = hl.scripted.iter().copied().collect();
+    scripted.sort_unstable();
+    toon_ok(json!({
+        "selection": hl.selection,
+        "hover": hl.hover,
+        "scripted": scripted,
+    }))
+}
+
+// --- endless/compute_mode ----------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct ComputeModeParams {
+    mode: Option<String>,
+    clamp_bounds: Option<bool>,
+}
+
+/// Switch the NPC compute passes between normal steering and scripted cutscene motion.
+/// Leaving scripted mode zeroes every scripted velocity so the next cutscene starts still.
+pub fn compute_mode_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: ComputeModeParams = parse_optional(params)?;
+    let mode = match p.mode.as_deref() {
+        None => None,
+        Some("normal") => Some(ComputeMode::Normal),
+        Some("scripted") => Some(ComputeMode::Scripted),
+        Some(other) => {
+            return Err(brp_err(format!(
+                "unknown mode {other:?} (expected normal or scripted)"
+            )));
+        }
+    };
+    let mut motion = world.resource_mut::<ScriptedMotion>();
+    if let Some(v) = p.clamp_bounds {
+        motion.clamp_bounds = v;
+    }
+    let zeroed = mode.map(|m| motion.set_mode(m)).unwrap_or_default();
+    let (mode, clamp_bounds, moving) = (motion.mode, motion.clamp_bounds, motion.moving.len());
+    for &idx in &zeroed {
+        world.write_message(GpuUpdateMsg(GpuUpdate::SetVelocity {
+            idx,
+            vx: 0.0,
+            vy: 0.0,
+        }));
+    }
+    toon_ok(json!({
+        "mode": if mode == ComputeMode::Scripted { "scripted" } else { "normal" },
+        "clamp_bounds": clamp_bounds,
+        "moving": moving,
+        "zeroed": zeroed.len(),
+    }))
+}
+
+// --- endless/npc_velocity ----------------------------------------------------
+
+#[derive(Deserialize)]
+struct NpcVelocityParams {
+    slot: usize,
+    vx: f32,
+    vy: f32,
+}
+
+/// Set one NPC's scripted velocity (px/s). Only integrated while the compute mode is
+/// `scripted`; `vx = vy = 0` stops it.
+pub fn npc_velocity_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: NpcVelocityParams = parse_some(params)?;
+    if !p.vx.is_finite() || !p.vy.is_finite() {
+        return Err(brp_err("vx/vy must be finite"));
+    }
+    if world
+        .resource::<EntityMap>()
+        .get_npc(p.slot)
+        .is_none_or(|n| n.dead)
+    {
+        return Err(brp_err(format!("no living NPC at slot {}", p.slot)));
+    }
+    world
+        .resource_mut::<ScriptedMotion>()
+        .set_velocity(p.slot, Vec2::new(p.vx, p.vy));
+    world.write_message(GpuUpdateMsg(GpuUpdate::SetVelocity {
+        idx: p.slot,
+        vx: p.vx,
+        vy: p.vy,
+    }));
+    let scripted = world.resource::<ScriptedMotion>().mode == ComputeMode::Scripted;
+    toon_ok(json!({
+        "slot": p.slot,
+        "vx": r2(p.vx),
+        "vy": r2(p.vy),
+        "active": scripted,
+    }))
+}
+
+// --- endless/select_next_idle ------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct SelectNextIdleParams {
+    town: Option<usize>,
+}
+
+/// Select the next idle, non-fighting NPC of a town (default: the focused one), round-robin in
+/// slot order. Returns its slot and position so a client can jump the camera; slot -1 = none.
+pub fn select_next_idle_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: SelectNextIdleParams = parse_optional(params)?;
+    let towns = &world.resource::<WorldData>().towns;
+    let town = match p.town {
+        Some(t) if t >= towns.len() => return Err(brp_err(format!("no town {t}"))),
+        Some(t) => t,
+        None => world
+            .resource::<crate::resources::PlayerFocus>()
+            .town(towns)
+            .ok_or_else(|| brp_err("no player town"))?,
+    };
+    let idle = {
+        let world: &World = world;
+        world
+            .resource::<EntityMap>()
+            .town_npc_slots_where(town as i32, |n| {
+                world
+                    .get::<Activity>(n.entity)
+                    .is_some_and(Activity::is_idle)
+                    && !world
+                        .get::<CombatState>(n.entity)
+                        .is_some_and(CombatState::is_fighting)
+            })
+    };
+    let Some(slot) = world.resource_mut::<IdleCycle>().next(town, &idle) else {
+        return toon_ok(json!({ "town": town, "slot": -1, "idle": 0 }));
+    };
+    world.resource_mut::<SelectedNpc>().0 = slot as i32;
+    world.resource_mut::<SelectedBuilding>().active = false;
+    let pos = world
+        .resource::<GpuReadState>()
+        .positions
+        .get(slot * 2..slot * 2 + 2)
+        .map_or([0.0, 0.0], |p| [p[0], p[1]]);
+    toon_ok(json!({
+        "town": town,
+        "slot": slot,
+        "x": r2(pos[0]),
+        "y": r2(pos[1]),
+        "idle": idle.len(),
+    }))
+}
+
+// --- endless/loot_config -----------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct LootConfigParams {
+    enabled: Option<bool>,
+    pickup_radius: Option<f32>,
+    lifetime: Option<f32>,
+    max_active: Option<usize>,
+    job: Option<String>,
+    chance: Option<f32>,
+    food: Option<(i32, i32)>,
+    gold: Option<(i32, i32)>,
+    item_chance: Option<f32>,
+    drop_inventory: Option<bool>,
+    #[serde(default)]
+    reset: bool,
+}
+
+/// Read or set corpse loot drops. Drop-rule fields apply to `job` (or the default rule when
+/// no job is given); omitted fields keep their current value. No params = read.
+pub fn loot_config_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: LootConfigParams = parse_optional(params)?;
+    let job = match p.job {
+        Some(ref name) => {
+            Some(parse_job(name).ok_or_else(|| brp_err(format!("unknown job: {name}")))?)
+        }
+        None => None,
+    };
+    for v in [p.pickup_radius, p.lifetime, p.chance, p.item_chance]
+        .into_iter()
+        .flatten()
+    {
+        if !v.is_finite() || v < 0.0 {
+            return Err(brp_err("values must be finite and non-negative"));
+        }
+    }
+    let active = world
+        .query_filtered::<(), With<crate::components::GroundLoot>>()
+        .iter(world)
+        .count();
+
+    let mut config = world.resource_mut::<crate::resources::LootConfig>();
+    if let Some(v) = p.enabled {
+        config.enabled = v;
+    }
+    if let Some(v) = p.pickup_radius {
+        config.pickup_radius = v;
+    }
+    if let Some(v) = p.lifetime {
+        config.lifetime = v;
+    }
+    if let Some(v) = p.max_active {
+        config.max_active = v;
+    }
+    if let Some(v) = p.drop_inventory {
+        config.drop_inventory = v;
+    }
+    if p.reset {
+        match job {
+            Some(job) => {
+                config.per_job.remove(&job);
+            }
+            None => config.default_rule = Default::default(),
+        }
+    } else if p.chance.is_some() || p.food.is_some() || p.gold.is_some() || p.item_chance.is_some()
+    {
+        let mut rule = job.map_or(config.default_rule, |j| config.rule(j));
+        if let Some(v) = p.chance {
+            rule.chance = v.min(1.0);
+        }
+        if let Some((lo, hi)) = p.food {
+            rule.food = (lo.max(0), hi.max(lo.max(0)));
+        }
+        if let Some((lo, hi)) = p.gold {
+            rule.gold = (lo.max(0), hi.max(lo.max(0)));
+        }
+        if let Some(v) = p.item_chance {
+            rule.item_chance = v.min(1.0);
+        }
+        match job {
+            Some(job) => {
+                config.per_job.insert(job, rule);
+            }
+            None => config.default_rule = rule,
+        }
+    }
+
+    let rule_json = |r: &crate::resources::LootDropRule| {
+        json!({
+            "chance": r2(r.chance),
+            "food": [r.food.0, r.food.1],
+            "gold": [r.gold.0, r.gold.1],
+            "item_chance": r2(r.item_chance),
+        })
+    };
+    let jobs: Vec<Value> = config
+        .per_job
+        .iter()
+        .map(|(job, r)| {
+            let mut v = rule_json(r);
+            v["job"] = json!(format!("{job:?}"));
+            v
+        })
+        .collect();
+    toon_ok(json!({
+        "enabled": config.enabled,
+        "pickup_radius": r2(config.pickup_radius),
+        "lifetime": r2(config.lifetime),
+        "max_active": config.max_active,
+        "drop_inventory": config.drop_inventory,
+        "active": active,
+        "default": rule_json(&config.default_rule),
+        "jobs": jobs,
+    }))
+}
+
+// --- endless/transforms / endless/transforms_packed --------------------------
+
+/// Layout version byte at the start of every `endless/transforms_packed` payload.
+pub const TRANSFORMS_PACKED_VERSION: u8 = 1;
+/// Record tag: live NPC transform.
+pub const PACKED_TAG_NPC: u8 = 1;
+/// Bytes per `PACKED_TAG_NPC` record, tag included.
+pub const PACKED_NPC_RECORD_LEN: usize = 16;
+
+/// One live NPC as seen by a sync poll.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NpcTransform {
+    pub slot: u32,
+    pub pos: Vec2,
+    pub job: u8,
+    pub faction: i16,
+}
+
+#[derive(Deserialize, Default)]
+struct TransformsParams {
+    town: Option<i32>,
+}
+
+/// Live NPC transforms in slot order, optionally limited to one town.
+pub fn collect_npc_transforms(world: &World, town: Option<i32>) -> Vec<NpcTransform> {
+    let entity_map = world.resource::<EntityMap>();
+    let positions = &world.resource::<GpuReadState>().positions;
+    let mut out: Vec<NpcTransform> = entity_map
+        .iter_npcs()
+        .filter(|n| !n.dead && town.is_none_or(|t| n.town_idx == t))
+        .filter_map(|n| {
+            let p = positions.get(n.slot * 2..n.slot * 2 + 2)?;
+            (p[0] > -9000.0).then(|| NpcTransform {
+                slot: n.slot as u32,
+                pos: Vec2::new(p[0], p[1]),
+                job: n.job as u8,
+                faction: n.faction as i16,
+            })
+        })
+        .collect();
+    out.sort_unstable_by_key(|t| t.slot);
+    out
+}
+
+/// Encode transforms into the packed layout (all little-endian):
+/// header `version: u8, count: u32`, then `count` records of
+/// `tag: u8 (=1), slot: u32, x: f32, y: f32, job: u8, faction: i16`.
+pub fn encode_transforms_packed(records: &[NpcTransform], out: &mut Vec<u8>) {
+    out.clear();
+    out.reserve(5 + records.len() * PACKED_NPC_RECORD_LEN);
+    out.push(TRANSFORMS_PACKED_VERSION);
+    out.extend_from_slice(&(records.len() as u32).to_le_bytes());
+    for t in records {
+        out.push(PACKED_TAG_NPC);
+        out.extend_from_slice(&t.slot.to_le_bytes());
+        out.extend_from_slice(&t.pos.x.to_le_bytes());
+        out.extend_from_slice(&t.pos.y.to_le_bytes());
+        out.push(t.job);
+        out.extend_from_slice(&t.faction.to_le_bytes());
+    }
+}
+
+/// Standard base64 (RFC 4648, padded) — JSON-RPC results can't carry raw bytes.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | ((b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Per-NPC transforms as a readable list. Convenient, but one object per NPC —
+/// pollers syncing hundreds of units per frame should use `endless/transforms_packed`.
+pub fn transforms_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: TransformsParams = parse_optional(params)?;
+    let npcs: Vec<Value> = collect_npc_transforms(world, p.town)
+        .iter()
+        .map(|t| {
+            json!({
+                "slot": t.slot,
+                "x": r2(t.pos.x),
+                "y": r2(t.pos.y),
+                "job": t.job,
+                "faction": t.faction,
+            })
+        })
+        .collect();
+    toon_ok(json!({ "count": npcs.len(), "npcs": npcs }))
+}
+
+/// Same records as `endless/transforms`, packed into one base64 binary blob
+/// (layout in `encode_transforms_packed`, docs/brp.md).
+pub fn transforms_packed_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: TransformsParams = parse_optional(params)?;
+    let records = collect_npc_transforms(world, p.town);
+    let mut bytes = Vec::new();
+    encode_transforms_packed(&records, &mut bytes);
+    toon_ok(json!({
+        "version": TRANSFORMS_PACKED_VERSION,
+        "count": records.len(),
+        "bytes": bytes.len(),
+        "data": base64_encode(&bytes),
+    }))
+}
+
+// --- endless/locations -------------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct LocationsParams {
+    x0: Option<f32>,
+    y0: Option<f32>,
+    x1: Option<f32>,
+    y1: Option<f32>,
+}
+
+/// One entry of the world layout: a town center (`kind: None`, `index` = town) or a
+/// building (`index` = its entity slot).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location {
+    pub kind: Option<BuildingKind>,
+    pub index: usize,
+    pub pos: Vec2,
+    pub town_idx: i32,
+}
+
+/// Every town center and live building in one pass, sorted by kind then index.
+/// `bounds` keeps only locations inside the rect.
+pub fn collect_locations(
+    world_data: &WorldData,
+    entity_map: &EntityMap,
+    bounds: Option<Rect>,
+) -> Vec<Location> {
+    let inside = |p: Vec2| bounds.is_none_or(|r| r.contains(p));
+    let mut out: Vec<Location> = world_data
+        .towns
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| inside(t.center))
+        .map(|(i, t)| Location {
+            kind: None,
+            index: i,
+            pos: t.center,
+            town_idx: i as i32,
+        })
+        .collect();
+    out.extend(
+        entity_map
+            .iter_instances()
+            .filter(|inst| inst.position.x > -9000.0 && inside(inst.position))
+            .map(|inst| Location {
+                kind: Some(inst.kind),
+                index: inst.slot,
+                pos: inst.position,
+                town_idx: inst.town_idx as i32,
+            }),
+    );
+    out.sort_unstable_by_key(|l| (l.kind.map_or(0, |k| k as usize + 1), l.index));
+    out
+}
+
+/// Whole world layout (town centers + all buildings) in one call, for drawing maps
+/// without per-location queries. Pass `x0,y0,x1,y1` to load one region at a time.
+pub fn locations_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: LocationsParams = parse_optional(params)?;
+    let bounds = match (p.x0, p.y0, p.x1, p.y1) {
+        (Some(x0), Some(y0), Some(x1), Some(y1)) => {
+            Some(Rect::from_corners(Vec2::new(x0, y0), Vec2::new(x1, y1)))
+        }
+        (None, None, None, None) => None,
+        _ => return Err(brp_err("bounds need all of x0, y0, x1, y1")),
+    };
+    let locations: Vec<Value> = collect_locations(
+        world.resource::<WorldData>(),
+        world.resource::<EntityMap>(),
+        bounds,
+    )
+    .iter()
+    .map(|l| {
+        let kind = l
+            .kind
+            .map_or("TownCenter".to_string(), |k| format!("{k:?}"));
+        json!({
+            "type": kind,
+            "index": l.index,
+            "x": r2(l.pos.x),
+            "y": r2(l.pos.y),
+            "town_idx": l.town_idx,
+        })
+    })
+    .collect();
+    toon_ok(json!({ "count": locations.len(), "locations": locations }))
+}
+
+// --- endless/version ---------------------------------------------------------
+
+pub fn version_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult {
+    let info = world.resource::<crate::resources::VersionInfo>();
+    toon_ok(json!({
+        "crate_version": info.crate_version,
+        "build_commit": info.build_commit,
+        "build_timestamp": info.build_timestamp,
+        "bevy_version": info.bevy_version,
+        "gpu_device_name": info.gpu_device_name,
+        "gpu_backend": info.gpu_backend,
+        "gpu_driver": info.gpu_driver,
+        "summary": info.summary(),
+    }))
+}
+
+// --- endless/target_priority -------------------------------------------------
+
+#[derive(Deserialize)]
+struct TargetPriorityParams {
+    entity: Option<String>,
+    squad: Option<usize>,
+    profile: String,
+}
+
+fn parse_target_priority(s: &str) -> Option<TargetPriority> {
+    match s {
+        "Nearest" => Some(TargetPriority::Nearest),
+        "LowestHp" => Some(TargetPriority::LowestHp),
+        "HighestThreat" => Some(TargetPriority::HighestThreat),
+        _ => None,
+    }
+}
+
+pub fn target_priority_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: TargetPriorityParams = parse_some(params)?;
+    let profile = parse_target_priority(&p.profile).ok_or_else(|| {
+        brp_err(format!(
+            "unknown profile '{}' (Nearest, LowestHp, HighestThreat)",
+            p.profile
+        ))
+    })?;
+
+    match (p.entity, p.squad) {
+        (Some(entity_str), None) => {
+            let entity = parse_entity_str(&entity_str)?;
+            let (slot, town) = {
+                let entity_map = world.resource::<EntityMap>();
+                let slot = entity_map
+                    .slot_for_entity(entity)
+                    .ok_or_else(|| brp_err(format!("no entity for {entity:?}")))?;
+                let npc = entity_map
+                    .get_npc(slot)
+                    .ok_or_else(|| brp_err(format!("entity {entity_str} is not an NPC")))?;
+                if npc.dead {
+                    return Err(brp_err(format!("npc #{slot} is dead")));
+                }
+                (slot, npc.town_idx)
+            };
+            if town >= 0 {
+                check_town_allowed(world, town as usize)?;
+                queue_llm_log(
+                    world,
+                    town as usize,
+                    format!("npc #{slot} target priority {}", profile.label()),
+                    None,
+                );
+            }
+            world.entity_mut(entity).insert(profile);
+            toon_ok(json!({"status": "ok", "slot": slot, "profile": profile.label()}))
+        }
+        (None, Some(si)) => {
+            let town = {
+                let state = world.resource::<SquadState>();
+                let squad = state
+                    .squads
+                    .get(si)
+                    .ok_or_else(|| brp_err(format!("squad {si} out of range")))?;
+                match squad.owner {
+                    SquadOwner::Player => 0,
+                    SquadOwner::Town(tdi) => tdi,
+                }
+            };
+            check_town_allowed(world, town)?;
+            queue_llm_log(
+                world,
+                town,
+                format!("squad {si} target priority {}", profile.label()),
+                None,
+            );
+            let mut state = world.resource_mut::<SquadState>();
+            state.squads[si].target_priority = profile;
+            let members = state.squads[si].members.len();
+            toon_ok(json!({
+                "status": "ok",
+                "squad": si,
+                "members": members,
+                "profile": profile.label(),
+            }))
+        }
+        _ => Err(brp_err("provide exactly one of 'entity' or 'squad'")),
+    }
+}
+
+// --- endless/combat_stance --------------------------------------------------
+
+#[derive(Deserialize)]
+struct CombatStanceParams {
+    entity: Option<String>,
+    squad: Option<usize>,
+    stance: String,
+}
+
+/// `Squad` parses to None: drop the unit's own stance and follow its squad again.
+fn parse_combat_stance(s: &str) -> Option<Option<CombatStance>> {
+    match s {
+        "FireAtWill" => Some(Some(CombatStance::FireAtWill)),
+        "ReturnFire" => Some(Some(CombatStance::ReturnFire)),
+        "HoldFire" => Some(Some(CombatStance::HoldFire)),
+        "Squad" => Some(None),
+        _ => None,
+    }
+}
+
+/// Give a unit its own stance (a `StanceOverride` its squad stance won't touch), or clear it.
+fn set_unit_stance(world: &mut World, entity: Entity, stance: Option<CombatStance>) -> bool {
+    let Ok(mut em) = world.get_entity_mut(entity) else {
+        return false;
+    };
+    match stance {
+        Some(stance) => {
+            em.insert((stance, StanceOverride))
+                .remove::<(SquadStanceApplied, EngageZone)>();
+        }
+        None => {
+            em.remove::<(CombatStance, StanceOverride)>();
+        }
+    }
+    true
+}
+
+/// Set a unit's stance, or stamp it onto every current member of a squad. `Squad` clears
+/// the override so the squad stance applies again.
+pub fn combat_stance_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: CombatStanceParams = parse_some(params)?;
+    let stance = parse_combat_stance(&p.stance).ok_or_else(|| {
+        brp_err(format!(
+            "unknown stance '{}' (FireAtWill, ReturnFire, HoldFire, Squad)",
+            p.stance
+        ))
+    })?;
+    let label = stance.map_or("Squad", |s| s.label());
+
+    match (p.entity, p.squad) {
+        (Some(entity_str), None) => {
+            let entity = parse_entity_str(&entity_str)?;
+            let (slot, town) = {
+                let entity_map = world.resource::<EntityMap>();
+                let slot = entity_map
+                    .slot_for_entity(entity)
+                    .ok_or_else(|| brp_err(format!("no entity for {entity:?}")))?;
+                let npc = entity_map
+                    .get_npc(slot)
+                    .ok_or_else(|| brp_err(format!("entity {entity_str} is not an NPC")))?;
+                if npc.dead {
+                    return Err(brp_err(format!("npc #{slot} is dead")));
+                }
+                (slot, npc.town_idx)
+            };
+            if town >= 0 {
+                check_town_allowed(world, town as usize)?;
+                queue_llm_log(
+                    world,
+                    town as usize,
+                    format!("npc #{slot} stance {label}"),
+                    None,
+                );
+            }
+            set_unit_stance(world, entity, stance);
+            toon_ok(json!({"status": "ok", "slot": slot, "stance": label}))
+        }
+        (None, Some(si)) => {
+            let (town, members) = {
+                let state = world.resource::<SquadState>();
+                let squad = state
+                    .squads
+                    .get(si)
+                    .ok_or_else(|| brp_err(format!("squad {si} out of range")))?;
+                let town = match squad.owner {
+                    SquadOwner::Player => 0,
+                    SquadOwner::Town(tdi) => tdi,
+                };
+                (town, squad.members.clone())
+            };
+            check_town_allowed(world, town)?;
+            queue_llm_log(world, town, format!("squad {si} stance {label}"), None);
+            let applied = members
+                .into_iter()
+                .filter(|&m| set_unit_stance(world, m, stance))
+                .count();
+            toon_ok(json!({
+                "status": "ok",
+                "squad": si,
+                "members": applied,
+                "stance": label,
+            }))
+        }
+        _ => Err(brp_err("provide exactly one of 'entity' or 'squad'")),
+    }
+}
+
+// --- endless/squad_stance ---------------------------------------------------
+
+#[derive(Deserialize)]
+struct SquadStanceParams {
+    squad: usize,
+    stance: String,
+    engage_radius: Option<f32>,
+}
+
+/// Set a squad's stance and engage radius. Members without their own stance pick it up
+/// next tick and drop it when they leave the squad.
+pub fn squad_stance_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: SquadStanceParams = parse_some(params)?;
+    let stance = match p.stance.as_str() {
+        "Aggressive" => SquadStance::Aggressive,
+        "Defensive" => SquadStance::Defensive,
+        "Passive" => SquadStance::Passive,
+        other => {
+            return Err(brp_err(format!(
+                "unknown stance '{other}' (Aggressive, Defensive, Passive)"
+            )));
+        }
+    };
+    if p.engage_radius.is_some_and(|r| r.is_nan() || r < 0.0) {
+        return Err(brp_err("engage_radius must be >= 0"));
+    }
+    let (town, radius) = {
+        let state = world.resource::<SquadState>();
+        let squad = state
+            .squads
+            .get(p.squad)
+            .ok_or_else(|| brp_err(format!("squad {} out of range", p.squad)))?;
+        let town = match squad.owner {
+            SquadOwner::Player => 0,
+            SquadOwner::Town(tdi) => tdi,
+        };
+        (town, p.engage_radius.unwrap_or(squad.engage_radius))
+    };
+    check_town_allowed(world, town)?;
+    queue_llm_log(
+        world,
+        town,
+        format!("squad {} stance {}", p.squad, stance.label()),
+        None,
+    );
+    world
+        .resource_mut::<SquadState>()
+        .set_squad_stance(p.squad, stance, radius);
+    toon_ok(json!({
+        "status": "ok",
+        "squad": p.squad,
+        "stance": stance.label(),
+        "engage_radius": r2(radius),
+    }))
+}
+
+// --- endless/world_bounds ---------------------------------------------------
+
+pub fn world_bounds_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult {
+    let bounds = world.resource::<crate::resources::WorldBounds>();
+    toon_ok(json!({
+        "set": bounds.is_set(),
+        "min_x": r2(bounds.min.x),
+        "min_y": r2(bounds.min.y),
+        "max_x": r2(bounds.max.x),
+        "max_y": r2(bounds.max.y),
+    }))
+}
+
+// --- endless/performance_budget ---------------------------------------------
+
+#[derive(Deserialize)]
+struct PerformanceBudgetParams {
+    target_ms: f32,
+}
+
+pub fn performance_budget_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: PerformanceBudgetParams = parse_some(params)?;
+    if !p.target_ms.is_finite() || p.target_ms < 0.0 {
+        return Err(brp_err(format!(
+            "target_ms must be >= 0 (got {})",
+            p.target_ms
+        )));
+    }
+    world
+        .resource_mut::<crate::resources::QualityState>()
+        .set_budget(p.target_ms);
+    toon_ok(json!({"status": "ok", "budget_ms": r2(p.target_ms), "level": 0}))
+}
+
+// --- endless/quality ---------------------------------------------------------
+
+pub fn quality_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult {
+    let q = world.resource::<crate::resources::QualityState>();
+    toon_ok(json!({
+        "level": q.level,
+        "max_level": crate::constants::QUALITY_MAX_LEVEL,
+        "budget_ms": r2(q.budget_ms),
+        "smoothed_ms": r2(q.smoothed_ms),
+        "readback_mult": q.readback_mult(),
+        "lod_mult": r2(q.lod_mult()),
+        "projectile_cap": q.projectile_cap(),
+        "separation_allowed": q.separation_allowed(),
+    }))
+}
+
+// --- endless/separation ------------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct SeparationParams {
+    enabled: Option<bool>,
+    mode: Option<crate::resources::SeparationMode>,
+}
+
+fn separation_json(world: &World) -> Value {
+    let sep = world.resource::<crate::resources::SeparationState>();
+    let allowed = world
+        .resource::<crate::resources::QualityState>()
+        .separation_allowed();
+    let entity_count = world.resource::<GpuSlotPool>().count();
+    json!({
+        "enabled": sep.enabled,
+        "quality_allowed": allowed,
+        "active": sep.enabled && allowed,
+        "ramp": r2(sep.ramp),
+        "mode": sep.mode.label(),
+        "effective_mode": sep.effective_mode(entity_count).label(),
+        "brute_force_max": crate::constants::SEPARATION_BRUTE_FORCE_MAX,
+        "max_per_cell": world.resource::<crate::gpu::GridConfig>().max_per_cell,
+    })
+}
+
+/// Read or toggle NPC separation. Off zeroes the separation push, so units overlap; dodge,
+/// anchored push and arrival keep working. Re-enabling ramps the push back in over a few frames.
+/// `mode` switches between the grid scan and the brute-force reference for A/B comparisons.
+pub fn separation_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: SeparationParams = parse_optional(params)?;
+    let mut sep = world.resource_mut::<crate::resources::SeparationState>();
+    if let Some(enabled) = p.enabled {
+        sep.enabled = enabled;
+    }
+    if let Some(mode) = p.mode {
+        sep.set_mode(mode);
+    }
+    toon_ok(separation_json(world))
+}
+
+// --- endless/threat_map ------------------------------------------------------
+
+#[derive(Deserialize)]
+struct ThreatMapParams {
+    faction: i32,
+    resolution: Option<f32>,
+}
+
+/// Enemy strength grid as seen by `faction`, optionally downsampled to `resolution` px cells.
+pub fn threat_map_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: ThreatMapParams = parse_some(params)?;
+    let tm = world.resource::<crate::systems::ThreatMap>();
+    let grid = tm.get_threat_map(p.faction, p.resolution.unwrap_or(0.0));
+    let totals: Vec<Value> = tm
+        .faction_totals()
+        .into_iter()
+        .map(|(faction, strength)| json!({ "faction": faction, "strength": strength }))
+        .collect();
+    toon_ok(json!({
+        "faction": p.faction,
+        "cell_size": grid.cell_size,
+        "cols": grid.cols,
+        "rows": grid.rows,
+        "updated_at": r2(tm.updated_at),
+        "values": grid.values,
+        "totals": totals,
+    }))
+}
+
+// --- endless/spawn_fade ------------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct SpawnFadeParams {
+    duration: Option<f32>,
+}
+
+/// Read or set how long newly spawned NPCs take to fade in (seconds, 0 = instant).
+pub fn spawn_fade_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: SpawnFadeParams = parse_optional(params)?;
+    let mut gpu_state = world.resource_mut::<crate::gpu::EntityGpuState>();
+    if let Some(secs) = p.duration {
+        gpu_state.set_spawn_fade_duration(secs);
+    }
+    toon_ok(json!({ "duration": r2(gpu_state.spawn_fade_secs) }))
+}
+
+// --- endless/pixel_snap ------------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct PixelSnapParams {
+    enabled: Option<bool>,
+}
+
+/// Read or toggle sprite pixel snapping. Saved to settings.json when the pause menu next closes.
+pub fn pixel_snap_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: PixelSnapParams = parse_optional(params)?;
+    let mut settings = world.resource_mut::<crate::settings::UserSettings>();
+    if let Some(enabled) = p.enabled {
+        settings.pixel_snap = enabled;
+    }
+    toon_ok(json!({
+        "enabled": settings.pixel_snap,
+        "max_zoom": crate::render::PIXEL_SNAP_MAX_ZOOM,
+    }))
+}
+
+// --- endless/death_knockback -------------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct DeathKnockbackParams {
+    strength: Option<f32>,
+}
+
+/// Read or set how far killed NPCs slide from the killing blow (px, 0 = off).
+pub fn death_knockback_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: DeathKnockbackParams = parse_optional(params)?;
+    let mut knockback = world.resource_mut::<crate::systems::DeathKnockback>();
+    if let Some(strength) = p.strength {
+        knockback.set_death_knockback(strength);
+    }
+    toon_ok(json!({
+        "strength": r2(knockback.strength),
+        "max": crate::constants::DEATH_KNOCKBACK_MAX,
+        "duration": crate::constants::DEATH_KNOCKBACK_SECS,
+        "sliding": knockback.corpses.len(),
+    }))
+}
+
+// --- endless/projectile_limits / projectile_debug ----------------------------
+
+#[derive(Deserialize)]
+struct ProjectileLimitsParams {
+    per_shooter_per_sec: Option<u32>,
+    global_cap: Option<usize>,
+    evict_oldest: Option<bool>,
+}
+
+fn projectile_status(alloc: &ProjSlotAllocator) -> Value {
+    json!({
+        "live": alloc.alive(),
+        "pool_max": crate::constants::MAX_PROJECTILES,
+        "quality_cap": alloc.combat_cap,
+        "effective_cap": alloc.effective_cap(),
+        "per_shooter_per_sec": alloc.limits.per_shooter_per_sec,
+        "global_cap": alloc.limits.global_cap,
+        "evict_oldest": alloc.limits.evict_oldest,
+        "dropped_rate_limited": alloc.drops.rate_limited,
+        "dropped_at_capacity": alloc.drops.at_capacity,
+        "evicted": alloc.drops.evicted,
+    })
+}
+
+pub fn projectile_limits_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: ProjectileLimitsParams = parse_some(params)?;
+    let mut alloc = world.resource_mut::<ProjSlotAllocator>();
+    let mut limits = alloc.limits;
+    if let Some(v) = p.per_shooter_per_sec {
+        limits.per_shooter_per_sec = v;
+    }
+    if let Some(v) = p.global_cap {
+        limits.global_cap = v.min(crate::constants::MAX_PROJECTILES);
+    }
+    if let Some(v) = p.evict_oldest {
+        limits.evict_oldest = v;
+    }
+    alloc.set_limits(limits);
+    toon_ok(projectile_status(&alloc))
+}
+
+pub fn projectile_debug_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult {
+    toon_ok(projectile_status(world.resource::<ProjSlotAllocator>()))
+}
+
+// --- endless/active_projectiles ----------------------------------------------
+
+#[derive(Deserialize, Default)]
+struct ActiveProjectilesParams {
+    limit: Option<usize>,
+}
+
+/// Every live projectile (capped): pool slot, live position, launch velocity, faction,
+/// damage, spawn lifetime and kind. `idx` matches the `proj_idx` in hit events.
+pub fn active_projectiles_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: ActiveProjectilesParams = parse_optional(params)?;
+    let cap = p
+        .limit
+        .unwrap_or(crate::constants::ACTIVE_PROJECTILES_QUERY_MAX)
+        .min(crate::constants::ACTIVE_PROJECTILES_QUERY_MAX);
+    let writes = world.resource::<crate::gpu::ProjBufferWrites>();
+    let live = &world.resource::<ProjPositionState>().0;
+    let (projectiles, total) = writes.active_projectiles(live, cap);
+    toon_ok(json!({
+        "total": total,
+        "truncated": total > projectiles.len(),
+        "projectiles": projectiles,
+    }))
+}
+
+// --- endless/win_condition / game_state --------------------------------------
+
+#[derive(Deserialize)]
+struct WinConditionParams {
+    faction: Option<i32>,
+    victory: Option<crate::systems::victory::Condition>,
+    defeat: Option<crate::systems::victory::Condition>,
+    pause_on_end: Option<bool>,
+}
+
+fn game_state_json(win: &crate::systems::WinCondition) -> Value {
+    json!({
+        "state": win.get_game_state(),
+        "reason": win.reason,
+        "ended_day": win.ended_day,
+        "faction": win.faction,
+        "victory": win.victory,
+        "defeat": win.defeat,
+        "pause_on_end": win.pause_on_end,
+    })
+}
+
+/// Replace the scenario's victory/defeat conditions and restart the check. Omitting both
+/// conditions turns the check off.
+pub fn win_condition_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: WinConditionParams = parse_some(params)?;
+    let mut win = world.resource_mut::<crate::systems::WinCondition>();
+    let faction = p.faction.unwrap_or(win.faction);
+    let pause_on_end = p.pause_on_end.unwrap_or(win.pause_on_end);
+    win.set_win_condition(faction, p.victory, p.defeat, pause_on_end);
+    toon_ok(game_state_json(&win))
+}
+
+/// Scenario state (ongoing/won/lost) plus game over events since the last call.
+pub fn game_state_handler(In(_params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let events: Vec<crate::messages::GameOverMsg> = world
+        .resource_mut::<crate::resources::GameOverOutbox>()
+        .0
+        .drain(..)
+        .collect();
+    let mut data = game_state_json(world.resource::<crate::systems::WinCondition>());
+    data["events"] = json!(events);
+    toon_ok(data)
+}
+
+// --- endless/behavior_lod ----------------------------------------------------
+
+#[derive(Deserialize)]
+struct BehaviorLodParams {
+    enabled: Option<bool>,
+    stride: Option<usize>,
+    margin: Option<f32>,
+}
+
+pub fn behavior_lod_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: BehaviorLodParams = parse_some(params)?;
+    if p.stride == Some(0) {
+        return Err(brp_err("stride must be >= 1"));
+    }
+    if p.margin.is_some_and(|m| !m.is_finite() || m < 0.0) {
+        return Err(brp_err("margin must be >= 0"));
+    }
+    {
+        let mut lod = world.resource_mut::<BehaviorLod>();
+        if let Some(v) = p.enabled {
+            lod.enabled = v;
+        }
+        if let Some(v) = p.stride {
+            lod.stride = v;
+        }
+        if let Some(v) = p.margin {
+            lod.margin = v;
+        }
+    }
+
+    let lod = world.resource::<BehaviorLod>();
+    let positions = &world.resource::<GpuReadState>().positions;
+    let (mut near, mut far) = (0usize, 0usize);
+    for npc in world
+        .resource::<EntityMap>()
+        .iter_npcs()
+        .filter(|n| !n.dead)
+    {
+        let pos = positions
+            .get(npc.slot * 2..npc.slot * 2 + 2)
+            .map_or(Vec2::ZERO, |p| Vec2::new(p[0], p[1]));
+        if lod.is_far(pos) {
+            far += 1;
+        } else {
+            near += 1;
+        }
+    }
+    toon_ok(json!({
+        "enabled": lod.enabled,
+        "stride": lod.stride,
+        "active_stride": lod.active_stride(),
+        "margin": r2(lod.margin),
+        "camera": [r2(lod.camera_center.x), r2(lod.camera_center.y)],
+        "view_half": [r2(lod.view_half.x), r2(lod.view_half.y)],
+        "near_npcs": near,
+        "far_npcs": far,
+    }))
+}
+
+// --- endless/migrate ---------------------------------------------------------
+
+#[derive(Deserialize)]
+struct MigrateParams {
+    from_town: usize,
+    x: f32,
+    y: f32,
+    members: Vec<usize>,
+}
+
+pub fn migrate_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: MigrateParams = parse_some(params)?;
+    check_town_allowed(world, p.from_town)?;
+    let to_pos = Vec2::new(p.x, p.y);
+    let bounds = *world.resource::<WorldBounds>();
+    if bounds.is_set() && !bounds.contains(to_pos) {
+        return Err(brp_err(format!(
+            "destination ({:.0},{:.0}) outside world bounds",
+            p.x, p.y
+        )));
+    }
+    let join_town = crate::systems::validate_migration(
+        world.resource::<WorldData>(),
+        world.resource::<EntityMap>(),
+        world.resource::<MigrationState>(),
+        p.from_town,
+        to_pos,
+        &p.members,
+    )
+    .map_err(brp_err)?;
+
+    queue_llm_log(
+        world,
+        p.from_town,
+        format!(
+            "migrate {} npcs to ({:.0},{:.0})",
+            p.members.len(),
+            p.x,
+            p.y
+        ),
+        Some(to_pos),
+    );
+    let count = p.members.len();
+    world
+        .resource_mut::<MigrationState>()
+        .requests
+        .push(MigrationRequest::Start {
+            from_town: p.from_town,
+            to_pos,
+            member_slots: p.members,
+        });
+    toon_ok(json!({
+        "status": "queued",
+        "members": count,
+        "join_town": join_town,
+    }))
+}
+
+// --- endless/migration -------------------------------------------------------
+
+pub fn migration_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult {
+    let state = world.resource::<MigrationState>();
+    let Some(mg) = &state.active else {
+        return toon_ok(json!({"active": false}));
+    };
+    let entity_map = world.resource::<EntityMap>();
+    let mut sum = Vec2::ZERO;
+    let mut alive = 0u32;
+    for &slot in &mg.member_slots {
+        let Some(npc) = entity_map.get_npc(slot).filter(|n| !n.dead) else {
+            continue;
+        };
+        if let Some(pos) = world.get::<crate::components::Position>(npc.entity) {
+            sum += Vec2::new(pos.x, pos.y);
+            alive += 1;
+        }
+    }
+    let center = (alive > 0).then(|| sum / alive as f32);
+    toon_ok(json!({
+        "active": true,
+        "phase": if mg.boat_slot.is_some() { "boat" } else { "walking" },
+        "scripted": mg.source_town.is_some(),
+        "source_town": mg.source_town,
+        "join_town": mg.join_town,
+        "faction": mg.faction,
+        "target_x": r2(mg.settle_target.x),
+        "target_y": r2(mg.settle_target.y),
+        "members": mg.member_slots.len(),
+        "alive": alive,
+        "center_x": center.map(|c| r2(c.x)),
+        "center_y": center.map(|c| r2(c.y)),
+        "distance": center.map(|c| r2(c.distance(mg.settle_target))),
+        "settle_radius": crate::constants::RAIDER_SETTLE_RADIUS,
+    }))
+}
+
+// --- endless/cancel_migration ------------------------------------------------
+
+pub fn cancel_migration_handler(In(_params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let source = {
+        let state = world.resource::<MigrationState>();
+        match &state.active {
+            None => return Err(brp_err("no active migration")),
+            Some(mg) => mg
+                .source_town
+                .ok_or_else(|| brp_err("endless-mode migrations cannot be cancelled"))?,
+        }
+    };
+    check_town_allowed(world, source)?;
+    queue_llm_log(world, source, "cancel migration".into(), None);
+    world
+        .resource_mut::<MigrationState>()
+        .requests
+        .push(MigrationRequest::Cancel);
+    toon_ok(json!({"status": "queued", "source_town": source}))
+}
+
+// --- endless/select_in_rect --------------------------------------------------
+
+#[derive(Deserialize)]
+struct SelectInRectParams {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    /// Faction to keep (default: player). Ignored when `any_faction` is set.
+    faction: Option<i32>,
+    #[serde(default)]
+    any_faction: bool,
+    limit: Option<usize>,
+}
+
+pub fn select_in_rect_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: SelectInRectParams = parse_some(params)?;
+    let faction = p.faction.unwrap_or(crate::constants::FACTION_PLAYER);
+    let limit = p
+        .limit
+        .unwrap_or(crate::constants::SELECT_RECT_MAX)
+        .min(crate::constants::SELECT_RECT_MAX);
+    // One past the limit tells a full page apart from a truncated one
+    let mut slots = world.resource::<EntityMap>().npcs_in_rect(
+        &world.resource::<GpuReadState>().positions,
+        Vec2::new(p.x0, p.y0),
+        Vec2::new(p.x1, p.y1),
+        limit + 1,
+        |n| p.any_faction || n.faction == faction,
+    );
+    let capped = slots.len() > limit;
+    slots.truncate(limit);
+    toon_ok(json!({
+        "count": slots.len(),
+        "capped": capped,
+        "slots": slots,
+    }))
+}
+
+// --- endless/selection_bounds ------------------------------------------------
+
+pub fn selection_bounds_handler(In(_params): In<Option<Value>>, world: &World) -> BrpResult {
+    let Some((min, max)) = world.resource::<SquadState>().selection_bounds() else {
+        return toon_ok(json!({"active": false}));
+    };
+    toon_ok(json!({
+        "active": true,
+        "min_x": r2(min.x),
+        "min_y": r2(min.y),
+        "max_x": r2(max.x),
+        "max_y": r2(max.y),
+    }))
+}
+
+// --- endless/sprite_mapping --------------------------------------------------
+
+#[derive(Deserialize)]
+struct SpriteMappingParams {
+    job: Option<String>,
+    /// "Idle" | "Working" | "Resting" | "Fighting".
+    state: Option<String>,
+    col: Option<f32>,
+    row: Option<f32>,
+    /// Remove the (job, state) override instead of setting it.
+    #[serde(default)]
+    clear: bool,
+}
+
+fn parse_sprite_state(s: &str) -> Option<SpriteState> {
+    SpriteState::ALL.into_iter().find(|st| st.label() == s)
+}
+
+fn parse_job(s: &str) -> Option<crate::components::Job> {
+    crate::constants::NPC_REGISTRY
+        .iter()
+        .find(|d| format!("{:?}", d.job) == s)
+        .map(|d| d.job)
+}
+
+pub fn sprite_mapping_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: SpriteMappingParams = parse_some(params)?;
+    if p.job.is_some() || p.state.is_some() {
+        let (Some(job_s), Some(state_s)) = (&p.job, &p.state) else {
+            return Err(brp_err("job and state are both required"));
+        };
+        let job = parse_job(job_s).ok_or_else(|| brp_err(format!("unknown job: {job_s}")))?;
+        let state = parse_sprite_state(state_s).ok_or_else(|| {
+            brp_err(format!(
+                "unknown state '{state_s}' (Idle, Working, Resting, Fighting)"
+            ))
+        })?;
+        let frame = if p.clear {
+            None
+        } else {
+            match (p.col, p.row) {
+                (Some(c), Some(r)) if c >= 0.0 && r >= 0.0 => Some((c, r)),
+                (Some(_), Some(_)) => return Err(brp_err("col and row must be >= 0")),
+                _ => return Err(brp_err("col and row are required (or clear: true)")),
+            }
+        };
+        world.resource_mut::<SpriteTable>().set(job, state, frame);
+    }
+
+    let table = world.resource::<SpriteTable>();
+    let mut mappings: Vec<Value> = table
+        .overrides
+        .iter()
+        .map(|((job, state), (col, row))| {
+            json!({"job": format!("{job:?}"), "state": state.label(), "col": col, "row": row})
+        })
+        .collect();
+    mappings.sort_by_key(|m| m.to_string());
+    toon_ok(json!({"count": mappings.len(), "mappings": mappings}))
+}
+
+// --- endless/sprite_atlas ----------------------------------------------------
+
+#[derive(Deserialize)]
+struct SpriteAtlasParams {
+    /// "character" | "world" | "item" | "location".
+    atlas: String,
+    /// Asset path of a replacement texture (omit to only change the layout).
+    path: Option<String>,
+    sheet_w: Option<f32>,
+    sheet_h: Option<f32>,
+    sprite_w: Option<f32>,
+    sprite_h: Option<f32>,
+    margin: Option<f32>,
+}
+
+pub fn sprite_atlas_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    use crate::render::{AtlasSwap, AtlasSwapQueue, AtlasTarget};
+    let p: SpriteAtlasParams = parse_some(params)?;
+    let target = match p.atlas.as_str() {
+        "character" => AtlasTarget::Character,
+        "world" => AtlasTarget::World,
+        "item" => AtlasTarget::Item,
+        "location" => AtlasTarget::Location,
+        other => {
+            return Err(brp_err(format!(
+                "unknown atlas '{other}' (character, world, item, location)"
+            )));
+        }
+    };
+    let textures = &world
+        .get_resource::<crate::gpu::RenderFrameConfig>()
+        .ok_or_else(|| brp_err("renderer not available"))?
+        .textures;
+    let current = match target {
+        AtlasTarget::Character => textures.char_layout,
+        AtlasTarget::World => textures.world_layout,
+        AtlasTarget::Item => textures.extras_layout,
+        AtlasTarget::Location => textures.building_layout,
+    };
+    let layout = crate::gpu::AtlasLayout {
+        sheet_w: p.sheet_w.unwrap_or(current.sheet_w),
+        sheet_h: p.sheet_h.unwrap_or(current.sheet_h),
+        sprite_w: p.sprite_w.unwrap_or(current.sprite_w),
+        sprite_h: p.sprite_h.unwrap_or(current.sprite_h),
+        margin: p.margin.unwrap_or(current.margin),
+    };
+    layout.validate().map_err(brp_err)?;
+    let Some(mut queue) = world.get_resource_mut::<AtlasSwapQueue>() else {
+        return Err(brp_err("renderer not available"));
+    };
+    // atlas_swap_system applies it on a later frame (after the texture loads, if any)
+    queue.requests.push(AtlasSwap {
+        target,
+        path: p.path.clone(),
+        layout,
+    });
+    toon_ok(json!({
+        "status": "queued",
+        "atlas": p.atlas,
+        "path": p.path,
+        "sheet": [layout.sheet_w, layout.sheet_h],
+        "sprite": [layout.sprite_w, layout.sprite_h],
+        "margin": layout.margin,
+    }))
+}
+
+// --- endless/quick_battle ----------------------------------------------------
+
+#[derive(Deserialize)]
+struct ArmyUnitParams {
+    job: String,
+    count: usize,
+}
+
+#[derive(Deserialize)]
+struct ArmyTraitParams {
+    /// TraitKind name, e.g. "Power". Negative magnitude = the opposite pole.
+    kind: String,
+    magnitude: f32,
+}
+
+#[derive(Deserialize)]
+struct ArmyParams {
+    units: Vec<ArmyUnitParams>,
+    level: Option<i32>,
+    #[serde(default)]
+    traits: Vec<ArmyTraitParams>,
+}
+
+#[derive(Deserialize)]
+struct QuickBattleParams {
+    a: ArmyParams,
+    b: ArmyParams,
+    x: Option<f32>,
+    y: Option<f32>,
+    gap: Option<f32>,
+    seed: Option<u64>,
+}
+
+fn parse_army(p: &ArmyParams) -> Result<crate::systems::quick_battle::ArmySpec, BrpError> {
+    let mut units = Vec::with_capacity(p.units.len());
+    for u in &p.units {
+        let job = parse_job(&u.job).ok_or_else(|| brp_err(format!("unknown job: {}", u.job)))?;
+        units.push((job, u.count));
+    }
+    if p.traits.len() > 2 {
+        return Err(brp_err("at most 2 traits per army"));
+    }
+    let mut traits = Vec::with_capacity(p.traits.len());
+    for t in &p.traits {
+        let kind = crate::components::TraitKind::ALL
+            .into_iter()
+            .find(|k| format!("{k:?}") == t.kind)
+            .ok_or_else(|| brp_err(format!("unknown trait: {}", t.kind)))?;
+        traits.push(crate::components::TraitInstance {
+            kind,
+            magnitude: t.magnitude.clamp(-1.5, 1.5),
+        });
+    }
+    let level = p.level.unwrap_or(0);
+    let max_level = crate::systems::quick_battle::QUICK_BATTLE_MAX_LEVEL;
+    if !(0..=max_level).contains(&level) {
+        return Err(brp_err(format!(
+            "level must be 0..={max_level}, got {level}"
+        )));
+    }
+    Ok(crate::systems::quick_battle::ArmySpec {
+        units,
+        level,
+        traits,
+    })
+}
+
+pub fn quick_battle_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    use crate::systems::quick_battle::*;
+    let p: QuickBattleParams = parse_some(params)?;
+    let armies = [parse_army(&p.a)?, parse_army(&p.b)?];
+    if armies.iter().any(|a| a.size() == 0) {
+        return Err(brp_err("both armies need at least one unit"));
+    }
+    let center = match (p.x, p.y) {
+        (Some(x), Some(y)) => Vec2::new(x, y),
+        _ => {
+            let grid = world.resource::<crate::world::WorldGrid>();
+            Vec2::new(grid.width as f32, grid.height as f32) * grid.cell_size * 0.5
+        }
+    };
+    let gap = p.gap.unwrap_or(QUICK_BATTLE_GAP).max(QUICK_BATTLE_SPACING);
+    let seed = p.seed.unwrap_or(QUICK_BATTLE_SEED);
+    let sizes = [armies[0].size(), armies[1].size()];
+    let id = world
+        .resource_mut::<QuickBattle>()
+        .request(armies, center, gap, seed);
+
+    toon_ok(json!({
+        "status": "queued",
+        "battle_id": id,
+        "seed": seed,
+        "x": r2(center.x),
+        "y": r2(center.y),
+        "gap": r2(gap),
+        "sizes": sizes,
+    }))
+}
+
+// --- endless/quick_battle_status ---------------------------------------------
+
+#[derive(Deserialize)]
+struct QuickBattleIdParams {
+    battle_id: Option<u32>,
+}
+
+pub fn quick_battle_status_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let battle_id = parse_optional::<QuickBattleIdParams>(params)?.battle_id;
+    let qb = world.resource::<crate::systems::quick_battle::QuickBattle>();
+    if let Some(req) = &qb.pending {
+        if battle_id.is_none_or(|id| id == req.id) {
+            return toon_ok(json!({"status": "pending", "battle_id": req.id}));
+        }
+    }
+    let battle = qb
+        .active
+        .as_ref()
+        .filter(|b| battle_id.is_none_or(|id| id == b.id))
+        .ok_or_else(|| brp_err("no such quick battle"))?;
+
+    let entity_map = world.resource::<EntityMap>();
+    let faction_stats = world.resource::<FactionStats>();
+    let mut alive = [0usize; 2];
+    let sides: Vec<Value> = (0..2)
+        .map(|side| {
+            let faction = battle.factions[side];
+            let mut jobs: BTreeMap<String, usize> = BTreeMap::new();
+            let mut hp = 0.0;
+            for &slot in &battle.slots[side] {
+                let Some(npc) = entity_map.get_npc(slot) else {
+                    continue;
+                };
+                if npc.dead || npc.faction != faction {
+                    continue;
+                }
+                alive[side] += 1;
+                *jobs.entry(format!("{:?}", npc.job)).or_default() += 1;
+                hp += world
+                    .get::<crate::components::Health>(npc.entity)
+                    .map_or(0.0, |h| h.0);
+            }
+            let stat = faction_stats.stats.get(faction as usize);
+            json!({
+                "faction": faction,
+                "deployed": battle.slots[side].len(),
+                "alive": alive[side],
+                "total_hp": r2(hp),
+                "kills": stat.map_or(0, |s| s.kills),
+                "dead": stat.map_or(0, |s| s.dead),
+                "jobs": jobs,
+            })
+        })
+        .collect();
+
+    let winner = match alive {
+        [0, 0] => json!("draw"),
+        [_, 0] => json!("a"),
+        [0, _] => json!("b"),
+        _ => Value::Null,
+    };
+    let status = if winner.is_null() {
+        "running"
+    } else {
+        "finished"
+    };
+    let elapsed = world.resource::<GameTime>().total_seconds - battle.started;
+    toon_ok(json!({
+        "status": status,
+        "battle_id": battle.id,
+        "seed": battle.seed,
+        "elapsed_secs": r2(elapsed),
+        "winner": winner,
+        "a": sides[0],
+        "b": sides[1],
+    }))
+}
+
+// --- endless/quick_battle_reset ----------------------------------------------
+
+#[derive(Deserialize)]
+struct QuickBattleResetParams {
+    battle_id: u32,
+}
+
+pub fn quick_battle_reset_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: QuickBattleResetParams = parse_some(params)?;
+    if !world
+        .resource_mut::<crate::systems::quick_battle::QuickBattle>()
+        .reset(p.battle_id)
+    {
+        return Err(brp_err(format!("no quick battle {}", p.battle_id)));
+    }
+    toon_ok(json!({"status": "queued", "battle_id": p.battle_id}))
+}
+
+// --- endless/npc_path --------------------------------------------------------
+
+/// Where an NPC is headed and the route it will walk there, as world points.
+/// `lead` (manual target or squad target) is the effective target when set; otherwise
+/// the path goal, then the raw GPU target. Points are the remaining path waypoints
+/// (plus the exact goal), or just the target when there's no path. Idle → empty.
+fn npc_route(
+    pos: Vec2,
+    gpu_target: Option<Vec2>,
+    path: Option<(Vec<Vec2>, Vec2)>,
+    lead: Option<Vec2>,
+) -> (Option<Vec2>, Vec<Vec2>) {
+    // GPU target parked on the NPC's own position = standing still
+    let moving = gpu_target.filter(|t| t.distance(pos) > 1.0);
+    let target = lead.or(path.as_ref().map(|(_, goal)| *goal)).or(moving);
+    let Some(target) = target else {
+        return (None, Vec::new());
+    };
+    let points = match path {
+        Some((mut points, goal)) if !points.is_empty() => {
+            if points.last().is_none_or(|p| p.distance(goal) > 1.0) {
+                points.push(goal);
+            }
+            points
+        }
+        _ => vec![target],
+    };
+    (Some(target), points)
+}
+
+#[derive(Deserialize)]
+struct NpcPathParams {
+    entity: String,
+}
+
+pub fn npc_path_handler(In(params): In<Option<Value>>, world: &World) -> BrpResult {
+    let p: NpcPathParams = parse_some(params)?;
+    let entity = parse_entity_str(&p.entity)?;
+    let entity_map = world.resource::<EntityMap>();
+    let slot = entity_map
+        .slot_for_entity(entity)
+        .ok_or_else(|| brp_err(format!("no entity for {entity:?}")))?;
+    let npc = entity_map
+        .get_npc(slot)
+        .ok_or_else(|| brp_err(format!("entity {} is not an NPC", p.entity)))?;
+
+    let gpu_state = world.resource::<GpuReadState>();
+    let slot_pos = |s: usize| -> Option<Vec2> {
+        let x = *gpu_state.positions.get(s * 2)?;
+        let y = *gpu_state.positions.get(s * 2 + 1)?;
+        Some(Vec2::new(x, y))
+    };
+    let pos = slot_pos(slot).unwrap_or(Vec2::NEG_ONE);
+    let targets = &world.resource::<crate::gpu::EntityGpuState>().targets;
+    let gpu_target = targets
+        .get(slot * 2..slot * 2 + 2)
+        .map(|t| Vec2::new(t[0], t[1]));
+
+    let grid = world.resource::<crate::world::WorldGrid>();
+    let path = world
+        .get::<crate::components::NpcPath>(npc.entity)
+        .filter(|p| p.current < p.waypoints.len())
+        .map(|p| {
+            let points = p.waypoints[p.current..]
+                .iter()
+                .map(|w| grid.grid_to_world(w.x as usize, w.y as usize))
+                .collect();
+            (points, p.goal_world)
+        });
+
+    // Manual target wins; a squad member otherwise heads for the squad's target
+    let (lead, source) = match world.get::<ManualTarget>(npc.entity) {
+        Some(ManualTarget::Npc(s)) => (slot_pos(*s), "manual"),
+        Some(ManualTarget::Building(at) | ManualTarget::Position(at)) => (Some(*at), "manual"),
+        None => {
+            let squad_target = world.get::<SquadId>(npc.entity).and_then(|sq| {
+                world
+                    .resource::<SquadState>()
+                    .squads
+                    .get(sq.0 as usize)
+                    .and_then(|s| s.target)
+            });
+            (squad_target, "squad")
+        }
+    };
+    let source = match (lead, &path) {
+        (Some(_), _) => source,
+        (None, Some(_)) => "path",
+        (None, None) => "target",
+    };
+
+    let (target, points) = npc_route(pos, gpu_target, path, lead);
+    let source = if target.is_some() { source } else { "idle" };
+    toon_ok(json!({
+        "entity": p.entity,
+        "slot": slot,
+        "pos": [r2(pos.x), r2(pos.y)],
+        "source": source,
+        "target": target.map(|t| [r2(t.x), r2(t.y)]),
+        "points": points.iter().map(|p| [r2(p.x), r2(p.y)]).collect::<Vec<_>>(),
+    }))
+}
+
+// --- endless/attack_target / clear_attack_target -----------------------------
+
+/// Resolve an alive NPC by entity string and check its town is allowed.
+fn alive_npc_slot(world: &World, entity_str: &str) -> Result<(Entity, usize, i32), BrpError> {
+    let entity = parse_entity_str(entity_str)?;
+    let entity_map = world.resource::<EntityMap>();
+    let slot = entity_map
+        .slot_for_entity(entity)
+        .ok_or_else(|| brp_err(format!("no entity for {entity:?}")))?;
+    let npc = entity_map
+        .get_npc(slot)
+        .ok_or_else(|| brp_err(format!("entity {entity_str} is not an NPC")))?;
+    if npc.dead {
+        return Err(brp_err(format!("npc #{slot} is dead")));
+    }
+    let (faction, town) = (npc.faction, npc.town_idx);
+    if town >= 0 {
+        check_town_allowed(world, town as usize)?;
+    }
+    Ok((entity, slot, faction))
+}
+
+#[derive(Deserialize)]
+struct AttackTargetParams {
+    entity: String,
+    target: String,
+}
+
+/// Force an NPC to attack a specific enemy (sets `ManualTarget::Npc`). attack_system prefers
+/// it over the GPU's nearest pick and chases when out of range. A dead, friendly or unknown
+/// target clears any override instead, so the unit falls back to auto-targeting.
+pub fn attack_target_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: AttackTargetParams = parse_some(params)?;
+    let (entity, slot, faction) = alive_npc_slot(world, &p.entity)?;
+
+    let target_slot = parse_entity_str(&p.target).ok().and_then(|t| {
+        let entity_map = world.resource::<EntityMap>();
+        entity_map
+            .slot_for_entity(t)
+            .filter(|&ts| crate::systems::combat::manual_target_valid(entity_map, ts, faction))
+    });
+    let Some(target_slot) = target_slot else {
+        if matches!(
+            world.get::<ManualTarget>(entity),
+            Some(ManualTarget::Npc(_))
+        ) {
+            world.entity_mut(entity).remove::<ManualTarget>();
+        }
+        if world.resource::<DebugFlags>().combat {
+            info!(
+                "npc #{slot}: attack target {} dead or invalid, back to auto",
+                p.target
+            );
+        }
+        return toon_ok(json!({"status": "cleared", "slot": slot, "reason": "invalid target"}));
+    };
+
+    world
+        .entity_mut(entity)
+        .insert(ManualTarget::Npc(target_slot));
+    toon_ok(json!({"status": "ok", "slot": slot, "target": target_slot}))
+}
+
+#[derive(Deserialize)]
+struct ClearAttackTargetParams {
+    entity: String,
+}
+
+/// Drop an NPC's attack override. Move orders (`ManualTarget::Position/Building`) are kept.
+pub fn clear_attack_target_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: ClearAttackTargetParams = parse_some(params)?;
+    let (entity, slot, _) = alive_npc_slot(world, &p.entity)?;
+    let cleared = matches!(
+        world.get::<ManualTarget>(entity),
+        Some(ManualTarget::Npc(_))
+    );
+    if cleared {
+        world.entity_mut(entity).remove::<ManualTarget>();
+    }
+    toon_ok(json!({"status": "ok", "slot": slot, "cleared": cleared}))
+}
+
+// --- endless/damage_area -----------------------------------------------------
+
+/// How area damage tapers from center to edge.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DamageFalloff {
+    /// Flat: full damage anywhere inside the radius.
+    #[default]
+    None,
+    Linear,
+    Quadratic,
+}
+
+impl DamageFalloff {
+    /// Damage multiplier at `dist` from the center (1.0 at center, 0.0 at the edge).
+    pub fn scale(self, dist: f32, radius: f32) -> f32 {
+        let t = if radius > 0.0 {
+            (1.0 - dist / radius).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        match self {
+            DamageFalloff::None => 1.0,
+            DamageFalloff::Linear => t,
+            DamageFalloff::Quadratic => t * t,
+        }
+    }
+}
+
+/// Live NPCs within `radius` of `center` (optionally one faction only) and the damage each
+/// takes. Skips dead entries and hidden slots (position sentinel), and hits scaled to zero.
+fn area_damage_targets(
+    entity_map: &EntityMap,
+    positions: &[f32],
+    center: Vec2,
+    radius: f32,
+    amount: f32,
+    faction: Option<i32>,
+    falloff: DamageFalloff,
+) -> Vec<(Entity, f32)> {
+    let radius_sq = radius * radius;
+    let mut hits = Vec::new();
+    for npc in entity_map.iter_npcs() {
+        if npc.dead || faction.is_some_and(|f| npc.faction != f) {
+            continue;
+        }
+        let b = npc.slot * 2;
+        if b + 1 >= positions.len() || positions[b] < -9000.0 {
+            continue;
+        }
+        let pos = Vec2::new(positions[b], positions[b + 1]);
+        let dist_sq = pos.distance_squared(center);
+        if dist_sq > radius_sq {
+            continue;
+        }
+        let dmg = amount * falloff.scale(dist_sq.sqrt(), radius);
+        if dmg > 0.0 {
+            hits.push((npc.entity, dmg));
+        }
+    }
+    hits
+}
+
+#[derive(Deserialize)]
+struct DamageAreaParams {
+    x: f32,
+    y: f32,
+    radius: f32,
+    amount: f32,
+    faction: Option<i32>,
+    #[serde(default)]
+    falloff: DamageFalloff,
+}
+
+/// Damage every NPC in a circle in one call (scripted disasters/spells). Hits are queued
+/// and applied next tick through the normal damage pipeline. Returns the number hit.
+pub fn damage_area_handler(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
+    let p: DamageAreaParams = parse_some(params)?;
+    if !p.radius.is_finite() || p.radius <= 0.0 {
+        return Err(brp_err("radius must be > 0"));
+    }
+    if p.amount.is_nan() || p.amount <= 0.0 {
+        return Err(brp_err("amount must be > 0"));
+    }
+    let center = Vec2::new(p.x, p.y);
+    let hits = area_damage_targets(
+        world.resource::<EntityMap>(),
+        &world.resource::<GpuReadState>().positions,
+        center,
+        p.radius,
+        p.amount,
+        p.faction,
+        p.falloff,
+    );
+    let total: f32 = hits.iter().map(|(_, d)| d).sum();
+    let hit = hits.len();
+    world.resource_mut::<RemoteDamageQueue>().0.extend(hits);
+
+    toon_ok(json!({
+        "status": "queued",
+        "hit": hit,
+        "total_damage": r2(total),
+    }))
+}
+
+// ============================================================================
+// DRAIN SYSTEM
+// ============================================================================
+
+pub fn drain_remote_queues(
+    mut build_q: ResMut<RemoteBuildQueue>,
+    mut destroy_q: ResMut<RemoteDestroyQueue>,
+    mut upgrade_q: ResMut<RemoteUpgradeQueue>,
+    mut despawn_q: ResMut<RemoteDespawnQueue>,
+    mut damage_q: ResMut<RemoteDamageQueue>,
+    mut llm_log_q: ResMut<RemoteLlmLogQueue>,
+    mut log_ring: ResMut<RemoteCombatLogRing>,
+    mut world_state: WorldState,
+    mut town_access: crate::systemparams::TownAccess,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+    mut combat_log: MessageWriter<crate::messages::CombatLogMsg>,
+    mut damage_writer: MessageWriter<crate::messages::DamageMsg>,
+    mut commands: Commands,
+    mut upgrade_writer: MessageWriter<crate::systems::stats::UpgradeMsg>,
+    mut despawn_writer: MessageWriter<crate::messages::DespawnNpcMsg>,
+    game_time: Res<GameTime>,
+) {
+    // Drain build queue
+    let builds: Vec<RemoteBuild> = build_q.0.drain(..).collect();
+    for build in builds {
+        if build.town >= world_state.world_data.towns.len() {
+            continue;
+        }
+        let pos = world_state.grid.grid_to_world(build.col, build.row);
+        let cost = world_state.building_costs.cost(build.kind);
+
+        let mut food_val = town_access.food(build.town as i32);
+        let upgrade_levels = town_access.upgrade_levels(build.town as i32);
+        let tech_locked = town_access.tech_locked_buildings(build.town as i32);
+        let check = crate::world::BuildCheck::new(
+            build.town,
+            &world_state.world_data.towns[build.town],
+            food_val,
+            &upgrade_levels,
+            &world_state.grid,
+            &world_state.entity_map,
+            &world_state.building_costs,
+        )
+        .with_tech_locks(tech_locked.clone());
+        if check.check(build.kind, &world_state.entity_map).is_err() {
+            continue;
+        }
+        let _ = world_state.place_building(
+            &mut food_val,
+            build.kind,
+            build.town,
+            pos,
+            cost,
+            &tech_locked,
+            &mut gpu_updates,
+            &mut commands,
+        );
+        if let Some(mut f) = town_access.food_mut(build.town as i32) {
+            f.0 = food_val;
+        }
+    }
+
+    // Drain destroy queue
+    let destroys: Vec<RemoteDestroy> = destroy_q.0.drain(..).collect();
+    for destroy in destroys {
+        let Some(town) = world_state.world_data.towns.get(destroy.town) else {
+            continue;
+        };
+        let town_name = town.name.clone();
+
+        // Look up building at grid cell
+        let Some(inst) = world_state
+            .entity_map
+            .get_at_grid(destroy.col as i32, destroy.row as i32)
+        else {
+            continue;
+        };
+        // Validate: not Fountain/GoldMine, belongs to requesting town
+        if matches!(inst.kind, BuildingKind::Fountain | BuildingKind::GoldMine) {
+            continue;
+        }
+        if inst.town_idx as usize != destroy.town {
+            continue;
+        }
+        let bld_kind = inst.kind;
+        let slot = inst.slot;
+
+        // Send lethal damage so death_system handles entity despawn
+        let Some(&target_entity) = world_state.entity_map.entities.get(&slot) else {
+            continue;
+        };
+        damage_writer.write(crate::messages::DamageMsg {
+            target: target_entity,
+            amount: f32::MAX,
+            attacker: -1,
+            attacker_faction: 0,
+        });
+
+        let _ = world_state.destroy_building(
+            &mut combat_log,
+            &game_time,
+            destroy.col,
+            destroy.row,
+            &format!("Destroyed building in {}", town_name),
+            &mut gpu_updates,
+        );
+        world_state.dirty_writers.mark_building_changed(bld_kind);
+    }
+
+    // Drain upgrade queue
+    for upgrade in upgrade_q.0.drain(..) {
+        upgrade_writer.write(crate::systems::stats::UpgradeMsg {
+            town_idx: upgrade.town,
+            upgrade_idx: upgrade.upgrade_idx,
+        });
+    }
+
+    // Drain despawn queue
+    for slot in despawn_q.0.drain(..) {
+        despawn_writer.write(crate::messages::DespawnNpcMsg { slot });
+    }
+
+    // Drain scripted area damage (unattributed, like tower/environment hits)
+    for (target, amount) in damage_q.0.drain(..) {
+        damage_writer.write(crate::messages::DamageMsg {
+            target,
+            amount,
+            attacker: -1,
+            attacker_faction: 0,
+        });
+    }
+
+    // Drain LLM log queue — write to both combat log and ring buffer
+    for msg in llm_log_q.0.drain(..) {
+        log_ring.push(msg.clone());
+        combat_log.write(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{
+        Activity, ActivityKind, ActivityPhase, ActivityTarget, CachedStats, CarriedLoot,
+        CombatState, Faction, GpuSlot, Health, Home, Job, NpcEquipment, NpcFlags, NpcWorkState,
+        Personality, Speed, TownId,
+    };
+    use crate::gpu::{EntityGpuState, NpcVisualUpload, ProjBufferWrites};
+    use crate::resources::{
+        EntityMap, GameTime, GpuReadState, NpcLogCache, NpcTargetThrashDebug, ProjPositionState,
+        SquadState, TownIndex,
+    };
+    use crate::world::WorldData;
+
+    #[test]
+    fn locations_cover_towns_and_buildings_within_bounds() {
+        let mut wd = WorldData::default();
+        wd.towns.push(crate::world::Town {
+            name: "A".into(),
+            center: Vec2::new(100.0, 100.0),
+            faction: 1,
+            kind: crate::constants::TownKind::Player,
+        });
+        let mut em = EntityMap::default();
+        for (slot, kind, pos) in [
+            (40, BuildingKind::Farm, Vec2::new(150.0, 100.0)),
+            (41, BuildingKind::Tower, Vec2::new(900.0, 900.0)),
+            (42, BuildingKind::Bed, Vec2::new(-99999.0, -99999.0)),
+        ] {
+            em.add_instance(crate::resources::BuildingInstance {
+                kind,
+                position: pos,
+                town_idx: 0,
+                slot,
+                faction: 1,
+            });
+        }
+
+        let all = collect_locations(&wd, &em, None);
+        assert_eq!(all.len(), 3, "hidden building skipped");
+        assert_eq!(all[0].kind, None, "town centers first");
+        assert_eq!(all[0].index, 0);
+
+        let near = Rect::from_corners(Vec2::ZERO, Vec2::new(200.0, 200.0));
+        let some = collect_locations(&wd, &em, Some(near));
+        assert_eq!(some.len(), 2);
+        assert_eq!(some[1].kind, Some(BuildingKind::Farm));
+        assert_eq!(some[1].index, 40);
+    }
+
+    #[test]
+    fn transforms_packed_layout_round_trips() {
+        let records = [
+            NpcTransform {
+                slot: 7,
+                pos: Vec2::new(12.5, -3.0),
+                job: Job::Archer as u8,
+                faction: 2,
+            },
+            NpcTransform {
+                slot: 300,
+                pos: Vec2::new(1024.0, 768.25),
+                job: Job::Raider as u8,
+                faction: -1,
+            },
+        ];
+        let mut bytes = Vec::new();
+        encode_transforms_packed(&records, &mut bytes);
+        assert_eq!(bytes.len(), 5 + records.len() * PACKED_NPC_RECORD_LEN);
+        assert_eq!(bytes[0], TRANSFORMS_PACKED_VERSION);
+        assert_eq!(u32::from_le_bytes(bytes[1..5].try_into().unwrap()), 2);
+
+        // Decode the way a client would: fixed-size records after the header
+        for (rec, want) in bytes[5..].chunks(PACKED_NPC_RECORD_LEN).zip(&records) {
+            let f32_at = |i: usize| f32::from_le_bytes(rec[i..i + 4].try_into().unwrap());
+            assert_eq!(rec[0], PACKED_TAG_NPC);
+            assert_eq!(u32::from_le_bytes(rec[1..5].try_into().unwrap()), want.slot);
+            assert_eq!(Vec2::new(f32_at(5), f32_at(9)), want.pos);
+            assert_eq!(rec[13], want.job);
+            assert_eq!(
+                i16::from_le_bytes(rec[14..16].try_into().unwrap()),
+                want.faction
+            );
+        }
+    }
+
+    #[test]
+    fn base64_matches_rfc4648_vectors() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn optional_params_read_bare_and_reject_malformed() {
+        let mut world = World::new();
+        world.insert_resource(crate::resources::EnergyThresholds::default());
+        assert!(energy_thresholds_handler(In(None), &mut world).is_ok());
+        let bad = json!({ "job": "Farmer", "rest_below": "low" });
+        assert!(energy_thresholds_handler(In(Some(bad)), &mut world).is_err());
+    }
+
+    #[test]
+    fn quick_battle_status_rejects_a_malformed_battle_id() {
+        let mut world = World::new();
+        world.init_resource::<crate::systems::quick_battle::QuickBattle>();
+        let bad = json!({ "battle_id": "latest" });
+        assert!(quick_battle_status_handler(In(Some(bad)), &world).is_err());
+        let err = quick_battle_status_handler(In(None), &world).unwrap_err();
+        assert_eq!(err.message, "no such quick battle");
+    }
+
+    #[test]
+    fn select_in_rect_reports_capped_only_past_the_limit() {
+        let mut world = World::new();
+        let mut map = EntityMap::default();
+        for slot in 0..2 {
+            let entity = world.spawn(crate::components::GpuSlot(slot)).id();
+            map.register_npc(
+                slot,
+                entity,
+                crate::components::Job::Archer,
+                crate::constants::FACTION_PLAYER,
+                0,
+            );
+        }
+        world.insert_resource(map);
+        world.insert_resource(GpuReadState {
+            positions: vec![10.0, 10.0, 20.0, 20.0],
+            ..Default::default()
+        });
+        let select = |world: &World, limit: usize| {
+            let params = json!({ "x0": 0.0, "y0": 0.0, "x1": 40.0, "y1": 40.0, "limit": limit });
+            let out = select_in_rect_handler(In(Some(params)), world).unwrap();
+            serde_toon2::from_str::<Value>(out.as_str().unwrap()).unwrap()
+        };
+        // Exactly `limit` matches is a full page, not a truncated one
+        let full = select(&world, 2);
+        assert_eq!(full["count"], 2);
+        assert_eq!(full["capped"], false);
+        let truncated = select(&world, 1);
+        assert_eq!(truncated["count"], 1);
+        assert_eq!(truncated["capped"], true);
+    }
+
+    #[test]
+    fn projectile_arc_sets_a_per_shooter_arc() {
+        use crate::components::ProjArc;
+        let mut world = World::new();
+        let mut map = EntityMap::default();
+        let archer = world.spawn(crate::components::GpuSlot(0)).id();
+        map.register_npc(0, archer, crate::components::Job::Archer, 1, 0);
+        world.insert_resource(map);
+        fn set(world: &mut World, params: Value) -> Result<Value, BrpError> {
+            let out = projectile_arc_handler(In(Some(params)), world)?;
+            Ok(serde_toon2::from_str::<Value>(out.as_str().unwrap()).unwrap())
+        }
+
+        let out = set(
+            &mut world,
+            json!({"slot": 0, "gravity": 400.0, "splash_radius": 1e6}),
+        )
+        .unwrap();
+        assert_eq!(out["arced"], true);
+        assert_eq!(out["splash_radius"], crate::constants::PROJ_ARC_SPLASH_MAX);
+        // Omitted fields keep the shooter's current value
+        let out = set(&mut world, json!({"slot": 0, "splash_radius": 32.0})).unwrap();
+        assert_eq!(out["gravity"], 400.0);
+        assert!(
+            set(&mut world, json!({"slot": 1})).is_err(),
+            "nothing at slot 1"
+        );
+        assert!(set(&mut world, json!({})).is_err(), "slot is required");
+        assert_eq!(
+            world.get::<ProjArc>(archer).copied(),
+            Some(ProjArc::new(400.0, 32.0))
+        );
+
+        let out = set(&mut world, json!({"slot": 0, "gravity": 0.0})).unwrap();
+        assert_eq!(out["arced"], false);
+        assert!(world.get::<ProjArc>(archer).is_none(), "flat drops the arc");
+    }
+
+    #[test]
+    fn sprite_atlas_queues_swaps_for_every_atlas() {
+        use crate::render::{AtlasSwapQueue, AtlasTarget};
+        let mut world = World::new();
+        world.init_resource::<crate::gpu::RenderFrameConfig>();
+        world.init_resource::<AtlasSwapQueue>();
+        for (name, target) in [
+            ("character", AtlasTarget::Character),
+            ("world", AtlasTarget::World),
+            ("item", AtlasTarget::Item),
+            ("location", AtlasTarget::Location),
+        ] {
+            let params = json!({ "atlas": name, "margin": 0.0 });
+            let out = sprite_atlas_handler(In(Some(params)), &mut world).unwrap();
+            let out: Value = serde_toon2::from_str(out.as_str().unwrap()).unwrap();
+            assert_eq!(out["status"], "queued", "{name}");
+            let queue = world.resource::<AtlasSwapQueue>();
+            let swap = queue.requests.last().unwrap();
+            assert_eq!(swap.target, target);
+            assert_eq!(swap.layout.margin, 0.0);
+        }
+        // Omitted fields keep the current layout: the building strip stays 64px per layer
+        let location = &world.resource::<AtlasSwapQueue>().requests[3].layout;
+        assert_eq!((location.sprite_w, location.sprite_h), (64.0, 64.0));
+
+        let bad = json!({ "atlas": "terrain" });
+        assert!(sprite_atlas_handler(In(Some(bad)), &mut world).is_err());
+    }
+
+    #[test]
+    fn lead_targeting_defaults_off_and_rejects_unknown_modes() {
+        let mut world = World::new();
+        world.init_resource::<crate::resources::LeadTargeting>();
+        let read = lead_targeting_handler(In(None), &mut world).unwrap();
+        let read: Value = serde_toon2::from_str(read.as_str().unwrap()).unwrap();
+        assert_eq!(read["mode"], "off");
+        let bad = json!({ "mode": "everyone" });
+        assert!(lead_targeting_handler(In(Some(bad)), &mut world).is_err());
+        let set = json!({ "mode": "sharpshot" });
+        assert!(lead_targeting_handler(In(Some(set)), &mut world).is_ok());
+        assert_eq!(
+            world.resource::<crate::resources::LeadTargeting>().mode,
+            crate::resources::LeadTargetMode::Sharpshot
+        );
+    }
+
+    #[test]
+    fn attack_windup_applies_to_living_npcs_and_rejects_bad_secs() {
+        let mut world = World::new();
+        world.insert_resource(crate::systems::stats::CombatConfig::default());
+        let npc = world.spawn((GpuSlot(0), Job::Archer)).id();
+
+        assert!(
+            attack_windup_handler(
+                In(Some(json!({ "secs": 0.4, "redirect": true }))),
+                &mut world
+            )
+            .is_ok()
+        );
+        let config = world.resource::<crate::systems::stats::CombatConfig>();
+        assert_eq!(config.attack_windup, 0.4);
+        assert!(config.windup_redirect);
+        assert_eq!(
+            world
+                .get::<crate::components::AttackWindup>(npc)
+                .map(|w| w.0),
+            Some(0.4)
+        );
+
+        assert!(attack_windup_handler(In(Some(json!({ "secs": -1.0 }))), &mut world).is_err());
+        assert!(attack_windup_handler(In(Some(json!({ "secs": "slow" }))), &mut world).is_err());
+        assert_eq!(
+            world
+                .resource::<crate::systems::stats::CombatConfig>()
+                .attack_windup,
+            0.4
+        );
+
+        assert!(attack_windup_handler(In(Some(json!({ "secs": 0.0 }))), &mut world).is_ok());
+        assert!(world.get::<crate::components::AttackWindup>(npc).is_none());
+    }
+
+    #[test]
+    fn set_tribute_is_gated_by_the_receiving_town() {
+        let mut world = World::new();
+        let mut data = WorldData::default();
+        for faction in 1..=3 {
+            data.towns.push(crate::world::Town {
+                name: format!("town {faction}"),
+                center: Vec2::ZERO,
+                faction,
+                kind: crate::constants::TownKind::Player,
+            });
+        }
+        world.insert_resource(data);
+        world.insert_resource(RemoteAllowedTowns { towns: vec![0] });
+        world.init_resource::<TributeState>();
+        world.init_resource::<GameTime>();
+        world.init_resource::<RemoteLlmLogQueue>();
+        let set = |world: &mut World, params: Value| set_tribute_handler(In(Some(params)), world);
+
+        // Tribute into a restricted town is rejected, even when the payer is allowed
+        let err = set(&mut world, json!({"from_town": 0, "to_town": 2})).unwrap_err();
+        assert_eq!(err.code, FORBIDDEN_CODE);
+        assert!(world.resource::<TributeState>().paid_by(0).is_none());
+        // A restricted town can be made to pay the allowed one, and the terms changed
+        assert!(set(&mut world, json!({"from_town": 1, "to_town": 0})).is_ok());
+        assert!(set(&mut world, json!({"from_town": 1, "fraction": 0.5})).is_ok());
+        let tribute = world
+            .resource::<TributeState>()
+            .paid_by(1)
+            .cloned()
+            .unwrap();
+        assert_eq!((tribute.to_town, tribute.fraction), (0, 0.5));
+
+        // Once the receiver is restricted, neither changes nor clears go through
+        world.resource_mut::<RemoteAllowedTowns>().towns = vec![1];
+        let err = set(&mut world, json!({"from_town": 1, "fraction": 0.2})).unwrap_err();
+        assert_eq!(err.code, FORBIDDEN_CODE);
+        let err = set(&mut world, json!({"from_town": 1, "clear": true})).unwrap_err();
+        assert_eq!(err.code, FORBIDDEN_CODE);
+        assert!(world.resource::<TributeState>().paid_by(1).is_some());
+    }
+
+    #[test]
+    fn assign_npc_reports_why_a_bed_claim_failed() {
+        let mut world = World::new();
+        world.init_resource::<RemoteAllowedTowns>();
+        let mut em = EntityMap::default();
+        em.add_instance(crate::resources::BuildingInstance {
+            kind: BuildingKind::Bed,
+            position: Vec2::new(32.0, 32.0),
+            slot: 10,
+            town_idx: 0,
+            faction: 1,
+        });
+        let bed = world
+            .spawn(crate::components::Building {
+                kind: BuildingKind::Bed,
+            })
+            .id();
+        em.set_entity(10, bed);
+        em.set_occupancy(10, 1);
+        let npc = world.spawn((GpuSlot(0), Home(Vec2::ZERO))).id();
+        em.register_npc(0, npc, Job::Farmer, 1, 0);
+        world.insert_resource(em);
+
+        let params = json!({"slot": 0, "kind": "bed", "building": 10});
+        let err = assign_npc_handler(In(Some(params)), &mut world).unwrap_err();
+        assert_eq!(err.message, "bed already occupied");
+        assert!(world.get::<crate::components::AssignedBed>(npc).is_none());
+    }
+
+    fn decode_toon(response: Value) -> Value {
+        let encoded = response
+            .as_str()
+            .expect("debug response should be TOON text");
+        serde_toon2::from_str(encoded).expect("debug response should decode from TOON")
+    }
+
+    fn setup_debug_world(activity: Activity) -> (World, Entity, usize) {
+        let mut world = World::default();
         world.insert_resource(EntityMap::default());
         world.insert_resource(NpcLogCache::default());
         world.insert_resource(NpcTargetThrashDebug::default());
@@ -1855,6 +6476,7 @@ mod tests {
         world.insert_resource(NpcVisualUpload::default());
         world.insert_resource(ProjBufferWrites::default());
         world.insert_resource(ProjPositionState::default());
+        world.insert_resource(crate::resources::EnergyThresholds::default());
 
         let slot = 7;
         let entity = world
@@ -1920,4 +6542,106 @@ mod tests {
         assert_eq!(data["transition_reason"], "unit-test");
         assert_eq!(data["last_transition_frame"], 77);
     }
+
+    #[test]
+    fn area_damage_filters_and_falls_off() {
+        let mut entity_map = EntityMap::default();
+        // slot: (pos, faction)
+        let npcs = [
+            ((100.0, 100.0), 1),     // center
+            ((150.0, 100.0), 1),     // half radius
+            ((150.0, 100.0), 2),     // other faction
+            ((250.0, 100.0), 1),     // outside
+            ((-9999.0, -9999.0), 1), // hidden slot
+            ((100.0, 100.0), 1),     // dead
+        ];
+        let mut positions = Vec::new();
+        for (slot, &((x, y), faction)) in npcs.iter().enumerate() {
+            entity_map.register_npc(
+                slot,
+                Entity::from_raw_u32(slot as u32 + 1).unwrap(),
+                Job::Fighter,
+                faction,
+                0,
+            );
+            positions.extend([x, y]);
+        }
+        entity_map.get_npc_mut(5).unwrap().dead = true;
+        let center = Vec2::new(100.0, 100.0);
+        let slots = |hits: &[(Entity, f32)]| {
+            let mut v: Vec<_> = hits
+                .iter()
+                .map(|(e, d)| (entity_map.slot_for_entity(*e).unwrap(), *d))
+                .collect();
+            v.sort_by_key(|(slot, _)| *slot);
+            v
+        };
+
+        let flat = area_damage_targets(
+            &entity_map,
+            &positions,
+            center,
+            100.0,
+            40.0,
+            None,
+            DamageFalloff::None,
+        );
+        assert_eq!(slots(&flat), vec![(0, 40.0), (1, 40.0), (2, 40.0)]);
+
+        let linear = area_damage_targets(
+            &entity_map,
+            &positions,
+            center,
+            100.0,
+            40.0,
+            Some(1),
+            DamageFalloff::Linear,
+        );
+        assert_eq!(slots(&linear), vec![(0, 40.0), (1, 20.0)]);
+
+        let quad = area_damage_targets(
+            &entity_map,
+            &positions,
+            center,
+            100.0,
+            40.0,
+            Some(1),
+            DamageFalloff::Quadratic,
+        );
+        assert_eq!(slots(&quad), vec![(0, 40.0), (1, 10.0)]);
+
+        // Edge of the radius takes nothing under falloff
+        assert_eq!(DamageFalloff::Linear.scale(100.0, 100.0), 0.0);
+        assert_eq!(DamageFalloff::None.scale(100.0, 100.0), 1.0);
+    }
+
+    #[test]
+    fn npc_route_prefers_lead_then_path_then_gpu_target() {
+        let pos = Vec2::new(10.0, 10.0);
+        let path = Some((
+            vec![Vec2::new(40.0, 40.0), Vec2::new(72.0, 72.0)],
+            Vec2::new(70.0, 75.0),
+        ));
+
+        // Idle: GPU target parked on own position, no path, no lead
+        assert_eq!(npc_route(pos, Some(pos), None, None), (None, Vec::new()));
+        // Plain GPU target
+        let t = Vec2::new(100.0, 10.0);
+        assert_eq!(npc_route(pos, Some(t), None, None), (Some(t), vec![t]));
+        // Path: remaining waypoints plus exact goal
+        let (target, points) = npc_route(pos, Some(t), path.clone(), None);
+        assert_eq!(target, Some(Vec2::new(70.0, 75.0)));
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[2], Vec2::new(70.0, 75.0));
+        // Squad/manual lead overrides the path goal but keeps the route
+        let lead = Vec2::new(500.0, 500.0);
+        let (target, points) = npc_route(pos, Some(t), path, Some(lead));
+        assert_eq!(target, Some(lead));
+        assert_eq!(points.len(), 3);
+        // Lead with no path → single point
+        assert_eq!(
+            npc_route(pos, Some(pos), None, Some(lead)),
+            (Some(lead), vec![lead])
+        );
+    }
 }
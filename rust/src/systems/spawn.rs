@@ -1,11 +1,16 @@
 //! Spawn systems - Create Bevy entities from spawn events
 
+use bevy::ecs::message::Messages;
 use bevy::prelude::*;
+use std::collections::HashMap;
 
 use crate::components::*;
 use crate::messages::{CombatLogMsg, GpuUpdate, GpuUpdateMsg, SpawnNpcMsg};
 use crate::messages::{DirtyWriters, MiningDirtyMsg, SquadsDirtyMsg};
-use crate::resources::{CombatEventKind, EntityMap, FactionStats, GameTime, PopulationStats};
+use crate::resources::{
+    CombatEventKind, DebugFlags, EntityMap, FactionStats, GameTime, GpuSlotPool, NpcLogCache,
+    PopulationStats,
+};
 use crate::systems::economy::*;
 use crate::systems::stats::{CombatConfig, resolve_combat_stats};
 use crate::world::BuildingKind;
@@ -85,13 +90,24 @@ pub struct NpcSpawnOverrides {
     pub name: Option<String>,
     pub level: Option<i32>,
     pub xp: Option<i32>,
+    pub kills: Option<i32>,
     pub equipment: NpcEquipment,
     pub carried_food: Option<i32>,
     pub carried_gold: Option<i32>,
     pub carried_equipment: Vec<crate::constants::LootItem>,
+    pub inventory: Vec<ItemStack>,
     pub squad_id: Option<i32>,
+    pub target_priority: Option<TargetPriority>,
+    pub officer: Option<Officer>,
+    /// The unit's own stance; inserted with `StanceOverride`.
+    pub stance: Option<CombatStance>,
 }
 
+/// Per-slot overrides for fresh spawns, consumed by spawn_npc_system when the slot's
+/// SpawnNpcMsg arrives. Used by scripted spawns that need fixed level/traits (quick battle).
+#[derive(Resource, Default)]
+pub struct SpawnOverrideQueue(pub HashMap<usize, NpcSpawnOverrides>);
+
 /// Shared NPC spawn: creates entity, emits GPU updates, registers in tracking caches.
 /// Used by both spawn_npc_system (fresh spawn) and spawn_npcs_from_save (load).
 pub fn materialize_npc(
@@ -172,7 +188,11 @@ pub fn materialize_npc(
         row: sprite_row,
         atlas: def.atlas,
     }));
-    let combat_flags = if job.is_military() { 1u32 } else { 0u32 };
+    let combat_flags = crate::systems::combat::npc_gpu_flags(
+        job,
+        overrides.target_priority.unwrap_or_default(),
+        crate::systems::combat::threat_value(job, &cached, overrides.officer.is_some()),
+    );
     gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetFlags {
         idx,
         flags: combat_flags,
@@ -182,6 +202,7 @@ pub fn materialize_npc(
         half_w: crate::constants::NPC_HITBOX_HALF[0],
         half_h: crate::constants::NPC_HITBOX_HALF[1],
     }));
+    gpu_updates.write(GpuUpdateMsg(GpuUpdate::StartSpawnFade { idx }));
 
     // Resolve spawn data
     let activity = overrides.activity.unwrap_or_default();
@@ -271,6 +292,7 @@ pub fn materialize_npc(
             stone: 0,
             equipment: overrides.carried_equipment.clone(),
         },
+        Inventory(overrides.inventory.clone()),
         // Work state (always present)
         NpcWorkState {
             worksite: initial_work_target,
@@ -284,11 +306,21 @@ pub fn materialize_npc(
                 .clone()
                 .unwrap_or_else(|| generate_name(job, idx)),
             xp: overrides.xp.unwrap_or(0),
+            kills: overrides.kills.unwrap_or(0),
         },
     ));
     if let Some(sq) = overrides.squad_id {
         ecmds.insert(SquadId(sq));
     }
+    if let Some(priority) = overrides.target_priority {
+        ecmds.insert(priority);
+    }
+    if let Some(officer) = overrides.officer {
+        ecmds.insert(officer);
+    }
+    if let Some(stance) = overrides.stance {
+        ecmds.insert((stance, StanceOverride));
+    }
     if let Some(pr) = patrol_route {
         ecmds.insert(pr);
     }
@@ -302,6 +334,9 @@ pub fn materialize_npc(
     if def.has_energy {
         ecmds.insert(HasEnergy);
     }
+    if combat_config.attack_windup > 0.0 {
+        ecmds.insert(AttackWindup(combat_config.attack_windup));
+    }
     let entity = ecmds.id();
 
     entity_map.register_npc(idx, entity, job, faction_id, town_idx);
@@ -310,6 +345,182 @@ pub fn materialize_npc(
     // npc_by_town bookkeeping handled by register_npc inside entity_map
 }
 
+// ============================================================================
+// SPAWN SITE VALIDATION — home/work must point at real buildings
+// ============================================================================
+
+/// Home/work positions a fresh spawn actually received after validation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpawnSites {
+    pub home: [f32; 2],
+    pub work: Option<[f32; 2]>,
+    /// Requested home did not match a home building and was snapped or cleared.
+    pub home_fixed: bool,
+    /// Requested work did not match a worksite and was snapped or cleared.
+    pub work_fixed: bool,
+}
+
+/// Buildings an NPC can call home: spawner homes, beds, and the town fountain.
+fn is_home_kind(kind: BuildingKind) -> bool {
+    crate::constants::building_def(kind).spawner.is_some()
+        || matches!(kind, BuildingKind::Bed | BuildingKind::Fountain)
+}
+
+/// Worksites a spawner can hand out (see `resolve_spawner_npc`).
+fn is_work_kind(kind: BuildingKind) -> bool {
+    matches!(
+        kind,
+        BuildingKind::Farm
+            | BuildingKind::GoldMine
+            | BuildingKind::TreeNode
+            | BuildingKind::RockNode
+    )
+}
+
+/// Nearest position matching `accept` for `town_idx` (or the town center when `center` is set).
+/// A request already inside a matching building's cell resolves to that building.
+fn resolve_site(
+    pos: Vec2,
+    town_idx: u32,
+    entity_map: &EntityMap,
+    towns: &[crate::world::Town],
+    accept: fn(&crate::resources::BuildingInstance, u32) -> bool,
+    center: bool,
+) -> Option<Vec2> {
+    let town_center = center
+        .then(|| towns.get(town_idx as usize).map(|t| t.center))
+        .flatten();
+    if let Some(inst) = entity_map
+        .find_by_position(pos)
+        .filter(|i| accept(i, town_idx))
+    {
+        return Some(inst.position);
+    }
+    if let Some(c) = town_center.filter(|c| c.distance(pos) <= SPAWN_SITE_TOLERANCE) {
+        return Some(c);
+    }
+    entity_map
+        .iter_instances()
+        .filter(|i| accept(i, town_idx))
+        .map(|i| i.position)
+        .chain(town_center)
+        .min_by(|a, b| a.distance_squared(pos).total_cmp(&b.distance_squared(pos)))
+}
+
+/// Max distance (px) from the town center that still counts as "home at the fountain".
+const SPAWN_SITE_TOLERANCE: f32 = crate::constants::TOWN_GRID_SPACING * 0.5;
+
+/// Validate fresh-spawn home/work positions against real buildings. Positions that miss
+/// snap to the nearest matching building of the NPC's town, or are cleared (home -1,-1,
+/// work None) when the town has none. -1 sentinels stay unset. Townless NPCs (migration
+/// groups, boats) are passed through: their home is a settle target, not a building.
+pub fn validate_spawn_sites(
+    town_idx: i32,
+    home: [f32; 2],
+    work: Option<[f32; 2]>,
+    entity_map: &EntityMap,
+    towns: &[crate::world::Town],
+) -> SpawnSites {
+    let mut sites = SpawnSites {
+        home,
+        work,
+        home_fixed: false,
+        work_fixed: false,
+    };
+    if town_idx < 0 {
+        return sites;
+    }
+    let town = town_idx as u32;
+
+    if home[0] >= 0.0 && home[1] >= 0.0 {
+        let req = Vec2::new(home[0], home[1]);
+        let resolved = resolve_site(
+            req,
+            town,
+            entity_map,
+            towns,
+            |i, t| i.town_idx == t && is_home_kind(i.kind),
+            true,
+        );
+        let new = resolved.map_or([-1.0, -1.0], |p| [p.x, p.y]);
+        sites.home_fixed = resolved != Some(req);
+        sites.home = new;
+    }
+
+    if let Some(wp) = work {
+        let req = Vec2::new(wp[0], wp[1]);
+        // Farms belong to a town; mines and resource nodes are shared wilderness
+        let resolved = resolve_site(
+            req,
+            town,
+            entity_map,
+            towns,
+            |i, t| is_work_kind(i.kind) && (i.kind != BuildingKind::Farm || i.town_idx == t),
+            false,
+        );
+        sites.work_fixed = resolved != Some(req);
+        sites.work = resolved.map(|p| [p.x, p.y]);
+    }
+    sites
+}
+
+/// Position of unit `i` of `count` in a square grid (row-major, `ceil(sqrt(count))` wide)
+/// with its first unit at `origin`. Pure function of the inputs.
+pub fn grid_position(i: usize, count: usize, spacing: f32, origin: Vec2) -> Vec2 {
+    let side = (count as f64).sqrt().ceil().max(1.0) as usize;
+    origin + Vec2::new((i % side) as f32, (i / side) as f32) * spacing
+}
+
+/// Deterministic stress scenario: allocate slots for `count` townless NPCs laid out by
+/// `grid_position` and queue them as one batch of `SpawnNpcMsg` for `spawn_npc_system`.
+/// Stops when the slot pool runs out; returns the slots actually allocated, in grid order.
+pub fn spawn_grid(
+    world: &mut World,
+    count: usize,
+    job: Job,
+    faction: i32,
+    spacing: f32,
+    origin: Vec2,
+) -> Vec<usize> {
+    let mut slots = Vec::new();
+    {
+        let mut pool = world.resource_mut::<GpuSlotPool>();
+        // Never reserve more than the pool can hand out, whatever the caller asked for
+        slots.reserve(count.min(pool.available()));
+        while slots.len() < count {
+            let Some(slot) = pool.alloc_reset() else {
+                break;
+            };
+            slots.push(slot);
+        }
+    }
+    let msgs: Vec<SpawnNpcMsg> = slots
+        .iter()
+        .enumerate()
+        .map(|(i, &slot)| {
+            let pos = grid_position(i, count, spacing, origin);
+            SpawnNpcMsg {
+                slot_idx: slot,
+                x: pos.x,
+                y: pos.y,
+                job: job as i32,
+                faction,
+                town_idx: -1,
+                home_x: pos.x,
+                home_y: pos.y,
+                work_x: -1.0,
+                work_y: -1.0,
+                starting_post: -1,
+                entity_override: None,
+            }
+        })
+        .collect();
+    world
+        .resource_mut::<Messages<SpawnNpcMsg>>()
+        .write_batch(msgs);
+    slots
+}
+
 /// Generic spawn system. Job determines the component template.
 /// All GPU writes go through GpuUpdateMsg messages (collected at end of frame).
 pub fn spawn_npc_system(
@@ -324,6 +535,10 @@ pub fn spawn_npc_system(
     combat_config: Res<CombatConfig>,
     town_access: crate::systemparams::TownAccess,
     mut dirty_writers: DirtyWriters,
+    world_data: Res<crate::world::WorldData>,
+    debug_flags: Res<DebugFlags>,
+    mut override_queue: ResMut<SpawnOverrideQueue>,
+    mut npc_logs: ResMut<NpcLogCache>,
 ) {
     for msg in events.read() {
         let work_pos = if msg.work_x >= 0.0 {
@@ -331,8 +546,28 @@ pub fn spawn_npc_system(
         } else {
             None
         };
+        let sites = validate_spawn_sites(
+            msg.town_idx,
+            [msg.home_x, msg.home_y],
+            work_pos,
+            &entity_map,
+            &world_data.towns,
+        );
+        if debug_flags.spawns && (sites.home_fixed || sites.work_fixed) {
+            warn!(
+                "spawn #{} ({}): home ({:.0},{:.0}) -> ({:.0},{:.0}), work {:?} -> {:?}",
+                msg.slot_idx,
+                crate::job_name(msg.job),
+                msg.home_x,
+                msg.home_y,
+                sites.home[0],
+                sites.home[1],
+                work_pos,
+                sites.work
+            );
+        }
 
-        let overrides = NpcSpawnOverrides::default();
+        let overrides = override_queue.0.remove(&msg.slot_idx).unwrap_or_default();
         materialize_npc(
             msg.slot_idx,
             msg.x,
@@ -340,8 +575,8 @@ pub fn spawn_npc_system(
             msg.job,
             msg.faction,
             msg.town_idx,
-            [msg.home_x, msg.home_y],
-            work_pos,
+            sites.home,
+            sites.work,
             msg.starting_post,
             &overrides,
             &mut commands,
@@ -355,6 +590,23 @@ pub fn spawn_npc_system(
         // Spawn-only bookkeeping (not needed for save-load)
         let job = Job::from_i32(msg.job);
         faction_stats.inc_alive(msg.faction);
+        npc_logs.begin_life(msg.slot_idx);
+        if sites.home_fixed || sites.work_fixed {
+            npc_logs.push(
+                msg.slot_idx,
+                game_time.day(),
+                game_time.hour(),
+                game_time.minute(),
+                format!(
+                    "Spawn sites fixed: home ({:.0},{:.0}), work {}",
+                    sites.home[0],
+                    sites.home[1],
+                    sites
+                        .work
+                        .map_or("none".to_string(), |w| format!("({:.0},{:.0})", w[0], w[1]))
+                ),
+            );
+        }
         if job == Job::Miner {
             dirty_writers.mining.write(MiningDirtyMsg);
         }
@@ -561,6 +813,172 @@ mod tests {
         );
     }
 
+    fn site_fixture() -> (EntityMap, Vec<crate::world::Town>) {
+        let mut map = EntityMap::default();
+        for (slot, kind, x, y, town) in [
+            (10, BuildingKind::FarmerHome, 96.0, 96.0, 0),
+            (11, BuildingKind::Farm, 224.0, 96.0, 0),
+            (12, BuildingKind::Farm, 1000.0, 1000.0, 1),
+        ] {
+            map.add_instance(crate::resources::BuildingInstance {
+                kind,
+                position: Vec2::new(x, y),
+                town_idx: town,
+                slot,
+                faction: 1,
+            });
+        }
+        let towns = vec![crate::world::Town {
+            name: "T".into(),
+            center: Vec2::new(352.0, 352.0),
+            faction: 1,
+            kind: crate::constants::TownKind::Player,
+        }];
+        (map, towns)
+    }
+
+    #[test]
+    fn spawn_sites_keep_valid_and_snap_invalid() {
+        let (map, towns) = site_fixture();
+        // Exact home + farm: untouched
+        let ok = validate_spawn_sites(0, [96.0, 96.0], Some([224.0, 96.0]), &map, &towns);
+        assert_eq!(ok.home, [96.0, 96.0]);
+        assert_eq!(ok.work, Some([224.0, 96.0]));
+        assert!(!ok.home_fixed && !ok.work_fixed);
+        // Town center counts as home
+        let center = validate_spawn_sites(0, [352.0, 352.0], None, &map, &towns);
+        assert!(!center.home_fixed);
+        // Off-target positions snap to the nearest match of the NPC's town
+        let snapped = validate_spawn_sites(0, [150.0, 10.0], Some([900.0, 900.0]), &map, &towns);
+        assert_eq!(snapped.home, [96.0, 96.0]);
+        assert_eq!(snapped.work, Some([224.0, 96.0]), "town 1 farm is not ours");
+        assert!(snapped.home_fixed && snapped.work_fixed);
+    }
+
+    #[test]
+    fn spawn_sites_sentinels_and_townless_untouched() {
+        let (map, towns) = site_fixture();
+        let unset = validate_spawn_sites(0, [-1.0, -1.0], None, &map, &towns);
+        assert_eq!(unset.home, [-1.0, -1.0]);
+        assert_eq!(unset.work, None);
+        assert!(!unset.home_fixed && !unset.work_fixed);
+        // Migration groups home at a settle target, not a building
+        let townless = validate_spawn_sites(-1, [5.0, 5.0], None, &map, &towns);
+        assert_eq!(townless.home, [5.0, 5.0]);
+        // Town with no home buildings and no center: home cleared
+        let cleared = validate_spawn_sites(3, [5.0, 5.0], None, &map, &towns);
+        assert_eq!(cleared.home, [-1.0, -1.0]);
+        assert!(cleared.home_fixed);
+    }
+
+    #[test]
+    fn spawn_system_relocates_invalid_home() {
+        let (map, towns) = site_fixture();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(map)
+            .insert_resource(crate::world::WorldData { towns })
+            .init_resource::<PopulationStats>()
+            .init_resource::<FactionStats>()
+            .init_resource::<GameTime>()
+            .init_resource::<CombatConfig>()
+            .init_resource::<crate::resources::TownIndex>()
+            .init_resource::<DebugFlags>()
+            .init_resource::<SpawnOverrideQueue>()
+            .init_resource::<NpcLogCache>();
+        app.world_mut().resource_mut::<NpcLogCache>().mode = crate::settings::NpcLogMode::All;
+        app.add_message::<SpawnNpcMsg>()
+            .add_message::<GpuUpdateMsg>()
+            .add_message::<CombatLogMsg>()
+            .add_message::<crate::messages::BuildingGridDirtyMsg>()
+            .add_message::<crate::messages::TerrainDirtyMsg>()
+            .add_message::<crate::messages::PatrolsDirtyMsg>()
+            .add_message::<crate::messages::PatrolPerimeterDirtyMsg>()
+            .add_message::<crate::messages::HealingZonesDirtyMsg>()
+            .add_message::<SquadsDirtyMsg>()
+            .add_message::<MiningDirtyMsg>()
+            .add_message::<crate::messages::PatrolSwapMsg>();
+        app.add_systems(Update, spawn_npc_system);
+        app.world_mut().write_message(SpawnNpcMsg {
+            slot_idx: 0,
+            x: 150.0,
+            y: 10.0,
+            job: Job::Farmer as i32,
+            faction: 1,
+            town_idx: 0,
+            home_x: 150.0,
+            home_y: 10.0,
+            work_x: -1.0,
+            work_y: -1.0,
+            starting_post: -1,
+            entity_override: None,
+        });
+        app.update();
+
+        let entity = app
+            .world()
+            .resource::<EntityMap>()
+            .get_npc(0)
+            .unwrap()
+            .entity;
+        assert_eq!(
+            app.world().get::<Home>(entity).unwrap().0,
+            Vec2::new(96.0, 96.0)
+        );
+        let logs = &app.world().resource::<NpcLogCache>().logs[0];
+        assert!(
+            logs.iter()
+                .any(|e| e.message.starts_with("Spawn sites fixed: home (96,96)"))
+        );
+    }
+
+    #[test]
+    fn spawn_grid_is_deterministic_and_stops_at_capacity() {
+        let run = |count, reserve| {
+            let mut world = World::new();
+            world.init_resource::<GpuSlotPool>();
+            world.init_resource::<Messages<SpawnNpcMsg>>();
+            {
+                let mut pool = world.resource_mut::<GpuSlotPool>();
+                while pool.count() < crate::constants::MAX_ENTITIES - reserve {
+                    pool.alloc_reset();
+                }
+            }
+            let slots = spawn_grid(
+                &mut world,
+                count,
+                Job::Fighter,
+                2,
+                32.0,
+                Vec2::new(100.0, 50.0),
+            );
+            let msgs = world.resource::<Messages<SpawnNpcMsg>>();
+            let sent: Vec<(usize, f32, f32)> = msgs
+                .get_cursor()
+                .read(msgs)
+                .map(|m| (m.slot_idx, m.x, m.y))
+                .collect();
+            (slots, sent)
+        };
+
+        let (slots, sent) = run(10, 100);
+        assert_eq!(slots.len(), 10);
+        assert_eq!(sent.len(), 10);
+        // 10 units -> 4 wide; unit 5 is row 1, column 1
+        assert_eq!((sent[5].1, sent[5].2), (132.0, 82.0));
+        assert_eq!(run(10, 100), (slots, sent), "same inputs, same scenario");
+
+        // Only 3 slots left: partial set, same positions as the first 3 of the full grid
+        let (partial, partial_sent) = run(10, 3);
+        assert_eq!(partial.len(), 3);
+        assert_eq!(partial_sent.len(), 3);
+        assert_eq!((partial_sent[1].1, partial_sent[1].2), (132.0, 50.0));
+
+        // An absurd count only reserves what the pool can hand out
+        let (capped, _) = run(usize::MAX, 3);
+        assert_eq!(capped.len(), 3);
+    }
+
     #[test]
     fn default_weapon_only_on_fresh_spawn() {
         let restored = NpcSpawnOverrides {
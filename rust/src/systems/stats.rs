@@ -24,6 +24,23 @@ pub struct CombatConfig {
     pub attacks: HashMap<BaseAttackType, AttackTypeStats>,
     pub heal_rate: f32,
     pub heal_radius: f32,
+    /// Crowd press damage per application to NPCs in overfull grid cells (0 = off).
+    pub trample_damage: f32,
+    /// Base attack windup in seconds given to spawned NPCs (0 = instant attacks).
+    pub attack_windup: f32,
+    /// On target change mid-windup: true = keep the swing and redirect, false = whiff.
+    pub windup_redirect: bool,
+    /// Multiplier on resolved NPC damage (set from `BalanceConfig`).
+    pub damage_mult: f32,
+    /// Extra damage fraction for hits from behind the defender's facing (0 = off, capped at
+    /// `FLANK_BONUS_CAP`). Set from `BalanceConfig`.
+    pub flank_bonus: f32,
+    /// Energy below which attacks get weaker and slower (0 = fatigue off).
+    pub fatigue_threshold: f32,
+    /// Damage/attack-speed fraction lost at 0 energy, scaled down toward the threshold.
+    pub fatigue_penalty: f32,
+    /// Floor on the combined DPS fraction from fatigue and supply penalties.
+    pub combat_modifier_floor: f32,
 }
 
 impl Default for CombatConfig {
@@ -52,6 +69,14 @@ impl Default for CombatConfig {
             attacks,
             heal_rate: 5.0,
             heal_radius: 300.0,
+            trample_damage: 0.0,
+            attack_windup: 0.0,
+            windup_redirect: false,
+            damage_mult: 1.0,
+            flank_bonus: crate::constants::FLANK_BONUS,
+            fatigue_threshold: crate::constants::FATIGUE_ENERGY_THRESHOLD,
+            fatigue_penalty: crate::constants::FATIGUE_MAX_PENALTY,
+            combat_modifier_floor: crate::constants::COMBAT_MODIFIER_FLOOR,
         }
     }
 }
@@ -742,7 +767,8 @@ pub fn resolve_combat_stats(
             * upgrade_dmg
             * trait_mods.damage
             * level_mult
-            * (1.0 + weapon_bonus),
+            * (1.0 + weapon_bonus)
+            * config.damage_mult,
         range: atk_base.range * upgrade_range * trait_mods.range,
         cooldown: atk_base.cooldown * cooldown_mult * trait_mods.cooldown,
         projectile_speed: atk_base.projectile_speed * upgrade_proj_speed,
@@ -755,6 +781,44 @@ pub fn resolve_combat_stats(
     }
 }
 
+/// `resolve_combat_stats` for a live NPC entity as `job`, from its town upgrades, level,
+/// personality and equipment.
+pub fn resolve_npc_stats(world: &World, entity: Entity, job: Job) -> CachedStats {
+    let town_idx = world
+        .get::<crate::components::TownId>(entity)
+        .map_or(-1, |t| t.0);
+    let town_levels = world
+        .resource::<crate::resources::TownIndex>()
+        .0
+        .get(&town_idx)
+        .and_then(|&e| world.get::<crate::components::TownUpgradeLevel>(e))
+        .map(|u| u.0.clone())
+        .unwrap_or_default();
+    let level = world
+        .get::<crate::components::NpcStats>(entity)
+        .map_or(0, |s| level_from_xp(s.xp));
+    let personality = world
+        .get::<Personality>(entity)
+        .cloned()
+        .unwrap_or_default();
+    let (weapon, armor) = world
+        .get::<crate::components::NpcEquipment>(entity)
+        .map_or((0.0, 0.0), |eq| {
+            (eq.total_weapon_bonus(), eq.total_armor_bonus())
+        });
+    resolve_combat_stats(
+        job,
+        npc_def(job).default_attack_type,
+        town_idx,
+        level,
+        &personality,
+        world.resource::<CombatConfig>(),
+        &town_levels,
+        weapon,
+        armor,
+    )
+}
+
 /// Re-resolve NPC stats after equipment/upgrade change. Updates CachedStats, Speed, Health (proportional), GPU.
 pub fn re_resolve_npc_stats(
     entity: Entity,
@@ -844,7 +908,11 @@ pub fn process_upgrades_system(
         let levels = economy.towns.upgrade_levels(town_idx as i32);
         let mut food = economy.towns.food(town_idx as i32);
         let mut gold = economy.towns.gold(town_idx as i32);
-        if !upgrade_available(&levels, upgrade_idx, food, gold) {
+        if !upgrade_available(&levels, upgrade_idx, food, gold)
+            || economy
+                .towns
+                .upgrade_tech_locked(town_idx as i32, upgrade_idx)
+        {
             continue;
         }
 
@@ -874,19 +942,22 @@ pub fn process_upgrades_system(
         }
 
         if node.triggers_expansion {
+            let n = world_state.world_data.towns.len();
+            let mut area_levels: Vec<i32> =
+                (0..n).map(|i| economy.towns.area_level(i as i32)).collect();
             let mut al = economy.towns.area_level(town_idx as i32);
             let _ = crate::world::expand_town_build_area(
                 &mut world_state.grid,
                 &world_state.world_data.towns,
-                &world_state.entity_map,
+                &area_levels,
                 town_idx,
                 &mut al,
             );
             economy.towns.set_area_level(town_idx as i32, al);
             // Rebuild buildability with updated area levels
-            let n = world_state.world_data.towns.len();
-            let area_levels: Vec<i32> =
-                (0..n).map(|i| economy.towns.area_level(i as i32)).collect();
+            if let Some(level) = area_levels.get_mut(town_idx) {
+                *level = al;
+            }
             world_state.grid.sync_town_buildability(
                 &world_state.world_data.towns,
                 &area_levels,
@@ -1159,6 +1230,55 @@ pub fn auto_upgrade_system(
     }
 }
 
+/// Once per game hour, queues an Expansion upgrade for `auto_expand` towns whose build area
+/// is at least `AUTO_EXPAND_UTILIZATION` full. Goes through the normal upgrade path, so it
+/// costs the usual expansion price (above policy reserves) and respects prerequisites.
+pub fn auto_expand_system(
+    game_time: Res<crate::resources::GameTime>,
+    town_access: crate::systemparams::TownAccess,
+    world_data: Res<crate::world::WorldData>,
+    grid: Res<crate::world::WorldGrid>,
+    entity_map: Res<crate::resources::EntityMap>,
+    mut queue: MessageWriter<UpgradeMsg>,
+) {
+    if !game_time.hour_ticked {
+        return;
+    }
+
+    for (town_idx, town) in world_data.towns.iter().enumerate() {
+        let ti = town_idx as i32;
+        let Some(policy) = town_access.policy(ti) else {
+            continue;
+        };
+        if !policy.auto_expand {
+            continue;
+        }
+        let (used, total) = crate::world::town_build_utilization(
+            &grid,
+            &entity_map,
+            town,
+            town_idx,
+            town_access.area_level(ti),
+        );
+        if total == 0 || (used as f32) < total as f32 * crate::constants::AUTO_EXPAND_UTILIZATION {
+            continue;
+        }
+        let levels = town_access.upgrade_levels(ti);
+        let food = (town_access.food(ti) - policy.reserve_food).max(0);
+        let gold = (town_access.gold(ti) - policy.reserve_gold).max(0);
+        let next =
+            UPGRADES.nodes.iter().enumerate().position(|(i, n)| {
+                n.triggers_expansion && upgrade_available(&levels, i, food, gold)
+            });
+        if let Some(upgrade_idx) = next {
+            queue.write(UpgradeMsg {
+                town_idx,
+                upgrade_idx,
+            });
+        }
+    }
+}
+
 /// Auto-buy cheapest tower upgrade each game-hour for towers with auto_upgrade enabled.
 pub fn auto_tower_upgrade_system(
     game_time: Res<crate::resources::GameTime>,
@@ -0,0 +1,143 @@
+//! Supply lines — field units degrade when they push too far from friendly territory.
+//! A military unit is supplied while inside its town's build area, sheltered in a building
+//! (any restful activity), or within the town's `supply_radius` of any building of its
+//! faction. Everyone else gets `OutOfSupply`: attack_system stretches the attack cooldown by
+//! `SUPPLY_COOLDOWN_MULT` and energy_system drains `SUPPLY_ENERGY_DRAIN_MULT` times faster.
+//! The marker is recomputed every check and the penalties apply at use, so walking back into
+//! supply lifts them without touching CachedStats.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::constants::SUPPLY_CHECK_SECS;
+use crate::resources::*;
+use crate::world::WorldGrid;
+
+/// True if a unit of `faction`/`town_idx` standing at `pos` is in supply. A radius of 0
+/// disables supply lines. The building check uses the EntityMap spatial grid and stops at
+/// the first friendly building, so it stays cheap however large the map gets.
+pub fn is_supplied(
+    entity_map: &EntityMap,
+    grid: &WorldGrid,
+    pos: Vec2,
+    faction: i32,
+    town_idx: i32,
+    radius: f32,
+) -> bool {
+    if radius <= 0.0 {
+        return true;
+    }
+    if town_idx >= 0 && !grid.town_owner.is_empty() {
+        let (col, row) = grid.world_to_grid(pos);
+        if grid.can_town_build(col, row, town_idx as u16) {
+            return true;
+        }
+    }
+    entity_map.any_faction_building_within(pos, radius, faction)
+}
+
+/// Mark military units out of supply (or back in). Runs every `SUPPLY_CHECK_SECS`.
+pub fn supply_system(
+    mut commands: Commands,
+    town_access: crate::systemparams::TownAccess,
+    entity_map: Res<EntityMap>,
+    grid: Res<WorldGrid>,
+    gpu_state: Res<GpuReadState>,
+    game_time: Res<GameTime>,
+    mut last_check: Local<f32>,
+    npc_q: Query<
+        (
+            Entity,
+            &GpuSlot,
+            &Job,
+            &Faction,
+            &Activity,
+            Has<OutOfSupply>,
+        ),
+        (Without<Building>, Without<Dead>),
+    >,
+) {
+    let now = game_time.total_seconds;
+    // A clock that restarted behind us (new game, load) checks right away
+    if now >= *last_check && now < *last_check + SUPPLY_CHECK_SECS {
+        return;
+    }
+    *last_check = now;
+
+    let positions = &gpu_state.positions;
+    let mut radius_by_town: HashMap<i32, f32> = HashMap::new();
+    for (entity, slot, job, faction, activity, out_of_supply) in npc_q.iter() {
+        let supplied = if !job.is_military() || activity.kind.def().is_restful {
+            true
+        } else {
+            let Some(pos) = positions
+                .get(slot.0 * 2..slot.0 * 2 + 2)
+                .map(|p| Vec2::new(p[0], p[1]))
+                .filter(|p| p.x > -9000.0)
+            else {
+                continue;
+            };
+            let town_idx = entity_map.get_npc(slot.0).map_or(-1, |n| n.town_idx);
+            let radius = *radius_by_town.entry(town_idx).or_insert_with(|| {
+                town_access
+                    .policy(town_idx)
+                    .map_or(0.0, |p| p.supply_radius)
+            });
+            is_supplied(&entity_map, &grid, pos, faction.0, town_idx, radius)
+        };
+        if supplied && out_of_supply {
+            commands.entity(entity).remove::<OutOfSupply>();
+        } else if !supplied && !out_of_supply {
+            commands.entity(entity).insert(OutOfSupply);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::BuildingKind;
+
+    fn map_with_farm(pos: Vec2, faction: i32) -> EntityMap {
+        let mut em = EntityMap::default();
+        em.init_spatial(8192.0);
+        em.add_instance(BuildingInstance {
+            kind: BuildingKind::Farm,
+            position: pos,
+            slot: 0,
+            town_idx: 0,
+            faction,
+        });
+        em
+    }
+
+    #[test]
+    fn supplied_near_friendly_building_only() {
+        let em = map_with_farm(Vec2::new(1000.0, 1000.0), 1);
+        let grid = WorldGrid::default();
+        let near = Vec2::new(1800.0, 1000.0);
+        let far = Vec2::new(4000.0, 4000.0);
+        assert!(is_supplied(&em, &grid, near, 1, 0, 1200.0));
+        assert!(!is_supplied(&em, &grid, far, 1, 0, 1200.0));
+        // An enemy farm supplies nobody else
+        assert!(!is_supplied(&em, &grid, near, 2, 1, 1200.0));
+        // Radius 0 turns supply lines off
+        assert!(is_supplied(&em, &grid, far, 1, 0, 0.0));
+    }
+
+    #[test]
+    fn own_build_area_always_supplied() {
+        let em = EntityMap::default();
+        let mut grid = WorldGrid {
+            width: 10,
+            height: 10,
+            ..Default::default()
+        };
+        grid.town_owner = vec![0u16; 100];
+        let pos = Vec2::new(300.0, 300.0);
+        assert!(is_supplied(&em, &grid, pos, 1, 0, 100.0));
+        assert!(!is_supplied(&em, &grid, pos, 2, 1, 100.0));
+    }
+}
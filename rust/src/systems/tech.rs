@@ -0,0 +1,479 @@
+//! Tech tree — optional research progression that locks buildings and town upgrades.
+//! Each tech costs research points and may require other techs or a building the town owns.
+//! Towns earn points every game hour (a flat trickle plus a bonus per research building) and
+//! spend them with `unlock_tech` (AI builder towns pick `next_affordable_tech` on their
+//! decision tick; raider towns earn nothing and stay locked). Until a tech is unlocked, the kinds it lists report
+//! `BuildBlock::TechLocked` through `BuildCheck` and the upgrades it lists are refused.
+//! Definitions are validated (unknown ids, prerequisite cycles) whenever they are set or
+//! loaded from a save, so the unlock check never walks a bad graph. Off by default.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::{EntityMap, GameTime};
+use crate::systems::stats::UPGRADES;
+use crate::world::{BuildingKind, WorldData};
+
+/// One researchable tech.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TechDef {
+    pub id: String,
+    /// Research points spent on unlock.
+    pub cost: f32,
+    /// Techs that must be unlocked first.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Building the town must own before unlocking.
+    #[serde(default)]
+    pub requires_building: Option<BuildingKind>,
+    /// Building kinds locked until this tech is unlocked.
+    #[serde(default)]
+    pub unlocks_buildings: Vec<BuildingKind>,
+    /// Town upgrades locked until this tech is unlocked, as "Category:Label".
+    #[serde(default)]
+    pub unlocks_upgrades: Vec<String>,
+}
+
+/// A town's research progress.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TownResearch {
+    pub points: f32,
+    pub unlocked: Vec<String>,
+}
+
+/// Tech definitions, research settings and per-town progress (`endless/tech`).
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TechTree {
+    pub enabled: bool,
+    /// Points every town earns per game hour.
+    pub research_per_hour: f32,
+    /// Extra points per hour for each `research_building` the town owns.
+    pub research_per_building: f32,
+    pub research_building: Option<BuildingKind>,
+    defs: Vec<TechDef>,
+    /// Indexed by town.
+    pub towns: Vec<TownResearch>,
+}
+
+impl Default for TechTree {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            research_per_hour: 1.0,
+            research_per_building: 2.0,
+            research_building: Some(BuildingKind::Merchant),
+            defs: default_tech_defs(),
+            towns: Vec::new(),
+        }
+    }
+}
+
+/// Stock techs: walls and quarries, then towers and fountain range, then crossbows.
+/// Casinos hang off owning a merchant.
+pub fn default_tech_defs() -> Vec<TechDef> {
+    let tech = |id: &str, cost: f32, requires: &[&str]| TechDef {
+        id: id.to_string(),
+        cost,
+        requires: requires.iter().map(|s| s.to_string()).collect(),
+        requires_building: None,
+        unlocks_buildings: Vec::new(),
+        unlocks_upgrades: Vec::new(),
+    };
+    vec![
+        TechDef {
+            unlocks_buildings: vec![BuildingKind::Wall, BuildingKind::Quarry],
+            ..tech("masonry", 40.0, &[])
+        },
+        TechDef {
+            unlocks_buildings: vec![BuildingKind::Tower],
+            unlocks_upgrades: vec!["Town:Fountain Range".to_string()],
+            ..tech("fortification", 120.0, &["masonry"])
+        },
+        TechDef {
+            requires_building: Some(BuildingKind::ArcherHome),
+            unlocks_buildings: vec![BuildingKind::CrossbowHome],
+            ..tech("crossbows", 160.0, &["fortification"])
+        },
+        TechDef {
+            requires_building: Some(BuildingKind::Merchant),
+            unlocks_buildings: vec![BuildingKind::Casino],
+            ..tech("gambling", 100.0, &[])
+        },
+    ]
+}
+
+/// Look up an upgrade node index by "Category:Label".
+fn upgrade_index(key: &str) -> Option<usize> {
+    let (category, label) = key.split_once(':')?;
+    UPGRADES
+        .nodes
+        .iter()
+        .position(|n| n.category == category && n.label == label)
+}
+
+/// Reject tech definitions the unlock check can't evaluate: empty or duplicate ids, negative
+/// costs, unknown prerequisites or upgrades, and prerequisite cycles.
+pub fn validate_tech_defs(defs: &[TechDef]) -> Result<(), String> {
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    for (i, def) in defs.iter().enumerate() {
+        if def.id.is_empty() {
+            return Err(format!("tech #{i} has an empty id"));
+        }
+        if ids.insert(def.id.as_str(), i).is_some() {
+            return Err(format!("duplicate tech id '{}'", def.id));
+        }
+        if !def.cost.is_finite() || def.cost < 0.0 {
+            return Err(format!(
+                "tech '{}' has an invalid cost {}",
+                def.id, def.cost
+            ));
+        }
+    }
+    for def in defs {
+        if let Some(req) = def.requires.iter().find(|r| !ids.contains_key(r.as_str())) {
+            return Err(format!("tech '{}' requires unknown tech '{req}'", def.id));
+        }
+        if let Some(key) = def
+            .unlocks_upgrades
+            .iter()
+            .find(|k| upgrade_index(k).is_none())
+        {
+            return Err(format!("tech '{}' unlocks unknown upgrade '{key}'", def.id));
+        }
+    }
+
+    // Depth-first walk; meeting a node already on the stack closes a cycle
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        OnStack,
+        Done,
+    }
+    fn visit(
+        i: usize,
+        defs: &[TechDef],
+        ids: &HashMap<&str, usize>,
+        marks: &mut [Mark],
+        stack: &mut Vec<usize>,
+    ) -> Result<(), String> {
+        match marks[i] {
+            Mark::Done => return Ok(()),
+            Mark::OnStack => {
+                let start = stack.iter().position(|&s| s == i).unwrap_or(0);
+                let mut path: Vec<&str> = stack[start..]
+                    .iter()
+                    .map(|&s| defs[s].id.as_str())
+                    .collect();
+                path.push(defs[i].id.as_str());
+                return Err(format!("tech prerequisite cycle: {}", path.join(" -> ")));
+            }
+            Mark::New => {}
+        }
+        marks[i] = Mark::OnStack;
+        stack.push(i);
+        for req in &defs[i].requires {
+            visit(ids[req.as_str()], defs, ids, marks, stack)?;
+        }
+        stack.pop();
+        marks[i] = Mark::Done;
+        Ok(())
+    }
+    let mut marks = vec![Mark::New; defs.len()];
+    let mut stack = Vec::new();
+    for i in 0..defs.len() {
+        visit(i, defs, &ids, &mut marks, &mut stack)?;
+    }
+    Ok(())
+}
+
+/// One tech as seen by one town.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TechStatus {
+    pub id: String,
+    pub cost: f32,
+    pub unlocked: bool,
+    /// Prerequisites (techs or building) met; only research points may be missing.
+    pub available: bool,
+    pub missing: Vec<String>,
+}
+
+impl TechTree {
+    pub fn defs(&self) -> &[TechDef] {
+        &self.defs
+    }
+
+    /// Replace the tech definitions after validating them. Unlocks of removed ids are dropped.
+    pub fn set_tech_defs(&mut self, defs: Vec<TechDef>) -> Result<(), String> {
+        validate_tech_defs(&defs)?;
+        let known: HashSet<&str> = defs.iter().map(|d| d.id.as_str()).collect();
+        for town in &mut self.towns {
+            town.unlocked.retain(|id| known.contains(id.as_str()));
+        }
+        self.defs = defs;
+        Ok(())
+    }
+
+    /// Check definitions and unlocks restored from a save.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_tech_defs(&self.defs)?;
+        for (t, town) in self.towns.iter().enumerate() {
+            if let Some(id) = town
+                .unlocked
+                .iter()
+                .find(|id| !self.defs.iter().any(|d| &d.id == *id))
+            {
+                return Err(format!("town {t} has unknown tech '{id}' unlocked"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn ensure_towns(&mut self, n: usize) {
+        if self.towns.len() < n {
+            self.towns.resize_with(n, TownResearch::default);
+        }
+    }
+
+    /// Clear every town's points and unlocks (new game).
+    pub fn reset_research(&mut self) {
+        self.towns.clear();
+    }
+
+    pub fn is_unlocked(&self, town_idx: usize, id: &str) -> bool {
+        self.towns
+            .get(town_idx)
+            .is_some_and(|t| t.unlocked.iter().any(|u| u == id))
+    }
+
+    fn locking_defs(&self, town_idx: usize) -> impl Iterator<Item = &TechDef> {
+        let enabled = self.enabled;
+        self.defs
+            .iter()
+            .filter(move |d| enabled && !self.is_unlocked(town_idx, &d.id))
+    }
+
+    /// Building kinds the town can't build yet. Empty while disabled.
+    pub fn locked_buildings(&self, town_idx: usize) -> Vec<BuildingKind> {
+        let mut kinds: Vec<BuildingKind> = self
+            .locking_defs(town_idx)
+            .flat_map(|d| d.unlocks_buildings.iter().copied())
+            .collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+        kinds
+    }
+
+    /// True if upgrade `upgrade_idx` still waits on a tech for this town.
+    pub fn upgrade_locked(&self, town_idx: usize, upgrade_idx: usize) -> bool {
+        self.locking_defs(town_idx).any(|d| {
+            d.unlocks_upgrades
+                .iter()
+                .any(|k| upgrade_index(k) == Some(upgrade_idx))
+        })
+    }
+
+    /// What keeps `def` from being unlocked, research points aside.
+    fn missing_prereqs(
+        &self,
+        town_idx: usize,
+        def: &TechDef,
+        entity_map: &EntityMap,
+    ) -> Vec<String> {
+        let mut missing: Vec<String> = def
+            .requires
+            .iter()
+            .filter(|r| !self.is_unlocked(town_idx, r))
+            .cloned()
+            .collect();
+        if let Some(kind) = def.requires_building {
+            if entity_map.count_for_town(kind, town_idx as u32) == 0 {
+                missing.push(format!("{kind:?}"));
+            }
+        }
+        missing
+    }
+
+    /// Every tech with its unlock state for one town.
+    pub fn get_tech_status(&self, town_idx: usize, entity_map: &EntityMap) -> Vec<TechStatus> {
+        self.defs
+            .iter()
+            .map(|def| {
+                let missing = self.missing_prereqs(town_idx, def, entity_map);
+                TechStatus {
+                    id: def.id.clone(),
+                    cost: def.cost,
+                    unlocked: self.is_unlocked(town_idx, &def.id),
+                    available: missing.is_empty(),
+                    missing,
+                }
+            })
+            .collect()
+    }
+
+    /// Cheapest tech the town could unlock right now (prerequisites met, enough points).
+    /// AI towns spend research through this.
+    pub fn next_affordable_tech(&self, town_idx: usize, entity_map: &EntityMap) -> Option<&str> {
+        if !self.enabled {
+            return None;
+        }
+        let points = self.towns.get(town_idx).map_or(0.0, |t| t.points);
+        self.defs
+            .iter()
+            .filter(|d| d.cost <= points && !self.is_unlocked(town_idx, &d.id))
+            .filter(|d| self.missing_prereqs(town_idx, d, entity_map).is_empty())
+            .min_by(|a, b| a.cost.total_cmp(&b.cost))
+            .map(|d| d.id.as_str())
+    }
+
+    /// Spend a town's research on `id`. Prerequisites are checked one level deep;
+    /// validation already guarantees the graph is acyclic.
+    pub fn unlock_tech(
+        &mut self,
+        town_idx: usize,
+        id: &str,
+        entity_map: &EntityMap,
+    ) -> Result<(), String> {
+        if !self.enabled {
+            return Err("tech tree is disabled".into());
+        }
+        let def = self
+            .defs
+            .iter()
+            .find(|d| d.id == id)
+            .ok_or_else(|| format!("unknown tech '{id}'"))?;
+        if self.is_unlocked(town_idx, id) {
+            return Err(format!("'{id}' is already unlocked"));
+        }
+        let missing = self.missing_prereqs(town_idx, def, entity_map);
+        if !missing.is_empty() {
+            return Err(format!("'{id}' requires {}", missing.join(", ")));
+        }
+        let cost = def.cost;
+        self.ensure_towns(town_idx + 1);
+        let town = &mut self.towns[town_idx];
+        if town.points < cost {
+            return Err(format!(
+                "'{id}' needs {cost:.0} research, town has {:.0}",
+                town.points
+            ));
+        }
+        town.points -= cost;
+        town.unlocked.push(id.to_string());
+        Ok(())
+    }
+}
+
+/// Hourly research: a flat trickle per town plus a bonus per research building.
+pub fn research_system(
+    game_time: Res<GameTime>,
+    mut tech: ResMut<TechTree>,
+    world_data: Res<WorldData>,
+    entity_map: Res<EntityMap>,
+) {
+    if !tech.enabled || !game_time.hour_ticked {
+        return;
+    }
+    tech.ensure_towns(world_data.towns.len());
+    let (per_hour, per_building, building) = (
+        tech.research_per_hour,
+        tech.research_per_building,
+        tech.research_building,
+    );
+    for (i, town) in world_data.towns.iter().enumerate() {
+        if town.is_raider() {
+            continue;
+        }
+        let buildings = building.map_or(0, |k| entity_map.count_for_town(k, i as u32));
+        tech.towns[i].points += per_hour + per_building * buildings as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(id: &str, requires: &[&str]) -> TechDef {
+        TechDef {
+            id: id.to_string(),
+            cost: 10.0,
+            requires: requires.iter().map(|s| s.to_string()).collect(),
+            requires_building: None,
+            unlocks_buildings: Vec::new(),
+            unlocks_upgrades: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn validation_rejects_cycles_and_unknown_ids() {
+        assert_eq!(validate_tech_defs(&default_tech_defs()), Ok(()));
+
+        let cycle = vec![def("a", &["c"]), def("b", &["a"]), def("c", &["b"])];
+        let err = validate_tech_defs(&cycle).unwrap_err();
+        assert!(err.contains("cycle"), "{err}");
+        assert!(err.contains("a -> c -> b -> a"), "{err}");
+        assert!(validate_tech_defs(&[def("a", &["a"])]).is_err());
+
+        let unknown = vec![def("a", &["ghost"])];
+        assert!(validate_tech_defs(&unknown).unwrap_err().contains("ghost"));
+        let dup = vec![def("a", &[]), def("a", &[])];
+        assert!(validate_tech_defs(&dup).unwrap_err().contains("duplicate"));
+        let mut bad_upgrade = def("a", &[]);
+        bad_upgrade.unlocks_upgrades = vec!["Town:Nope".into()];
+        assert!(validate_tech_defs(&[bad_upgrade]).is_err());
+
+        // A rejected set leaves the current definitions alone
+        let mut tree = TechTree::default();
+        assert!(tree.set_tech_defs(cycle).is_err());
+        assert_eq!(tree.defs(), default_tech_defs().as_slice());
+    }
+
+    #[test]
+    fn unlock_needs_prereqs_and_points_then_clears_locks() {
+        let entity_map = EntityMap::default();
+        let mut tree = TechTree {
+            enabled: true,
+            ..Default::default()
+        };
+        tree.ensure_towns(1);
+        assert!(tree.locked_buildings(0).contains(&BuildingKind::Tower));
+        let range = upgrade_index("Town:Fountain Range").unwrap();
+        assert!(tree.upgrade_locked(0, range));
+
+        // Prerequisite before points
+        tree.towns[0].points = 1000.0;
+        assert!(
+            tree.unlock_tech(0, "fortification", &entity_map)
+                .unwrap_err()
+                .contains("masonry")
+        );
+        tree.towns[0].points = 10.0;
+        assert!(tree.unlock_tech(0, "masonry", &entity_map).is_err());
+        tree.towns[0].points = 200.0;
+        tree.unlock_tech(0, "masonry", &entity_map).unwrap();
+        tree.unlock_tech(0, "fortification", &entity_map).unwrap();
+        assert_eq!(tree.towns[0].points, 40.0);
+        assert!(!tree.locked_buildings(0).contains(&BuildingKind::Tower));
+        assert!(!tree.upgrade_locked(0, range));
+        // Other towns keep their locks
+        assert!(tree.locked_buildings(1).contains(&BuildingKind::Tower));
+
+        // Building prerequisite
+        let status = tree.get_tech_status(0, &entity_map);
+        let crossbows = status.iter().find(|s| s.id == "crossbows").unwrap();
+        assert!(!crossbows.available);
+        assert_eq!(crossbows.missing, vec!["ArcherHome".to_string()]);
+
+        // AI pick: cheapest affordable tech whose prerequisites are met
+        tree.ensure_towns(2);
+        assert_eq!(tree.next_affordable_tech(1, &entity_map), None);
+        tree.towns[1].points = 1000.0;
+        assert_eq!(tree.next_affordable_tech(1, &entity_map), Some("masonry"));
+        assert_eq!(tree.next_affordable_tech(0, &entity_map), None);
+
+        // Disabled tree locks nothing
+        tree.enabled = false;
+        assert!(tree.locked_buildings(1).is_empty());
+    }
+}
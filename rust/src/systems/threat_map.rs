@@ -0,0 +1,289 @@
+//! Threat map — coarse per-faction grid of military strength for AI targeting and HUD overlays.
+//! Every `THREAT_MAP_UPDATE_SECS` the system rebuilds one strength grid per faction from live
+//! military units. An observer's view of a cell is the sum over every faction hostile to it
+//! (different faction, not neutral). There is no fog of war yet, so every unit counts.
+//!
+//! Strength is an integer per unit (`unit_strength`) derived from CachedStats, which already
+//! folds in level, upgrades, traits, weapon and armor, times `OFFICER_THREAT_MULT` for officers.
+//! Cells hold integer sums, so the map is identical whatever order the query yields units in.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::constants::{
+    FACTION_NEUTRAL, OFFICER_THREAT_MULT, THREAT_MAP_CELL_SIZE, THREAT_MAP_UPDATE_SECS,
+};
+use crate::resources::*;
+
+/// Per-faction strength grids, row-major `cols x rows` cells of `cell_size` px.
+#[derive(Resource, Default)]
+pub struct ThreatMap {
+    pub cell_size: f32,
+    pub cols: usize,
+    pub rows: usize,
+    pub origin: Vec2,
+    /// Game seconds of the last rebuild.
+    pub updated_at: f32,
+    /// BTreeMap keeps faction iteration (and BRP output) stable.
+    by_faction: BTreeMap<i32, Vec<u32>>,
+}
+
+/// A downsampled view of one observer's enemy strength (see `ThreatMap::get_threat_map`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreatGrid {
+    pub cell_size: f32,
+    pub cols: usize,
+    pub rows: usize,
+    /// Row-major enemy strength per cell.
+    pub values: Vec<u32>,
+}
+
+/// Combat strength one unit adds to its cell: DPS weighted by toughness (max HP / 100),
+/// doubled for officers. Non-military jobs contribute nothing.
+pub fn unit_strength(job: Job, stats: &CachedStats, officer: bool) -> u32 {
+    if !job.is_military() {
+        return 0;
+    }
+    let dps = stats.damage / stats.cooldown.max(0.1);
+    let mut strength = dps * stats.max_health / 100.0;
+    if officer {
+        strength *= OFFICER_THREAT_MULT;
+    }
+    strength.round().max(0.0) as u32
+}
+
+fn is_hostile(observer: i32, faction: i32) -> bool {
+    faction != observer && faction != FACTION_NEUTRAL
+}
+
+impl ThreatMap {
+    /// Clear all strength and size the grid to cover `bounds`.
+    pub fn reset(&mut self, bounds: &WorldBounds, cell_size: f32) {
+        let cell_size = cell_size.max(1.0);
+        let size = if bounds.is_set() {
+            bounds.max - bounds.min
+        } else {
+            Vec2::ZERO
+        };
+        self.cell_size = cell_size;
+        self.origin = bounds.min;
+        self.cols = (size.x / cell_size).ceil().max(1.0) as usize;
+        self.rows = (size.y / cell_size).ceil().max(1.0) as usize;
+        self.by_faction.clear();
+    }
+
+    fn cell_coords(&self, pos: Vec2) -> Option<(usize, usize)> {
+        if self.cols == 0 || !pos.is_finite() {
+            return None;
+        }
+        let local = (pos - self.origin) / self.cell_size;
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+        let (c, r) = (local.x as usize, local.y as usize);
+        (c < self.cols && r < self.rows).then_some((c, r))
+    }
+
+    /// Add `strength` for `faction` at world position `pos`. Off-map positions are ignored.
+    pub fn add(&mut self, faction: i32, pos: Vec2, strength: u32) {
+        if strength == 0 {
+            return;
+        }
+        let Some((c, r)) = self.cell_coords(pos) else {
+            return;
+        };
+        let len = self.cols * self.rows;
+        let grid = self
+            .by_faction
+            .entry(faction)
+            .or_insert_with(|| vec![0; len]);
+        grid[r * self.cols + c] = grid[r * self.cols + c].saturating_add(strength);
+    }
+
+    fn enemy_cell(&self, observer: i32, c: usize, r: usize) -> u32 {
+        let i = r * self.cols + c;
+        self.by_faction
+            .iter()
+            .filter(|&(&f, _)| is_hostile(observer, f))
+            .fold(0u32, |acc, (_, grid)| acc.saturating_add(grid[i]))
+    }
+
+    /// Strength of a single faction in the cell containing `pos`.
+    pub fn faction_strength_at(&self, faction: i32, pos: Vec2) -> u32 {
+        let Some((c, r)) = self.cell_coords(pos) else {
+            return 0;
+        };
+        self.by_faction
+            .get(&faction)
+            .map_or(0, |g| g[r * self.cols + c])
+    }
+
+    /// Enemy strength `observer` faces within `radius_cells` cells of `pos` (0 = just that cell).
+    pub fn enemy_strength_near(&self, observer: i32, pos: Vec2, radius_cells: usize) -> u32 {
+        let Some((c, r)) = self.cell_coords(pos) else {
+            return 0;
+        };
+        let mut total = 0u32;
+        for row in r.saturating_sub(radius_cells)..=(r + radius_cells).min(self.rows - 1) {
+            for col in c.saturating_sub(radius_cells)..=(c + radius_cells).min(self.cols - 1) {
+                total = total.saturating_add(self.enemy_cell(observer, col, row));
+            }
+        }
+        total
+    }
+
+    /// Enemy strength as seen by `observer`, downsampled to cells of at least `resolution` px.
+    /// The output cell size rounds up to a whole number of map cells; 0 = native resolution.
+    pub fn get_threat_map(&self, observer: i32, resolution: f32) -> ThreatGrid {
+        if self.cols == 0 {
+            return ThreatGrid {
+                cell_size: 0.0,
+                cols: 0,
+                rows: 0,
+                values: Vec::new(),
+            };
+        }
+        let step = if resolution.is_finite() && resolution > self.cell_size {
+            (resolution / self.cell_size).ceil() as usize
+        } else {
+            1
+        };
+        let cols = self.cols.div_ceil(step);
+        let rows = self.rows.div_ceil(step);
+        let mut values = vec![0u32; cols * rows];
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                let v = &mut values[(r / step) * cols + c / step];
+                *v = v.saturating_add(self.enemy_cell(observer, c, r));
+            }
+        }
+        ThreatGrid {
+            cell_size: self.cell_size * step as f32,
+            cols,
+            rows,
+            values,
+        }
+    }
+
+    /// Total strength per faction across the whole map, in faction order.
+    pub fn faction_totals(&self) -> Vec<(i32, u64)> {
+        self.by_faction
+            .iter()
+            .map(|(&f, g)| (f, g.iter().map(|&v| v as u64).sum()))
+            .collect()
+    }
+}
+
+/// Rebuild the threat map from live military units. Runs every `THREAT_MAP_UPDATE_SECS`.
+pub fn threat_map_system(
+    mut threat_map: ResMut<ThreatMap>,
+    bounds: Res<WorldBounds>,
+    gpu_state: Res<GpuReadState>,
+    game_time: Res<GameTime>,
+    mut last_update: Local<Option<f32>>,
+    npc_q: Query<
+        (&GpuSlot, &Job, &Faction, &CachedStats, Has<Officer>),
+        (Without<Building>, Without<Dead>),
+    >,
+) {
+    let now = game_time.total_seconds;
+    // A clock that restarted behind us (new game, load) rebuilds right away
+    if let Some(last) = *last_update
+        && now >= last
+        && now < last + THREAT_MAP_UPDATE_SECS
+    {
+        return;
+    }
+    *last_update = Some(now);
+
+    threat_map.reset(&bounds, THREAT_MAP_CELL_SIZE);
+    threat_map.updated_at = now;
+    let positions = &gpu_state.positions;
+    for (slot, job, faction, stats, officer) in npc_q.iter() {
+        let strength = unit_strength(*job, stats, officer);
+        if strength == 0 {
+            continue;
+        }
+        let Some(pos) = positions
+            .get(slot.0 * 2..slot.0 * 2 + 2)
+            .map(|p| Vec2::new(p[0], p[1]))
+            .filter(|p| p.x > -9000.0)
+        else {
+            continue;
+        };
+        threat_map.add(faction.0, pos, strength);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> ThreatMap {
+        let mut tm = ThreatMap::default();
+        tm.reset(&WorldBounds::from_grid(64, 64, 32.0), 512.0);
+        tm
+    }
+
+    #[test]
+    fn observer_sees_only_hostile_strength() {
+        let mut tm = map();
+        assert_eq!((tm.cols, tm.rows), (4, 4));
+        let pos = Vec2::new(100.0, 100.0);
+        tm.add(1, pos, 10);
+        tm.add(2, pos, 7);
+        tm.add(FACTION_NEUTRAL, pos, 50);
+        assert_eq!(tm.enemy_strength_near(1, pos, 0), 7);
+        assert_eq!(tm.enemy_strength_near(2, pos, 0), 10);
+        assert_eq!(tm.enemy_strength_near(3, pos, 0), 17);
+        // Neighbor cell is empty unless the radius reaches back
+        let next = Vec2::new(700.0, 100.0);
+        assert_eq!(tm.enemy_strength_near(3, next, 0), 0);
+        assert_eq!(tm.enemy_strength_near(3, next, 1), 17);
+        // Off-map adds are dropped
+        tm.add(2, Vec2::new(-5.0, 100.0), 99);
+        assert_eq!(tm.faction_totals(), vec![(0, 50), (1, 10), (2, 7)]);
+    }
+
+    #[test]
+    fn downsampled_grid_sums_blocks() {
+        let mut tm = map();
+        tm.add(2, Vec2::new(100.0, 100.0), 3);
+        tm.add(2, Vec2::new(600.0, 600.0), 4);
+        tm.add(2, Vec2::new(1900.0, 1900.0), 5);
+        let native = tm.get_threat_map(1, 0.0);
+        assert_eq!((native.cols, native.rows, native.cell_size), (4, 4, 512.0));
+        assert_eq!(native.values.iter().sum::<u32>(), 12);
+        let coarse = tm.get_threat_map(1, 1000.0);
+        assert_eq!((coarse.cols, coarse.rows, coarse.cell_size), (2, 2, 1024.0));
+        assert_eq!(coarse.values, vec![7, 0, 0, 5]);
+        // Own strength is invisible to the owner
+        assert!(tm.get_threat_map(2, 0.0).values.iter().all(|&v| v == 0));
+    }
+
+    #[test]
+    fn strength_tracks_stats_and_rank() {
+        let stats = CachedStats {
+            damage: 15.0,
+            range: 100.0,
+            cooldown: 1.5,
+            projectile_speed: 0.0,
+            projectile_lifetime: 0.0,
+            max_health: 100.0,
+            speed: 100.0,
+            stamina: 1.0,
+            hp_regen: 0.0,
+            berserk_bonus: 0.0,
+        };
+        assert_eq!(unit_strength(Job::Archer, &stats, false), 10);
+        assert_eq!(unit_strength(Job::Archer, &stats, true), 20);
+        let armored = CachedStats {
+            max_health: 150.0,
+            ..stats.clone()
+        };
+        assert_eq!(unit_strength(Job::Archer, &armored, false), 15);
+        assert_eq!(unit_strength(Job::Farmer, &stats, false), 0);
+    }
+}
@@ -0,0 +1,356 @@
+//! Win conditions — optional scenario goals checked every `VICTORY_CHECK_SECS`.
+//! `WinCondition` holds a victory and a defeat `Condition` tree for one faction (All/Any nest
+//! for AND/OR). When either is met, `victory_system` records the outcome, writes a
+//! `GameOverMsg` (drained into `GameOverOutbox`) plus a combat log line and, if asked, pauses
+//! behind the game over screen. The resource is saved, so a loaded scenario keeps checking.
+//!
+//! There is no town capture mechanic: a town counts as standing while its fountain stands,
+//! and "capture all towns" means every rival town has fallen.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{GoldStore, TownMarker};
+use crate::constants::{FACTION_NEUTRAL, FACTION_PLAYER, VICTORY_CHECK_SECS};
+use crate::messages::{CombatLogMsg, GameOverMsg};
+use crate::resources::{CombatEventKind, EntityMap, FactionStats, GameTime, TownIndex, UiState};
+use crate::world::{BuildingKind, WorldData};
+
+/// One goal. Leaf conditions read `VictoryFacts`; `All` / `Any` combine them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    /// `faction` has no standing town and no living NPCs.
+    EliminateFaction {
+        faction: i32,
+    },
+    /// The game clock reaches day `days`.
+    SurviveDays {
+        days: i32,
+    },
+    /// The owning faction has at least `count` living NPCs.
+    Population {
+        count: i32,
+    },
+    /// The owning faction's standing towns hold at least `amount` gold together.
+    Gold {
+        amount: i32,
+    },
+    /// Every town of another (non-neutral) faction has fallen while one of ours stands.
+    CaptureAllTowns,
+    All {
+        conditions: Vec<Condition>,
+    },
+    Any {
+        conditions: Vec<Condition>,
+    },
+}
+
+/// World state the conditions are checked against, gathered once per check.
+#[derive(Debug, Default)]
+pub struct VictoryFacts {
+    pub day: i32,
+    /// Per town: (faction, fountain standing, gold).
+    pub towns: Vec<(i32, bool, i32)>,
+    /// Living NPCs per faction id.
+    pub alive: Vec<i32>,
+}
+
+impl VictoryFacts {
+    fn alive(&self, faction: i32) -> i32 {
+        usize::try_from(faction)
+            .ok()
+            .and_then(|f| self.alive.get(f))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn standing(&self, faction: i32) -> impl Iterator<Item = &(i32, bool, i32)> {
+        self.towns.iter().filter(move |t| t.0 == faction && t.1)
+    }
+}
+
+impl Condition {
+    /// Why the condition holds for `faction`, or None while it doesn't. An empty All/Any
+    /// never holds.
+    pub fn met(&self, facts: &VictoryFacts, faction: i32) -> Option<String> {
+        match self {
+            Self::EliminateFaction { faction: target } => (facts.standing(*target).count() == 0
+                && facts.alive(*target) == 0)
+                .then(|| format!("faction {target} eliminated")),
+            Self::SurviveDays { days } => {
+                (facts.day >= *days).then(|| format!("survived {days} days"))
+            }
+            Self::Population { count } => {
+                (facts.alive(faction) >= *count).then(|| format!("population reached {count}"))
+            }
+            Self::Gold { amount } => {
+                let gold: i64 = facts.standing(faction).map(|t| t.2 as i64).sum();
+                (gold >= *amount as i64).then(|| format!("gold reached {amount}"))
+            }
+            Self::CaptureAllTowns => {
+                let rivals_fallen = facts
+                    .towns
+                    .iter()
+                    .filter(|t| t.0 != faction && t.0 != FACTION_NEUTRAL)
+                    .all(|t| !t.1);
+                (rivals_fallen && facts.standing(faction).count() > 0)
+                    .then(|| "all rival towns taken".to_string())
+            }
+            Self::All { conditions } if !conditions.is_empty() => conditions
+                .iter()
+                .map(|c| c.met(facts, faction))
+                .collect::<Option<Vec<_>>>()
+                .map(|reasons| reasons.join(" and ")),
+            Self::Any { conditions } => conditions.iter().find_map(|c| c.met(facts, faction)),
+            Self::All { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameOutcome {
+    #[default]
+    Ongoing,
+    Won,
+    Lost,
+}
+
+/// Scenario goals for one faction. No conditions = an endless sandbox, never checked.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WinCondition {
+    /// Faction the conditions are judged for (Population, Gold and CaptureAllTowns).
+    pub faction: i32,
+    pub victory: Option<Condition>,
+    pub defeat: Option<Condition>,
+    /// Pause and show the game over screen when the scenario ends.
+    pub pause_on_end: bool,
+    pub outcome: GameOutcome,
+    /// Why the scenario ended (empty while ongoing).
+    pub reason: String,
+    /// Game day the scenario ended on.
+    pub ended_day: i32,
+}
+
+impl Default for WinCondition {
+    fn default() -> Self {
+        Self {
+            faction: FACTION_PLAYER,
+            victory: None,
+            defeat: None,
+            pause_on_end: true,
+            outcome: GameOutcome::Ongoing,
+            reason: String::new(),
+            ended_day: 0,
+        }
+    }
+}
+
+impl WinCondition {
+    /// Replace the scenario goals and restart the check (any previous outcome is cleared).
+    pub fn set_win_condition(
+        &mut self,
+        faction: i32,
+        victory: Option<Condition>,
+        defeat: Option<Condition>,
+        pause_on_end: bool,
+    ) {
+        *self = Self {
+            faction,
+            victory,
+            defeat,
+            pause_on_end,
+            ..Default::default()
+        };
+    }
+
+    /// Ongoing, won or lost.
+    pub fn get_game_state(&self) -> GameOutcome {
+        self.outcome
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.outcome == GameOutcome::Ongoing && (self.victory.is_some() || self.defeat.is_some())
+    }
+
+    /// Check the goals against `facts`. Defeat is checked first, so a tick that meets both
+    /// is a loss. Returns (won, reason) when the scenario ends on this check.
+    pub fn evaluate(&mut self, facts: &VictoryFacts) -> Option<(bool, String)> {
+        if !self.is_active() {
+            return None;
+        }
+        let lost = self
+            .defeat
+            .as_ref()
+            .and_then(|c| c.met(facts, self.faction));
+        let (won, reason) = match lost {
+            Some(reason) => (false, reason),
+            None => (true, self.victory.as_ref()?.met(facts, self.faction)?),
+        };
+        self.outcome = if won {
+            GameOutcome::Won
+        } else {
+            GameOutcome::Lost
+        };
+        self.reason = reason.clone();
+        self.ended_day = facts.day;
+        Some((won, reason))
+    }
+}
+
+/// Check the win/loss conditions every `VICTORY_CHECK_SECS` while a scenario is running.
+pub fn victory_system(
+    mut win: ResMut<WinCondition>,
+    mut game_time: ResMut<GameTime>,
+    mut ui_state: ResMut<UiState>,
+    mut last_check: Local<Option<f32>>,
+    world_data: Res<WorldData>,
+    entity_map: Res<EntityMap>,
+    faction_stats: Res<FactionStats>,
+    town_index: Res<TownIndex>,
+    gold_q: Query<&GoldStore, With<TownMarker>>,
+    mut game_over: MessageWriter<GameOverMsg>,
+    mut combat_log: MessageWriter<CombatLogMsg>,
+) {
+    if !win.is_active() {
+        return;
+    }
+    let now = game_time.total_seconds;
+    // A clock that restarted behind us (new game, load) checks right away
+    if let Some(last) = *last_check
+        && now >= last
+        && now < last + VICTORY_CHECK_SECS
+    {
+        return;
+    }
+    *last_check = Some(now);
+
+    let facts = VictoryFacts {
+        day: game_time.day(),
+        towns: world_data
+            .towns
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let standing = entity_map.count_for_town(BuildingKind::Fountain, i as u32) > 0;
+                let gold = town_index
+                    .0
+                    .get(&(i as i32))
+                    .and_then(|&e| gold_q.get(e).ok())
+                    .map_or(0, |g| g.0);
+                (t.faction, standing, gold)
+            })
+            .collect(),
+        alive: faction_stats.stats.iter().map(|s| s.alive).collect(),
+    };
+    let Some((won, reason)) = win.evaluate(&facts) else {
+        return;
+    };
+
+    let verdict = if won { "Victory" } else { "Defeat" };
+    info!("{verdict}: {reason}");
+    combat_log.write(CombatLogMsg {
+        kind: CombatEventKind::GameOver,
+        faction: win.faction,
+        day: game_time.day(),
+        hour: game_time.hour(),
+        minute: game_time.minute(),
+        message: format!("{verdict}: {reason}"),
+        location: None,
+    });
+    game_over.write(GameOverMsg {
+        won,
+        reason,
+        day: facts.day,
+    });
+    if win.pause_on_end {
+        game_time.paused = true;
+        ui_state.game_over = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facts() -> VictoryFacts {
+        VictoryFacts {
+            day: 5,
+            // Player town (standing, 300 gold), two rival towns of faction 2, one fallen
+            towns: vec![(1, true, 300), (2, true, 50), (2, false, 0)],
+            alive: vec![0, 20, 4],
+        }
+    }
+
+    #[test]
+    fn leaf_conditions() {
+        let f = facts();
+        let met = |c: Condition| c.met(&f, FACTION_PLAYER).is_some();
+        assert!(met(Condition::SurviveDays { days: 5 }));
+        assert!(!met(Condition::SurviveDays { days: 6 }));
+        assert!(met(Condition::Population { count: 20 }));
+        assert!(!met(Condition::Gold { amount: 301 }));
+        assert!(!met(Condition::EliminateFaction { faction: 2 }));
+        assert!(!met(Condition::CaptureAllTowns));
+
+        let mut f = facts();
+        f.towns[1].1 = false;
+        assert!(Condition::CaptureAllTowns.met(&f, FACTION_PLAYER).is_some());
+        assert!(
+            Condition::EliminateFaction { faction: 2 }
+                .met(&f, FACTION_PLAYER)
+                .is_none(),
+            "survivors keep a faction alive"
+        );
+        f.alive[2] = 0;
+        assert_eq!(
+            Condition::EliminateFaction { faction: 2 }.met(&f, FACTION_PLAYER),
+            Some("faction 2 eliminated".into())
+        );
+    }
+
+    #[test]
+    fn all_and_any_combine() {
+        let f = facts();
+        let days = Condition::SurviveDays { days: 3 };
+        let gold = Condition::Gold { amount: 1000 };
+        let all = Condition::All {
+            conditions: vec![days.clone(), gold.clone()],
+        };
+        let any = Condition::Any {
+            conditions: vec![gold, days],
+        };
+        assert_eq!(all.met(&f, FACTION_PLAYER), None);
+        assert_eq!(any.met(&f, FACTION_PLAYER), Some("survived 3 days".into()));
+        assert_eq!(
+            Condition::All { conditions: vec![] }.met(&f, FACTION_PLAYER),
+            None
+        );
+    }
+
+    #[test]
+    fn defeat_wins_ties_and_outcome_sticks() {
+        let mut win = WinCondition::default();
+        assert!(!win.is_active(), "no conditions, no checks");
+        win.set_win_condition(
+            FACTION_PLAYER,
+            Some(Condition::SurviveDays { days: 5 }),
+            Some(Condition::Population { count: 1 }),
+            false,
+        );
+        let ended = win.evaluate(&facts());
+        assert_eq!(ended, Some((false, "population reached 1".into())));
+        assert_eq!(win.get_game_state(), GameOutcome::Lost);
+        assert_eq!(
+            win.evaluate(&facts()),
+            None,
+            "decided scenarios stop checking"
+        );
+
+        // Round-trips through the save format with the outcome intact
+        let json = serde_json::to_string(&win).unwrap();
+        let loaded: WinCondition = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, win);
+    }
+}
@@ -4,6 +4,8 @@
 //! - Claim: spatial search → try_claim_worksite → update NpcWorkState + submit movement
 //! - Release: entity_map.release_for (occupancy + claim-queue cleanup) → clear NpcWorkState
 //! - Retarget: Release then Claim atomically
+//!
+//! Farmers with an `AssignedFarm` skip the search and claim their pinned farm.
 
 use bevy::prelude::*;
 
@@ -21,6 +23,7 @@ pub fn resolve_work_targets(
     mut activity_q: Query<&mut crate::components::Activity>,
     mut path_queue: ResMut<PathRequestQueue>,
     production_q: Query<&ProductionState, With<Building>>,
+    assigned_q: Query<&AssignedFarm>,
 ) {
     let msgs: Vec<_> = intents.read().collect();
     if msgs.is_empty() {
@@ -55,6 +58,7 @@ pub fn resolve_work_targets(
                     *kind,
                     *town_idx,
                     *from,
+                    assigned_q.get(*entity).ok().map(|a| a.0),
                     &mut entity_map,
                     &mut work_state_q,
                     &mut activity_q,
@@ -74,6 +78,7 @@ pub fn resolve_work_targets(
                     *kind,
                     *town_idx,
                     *from,
+                    assigned_q.get(*entity).ok().map(|a| a.0),
                     &mut entity_map,
                     &mut work_state_q,
                     &mut activity_q,
@@ -121,6 +126,7 @@ fn claim_worksite(
     kind: BuildingKind,
     town_idx: u32,
     from: Vec2,
+    pinned: Option<Entity>,
     entity_map: &mut EntityMap,
     work_state_q: &mut Query<&mut NpcWorkState>,
     activity_q: &mut Query<&mut crate::components::Activity>,
@@ -132,8 +138,17 @@ fn claim_worksite(
         None => return,
     };
 
+    // Pinned farm first; a destroyed one falls back to the spatial search
+    let pinned_farm = pinned
+        .filter(|_| kind == BuildingKind::Farm)
+        .and_then(|e| entity_map.slot_for_entity(e))
+        .and_then(|slot| entity_map.get_instance(slot))
+        .filter(|inst| inst.kind == BuildingKind::Farm)
+        .map(|inst| (inst.slot, inst.position, 0.0));
+
     // Spatial search for best worksite
     let result = match kind {
+        _ if pinned_farm.is_some() => pinned_farm,
         BuildingKind::Farm => find_farm_target(from, entity_map, town_idx, production_map),
         BuildingKind::GoldMine => find_mine_target(from, entity_map, town_idx, production_map),
         _ => return,
@@ -0,0 +1,112 @@
+//! Combat Prediction Test (one phase per canned matchup)
+//! Validates: each `canned_matchups` fight, run as a real quick battle, is won by the
+//! predicted side with survivors within the matchup's tolerance of the prediction.
+
+use bevy::prelude::*;
+
+use crate::resources::*;
+use crate::systems::combat_prediction::{canned_matchups, predict_combat};
+use crate::systems::quick_battle::{QUICK_BATTLE_GAP, QUICK_BATTLE_SEED, QuickBattle};
+use crate::systems::stats::CombatConfig;
+
+use super::TestState;
+
+const CENTER: Vec2 = Vec2::new(384.0, 384.0);
+
+pub fn setup(
+    mut faction_stats: ResMut<FactionStats>,
+    mut test_state: ResMut<TestState>,
+    mut camera_query: Query<&mut Transform, With<crate::render::MainCamera>>,
+) {
+    faction_stats.init(1);
+    if let Ok(mut cam) = camera_query.single_mut() {
+        cam.translation.x = CENTER.x;
+        cam.translation.y = CENTER.y;
+    }
+    test_state.phase_name = "Deploying...".into();
+    info!(
+        "combat-prediction: setup — {} matchups",
+        canned_matchups().len()
+    );
+}
+
+pub fn tick(
+    mut battle: ResMut<QuickBattle>,
+    entity_map: Res<EntityMap>,
+    config: Res<CombatConfig>,
+    combat_rng: Res<CombatRng>,
+    time: Res<Time>,
+    mut test: ResMut<TestState>,
+) {
+    let Some(elapsed) = test.tick_elapsed(&time) else {
+        return;
+    };
+    let matchups = canned_matchups();
+    let Some(m) = matchups.get(test.phase.saturating_sub(1) as usize) else {
+        return;
+    };
+
+    // Deploy this phase's matchup once the previous battle has been torn down
+    if !test.get_flag("deployed") {
+        if battle.current_id().is_none() {
+            battle.request(
+                m.armies.clone(),
+                CENTER,
+                QUICK_BATTLE_GAP,
+                QUICK_BATTLE_SEED,
+            );
+            test.set_flag("deployed", true);
+            test.set_flag("engaged", false);
+            test.counters.insert("deployed_at".into(), elapsed as u32);
+        }
+        return;
+    }
+    let Some(active) = battle.active.as_ref() else {
+        return;
+    };
+    let alive = [0, 1].map(|side| {
+        active.slots[side]
+            .iter()
+            .filter(|&&s| {
+                entity_map
+                    .get_npc(s)
+                    .is_some_and(|n| !n.dead && n.faction == active.factions[side])
+            })
+            .count()
+    });
+    // Spawns land a tick after deploy; don't read an empty field as a wipe
+    if alive[0] > 0 && alive[1] > 0 {
+        test.set_flag("engaged", true);
+    }
+    let since = elapsed - test.count("deployed_at") as f32;
+    test.phase_name = format!("{}: alive={}v{}", m.name, alive[0], alive[1]);
+    if !test.get_flag("engaged") || (alive[0] > 0 && alive[1] > 0) {
+        if since > 90.0 {
+            test.fail_phase(
+                elapsed,
+                format!("{}: stalemate {}v{}", m.name, alive[0], alive[1]),
+            );
+        }
+        return;
+    }
+
+    let prediction = predict_combat(&m.armies[0], &m.armies[1], &config, &combat_rng);
+    let winner = usize::from(alive[0] == 0);
+    let predicted = prediction.survivors[winner];
+    let msg = format!(
+        "{}: side {} won with {} left, predicted {:?} with {}",
+        m.name, winner, alive[winner], prediction.winner, predicted
+    );
+    let id = active.id;
+    battle.reset(id);
+    test.set_flag("deployed", false);
+    if prediction.winner != Some(winner) || alive[winner].abs_diff(predicted) > m.survivor_tolerance
+    {
+        test.fail_phase(elapsed, msg);
+        return;
+    }
+    test.pass_phase(elapsed, msg);
+    if test.phase as usize > matchups.len() {
+        test.complete(elapsed);
+    }
+}
@@ -26,7 +26,7 @@ pub(super) fn setup(
     mut gpu_updates: MessageWriter<crate::messages::GpuUpdateMsg>,
     mut town_index: ResMut<crate::resources::TownIndex>,
 ) {
-    config.gen_style = WorldGenStyle::Continents;
+    config.generator = WorldGenStyle::Continents;
     config.num_towns = 1;
     config.ai_towns = 2;
     config.raider_towns = 2;
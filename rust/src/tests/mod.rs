@@ -8,6 +8,7 @@ pub mod archer_patrol;
 pub mod archer_tent_reliability;
 pub mod coalesce_safety;
 pub mod combat;
+pub mod combat_prediction;
 pub mod economy;
 pub mod endless_mode;
 pub mod energy;
@@ -23,12 +24,15 @@ pub mod movement;
 pub mod npc_visuals;
 pub mod pathfind_maze;
 pub mod projectiles;
+pub mod quick_battle;
 pub mod raider_cycle;
 pub mod sandbox;
+pub mod separation_ab;
 pub mod sleep_visual;
 pub mod slot_reuse_wave;
 pub mod spawning;
 pub mod stress_archer_towns;
+pub mod target_stickiness;
 pub mod terrain_visual;
 pub mod tower_massacre;
 pub mod vertical_slice;
@@ -351,6 +355,144 @@ pub struct TestResult {
     pub passed: bool,
 }
 
+// ============================================================================
+// TEST CONTEXT (reusable assertions)
+// ============================================================================
+
+/// Comparison for `TestContext::assert_npc_hp`.
+#[derive(Clone, Copy, Debug)]
+pub enum Cmp {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Cmp {
+    pub fn check(self, a: f32, b: f32) -> bool {
+        match self {
+            Cmp::Lt => a < b,
+            Cmp::Le => a <= b,
+            Cmp::Eq => (a - b).abs() < 0.01,
+            Cmp::Ge => a >= b,
+            Cmp::Gt => a > b,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Cmp::Lt => "<",
+            Cmp::Le => "<=",
+            Cmp::Eq => "==",
+            Cmp::Ge => ">=",
+            Cmp::Gt => ">",
+        }
+    }
+}
+
+/// TestState plus the GPU/ECS state assertions read from. Assertions return true once the
+/// condition holds; while it doesn't they show the live reading as `phase_name`, and once
+/// test elapsed time passes `timeout` they fail the phase with a descriptive message.
+#[derive(SystemParam)]
+pub struct TestContext<'w, 's> {
+    pub test: ResMut<'w, TestState>,
+    pub time: Res<'w, Time>,
+    pub entity_map: Res<'w, EntityMap>,
+    pub gpu_state: Res<'w, GpuReadState>,
+    pub health_q:
+        Query<'w, 's, &'static crate::components::Health, Without<crate::components::Building>>,
+}
+
+impl TestContext<'_, '_> {
+    /// Tick preamble (see `TestState::tick_elapsed`).
+    pub fn tick_elapsed(&mut self) -> Option<f32> {
+        self.test.tick_elapsed(&self.time)
+    }
+
+    /// Seconds since the test's first tick.
+    pub fn elapsed(&self) -> f32 {
+        self.time.elapsed_secs() - self.test.start
+    }
+
+    /// Core assertion: true if `ok`; otherwise shows `describe()` and fails after `timeout`.
+    pub fn expect(&mut self, ok: bool, timeout: f32, describe: impl FnOnce() -> String) -> bool {
+        if ok {
+            return true;
+        }
+        if self.test.failed {
+            return false;
+        }
+        let msg = describe();
+        let elapsed = self.elapsed();
+        if elapsed > timeout {
+            self.test.fail_phase(elapsed, msg);
+        } else {
+            self.test.phase_name = msg;
+        }
+        false
+    }
+
+    /// GPU-readback position of an NPC slot (None before the first readback covers it).
+    pub fn npc_pos(&self, idx: usize) -> Option<Vec2> {
+        let p = &self.gpu_state.positions;
+        (idx * 2 + 1 < p.len()).then(|| Vec2::new(p[idx * 2], p[idx * 2 + 1]))
+    }
+
+    /// ECS health of a live NPC slot.
+    pub fn npc_hp(&self, idx: usize) -> Option<f32> {
+        let npc = self.entity_map.get_npc(idx).filter(|n| !n.dead)?;
+        self.health_q.get(npc.entity).ok().map(|h| h.0)
+    }
+
+    /// Live NPCs with this job.
+    pub fn population(&self, job: crate::components::Job) -> usize {
+        self.entity_map
+            .iter_npcs()
+            .filter(|n| !n.dead && n.job == job)
+            .count()
+    }
+
+    /// NPC slot `idx` within `tol` of (x, y).
+    pub fn assert_npc_near(&mut self, idx: usize, x: f32, y: f32, tol: f32, timeout: f32) -> bool {
+        let target = Vec2::new(x, y);
+        let pos = self.npc_pos(idx);
+        let ok = pos.is_some_and(|p| p.distance(target) <= tol);
+        self.expect(ok, timeout, || match pos {
+            Some(p) => format!(
+                "npc {idx} at ({:.0}, {:.0}), {:.0}px from ({x:.0}, {y:.0}) (tol {tol:.0})",
+                p.x,
+                p.y,
+                p.distance(target)
+            ),
+            None => format!("npc {idx} has no GPU position"),
+        })
+    }
+
+    /// NPC slot `idx` health satisfies `hp <cmp> value`.
+    pub fn assert_npc_hp(&mut self, idx: usize, cmp: Cmp, value: f32, timeout: f32) -> bool {
+        let hp = self.npc_hp(idx);
+        let ok = hp.is_some_and(|h| cmp.check(h, value));
+        self.expect(ok, timeout, || match hp {
+            Some(h) => format!("npc {idx} hp={h:.1}, expected {} {value:.1}", cmp.symbol()),
+            None => format!("npc {idx} not alive"),
+        })
+    }
+
+    /// Exactly `expected` live NPCs with this job.
+    pub fn assert_population(
+        &mut self,
+        job: crate::components::Job,
+        expected: usize,
+        timeout: f32,
+    ) -> bool {
+        let n = self.population(job);
+        self.expect(n == expected, timeout, || {
+            format!("{job:?} population={n}, expected {expected}")
+        })
+    }
+}
+
 // ============================================================================
 // TEST REGISTRY
 // ============================================================================
@@ -600,6 +742,84 @@ pub fn register_tests(app: &mut App) {
             .after(Step::Behavior),
     );
 
+    // quick-battle
+    registry.tests.push(TestEntry {
+        name: "quick-battle".into(),
+        description: "Quick battle: 10v10 lines → engage → one side wiped → slots returned".into(),
+        phase_count: 4,
+        time_scale: 1.0,
+    });
+    app.add_systems(
+        OnEnter(AppState::Running),
+        quick_battle::setup.run_if(test_is("quick-battle")),
+    );
+    app.add_systems(
+        FixedUpdate,
+        quick_battle::tick
+            .run_if(in_state(AppState::Running))
+            .run_if(test_is("quick-battle"))
+            .after(Step::Behavior),
+    );
+
+    // combat-prediction
+    registry.tests.push(TestEntry {
+        name: "combat-prediction".into(),
+        description: "Canned quick battles end as predict_combat calls them (winner, survivors)"
+            .into(),
+        phase_count: crate::systems::combat_prediction::canned_matchups().len() as u32,
+        time_scale: 1.0,
+    });
+    app.add_systems(
+        OnEnter(AppState::Running),
+        combat_prediction::setup.run_if(test_is("combat-prediction")),
+    );
+    app.add_systems(
+        FixedUpdate,
+        combat_prediction::tick
+            .run_if(in_state(AppState::Running))
+            .run_if(test_is("combat-prediction"))
+            .after(Step::Behavior),
+    );
+
+    // separation-ab
+    registry.tests.push(TestEntry {
+        name: "separation-ab".into(),
+        description: "Grid and brute-force separation settle a small stack to the same layout"
+            .into(),
+        phase_count: 2,
+        time_scale: 1.0,
+    });
+    app.add_systems(
+        OnEnter(AppState::Running),
+        separation_ab::setup.run_if(test_is("separation-ab")),
+    );
+    app.add_systems(
+        FixedUpdate,
+        separation_ab::tick
+            .run_if(in_state(AppState::Running))
+            .run_if(test_is("separation-ab"))
+            .after(Step::Behavior),
+    );
+
+    // target-stickiness
+    registry.tests.push(TestEntry {
+        name: "target-stickiness".into(),
+        description: "Dense brawl lands at least as many attacks with target commitment on".into(),
+        phase_count: 2,
+        time_scale: 1.0,
+    });
+    app.add_systems(
+        OnEnter(AppState::Running),
+        target_stickiness::setup.run_if(test_is("target-stickiness")),
+    );
+    app.add_systems(
+        FixedUpdate,
+        target_stickiness::tick
+            .run_if(in_state(AppState::Running))
+            .run_if(test_is("target-stickiness"))
+            .after(Step::Behavior),
+    );
+
     // projectiles
     registry.tests.push(TestEntry {
         name: "projectiles".into(),
@@ -3,19 +3,13 @@
 //! 3 farmer homes + 3 farms → spawner system creates 3 NPCs.
 
 use crate::components::*;
-use crate::resources::*;
 use bevy::prelude::*;
 
-use super::{TestSetupParams, TestState};
+use super::{Cmp, TestContext, TestSetupParams};
 
 const HOME_Y: f32 = 448.0;
 const FARM_Y: f32 = 320.0;
 
-/// Count live (non-dead) NPCs via EntityMap.
-fn alive_npcs(entity_map: &EntityMap) -> usize {
-    entity_map.iter_npcs().filter(|n| !n.dead).count()
-}
-
 pub fn setup(mut params: TestSetupParams) {
     params.add_town("TestTown");
     params.world_data.towns[0].center = Vec2::new(320.0, 384.0);
@@ -37,36 +31,39 @@ pub fn setup(mut params: TestSetupParams) {
 }
 
 pub fn tick(
-    entity_map: Res<EntityMap>,
-    gpu_state: Res<GpuReadState>,
-    time: Res<Time>,
-    mut test: ResMut<TestState>,
+    mut ctx: TestContext,
     activity_q: Query<&Activity>,
     npc_flags_q: Query<&NpcFlags>,
     mut energy_q: Query<&mut Energy>,
+    npc_q: Query<(&CachedStats, &Home)>,
 ) {
-    let Some(elapsed) = test.tick_elapsed(&time) else {
+    let Some(elapsed) = ctx.tick_elapsed() else {
         return;
     };
 
-    let npc_count = alive_npcs(&entity_map);
+    let npc_count = ctx.population(Job::Farmer);
 
     // Set energy near tired threshold so drain→rest fits in test window
-    if !test.get_flag("energy_set") && npc_count >= 3 {
-        for npc in entity_map.iter_npcs() {
+    if !ctx.test.get_flag("energy_set") && npc_count >= 3 {
+        for npc in ctx.entity_map.iter_npcs() {
             if !npc.dead && npc.job == Job::Farmer {
                 if let Ok(mut en) = energy_q.get_mut(npc.entity) {
                     en.0 = 35.0;
                 }
             }
         }
-        test.set_flag("energy_set", true);
+        ctx.test.set_flag("energy_set", true);
     }
 
+    let entity_map = &ctx.entity_map;
     let transit = entity_map
         .iter_npcs()
         .filter(|n| !n.dead && npc_flags_q.get(n.entity).is_ok_and(|f| !f.at_destination))
         .count();
+    let at_dest = entity_map
+        .iter_npcs()
+        .filter(|n| !n.dead && npc_flags_q.get(n.entity).is_ok_and(|f| f.at_destination))
+        .count();
     let working = entity_map
         .iter_npcs()
         .filter(|n| {
@@ -96,128 +93,127 @@ pub fn tick(
                 && npc_flags_q.get(n.entity).is_ok_and(|f| f.at_destination)
         })
         .count();
+    let energy = entity_map
+        .iter_npcs()
+        .find(|n| !n.dead)
+        .and_then(|n| energy_q.get(n.entity).ok())
+        .map(|e| e.0)
+        .unwrap_or(100.0);
 
-    match test.phase {
-        // Phase 1: Farmers spawned and heading to farms
+    match ctx.test.phase {
+        // Phase 1: Farmers spawned at full health and heading to farms
         1 => {
-            test.phase_name = format!(
-                "npcs={}/3 transit={} working={}",
-                npc_count, transit, working
-            );
-            if transit + working >= 3 {
-                test.pass_phase(elapsed, format!("transit={} working={}", transit, working));
-            } else if npc_count >= 3 && elapsed > 0.5 {
-                let at_dest = entity_map
-                    .iter_npcs()
-                    .filter(|n| {
-                        !n.dead && npc_flags_q.get(n.entity).is_ok_and(|f| f.at_destination)
-                    })
-                    .count();
-                if transit + working + at_dest >= 3 {
-                    test.pass_phase(
-                        elapsed,
-                        format!(
-                            "transit={} working={} at_dest={}",
-                            transit, working, at_dest
-                        ),
-                    );
-                }
+            if !ctx.assert_population(Job::Farmer, 3, 10.0) {
+                return;
             }
-            if elapsed > 10.0 {
-                test.fail_phase(
+            let first = ctx
+                .entity_map
+                .iter_npcs()
+                .find(|n| !n.dead && n.job == Job::Farmer)
+                .map(|n| (n.slot, n.entity));
+            let Some((slot, entity)) = first else {
+                return;
+            };
+            let max_hp = npc_q.get(entity).map_or(100.0, |(s, _)| s.max_health);
+            if !ctx.assert_npc_hp(slot, Cmp::Ge, max_hp, 10.0) {
+                return;
+            }
+            let ok = transit + working >= 3 || (elapsed > 0.5 && transit + working + at_dest >= 3);
+            if ctx.expect(ok, 10.0, || {
+                format!(
+                    "transit={} working={} at_dest={}",
+                    transit, working, at_dest
+                )
+            }) {
+                ctx.test.pass_phase(
                     elapsed,
-                    format!("transit={} working={} npcs={}", transit, working, npc_count),
+                    format!(
+                        "transit={} working={} at_dest={}",
+                        transit, working, at_dest
+                    ),
                 );
             }
         }
         // Phase 2: GPU positions changed (moved from HOME_Y toward FARM_Y)
         2 => {
-            let positions = &gpu_state.positions;
-            let mut moved_count = 0;
-            for npc in entity_map.iter_npcs() {
-                if npc.dead {
-                    continue;
-                }
-                let idx = npc.slot * 2;
-                if idx + 1 < positions.len() {
-                    let y = positions[idx + 1];
-                    if y > 0.0 && (y - HOME_Y).abs() > 5.0 {
-                        moved_count += 1;
-                    }
-                }
-            }
-            test.phase_name = format!("moved={}/3 positions_len={}", moved_count, positions.len());
-            if moved_count >= 1 {
-                test.pass_phase(elapsed, format!("moved={}", moved_count));
-            } else if elapsed > 8.0 {
-                test.fail_phase(elapsed, format!("moved=0, len={}", positions.len()));
+            let moved_count = ctx
+                .entity_map
+                .iter_npcs()
+                .filter(|n| !n.dead)
+                .filter_map(|n| ctx.npc_pos(n.slot))
+                .filter(|p| p.y > 0.0 && (p.y - HOME_Y).abs() > 5.0)
+                .count();
+            let positions_len = ctx.gpu_state.positions.len();
+            if ctx.expect(moved_count >= 1, 8.0, || {
+                format!("moved={}/3 positions_len={}", moved_count, positions_len)
+            }) {
+                ctx.test
+                    .pass_phase(elapsed, format!("moved={}", moved_count));
             }
         }
-        // Phase 3: Farmers working at farms
+        // Phase 3: Farmers working, standing on a farm
         3 => {
-            test.phase_name = format!("working={}/3 transit={}", working, transit);
-            if working >= 1 {
-                test.pass_phase(elapsed, format!("working={}", working));
-            } else if elapsed > 20.0 {
-                let at_dest = entity_map
-                    .iter_npcs()
-                    .filter(|n| {
-                        !n.dead && npc_flags_q.get(n.entity).is_ok_and(|f| f.at_destination)
-                    })
-                    .count();
-                test.fail_phase(
-                    elapsed,
-                    format!("working=0 transit={} at_dest={}", transit, at_dest),
-                );
+            let worker = ctx.entity_map.iter_npcs().find(|n| {
+                !n.dead
+                    && activity_q
+                        .get(n.entity)
+                        .is_ok_and(|a| a.kind == ActivityKind::Work)
+            });
+            let Some(slot) = worker.map(|n| n.slot) else {
+                ctx.expect(false, 20.0, || {
+                    format!("working=0/3 transit={} at_dest={}", transit, at_dest)
+                });
+                return;
+            };
+            // Nearest of the three farms
+            let x = ctx.npc_pos(slot).map_or(192.0, |p| {
+                (((p.x - 192.0) / 128.0).round().clamp(0.0, 2.0)) * 128.0 + 192.0
+            });
+            if ctx.assert_npc_near(slot, x, FARM_Y, 48.0, 20.0) {
+                ctx.test.pass_phase(elapsed, format!("working={}", working));
             }
         }
         // Phase 4: Energy drains → going home to rest
         4 => {
-            let energy = entity_map
-                .iter_npcs()
-                .find(|n| !n.dead)
-                .and_then(|n| energy_q.get(n.entity).ok())
-                .map(|e| e.0)
-                .unwrap_or(100.0);
-            test.phase_name = format!(
-                "going_rest={} resting={} e={:.0}",
-                going_rest, resting, energy
-            );
-            if going_rest > 0 || resting > 0 {
-                test.pass_phase(
+            if ctx.expect(going_rest > 0 || resting > 0, 30.0, || {
+                format!(
+                    "not resting, working={} going_rest={} e={:.0}",
+                    working, going_rest, energy
+                )
+            }) {
+                ctx.test.pass_phase(
                     elapsed,
                     format!(
                         "going_rest={} resting={} (energy={:.0})",
                         going_rest, resting, energy
                     ),
                 );
-            } else if elapsed > 30.0 {
-                test.fail_phase(
-                    elapsed,
-                    format!("not resting, working={} energy={:.0}", working, energy),
-                );
             }
         }
         // Phase 5: Resting at home
         5 => {
-            let energy = entity_map
-                .iter_npcs()
-                .find(|n| !n.dead)
-                .and_then(|n| energy_q.get(n.entity).ok())
-                .map(|e| e.0)
-                .unwrap_or(100.0);
-            test.phase_name = format!("resting={} e={:.0}", resting, energy);
-            if resting > 0 {
-                test.pass_phase(
+            let rester = ctx.entity_map.iter_npcs().find(|n| {
+                !n.dead
+                    && activity_q
+                        .get(n.entity)
+                        .is_ok_and(|a| matches!(a.kind, ActivityKind::Rest))
+                    && npc_flags_q.get(n.entity).is_ok_and(|f| f.at_destination)
+            });
+            let Some((slot, entity)) = rester.map(|n| (n.slot, n.entity)) else {
+                ctx.expect(false, 35.0, || {
+                    format!("resting=0 going_rest={} energy={:.0}", going_rest, energy)
+                });
+                return;
+            };
+            let Ok((_, home)) = npc_q.get(entity) else {
+                return;
+            };
+            if ctx.assert_npc_near(slot, home.0.x, home.0.y, 48.0, 35.0) {
+                ctx.test.pass_phase(
                     elapsed,
                     format!("resting={} (energy={:.0})", resting, energy),
                 );
-                test.complete(elapsed);
-            } else if elapsed > 35.0 {
-                test.fail_phase(
-                    elapsed,
-                    format!("resting=0 going_rest={} energy={:.0}", going_rest, energy),
-                );
+                ctx.test.complete(elapsed);
             }
         }
         _ => {}
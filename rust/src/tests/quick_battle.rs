@@ -0,0 +1,118 @@
+//! Quick Battle Test (4 phases)
+//! Validates: symmetric deploy → engagement → one side wiped → teardown returns every slot.
+
+use bevy::prelude::*;
+
+use crate::components::Job;
+use crate::resources::*;
+use crate::systems::quick_battle::{ArmySpec, QUICK_BATTLE_GAP, QUICK_BATTLE_SEED, QuickBattle};
+
+use super::TestState;
+
+const CENTER: Vec2 = Vec2::new(384.0, 384.0);
+
+pub fn setup(
+    mut battle: ResMut<QuickBattle>,
+    mut faction_stats: ResMut<FactionStats>,
+    mut test_state: ResMut<TestState>,
+    slot_alloc: Res<GpuSlotPool>,
+    mut camera_query: Query<&mut Transform, With<crate::render::MainCamera>>,
+) {
+    faction_stats.init(1);
+    // Mixed melee/ranged line vs an all-melee line, both level 2
+    let armies = [
+        ArmySpec {
+            units: vec![(Job::Fighter, 6), (Job::Archer, 4)],
+            level: 2,
+            traits: Vec::new(),
+        },
+        ArmySpec {
+            units: vec![(Job::Raider, 10)],
+            level: 2,
+            traits: Vec::new(),
+        },
+    ];
+    let id = battle.request(armies, CENTER, QUICK_BATTLE_GAP, QUICK_BATTLE_SEED);
+    test_state
+        .counters
+        .insert("baseline_slots".into(), slot_alloc.alive() as u32);
+
+    if let Ok(mut cam) = camera_query.single_mut() {
+        cam.translation.x = CENTER.x;
+        cam.translation.y = CENTER.y;
+    }
+
+    test_state.phase_name = "Waiting for deploy...".into();
+    info!("quick-battle: setup — battle #{id}, 10 vs 10");
+}
+
+pub fn tick(
+    mut battle: ResMut<QuickBattle>,
+    entity_map: Res<EntityMap>,
+    health_debug: Res<HealthDebug>,
+    slot_alloc: Res<GpuSlotPool>,
+    time: Res<Time>,
+    mut test: ResMut<TestState>,
+) {
+    let Some(elapsed) = test.tick_elapsed(&time) else {
+        return;
+    };
+
+    let alive = battle.active.as_ref().map_or([0, 0], |b| {
+        [0, 1].map(|side| {
+            b.slots[side]
+                .iter()
+                .filter(|&&s| {
+                    entity_map
+                        .get_npc(s)
+                        .is_some_and(|n| !n.dead && n.faction == b.factions[side])
+                })
+                .count()
+        })
+    });
+
+    match test.phase {
+        // Phase 1: both lines materialized
+        1 => {
+            test.phase_name = format!("alive={}v{}", alive[0], alive[1]);
+            if alive == [10, 10] {
+                test.pass_phase(elapsed, "10v10 deployed");
+            } else if elapsed > 5.0 {
+                test.fail_phase(elapsed, format!("alive={}v{}", alive[0], alive[1]));
+            }
+        }
+        // Phase 2: lines engage
+        2 => {
+            test.phase_name = format!("damage={}", health_debug.damage_processed);
+            if health_debug.damage_processed > 0 {
+                test.pass_phase(elapsed, format!("damage={}", health_debug.damage_processed));
+            } else if elapsed > 20.0 {
+                test.fail_phase(elapsed, "no damage dealt");
+            }
+        }
+        // Phase 3: one side wiped — then tear down
+        3 => {
+            test.phase_name = format!("alive={}v{}", alive[0], alive[1]);
+            if alive[0] == 0 || alive[1] == 0 {
+                test.pass_phase(elapsed, format!("final {}v{}", alive[0], alive[1]));
+                let id = battle.current_id().unwrap_or(0);
+                battle.reset(id);
+            } else if elapsed > 90.0 {
+                test.fail_phase(elapsed, format!("stalemate {}v{}", alive[0], alive[1]));
+            }
+        }
+        // Phase 4: every battle slot returned to the pool
+        4 => {
+            let baseline = test.count("baseline_slots") as usize;
+            let live = slot_alloc.alive();
+            test.phase_name = format!("slots={} baseline={}", live, baseline);
+            if battle.active.is_none() && live == baseline {
+                test.pass_phase(elapsed, format!("slots back to {}", baseline));
+                test.complete(elapsed);
+            } else if elapsed > 100.0 {
+                test.fail_phase(elapsed, format!("slots={} baseline={}", live, baseline));
+            }
+        }
+        _ => {}
+    }
+}
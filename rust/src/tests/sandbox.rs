@@ -23,7 +23,7 @@ pub(super) fn setup(
     mut camera_query: Query<&mut Transform, With<crate::render::MainCamera>>,
     mut town_index: ResMut<crate::resources::TownIndex>,
 ) {
-    config.gen_style = WorldGenStyle::Continents;
+    config.generator = WorldGenStyle::Continents;
     config.num_towns = 1;
     config.ai_towns = 1;
     config.raider_towns = 0;
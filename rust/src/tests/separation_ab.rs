@@ -0,0 +1,137 @@
+//! Separation A/B Test (2 phases)
+//! Validates: at low counts (well under `max_per_cell`) grid and brute-force separation
+//! settle the same clustered group into near-identical positions.
+//! 8 archers start stacked on their patrol waypoint; each phase resets them to the same
+//! start and lets separation spread them for `RUN_SECS`, first on the grid, then brute force.
+
+use bevy::prelude::*;
+
+use crate::messages::{GpuUpdate, GpuUpdateMsg};
+use crate::resources::*;
+
+use super::{TestContext, TestSetupParams};
+
+const CENTER: Vec2 = Vec2::new(384.0, 384.0);
+const ARCHERS: usize = 8;
+/// Starting ring radius: tight enough that every pair overlaps.
+const START_RADIUS: f32 = 4.0;
+const RUN_SECS: f32 = 4.0;
+/// Mean per-archer distance between the two settled layouts.
+const MAX_MEAN_DELTA: f32 = 8.0;
+
+fn start_pos(i: usize) -> Vec2 {
+    let a = i as f32 / ARCHERS as f32 * std::f32::consts::TAU;
+    CENTER + Vec2::new(a.cos(), a.sin()) * START_RADIUS
+}
+
+pub fn setup(mut params: TestSetupParams, mut separation: ResMut<SeparationState>) {
+    params.add_town("SeparationTown");
+    params.add_waypoint(CENTER.x, CENTER.y, 0, 0);
+    params.init_economy(1);
+    for i in 0..ARCHERS {
+        let p = start_pos(i);
+        params.spawn_npc(1, p.x, p.y, CENTER.x, CENTER.y);
+    }
+    separation.enabled = true;
+    separation.set_mode(SeparationMode::Grid);
+    params.focus_camera(CENTER.x, CENTER.y);
+    params.test_state.phase_name = "Waiting for archers...".into();
+    info!("separation-ab: setup — {ARCHERS} archers stacked at the waypoint");
+}
+
+/// Put every archer back on its starting spot, heading for the waypoint.
+fn reset_positions(slots: &[usize], gpu_updates: &mut MessageWriter<GpuUpdateMsg>) {
+    for (i, &idx) in slots.iter().enumerate() {
+        let p = start_pos(i);
+        gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetPosition {
+            idx,
+            x: p.x,
+            y: p.y,
+        }));
+        gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetTarget {
+            idx,
+            x: CENTER.x,
+            y: CENTER.y,
+        }));
+    }
+}
+
+pub fn tick(
+    mut ctx: TestContext,
+    mut separation: ResMut<SeparationState>,
+    mut gpu_updates: MessageWriter<GpuUpdateMsg>,
+    mut grid_layout: Local<Vec<Vec2>>,
+) {
+    let Some(elapsed) = ctx.tick_elapsed() else {
+        return;
+    };
+    let mut slots: Vec<usize> = ctx
+        .entity_map
+        .iter_npcs()
+        .filter(|n| !n.dead)
+        .map(|n| n.slot)
+        .collect();
+    slots.sort_unstable();
+    if !ctx.expect(slots.len() == ARCHERS, 5.0, || {
+        format!("archers={}/{ARCHERS}", slots.len())
+    }) {
+        return;
+    }
+
+    // Each phase: reset once, run RUN_SECS, then read the settled layout
+    if !ctx.test.get_flag("reset") {
+        let mode = if ctx.test.phase == 1 {
+            SeparationMode::Grid
+        } else {
+            SeparationMode::BruteForce
+        };
+        separation.set_mode(mode);
+        reset_positions(&slots, &mut gpu_updates);
+        ctx.test.set_flag("reset", true);
+        ctx.test
+            .counters
+            .insert("run_start_ms".into(), (elapsed * 1000.0) as u32);
+        ctx.test.phase_name = format!("{} run...", mode.label());
+        return;
+    }
+    let run_start = ctx.test.count("run_start_ms") as f32 / 1000.0;
+    if elapsed - run_start < RUN_SECS {
+        return;
+    }
+    let layout: Vec<Vec2> = slots.iter().filter_map(|&s| ctx.npc_pos(s)).collect();
+    if !ctx.expect(layout.len() == ARCHERS, run_start + RUN_SECS + 2.0, || {
+        format!("GPU positions for {}/{ARCHERS} archers", layout.len())
+    }) {
+        return;
+    }
+
+    match ctx.test.phase {
+        // Phase 1: Grid separation spreads the stack; remember where everyone settled
+        1 => {
+            let spread = layout.iter().map(|p| p.distance(CENTER)).sum::<f32>() / ARCHERS as f32;
+            *grid_layout = layout;
+            ctx.test.set_flag("reset", false);
+            ctx.test
+                .pass_phase(elapsed, format!("grid settled, mean spread={spread:.1}px"));
+        }
+        // Phase 2: Brute force from the same start lands within MAX_MEAN_DELTA of the grid run
+        2 => {
+            separation.set_mode(SeparationMode::Grid);
+            let mean_delta = layout
+                .iter()
+                .zip(grid_layout.iter())
+                .map(|(a, b)| a.distance(*b))
+                .sum::<f32>()
+                / ARCHERS as f32;
+            let msg = format!("mean delta grid vs brute_force={mean_delta:.1}px");
+            if mean_delta <= MAX_MEAN_DELTA {
+                ctx.test.pass_phase(elapsed, msg);
+                ctx.test.complete(elapsed);
+            } else {
+                ctx.test
+                    .fail_phase(elapsed, format!("{msg} (max {MAX_MEAN_DELTA:.0})"));
+            }
+        }
+        _ => {}
+    }
+}
@@ -52,7 +52,7 @@ pub fn setup(
     mut town_index: ResMut<crate::resources::TownIndex>,
 ) {
     // 1 player town + 1 AI builder town, no raiders
-    config.gen_style = WorldGenStyle::Continents;
+    config.generator = WorldGenStyle::Continents;
     config.num_towns = 1;
     config.ai_towns = 1;
     config.raider_towns = 0;
@@ -49,7 +49,7 @@ pub(super) fn setup(
 ) {
     let archer_homes = stress_config.archer_homes;
 
-    config.gen_style = WorldGenStyle::Classic;
+    config.generator = WorldGenStyle::Classic;
     config.num_towns = 0;
     config.ai_towns = STRESS_AI_TOWNS;
     config.raider_towns = 0;
@@ -0,0 +1,115 @@
+//! Target Stickiness Test (2 phases)
+//! Validates: in a dense melee, committing to a target completes at least as many attacks
+//! (summed `CombatDebug::attacks_made`) as re-picking the nearest enemy every frame.
+//! Phase 1 brawls with stickiness off, phase 2 replays the same seeded battle with it on.
+
+use bevy::prelude::*;
+
+use crate::components::Job;
+use crate::resources::*;
+use crate::systems::quick_battle::{ArmySpec, QUICK_BATTLE_SEED, QuickBattle};
+
+use super::TestState;
+
+const CENTER: Vec2 = Vec2::new(384.0, 384.0);
+/// Front ranks start close so both lines collide into one scrum.
+const GAP: f32 = 48.0;
+/// Attacks are summed over this window from the first landed attack.
+const BRAWL_SECS: f32 = 8.0;
+const STICKY_SECS: f32 = 0.75;
+
+fn armies() -> [ArmySpec; 2] {
+    let army = |job: Job| ArmySpec {
+        units: vec![(job, 24)],
+        ..Default::default()
+    };
+    [army(Job::Fighter), army(Job::Raider)]
+}
+
+pub fn setup(
+    mut faction_stats: ResMut<FactionStats>,
+    mut stickiness: ResMut<TargetStickiness>,
+    mut test_state: ResMut<TestState>,
+    mut camera_query: Query<&mut Transform, With<crate::render::MainCamera>>,
+) {
+    faction_stats.init(1);
+    stickiness.secs = 0.0;
+    if let Ok(mut cam) = camera_query.single_mut() {
+        cam.translation.x = CENTER.x;
+        cam.translation.y = CENTER.y;
+    }
+    test_state.phase_name = "Deploying...".into();
+    info!("target-stickiness: setup — 24 fighters v 24 raiders, off then {STICKY_SECS}s");
+}
+
+pub fn tick(
+    mut battle: ResMut<QuickBattle>,
+    mut stickiness: ResMut<TargetStickiness>,
+    debug: Res<CombatDebug>,
+    time: Res<Time>,
+    mut test: ResMut<TestState>,
+) {
+    let Some(elapsed) = test.tick_elapsed(&time) else {
+        return;
+    };
+
+    // Deploy this phase's brawl once the previous battle has been torn down
+    if !test.get_flag("deployed") {
+        if battle.current_id().is_none() {
+            stickiness.secs = if test.phase == 1 { 0.0 } else { STICKY_SECS };
+            battle.request(armies(), CENTER, GAP, QUICK_BATTLE_SEED);
+            test.set_flag("deployed", true);
+            test.set_flag("engaged", false);
+            test.counters.insert("attacks".into(), 0);
+            test.counters.insert("deployed_at".into(), elapsed as u32);
+        }
+        return;
+    }
+
+    if !test.get_flag("engaged") {
+        if debug.attacks_made == 0 {
+            if elapsed - test.count("deployed_at") as f32 > 20.0 {
+                test.fail_phase(elapsed, "armies never engaged");
+            }
+            return;
+        }
+        test.set_flag("engaged", true);
+        test.counters
+            .insert("engaged_ms".into(), (elapsed * 1000.0) as u32);
+    }
+    let attacks = test.count("attacks") + debug.attacks_made as u32;
+    test.counters.insert("attacks".into(), attacks);
+    let brawl = elapsed - test.count("engaged_ms") as f32 / 1000.0;
+    test.phase_name = format!(
+        "stickiness {:.2}s: {attacks} attacks in {brawl:.1}s",
+        stickiness.secs
+    );
+    if brawl < BRAWL_SECS {
+        return;
+    }
+
+    if let Some(id) = battle.current_id() {
+        battle.reset(id);
+    }
+    test.set_flag("deployed", false);
+    match test.phase {
+        // Phase 1: Re-pick every frame; record the baseline
+        1 => {
+            test.counters.insert("attacks_off".into(), attacks);
+            test.pass_phase(elapsed, format!("stickiness off: {attacks} attacks"));
+        }
+        // Phase 2: Same brawl with commitment lands at least as many attacks
+        2 => {
+            stickiness.secs = 0.0;
+            let off = test.count("attacks_off");
+            let msg = format!("stickiness {STICKY_SECS}s: {attacks} attacks vs {off} off");
+            if attacks >= off {
+                test.pass_phase(elapsed, msg);
+                test.complete(elapsed);
+            } else {
+                test.fail_phase(elapsed, msg);
+            }
+        }
+        _ => {}
+    }
+}
@@ -20,6 +20,7 @@ use tracing_subscriber::layer::Context;
 use tracing_subscriber::registry::LookupSpan;
 
 use crate::messages::RENDER_PROFILING;
+use crate::resources::recover_or_log;
 
 /// Global EMA-smoothed timings written by the tracing Layer, read by frame_timer_start.
 pub static TRACING_TIMINGS: LazyLock<Mutex<HashMap<String, f32>>> =
@@ -96,11 +97,13 @@ impl<S: Subscriber + for<'a> LookupSpan<'a>> tracing_subscriber::Layer<S> for Sy
             return;
         };
         let ms = start.0.elapsed().as_secs_f64() as f32 * 1000.0;
-        if let Ok(mut map) = TRACING_TIMINGS.lock() {
+        {
+            let mut map = recover_or_log(&TRACING_TIMINGS, "tracing_timings");
             let entry = map.entry(name.0.clone()).or_insert(0.0);
             *entry = *entry * 0.9 + ms * 0.1;
         }
-        if let Ok(mut peaks) = TRACING_PEAKS.lock() {
+        {
+            let mut peaks = recover_or_log(&TRACING_PEAKS, "tracing_peaks");
             let entry = peaks.entry(name.0.clone()).or_insert((0.0, 0));
             entry.0 = entry.0.max(ms);
             entry.1 += 1;
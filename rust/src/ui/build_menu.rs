@@ -172,8 +172,11 @@ pub(crate) fn build_menu_system(
     mut ui_state: ResMut<UiState>,
     mut build_ctx: ResMut<BuildMenuContext>,
     world_data: Res<world::WorldData>,
+    player_focus: Res<PlayerFocus>,
     town_access: crate::systemparams::TownAccess,
     entity_map: Res<EntityMap>,
+    grid: Res<world::WorldGrid>,
+    costs: Res<crate::resources::BuildingCosts>,
     user_settings: Res<UserSettings>,
     _difficulty: Res<Difficulty>,
     sprites: Res<SpriteAssets>,
@@ -206,10 +209,7 @@ pub(crate) fn build_menu_system(
             if ui.add(btn).clicked() {
                 ui_state.build_menu_open = !ui_state.build_menu_open;
                 if ui_state.build_menu_open {
-                    build_ctx.town_data_idx = world_data
-                        .towns
-                        .iter()
-                        .position(|t| t.faction == crate::constants::FACTION_PLAYER);
+                    build_ctx.town_data_idx = player_focus.town(&world_data.towns);
                 } else {
                     build_ctx.selected_build = None;
                 }
@@ -221,10 +221,7 @@ pub(crate) fn build_menu_system(
     }
 
     if build_ctx.town_data_idx.is_none() {
-        build_ctx.town_data_idx = world_data
-            .towns
-            .iter()
-            .position(|t| t.faction == crate::constants::FACTION_PLAYER);
+        build_ctx.town_data_idx = player_focus.town(&world_data.towns);
     }
 
     let Some(town_data_idx) = build_ctx.town_data_idx else {
@@ -233,8 +230,17 @@ pub(crate) fn build_menu_system(
     let Some(town) = world_data.towns.get(town_data_idx) else {
         return Ok(());
     };
-    let is_raider = town.faction > crate::constants::FACTION_PLAYER;
-    let food = town_access.food(town_data_idx as i32);
+    let upgrade_levels = town_access.upgrade_levels(town_data_idx as i32);
+    let build_check = world::BuildCheck::new(
+        town_data_idx,
+        town,
+        town_access.food(town_data_idx as i32),
+        &upgrade_levels,
+        &grid,
+        &entity_map,
+        &costs,
+    )
+    .with_tech_locks(town_access.tech_locked_buildings(town_data_idx as i32));
     let text_scale = user_settings.build_menu_text_scale.clamp(0.7, 2.0);
     let label_size = 13.0 * text_scale;
     let help_size = 11.0 * text_scale;
@@ -314,46 +320,16 @@ pub(crate) fn build_menu_system(
             });
             ui.horizontal(|ui| {
                 for def in BUILDING_REGISTRY {
-                    let show = if is_raider {
-                        def.raider_buildable
-                    } else {
-                        def.player_buildable
-                    };
-                    if !show || def.display != build_ctx.build_tab {
+                    if def.display != build_ctx.build_tab {
                         continue;
                     }
-                    // 1-per-town limit for Merchant and Casino
-                    if matches!(def.kind, BuildingKind::Merchant | BuildingKind::Casino) {
-                        let tidx = build_ctx.town_data_idx.unwrap_or(0);
-                        if entity_map.count_for_town(def.kind, tidx as u32) >= 1 {
-                            continue;
-                        }
-                    }
-                    // Tech tree building unlocks (player only)
-                    if !is_raider {
-                        use crate::constants::UpgradeStatKind as USK;
-                        use crate::systems::stats::UPGRADES;
-                        let tidx = build_ctx.town_data_idx.unwrap_or(0);
-                        let levels = town_access.upgrade_levels(tidx as i32);
-                        let required_unlock = match def.kind {
-                            BuildingKind::StoneRoad => Some(USK::UnlockStoneRoad),
-                            BuildingKind::MetalRoad => Some(USK::UnlockMetalRoad),
-                            _ => None,
-                        };
-                        if let Some(unlock_kind) = required_unlock {
-                            let unlocked = UPGRADES
-                                .index_map
-                                .get(&("Town", unlock_kind))
-                                .map(|&idx| levels.get(idx).copied().unwrap_or(0) >= 1)
-                                .unwrap_or(false);
-                            if !unlocked {
-                                continue;
-                            }
-                        }
+                    let status = build_check.check(def.kind, &entity_map);
+                    if status == Err(world::BuildBlock::NotAvailable) {
+                        continue;
                     }
 
-                    let cost = def.cost;
-                    let can_afford = food >= cost;
+                    let cost = costs.cost(def.kind);
+                    let can_afford = status.is_ok();
                     let selected = build_ctx.selected_build == Some(def.kind);
 
                     let resp = ui.vertical(|ui| {
@@ -407,8 +383,21 @@ pub(crate) fn build_menu_system(
                         }
                     }
 
-                    if !def.tooltip.is_empty() {
-                        resp.response.on_hover_text(def.tooltip);
+                    match status {
+                        Err(block) if def.tooltip.is_empty() => {
+                            resp.response.on_hover_text(block.reason());
+                        }
+                        Err(block) => {
+                            resp.response.on_hover_text(format!(
+                                "{}\n\n{}",
+                                block.reason(),
+                                def.tooltip
+                            ));
+                        }
+                        Ok(()) if !def.tooltip.is_empty() => {
+                            resp.response.on_hover_text(def.tooltip);
+                        }
+                        Ok(()) => {}
                     }
 
                     ui.separator();
@@ -494,6 +483,12 @@ pub(crate) fn build_menu_system(
                                 .tint(egui::Color32::from_rgba_unmultiplied(255, 255, 255, 180));
                                 ui.add(img);
                             }
+                            if let Some(reason) = build_ctx.ghost_block_reason {
+                                ui.label(
+                                    egui::RichText::new(reason)
+                                        .color(egui::Color32::from_rgb(220, 80, 80)),
+                                );
+                            }
                         }
                     });
             }
@@ -158,8 +158,10 @@ pub fn top_bar_system(
     settings: Res<crate::settings::UserSettings>,
     mut camera_query: Query<&mut Transform, With<MainCamera>>,
     llm_state: Option<Res<crate::systems::llm_player::LlmPlayerState>>,
+    mut player_focus: ResMut<PlayerFocus>,
 ) -> Result {
     let ctx = contexts.ctx_mut()?;
+    let focus = player_focus.town(&world_data.towns).unwrap_or(0);
 
     let frame = egui::Frame::new()
         .fill(egui::Color32::from_rgb(30, 30, 35))
@@ -252,12 +254,33 @@ pub fn top_bar_system(
                     }
                 }
 
-                // CENTER: town name + time (painted at true center of bar)
                 let town_name = world_data
                     .towns
-                    .first()
+                    .get(focus)
                     .map(|t| t.name.as_str())
                     .unwrap_or("Unknown");
+
+                // Town focus switcher (only once the player holds more than one town)
+                let owned: Vec<usize> = (0..world_data.towns.len())
+                    .filter(|&i| world_data.towns[i].faction == crate::constants::FACTION_PLAYER)
+                    .collect();
+                if owned.len() > 1 {
+                    ui.separator();
+                    egui::ComboBox::from_id_salt("town_focus")
+                        .selected_text(town_name)
+                        .show_ui(ui, |ui| {
+                            for &i in &owned {
+                                if ui
+                                    .selectable_label(i == focus, &world_data.towns[i].name)
+                                    .clicked()
+                                {
+                                    player_focus.0 = Some(i);
+                                }
+                            }
+                        });
+                }
+
+                // CENTER: town name + time (painted at true center of bar)
                 let period = if game_time.is_daytime() {
                     "Day"
                 } else {
@@ -292,7 +315,7 @@ pub fn top_bar_system(
                 );
                 if center_resp.double_clicked() {
                     if let (Some(town), Ok(mut cam)) =
-                        (world_data.towns.first(), camera_query.single_mut())
+                        (world_data.towns.get(focus), camera_query.single_mut())
                     {
                         cam.translation.x = town.center.x;
                         cam.translation.y = town.center.y;
@@ -365,11 +388,12 @@ pub fn top_bar_system(
 
                     ui.separator();
 
-                    // Player stats (right-aligned) — player's town is index 0
-                    let town_food = town_access.food(0);
-                    let town_gold = town_access.gold(0);
-                    let town_wood = town_access.wood(0);
-                    let town_stone = town_access.stone(0);
+                    // Player stats (right-aligned) — focused town
+                    let ti = focus as i32;
+                    let town_food = town_access.food(ti);
+                    let town_gold = town_access.gold(ti);
+                    let town_wood = town_access.wood(ti);
+                    let town_stone = town_access.stone(ti);
                     tipped(
                         ui,
                         egui::RichText::new(format!("Stone: {}", town_stone))
@@ -394,12 +418,14 @@ pub fn top_bar_system(
                         catalog.0.get("food").unwrap_or(&""),
                     );
 
-                    let farmers = pop_stats.0.get(&(0, 0)).map(|s| s.alive).unwrap_or(0);
-                    let guards = pop_stats.0.get(&(1, 0)).map(|s| s.alive).unwrap_or(0);
-                    let crossbows = pop_stats.0.get(&(5, 0)).map(|s| s.alive).unwrap_or(0);
-                    let houses = entity_map.count_for_town(BuildingKind::FarmerHome, 0);
-                    let barracks = entity_map.count_for_town(BuildingKind::ArcherHome, 0);
-                    let xbow_homes = entity_map.count_for_town(BuildingKind::CrossbowHome, 0);
+                    let farmers = pop_stats.0.get(&(0, ti)).map(|s| s.alive).unwrap_or(0);
+                    let guards = pop_stats.0.get(&(1, ti)).map(|s| s.alive).unwrap_or(0);
+                    let crossbows = pop_stats.0.get(&(5, ti)).map(|s| s.alive).unwrap_or(0);
+                    let houses = entity_map.count_for_town(BuildingKind::FarmerHome, focus as u32);
+                    let barracks =
+                        entity_map.count_for_town(BuildingKind::ArcherHome, focus as u32);
+                    let xbow_homes =
+                        entity_map.count_for_town(BuildingKind::CrossbowHome, focus as u32);
                     tipped(
                         ui,
                         format!("Archers: {}/{}", guards, barracks),
@@ -478,6 +504,16 @@ pub struct BuildingInspectorData<'w, 's> {
     pub spawner_q: Query<'w, 's, &'static SpawnerState, With<Building>>,
     pub wall_level_q: Query<'w, 's, &'static mut WallLevel, With<Building>>,
     pub waypoint_order_q: Query<'w, 's, &'static WaypointOrder, With<Building>>,
+    pub assignment_q: Query<
+        'w,
+        's,
+        (
+            Option<&'static AssignedFarm>,
+            Option<&'static AssignedBed>,
+            Option<&'static AssignedPost>,
+        ),
+    >,
+    pub commands: Commands<'w, 's>,
 }
 
 #[derive(SystemParam)]
@@ -1062,13 +1098,16 @@ pub fn combat_log_system(
                         CombatEventKind::Kill => filter_state.show_kills,
                         CombatEventKind::Spawn => filter_state.show_spawns,
                         CombatEventKind::Raid => filter_state.show_raids,
-                        CombatEventKind::Harvest => filter_state.show_harvests,
+                        CombatEventKind::Harvest | CombatEventKind::Spoilage => {
+                            filter_state.show_harvests
+                        }
                         CombatEventKind::LevelUp => filter_state.show_levelups,
                         CombatEventKind::Ai => filter_state.show_ai,
                         CombatEventKind::BuildingDamage => filter_state.show_building_damage,
                         CombatEventKind::Loot => filter_state.show_loot,
                         CombatEventKind::Llm => filter_state.show_llm,
                         CombatEventKind::Chat => filter_state.show_chat,
+                        CombatEventKind::GameOver => true,
                     };
                     if !show {
                         continue;
@@ -1092,6 +1131,8 @@ pub fn combat_log_system(
                         CombatEventKind::Loot => egui::Color32::from_rgb(255, 215, 0),
                         CombatEventKind::Llm => egui::Color32::from_rgb(0, 200, 180),
                         CombatEventKind::Chat => egui::Color32::from_rgb(240, 200, 80),
+                        CombatEventKind::GameOver => egui::Color32::from_rgb(255, 255, 255),
+                        CombatEventKind::Spoilage => egui::Color32::from_rgb(150, 130, 70),
                     };
 
                     let key = (entry.day as i64) * 10000
@@ -1770,6 +1811,39 @@ fn inspector_content(
         if let Some(sq) = squad_id {
             ui.label(format!("Squad: {}", sq));
         }
+        // Manual assignments (endless/assign_npc): jump to the pinned building or unpin
+        let pins = bld_data
+            .entity_map
+            .get_npc(idx)
+            .and_then(|npc| bld_data.assignment_q.get(npc.entity).ok())
+            .map(|(farm, bed, post)| {
+                use crate::systems::AssignmentKind as K;
+                [
+                    farm.map(|a| (K::Farm, "Farm", a.0)),
+                    bed.map(|a| (K::Bed, "Bed", a.bed)),
+                    post.map(|a| (K::Post, "Post", a.0)),
+                ]
+            })
+            .unwrap_or_default();
+        for (kind, label, building) in pins.into_iter().flatten() {
+            let Some(slot) = bld_data.entity_map.slot_for_entity(building) else {
+                continue;
+            };
+            let action = ui
+                .horizontal(|ui| {
+                    let action = building_link(ui, &format!("Assigned {}", label), slot);
+                    if ui.small_button("Unpin").clicked() {
+                        bld_data.commands.queue(move |world: &mut World| {
+                            let _ = crate::systems::clear_assignment(world, idx, kind);
+                        });
+                    }
+                    action
+                })
+                .inner;
+            if action.is_some() {
+                return action;
+            }
+        }
     }
 
     if show_economy {
@@ -3425,3 +3499,35 @@ pub fn save_toast_system(mut contexts: EguiContexts, toast: Res<crate::save::Sav
 
     Ok(())
 }
+
+/// Persistent banner while the CPU compute fallback is active (GPU compute unavailable).
+pub fn compute_backend_warning_system(
+    mut contexts: EguiContexts,
+    backend: Res<crate::resources::ComputeBackend>,
+) -> Result {
+    if *backend != crate::resources::ComputeBackend::Cpu {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+
+    egui::Area::new(egui::Id::new("compute_backend_warning"))
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -180.0])
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(egui::Color32::from_rgba_unmultiplied(60, 20, 0, 200))
+                .corner_radius(4.0)
+                .inner_margin(egui::Margin::symmetric(10, 4))
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "GPU compute unavailable — CPU fallback (first {} units simulated)",
+                            crate::constants::CPU_FALLBACK_MAX_ENTITIES
+                        ))
+                        .color(egui::Color32::from_rgb(255, 180, 80)),
+                    );
+                });
+        });
+
+    Ok(())
+}
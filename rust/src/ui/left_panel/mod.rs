@@ -111,6 +111,7 @@ pub struct FactionsParams<'w, 's> {
     miner_cfg_q: Query<'w, 's, &'static MinerHomeConfig>,
     spawner_q: Query<'w, 's, &'static SpawnerState>,
     waypoint_q: Query<'w, 's, &'static WaypointOrder, With<Building>>,
+    faction_mvp: Res<'w, crate::resources::FactionMvp>,
 }
 
 #[derive(Clone)]
@@ -139,6 +140,8 @@ struct AiSnapshot {
     alive: i32,
     dead: i32,
     kills: i32,
+    /// Top soldier: (name, level, kills).
+    mvp: Option<(String, i32, i32)>,
     upgrades: Vec<u8>,
     last_actions: Vec<(String, i32, i32)>,
     mining_radius: f32,
@@ -325,6 +328,7 @@ pub fn left_panel_system(
 
     let debug_all = settings.debug_all_npcs;
     let help_text_size = settings.help_text_size;
+    let focus_town = upgrade.focus.town(&world_data.towns).unwrap_or(0);
 
     let tab_name = match ui_state.left_panel_tab {
         LeftPanelTab::Roster => "Roster",
@@ -385,6 +389,7 @@ pub fn left_panel_system(
                 ),
                 LeftPanelTab::Policies => policies_content(
                     ui,
+                    focus_town,
                     &mut factions.town_access,
                     &world_data,
                     &factions.entity_map,
@@ -397,6 +402,7 @@ pub fn left_panel_system(
                 LeftPanelTab::Patrols => {
                     patrol_swap = patrols_content(
                         ui,
+                        focus_town,
                         &world_data,
                         &factions.entity_map,
                         &mut jump_target,
@@ -422,6 +428,7 @@ pub fn left_panel_system(
                 LeftPanelTab::Factions => factions_content(
                     ui,
                     &factions,
+                    &roster.npc_stats_q,
                     &squad.squad_state,
                     &world_data,
                     &profiler.mining_policy,
@@ -529,6 +536,7 @@ fn save_left_panel_state(
 
 fn policies_content(
     ui: &mut egui::Ui,
+    town_idx: usize,
     town_access: &mut crate::systemparams::TownAccess<'_, '_>,
     world_data: &WorldData,
     entity_map: &EntityMap,
@@ -538,12 +546,6 @@ fn policies_content(
     ai_state: &mut AiPlayerState,
     miner_cfg_q: &Query<&MinerHomeConfig>,
 ) {
-    let town_idx = world_data
-        .towns
-        .iter()
-        .position(|t| t.faction == crate::constants::FACTION_PLAYER)
-        .unwrap_or(0);
-
     let Some(mut town_policy) = town_access.policy_mut(town_idx as i32) else {
         ui.label("No policy data");
         return;
@@ -616,6 +618,8 @@ fn policies_content(
         ui.add(egui::Slider::new(&mut recovery_pct, 0.0..=100.0).suffix("%"));
     });
     policy.recovery_hp = recovery_pct / 100.0;
+    ui.checkbox(&mut policy.auto_expand, "Auto Expand")
+        .on_hover_text("Buy the next Expansion ring when the build area is 80% full");
 
     // -- Archers --
     ui.add_space(8.0);
@@ -624,12 +628,89 @@ fn policies_content(
         .on_hover_text("Archers never flee combat");
     ui.checkbox(&mut policy.archer_leash, "Leash")
         .on_hover_text("Archers return home if too far from post");
+    ui.checkbox(&mut policy.auto_assign_guards, "Auto Assign Posts")
+        .on_hover_text("Pin free guards to uncovered posts when posts are added or removed");
     let mut archer_flee_pct = policy.archer_flee_hp * 100.0;
     ui.horizontal(|ui| {
         ui.label("Flee HP:");
         ui.add(egui::Slider::new(&mut archer_flee_pct, 0.0..=100.0).suffix("%"));
     });
     policy.archer_flee_hp = archer_flee_pct / 100.0;
+    ui.checkbox(&mut policy.reinforce_enabled, "Reinforce")
+        .on_hover_text("Idle and patrolling guards near an attack move to help");
+    if policy.reinforce_enabled {
+        ui.horizontal(|ui| {
+            ui.label("Reinforce radius:");
+            ui.add(egui::Slider::new(&mut policy.reinforce_radius, 100.0..=3000.0).suffix("px"));
+        });
+        let mut reserve_pct = policy.reinforce_reserve * 100.0;
+        ui.horizontal(|ui| {
+            ui.label("Keep on posts:");
+            ui.add(egui::Slider::new(&mut reserve_pct, 0.0..=100.0).suffix("%"));
+        });
+        policy.reinforce_reserve = reserve_pct / 100.0;
+    }
+    let mut last_stand = policy.last_stand_ratio > 0.0;
+    if ui
+        .checkbox(&mut last_stand, "Last stand")
+        .on_hover_text("Defenders fall back to the town center when badly outnumbered")
+        .changed()
+    {
+        policy.last_stand_ratio = if last_stand {
+            crate::constants::LAST_STAND_RATIO
+        } else {
+            0.0
+        };
+    }
+    if last_stand {
+        ui.horizontal(|ui| {
+            ui.label("Fall back at:");
+            ui.add(
+                egui::Slider::new(&mut policy.last_stand_ratio, 1.0..=6.0)
+                    .suffix(" enemies/defender"),
+            );
+        });
+    }
+    let mut panic = policy.panic_spread > 0.0;
+    if ui
+        .checkbox(&mut panic, "Panic spreads")
+        .on_hover_text("Units near fleeing allies can panic and flee too")
+        .changed()
+    {
+        policy.panic_spread = if panic {
+            crate::constants::PANIC_SPREAD
+        } else {
+            0.0
+        };
+    }
+    if panic {
+        ui.horizontal(|ui| {
+            ui.label("Spread rate:");
+            ui.add(egui::Slider::new(&mut policy.panic_spread, 0.05..=1.0).suffix("/s"));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Rout at:");
+            ui.add(egui::Slider::new(&mut policy.panic_threshold, 0.5..=5.0));
+        });
+    }
+    let mut supply = policy.supply_radius > 0.0;
+    if ui
+        .checkbox(&mut supply, "Supply lines")
+        .on_hover_text("Soldiers far from every friendly building attack slower and tire faster")
+        .changed()
+    {
+        policy.supply_radius = if supply {
+            crate::constants::SUPPLY_RADIUS
+        } else {
+            0.0
+        };
+    }
+    if supply {
+        ui.horizontal(|ui| {
+            ui.label("Supply radius:");
+            ui.add(egui::Slider::new(&mut policy.supply_radius, 400.0..=4000.0).suffix("px"));
+        });
+    }
     let mut archer_sched_idx = policy.archer_schedule as usize;
     ui.horizontal(|ui| {
         ui.label("Schedule:");
@@ -825,16 +906,13 @@ fn policies_content(
 /// Returns swap indices if the user clicked a reorder button.
 fn patrols_content(
     ui: &mut egui::Ui,
+    town_idx: usize,
     world_data: &WorldData,
     entity_map: &EntityMap,
     jump_target: &mut Option<Vec2>,
     waypoint_q: &Query<&WaypointOrder, With<Building>>,
 ) -> Option<(usize, usize)> {
-    let town_pair_idx = world_data
-        .towns
-        .iter()
-        .position(|t| t.faction == crate::constants::FACTION_PLAYER)
-        .unwrap_or(0) as u32;
+    let town_pair_idx = town_idx as u32;
 
     if let Some(town) = world_data.towns.get(town_pair_idx as usize) {
         ui.small(format!("Town: {}", town.name));
@@ -995,6 +1073,36 @@ fn squads_content(
     {
         squad.squad_state.squads[si].hold_fire = hold_fire;
     }
+    const STANCES: [crate::components::SquadStance; 3] = [
+        crate::components::SquadStance::Aggressive,
+        crate::components::SquadStance::Defensive,
+        crate::components::SquadStance::Passive,
+    ];
+    let (stance, radius) = {
+        let s = &squad.squad_state.squads[si];
+        (s.stance, s.engage_radius)
+    };
+    let mut stance_idx = STANCES.iter().position(|&s| s == stance).unwrap_or(0);
+    let mut engage_radius = radius;
+    ui.horizontal(|ui| {
+        ui.label("Stance:");
+        egui::ComboBox::from_id_salt("squad_stance")
+            .selected_text(STANCES[stance_idx].label())
+            .show_index(ui, &mut stance_idx, STANCES.len(), |i| STANCES[i].label());
+    })
+    .response
+    .on_hover_text("Aggressive pursues, Defensive holds the target and engages within range, Passive only returns fire");
+    if STANCES[stance_idx] == crate::components::SquadStance::Defensive {
+        ui.horizontal(|ui| {
+            ui.label("Engage radius:");
+            ui.add(egui::Slider::new(&mut engage_radius, 100.0..=2000.0).suffix("px"));
+        });
+    }
+    if STANCES[stance_idx] != stance || engage_radius != radius {
+        squad
+            .squad_state
+            .set_squad_stance(si, STANCES[stance_idx], engage_radius);
+    }
 
     ui.add_space(4.0);
 
@@ -1426,6 +1534,7 @@ fn rebuild_factions_cache(
             alive,
             dead,
             kills,
+            mvp: None,
             upgrades,
             last_actions,
             mining_radius,
@@ -1711,6 +1820,7 @@ fn build_faction_debug_string(snap: &AiSnapshot) -> String {
 fn factions_content(
     ui: &mut egui::Ui,
     factions: &FactionsParams,
+    npc_stats_q: &Query<&mut NpcStats>,
     squad_state: &SquadState,
     world_data: &WorldData,
     mining_policy: &MiningPolicy,
@@ -1731,6 +1841,18 @@ fn factions_content(
             mining_policy,
             cache,
         );
+        // Top soldier per faction (name/level live from NpcStats)
+        for snap in &mut cache.snapshots {
+            snap.mvp = factions.faction_mvp.mvp.get(&snap.faction).and_then(|m| {
+                let npc = factions.entity_map.get_npc(m.slot)?;
+                let stats = npc_stats_q.get(npc.entity).ok()?;
+                Some((
+                    stats.name.clone(),
+                    crate::systems::stats::level_from_xp(stats.xp),
+                    m.kills,
+                ))
+            });
+        }
     }
 
     if cache.snapshots.is_empty() {
@@ -1801,6 +1923,10 @@ fn factions_content(
             "Alive: {}  Dead: {}  Kills: {}",
             snap.alive, snap.dead, snap.kills
         ));
+        if let Some((name, level, kills)) = &snap.mvp {
+            ui.separator();
+            ui.label(format!("Top soldier: {name} Lv.{level} ({kills} kills)"));
+        }
     });
     ui.separator();
 
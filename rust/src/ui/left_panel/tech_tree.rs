@@ -3,7 +3,6 @@
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 
-use crate::constants::FACTION_PLAYER;
 use crate::resources::*;
 use crate::settings::UserSettings;
 use crate::systems::stats::{
@@ -241,11 +240,7 @@ pub fn tech_tree_system(
 
     let ctx = contexts.ctx_mut()?;
 
-    let town_idx = world_data
-        .towns
-        .iter()
-        .position(|t| t.faction == FACTION_PLAYER)
-        .unwrap_or(0);
+    let town_idx = upgrade.focus.town(&world_data.towns).unwrap_or(0);
     let food = town_access.food(town_idx as i32);
     let gold = town_access.gold(town_idx as i32);
     let player_faction = world_data
@@ -345,7 +340,13 @@ pub fn tech_tree_system(
                         for pn in &placed {
                             let node = &reg.nodes[pn.idx];
                             let lv = levels.get(pn.idx).copied().unwrap_or(0);
-                            let state = node_state(&levels, pn.idx, food, gold);
+                            let tech_locked =
+                                town_access.upgrade_tech_locked(town_idx as i32, pn.idx);
+                            let state = if tech_locked {
+                                NodeState::Locked
+                            } else {
+                                node_state(&levels, pn.idx, food, gold)
+                            };
                             let node_idx = pn.idx;
 
                             // Node interaction region is registered before child widgets so
@@ -478,6 +479,12 @@ pub fn tech_tree_system(
                                             .color(egui::Color32::from_rgb(200, 100, 100)),
                                     );
                                 }
+                                if tech_locked {
+                                    ui.label(
+                                        egui::RichText::new("Requires tech unlock")
+                                            .color(egui::Color32::from_rgb(200, 100, 100)),
+                                    );
+                                }
                             });
 
                             // Click to buy
@@ -5,6 +5,7 @@ pub struct UpgradeParams<'w> {
     pub(crate) faction_stats: Res<'w, FactionStats>,
     pub(crate) queue: MessageWriter<'w, UpgradeMsg>,
     pub(crate) auto: ResMut<'w, AutoUpgrade>,
+    pub(crate) focus: Res<'w, PlayerFocus>,
 }
 
 // ============================================================================
@@ -18,11 +19,7 @@ pub(crate) fn upgrade_content(
     world_data: &WorldData,
     settings: &mut UserSettings,
 ) {
-    let town_idx = world_data
-        .towns
-        .iter()
-        .position(|t| t.faction == crate::constants::FACTION_PLAYER)
-        .unwrap_or(0);
+    let town_idx = upgrade.focus.town(&world_data.towns).unwrap_or(0);
     let food = town_access.food(town_idx as i32);
     let gold = town_access.gold(town_idx as i32);
     let player_faction = world_data
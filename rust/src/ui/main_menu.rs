@@ -121,6 +121,7 @@ pub fn main_menu_system(
 ) -> Result {
     let ctx = contexts.ctx_mut()?;
     pathfind_config.max_per_frame = user_settings.pathfind_max_per_frame.max(1);
+    pathfind_config.retarget_cooldown = user_settings.retarget_cooldown.max(0.0);
 
     // Init slider defaults from saved settings (or WorldGenConfig defaults)
     if !state.initialized {
@@ -372,7 +373,8 @@ pub fn main_menu_system(
             if ui.button(egui::RichText::new("  Play  ").size(18.0)).clicked() {
                 strip_disabled_home_jobs(&mut state.npc_counts);
                 clamp_player_menu_caps(&mut state);
-                wg_config.gen_style = WorldGenStyle::Continents;
+                wg_config.generator = WorldGenStyle::Continents;
+                wg_config.seed = None;
                 wg_config.world_width = state.world_size;
                 wg_config.world_height = state.world_size;
                 wg_config.num_towns = 1;
@@ -383,9 +385,11 @@ pub fn main_menu_system(
                 wg_config.ai_towns = ai_builder_count;
                 wg_config.raider_towns = ai_raider_count;
                 wg_config.gold_mines_per_town = state.gold_mines as usize;
+                wg_config.faction_loadouts = user_settings.faction_loadouts.clone();
                 ai_config.decision_interval = state.ai_interval;
                 npc_config.interval = state.npc_interval;
                 pathfind_config.max_per_frame = user_settings.pathfind_max_per_frame.max(1);
+                pathfind_config.retarget_cooldown = user_settings.retarget_cooldown.max(0.0);
 
                 let mut saved = settings::load_settings();
                 saved.world_size = state.world_size;
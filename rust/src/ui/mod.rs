@@ -176,6 +176,8 @@ pub fn settings_panel_ui(
                                 ui.label(label);
                             });
                             ui.small("Auto-save interval in game hours. 0 = disabled.");
+                            ui.checkbox(&mut settings.compress_saves, "Compress Saves");
+                            ui.small("Smaller save files for big worlds; saving takes a little longer.");
                             ui.add_space(6.0);
 
                             if settings.tutorial_completed {
@@ -309,6 +311,22 @@ pub fn settings_panel_ui(
                             ui.add(egui::Slider::new(&mut settings.lod_transition, 0.1..=2.0).text("LOD Transition"))
                                 .on_hover_text("Below this zoom level, sprites render as flat rectangles.");
                             ui.small("Lower values keep detailed sprites visible longer.");
+                            ui.add_space(6.0);
+
+                            ui.checkbox(&mut settings.pixel_snap, "Pixel Snap")
+                                .on_hover_text("Snap sprites to the screen pixel grid to stop shimmering at fractional zooms.");
+                            ui.small("Turns itself off when zoomed in close, so movement stays smooth.");
+                            ui.add_space(6.0);
+
+                            ui.add(egui::Slider::new(&mut settings.camera_follow.smoothing, 0.02..=1.0).text("Follow Smoothing"))
+                                .on_hover_text("How quickly the camera catches up to a followed unit (1 = locked on).");
+                            ui.small("Lower values glide; the camera still snaps when switching units.");
+                            ui.add_space(6.0);
+
+                            ui.add(egui::Slider::new(&mut settings.camera_follow.dead_zone[0], 0.0..=400.0).text("Follow Dead Zone X"))
+                                .on_hover_text("Screen pixels the unit can drift left/right of center before the camera moves.");
+                            ui.add(egui::Slider::new(&mut settings.camera_follow.dead_zone[1], 0.0..=300.0).text("Follow Dead Zone Y"))
+                                .on_hover_text("Screen pixels the unit can drift up/down of center before the camera moves.");
                         }
                         PauseSettingsTab::Controls => {
                             if let Some(action) = *rebinding_action {
@@ -415,6 +433,8 @@ pub fn settings_panel_ui(
                             ui.small("Enable per-system timing overlays/logging.");
                             ui.checkbox(&mut settings.debug_ai_decisions, "AI Decision Logging");
                             ui.small("Log AI player action selection details.");
+                            ui.checkbox(&mut settings.debug_rewind, "Rewind Snapshots");
+                            ui.small("Keep a compressed snapshot per game day for endless/rewind.");
                             ui.checkbox(&mut settings.show_terrain_sprites, "Show Terrain Sprites");
                             ui.small("Toggle sprite-vs-plain rendering for terrain.");
                             ui.checkbox(&mut settings.show_all_faction_squad_lines, "Show All Faction Squad Lines");
@@ -440,6 +460,13 @@ pub fn settings_panel_ui(
                                     .step_by(10.0));
                             });
                             ui.small("Max path requests processed per tick. Higher reduces queueing but costs more CPU.");
+                            ui.horizontal(|ui| {
+                                ui.label("Retarget Cooldown:");
+                                ui.add(egui::Slider::new(&mut settings.retarget_cooldown, 0.0..=2.0)
+                                    .step_by(0.05)
+                                    .suffix("s"));
+                            });
+                            ui.small("Delay after arriving before NPCs take a new automatic target. Smooths mass arrivals; player orders are never delayed.");
                         }
                         PauseSettingsTab::LlmPlayer => {
                             ui.horizontal(|ui| {
@@ -657,6 +684,7 @@ pub fn register_ui(app: &mut App) {
             pause_menu_system,
             game_over_system,
             game_hud::save_toast_system,
+            game_hud::compute_backend_warning_system,
             tutorial::tutorial_ui_system,
         )
             .chain()
@@ -666,13 +694,25 @@ pub fn register_ui(app: &mut App) {
     // Panel toggle keyboard shortcuts + ESC
     app.add_systems(
         Update,
-        (ui_toggle_system, game_escape_system).run_if(in_state(AppState::Playing)),
+        (
+            ui_toggle_system,
+            select_next_idle_system,
+            player_focus_system,
+            game_escape_system,
+        )
+            .run_if(in_state(AppState::Playing)),
     );
 
     // Escape + settings + keyboard toggles in test scenes
     app.add_systems(
         Update,
-        (game_escape_system, ui_toggle_system).run_if(in_state(AppState::Running)),
+        (
+            game_escape_system,
+            ui_toggle_system,
+            select_next_idle_system,
+            player_focus_system,
+        )
+            .run_if(in_state(AppState::Running)),
     );
     // Test scene UI: bottom panel + overlays + build menu + pause
     // (top_bar, left_panel, combat_log already registered in tests/mod.rs)
@@ -688,6 +728,7 @@ pub fn register_ui(app: &mut App) {
             armory::armory_window_system,
             pause_menu_system,
             game_hud::save_toast_system,
+            game_hud::compute_backend_warning_system,
         )
             .run_if(in_state(AppState::Running)),
     );
@@ -812,6 +853,86 @@ pub fn ui_toggle_system(
     }
 }
 
+/// "Next idle unit" hotkey: select the focused town's next idle, non-fighting NPC and jump
+/// the camera to it. Cycles in slot order via `IdleCycle`.
+pub fn select_next_idle_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<UserSettings>,
+    ui_state: Res<UiState>,
+    world_data: Res<world::WorldData>,
+    player_focus: Res<PlayerFocus>,
+    entity_map: Res<EntityMap>,
+    gpu_state: Res<GpuReadState>,
+    npc_q: Query<(&Activity, &CombatState), Without<Building>>,
+    mut cycle: ResMut<IdleCycle>,
+    mut selected_npc: ResMut<SelectedNpc>,
+    mut selected_building: ResMut<SelectedBuilding>,
+    mut camera_query: Query<&mut Transform, With<crate::render::MainCamera>>,
+    mut contexts: bevy_egui::EguiContexts,
+) {
+    if ui_state.pause_menu_open
+        || !keys.just_pressed(settings.key_for_action(ControlAction::SelectNextIdle))
+    {
+        return;
+    }
+    if contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.wants_keyboard_input())
+    {
+        return;
+    }
+    let Some(town) = player_focus.town(&world_data.towns) else {
+        return;
+    };
+    let idle = entity_map.town_npc_slots_where(town as i32, |n| {
+        npc_q
+            .get(n.entity)
+            .is_ok_and(|(a, c)| a.is_idle() && !c.is_fighting())
+    });
+    let Some(slot) = cycle.next(town, &idle) else {
+        return;
+    };
+    selected_npc.0 = slot as i32;
+    selected_building.active = false;
+    if let Some(p) = gpu_state.positions.get(slot * 2..slot * 2 + 2) {
+        if let Ok(mut transform) = camera_query.single_mut() {
+            transform.translation.x = p[0];
+            transform.translation.y = p[1];
+        }
+    }
+}
+
+/// Keep `PlayerFocus` on a town the player owns (a lost or captured town hands focus to the
+/// next owned one, or clears it), and on a switch retarget the build menu and jump the
+/// camera to the new town center.
+pub fn player_focus_system(
+    world_data: Res<world::WorldData>,
+    mut player_focus: ResMut<PlayerFocus>,
+    mut build_ctx: ResMut<BuildMenuContext>,
+    mut camera_query: Query<&mut Transform, With<crate::render::MainCamera>>,
+    mut prev: Local<Option<usize>>,
+) {
+    let town = player_focus.town(&world_data.towns);
+    if player_focus.0.is_some() && player_focus.0 != town {
+        player_focus.0 = town;
+    }
+    if town == *prev {
+        return;
+    }
+    let switched = prev.is_some();
+    *prev = town;
+    build_ctx.town_data_idx = town;
+    let Some(center) = town.and_then(|t| world_data.towns.get(t)).map(|t| t.center) else {
+        return;
+    };
+    if switched {
+        if let Ok(mut transform) = camera_query.single_mut() {
+            transform.translation.x = center.x;
+            transform.translation.y = center.y;
+        }
+    }
+}
+
 // ============================================================================
 // GAME STARTUP
 // ============================================================================
@@ -824,6 +945,7 @@ struct StartupExtra<'w> {
     reputation: ResMut<'w, Reputation>,
     auto_upgrade: ResMut<'w, AutoUpgrade>,
     mining_policy: ResMut<'w, MiningPolicy>,
+    combat_rng: ResMut<'w, crate::resources::CombatRng>,
 }
 
 /// Load a saved game when entering Playing state (if load_on_enter is set).
@@ -901,7 +1023,7 @@ fn game_load_system(
 /// Skips world gen if load_on_enter was handled by game_load_system.
 fn game_startup_system(
     mut commands: Commands,
-    config: Res<WorldGenConfig>,
+    mut config: ResMut<WorldGenConfig>,
     mut world_state: WorldState,
     mut faction_list: ResMut<crate::resources::FactionList>,
     mut faction_stats: ResMut<FactionStats>,
@@ -923,6 +1045,10 @@ fn game_startup_system(
     }
 
     info!("Game startup: generating world...");
+    // One seed for the whole game: terrain, loadouts and combat rolls
+    let seed = *config.seed.get_or_insert_with(rand::random);
+    extra.combat_rng.seed = seed;
+    extra.combat_rng.counter = 0;
 
     // Full world setup: terrain, towns, resources, buildings, spawners, NPCs, AI players
     let ai_players = world::setup_world(
@@ -1054,7 +1180,7 @@ fn tutorial_init_system(
 
     // Skip if already completed or loading a save (loaded saves have non-zero game time)
     if settings.tutorial_completed || game_time.total_seconds > 0.0 {
-        tutorial.step = 255;
+        tutorial.finish();
         return;
     }
 
@@ -1064,28 +1190,23 @@ fn tutorial_init_system(
         .position(|t| t.faction == crate::constants::FACTION_PLAYER)
         .unwrap_or(0);
 
-    // Snapshot initial building counts for completion checks
+    // Snapshot initial building counts for BuildCount conditions
     let pt = player_town as u32;
-    tutorial.initial_farms = entity_map.count_for_town(BuildingKind::Farm, pt);
-    tutorial.initial_farmer_homes = entity_map.count_for_town(BuildingKind::FarmerHome, pt);
-    tutorial.initial_waypoints = entity_map.count_for_town(BuildingKind::Waypoint, pt);
-    tutorial.initial_archer_homes = entity_map.count_for_town(BuildingKind::ArcherHome, pt);
-    tutorial.initial_miner_homes = entity_map.count_for_town(BuildingKind::MinerHome, pt);
+    for kind in tutorial.tracked_building_kinds() {
+        let count = entity_map.count_for_town(kind, pt);
+        tutorial.initial_buildings.insert(kind, count);
+    }
 
     // Snapshot camera start position
     if let Ok(transform) = camera_query.single() {
         tutorial.camera_start = Vec2::new(transform.translation.x, transform.translation.y);
     }
 
-    tutorial.start_time = time.elapsed_secs_f64();
-    tutorial.step = 1;
+    tutorial.start(time.elapsed_secs_f64());
     info!(
-        "Tutorial started (farms={}, farmer_homes={}, waypoints={}, archer_homes={}, miner_homes={})",
-        tutorial.initial_farms,
-        tutorial.initial_farmer_homes,
-        tutorial.initial_waypoints,
-        tutorial.initial_archer_homes,
-        tutorial.initial_miner_homes
+        "Tutorial started ({} steps, initial buildings={:?})",
+        tutorial.steps.len(),
+        tutorial.initial_buildings
     );
 }
 
@@ -1383,6 +1504,7 @@ fn pause_menu_system(
     runtime_configs.ai_config.decision_interval = settings.ai_interval;
     runtime_configs.npc_config.interval = settings.npc_interval;
     runtime_configs.pathfind_config.max_per_frame = settings.pathfind_max_per_frame.max(1);
+    runtime_configs.pathfind_config.retarget_cooldown = settings.retarget_cooldown.max(0.0);
     save_request.autosave_hours = settings.autosave_hours;
 
     Ok(())
@@ -1401,6 +1523,7 @@ fn game_over_system(
     kill_stats: Res<crate::resources::KillStats>,
     town_access: crate::systemparams::TownAccess,
     world_data: Res<crate::world::WorldData>,
+    win: Res<crate::systems::WinCondition>,
 ) -> Result {
     if !ui_state.game_over {
         return Ok(());
@@ -1427,6 +1550,20 @@ fn game_over_system(
         .show(ctx, |ui| {
             ui.add_space(4.0);
 
+            // Scenario ends say which condition decided it
+            let verdict = match win.get_game_state() {
+                crate::systems::victory::GameOutcome::Won => Some("Victory"),
+                crate::systems::victory::GameOutcome::Lost => Some("Defeat"),
+                crate::systems::victory::GameOutcome::Ongoing => None,
+            };
+            if let Some(verdict) = verdict {
+                ui.vertical_centered(|ui| {
+                    ui.heading(verdict);
+                    ui.label(&win.reason);
+                });
+                ui.add_space(8.0);
+            }
+
             let player_town = world_data
                 .towns
                 .iter()
@@ -1634,7 +1771,6 @@ fn build_place_click_system(
     let Some(town) = world_state.world_data.towns.get(town_data_idx) else {
         return;
     };
-    let center = town.center;
     let town_name = town.name.clone();
     // Track food locally; write back to ECS at function exits that modify it
     let mut food_val = town_access.food(town_data_idx as i32);
@@ -1707,19 +1843,44 @@ fn build_place_click_system(
 
     let kind = build_ctx.selected_build.expect("checked above");
 
+    // Same town-level availability the build menu grays out with
+    let upgrade_levels = town_access.upgrade_levels(town_data_idx as i32);
+    let tech_locked = town_access.tech_locked_buildings(town_data_idx as i32);
+    let blocked = world::BuildCheck::new(
+        town_data_idx,
+        &world_state.world_data.towns[town_data_idx],
+        food_val,
+        &upgrade_levels,
+        &world_state.grid,
+        &world_state.entity_map,
+        &world_state.building_costs,
+    )
+    .with_tech_locks(tech_locked.clone())
+    .check(kind, &world_state.entity_map)
+    .err();
+    if let Some(block) = blocked {
+        if just_pressed {
+            toast.message = block.reason().to_string();
+            toast.timer = 2.0;
+        }
+        build_ctx.clear_drag();
+        return;
+    }
+
     // Waypoint: single-click placement
     if kind == BuildingKind::Waypoint {
         if !just_pressed {
             return;
         }
         build_ctx.clear_drag();
-        let cost = crate::constants::building_cost(kind);
+        let cost = world_state.building_costs.cost(kind);
         match world_state.place_building(
             &mut food_val,
             kind,
             town_data_idx,
             world_pos,
             cost,
+            &tech_locked,
             &mut gpu_updates,
             &mut commands,
         ) {
@@ -1758,7 +1919,7 @@ fn build_place_click_system(
 
         let start = build_ctx.drag_start_slot.take().unwrap_or((gc, gr));
         let end = build_ctx.drag_current_slot.take().unwrap_or((gc, gr));
-        let cost = crate::constants::building_cost(kind);
+        let cost = world_state.building_costs.cost(kind);
         let mut placed = 0usize;
         let mut upgraded = 0usize;
         let mut last_err: Option<&str> = None;
@@ -1770,6 +1931,7 @@ fn build_place_click_system(
                 town_data_idx,
                 cell_pos,
                 cost,
+                &tech_locked,
                 &mut gpu_updates,
                 &mut commands,
             ) {
@@ -1835,25 +1997,14 @@ fn build_place_click_system(
     }
 
     // World-grid build mode: supports single-click and click-drag line placement.
+    // Area and town-center checks live in get_build_validity (via place_building).
     let label = crate::constants::building_def(kind).label;
-    let (cc, cr) = world_state.grid.world_to_grid(center);
 
     let mut last_place_err: Option<&str> = None;
     let mut try_place_at_slot =
         |slot_col: usize, slot_row: usize, err_out: &mut Option<&str>| -> bool {
-            if !world_state
-                .grid
-                .can_town_build(slot_col, slot_row, town_data_idx as u16)
-            {
-                *err_out = Some("outside buildable area");
-                return false;
-            }
-            if slot_col == cc && slot_row == cr {
-                *err_out = Some("cannot build on town center");
-                return false;
-            }
             let pos = world_state.grid.grid_to_world(slot_col, slot_row);
-            let cost = crate::constants::building_cost(kind);
+            let cost = world_state.building_costs.cost(kind);
 
             match world_state.place_building(
                 &mut food_val,
@@ -1861,6 +2012,7 @@ fn build_place_click_system(
                 town_data_idx,
                 pos,
                 cost,
+                &tech_locked,
                 &mut gpu_updates,
                 &mut commands,
             ) {
@@ -1950,6 +2102,37 @@ fn road_ui_cell_allowed(cell: Option<&world::WorldCell>) -> bool {
     })
 }
 
+/// Cursor ghost tint: white when `get_build_validity` passes, red when placement would fail.
+fn ghost_color(valid: bool) -> Color {
+    if valid {
+        Color::srgba(1.0, 1.0, 1.0, 0.7)
+    } else {
+        Color::srgba(0.8, 0.2, 0.2, 0.5)
+    }
+}
+
+/// Ghost verdict for one cell: `get_build_validity` with `budget` as the food on hand. A valid
+/// cell spends `cost` from the budget, so a drag trail only stays white as far as food covers.
+fn ghost_validity(
+    kind: BuildingKind,
+    pos: Vec2,
+    town_idx: usize,
+    grid: &world::WorldGrid,
+    world_data: &world::WorldData,
+    entity_map: &EntityMap,
+    no_build: &crate::resources::NoBuildZones,
+    budget: &mut i32,
+    cost: i32,
+) -> Result<Vec2, world::CellBlock> {
+    let validity = world::get_build_validity(
+        kind, pos, town_idx, grid, world_data, entity_map, no_build, *budget, cost,
+    );
+    if validity.is_ok() {
+        *budget -= cost;
+    }
+    validity
+}
+
 /// Marker for the build ghost preview sprite.
 #[derive(Component)]
 pub(crate) struct BuildGhost;
@@ -1969,6 +2152,8 @@ fn build_ghost_system(
     world_data: Res<world::WorldData>,
     town_access: crate::systemparams::TownAccess,
     entity_map: Res<EntityMap>,
+    no_build: Res<crate::resources::NoBuildZones>,
+    costs: Res<crate::resources::BuildingCosts>,
     mut ghost_query: Query<
         (Entity, &mut Transform, &mut Sprite),
         (
@@ -1980,6 +2165,7 @@ fn build_ghost_system(
     trail_query: Query<Entity, With<BuildGhostTrail>>,
 ) {
     let has_selection = build_ctx.selected_build.is_some() || build_ctx.destroy_mode;
+    build_ctx.ghost_block_reason = None;
 
     // Despawn ghost if no selection
     if !has_selection {
@@ -2055,6 +2241,7 @@ fn build_ghost_system(
             transform.translation = Vec3::new(snapped.x, snapped.y, ghost_z);
             sprite.color = color;
             sprite.image = Handle::default();
+            sprite.custom_size = Some(Vec2::splat(TOWN_GRID_SPACING));
         } else {
             commands.spawn((
                 Sprite {
@@ -2086,7 +2273,7 @@ fn build_ghost_system(
             None => vec![(gc, gr)],
         };
 
-        let cost = crate::constants::building_cost(kind);
+        let cost = costs.cost(kind);
         let town_idx = build_ctx.town_data_idx.unwrap_or(0);
         let mut budget = town_access.food(town_idx as i32);
         let ghost_image = build_ctx
@@ -2104,16 +2291,22 @@ fn build_ghost_system(
         let mut cursor_valid = false;
         for (idx, &(sc, sr)) in path.iter().enumerate() {
             let cell_world = grid.grid_to_world(sc, sr);
-            let cell = grid.cell(sc, sr);
-            let empty = !entity_map.has_building_at(sc as i32, sr as i32);
-            let buildable_terrain = road_ui_cell_allowed(cell);
-            let valid = empty && buildable_terrain && budget >= cost;
-            if valid {
-                budget -= cost;
-            }
+            let validity = ghost_validity(
+                kind,
+                cell_world,
+                town_idx,
+                &grid,
+                &world_data,
+                &entity_map,
+                &no_build,
+                &mut budget,
+                cost,
+            );
+            let valid = validity.is_ok();
 
             if idx == path.len() - 1 {
                 cursor_valid = valid;
+                build_ctx.ghost_block_reason = validity.err().map(world::CellBlock::message);
             } else {
                 let color = if valid {
                     Color::srgba(1.0, 1.0, 1.0, 0.45)
@@ -2135,15 +2328,12 @@ fn build_ghost_system(
         }
 
         build_ctx.show_cursor_hint = !cursor_valid;
-        let color = if cursor_valid {
-            Color::srgba(1.0, 1.0, 1.0, 0.7)
-        } else {
-            Color::srgba(0.8, 0.2, 0.2, 0.5)
-        };
+        let color = ghost_color(cursor_valid);
         if let Some((_, mut transform, mut sprite)) = ghost_query.iter_mut().next() {
             transform.translation = Vec3::new(snapped.x, snapped.y, ghost_z);
             sprite.color = color;
             sprite.image = ghost_image;
+            sprite.custom_size = Some(Vec2::splat(TOWN_GRID_SPACING));
         } else {
             commands.spawn((
                 Sprite {
@@ -2167,19 +2357,23 @@ fn build_ghost_system(
         let (gc, gr) = grid.world_to_grid(world_pos);
         let snapped = grid.grid_to_world(gc, gr);
         build_ctx.hover_world_pos = snapped;
-        let cell = grid.cell(gc, gr);
-        let empty = !entity_map.has_building_at(gc as i32, gr as i32);
-        let buildable_terrain = cell
-            .map(|c| !matches!(c.terrain, world::Biome::Water | world::Biome::Rock))
-            .unwrap_or(false);
-        let valid = empty && buildable_terrain;
+        let town_idx = build_ctx.town_data_idx.unwrap_or(0);
+        let validity = ghost_validity(
+            kind,
+            snapped,
+            town_idx,
+            &grid,
+            &world_data,
+            &entity_map,
+            &no_build,
+            &mut town_access.food(town_idx as i32),
+            costs.cost(kind),
+        );
+        let valid = validity.is_ok();
         build_ctx.show_cursor_hint = !valid;
+        build_ctx.ghost_block_reason = validity.err().map(world::CellBlock::message);
 
-        let color = if valid {
-            Color::srgba(1.0, 1.0, 1.0, 0.7)
-        } else {
-            Color::srgba(0.8, 0.2, 0.2, 0.5)
-        };
+        let color = ghost_color(valid);
         let ghost_image = build_ctx
             .ghost_sprites
             .get(&kind)
@@ -2191,6 +2385,7 @@ fn build_ghost_system(
             transform.translation = Vec3::new(snapped.x, snapped.y, ghost_z);
             sprite.color = color;
             sprite.image = ghost_image;
+            sprite.custom_size = Some(Vec2::splat(TOWN_GRID_SPACING));
         } else {
             commands.spawn((
                 Sprite {
@@ -2210,74 +2405,69 @@ fn build_ghost_system(
     let Some(town_data_idx) = build_ctx.town_data_idx else {
         return;
     };
-    let Some(town) = world_data.towns.get(town_data_idx) else {
+    if town_data_idx >= world_data.towns.len() {
         return;
-    };
-    let center = town.center;
+    }
     let (gc, gr) = grid.world_to_grid(world_pos);
     let slot_pos = grid.grid_to_world(gc, gr);
     build_ctx.hover_world_pos = slot_pos;
 
-    // Determine validity
-    let has_building = entity_map.has_building_at(gc as i32, gr as i32);
-    let in_bounds = grid.can_town_build(gc, gr, town_data_idx as u16);
-    let (cc, cr) = grid.world_to_grid(center);
-    let is_center = gc == cc && gr == cr;
-
+    // Same check placement runs, spending a drag budget so the trail shows what food covers.
+    // Slots outside the town area (and the center) get no ghost at all.
+    let cost = costs.cost(kind);
+    let mut budget = town_access.food(town_data_idx as i32);
+    let mut check = |col: usize, row: usize| {
+        let validity = ghost_validity(
+            kind,
+            grid.grid_to_world(col, row),
+            town_data_idx,
+            &grid,
+            &world_data,
+            &entity_map,
+            &no_build,
+            &mut budget,
+            cost,
+        );
+        let visible = !matches!(
+            validity,
+            Err(world::CellBlock::OutsideBuildArea
+                | world::CellBlock::IsCenter
+                | world::CellBlock::OffMap)
+        );
+        (validity, visible)
+    };
+    let path = match (build_ctx.drag_start_slot, build_ctx.drag_current_slot) {
+        (Some(start), Some(end)) => slots_on_line(start, end),
+        _ => vec![(gc, gr)],
+    };
     let mut drag_preview: Vec<(usize, usize, bool, bool)> = Vec::new();
-    {
-        let path = match (build_ctx.drag_start_slot, build_ctx.drag_current_slot) {
-            (Some(start), Some(end)) => slots_on_line(start, end),
-            _ => vec![(gc, gr)],
-        };
-        let cost = crate::constants::building_cost(kind);
-        let mut budget = town_access.food(town_data_idx as i32);
-
-        for (slot_col, slot_row) in path {
-            let visible_slot = grid.can_town_build(slot_col, slot_row, town_data_idx as u16)
-                && !(slot_col == cc && slot_row == cr);
-            if !visible_slot {
-                drag_preview.push((slot_col, slot_row, false, false));
-                continue;
-            }
-
-            let slot_empty = !entity_map.has_building_at(slot_col as i32, slot_row as i32);
-            let can_pay = budget >= cost;
-            let slot_valid = slot_empty && can_pay;
-            if slot_valid {
-                budget -= cost;
-            }
-            drag_preview.push((slot_col, slot_row, slot_valid, true));
+    let mut cursor = None;
+    for (slot_col, slot_row) in path {
+        let (validity, visible) = check(slot_col, slot_row);
+        if (slot_col, slot_row) == (gc, gr) {
+            cursor = Some((validity, visible));
         }
+        drag_preview.push((slot_col, slot_row, validity.is_ok(), visible));
     }
-
-    // Build: valid on empty buildable non-center slots, including drag budget preview.
-    let (valid, visible) = {
-        let current = drag_preview
-            .iter()
-            .find(|(sc, sr, _, _)| *sc == gc && *sr == gr)
-            .copied();
-        if let Some((_, _, v, vis)) = current {
-            (v, vis)
-        } else {
-            (
-                in_bounds && !is_center && !has_building,
-                in_bounds && !is_center,
-            )
-        }
-    };
+    let (validity, visible) = cursor.unwrap_or_else(|| check(gc, gr));
+    let valid = validity.is_ok();
     // Hide mouse-follow sprite when we're snapped to a valid build slot.
     build_ctx.show_cursor_hint = !valid;
+    if visible {
+        build_ctx.ghost_block_reason = validity.err().map(world::CellBlock::message);
+    }
 
-    let color = if !visible {
-        Color::NONE
-    } else if valid {
-        Color::srgba(1.0, 1.0, 1.0, 0.7)
+    let color = if visible {
+        ghost_color(valid)
     } else {
-        Color::srgba(0.8, 0.2, 0.2, 0.5)
+        Color::NONE
     };
 
-    let snapped = grid.grid_to_world(gc, gr);
+    // Multi-cell buildings preview their whole footprint, anchored like placement is
+    let footprint = crate::constants::building_def(kind).footprint;
+    let ghost_size =
+        Vec2::new(footprint.0.max(1) as f32, footprint.1.max(1) as f32) * TOWN_GRID_SPACING;
+    let snapped = grid.footprint_center(gc, gr, footprint);
     let ghost_z = 0.5;
     let ghost_image = build_ctx
         .ghost_sprites
@@ -2294,7 +2484,7 @@ fn build_ghost_system(
             if (slot_col == gc && slot_row == gr) || !slot_visible {
                 continue;
             }
-            let snapped_slot = grid.grid_to_world(slot_col, slot_row);
+            let snapped_slot = grid.footprint_center(slot_col, slot_row, footprint);
             let slot_color = if slot_valid {
                 Color::srgba(1.0, 1.0, 1.0, 0.45)
             } else {
@@ -2304,7 +2494,7 @@ fn build_ghost_system(
                 Sprite {
                     color: slot_color,
                     image: ghost_image.clone(),
-                    custom_size: Some(Vec2::splat(TOWN_GRID_SPACING)),
+                    custom_size: Some(ghost_size),
                     ..default()
                 },
                 Transform::from_xyz(snapped_slot.x, snapped_slot.y, ghost_z),
@@ -2318,13 +2508,14 @@ fn build_ghost_system(
         transform.translation = Vec3::new(snapped.x, snapped.y, ghost_z);
         sprite.color = color;
         sprite.image = ghost_image;
+        sprite.custom_size = Some(ghost_size);
     } else {
         // Spawn ghost
         commands.spawn((
             Sprite {
                 color,
                 image: ghost_image,
-                custom_size: Some(Vec2::splat(TOWN_GRID_SPACING)),
+                custom_size: Some(ghost_size),
                 ..default()
             },
             Transform::from_xyz(snapped.x, snapped.y, ghost_z),
@@ -2574,6 +2765,7 @@ fn process_destroy_system(
 pub(crate) struct CleanupWorld<'w> {
     world_state: WorldState<'w>,
     faction_stats: ResMut<'w, FactionStats>,
+    faction_mvp: ResMut<'w, crate::resources::FactionMvp>,
     gpu_state: ResMut<'w, GpuReadState>,
     render_config: ResMut<'w, crate::gpu::RenderFrameConfig>,
     npc_gpu_state: ResMut<'w, crate::gpu::EntityGpuState>,
@@ -2584,6 +2776,8 @@ pub(crate) struct CleanupWorld<'w> {
     build_menu_ctx: ResMut<'w, BuildMenuContext>,
     ai_state: ResMut<'w, AiPlayerState>,
     town_index: ResMut<'w, crate::resources::TownIndex>,
+    position_sync: ResMut<'w, crate::resources::PositionSync>,
+    panic: ResMut<'w, crate::resources::PanicState>,
 }
 
 #[derive(SystemParam)]
@@ -2592,6 +2786,7 @@ pub(crate) struct CleanupDebug<'w> {
     health_debug: ResMut<'w, HealthDebug>,
     kill_stats: ResMut<'w, KillStats>,
     raider_state: ResMut<'w, RaiderState>,
+    raid_parties: ResMut<'w, crate::resources::RaidParties>,
     pop_stats: ResMut<'w, PopulationStats>,
     debug_flags: ResMut<'w, DebugFlags>,
 }
@@ -2607,6 +2802,17 @@ pub(crate) struct CleanupGameplay<'w> {
     follow: ResMut<'w, FollowSelected>,
     proj_slots: ResMut<'w, ProjSlotAllocator>,
     mining_policy: ResMut<'w, MiningPolicy>,
+    quick_battle: ResMut<'w, crate::systems::quick_battle::QuickBattle>,
+    spawn_overrides: ResMut<'w, crate::systems::SpawnOverrideQueue>,
+    tribute: ResMut<'w, TributeState>,
+    town_alerts: ResMut<'w, TownAlerts>,
+    last_stand: ResMut<'w, LastStandState>,
+    food_storage: ResMut<'w, crate::resources::FoodStorageState>,
+    happiness: ResMut<'w, crate::systems::TownHappiness>,
+    idle_cycle: ResMut<'w, crate::resources::IdleCycle>,
+    win_condition: ResMut<'w, crate::systems::WinCondition>,
+    tech: ResMut<'w, crate::systems::TechTree>,
+    death_knockback: ResMut<'w, crate::systems::DeathKnockback>,
 }
 
 #[derive(SystemParam)]
@@ -2620,6 +2826,12 @@ pub(crate) struct CleanupUi<'w> {
     endless: ResMut<'w, EndlessMode>,
     merchant_inv: ResMut<'w, MerchantInventory>,
     next_loot_id: ResMut<'w, NextLootItemId>,
+    npc_highlight: ResMut<'w, NpcHighlight>,
+    scripted_motion: ResMut<'w, crate::resources::ScriptedMotion>,
+    respawn_policy: ResMut<'w, crate::resources::RespawnPolicy>,
+    player_focus: ResMut<'w, PlayerFocus>,
+    combat_zones: ResMut<'w, CombatZones>,
+    game_over_outbox: ResMut<'w, crate::resources::GameOverOutbox>,
 }
 
 /// Clean up world when leaving Playing or Running (test) state.
@@ -2631,6 +2843,7 @@ pub(crate) fn game_cleanup_system(
     ghost_query: Query<Entity, With<BuildGhost>>,
     tilemap_query: Query<Entity, With<bevy::sprite_render::TilemapChunk>>,
     terrain_query: Query<Entity, With<crate::render::TerrainChunk>>,
+    loot_query: Query<Entity, With<crate::components::GroundLoot>>,
     mut world: CleanupWorld,
     mut debug: CleanupDebug,
     mut ui: CleanupUi,
@@ -2655,6 +2868,9 @@ pub(crate) fn game_cleanup_system(
     for entity in terrain_query.iter() {
         commands.entity(entity).despawn();
     }
+    for entity in loot_query.iter() {
+        commands.entity(entity).despawn();
+    }
 
     // Despawn town entities
     for &entity in world.town_index.0.values() {
@@ -2666,6 +2882,9 @@ pub(crate) fn game_cleanup_system(
     world.world_state.entity_slots.reset();
     *world.world_state.world_data = Default::default();
     *world.faction_stats = Default::default();
+    *world.faction_mvp = Default::default();
+    *world.panic = Default::default();
+    *world.world_state.no_build_zones = Default::default();
     *world.gpu_state = Default::default();
     *world.game_time = Default::default();
     *world.world_state.grid = Default::default();
@@ -2677,12 +2896,14 @@ pub(crate) fn game_cleanup_system(
     *world.npc_gpu_state = Default::default();
     *world.npc_visual_upload = Default::default();
     *world.proj_buffer_writes = Default::default();
+    world.position_sync.reset();
 
     // Reset debug/tracking resources
     *debug.combat_debug = Default::default();
     *debug.health_debug = Default::default();
     *debug.kill_stats = Default::default();
     *debug.raider_state = Default::default();
+    *debug.raid_parties = Default::default();
     *debug.pop_stats = Default::default();
     *debug.debug_flags = Default::default();
     *world.world_state.entity_map = Default::default();
@@ -2698,6 +2919,16 @@ pub(crate) fn game_cleanup_system(
     *ui.endless = Default::default();
     *ui.merchant_inv = Default::default();
     *ui.next_loot_id = Default::default();
+    // Keep the selection/hover toggles; drop per-game slots (GPU state is reset above)
+    let hl = &mut *ui.npc_highlight;
+    hl.hovered = None;
+    hl.scripted.clear();
+    hl.applied.clear();
+    *ui.scripted_motion = Default::default();
+    *ui.respawn_policy = Default::default();
+    *ui.player_focus = Default::default();
+    *ui.combat_zones = Default::default();
+    *ui.game_over_outbox = Default::default();
 
     // Reset gameplay resources
     *gameplay.auto_upgrade = Default::default();
@@ -2707,8 +2938,22 @@ pub(crate) fn game_cleanup_system(
     *gameplay.selected_npc = Default::default();
     *gameplay.selected_building = Default::default();
     *gameplay.follow = Default::default();
+    let proj_limits = gameplay.proj_slots.limits;
     *gameplay.proj_slots = Default::default();
+    gameplay.proj_slots.set_limits(proj_limits);
     *gameplay.mining_policy = Default::default();
+    *gameplay.quick_battle = Default::default();
+    gameplay.spawn_overrides.0.clear();
+    *gameplay.tribute = Default::default();
+    *gameplay.town_alerts = Default::default();
+    *gameplay.last_stand = Default::default();
+    *gameplay.food_storage = Default::default();
+    *gameplay.happiness = Default::default();
+    gameplay.tech.reset_research();
+    *gameplay.idle_cycle = Default::default();
+    *gameplay.win_condition = Default::default();
+    // Slots were reset with the pool; the knockback strength is a setting and stays
+    gameplay.death_knockback.corpses.clear();
 
     commands.remove_resource::<crate::systems::llm_player::LlmPlayerState>();
 
@@ -2719,6 +2964,82 @@ pub(crate) fn game_cleanup_system(
 mod tests {
     use super::*;
 
+    #[test]
+    fn ghost_is_red_exactly_when_placement_fails() {
+        let valid = ghost_color(true).to_srgba();
+        let blocked = ghost_color(false).to_srgba();
+        assert_eq!((valid.red, valid.green, valid.blue), (1.0, 1.0, 1.0));
+        assert!(blocked.red > blocked.green && blocked.red > blocked.blue);
+
+        let mut grid = world::WorldGrid::default();
+        grid.width = 10;
+        grid.height = 10;
+        grid.cell_size = TOWN_GRID_SPACING;
+        grid.cells = vec![
+            world::WorldCell {
+                terrain: world::Biome::Grass,
+                original_terrain: world::Biome::Grass,
+            };
+            100
+        ];
+        grid.town_owner = vec![0u16; 100];
+        let world_data = world::WorldData {
+            towns: vec![world::Town {
+                name: "Test".into(),
+                center: grid.grid_to_world(5, 5),
+                faction: 1,
+                kind: crate::constants::TownKind::Player,
+            }],
+        };
+        let mut entity_map = EntityMap::default();
+        entity_map.add_instance(crate::resources::BuildingInstance {
+            kind: BuildingKind::Farm,
+            position: grid.grid_to_world(6, 5),
+            slot: 0,
+            town_idx: 0,
+            faction: 1,
+        });
+        let no_build = crate::resources::NoBuildZones::default();
+        let cost = crate::resources::BuildingCosts::default().cost(BuildingKind::Farm);
+
+        // (4, 5) is free; (6, 5) already holds a farm
+        for (col, expect_ok) in [(4, true), (6, false)] {
+            let pos = grid.grid_to_world(col, 5);
+            let placement = world::get_build_validity(
+                BuildingKind::Farm,
+                pos,
+                0,
+                &grid,
+                &world_data,
+                &entity_map,
+                &no_build,
+                100,
+                cost,
+            );
+            let mut budget = 100;
+            let ghost = ghost_validity(
+                BuildingKind::Farm,
+                pos,
+                0,
+                &grid,
+                &world_data,
+                &entity_map,
+                &no_build,
+                &mut budget,
+                cost,
+            );
+            assert_eq!(ghost, placement);
+            assert_eq!(ghost.is_ok(), expect_ok);
+            let tint = ghost_color(ghost.is_ok()).to_srgba();
+            assert_eq!(
+                tint.green == 1.0,
+                expect_ok,
+                "white only when placement passes"
+            );
+            assert_eq!(budget, if expect_ok { 100 - cost } else { 100 });
+        }
+    }
+
     #[test]
     fn road_ui_cell_allowed_rejects_tree_like_blockers() {
         let grass = world::WorldCell {
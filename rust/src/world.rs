@@ -178,6 +178,50 @@ pub fn build_bounds(
     (min_col, max_col, min_row, max_row)
 }
 
+/// True if grid (col, row) lies inside the current build bounds of any town other than `own`.
+fn in_foreign_bounds(
+    grid: &WorldGrid,
+    towns: &[Town],
+    area_levels: &[i32],
+    own: usize,
+    col: usize,
+    row: usize,
+) -> bool {
+    towns.iter().enumerate().any(|(ti, t)| {
+        if ti == own {
+            return false;
+        }
+        let al = area_levels.get(ti).copied().unwrap_or(0);
+        let (min_c, max_c, min_r, max_r) = build_bounds(al, t.center, grid);
+        (min_c..=max_c).contains(&col) && (min_r..=max_r).contains(&row)
+    })
+}
+
+/// Build-area utilization for a town: (occupied, buildable) over the cells inside its
+/// current bounds that it can build on. Any building (roads included) counts as occupied.
+pub fn town_build_utilization(
+    grid: &WorldGrid,
+    entity_map: &EntityMap,
+    town: &Town,
+    town_idx: usize,
+    area_level: i32,
+) -> (usize, usize) {
+    let (min_c, max_c, min_r, max_r) = build_bounds(area_level, town.center, grid);
+    let (mut used, mut total) = (0, 0);
+    for row in min_r..=max_r {
+        for col in min_c..=max_c {
+            if !grid.can_town_build(col, row, town_idx as u16) {
+                continue;
+            }
+            total += 1;
+            if entity_map.get_at_grid(col as i32, row as i32).is_some() {
+                used += 1;
+            }
+        }
+    }
+    (used, total)
+}
+
 /// Check if a road can be placed at this position for a town.
 /// Roads can extend 1 tile beyond existing buildable area (chain outward).
 pub fn is_road_placeable_for_town(pos: Vec2, town_idx: usize, grid: &WorldGrid) -> bool {
@@ -210,24 +254,300 @@ pub fn empty_slots(
     grid: &WorldGrid,
     entity_map: &crate::resources::EntityMap,
 ) -> Vec<(usize, usize)> {
+    empty_slot_iter(town_idx, center, grid, entity_map).collect()
+}
+
+/// True if the town has at least one empty buildable slot (stops at the first).
+pub fn has_empty_slot(
+    town_idx: usize,
+    center: Vec2,
+    grid: &WorldGrid,
+    entity_map: &crate::resources::EntityMap,
+) -> bool {
+    empty_slot_iter(town_idx, center, grid, entity_map)
+        .next()
+        .is_some()
+}
+
+fn empty_slot_iter<'a>(
+    town_idx: usize,
+    center: Vec2,
+    grid: &'a WorldGrid,
+    entity_map: &'a crate::resources::EntityMap,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
     let ti = town_idx as u16;
     let (center_col, center_row) = grid.world_to_grid(center);
-    let mut out = Vec::new();
-    for row in 0..grid.height {
-        for col in 0..grid.width {
-            if col == center_col && row == center_row {
-                continue;
-            } // skip town center
-            if !grid.can_town_build(col, row, ti) {
-                continue;
+    (0..grid.height)
+        .flat_map(move |row| (0..grid.width).map(move |col| (col, row)))
+        .filter(move |&(col, row)| {
+            // skip town center
+            !(col == center_col && row == center_row)
+                && grid.can_town_build(col, row, ti)
+                && !entity_map.has_building_at(col as i32, row as i32)
+        })
+}
+
+// ============================================================================
+// BUILD AVAILABILITY
+// ============================================================================
+
+/// Why a town can't build a kind right now. Cell-level problems (occupied, water,
+/// foreign territory) are reported by `place_building` at placement time instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildBlock {
+    /// Not in this town's build menu (player vs raider set).
+    NotAvailable,
+    /// Requires a tech tree unlock the town hasn't bought.
+    TechLocked,
+    /// Per-town limit reached (Merchant, Casino).
+    TownLimit,
+    /// No empty town-grid cell left (town-grid kinds only).
+    NoSlots,
+    NotEnoughFood,
+}
+
+impl BuildBlock {
+    pub fn reason(self) -> &'static str {
+        match self {
+            Self::NotAvailable => "not buildable by this town",
+            Self::TechLocked => "requires tech unlock",
+            Self::TownLimit => "only one per town",
+            Self::NoSlots => "no buildable slots",
+            Self::NotEnoughFood => "not enough food",
+        }
+    }
+}
+
+/// Why a cell can't take a building. `code()` is the coarse reason shown by the ghost tooltip
+/// and `endless/build_validity`; `message()` keeps placement's detailed wording.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellBlock {
+    /// Another building covers a footprint cell.
+    Occupied,
+    /// Off the edge of the map.
+    OffMap,
+    /// Outside the town's buildable area (town grid, or road/fountain area for wilderness kinds).
+    OutsideBuildArea,
+    ForeignTerritory,
+    /// Roads must touch the town or an existing road.
+    RoadNotConnected,
+    /// Water or rock.
+    Water,
+    /// Roads can't be laid on forest.
+    RoadOnForest,
+    TooPoor,
+    NoBuildZone,
+    /// The town's own center cell (town-grid kinds).
+    IsCenter,
+}
+
+impl CellBlock {
+    /// occupied / out_of_bounds / water / too_poor / no_build_zone / is_center.
+    /// Every terrain block reads as water; every area/territory block as out_of_bounds.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Occupied => "occupied",
+            Self::OffMap
+            | Self::OutsideBuildArea
+            | Self::ForeignTerritory
+            | Self::RoadNotConnected => "out_of_bounds",
+            Self::Water | Self::RoadOnForest => "water",
+            Self::TooPoor => "too_poor",
+            Self::NoBuildZone => "no_build_zone",
+            Self::IsCenter => "is_center",
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::Occupied => "cell already has a building",
+            Self::OffMap => "cell out of bounds",
+            Self::OutsideBuildArea => "outside buildable area",
+            Self::ForeignTerritory => "cannot build in foreign territory",
+            Self::RoadNotConnected => "road must be adjacent to town or existing road",
+            Self::Water => "cannot build on water or rock",
+            Self::RoadOnForest => "cannot build road on forest",
+            Self::TooPoor => "not enough food",
+            Self::NoBuildZone => crate::resources::NoBuildZones::REASON,
+            Self::IsCenter => "cannot build on town center",
+        }
+    }
+}
+
+/// Can `town_idx` put `kind` at `pos` with `food` on hand? The one cell check shared by the
+/// build ghost, validated `place_building` (player, AI, BRP, blueprints) and
+/// `endless/build_validity`, so what the ghost shows is what placement allows. Town-level
+/// gates (menu, tech, per-town limits) stay with `BuildCheck`. Returns the snapped position.
+pub fn get_build_validity(
+    kind: BuildingKind,
+    pos: Vec2,
+    town_idx: usize,
+    grid: &WorldGrid,
+    world_data: &WorldData,
+    entity_map: &EntityMap,
+    no_build: &crate::resources::NoBuildZones,
+    food: i32,
+    cost: i32,
+) -> Result<Vec2, CellBlock> {
+    let def = crate::constants::building_def(kind);
+    let extent = Vec2::new(grid.width as f32, grid.height as f32) * grid.cell_size;
+    if pos.x < 0.0 || pos.y < 0.0 || pos.x >= extent.x || pos.y >= extent.y {
+        return Err(CellBlock::OffMap);
+    }
+    let town = town_idx as u16;
+    let (gc, gr) = grid.world_to_grid(pos);
+    let snapped = grid.footprint_center(gc, gr, def.footprint);
+    let wilderness = def.placement == crate::constants::PlacementMode::Wilderness;
+
+    // Town-grid kinds snap to the town's own buildable area, never its center
+    if !wilderness {
+        let center = world_data
+            .towns
+            .get(town_idx)
+            .map(|t| grid.world_to_grid(t.center));
+        for (cc, cr) in footprint_cells(snapped, def.footprint, grid.cell_size) {
+            let cell = (cc.max(0) as usize, cr.max(0) as usize);
+            if cc < 0 || cr < 0 || !grid.can_town_build(cell.0, cell.1, town) {
+                return Err(CellBlock::OutsideBuildArea);
             }
-            if entity_map.has_building_at(col as i32, row as i32) {
-                continue;
+            if center == Some(cell) {
+                return Err(CellBlock::IsCenter);
             }
-            out.push((col, row));
         }
     }
-    out
+
+    // Every covered cell must be free and buildable
+    for (cc, cr) in footprint_cells(snapped, def.footprint, grid.cell_size) {
+        let (cc, cr) = (cc as usize, cr as usize);
+        let cell = grid.cell(cc, cr).ok_or(CellBlock::OffMap)?;
+        if entity_map.has_building_at(cc as i32, cr as i32) {
+            return Err(CellBlock::Occupied);
+        }
+        if matches!(cell.terrain, Biome::Water | Biome::Rock) {
+            return Err(CellBlock::Water);
+        }
+        if kind.is_road() && cell.terrain == Biome::Forest {
+            return Err(CellBlock::RoadOnForest);
+        }
+        if grid.is_foreign_territory(cc, cr, town) {
+            return Err(CellBlock::ForeignTerritory);
+        }
+        if no_build.contains(grid.grid_to_world(cc, cr)) {
+            return Err(CellBlock::NoBuildZone);
+        }
+        // Wilderness buildings must be within road or fountain buildable area
+        if wilderness && !kind.is_road() && !grid.can_town_build(cc, cr, town) {
+            return Err(CellBlock::OutsideBuildArea);
+        }
+    }
+    if wilderness && kind.is_road() && !is_road_placeable_for_town(snapped, town_idx, grid) {
+        return Err(CellBlock::RoadNotConnected);
+    }
+    if food < cost {
+        return Err(CellBlock::TooPoor);
+    }
+    Ok(snapped)
+}
+
+/// Max instances of `kind` a single town may own.
+pub fn town_build_limit(kind: BuildingKind) -> Option<usize> {
+    match kind {
+        BuildingKind::Merchant | BuildingKind::Casino => Some(1),
+        _ => None,
+    }
+}
+
+/// Town tech upgrade that gates `kind` in the player build menu.
+pub fn required_unlock(kind: BuildingKind) -> Option<crate::constants::UpgradeStatKind> {
+    use crate::constants::UpgradeStatKind as USK;
+    match kind {
+        BuildingKind::StoneRoad => Some(USK::UnlockStoneRoad),
+        BuildingKind::MetalRoad => Some(USK::UnlockMetalRoad),
+        _ => None,
+    }
+}
+
+/// Town-level build availability, gathered once and checked per kind. Single source of truth
+/// for the build menu, player/BRP placement, and `endless/buildable_status`.
+pub struct BuildCheck<'a> {
+    pub town_idx: usize,
+    pub is_raider: bool,
+    pub food: i32,
+    pub upgrade_levels: &'a [u8],
+    /// Town has at least one empty town-grid cell.
+    pub has_slots: bool,
+    /// Kinds still waiting on a research tech (`TechTree::locked_buildings`).
+    pub tech_locked: Vec<BuildingKind>,
+    pub costs: &'a crate::resources::BuildingCosts,
+}
+
+impl<'a> BuildCheck<'a> {
+    pub fn new(
+        town_idx: usize,
+        town: &Town,
+        food: i32,
+        upgrade_levels: &'a [u8],
+        grid: &WorldGrid,
+        entity_map: &crate::resources::EntityMap,
+        costs: &'a crate::resources::BuildingCosts,
+    ) -> Self {
+        Self {
+            town_idx,
+            is_raider: town.is_raider(),
+            food,
+            upgrade_levels,
+            has_slots: has_empty_slot(town_idx, town.center, grid, entity_map),
+            tech_locked: Vec::new(),
+            costs,
+        }
+    }
+
+    pub fn with_tech_locks(mut self, tech_locked: Vec<BuildingKind>) -> Self {
+        self.tech_locked = tech_locked;
+        self
+    }
+
+    pub fn check(
+        &self,
+        kind: BuildingKind,
+        entity_map: &crate::resources::EntityMap,
+    ) -> Result<(), BuildBlock> {
+        let def = crate::constants::building_def(kind);
+        let in_menu = if self.is_raider {
+            def.raider_buildable
+        } else {
+            def.player_buildable
+        };
+        if !in_menu {
+            return Err(BuildBlock::NotAvailable);
+        }
+        if !self.is_raider {
+            if let Some(unlock) = required_unlock(kind) {
+                let unlocked = crate::systems::stats::UPGRADES
+                    .index_map
+                    .get(&("Town", unlock))
+                    .is_some_and(|&idx| self.upgrade_levels.get(idx).copied().unwrap_or(0) >= 1);
+                if !unlocked {
+                    return Err(BuildBlock::TechLocked);
+                }
+            }
+            if self.tech_locked.contains(&kind) {
+                return Err(BuildBlock::TechLocked);
+            }
+        }
+        if town_build_limit(kind)
+            .is_some_and(|limit| entity_map.count_for_town(kind, self.town_idx as u32) >= limit)
+        {
+            return Err(BuildBlock::TownLimit);
+        }
+        if def.placement == crate::constants::PlacementMode::TownGrid && !self.has_slots {
+            return Err(BuildBlock::NoSlots);
+        }
+        if self.food < self.costs.cost(def.kind) {
+            return Err(BuildBlock::NotEnoughFood);
+        }
+        Ok(())
+    }
 }
 
 /// Find interior roads for a town — roads whose build-area contribution is fully redundant.
@@ -605,7 +925,9 @@ fn push_building_gpu_updates(
     let half = if kind.is_road() {
         [0.0, 0.0]
     } else {
-        crate::constants::BUILDING_HITBOX_HALF
+        let (fw, fh) = crate::constants::building_def(kind).footprint;
+        let [hw, hh] = crate::constants::BUILDING_HITBOX_HALF;
+        [hw * fw.max(1) as f32, hh * fh.max(1) as f32]
     };
     gpu_updates.write(GpuUpdateMsg(GpuUpdate::SetHalfSize {
         idx: slot,
@@ -625,6 +947,7 @@ fn push_building_gpu_updates(
 pub struct BuildContext<'a> {
     pub grid: &'a mut WorldGrid,
     pub world_data: &'a WorldData,
+    pub no_build: &'a crate::resources::NoBuildZones,
     pub food: &'a mut i32,
     pub cost: i32,
 }
@@ -663,41 +986,29 @@ pub fn place_building(
 
     // Runtime validation + cost deduction (only when BuildContext provided)
     let (snapped, gc, gr) = if let Some(ref mut ctx) = ctx {
+        let snapped = get_build_validity(
+            kind,
+            pos,
+            town_idx as usize,
+            ctx.grid,
+            ctx.world_data,
+            entity_map,
+            ctx.no_build,
+            *ctx.food,
+            ctx.cost,
+        )
+        .map_err(CellBlock::message)?;
         let (gc, gr) = ctx.grid.world_to_grid(pos);
-        let snapped = ctx.grid.grid_to_world(gc, gr);
-
-        let cell = ctx.grid.cell(gc, gr).ok_or("cell out of bounds")?;
-        if entity_map.has_building_at(gc as i32, gr as i32) {
-            return Err("cell already has a building");
-        }
-        if matches!(cell.terrain, Biome::Water | Biome::Rock) {
-            return Err("cannot build on water or rock");
-        }
-        if kind.is_road() && cell.terrain == Biome::Forest {
-            return Err("cannot build road on forest");
-        }
-        if ctx.grid.is_foreign_territory(gc, gr, town_idx as u16) {
-            return Err("cannot build in foreign territory");
-        }
-
-        // Wilderness buildings must be within road or fountain buildable area
-        if def.placement == crate::constants::PlacementMode::Wilderness {
-            if kind.is_road() {
-                if !is_road_placeable_for_town(snapped, town_idx as usize, ctx.grid) {
-                    return Err("road must be adjacent to town or existing road");
-                }
-            } else if !ctx.grid.can_town_build(gc, gr, town_idx as u16) {
-                return Err("outside buildable area");
-            }
-        }
-
-        if *ctx.food < ctx.cost {
-            return Err("not enough food");
-        }
         *ctx.food -= ctx.cost;
 
         (snapped, gc, gr)
     } else {
+        // World-gen and load skip the rules, but never stack a footprint on another building
+        if footprint_cells(pos, def.footprint, TOWN_GRID_SPACING)
+            .any(|(gc, gr)| entity_map.has_building_at(gc, gr))
+        {
+            return Err(CellBlock::Occupied.message());
+        }
         (pos, 0, 0) // gc/gr unused when no ctx
     };
 
@@ -923,14 +1234,23 @@ pub fn setup_world(
         &[],
     );
 
+    // Initial NPCs spawn from their homes on the first hour tick and pick these up there
+    commands.insert_resource(StartingLoadouts::roll(
+        config,
+        &world_data.towns,
+        config.seed.unwrap_or_else(rand::random),
+    ));
+
     create_ai_players(world_data, faction_list)
 }
 
 /// Expand one town's buildable area by one ring and convert new ring terrain to Dirt.
+/// Water cells and cells inside another town's bounds are left alone (not unlocked).
+/// `area_levels` are every town's current levels (this town's entry is ignored).
 pub fn expand_town_build_area(
     grid: &mut WorldGrid,
     towns: &[Town],
-    _entity_map: &EntityMap,
+    area_levels: &[i32],
     town_idx: usize,
     area_level: &mut i32,
 ) -> Result<(), &'static str> {
@@ -947,11 +1267,13 @@ pub fn expand_town_build_area(
         for col in new_min_c..=new_max_c {
             let is_old =
                 row >= old_min_r && row <= old_max_r && col >= old_min_c && col <= old_max_c;
-            if is_old {
+            if is_old || in_foreign_bounds(grid, towns, area_levels, town_idx, col, row) {
                 continue;
             }
             if let Some(cell) = grid.cell_mut(col, row) {
-                cell.terrain = Biome::Dirt;
+                if cell.terrain != Biome::Water {
+                    cell.terrain = Biome::Dirt;
+                }
             }
         }
     }
@@ -1056,6 +1378,20 @@ pub fn pos_to_key(pos: Vec2) -> (i32, i32) {
     (pos.x.round() as i32, pos.y.round() as i32)
 }
 
+/// Grid cells (col, row) covered by a `(w, h)` footprint centered on `pos`.
+/// A 1x1 footprint yields the single cell containing `pos`.
+pub fn footprint_cells(
+    pos: Vec2,
+    footprint: (u8, u8),
+    cell_size: f32,
+) -> impl Iterator<Item = (i32, i32)> {
+    let w = footprint.0.max(1) as i32;
+    let h = footprint.1.max(1) as i32;
+    let gc0 = ((pos.x - (w - 1) as f32 * cell_size * 0.5) / cell_size).floor() as i32;
+    let gr0 = ((pos.y - (h - 1) as f32 * cell_size * 0.5) / cell_size).floor() as i32;
+    (gr0..gr0 + h).flat_map(move |gr| (gc0..gc0 + w).map(move |gc| (gc, gr)))
+}
+
 /// Any building with a position and town affiliation. Used by generic find functions.
 pub trait Worksite {
     fn position(&self) -> Vec2;
@@ -1186,6 +1522,7 @@ pub enum BuildingKind {
     RockNode,
     LumberMill,
     Quarry,
+    Granary,
 }
 
 impl BuildingKind {
@@ -1270,12 +1607,10 @@ impl Biome {
     }
 }
 
-/// Fast deterministic hash for tile variant selection.
-/// Returns a value in `0..variants` that looks random but is stable for a given cell.
-/// Uses splitmix64 finalizer for excellent distribution on sequential inputs.
+/// splitmix64 finalizer: excellent distribution on sequential inputs. The one mixer behind
+/// tile variants, combat rolls and loot rolls.
 #[inline]
-fn splitmix64_hash(value: usize) -> u64 {
-    let mut h = value as u64;
+pub fn splitmix64(mut h: u64) -> u64 {
     h ^= h >> 30;
     h = h.wrapping_mul(0xbf58476d1ce4e5b9);
     h ^= h >> 27;
@@ -1284,9 +1619,17 @@ fn splitmix64_hash(value: usize) -> u64 {
     h
 }
 
+/// `splitmix64(h)` mapped to [0, 1) from its top 24 bits.
+#[inline]
+pub fn splitmix_unit(h: u64) -> f32 {
+    (splitmix64(h) >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Fast deterministic hash for tile variant selection.
+/// Returns a value in `0..variants` that looks random but is stable for a given cell.
 #[inline]
 fn tile_hash(cell_index: usize, variants: usize) -> usize {
-    splitmix64_hash(cell_index) as usize % variants
+    splitmix64(cell_index as u64) as usize % variants
 }
 
 // TileSpec is now in constants.rs (part of BUILDING_REGISTRY)
@@ -1777,6 +2120,17 @@ impl WorldGrid {
         )
     }
 
+    /// World position of a footprint's center when its bottom-left cell is (col, row).
+    pub fn footprint_center(&self, col: usize, row: usize, footprint: (u8, u8)) -> Vec2 {
+        let w = footprint.0.max(1) as f32;
+        let h = footprint.1.max(1) as f32;
+        self.grid_to_world(col, row)
+            + Vec2::new(
+                (w - 1.0) * self.cell_size * 0.5,
+                (h - 1.0) * self.cell_size * 0.5,
+            )
+    }
+
     // ── Pathfinding cost grid ────────────────────────────────────────
 
     /// Build terrain base costs. Called once on world init/load.
@@ -1836,15 +2190,20 @@ impl WorldGrid {
         kind: BuildingKind,
         cost: u16,
     ) {
+        let footprint = crate::constants::building_def(kind).footprint;
         for inst in entity_map.iter_kind(kind) {
-            let (gc, gr) = self.world_to_grid(inst.position);
-            let idx = gr * self.width + gc;
-            // Don't override water with road bonus (water is always impassable)
-            if cost > 0 && self.pathfind_costs[idx] == 0 {
-                continue;
+            for (gc, gr) in footprint_cells(inst.position, footprint, self.cell_size) {
+                if gc < 0 || gr < 0 || gc as usize >= self.width || gr as usize >= self.height {
+                    continue;
+                }
+                let idx = gr as usize * self.width + gc as usize;
+                // Don't override water with road bonus (water is always impassable)
+                if cost > 0 && self.pathfind_costs[idx] == 0 {
+                    continue;
+                }
+                self.pathfind_costs[idx] = cost;
+                self.building_cost_cells.push(idx);
             }
-            self.pathfind_costs[idx] = cost;
-            self.building_cost_cells.push(idx);
         }
     }
 
@@ -1915,7 +2274,9 @@ impl WorldGrid {
             return false;
         }
         let idx = row * self.width + col;
-        let owner = self.town_owner[idx];
+        let Some(&owner) = self.town_owner.get(idx) else {
+            return false;
+        };
         if owner == u16::MAX {
             return false;
         }
@@ -1942,7 +2303,13 @@ impl WorldGrid {
             return;
         }
 
-        // 1. Stamp base area for each town
+        // 1. Stamp base area for each town. Expansion rings (beyond the level-0 area)
+        // never reach into another town's bounds.
+        let bounds: Vec<_> = towns
+            .iter()
+            .enumerate()
+            .map(|(ti, t)| build_bounds(area_levels.get(ti).copied().unwrap_or(0), t.center, self))
+            .collect();
         for (ti, town) in towns.iter().enumerate() {
             let ti16 = ti as u16;
             let (center_col, center_row) = self.world_to_grid(town.center);
@@ -1971,7 +2338,15 @@ impl WorldGrid {
                     if gc >= w {
                         continue;
                     }
-                    self.add_town_buildable(gc, gr, ti16);
+                    let in_base = (BASE_GRID_MIN..=BASE_GRID_MAX).contains(&r)
+                        && (BASE_GRID_MIN..=BASE_GRID_MAX).contains(&c);
+                    let foreign = !in_base
+                        && bounds.iter().enumerate().any(|(oi, &(c0, c1, r0, r1))| {
+                            oi != ti && (c0..=c1).contains(&gc) && (r0..=r1).contains(&gr)
+                        });
+                    if !foreign {
+                        self.add_town_buildable(gc, gr, ti16);
+                    }
                 }
             }
         }
@@ -2024,18 +2399,120 @@ pub(crate) fn terrain_base_cost(biome: Biome) -> u16 {
 // WORLD GEN CONFIG
 // ============================================================================
 
-/// World generation algorithm style.
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+/// World generation algorithm style. Each style is a `TerrainGenerator`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum WorldGenStyle {
     Classic,
     #[default]
     Continents,
+    /// Flat grass, no water or obstacles (quick battles, tests).
+    Arena,
+}
+
+impl WorldGenStyle {
+    pub fn generator(self) -> &'static dyn TerrainGenerator {
+        match self {
+            Self::Classic => &ClassicTerrain,
+            Self::Continents => &ContinentsTerrain,
+            Self::Arena => &ArenaTerrain,
+        }
+    }
+}
+
+/// Terrain step of world generation. A generator fills every cell's biome before towns are
+/// placed, then proposes town positions; `generate_world` keeps only candidates that pass
+/// `valid_town_site`, so water and off-grid rejection is the same for every generator.
+/// Same seed, same terrain.
+pub trait TerrainGenerator: Sync {
+    fn name(&self) -> &'static str;
+
+    /// Set `terrain` and `original_terrain` for every cell.
+    fn fill(&self, grid: &mut WorldGrid, seed: u32);
+
+    /// One town position to try. Default: uniform inside the world margin.
+    fn town_candidate(
+        &self,
+        _grid: &WorldGrid,
+        config: &WorldGenConfig,
+        rng: &mut rand::rngs::StdRng,
+    ) -> Vec2 {
+        use rand::Rng;
+        Vec2::new(
+            rng.random_range(config.world_margin..config.world_width - config.world_margin),
+            rng.random_range(config.world_margin..config.world_height - config.world_margin),
+        )
+    }
+
+    /// Candidates drawn per town kind (and for gold mines) before giving up.
+    fn placement_attempts(&self) -> usize {
+        2000
+    }
+}
+
+/// Shared placement check for every generator: on the grid and not water.
+pub fn valid_town_site(grid: &WorldGrid, pos: Vec2) -> bool {
+    let extent = Vec2::new(grid.width as f32, grid.height as f32) * grid.cell_size;
+    if pos.x < 0.0 || pos.y < 0.0 || pos.x >= extent.x || pos.y >= extent.y {
+        return false;
+    }
+    let (gc, gr) = grid.world_to_grid(pos);
+    grid.cell(gc, gr).is_some_and(|c| c.terrain != Biome::Water)
+}
+
+/// Single-octave simplex noise: scattered lakes, grass, forest and rock everywhere.
+pub struct ClassicTerrain;
+
+impl TerrainGenerator for ClassicTerrain {
+    fn name(&self) -> &'static str {
+        "classic"
+    }
+
+    fn fill(&self, grid: &mut WorldGrid, seed: u32) {
+        generate_terrain(grid, seed);
+    }
+}
+
+/// Land masses ringed by ocean (see `generate_terrain_continents`).
+pub struct ContinentsTerrain;
+
+impl TerrainGenerator for ContinentsTerrain {
+    fn name(&self) -> &'static str {
+        "continents"
+    }
+
+    fn fill(&self, grid: &mut WorldGrid, seed: u32) {
+        generate_terrain_continents(grid, seed);
+    }
+
+    // Many candidates land in the ocean
+    fn placement_attempts(&self) -> usize {
+        5000
+    }
+}
+
+/// All grass: an empty field for battles and tests.
+pub struct ArenaTerrain;
+
+impl TerrainGenerator for ArenaTerrain {
+    fn name(&self) -> &'static str {
+        "arena"
+    }
+
+    fn fill(&self, grid: &mut WorldGrid, _seed: u32) {
+        for cell in &mut grid.cells {
+            cell.terrain = Biome::Grass;
+            cell.original_terrain = Biome::Grass;
+        }
+    }
 }
 
 /// Configuration for procedural world generation.
 #[derive(Resource)]
 pub struct WorldGenConfig {
-    pub gen_style: WorldGenStyle,
+    pub generator: WorldGenStyle,
+    /// World seed for terrain, settlements, starting loadouts and combat rolls. None = roll a
+    /// fresh one; game start fills in the seed it rolled.
+    pub seed: Option<u64>,
     pub world_width: f32,
     pub world_height: f32,
     pub world_margin: f32,
@@ -2054,12 +2531,16 @@ pub struct WorldGenConfig {
     /// Fraction of Rock cells that get a RockNode (0.0-1.0).
     pub rock_density: f32,
     pub town_names: Vec<String>,
+    /// Starting quality of the initial NPCs per town kind. Kinds without an entry keep the
+    /// neutral defaults (level 0, slot-seeded personality, no equipment).
+    pub faction_loadouts: BTreeMap<TownKind, FactionLoadout>,
 }
 
 impl Default for WorldGenConfig {
     fn default() -> Self {
         Self {
-            gen_style: WorldGenStyle::Classic,
+            generator: WorldGenStyle::Classic,
+            seed: None,
             world_width: 16000.0,
             world_height: 16000.0,
             world_margin: 800.0,
@@ -2094,6 +2575,7 @@ impl Default for WorldGenConfig {
                 "Key West".into(),
                 "Fort Myers".into(),
             ],
+            faction_loadouts: BTreeMap::new(),
         }
     }
 }
@@ -2109,6 +2591,140 @@ impl WorldGenConfig {
     }
 }
 
+/// One trait a loadout can roll: each unit gets it with `chance`, magnitude uniform in
+/// `min..=max` (negative = the trait's low pole). Personalities hold at most 2 traits, so
+/// later entries only apply while a slot is free.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoadoutTrait {
+    pub kind: crate::components::TraitKind,
+    pub chance: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// One starting item: fixed kind and rarity, stat bonus at the middle of the rarity range.
+/// Resource kinds (Food/Gold) are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoadoutItem {
+    pub kind: crate::constants::ItemKind,
+    pub rarity: crate::constants::Rarity,
+}
+
+/// Starting loadout for one town kind's initial NPCs ("veteran raider band" vs "green
+/// militia"). `units` picks how many homes per job get it, clamped to `npc_counts`; the rest
+/// spawn with neutral defaults.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FactionLoadout {
+    pub units: BTreeMap<Job, usize>,
+    pub level: i32,
+    pub traits: Vec<LoadoutTrait>,
+    pub equipment: Vec<LoadoutItem>,
+}
+
+/// One rolled starting unit, waiting for its home's first spawn.
+#[derive(Clone, Debug)]
+pub struct LoadoutUnit {
+    pub level: i32,
+    pub personality: crate::components::Personality,
+    pub equipment: Vec<LoadoutItem>,
+}
+
+/// Rolled loadouts per (town, job), built by `setup_world` and consumed one per spawn by
+/// `spawner_respawn_system` (via `SpawnOverrideQueue`). Empty once the initial wave is out.
+#[derive(Resource, Default)]
+pub struct StartingLoadouts(pub HashMap<(usize, Job), Vec<LoadoutUnit>>);
+
+impl StartingLoadouts {
+    /// Roll every town's loadout units. Trait rolls come from `seed` in town/job order, so a
+    /// seeded world always starts with the same personalities.
+    pub fn roll(config: &WorldGenConfig, towns: &[Town], seed: u64) -> Self {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut plan = HashMap::new();
+        for (town_idx, town) in towns.iter().enumerate() {
+            let Some(loadout) = config.faction_loadouts.get(&town.kind) else {
+                continue;
+            };
+            for (&job, &requested) in &loadout.units {
+                let count = requested.min(config.npc_counts.get(&job).copied().unwrap_or(0));
+                let units: Vec<LoadoutUnit> = (0..count)
+                    .map(|_| {
+                        let mut traits = Vec::new();
+                        for t in &loadout.traits {
+                            if traits.len() < 2 && rng.random::<f32>() < t.chance {
+                                let (lo, hi) = (t.min.min(t.max), t.min.max(t.max));
+                                traits.push(crate::components::TraitInstance {
+                                    kind: t.kind,
+                                    magnitude: rng.random_range(lo..=hi).clamp(-1.5, 1.5),
+                                });
+                            }
+                        }
+                        LoadoutUnit {
+                            level: loadout.level.max(0),
+                            personality: crate::components::Personality {
+                                trait1: traits.first().copied(),
+                                trait2: traits.get(1).copied(),
+                            },
+                            equipment: loadout.equipment.clone(),
+                        }
+                    })
+                    .collect();
+                if !units.is_empty() {
+                    plan.insert((town_idx, job), units);
+                }
+            }
+        }
+        Self(plan)
+    }
+
+    /// Spawn overrides for the next loadout unit of `job` in `town_idx`, if any are left.
+    pub fn take(
+        &mut self,
+        town_idx: usize,
+        job: Job,
+        next_loot_id: &mut crate::resources::NextLootItemId,
+    ) -> Option<crate::systems::spawn::NpcSpawnOverrides> {
+        let units = self.0.get_mut(&(town_idx, job))?;
+        let unit = units.pop()?;
+        if units.is_empty() {
+            self.0.remove(&(town_idx, job));
+        }
+        let mut equipment = crate::components::NpcEquipment::default();
+        for item in unit.equipment {
+            if crate::constants::item_def(item.kind).stackable {
+                continue; // Food/Gold have no equipment slot
+            }
+            *equipment.slot_mut(item.kind) = Some(loadout_item(item, next_loot_id.alloc()));
+        }
+        Some(crate::systems::spawn::NpcSpawnOverrides {
+            personality: Some(unit.personality),
+            level: Some(unit.level),
+            xp: Some(100 * unit.level * unit.level),
+            equipment,
+            ..Default::default()
+        })
+    }
+}
+
+/// A fixed starting item (first sprite and name of the kind, mid-range stat bonus).
+fn loadout_item(item: LoadoutItem, id: u64) -> crate::constants::LootItem {
+    let def = crate::constants::item_def(item.kind);
+    let (min_stat, max_stat) = item.rarity.stat_range();
+    crate::constants::LootItem {
+        id,
+        kind: item.kind,
+        rarity: item.rarity,
+        stat_bonus: (min_stat + max_stat) * 0.5,
+        sprite: def.sprites.first().copied().unwrap_or((-1.0, 0.0)),
+        name: format!(
+            "{} {}",
+            item.rarity.label(),
+            def.names.first().copied().unwrap_or(def.label)
+        ),
+    }
+}
+
 fn spawn_resource_nodes(
     config: &WorldGenConfig,
     grid: &WorldGrid,
@@ -2146,7 +2762,7 @@ fn spawn_resource_nodes(
             }
 
             let threshold = (density as f64 * u64::MAX as f64) as u64;
-            if splitmix64_hash(idx) > threshold {
+            if splitmix64(idx as u64) > threshold {
                 continue;
             }
             if entity_map.has_building_at(col as i32, row as i32) {
@@ -2208,8 +2824,10 @@ pub fn generate_world(
     gpu_updates: &mut MessageWriter<GpuUpdateMsg>,
 ) -> Vec<i32> {
     use crate::resources::{FactionData, FactionKind};
-    use rand::Rng;
-    let mut rng = rand::rng();
+    use rand::{Rng, SeedableRng};
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let generator = config.generator.generator();
     let mut area_levels: Vec<i32> = Vec::new();
 
     // Faction 0 = Neutral (gold mines, world objects)
@@ -2218,6 +2836,7 @@ pub fn generate_world(
         kind: FactionKind::Neutral,
         name: "Neutral".into(),
         towns: Vec::new(),
+        stance: None,
     });
 
     // Step 1: Initialize grid
@@ -2236,17 +2855,12 @@ pub fn generate_world(
     }
     let mut name_idx = 0;
 
-    let is_continents = config.gen_style == WorldGenStyle::Continents;
-
-    // Continents: generate terrain first so we can reject Water positions
-    if is_continents {
-        generate_terrain_continents(grid);
-    }
+    // Step 1b: Terrain first, so placement can reject Water positions
+    generator.fill(grid, rng.random());
 
     // All settlement positions for min_distance checks
     let mut all_positions: Vec<Vec2> = Vec::new();
-    // Continents needs more attempts since many positions land in ocean
-    let max_attempts = if is_continents { 5000 } else { 2000 };
+    let max_attempts = generator.placement_attempts();
     // Step 2: Place towns — single loop driven by TOWN_REGISTRY
     for town_def in TOWN_REGISTRY {
         let count = config.count_for(town_def.kind);
@@ -2254,15 +2868,9 @@ pub fn generate_world(
         let mut attempts = 0;
         while positions.len() < count && attempts < max_attempts {
             attempts += 1;
-            let x = rng.random_range(config.world_margin..config.world_width - config.world_margin);
-            let y =
-                rng.random_range(config.world_margin..config.world_height - config.world_margin);
-            let pos = Vec2::new(x, y);
-            if is_continents {
-                let (gc, gr) = grid.world_to_grid(pos);
-                if grid.cell(gc, gr).is_some_and(|c| c.terrain == Biome::Water) {
-                    continue;
-                }
+            let pos = generator.town_candidate(grid, config, &mut rng);
+            if !valid_town_site(grid, pos) {
+                continue;
             }
             if all_positions
                 .iter()
@@ -2301,6 +2909,7 @@ pub fn generate_world(
                 kind: town_def.kind.faction_kind(),
                 name: name.clone(),
                 towns: vec![town_data_idx],
+                stance: None,
             });
             world_data.towns.push(Town {
                 name,
@@ -2326,13 +2935,8 @@ pub fn generate_world(
         }
     }
 
-    // Step 3: Generate terrain
-    if is_continents {
-        // Terrain already generated; stamp dirt clearings around settlements
-        stamp_dirt(grid, &all_positions);
-    } else {
-        generate_terrain(grid, &all_positions, &[]);
-    }
+    // Step 3: Dirt clearings around settlements
+    stamp_dirt(grid, &all_positions);
 
     // Step 4: Place gold mines in wilderness between settlements
     let total_mines = config.gold_mines_per_town * all_positions.len();
@@ -2343,12 +2947,8 @@ pub fn generate_world(
         let x = rng.random_range(config.world_margin..config.world_width - config.world_margin);
         let y = rng.random_range(config.world_margin..config.world_height - config.world_margin);
         let pos = Vec2::new(x, y);
-        // Not on water
-        if is_continents {
-            let (gc, gr) = grid.world_to_grid(pos);
-            if grid.cell(gc, gr).is_some_and(|c| c.terrain == Biome::Water) {
-                continue;
-            }
+        if !valid_town_site(grid, pos) {
+            continue;
         }
         // Min distance from settlements
         if all_positions
@@ -2392,18 +2992,15 @@ pub fn generate_world(
 
     let total_towns = world_data.towns.len();
     info!(
-        "generate_world: {} towns, {} gold mines, {} trees, {} rocks, grid {}x{} ({})",
+        "generate_world: {} towns, {} gold mines, {} trees, {} rocks, grid {}x{} ({}, seed {})",
         total_towns,
         mine_positions.len(),
         tree_count,
         rock_count,
         w,
         h,
-        if is_continents {
-            "continents"
-        } else {
-            "classic"
-        },
+        generator.name(),
+        seed,
     );
     area_levels
 }
@@ -2620,7 +3217,9 @@ pub fn place_buildings(
 
 /// Generate `count` grid positions in a spiral pattern outward from (0,0), skipping occupied cells.
 fn spiral_slots(occupied: &HashSet<(i32, i32)>, count: usize) -> Vec<(i32, i32)> {
-    let mut result = Vec::with_capacity(count);
+    // The rings hold at most (2 * MAX_GRID_EXTENT + 1)^2 cells, so cap the reservation there
+    let side = (2 * MAX_GRID_EXTENT + 1) as usize;
+    let mut result = Vec::with_capacity(count.min(side * side));
     // Walk rings outward: ring 1 = distance 1 from center, ring 2 = distance 2, etc.
     for ring in 1..=MAX_GRID_EXTENT {
         if result.len() >= count {
@@ -2670,53 +3269,33 @@ fn spiral_slots(occupied: &HashSet<(i32, i32)>, count: usize) -> Vec<(i32, i32)>
     result
 }
 
-/// Fill grid terrain using simplex noise, with Dirt override near towns.
-fn generate_terrain(grid: &mut WorldGrid, town_positions: &[Vec2], raider_positions: &[Vec2]) {
+/// Fill grid terrain using single-octave simplex noise.
+fn generate_terrain(grid: &mut WorldGrid, seed: u32) {
     use noise::{NoiseFn, Simplex};
 
-    let noise = Simplex::new(rand::random::<u32>());
+    let noise = Simplex::new(seed);
     let frequency = 0.0015;
-    let town_clear_radius = 6.0 * grid.cell_size; // ~192px
-    let raider_clear_radius = 5.0 * grid.cell_size; // ~160px
 
     for row in 0..grid.height {
         for col in 0..grid.width {
             let world_pos = grid.grid_to_world(col, row);
-
-            // Check proximity to towns → Dirt
-            let near_town = town_positions
-                .iter()
-                .any(|tc| world_pos.distance(*tc) < town_clear_radius);
-            let near_raider = raider_positions
-                .iter()
-                .any(|cp| world_pos.distance(*cp) < raider_clear_radius);
-
-            let natural = {
-                let n = noise.get([
-                    world_pos.x as f64 * frequency,
-                    world_pos.y as f64 * frequency,
-                ]);
-                if n < -0.3 {
-                    Biome::Water
-                } else if n < 0.1 {
-                    Biome::Grass
-                } else if n < 0.4 {
-                    Biome::Forest
-                } else {
-                    Biome::Rock
-                }
-            };
-
-            let biome = if near_town || near_raider {
-                Biome::Dirt
+            let n = noise.get([
+                world_pos.x as f64 * frequency,
+                world_pos.y as f64 * frequency,
+            ]);
+            let biome = if n < -0.3 {
+                Biome::Water
+            } else if n < 0.1 {
+                Biome::Grass
+            } else if n < 0.4 {
+                Biome::Forest
             } else {
-                natural
+                Biome::Rock
             };
 
-            let idx = row * grid.width + col;
-            let cell = &mut grid.cells[idx];
+            let cell = &mut grid.cells[row * grid.width + col];
             cell.terrain = biome;
-            cell.original_terrain = natural;
+            cell.original_terrain = biome;
         }
     }
 }
@@ -2791,11 +3370,11 @@ pub fn clear_town_roads_and_dirt(
 /// Based on Red Blob Games "Making maps with noise" approach:
 /// - 3-octave fBm for elevation with square-bump edge falloff
 /// - Separate moisture noise for biome selection within land
-fn generate_terrain_continents(grid: &mut WorldGrid) {
+fn generate_terrain_continents(grid: &mut WorldGrid, seed: u32) {
     use noise::{NoiseFn, Simplex};
 
-    let elevation_noise = Simplex::new(rand::random::<u32>());
-    let moisture_noise = Simplex::new(rand::random::<u32>());
+    let elevation_noise = Simplex::new(seed);
+    let moisture_noise = Simplex::new(seed.wrapping_add(1));
 
     let world_w = grid.width as f64 * grid.cell_size as f64;
     let world_h = grid.height as f64 * grid.cell_size as f64;
@@ -2846,6 +3425,69 @@ mod tests {
     use super::*;
     use bevy::ecs::system::RunSystemOnce;
 
+    #[test]
+    fn splitmix_matches_reference_values() {
+        assert_eq!(splitmix64(1), 0x5692_161d_100b_05e5);
+        assert_eq!(splitmix64(12345), 0xf36c_f116_4265_dd51);
+        assert_eq!(splitmix_unit(1), 0.338_166_6);
+        assert!(
+            (0..1000)
+                .map(splitmix_unit)
+                .all(|u| (0.0..1.0).contains(&u))
+        );
+    }
+
+    #[test]
+    fn expansion_skips_water_and_neighbor_grids() {
+        let mut grid = WorldGrid::default();
+        grid.width = 40;
+        grid.height = 40;
+        grid.cell_size = 32.0;
+        grid.cells = vec![
+            WorldCell {
+                terrain: Biome::Grass,
+                original_terrain: Biome::Grass
+            };
+            1600
+        ];
+        grid.init_town_buildable();
+        grid.cells[20 * 40 + 5].terrain = Biome::Water;
+        let town = |col, row| Town {
+            name: "T".into(),
+            center: grid.grid_to_world(col, row),
+            faction: 0,
+            kind: crate::constants::TownKind::Player,
+        };
+        // A at col 10, B at col 20 — base areas (cols 6..=13 and 16..=23) don't touch
+        let towns = vec![town(10, 20), town(20, 20)];
+        let entity_map = EntityMap::default();
+
+        let mut levels = vec![0, 0];
+        for _ in 0..3 {
+            let mut al = levels[0];
+            expand_town_build_area(&mut grid, &towns, &levels, 0, &mut al).unwrap();
+            levels[0] = al;
+        }
+        grid.sync_town_buildability(&towns, &levels, &entity_map);
+        assert_eq!(levels[0], 3);
+
+        // New ring cell in the clear: unlocked and cleared to dirt
+        assert!(grid.can_town_build(14, 20, 0));
+        assert_eq!(grid.cell(14, 20).unwrap().terrain, Biome::Dirt);
+        // Water stays water and unbuildable
+        assert_eq!(grid.cell(5, 20).unwrap().terrain, Biome::Water);
+        assert!(!grid.can_town_build(5, 20, 0));
+        // Col 16 is inside B's grid: A's ring stops short, B keeps it untouched
+        assert!(!grid.can_town_build(16, 20, 0));
+        assert!(grid.can_town_build(16, 20, 1));
+        assert_eq!(grid.cell(16, 20).unwrap().terrain, Biome::Grass);
+
+        // Empty town: nothing used; buildable excludes the water and B's cells
+        let (used, total) = town_build_utilization(&grid, &entity_map, &towns[0], 0, 3);
+        assert_eq!(used, 0);
+        assert_eq!(total, 14 * 14 - 1 - 8);
+    }
+
     #[test]
     fn road_blocked_on_forest_biome() {
         let mut app = App::new();
@@ -2909,6 +3551,7 @@ mod tests {
                         Some(BuildContext {
                             grid: &mut grid,
                             world_data: &world_data,
+                            no_build: &Default::default(),
                             food: &mut 9999,
                             cost: 10,
                         }),
@@ -2941,6 +3584,7 @@ mod tests {
                         Some(BuildContext {
                             grid: &mut grid,
                             world_data: &world_data,
+                            no_build: &Default::default(),
                             food: &mut 9999,
                             cost: 10,
                         }),
@@ -2973,6 +3617,7 @@ mod tests {
                         Some(BuildContext {
                             grid: &mut grid,
                             world_data: &world_data,
+                            no_build: &Default::default(),
                             food: &mut 9999,
                             cost: 10,
                         }),
@@ -2988,6 +3633,182 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn build_validity_reports_each_reason() {
+        // 10x10 grass, town 0 owns all but the last column; water at (1,1); center (5,5)
+        let mut grid = WorldGrid::default();
+        grid.width = 10;
+        grid.height = 10;
+        grid.cells = vec![
+            WorldCell {
+                terrain: Biome::Grass,
+                original_terrain: Biome::Grass
+            };
+            100
+        ];
+        grid.cells[11].terrain = Biome::Water;
+        grid.town_owner = vec![0u16; 100];
+        for row in 0..10 {
+            grid.town_owner[row * 10 + 9] = u16::MAX;
+        }
+        let world_data = WorldData {
+            towns: vec![Town {
+                name: "Test".into(),
+                center: grid.grid_to_world(5, 5),
+                faction: 1,
+                kind: crate::constants::TownKind::Player,
+            }],
+        };
+        let mut entity_map = EntityMap::default();
+        entity_map.add_instance(crate::resources::BuildingInstance {
+            kind: BuildingKind::Farm,
+            position: grid.grid_to_world(3, 3),
+            town_idx: 0,
+            slot: 0,
+            faction: 1,
+        });
+        let mut zones = crate::resources::NoBuildZones::default();
+        let zoned = grid.grid_to_world(7, 2);
+        zones.add_no_build_zone(Rect::from_center_size(zoned, Vec2::splat(10.0)));
+
+        let code = |col: usize, row: usize, food: i32| {
+            get_build_validity(
+                BuildingKind::Farm,
+                grid.grid_to_world(col, row),
+                0,
+                &grid,
+                &world_data,
+                &entity_map,
+                &zones,
+                food,
+                10,
+            )
+            .err()
+            .map(CellBlock::code)
+        };
+        assert_eq!(code(2, 2, 100), None);
+        assert_eq!(code(3, 3, 100), Some("occupied"));
+        assert_eq!(code(9, 4, 100), Some("out_of_bounds"));
+        assert_eq!(code(1, 1, 100), Some("water"));
+        assert_eq!(code(2, 2, 5), Some("too_poor"));
+        assert_eq!(code(7, 2, 100), Some("no_build_zone"));
+        assert_eq!(code(5, 5, 100), Some("is_center"));
+        // Off the map entirely
+        let off = get_build_validity(
+            BuildingKind::Farm,
+            Vec2::new(-50.0, 10.0),
+            0,
+            &grid,
+            &world_data,
+            &entity_map,
+            &zones,
+            100,
+            10,
+        );
+        assert_eq!(off, Err(CellBlock::OffMap));
+        assert_eq!(CellBlock::OffMap.code(), "out_of_bounds");
+        // Wilderness kinds check terrain per cell
+        let water = get_build_validity(
+            BuildingKind::Waypoint,
+            grid.grid_to_world(1, 1),
+            0,
+            &grid,
+            &world_data,
+            &entity_map,
+            &zones,
+            100,
+            10,
+        );
+        assert_eq!(water.map_err(CellBlock::code), Err("water"));
+    }
+
+    #[test]
+    fn no_build_zone_blocks_new_placement_only() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(crate::resources::EntityMap::default());
+        app.insert_resource(crate::resources::GpuSlotPool::default());
+        app.add_message::<crate::messages::GpuUpdateMsg>();
+
+        let mut grid = WorldGrid::default();
+        grid.width = 10;
+        grid.height = 10;
+        grid.cell_size = 32.0;
+        grid.cells = vec![
+            WorldCell {
+                terrain: Biome::Grass,
+                original_terrain: Biome::Grass
+            };
+            100
+        ];
+        grid.town_owner = vec![0u16; 100];
+        let world_data = WorldData {
+            towns: vec![Town {
+                name: "Test".into(),
+                center: Vec2::new(160.0, 160.0),
+                faction: 0,
+                kind: crate::constants::TownKind::Player,
+            }],
+        };
+        let existing_pos = grid.grid_to_world(3, 3);
+        let blocked_pos = grid.grid_to_world(4, 3);
+        let outside_pos = grid.grid_to_world(7, 7);
+        app.insert_resource(grid);
+        app.insert_resource(world_data);
+        app.update();
+
+        // Zone added after a building already stands inside it
+        let mut zones = crate::resources::NoBuildZones::default();
+        assert!(!zones.contains(existing_pos));
+        zones.add_no_build_zone(Rect::new(80.0, 80.0, 160.0, 130.0));
+        assert!(zones.contains(existing_pos) && zones.contains(blocked_pos));
+        assert!(!zones.contains(outside_pos));
+
+        app.world_mut()
+            .run_system_once(
+                move |mut slot_alloc: ResMut<crate::resources::GpuSlotPool>,
+                      mut entity_map: ResMut<crate::resources::EntityMap>,
+                      mut commands: Commands,
+                      mut gpu_updates: MessageWriter<crate::messages::GpuUpdateMsg>,
+                      mut grid: ResMut<WorldGrid>,
+                      world_data: Res<WorldData>| {
+                    let mut place = |kind, pos, zones: &crate::resources::NoBuildZones| {
+                        place_building(
+                            &mut slot_alloc,
+                            &mut entity_map,
+                            &mut commands,
+                            &mut gpu_updates,
+                            kind,
+                            pos,
+                            0,
+                            0,
+                            &BuildingOverrides::default(),
+                            Some(BuildContext {
+                                grid: &mut grid,
+                                world_data: &world_data,
+                                no_build: zones,
+                                food: &mut 9999,
+                                cost: 10,
+                            }),
+                            None,
+                        )
+                    };
+                    let empty = crate::resources::NoBuildZones::default();
+                    assert!(place(BuildingKind::Waypoint, existing_pos, &empty).is_ok());
+                    for kind in [BuildingKind::Waypoint, BuildingKind::Road] {
+                        assert_eq!(
+                            place(kind, blocked_pos, &zones),
+                            Err(crate::resources::NoBuildZones::REASON)
+                        );
+                    }
+                    assert!(place(BuildingKind::Waypoint, outside_pos, &zones).is_ok());
+                    assert!(entity_map.has_building_at(3, 3), "existing building stays");
+                    assert!(!entity_map.has_building_at(4, 3));
+                },
+            )
+            .unwrap();
+    }
+
     #[test]
     fn resource_nodes_follow_biomes_spacing_and_occupied_cells() {
         let mut app = App::new();
@@ -3104,4 +3925,316 @@ mod tests {
             )
             .unwrap();
     }
+
+    #[test]
+    fn build_check_reports_blocking_reason() {
+        let mut entity_map = crate::resources::EntityMap::default();
+        entity_map.add_instance(crate::resources::BuildingInstance {
+            kind: BuildingKind::Merchant,
+            position: Vec2::new(96.0, 96.0),
+            town_idx: 0,
+            slot: 3,
+            faction: 1,
+        });
+        let mut check = BuildCheck {
+            town_idx: 0,
+            is_raider: false,
+            food: 100,
+            upgrade_levels: &[],
+            has_slots: true,
+            tech_locked: vec![BuildingKind::Tower],
+            costs: &crate::resources::BuildingCosts::default(),
+        };
+        assert_eq!(check.check(BuildingKind::Farm, &entity_map), Ok(()));
+        assert_eq!(
+            check.check(BuildingKind::Tower, &entity_map),
+            Err(BuildBlock::TechLocked)
+        );
+        assert_eq!(
+            check.check(BuildingKind::Tent, &entity_map),
+            Err(BuildBlock::NotAvailable)
+        );
+        assert_eq!(
+            check.check(BuildingKind::StoneRoad, &entity_map),
+            Err(BuildBlock::TechLocked)
+        );
+        assert_eq!(
+            check.check(BuildingKind::Merchant, &entity_map),
+            Err(BuildBlock::TownLimit)
+        );
+
+        // Raiders have no tech gate on roads
+        check.is_raider = true;
+        assert_eq!(check.check(BuildingKind::StoneRoad, &entity_map), Ok(()));
+        check.is_raider = false;
+
+        check.has_slots = false;
+        assert_eq!(
+            check.check(BuildingKind::Farm, &entity_map),
+            Err(BuildBlock::NoSlots)
+        );
+        // Wilderness kinds don't need a town-grid slot
+        assert_eq!(check.check(BuildingKind::Waypoint, &entity_map), Ok(()));
+
+        check.has_slots = true;
+        check.food = 1;
+        assert_eq!(
+            check.check(BuildingKind::Farm, &entity_map),
+            Err(BuildBlock::NotEnoughFood)
+        );
+
+        // Balance-file overrides come from the costs resource, not the registry
+        let mut costs = crate::resources::BuildingCosts::default();
+        costs.set_overrides(vec![(BuildingKind::Farm, 0)]);
+        check.costs = &costs;
+        assert_eq!(check.check(BuildingKind::Farm, &entity_map), Ok(()));
+    }
+
+    #[test]
+    fn footprint_covers_all_cells_around_center() {
+        let mut grid = WorldGrid::default();
+        grid.width = 10;
+        grid.height = 10;
+        grid.cell_size = 64.0;
+
+        // 1x1 is the single cell containing the position
+        let single = grid.footprint_center(3, 4, (1, 1));
+        assert_eq!(single, grid.grid_to_world(3, 4));
+        let cells: Vec<_> = footprint_cells(single, (1, 1), 64.0).collect();
+        assert_eq!(cells, vec![(3, 4)]);
+
+        // 2x2 anchored at (1, 1) is centered on the shared corner and covers all four cells
+        let center = grid.footprint_center(1, 1, (2, 2));
+        assert_eq!(center, Vec2::new(128.0, 128.0));
+        let mut cells: Vec<_> = footprint_cells(center, (2, 2), 64.0).collect();
+        cells.sort();
+        assert_eq!(cells, vec![(1, 1), (1, 2), (2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn building_occupancy_cleared_on_remove() {
+        let mut entity_map = crate::resources::EntityMap::default();
+        let footprint = crate::constants::building_def(BuildingKind::Casino).footprint;
+        assert_eq!(footprint, (2, 2));
+        // 2x2 anchored at cell (1, 2): centered on the corner shared by (1..=2, 2..=3)
+        let pos = Vec2::new(2.0, 3.0) * TOWN_GRID_SPACING;
+        entity_map.add_instance(crate::resources::BuildingInstance {
+            kind: BuildingKind::Casino,
+            position: pos,
+            town_idx: 0,
+            slot: 7,
+            faction: 0,
+        });
+        let cells: Vec<_> = footprint_cells(pos, footprint, TOWN_GRID_SPACING).collect();
+        assert_eq!(cells.len(), 4);
+        for &(gc, gr) in &cells {
+            assert!(entity_map.has_building_at(gc, gr));
+            assert_eq!(entity_map.get_at_grid(gc, gr).map(|b| b.slot), Some(7));
+        }
+        assert!(!entity_map.has_building_at(3, 2));
+        entity_map.remove_by_slot(7);
+        for &(gc, gr) in &cells {
+            assert!(!entity_map.has_building_at(gc, gr));
+        }
+    }
+
+    #[test]
+    fn multi_cell_placement_checks_every_covered_cell() {
+        // 10x10 grass owned by town 0, center (5,5), a farm at (4,3)
+        let mut grid = WorldGrid::default();
+        grid.width = 10;
+        grid.height = 10;
+        grid.cells = vec![
+            WorldCell {
+                terrain: Biome::Grass,
+                original_terrain: Biome::Grass
+            };
+            100
+        ];
+        grid.town_owner = vec![0u16; 100];
+        let world_data = WorldData {
+            towns: vec![Town {
+                name: "Test".into(),
+                center: grid.grid_to_world(5, 5),
+                faction: 1,
+                kind: crate::constants::TownKind::Player,
+            }],
+        };
+        let mut entity_map = EntityMap::default();
+        entity_map.add_instance(crate::resources::BuildingInstance {
+            kind: BuildingKind::Farm,
+            position: grid.grid_to_world(4, 3),
+            town_idx: 0,
+            slot: 0,
+            faction: 1,
+        });
+        let zones = crate::resources::NoBuildZones::default();
+        let check = |col: usize, row: usize, entity_map: &EntityMap| {
+            get_build_validity(
+                BuildingKind::Casino,
+                grid.grid_to_world(col, row),
+                0,
+                &grid,
+                &world_data,
+                entity_map,
+                &zones,
+                1000,
+                10,
+            )
+        };
+        // Anchor cell free, but the 2x2 reaches the farm / the town center
+        assert_eq!(check(3, 3, &entity_map), Err(CellBlock::Occupied));
+        assert_eq!(check(4, 4, &entity_map), Err(CellBlock::IsCenter));
+        let snapped = check(1, 1, &entity_map).expect("2x2 fits at (1,1)");
+        assert_eq!(snapped, grid.footprint_center(1, 1, (2, 2)));
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(entity_map);
+        app.insert_resource(crate::resources::GpuSlotPool::default());
+        app.add_message::<crate::messages::GpuUpdateMsg>();
+        app.world_mut()
+            .run_system_once(
+                move |mut slot_alloc: ResMut<crate::resources::GpuSlotPool>,
+                      mut entity_map: ResMut<crate::resources::EntityMap>,
+                      mut commands: Commands,
+                      mut gpu_updates: MessageWriter<crate::messages::GpuUpdateMsg>| {
+                    let mut place = |entity_map: &mut EntityMap, kind, pos| {
+                        place_building(
+                            &mut slot_alloc,
+                            entity_map,
+                            &mut commands,
+                            &mut gpu_updates,
+                            kind,
+                            pos,
+                            0,
+                            1,
+                            &BuildingOverrides::default(),
+                            None,
+                            None,
+                        )
+                    };
+                    let casino = place(&mut entity_map, BuildingKind::Casino, snapped)
+                        .expect("casino placed");
+                    // Unvalidated placement still refuses any covered cell
+                    let covered = Vec2::new(2.5, 2.5) * TOWN_GRID_SPACING;
+                    assert_eq!(
+                        place(&mut entity_map, BuildingKind::Farm, covered),
+                        Err(CellBlock::Occupied.message())
+                    );
+                    assert_eq!(entity_map.get_at_grid(2, 2).map(|b| b.slot), Some(casino));
+                    entity_map.remove_by_slot(casino);
+                    assert!(place(&mut entity_map, BuildingKind::Farm, covered).is_ok());
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn terrain_generators_are_seeded_and_share_site_validation() {
+        let blank = || {
+            let mut grid = WorldGrid::default();
+            grid.width = 64;
+            grid.height = 64;
+            grid.cell_size = 64.0;
+            grid.cells = vec![WorldCell::default(); 64 * 64];
+            grid
+        };
+        let terrain = |g: &WorldGrid| g.cells.iter().map(|c| c.terrain).collect::<Vec<_>>();
+        for style in [WorldGenStyle::Classic, WorldGenStyle::Continents] {
+            let (mut a, mut b, mut c) = (blank(), blank(), blank());
+            style.generator().fill(&mut a, 7);
+            style.generator().fill(&mut b, 7);
+            style.generator().fill(&mut c, 8);
+            assert_eq!(terrain(&a), terrain(&b), "{style:?} same seed");
+            assert_ne!(terrain(&a), terrain(&c), "{style:?} different seed");
+        }
+
+        let mut arena = blank();
+        WorldGenStyle::Arena.generator().fill(&mut arena, 7);
+        assert!(arena.cells.iter().all(|c| c.terrain == Biome::Grass));
+        assert!(valid_town_site(&arena, arena.grid_to_world(10, 10)));
+        arena.cells[10 * 64 + 10].terrain = Biome::Water;
+        assert!(!valid_town_site(&arena, arena.grid_to_world(10, 10)));
+        assert!(!valid_town_site(&arena, Vec2::new(-50.0, 100.0)));
+        assert!(!valid_town_site(
+            &arena,
+            Vec2::new(100.0, 64.0 * 64.0 + 10.0)
+        ));
+    }
+
+    #[test]
+    fn starting_loadouts_clamp_and_roll_deterministically() {
+        use crate::components::TraitKind;
+        use crate::constants::{ItemKind, Rarity};
+        let town = |kind| Town {
+            name: "T".into(),
+            center: Vec2::ZERO,
+            faction: 1,
+            kind,
+        };
+        let towns = [town(TownKind::AiRaider), town(TownKind::Player)];
+        let mut config = WorldGenConfig::default();
+        config.npc_counts.insert(Job::Raider, 3);
+        config.faction_loadouts.insert(
+            TownKind::AiRaider,
+            FactionLoadout {
+                units: BTreeMap::from([(Job::Raider, 10)]),
+                level: 5,
+                traits: vec![LoadoutTrait {
+                    kind: TraitKind::Ferocity,
+                    chance: 1.0,
+                    min: 0.5,
+                    max: 1.0,
+                }],
+                equipment: vec![
+                    LoadoutItem {
+                        kind: ItemKind::Weapon,
+                        rarity: Rarity::Rare,
+                    },
+                    LoadoutItem {
+                        kind: ItemKind::Gold,
+                        rarity: Rarity::Common,
+                    },
+                ],
+            },
+        );
+
+        let mut plan = StartingLoadouts::roll(&config, &towns, 7);
+        assert_eq!(
+            plan.0.get(&(0, Job::Raider)).map(Vec::len),
+            Some(3),
+            "clamped to npc_counts"
+        );
+        assert_eq!(plan.0.len(), 1, "towns without a loadout keep defaults");
+        let again = StartingLoadouts::roll(&config, &towns, 7);
+        let magnitudes = |p: &StartingLoadouts| -> Vec<u32> {
+            p.0[&(0, Job::Raider)]
+                .iter()
+                .map(|u| u.personality.trait1.unwrap().magnitude.to_bits())
+                .collect()
+        };
+        assert_eq!(
+            magnitudes(&plan),
+            magnitudes(&again),
+            "same seed, same rolls"
+        );
+
+        let mut ids = crate::resources::NextLootItemId::default();
+        let o = plan.take(0, Job::Raider, &mut ids).unwrap();
+        assert_eq!(o.level, Some(5));
+        assert_eq!(
+            o.personality.unwrap().trait1.unwrap().kind,
+            TraitKind::Ferocity
+        );
+        assert_eq!(o.equipment.weapon.unwrap().rarity, Rarity::Rare);
+        assert!(plan.take(1, Job::Farmer, &mut ids).is_none());
+        assert!(plan.take(0, Job::Raider, &mut ids).is_some());
+        assert!(plan.take(0, Job::Raider, &mut ids).is_some());
+        assert!(
+            plan.take(0, Job::Raider, &mut ids).is_none(),
+            "one wave only"
+        );
+        assert!(plan.0.is_empty());
+    }
 }